@@ -0,0 +1,54 @@
+#!/bin/bash
+# =============================================================================
+# RESERVE CONTENTION BENCHMARK
+# =============================================================================
+# Fires concurrent reservations at one hot SKU and reports latency
+# percentiles, to compare RESERVE_STRATEGY settings of inventory-service.
+#
+# Usage:
+#   RESERVE_STRATEGY=advisory docker compose up -d inventory-service
+#   ./scripts/bench-reserve.sh [sku] [requests] [concurrency]
+#
+# Run once per strategy (for_update, advisory, atomic_update) against the
+# same stock level; db_query_duration_seconds{operation="reserve_<strategy>"}
+# shows the same comparison on dashboards.
+# =============================================================================
+
+INVENTORY_URL="${INVENTORY_URL:-http://localhost:8002}"
+SKU="${1:-SKU-PHONE-001}"
+REQUESTS="${2:-200}"
+CONCURRENCY="${3:-20}"
+
+RESULTS=$(mktemp)
+trap 'rm -f "$RESULTS"' EXIT
+
+echo "=== Reserve Benchmark ==="
+echo "SKU: $SKU, requests: $REQUESTS, concurrency: $CONCURRENCY"
+echo ""
+
+reserve() {
+    curl -s -o /dev/null -w "%{http_code} %{time_total}\n" \
+        -X POST "$INVENTORY_URL/api/v1/inventory/reserve" \
+        -H "Content-Type: application/json" \
+        -d "{\"sku\": \"$SKU\", \"quantity\": 1, \"order_id\": \"BENCH-$1\"}"
+}
+export -f reserve
+export INVENTORY_URL SKU
+
+start_ms=$(($(date +%s%N) / 1000000))
+seq 1 "$REQUESTS" | xargs -P "$CONCURRENCY" -I{} bash -c 'reserve {}' >> "$RESULTS"
+elapsed_ms=$(( $(date +%s%N) / 1000000 - start_ms ))
+
+# Status code breakdown
+echo "Responses:"
+cut -d' ' -f1 "$RESULTS" | sort | uniq -c | sed 's/^/  /'
+
+# Latency percentiles (seconds)
+sort -n -k2 "$RESULTS" | awk -v elapsed_ms="$elapsed_ms" '
+    function pct(p,   i) { i = int(NR * p) + 1; if (i > NR) i = NR; return t[i] }
+    { t[NR] = $2 }
+    END {
+        printf "Latency: p50=%.3fs p95=%.3fs p99=%.3fs max=%.3fs\n",
+            pct(0.50), pct(0.95), pct(0.99), t[NR]
+        printf "Throughput: %.1f req/s\n", NR * 1000 / (elapsed_ms > 0 ? elapsed_ms : 1)
+    }'
@@ -0,0 +1,28 @@
+// =============================================================================
+// BUILD SCRIPT
+// =============================================================================
+// Cargo runs this before compiling the crate. It generates the gRPC server
+// code for proto/inventory/v1/inventory.proto (included by src/grpc/mod.rs).
+//
+// LEARNING NOTES:
+// - protoc comes from the protoc-bin-vendored crate, so nothing has to be
+//   installed. Set PROTOC to use another one (the Alpine image does: the
+//   vendored binary needs glibc).
+// - Only the server side is generated; callers build their own client from
+//   the same .proto
+// =============================================================================
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+    let well_known_types = protoc_bin_vendored::include_path()?;
+
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(
+            &["proto/inventory/v1/inventory.proto"],
+            &[std::path::Path::new("proto"), well_known_types.as_path()],
+        )?;
+    Ok(())
+}
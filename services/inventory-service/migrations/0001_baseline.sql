@@ -0,0 +1,229 @@
+-- =============================================================================
+-- BASELINE SCHEMA
+-- =============================================================================
+-- The schema as the old statement-by-statement runner left it. Everything is
+-- IF NOT EXISTS / OR REPLACE, so databases created by that runner adopt this
+-- migration without changes; new databases get the full schema.
+--
+-- Never edit this file: sqlx checksums applied migrations and refuses to
+-- start when one changed. Schema changes go in a new, higher-numbered file.
+-- =============================================================================
+
+-- -----------------------------------------------------------------------------
+-- INVENTORY
+-- -----------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS inventory (
+    -- Primary key: UUID for global uniqueness
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+
+    -- SKU must be unique (can't have duplicate products)
+    sku VARCHAR(50) UNIQUE NOT NULL,
+
+    -- Product name for display
+    name VARCHAR(255) NOT NULL,
+
+    -- Current stock quantity
+    quantity INTEGER NOT NULL DEFAULT 0,
+
+    -- Reserved stock (for pending orders)
+    reserved INTEGER NOT NULL DEFAULT 0,
+
+    -- Warehouse location code
+    warehouse VARCHAR(50) NOT NULL DEFAULT 'DEFAULT',
+
+    -- Alert threshold
+    low_stock_threshold INTEGER NOT NULL DEFAULT 10,
+
+    -- Timestamps for auditing
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    -- Ensure quantity is never negative
+    CONSTRAINT positive_quantity CHECK (quantity >= 0),
+
+    -- Ensure reserved doesn't exceed quantity
+    CONSTRAINT valid_reserved CHECK (reserved >= 0 AND reserved <= quantity)
+);
+
+-- Fast lookups by SKU, filtering by warehouse
+CREATE INDEX IF NOT EXISTS idx_inventory_sku ON inventory(sku);
+CREATE INDEX IF NOT EXISTS idx_inventory_warehouse ON inventory(warehouse);
+
+-- Per-SKU reservation policy (NULL = no limit)
+ALTER TABLE inventory
+    ADD COLUMN IF NOT EXISTS max_per_order INTEGER
+        CHECK (max_per_order > 0),
+    ADD COLUMN IF NOT EXISTS max_reserved_pct INTEGER
+        CHECK (max_reserved_pct BETWEEN 1 AND 100);
+
+-- Set when low_stock_threshold was chosen by hand, so dynamic threshold
+-- recalculation leaves it alone
+ALTER TABLE inventory
+    ADD COLUMN IF NOT EXISTS threshold_manual BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Catalog details for the frontend: free-form attributes plus optional
+-- image and spec sheet URLs and unit price
+ALTER TABLE inventory
+    ADD COLUMN IF NOT EXISTS attributes JSONB NOT NULL DEFAULT '{}'
+        CHECK (jsonb_typeof(attributes) = 'object'),
+    ADD COLUMN IF NOT EXISTS image_url TEXT,
+    ADD COLUMN IF NOT EXISTS spec_url TEXT,
+    ADD COLUMN IF NOT EXISTS unit_price NUMERIC(12, 2)
+        CHECK (unit_price >= 0);
+
+-- Serves the `attr.<name>=<value>` filters (containment, @>)
+CREATE INDEX IF NOT EXISTS idx_inventory_attributes
+    ON inventory USING GIN (attributes jsonb_path_ops);
+
+-- -----------------------------------------------------------------------------
+-- CATALOG
+-- -----------------------------------------------------------------------------
+-- Typed schemas of catalog attributes (see attributes.rs)
+CREATE TABLE IF NOT EXISTS attribute_schemas (
+    name VARCHAR(64) PRIMARY KEY,
+    attr_type VARCHAR(16) NOT NULL,
+    required BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Low-stock thresholds per (sku, warehouse), overriding the item's own
+-- threshold. No foreign key on purpose: once stock is kept per warehouse,
+-- `sku` alone no longer identifies an inventory row.
+CREATE TABLE IF NOT EXISTS warehouse_thresholds (
+    sku VARCHAR(50) NOT NULL,
+    warehouse VARCHAR(50) NOT NULL,
+    threshold INTEGER NOT NULL CHECK (threshold >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (sku, warehouse)
+);
+
+-- Alternate identifiers (vendor SKU, legacy code, GTIN) per item. The
+-- identifier is the key, so each resolves to exactly one item.
+CREATE TABLE IF NOT EXISTS item_identifiers (
+    identifier VARCHAR(100) PRIMARY KEY,
+    kind VARCHAR(16) NOT NULL,
+    sku VARCHAR(50) NOT NULL
+        REFERENCES inventory(sku) ON UPDATE CASCADE ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_item_identifiers_sku ON item_identifiers(sku);
+
+-- -----------------------------------------------------------------------------
+-- AUDIT TRAIL AND SETTLEMENTS
+-- -----------------------------------------------------------------------------
+-- BIGSERIAL ids give exporters a simple, ordered cursor
+CREATE TABLE IF NOT EXISTS audit_events (
+    id BIGSERIAL PRIMARY KEY,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    action VARCHAR(32) NOT NULL,
+    outcome VARCHAR(16) NOT NULL,
+    sku VARCHAR(50) NOT NULL,
+    quantity INTEGER NOT NULL,
+    reference VARCHAR(255),
+    detail TEXT
+);
+
+-- Sales channel of successful reservations (web/pos/b2b/unknown)
+ALTER TABLE audit_events
+    ADD COLUMN IF NOT EXISTS channel VARCHAR(16);
+
+-- Orders whose reservations were settled by a status callback. One row per
+-- order makes callback delivery idempotent.
+CREATE TABLE IF NOT EXISTS order_settlements (
+    order_id VARCHAR(255) PRIMARY KEY,
+    action VARCHAR(16) NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    settled_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- -----------------------------------------------------------------------------
+-- EVENTS AND WEBHOOKS
+-- -----------------------------------------------------------------------------
+-- Third-party webhook subscriptions (see webhooks.rs)
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    id BIGSERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    event_types TEXT[] NOT NULL DEFAULT '{}',
+    sku_pattern VARCHAR(100),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Outbox of stock.out / stock.back events, filled by the trigger below and
+-- drained by the stock event dispatcher
+CREATE TABLE IF NOT EXISTS stock_events (
+    id BIGSERIAL PRIMARY KEY,
+    event_type VARCHAR(16) NOT NULL,
+    sku VARCHAR(50) NOT NULL,
+    warehouse VARCHAR(50) NOT NULL,
+    available INTEGER NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_stock_events_pending
+    ON stock_events(id) WHERE delivered_at IS NULL;
+
+-- Record when available stock (quantity - reserved) reaches zero or comes
+-- back. A trigger sees every write path: reservations, the fast-path
+-- write-behind, adjustments, settlements and manual SQL.
+CREATE OR REPLACE FUNCTION record_stock_transition() RETURNS trigger AS $$
+BEGIN
+    IF OLD.quantity - OLD.reserved > 0 AND NEW.quantity - NEW.reserved <= 0 THEN
+        INSERT INTO stock_events (event_type, sku, warehouse, available)
+        VALUES ('stock.out', NEW.sku, NEW.warehouse, NEW.quantity - NEW.reserved);
+    ELSIF OLD.quantity - OLD.reserved <= 0 AND NEW.quantity - NEW.reserved > 0 THEN
+        INSERT INTO stock_events (event_type, sku, warehouse, available)
+        VALUES ('stock.back', NEW.sku, NEW.warehouse, NEW.quantity - NEW.reserved);
+    END IF;
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER inventory_stock_transition
+    AFTER UPDATE OF quantity, reserved ON inventory
+    FOR EACH ROW EXECUTE FUNCTION record_stock_transition();
+
+-- -----------------------------------------------------------------------------
+-- SNAPSHOTS
+-- -----------------------------------------------------------------------------
+-- Point-in-time copies of stock (see snapshots.rs)
+CREATE TABLE IF NOT EXISTS inventory_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    label VARCHAR(255),
+    taken_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS inventory_snapshot_items (
+    snapshot_id BIGINT NOT NULL
+        REFERENCES inventory_snapshots(id) ON DELETE CASCADE,
+    sku VARCHAR(50) NOT NULL,
+    quantity INTEGER NOT NULL,
+    reserved INTEGER NOT NULL,
+    unit_price NUMERIC(12, 2),
+    PRIMARY KEY (snapshot_id, sku)
+);
+
+-- -----------------------------------------------------------------------------
+-- STOCK MOVEMENTS
+-- -----------------------------------------------------------------------------
+-- Ledger of stock movements, written alongside each change
+CREATE TABLE IF NOT EXISTS stock_movements (
+    id BIGSERIAL PRIMARY KEY,
+    sku VARCHAR(50) NOT NULL,
+    movement_type VARCHAR(16) NOT NULL,
+    quantity_delta INTEGER NOT NULL,
+    reserved_delta INTEGER NOT NULL,
+    quantity_after INTEGER NOT NULL,
+    reserved_after INTEGER NOT NULL,
+    reference VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_stock_movements_sku_created
+    ON stock_movements (sku, created_at DESC, id DESC);
+
+-- Sortable public ID of each movement (see ids.rs); a reserve movement
+-- shares its ID with the reservation
+ALTER TABLE stock_movements
+    ADD COLUMN IF NOT EXISTS movement_id VARCHAR(36);
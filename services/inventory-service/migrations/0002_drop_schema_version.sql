@@ -0,0 +1,3 @@
+-- Applied versions are tracked by sqlx in _sqlx_migrations now; this table
+-- was written by the old runner.
+DROP TABLE IF EXISTS schema_version;
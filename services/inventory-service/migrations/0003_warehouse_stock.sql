@@ -0,0 +1,13 @@
+-- Stock a SKU holds away from its home warehouse (`inventory.warehouse`).
+-- `inventory.quantity` stays the SKU's total; the home warehouse holds
+-- whatever these rows don't. No foreign key, like warehouse_thresholds.
+CREATE TABLE warehouse_stock (
+    sku VARCHAR(50) NOT NULL,
+    warehouse VARCHAR(50) NOT NULL,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (sku, warehouse)
+);
+
+-- Where each movement happened; transfers write one row per warehouse
+ALTER TABLE stock_movements ADD COLUMN warehouse VARCHAR(50);
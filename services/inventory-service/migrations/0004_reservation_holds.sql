@@ -0,0 +1,17 @@
+-- Soft holds: (order, SKU) holdings that a higher-priority reservation may
+-- bump. Holdings without a row are hard. Rows outlive released holdings;
+-- only a positive holding in the audit trail counts.
+CREATE TABLE reservation_holds (
+    order_id VARCHAR(255) NOT NULL,
+    sku VARCHAR(50) NOT NULL,
+    priority INTEGER NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (order_id, sku)
+);
+
+CREATE INDEX idx_reservation_holds_sku ON reservation_holds (sku, priority);
+
+-- Holdings are summed per (order, SKU) from the audit trail; bumping and
+-- recording holds look one SKU's holdings up on the reservation path
+CREATE INDEX idx_audit_events_holdings ON audit_events (sku, reference)
+    WHERE outcome = 'success';
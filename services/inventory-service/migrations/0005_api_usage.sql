@@ -0,0 +1,14 @@
+-- Per-API-key usage, one row per key and UTC day. Counted in Redis and
+-- rolled up here by the usage-rollup job (usage.rs); key_id is a
+-- fingerprint of the key, never the key itself.
+CREATE TABLE api_usage_daily (
+    day DATE NOT NULL,
+    key_id VARCHAR(32) NOT NULL,
+    requests BIGINT NOT NULL DEFAULT 0,
+    reserved_units BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (day, key_id)
+);
+
+-- Reports filtered to one key
+CREATE INDEX idx_api_usage_daily_key ON api_usage_daily (key_id, day);
@@ -0,0 +1,19 @@
+-- Two-phase stock commitments (prepare / commit / abort, see
+-- commitments.rs). The stock itself moves through the usual audit trail
+-- and movement ledger under order_id; this table tracks where each
+-- transaction stands. Aborts of unknown IDs leave an 'aborted' row with no
+-- lines, so a prepare arriving late can't hold stock.
+CREATE TABLE stock_commitments (
+    transaction_id VARCHAR(64) PRIMARY KEY,
+    order_id VARCHAR(255) NOT NULL,
+    state VARCHAR(16) NOT NULL
+        CHECK (state IN ('prepared', 'committed', 'aborted', 'expired')),
+    lines JSONB NOT NULL DEFAULT '[]',
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- The timeout sweeper looks for prepared commitments past expires_at
+CREATE INDEX idx_stock_commitments_expiry ON stock_commitments (expires_at)
+    WHERE state = 'prepared';
@@ -0,0 +1,45 @@
+-- Column-level history of item metadata (name, warehouse, low-stock
+-- threshold, unit price), listed by GET /api/v1/inventory/:sku/revisions.
+-- Values are JSON so numbers, text and NULL keep their type.
+CREATE TABLE item_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    sku VARCHAR(50) NOT NULL,
+    field VARCHAR(32) NOT NULL,
+    old_value JSONB NOT NULL,
+    new_value JSONB NOT NULL,
+    -- What made the change (catalog, threshold, bulk_update, revert, ...);
+    -- 'sql' when the writer didn't say, e.g. a manual UPDATE
+    source VARCHAR(64) NOT NULL,
+    reason TEXT,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_item_revisions_sku ON item_revisions (sku, id);
+
+-- Like the stock transition trigger, this sees every write path. Writers
+-- label their transaction with set_config('inventory.revision_source' /
+-- 'inventory.revision_reason', ..., true) to say why.
+CREATE FUNCTION record_item_revision() RETURNS trigger AS $$
+DECLARE
+    old_row JSONB := to_jsonb(OLD);
+    new_row JSONB := to_jsonb(NEW);
+    change_source TEXT :=
+        COALESCE(NULLIF(current_setting('inventory.revision_source', true), ''), 'sql');
+    change_reason TEXT := NULLIF(current_setting('inventory.revision_reason', true), '');
+    column_name TEXT;
+BEGIN
+    FOREACH column_name IN ARRAY ARRAY['name', 'warehouse', 'low_stock_threshold', 'unit_price']
+    LOOP
+        IF old_row -> column_name IS DISTINCT FROM new_row -> column_name THEN
+            INSERT INTO item_revisions (sku, field, old_value, new_value, source, reason)
+            VALUES (NEW.sku, column_name, old_row -> column_name, new_row -> column_name,
+                    change_source, change_reason);
+        END IF;
+    END LOOP;
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER inventory_item_revision
+    AFTER UPDATE OF name, warehouse, low_stock_threshold, unit_price ON inventory
+    FOR EACH ROW EXECUTE FUNCTION record_item_revision();
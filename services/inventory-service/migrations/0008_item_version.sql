@@ -0,0 +1,19 @@
+-- Version of each item for optimistic concurrency (see versioning.rs).
+-- Clients send the version they read (If-Match or expected_version) and
+-- get 412 when the item changed in between.
+ALTER TABLE inventory ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
+
+-- Bumped by a trigger so every write path counts, including reservations,
+-- bulk updates and manual SQL. Updates that change nothing keep the version.
+CREATE FUNCTION bump_item_version() RETURNS trigger AS $$
+BEGIN
+    IF NEW IS DISTINCT FROM OLD THEN
+        NEW.version := OLD.version + 1;
+    END IF;
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER inventory_item_version
+    BEFORE UPDATE ON inventory
+    FOR EACH ROW EXECUTE FUNCTION bump_item_version();
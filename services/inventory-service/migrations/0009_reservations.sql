@@ -0,0 +1,15 @@
+-- One reservation per (order, SKU), so a retried reserve returns the
+-- reservation it already made instead of holding the stock twice. What the
+-- order holds still comes from the audit trail; once that holding is gone
+-- (released, confirmed, bumped or expired) the next reserve replaces the
+-- row. Holdings made before this table existed have no row, so their next
+-- reserve adds to them as before.
+CREATE TABLE reservations (
+    order_id VARCHAR(255) NOT NULL,
+    sku VARCHAR(50) NOT NULL,
+    reservation_id VARCHAR(36) NOT NULL,
+    quantity INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ,
+    PRIMARY KEY (order_id, sku)
+);
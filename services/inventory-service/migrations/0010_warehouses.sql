@@ -0,0 +1,29 @@
+-- Registered warehouses. Items, transfers and bulk updates may only name
+-- a warehouse listed here. Still no foreign keys from the stock tables
+-- (see warehouse_stock): the service checks codes when they're written and
+-- refuses to delete a warehouse that holds stock.
+CREATE TABLE warehouses (
+    code VARCHAR(50) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    region VARCHAR(100),
+    -- Units the warehouse can hold; NULL = not tracked
+    capacity INTEGER CHECK (capacity > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- The lab's warehouses, and the default of items created without one
+INSERT INTO warehouses (code, name, region) VALUES
+    ('DEFAULT', 'Default warehouse', NULL),
+    ('JKT-1', 'Jakarta 1', 'Jakarta'),
+    ('JKT-2', 'Jakarta 2', 'Jakarta'),
+    ('SBY-1', 'Surabaya 1', 'Surabaya');
+
+-- Any other code already in use, so existing stock stays valid
+INSERT INTO warehouses (code, name)
+SELECT warehouse, warehouse FROM inventory
+UNION
+SELECT warehouse, warehouse FROM warehouse_stock
+UNION
+SELECT warehouse, warehouse FROM warehouse_thresholds
+ON CONFLICT (code) DO NOTHING;
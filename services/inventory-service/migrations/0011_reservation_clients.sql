@@ -0,0 +1,8 @@
+-- The client (API key fingerprint or IP) each reservation was made for, so
+-- the per-client fairness cap can add up what one client holds of a SKU.
+-- Reservations made before this column existed, or without a client cap
+-- configured, have none.
+ALTER TABLE reservations ADD COLUMN client_id VARCHAR(255);
+
+CREATE INDEX idx_reservations_client_sku ON reservations (client_id, sku)
+    WHERE client_id IS NOT NULL;
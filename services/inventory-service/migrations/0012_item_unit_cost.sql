@@ -0,0 +1,6 @@
+-- What one unit of an item cost, next to its selling price. Stock value
+-- (quantity × unit_cost) is computed when items are read and summed per
+-- warehouse for the inventory_stock_value gauge; items without a cost
+-- count for nothing.
+ALTER TABLE inventory ADD COLUMN unit_cost NUMERIC(12, 2)
+    CHECK (unit_cost >= 0);
@@ -0,0 +1,12 @@
+-- Purchase orders drafted from reorder suggestions, one per delivery
+-- warehouse. The service only creates drafts; `status` leaves room for
+-- whatever buyers do with them afterwards.
+CREATE TABLE purchase_orders (
+    id BIGSERIAL PRIMARY KEY,
+    warehouse VARCHAR(50) NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'draft',
+    lines JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_purchase_orders_created ON purchase_orders (created_at DESC, id DESC);
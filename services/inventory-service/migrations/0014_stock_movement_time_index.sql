@@ -0,0 +1,6 @@
+-- The analytics endpoints sum the movement ledger over a time window for
+-- all SKUs at once; the (sku, created_at) index only helps per SKU. The
+-- included columns let those sums run from the index alone.
+CREATE INDEX idx_stock_movements_created
+    ON stock_movements (created_at)
+    INCLUDE (sku, movement_type, quantity_delta);
@@ -0,0 +1,68 @@
+-- What each (order, SKU) reservation still holds, kept in the same
+-- transaction as inventory.reserved. Until now holdings were summed from
+-- the audit trail on every read, which grows without bound and lets an
+-- audit row written outside the stock change skew them.
+--
+-- status: active while units are held, otherwise how the last of them went
+-- (released, expired or confirmed; a bumped holding is released).
+ALTER TABLE reservations
+    ADD COLUMN held_quantity INTEGER NOT NULL DEFAULT 0 CHECK (held_quantity >= 0),
+    ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'active'
+        CHECK (status IN ('active', 'released', 'expired', 'confirmed')),
+    ADD COLUMN ended_at TIMESTAMPTZ;
+
+-- Holdings made before the reservations table existed get a row of their
+-- own, dated by their latest reserve
+INSERT INTO reservations (order_id, sku, reservation_id, quantity, created_at, expires_at)
+SELECT reference, sku, gen_random_uuid()::text, held, last_reserved_at,
+       last_reserved_at + INTERVAL '24 hours'
+FROM (
+    SELECT reference, sku,
+           SUM(CASE WHEN action = 'reserve' THEN quantity ELSE -quantity END) AS held,
+           MAX(occurred_at) FILTER (WHERE action = 'reserve') AS last_reserved_at
+    FROM audit_events
+    WHERE outcome = 'success'
+      AND action IN ('reserve', 'release', 'confirm', 'bump')
+      AND reference IS NOT NULL
+    GROUP BY reference, sku
+) holdings
+WHERE held > 0 AND last_reserved_at IS NOT NULL
+ON CONFLICT (order_id, sku) DO NOTHING;
+
+-- Carry over what the audit trail says each reservation holds and, for
+-- the finished ones, how and when they ended
+UPDATE reservations r
+SET held_quantity = GREATEST(COALESCE(holding.held, 0), 0)::int,
+    status = CASE
+                 WHEN COALESCE(holding.held, 0) > 0 THEN 'active'
+                 WHEN ended.action = 'confirm' THEN 'confirmed'
+                 WHEN ended.detail = 'expired' THEN 'expired'
+                 ELSE 'released'
+             END,
+    ended_at = CASE WHEN COALESCE(holding.held, 0) > 0 THEN NULL
+                    ELSE COALESCE(ended.occurred_at, r.created_at) END
+FROM reservations r2
+LEFT JOIN LATERAL (
+    SELECT SUM(CASE WHEN a.action = 'reserve' THEN a.quantity ELSE -a.quantity END) AS held
+    FROM audit_events a
+    WHERE a.outcome = 'success'
+      AND a.action IN ('reserve', 'release', 'confirm', 'bump')
+      AND a.sku = r2.sku
+      AND a.reference = r2.order_id
+) holding ON TRUE
+LEFT JOIN LATERAL (
+    SELECT a.action, a.detail, a.occurred_at
+    FROM audit_events a
+    WHERE a.outcome = 'success'
+      AND a.action IN ('release', 'confirm', 'bump')
+      AND a.sku = r2.sku
+      AND a.reference = r2.order_id
+      AND a.occurred_at >= r2.created_at
+    ORDER BY a.id DESC
+    LIMIT 1
+) ended ON TRUE
+WHERE r.order_id = r2.order_id AND r.sku = r2.sku;
+
+-- The sweeper and the bump/fairness lookups only care about active rows
+CREATE INDEX idx_reservations_active ON reservations (sku, created_at)
+    WHERE held_quantity > 0;
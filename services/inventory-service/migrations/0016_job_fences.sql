@@ -0,0 +1,9 @@
+-- The newest fencing token each leased job (distributed_lock.rs) has
+-- written with. Sweeps record their lease's token here inside every write
+-- transaction; a transaction carrying an older token than the one stored
+-- belongs to a holder whose lease has moved on and is rolled back.
+CREATE TABLE job_fences (
+    name VARCHAR(100) PRIMARY KEY,
+    token BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
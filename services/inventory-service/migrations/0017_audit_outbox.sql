@@ -0,0 +1,39 @@
+-- Outbox of audit events for the jobs that follow the audit trail (syslog
+-- and HTTP shippers, Kafka publisher, webhook dispatcher), one queue per
+-- follower. Until now each kept an `id > last seen` cursor, which passes
+-- over an event whose transaction took its ID before a newer one but
+-- committed after it.
+--
+-- A follower registers in audit_followers when it starts; from then on
+-- the trigger below queues every new event for it, in the transaction
+-- that writes the event. Shipped rows get shipped_at and are pruned later.
+CREATE TABLE audit_followers (
+    name VARCHAR(50) PRIMARY KEY,
+    registered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Last time a replica shipped for it; followers gone for a week are dropped
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE audit_outbox (
+    follower VARCHAR(50) NOT NULL REFERENCES audit_followers(name) ON DELETE CASCADE,
+    event_id BIGINT NOT NULL REFERENCES audit_events(id) ON DELETE CASCADE,
+    shipped_at TIMESTAMPTZ,
+    PRIMARY KEY (follower, event_id)
+);
+
+CREATE INDEX idx_audit_outbox_pending ON audit_outbox (follower, event_id)
+    WHERE shipped_at IS NULL;
+CREATE INDEX idx_audit_outbox_shipped ON audit_outbox (follower, shipped_at)
+    WHERE shipped_at IS NOT NULL;
+
+CREATE FUNCTION queue_audit_event() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO audit_outbox (follower, event_id)
+    SELECT name, NEW.id FROM audit_followers;
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_events_outbox
+    AFTER INSERT ON audit_events
+    FOR EACH ROW EXECUTE FUNCTION queue_audit_event();
@@ -0,0 +1,138 @@
+-- Stock per (SKU, warehouse). Until now `inventory` kept each SKU's total
+-- and home warehouse, units moved away were `warehouse_stock` rows and the
+-- home warehouse held whatever those didn't. Reservations and adjustments
+-- only looked at the total, so the home warehouse could promise units that
+-- had been transferred elsewhere.
+--
+-- - products: one row per SKU, what `inventory` was. quantity and reserved
+--   stay, as the totals of the SKU's stock rows: the stock_totals trigger
+--   keeps them and writing them directly is refused.
+-- - stock: units on hand and reserved at each warehouse. Every product has
+--   a row at its home warehouse (products.warehouse), made by the
+--   products_home_stock trigger; new stock and reservations go there.
+-- - reservations.warehouse: where a holding's units are reserved, so they
+--   are given back there after the home warehouse changes
+--
+-- Lock the product row before writing its stock rows (db.rs does, see
+-- change_stock): the totals trigger locks it after the stock row, so two
+-- writers taking them in opposite orders can deadlock.
+ALTER TABLE inventory RENAME TO products;
+
+CREATE TABLE stock (
+    sku VARCHAR(50) NOT NULL
+        REFERENCES products(sku) ON UPDATE CASCADE ON DELETE CASCADE,
+    warehouse VARCHAR(50) NOT NULL,
+    quantity INTEGER NOT NULL DEFAULT 0 CHECK (quantity >= 0),
+    reserved INTEGER NOT NULL DEFAULT 0 CHECK (reserved >= 0 AND reserved <= quantity),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (sku, warehouse)
+);
+
+CREATE INDEX idx_stock_warehouse ON stock (warehouse);
+
+-- Every reserved unit was taken from home. Where transfers left home with
+-- fewer units than it has reserved, the difference comes back from the
+-- other warehouses, in warehouse order.
+WITH away AS (
+    SELECT w.sku, w.warehouse, w.quantity,
+           COALESCE(SUM(w.quantity) OVER (PARTITION BY w.sku ORDER BY w.warehouse
+                    ROWS BETWEEN UNBOUNDED PRECEDING AND 1 PRECEDING), 0) AS before,
+           SUM(w.quantity) OVER (PARTITION BY w.sku) AS total
+    FROM warehouse_stock w
+    JOIN products p ON p.sku = w.sku AND w.warehouse <> p.warehouse
+), home AS (
+    SELECT p.sku, p.warehouse, p.reserved,
+           p.quantity - COALESCE((SELECT MAX(a.total) FROM away a WHERE a.sku = p.sku), 0) AS left_at_home
+    FROM products p
+), home_rows AS (
+    INSERT INTO stock (sku, warehouse, quantity, reserved)
+    SELECT sku, warehouse, GREATEST(left_at_home, reserved), reserved
+    FROM home
+)
+INSERT INTO stock (sku, warehouse, quantity)
+SELECT sku, warehouse, quantity
+FROM (
+    SELECT a.sku, a.warehouse,
+           a.quantity - LEAST(a.quantity, GREATEST(h.reserved - h.left_at_home - a.before, 0)) AS quantity
+    FROM away a
+    JOIN home h ON h.sku = a.sku
+) moved
+WHERE quantity > 0;
+
+DROP TABLE warehouse_stock;
+
+ALTER TABLE reservations ADD COLUMN warehouse VARCHAR(50);
+
+UPDATE reservations r
+SET warehouse = p.warehouse
+FROM products p
+WHERE p.sku = r.sku;
+
+-- Keep products.quantity and products.reserved at the totals of the stock
+-- rows. Stock rows written by other triggers (a new product's home row, a
+-- cascade from products) already agree with the totals and are skipped.
+CREATE FUNCTION apply_stock_totals() RETURNS trigger AS $$
+DECLARE
+    quantity_delta INTEGER := 0;
+    reserved_delta INTEGER := 0;
+BEGIN
+    IF pg_trigger_depth() > 1 THEN
+        RETURN NULL;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        quantity_delta := NEW.quantity;
+        reserved_delta := NEW.reserved;
+    END IF;
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        quantity_delta := quantity_delta - OLD.quantity;
+        reserved_delta := reserved_delta - OLD.reserved;
+    END IF;
+    IF quantity_delta <> 0 OR reserved_delta <> 0 THEN
+        UPDATE products
+        SET quantity = quantity + quantity_delta,
+            reserved = reserved + reserved_delta,
+            updated_at = NOW()
+        WHERE sku = COALESCE(NEW.sku, OLD.sku);
+    END IF;
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER stock_totals
+    AFTER INSERT OR UPDATE OR DELETE ON stock
+    FOR EACH ROW EXECUTE FUNCTION apply_stock_totals();
+
+-- The totals only change through stock rows
+CREATE FUNCTION guard_stock_totals() RETURNS trigger AS $$
+BEGIN
+    IF pg_trigger_depth() < 2
+       AND (NEW.quantity, NEW.reserved) IS DISTINCT FROM (OLD.quantity, OLD.reserved) THEN
+        RAISE EXCEPTION 'quantity and reserved of % are totals of its stock rows; change those', NEW.sku;
+    END IF;
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER products_stock_totals
+    BEFORE UPDATE OF quantity, reserved ON products
+    FOR EACH ROW EXECUTE FUNCTION guard_stock_totals();
+
+-- A new product's stock starts at its home warehouse; a new home warehouse
+-- gets an empty row, while the old one keeps what it holds
+CREATE FUNCTION create_home_stock() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO stock (sku, warehouse, quantity, reserved)
+        VALUES (NEW.sku, NEW.warehouse, NEW.quantity, NEW.reserved);
+    ELSE
+        INSERT INTO stock (sku, warehouse)
+        VALUES (NEW.sku, NEW.warehouse)
+        ON CONFLICT (sku, warehouse) DO NOTHING;
+    END IF;
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER products_home_stock
+    AFTER INSERT OR UPDATE OF warehouse ON products
+    FOR EACH ROW EXECUTE FUNCTION create_home_stock();
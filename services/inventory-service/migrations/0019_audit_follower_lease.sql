@@ -0,0 +1,13 @@
+-- A follower's claim becomes a lease instead of a row lock held by an open
+-- transaction. The claim used to keep a pooled connection (and its
+-- transaction) for as long as the batch took to send, so a slow or
+-- unreachable sink could tie up the pool the request handlers need.
+--
+-- - claim_token: bumped by every claim; marking events shipped only
+--   counts while it is still the claimer's, so a replica whose lease ran
+--   out can't mark a batch the next one is sending
+-- - claimed_until: other replicas skip the follower until then; NULL when
+--   nobody ships for it
+ALTER TABLE audit_followers
+    ADD COLUMN claim_token BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN claimed_until TIMESTAMPTZ;
@@ -0,0 +1,400 @@
+// =============================================================================
+// ALERT NOTIFIER MODULE
+// =============================================================================
+// Pushes low-stock alerts to a webhook (Alertmanager or Slack), so on-call
+// hears about them without polling GET /api/v1/inventory/alerts.
+//
+// HOW:
+// - After every low-stock evaluation (low_stock.rs, every
+//   LOW_STOCK_EVAL_INTERVAL_SECS) the notifier compares the low
+//   (sku, warehouse) pairs with the ones it has already notified:
+//   - newly low: sent as firing
+//   - still low: sent again once LOW_STOCK_ALERT_REPEAT_SECS have passed
+//   - no longer low (restocked, threshold lowered, item deleted): sent as
+//     resolved, once
+// - Everything due is sent in one POST per evaluation
+//
+// FORMATS (LOW_STOCK_ALERT_FORMAT):
+// - alertmanager (default): the body of Alertmanager's POST /api/v2/alerts,
+//     [{"labels": {"alertname": "InventoryLowStock", "sku": "SKU-1",
+//                  "warehouse": "JKT-1", "severity": "warning"},
+//       "annotations": {"summary": "...", "available": "3", "threshold": "10"},
+//       "startsAt": "...", "endsAt": "..."}]
+//   severity is critical when nothing is available. Firing alerts end two
+//   repeat intervals ahead, so Alertmanager resolves them by itself if this
+//   service stops sending; resolved ones end now.
+// - slack: {"text": "..."} for an incoming webhook, one line per alert
+//
+// CONFIGURATION:
+//   LOW_STOCK_ALERT_WEBHOOK_URL=http://alertmanager:9093/api/v2/alerts
+//   LOW_STOCK_ALERT_FORMAT=alertmanager      or slack
+//   LOW_STOCK_ALERT_REPEAT_SECS=3600         resend interval of firing alerts
+//
+// LEARNING NOTES:
+// - Alerts are only as fresh as the evaluation: a SKU that runs low is
+//   notified within one evaluation interval, not at the reservation
+// - A failed POST (after the client's retries) is logged and counted; the
+//   alerts stay due and go out with the next evaluation
+// - What was notified is kept in memory: after a restart the low SKUs fire
+//   again. Alertmanager merges them with the alerts it has (same labels);
+//   a Slack channel sees them once more.
+// - low_stock_notifications_total counts alerts sent, by status and outcome
+// =============================================================================
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use crate::http_client::HttpClient;
+use crate::metrics;
+use crate::models::LowStockAlert;
+
+/// alertname label of the Alertmanager alerts
+const ALERT_NAME: &str = "InventoryLowStock";
+
+// -----------------------------------------------------------------------------
+// CONFIGURATION
+// -----------------------------------------------------------------------------
+/// Body format of the notifications
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertFormat {
+    Alertmanager,
+    Slack,
+}
+
+impl AlertFormat {
+    /// Parse LOW_STOCK_ALERT_FORMAT
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "alertmanager" => Ok(Self::Alertmanager),
+            "slack" => Ok(Self::Slack),
+            other => Err(format!(
+                "Unsupported alert format '{}', expected alertmanager or slack",
+                other
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Alertmanager => "alertmanager",
+            Self::Slack => "slack",
+        }
+    }
+}
+
+/// Where and how low-stock alerts are sent
+#[derive(Debug, Clone)]
+pub struct AlertTarget {
+    pub url: String,
+    pub format: AlertFormat,
+    /// Time before a still-firing alert is sent again
+    pub repeat: Duration,
+}
+
+// -----------------------------------------------------------------------------
+// NOTIFIER
+// -----------------------------------------------------------------------------
+/// Whether an alert started or ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Firing,
+    Resolved,
+}
+
+impl Status {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Firing => "firing",
+            Self::Resolved => "resolved",
+        }
+    }
+}
+
+/// An alert that has been notified as firing
+#[derive(Debug, Clone)]
+struct Notified {
+    alert: LowStockAlert,
+    starts_at: DateTime<Utc>,
+    last_sent: DateTime<Utc>,
+}
+
+/// One alert due to be sent
+#[derive(Debug, Clone)]
+struct Notification {
+    alert: LowStockAlert,
+    status: Status,
+    starts_at: DateTime<Utc>,
+}
+
+/// Alerts notified so far, by (sku, warehouse)
+type NotifiedAlerts = HashMap<(String, String), Notified>;
+
+/// Sends low-stock alerts to the configured webhook; cheap to clone
+#[derive(Clone)]
+pub struct AlertNotifier {
+    http: HttpClient,
+    target: AlertTarget,
+    // Held across the POST so overlapping evaluations don't send twice
+    notified: Arc<Mutex<NotifiedAlerts>>,
+}
+
+impl AlertNotifier {
+    pub fn new(http: HttpClient, target: AlertTarget) -> Self {
+        Self {
+            http,
+            target,
+            notified: Arc::default(),
+        }
+    }
+
+    /// Send what changed since the last notification, given the alerts of
+    /// an evaluation made at `now`
+    pub async fn notify(&self, alerts: &[LowStockAlert], now: DateTime<Utc>) {
+        let mut notified = self.notified.lock().await;
+        let due = due(&notified, alerts, now, self.target.repeat);
+        if due.is_empty() {
+            return;
+        }
+
+        let result = self.send(&due, now).await;
+        for notification in &due {
+            metrics::record_low_stock_notification(notification.status.as_str(), result.is_ok());
+        }
+        match result {
+            Ok(()) => {
+                record_sent(&mut notified, &due, now);
+                tracing::info!(alerts = due.len(), "Low stock alerts notified");
+            }
+            Err(e) => {
+                tracing::warn!(alerts = due.len(), error = %e, "Failed to notify low stock alerts");
+            }
+        }
+    }
+
+    /// POST the notifications in the target's format
+    async fn send(&self, due: &[Notification], now: DateTime<Utc>) -> anyhow::Result<()> {
+        let request = self.http.client().post(&self.target.url);
+        let response = match self.target.format {
+            // Posting the same alerts again is harmless to Alertmanager
+            AlertFormat::Alertmanager => {
+                let body = alertmanager_body(due, now, self.target.repeat);
+                self.http.send("low-stock-alerts", request.json(&body)).await?
+            }
+            // A retried Slack message would show up twice
+            AlertFormat::Slack => {
+                let body = SlackMessage { text: slack_text(due) };
+                self.http.send_once("low-stock-alerts", request.json(&body)).await?
+            }
+        };
+        response.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Alerts to send: new ones, resolved ones and firing ones due for a repeat
+fn due(
+    notified: &NotifiedAlerts,
+    alerts: &[LowStockAlert],
+    now: DateTime<Utc>,
+    repeat: Duration,
+) -> Vec<Notification> {
+    let repeat = chrono::Duration::from_std(repeat).unwrap_or(chrono::Duration::MAX);
+    let mut due = Vec::new();
+
+    for alert in alerts {
+        match notified.get(&key(alert)) {
+            Some(previous) if now - previous.last_sent < repeat => {}
+            previous => due.push(Notification {
+                alert: alert.clone(),
+                status: Status::Firing,
+                starts_at: previous.map_or(now, |p| p.starts_at),
+            }),
+        }
+    }
+
+    let current: HashSet<_> = alerts.iter().map(key).collect();
+    let mut resolved: Vec<&Notified> = notified
+        .iter()
+        .filter(|(key, _)| !current.contains(*key))
+        .map(|(_, previous)| previous)
+        .collect();
+    resolved.sort_by_key(|previous| key(&previous.alert));
+    due.extend(resolved.into_iter().map(|previous| Notification {
+        alert: previous.alert.clone(),
+        status: Status::Resolved,
+        starts_at: previous.starts_at,
+    }));
+
+    due
+}
+
+/// Remember what was sent: firing alerts with the time, resolved ones gone
+fn record_sent(notified: &mut NotifiedAlerts, sent: &[Notification], now: DateTime<Utc>) {
+    for notification in sent {
+        match notification.status {
+            Status::Firing => {
+                notified.insert(
+                    key(&notification.alert),
+                    Notified {
+                        alert: notification.alert.clone(),
+                        starts_at: notification.starts_at,
+                        last_sent: now,
+                    },
+                );
+            }
+            Status::Resolved => {
+                notified.remove(&key(&notification.alert));
+            }
+        }
+    }
+}
+
+fn key(alert: &LowStockAlert) -> (String, String) {
+    (alert.sku.clone(), alert.warehouse.clone())
+}
+
+// -----------------------------------------------------------------------------
+// PAYLOADS
+// -----------------------------------------------------------------------------
+/// An alert as Alertmanager's API takes it
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AlertmanagerAlert {
+    labels: BTreeMap<&'static str, String>,
+    annotations: BTreeMap<&'static str, String>,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+}
+
+fn alertmanager_body(due: &[Notification], now: DateTime<Utc>, repeat: Duration) -> Vec<AlertmanagerAlert> {
+    let expires = now + chrono::Duration::from_std(repeat * 2).unwrap_or(chrono::Duration::days(1));
+
+    due.iter()
+        .map(|n| {
+            let severity = if n.alert.available <= 0 { "critical" } else { "warning" };
+            AlertmanagerAlert {
+                labels: BTreeMap::from([
+                    ("alertname", ALERT_NAME.to_string()),
+                    ("sku", n.alert.sku.clone()),
+                    ("warehouse", n.alert.warehouse.clone()),
+                    ("severity", severity.to_string()),
+                ]),
+                annotations: BTreeMap::from([
+                    ("summary", summary(&n.alert)),
+                    ("available", n.alert.available.to_string()),
+                    ("threshold", n.alert.threshold.to_string()),
+                ]),
+                starts_at: n.starts_at,
+                ends_at: match n.status {
+                    Status::Firing => expires,
+                    Status::Resolved => now,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Body of a Slack incoming webhook
+#[derive(Debug, Serialize)]
+struct SlackMessage {
+    text: String,
+}
+
+fn slack_text(due: &[Notification]) -> String {
+    due.iter()
+        .map(|n| match n.status {
+            Status::Firing => format!(":warning: {}", summary(&n.alert)),
+            Status::Resolved => format!(
+                ":white_check_mark: Resolved: {} ({}) in {} is back above its threshold",
+                n.alert.sku, n.alert.name, n.alert.warehouse
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn summary(alert: &LowStockAlert) -> String {
+    format!(
+        "Low stock: {} ({}) in {} has {} available (threshold {})",
+        alert.sku, alert.name, alert.warehouse, alert.available, alert.threshold
+    )
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn alert(sku: &str, available: i32) -> LowStockAlert {
+        LowStockAlert {
+            sku: sku.to_string(),
+            name: format!("{} name", sku),
+            available,
+            threshold: 10,
+            warehouse: "JKT-1".to_string(),
+        }
+    }
+
+    fn statuses(due: &[Notification]) -> Vec<(&str, Status)> {
+        due.iter().map(|n| (n.alert.sku.as_str(), n.status)).collect()
+    }
+
+    #[test]
+    fn test_alerts_are_deduplicated_and_resolved() {
+        let start = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let mut notified = NotifiedAlerts::new();
+
+        let first = due(&notified, &[alert("A", 3), alert("B", 0)], start, HOUR);
+        assert_eq!(statuses(&first), vec![("A", Status::Firing), ("B", Status::Firing)]);
+        record_sent(&mut notified, &first, start);
+
+        // Still low a minute later: nothing new to say about A and B
+        let later = start + chrono::Duration::minutes(1);
+        let second = due(&notified, &[alert("A", 2), alert("B", 0)], later, HOUR);
+        assert!(second.is_empty());
+
+        // B restocked, C ran low
+        let third = due(&notified, &[alert("A", 2), alert("C", 1)], later, HOUR);
+        assert_eq!(statuses(&third), vec![("C", Status::Firing), ("B", Status::Resolved)]);
+        record_sent(&mut notified, &third, later);
+        assert!(!notified.contains_key(&key(&alert("B", 0))));
+
+        // After the repeat interval A and C fire again, keeping their start
+        let repeat = start + chrono::Duration::hours(1);
+        let fourth = due(&notified, &[alert("A", 2), alert("C", 1)], repeat, HOUR);
+        assert_eq!(statuses(&fourth), vec![("A", Status::Firing)]);
+        assert_eq!(fourth[0].starts_at, start);
+    }
+
+    #[test]
+    fn test_payloads() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let due = vec![
+            Notification { alert: alert("A", 0), status: Status::Firing, starts_at: now },
+            Notification { alert: alert("B", 4), status: Status::Resolved, starts_at: now },
+        ];
+
+        let body = serde_json::to_value(alertmanager_body(&due, now, HOUR)).unwrap();
+        assert_eq!(body[0]["labels"]["alertname"], ALERT_NAME);
+        assert_eq!(body[0]["labels"]["severity"], "critical");
+        assert_eq!(body[0]["endsAt"], "2024-05-01T14:00:00Z");
+        assert_eq!(body[1]["labels"]["severity"], "warning");
+        assert_eq!(body[1]["endsAt"], "2024-05-01T12:00:00Z");
+
+        let text = slack_text(&due);
+        assert!(text.starts_with(":warning: Low stock: A (A name) in JKT-1 has 0 available"));
+        assert!(text.contains("\n:white_check_mark: Resolved: B"));
+
+        assert_eq!(AlertFormat::parse("Slack"), Ok(AlertFormat::Slack));
+        assert!(AlertFormat::parse("teams").is_err());
+    }
+}
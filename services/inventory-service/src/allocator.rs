@@ -0,0 +1,108 @@
+// =============================================================================
+// ALLOCATOR MODULE
+// =============================================================================
+// This module selects the global memory allocator and reports its statistics.
+//
+// WHY THIS EXISTS:
+// Container RSS only tells you how much memory the OS handed to the process.
+// It doesn't say how much of it is live data versus allocator caches and
+// fragmentation. jemalloc can answer that, which makes memory experiments
+// in the lab (leaks, bursts, fragmentation) much easier to reason about.
+//
+// LEARNING NOTES:
+// - #[global_allocator] replaces the allocator for the whole binary
+// - jemalloc caches its statistics; advancing the "epoch" refreshes them
+// - Without the `jemalloc` feature the system allocator is used and no
+//   statistics are available
+// - The allocator_bytes gauges are sampled on every /metrics scrape, so
+//   they're as fresh as the scrape interval
+// =============================================================================
+
+use serde::Serialize;
+
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+/// Name of the allocator compiled into this binary
+pub const ALLOCATOR_NAME: &str = if cfg!(feature = "jemalloc") {
+    "jemalloc"
+} else {
+    "system"
+};
+
+// -----------------------------------------------------------------------------
+// STATISTICS
+// -----------------------------------------------------------------------------
+/// Heap statistics in bytes (see jemalloc's `stats.*` mallctl docs)
+#[derive(Debug, Clone, Serialize)]
+pub struct AllocatorStats {
+    /// Bytes allocated by the application
+    pub allocated: u64,
+    /// Bytes in active pages (allocated + page-level fragmentation)
+    pub active: u64,
+    /// Bytes dedicated to allocator metadata
+    pub metadata: u64,
+    /// Bytes in physically resident data pages
+    pub resident: u64,
+    /// Bytes in active extents mapped by the allocator
+    pub mapped: u64,
+    /// Bytes in virtual memory retained for future reuse
+    pub retained: u64,
+}
+
+impl AllocatorStats {
+    /// (kind, bytes) pairs, used as gauge labels
+    pub fn as_pairs(&self) -> [(&'static str, u64); 6] {
+        [
+            ("allocated", self.allocated),
+            ("active", self.active),
+            ("metadata", self.metadata),
+            ("resident", self.resident),
+            ("mapped", self.mapped),
+            ("retained", self.retained),
+        ]
+    }
+}
+
+/// Read current heap statistics.
+///
+/// Returns `Ok(None)` when the binary was built without jemalloc.
+#[cfg(feature = "jemalloc")]
+pub fn stats() -> anyhow::Result<Option<AllocatorStats>> {
+    use tikv_jemalloc_ctl::{epoch, stats};
+
+    // Refresh jemalloc's cached counters before reading them
+    epoch::advance().map_err(|e| anyhow::anyhow!("jemalloc epoch: {}", e))?;
+
+    let read = |r: tikv_jemalloc_ctl::Result<usize>| {
+        r.map(|v| v as u64)
+            .map_err(|e| anyhow::anyhow!("jemalloc stats: {}", e))
+    };
+
+    Ok(Some(AllocatorStats {
+        allocated: read(stats::allocated::read())?,
+        active: read(stats::active::read())?,
+        metadata: read(stats::metadata::read())?,
+        resident: read(stats::resident::read())?,
+        mapped: read(stats::mapped::read())?,
+        retained: read(stats::retained::read())?,
+    }))
+}
+
+#[cfg(not(feature = "jemalloc"))]
+pub fn stats() -> anyhow::Result<Option<AllocatorStats>> {
+    Ok(None)
+}
+
+/// Read current heap statistics and publish them as the `allocator_bytes`
+/// gauges
+pub fn sample() -> anyhow::Result<Option<AllocatorStats>> {
+    let stats = stats()?;
+    if let Some(stats) = &stats {
+        for (kind, bytes) in stats.as_pairs() {
+            crate::metrics::set_allocator_bytes(kind, bytes);
+        }
+    }
+    Ok(stats)
+}
@@ -0,0 +1,241 @@
+// =============================================================================
+// ATTRIBUTES MODULE
+// =============================================================================
+// Typed schemas for the catalog attributes of items (see CatalogDetailsRequest),
+// and the `attr.<name>=<value>` filters of the list endpoint.
+//
+// SCHEMAS (one per attribute name, shared by all items):
+// - GET    /api/v1/admin/attribute-schemas
+// - PUT    /api/v1/admin/attribute-schemas/:name   {type, required}
+// - DELETE /api/v1/admin/attribute-schemas/:name
+// `type` is "string", "number" or "boolean". Attributes without a schema
+// stay free-form.
+//
+// FILTERING:
+//   GET /api/v1/inventory?attr.color=red&attr.touch=true
+// Values are converted to the schema type ("14" matches the number 14) and
+// matched with JSONB containment (`attributes @> '{"color":"red"}'`), which
+// the GIN index on `inventory.attributes` serves.
+//
+// LEARNING NOTES:
+// - Schemas are checked when an item's catalog details are written. Adding
+//   a required attribute doesn't touch existing items; each one has to
+//   comply on its next catalog update.
+// - Containment only does equality; ranges ("screen_inches > 13") would need
+//   jsonpath queries and aren't supported
+// =============================================================================
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::models::{AttributeSchema, MAX_ATTRIBUTE_NAME_LEN};
+
+/// Query parameter prefix of attribute filters
+pub const FILTER_PREFIX: &str = "attr.";
+
+/// Most attribute filters in one request
+const MAX_FILTERS: usize = 10;
+
+/// Type of an attribute value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AttributeType {
+    String,
+    Number,
+    Boolean,
+}
+
+impl AttributeType {
+    /// Name stored in the database
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+        }
+    }
+
+    /// Parse a stored type name
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "string" => Some(Self::String),
+            "number" => Some(Self::Number),
+            "boolean" => Some(Self::Boolean),
+            _ => None,
+        }
+    }
+
+    /// Whether a JSON value has this type
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Number => value.is_number(),
+            Self::Boolean => value.is_boolean(),
+        }
+    }
+
+    /// Convert a query string value to a JSON value of this type
+    fn parse_value(self, value: &str) -> Option<Value> {
+        match self {
+            Self::String => Some(Value::String(value.to_string())),
+            Self::Number => value
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number),
+            Self::Boolean => value.parse::<bool>().ok().map(Value::Bool),
+        }
+    }
+}
+
+/// Check an attribute name before defining a schema for it
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() || name.len() > MAX_ATTRIBUTE_NAME_LEN {
+        return Err(format!(
+            "Attribute names must be 1-{} characters, got '{}'",
+            MAX_ATTRIBUTE_NAME_LEN, name
+        ));
+    }
+    Ok(())
+}
+
+/// Schema type of an attribute, if it has one
+fn schema_type(schemas: &[AttributeSchema], name: &str) -> Option<AttributeType> {
+    schemas
+        .iter()
+        .find(|schema| schema.name == name)
+        .and_then(|schema| AttributeType::parse(&schema.attr_type))
+}
+
+// -----------------------------------------------------------------------------
+// VALIDATION
+// -----------------------------------------------------------------------------
+/// Check an item's attributes against the schemas
+///
+/// # Returns
+/// - `Err(reason)` for a missing required attribute or a value of the
+///   wrong type (null counts as missing)
+pub fn validate(attributes: &Value, schemas: &[AttributeSchema]) -> Result<(), String> {
+    let empty = serde_json::Map::new();
+    let attributes = attributes.as_object().unwrap_or(&empty);
+
+    for schema in schemas {
+        let value = attributes.get(&schema.name).filter(|v| !v.is_null());
+        match (value, AttributeType::parse(&schema.attr_type)) {
+            (None, _) if schema.required => {
+                return Err(format!("Attribute '{}' is required", schema.name));
+            }
+            (Some(value), Some(expected)) if !expected.matches(value) => {
+                return Err(format!(
+                    "Attribute '{}' must be a {}, got {}",
+                    schema.name,
+                    expected.as_str(),
+                    value
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// FILTERING
+// -----------------------------------------------------------------------------
+/// Build the containment filter from `attr.<name>=<value>` query parameters
+///
+/// Other parameters are ignored.
+///
+/// # Returns
+/// - `Ok(None)` without attribute filters
+/// - `Ok(Some(object))` to match with `attributes @> object`
+/// - `Err(reason)` for an empty name, too many filters, or a value that
+///   doesn't fit the attribute's type
+pub fn parse_filters<'a>(
+    params: impl IntoIterator<Item = (&'a String, &'a String)>,
+    schemas: &[AttributeSchema],
+) -> Result<Option<Value>, String> {
+    let mut filter = serde_json::Map::new();
+
+    for (key, raw) in params {
+        let Some(name) = key.strip_prefix(FILTER_PREFIX) else {
+            continue;
+        };
+        validate_name(name)?;
+
+        let expected = schema_type(schemas, name).unwrap_or(AttributeType::String);
+        let value = expected.parse_value(raw).ok_or_else(|| {
+            format!("Filter {} must be a {}, got '{}'", key, expected.as_str(), raw)
+        })?;
+        filter.insert(name.to_string(), value);
+    }
+
+    if filter.len() > MAX_FILTERS {
+        return Err(format!("At most {} attribute filters are allowed", MAX_FILTERS));
+    }
+
+    Ok((!filter.is_empty()).then_some(Value::Object(filter)))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn schema(name: &str, attr_type: AttributeType, required: bool) -> AttributeSchema {
+        AttributeSchema {
+            name: name.to_string(),
+            attr_type: attr_type.as_str().to_string(),
+            required,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_validate_against_schemas() {
+        let schemas = [
+            schema("color", AttributeType::String, true),
+            schema("screen_inches", AttributeType::Number, false),
+        ];
+
+        assert!(validate(&json!({ "color": "red", "screen_inches": 14 }), &schemas).is_ok());
+        assert!(validate(&json!({ "color": "red", "extra": [1, 2] }), &schemas).is_ok());
+        assert!(validate(&json!({ "screen_inches": 14 }), &schemas).is_err());
+        assert!(validate(&json!({ "color": null }), &schemas).is_err());
+        assert!(validate(&json!({ "color": "red", "screen_inches": "14" }), &schemas).is_err());
+    }
+
+    #[test]
+    fn test_parse_filters() {
+        let schemas = [
+            schema("screen_inches", AttributeType::Number, false),
+            schema("touch", AttributeType::Boolean, false),
+        ];
+        let params: BTreeMap<String, String> = [
+            ("page", "2"),
+            ("attr.color", "red"),
+            ("attr.screen_inches", "14"),
+            ("attr.touch", "true"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let filter = parse_filters(&params, &schemas).unwrap().unwrap();
+        assert_eq!(filter, json!({ "color": "red", "screen_inches": 14.0, "touch": true }));
+
+        let none: BTreeMap<String, String> = BTreeMap::new();
+        assert_eq!(parse_filters(&none, &schemas), Ok(None));
+
+        let bad: BTreeMap<String, String> =
+            [("attr.touch".to_string(), "yes".to_string())].into_iter().collect();
+        assert!(parse_filters(&bad, &schemas).is_err());
+    }
+}
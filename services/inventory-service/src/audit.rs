@@ -0,0 +1,345 @@
+// =============================================================================
+// AUDIT MODULE
+// =============================================================================
+// Makes the audit trail (every reserve/release/adjust attempt) consumable by
+// SIEM tools, both on demand and as a continuous stream.
+//
+// OUTPUT FORMATS:
+// - JSON lines: one AuditEvent object per line (Loki, Elastic, Splunk HEC)
+// - CEF: ArcSight Common Event Format, understood by most SIEMs
+//
+// SHIPPING:
+// - Pull: GET /api/v1/admin/audit/export?format=cef&after_id=123
+// - Push: set AUDIT_SYSLOG_ADDR=host:514 and new events are sent as RFC 5424
+//   syslog messages (CEF payload) over UDP by a supervised background task
+// - Push: set AUDIT_HTTP_URL and new events are POSTed as JSON lines (Loki
+//   push gateways, Splunk HEC raw endpoints, Vector http source)
+//
+// FOLLOWERS:
+// The shippers, the Kafka publisher (events.rs) and the webhook dispatcher
+// (webhooks.rs) each have a queue in `audit_outbox`, filled by a trigger in
+// the transaction that writes the event (migration 0017). A follower
+// claims its queue (a lease on its `audit_followers` row, so one replica
+// ships at a time), sends the oldest events and marks them shipped. An
+// event whose transaction commits after newer ones is queued when it
+// commits and shipped on the next tick, never passed over.
+//
+// LEARNING NOTES:
+// - Events have increasing IDs, so export consumers resume with `after_id`
+//   = the last ID they saw (returned in the X-Next-Cursor header). IDs are
+//   taken before commit, so the export stops short of events younger than
+//   twice REQUEST_TIMEOUT_MS (export_settle): one with a lower ID may still
+//   be committing. A transaction running longer than that (none of the
+//   service's own should) can still be missed by a pull consumer.
+// - A follower's queue starts when it first registers; history is
+//   available through the export endpoint. Queues survive restarts and
+//   deploys, so nothing is lost while a follower is down, and delivery is
+//   at-least-once: a batch that fails is sent again.
+// - The claim holds no connection while events are sent (migration 0019),
+//   so a stuck sink can't take the pool from request handlers. A replica
+//   that stalls past CLAIM_LEASE loses the follower to another one, which
+//   sends the batch again; the stalled one's "shipped" is then ignored.
+// - Shipped events stay queued for a day; a follower no replica has run
+//   for a week is dropped with its queue
+// - CEF requires escaping: `|` and `\` in the header, `=` and `\` in values
+// =============================================================================
+
+use anyhow::{Context, Result};
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+
+use crate::db::{AuditClaim, Database};
+use crate::http_client::HttpClient;
+use crate::models::{AuditEvent, NewAuditEvent};
+use crate::repository::InventoryRepository;
+
+/// Syslog facility 13 = "log audit"
+const SYSLOG_FACILITY: u8 = 13;
+
+/// Maximum events sent per shipper tick
+const SHIP_BATCH_SIZE: i64 = 500;
+
+/// Hours shipped events stay in the outbox
+const SHIPPED_RETENTION_HOURS: i32 = 24;
+
+/// How long a follower's claim keeps other replicas away; longer than a
+/// batch takes to send, retries included
+const CLAIM_LEASE: Duration = Duration::from_secs(120);
+
+/// Days after which a follower no replica has run is dropped
+const IDLE_FOLLOWER_DAYS: i32 = 7;
+
+/// How often a follower prunes its shipped events
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+// =============================================================================
+// RECORDING HELPERS
+// =============================================================================
+/// Record a failed mutation.
+///
+/// The mutation's own transaction was rolled back, so the failure is written
+/// separately. Errors here are logged, never returned: a broken audit insert
+/// must not mask the original error.
+pub async fn record_failure(
+    repo: &dyn InventoryRepository,
+    action: &str,
+    sku: &str,
+    quantity: i32,
+    reference: &str,
+    error: &anyhow::Error,
+) {
+    let detail = error.to_string();
+    let event = NewAuditEvent {
+        action,
+        outcome: "failure",
+        sku,
+        quantity,
+        reference: Some(reference),
+        detail: Some(&detail),
+        channel: None,
+    };
+
+    if let Err(e) = repo.record_audit_event(&event).await {
+        tracing::warn!(action, sku, error = %e, "Failed to record audit event");
+    }
+}
+
+// =============================================================================
+// FORMATTING
+// =============================================================================
+
+/// Format one event as a JSON line (with trailing newline)
+pub fn to_json_line(event: &AuditEvent) -> String {
+    let mut line = serde_json::to_string(event).unwrap_or_default();
+    line.push('\n');
+    line
+}
+
+/// Format one event as a CEF record (without trailing newline)
+///
+/// # Example
+/// ```text
+/// CEF:0|grafana-lab|inventory-service|1.0.0|inventory.reserve|Stock reserve success|3|rt=1700000000000 ...
+/// ```
+pub fn to_cef(event: &AuditEvent) -> String {
+    let severity = if event.outcome == "success" { 3 } else { 6 };
+
+    let mut extension = vec![
+        format!("rt={}", event.occurred_at.timestamp_millis()),
+        format!("externalId={}", event.id),
+        format!("outcome={}", cef_value(&event.outcome)),
+        "cs1Label=sku".to_string(),
+        format!("cs1={}", cef_value(&event.sku)),
+        format!("cnt={}", event.quantity),
+    ];
+    if let Some(reference) = &event.reference {
+        extension.push("cs2Label=reference".to_string());
+        extension.push(format!("cs2={}", cef_value(reference)));
+    }
+    if let Some(detail) = &event.detail {
+        extension.push(format!("msg={}", cef_value(detail)));
+    }
+
+    format!(
+        "CEF:0|grafana-lab|inventory-service|{}|inventory.{}|{}|{}|{}",
+        cef_header(env!("CARGO_PKG_VERSION")),
+        cef_header(&event.action),
+        cef_header(&format!("Stock {} {}", event.action, event.outcome)),
+        severity,
+        extension.join(" ")
+    )
+}
+
+/// Escape a CEF header field
+fn cef_header(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// Escape a CEF extension value
+fn cef_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+/// Wrap a CEF record in an RFC 5424 syslog message
+pub fn to_syslog(event: &AuditEvent, hostname: &str) -> String {
+    // Severity 6 = informational, 4 = warning
+    let severity = if event.outcome == "success" { 6 } else { 4 };
+    let priority = SYSLOG_FACILITY * 8 + severity;
+
+    format!(
+        "<{}>1 {} {} inventory-service - audit - {}",
+        priority,
+        event.occurred_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        hostname,
+        to_cef(event)
+    )
+}
+
+// =============================================================================
+// SYSLOG SHIPPER
+// =============================================================================
+/// How long the export waits before listing an event: twice the longest a
+/// request's transaction may run, so every event with a lower ID has
+/// committed (see Database::list_audit_events)
+pub fn export_settle(request_timeout: Duration) -> Duration {
+    request_timeout * 2
+}
+
+/// One job following the audit trail through its queue in `audit_outbox`
+pub struct AuditFollower {
+    name: &'static str,
+    last_prune: Option<Instant>,
+}
+
+impl AuditFollower {
+    /// Register `name`, so new events are queued for it; a follower that
+    /// ran before picks up its queue where it stopped
+    pub async fn register(db: &Database, name: &'static str) -> Result<Self> {
+        db.register_audit_follower(name).await?;
+        Ok(Self { name, last_prune: None })
+    }
+
+    /// Claim up to `limit` of the oldest unshipped events
+    ///
+    /// # Returns
+    /// - `None` while another replica ships for this follower
+    pub async fn claim(&mut self, db: &Database, limit: i64) -> Result<Option<AuditClaim>> {
+        if self.last_prune.is_none_or(|at| at.elapsed() >= PRUNE_INTERVAL) {
+            let pruned = db
+                .prune_audit_outbox(self.name, SHIPPED_RETENTION_HOURS, IDLE_FOLLOWER_DAYS)
+                .await?;
+            if pruned > 0 {
+                tracing::debug!(follower = self.name, pruned, "Pruned shipped audit events");
+            }
+            self.last_prune = Some(Instant::now());
+        }
+        db.claim_audit_events(self.name, limit, CLAIM_LEASE).await
+    }
+}
+
+/// Send new audit events to a syslog collector over UDP, forever.
+///
+/// Starts with the events written after it first registered; history is
+/// available through the export endpoint.
+pub async fn run_syslog_shipper(db: Database, addr: String, interval: Duration) -> Result<()> {
+    let socket = UdpSocket::bind("0.0.0.0:0")
+        .await
+        .context("Failed to bind UDP socket for audit shipping")?;
+    socket
+        .connect(&addr)
+        .await
+        .with_context(|| format!("Failed to resolve AUDIT_SYSLOG_ADDR {}", addr))?;
+
+    let mut follower = AuditFollower::register(&db, "audit-syslog").await?;
+
+    let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "-".to_string());
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        let Some(claim) = follower.claim(&db, SHIP_BATCH_SIZE).await? else {
+            continue;
+        };
+
+        for event in &claim.events {
+            socket
+                .send(to_syslog(event, &hostname).as_bytes())
+                .await
+                .context("Failed to send audit event to syslog")?;
+        }
+
+        let count = claim.events.len();
+        claim.finish().await?;
+        if count > 0 {
+            tracing::debug!(count, "Shipped audit events to syslog");
+        }
+    }
+}
+
+// =============================================================================
+// HTTP SHIPPER
+// =============================================================================
+/// POST new audit events as JSON lines to an HTTP collector, forever.
+///
+/// Each tick sends one batch; it is only marked shipped once the collector
+/// accepted it (2xx), so a failed batch is retried by the HTTP client and,
+/// if that runs out, after the supervisor restart.
+pub async fn run_http_shipper(db: Database, http: HttpClient, url: String, interval: Duration) -> Result<()> {
+    let mut follower = AuditFollower::register(&db, "audit-http").await?;
+
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        let Some(claim) = follower.claim(&db, SHIP_BATCH_SIZE).await? else {
+            continue;
+        };
+        if claim.events.is_empty() {
+            claim.finish().await?;
+            continue;
+        }
+
+        let body: String = claim.events.iter().map(to_json_line).collect();
+        let request = http
+            .client()
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+            .body(body);
+
+        http.send("audit-http", request)
+            .await?
+            .error_for_status()
+            .context("Audit collector rejected the batch")?;
+
+        let count = claim.events.len();
+        claim.finish().await?;
+        tracing::debug!(count, "Shipped audit events over HTTP");
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample_event() -> AuditEvent {
+        AuditEvent {
+            id: 42,
+            occurred_at: chrono::Utc.timestamp_millis_opt(1_700_000_000_000).unwrap(),
+            action: "reserve".to_string(),
+            outcome: "failure".to_string(),
+            sku: "SKU-1".to_string(),
+            quantity: 5,
+            reference: Some("ORD=1".to_string()),
+            detail: Some("Insufficient stock|retry".to_string()),
+            channel: None,
+        }
+    }
+
+    #[test]
+    fn test_cef_format_and_escaping() {
+        let cef = to_cef(&sample_event());
+
+        assert!(cef.starts_with("CEF:0|grafana-lab|inventory-service|"));
+        assert!(cef.contains("|inventory.reserve|Stock reserve failure|6|"));
+        assert!(cef.contains("rt=1700000000000 externalId=42"));
+        assert!(cef.contains("cs2=ORD\\=1"));
+        assert!(cef.contains("msg=Insufficient stock|retry"));
+    }
+
+    #[test]
+    fn test_syslog_priority() {
+        let line = to_syslog(&sample_event(), "host-1");
+        // facility 13 * 8 + severity 4 (warning) = 108
+        assert!(line.starts_with("<108>1 2023-11-14T22:13:20.000Z host-1 inventory-service"));
+    }
+}
@@ -0,0 +1,348 @@
+// =============================================================================
+// CACHE HEADERS MODULE
+// =============================================================================
+// HTTP caching hints on read endpoints, so browsers, the frontend and any
+// proxy in between can skip refetching data that hasn't changed.
+//
+// WHAT IT DOES (GET and HEAD, successful responses):
+// - Cache-Control from the route's policy (see below), unless the handler
+//   or public mode already set one
+// - Vary: Accept, Accept-Language (/metrics negotiates its format, errors
+//   and messages are localized)
+// - Handlers that know when their data last changed return `LastModified`
+//   (GET /api/v1/inventory/:sku uses the item's updated_at). A request with
+//   If-Modified-Since at or after that time gets 304 Not Modified.
+// - Responses with an ETag (the item version on GET /api/v1/inventory/:sku,
+//   `ContentETag` on GET /api/v1/inventory) answer If-None-Match: a request
+//   naming the current tag gets 304 Not Modified. If-None-Match wins over
+//   If-Modified-Since when both are sent (RFC 9110).
+//
+// CONFIGURATION:
+//   CACHE_CONTROL_DEFAULT="no-cache"
+//   CACHE_CONTROL_ROUTES="/api/v1/inventory/:sku=private, max-age=5;/api/v1/stats=max-age=30"
+// Rules are separated by `;` (Cache-Control itself uses commas) and match
+// the route template. Without a rule, /health, /ready, /metrics, /debug/*
+// and /api/v1/admin/* get `no-store`, the API docs `public, max-age=300`,
+// and everything else CACHE_CONTROL_DEFAULT.
+//
+// LEARNING NOTES:
+// - `no-cache` doesn't mean "don't cache": caches keep the response but
+//   must revalidate it (If-Modified-Since) before reusing it
+// - `no-store` keeps probes, metrics and admin data out of every cache
+// - HTTP dates have whole seconds, so two changes within the same second
+//   carry the same Last-Modified; a client that fetched between them keeps
+//   the first version until the next change. ETags don't have that gap.
+// - The handler still runs for a 304: it saves the transfer, not the query
+//   (which the item and list caches mostly save anyway)
+// - The list has no Last-Modified: an item deleted from a page, or pushed
+//   off it, leaves the newest updated_at on the page unchanged
+// =============================================================================
+
+use anyhow::{Context, Result};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponseParts, Response, ResponseParts},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::hash::Hasher;
+use std::sync::Arc;
+
+use crate::AppState;
+
+/// Value of the Vary header on read responses
+const VARY: &str = "Accept, Accept-Language";
+
+/// Policy of routes that must never be cached
+const NO_STORE: &str = "no-store";
+
+/// Route prefixes that get NO_STORE unless a rule says otherwise
+const NO_STORE_PREFIXES: [&str; 2] = ["/debug/", "/api/v1/admin/"];
+
+/// Built-in rules, applied unless CACHE_CONTROL_ROUTES overrides them
+const BUILT_IN_RULES: [(&str, &str); 5] = [
+    ("/health", NO_STORE),
+    ("/ready", NO_STORE),
+    ("/metrics", NO_STORE),
+    ("/api-docs/openapi.json", "public, max-age=300"),
+    ("/swagger", "public, max-age=300"),
+];
+
+// -----------------------------------------------------------------------------
+// POLICY
+// -----------------------------------------------------------------------------
+/// Cache-Control per route with a default for everything else
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// Value for routes without a rule
+    pub default: String,
+
+    /// Route template → Cache-Control value
+    pub routes: HashMap<String, String>,
+}
+
+impl CachePolicy {
+    /// Build the policy from the default value and a rule list.
+    ///
+    /// # Arguments
+    /// * `default` - Cache-Control for routes without a rule
+    /// * `rules` - `;`-separated `route=value` pairs (may be empty)
+    pub fn parse(default: &str, rules: &str) -> Result<Self> {
+        let default = check_value(default).context("Invalid CACHE_CONTROL_DEFAULT")?;
+
+        let mut routes: HashMap<String, String> = BUILT_IN_RULES
+            .iter()
+            .map(|(route, value)| (route.to_string(), value.to_string()))
+            .collect();
+        for rule in rules.split(';').map(str::trim).filter(|r| !r.is_empty()) {
+            // Routes never contain '=', values may ("max-age=5")
+            let (route, value) = rule
+                .split_once('=')
+                .with_context(|| format!("Expected route=value, got '{}'", rule))?;
+            let value = check_value(value)
+                .with_context(|| format!("Invalid Cache-Control in '{}'", rule))?;
+            routes.insert(route.trim().to_string(), value);
+        }
+
+        Ok(Self { default, routes })
+    }
+
+    /// Cache-Control value for a route template
+    pub fn value_for(&self, route: &str) -> &str {
+        if let Some(value) = self.routes.get(route) {
+            return value;
+        }
+        if NO_STORE_PREFIXES.iter().any(|prefix| route.starts_with(prefix)) {
+            return NO_STORE;
+        }
+        &self.default
+    }
+}
+
+/// Trim a Cache-Control value and make sure it is a valid header
+fn check_value(value: &str) -> Result<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        anyhow::bail!("Cache-Control value must not be empty");
+    }
+    HeaderValue::from_str(value).context("not a valid header value")?;
+    Ok(value.to_string())
+}
+
+// -----------------------------------------------------------------------------
+// LAST-MODIFIED
+// -----------------------------------------------------------------------------
+/// When a response's data last changed; add it to a handler's response tuple
+/// to send Last-Modified and answer conditional requests
+#[derive(Debug, Clone, Copy)]
+pub struct LastModified(pub DateTime<Utc>);
+
+impl IntoResponseParts for LastModified {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        if let Ok(value) = HeaderValue::from_str(&http_date(self.0)) {
+            res.headers_mut().insert(header::LAST_MODIFIED, value);
+        }
+        Ok(res)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// CONTENT ETAG
+// -----------------------------------------------------------------------------
+/// Weak ETag from a hash of a response body, for responses that aren't a
+/// single versioned item; add it to a handler's response tuple
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentETag(pub u64);
+
+impl ContentETag {
+    /// Tag of a body as it will be serialized
+    ///
+    /// DefaultHasher is keyed the same in every process, so replicas
+    /// running the same build agree on tags.
+    pub fn of(body: &impl Serialize) -> Self {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(&serde_json::to_vec(body).unwrap_or_default());
+        Self(hasher.finish())
+    }
+}
+
+impl IntoResponseParts for ContentETag {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        if let Ok(value) = HeaderValue::from_str(&format!("W/\"{:016x}\"", self.0)) {
+            res.headers_mut().insert(header::ETAG, value);
+        }
+        Ok(res)
+    }
+}
+
+/// Whether an If-None-Match value names the response's ETag
+///
+/// Weak comparison (W/"x" matches "x"), as RFC 9110 requires for GET;
+/// `*` matches any current representation.
+fn none_match(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    if_none_match
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == etag)
+}
+
+/// Format a time as an HTTP date (RFC 9110, always GMT)
+fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parse an HTTP date as sent in If-Modified-Since
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Whether a response modified at `modified` is still fresh for a client
+/// holding the version from `since`
+fn not_modified(modified: DateTime<Utc>, since: DateTime<Utc>) -> bool {
+    modified.timestamp() <= since.timestamp()
+}
+
+// =============================================================================
+// MIDDLEWARE
+// =============================================================================
+/// Add Cache-Control and Vary to read responses and answer If-None-Match
+/// and If-Modified-Since
+pub async fn apply_cache_headers(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return next.run(request).await;
+    }
+
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let if_modified_since = request
+        .headers()
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_http_date);
+    let if_none_match = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let mut response = next.run(request).await;
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let headers = response.headers_mut();
+    if !headers.contains_key(header::CACHE_CONTROL) {
+        let value = state.config.cache_headers.value_for(&route);
+        if let Ok(value) = HeaderValue::from_str(value) {
+            headers.insert(header::CACHE_CONTROL, value);
+        }
+    }
+    headers.append(header::VARY, HeaderValue::from_static(VARY));
+
+    let fresh = match if_none_match {
+        Some(if_none_match) => headers
+            .get(header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|etag| none_match(&if_none_match, etag)),
+        None => {
+            let last_modified = headers
+                .get(header::LAST_MODIFIED)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_http_date);
+            matches!(
+                (last_modified, if_modified_since),
+                (Some(modified), Some(since)) if not_modified(modified, since)
+            )
+        }
+    };
+    if fresh {
+        let (mut parts, _) = response.into_parts();
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(header::CONTENT_TYPE);
+        parts.headers.remove(header::CONTENT_LENGTH);
+        return Response::from_parts(parts, axum::body::Body::empty());
+    }
+
+    response
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_policy_rules_and_defaults() {
+        let policy = CachePolicy::parse(
+            "no-cache",
+            "/api/v1/inventory/:sku=private, max-age=5; /health=max-age=1",
+        )
+        .unwrap();
+
+        assert_eq!(policy.value_for("/api/v1/inventory/:sku"), "private, max-age=5");
+        assert_eq!(policy.value_for("/health"), "max-age=1");
+        assert_eq!(policy.value_for("/metrics"), "no-store");
+        assert_eq!(policy.value_for("/api/v1/admin/usage"), "no-store");
+        assert_eq!(policy.value_for("/swagger"), "public, max-age=300");
+        assert_eq!(policy.value_for("/api/v1/inventory"), "no-cache");
+    }
+
+    #[test]
+    fn test_policy_rejects_bad_rules() {
+        assert!(CachePolicy::parse("", "").is_err());
+        assert!(CachePolicy::parse("no-cache", "/health").is_err());
+        assert!(CachePolicy::parse("no-cache", "/health=").is_err());
+    }
+
+    #[test]
+    fn test_http_date_round_trip() {
+        let time = Utc.with_ymd_and_hms(1994, 11, 6, 8, 49, 37).unwrap();
+        assert_eq!(http_date(time), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(time));
+        assert_eq!(parse_http_date("yesterday"), None);
+    }
+
+    #[test]
+    fn test_none_match_and_content_etag() {
+        assert!(none_match("\"7\"", "\"7\""));
+        assert!(none_match("\"6\", W/\"7\"", "\"7\""));
+        assert!(none_match("*", "W/\"00ff\""));
+        assert!(!none_match("\"6\"", "\"7\""));
+
+        let page = serde_json::json!({ "items": [], "total": 0 });
+        assert_eq!(ContentETag::of(&page), ContentETag::of(&page));
+        assert_ne!(ContentETag::of(&page), ContentETag::of(&serde_json::json!({ "total": 1 })));
+    }
+
+    #[test]
+    fn test_not_modified_ignores_fractions() {
+        let since = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let same_second = since + chrono::Duration::milliseconds(400);
+
+        assert!(not_modified(same_second, since));
+        assert!(not_modified(since - chrono::Duration::seconds(5), since));
+        assert!(!not_modified(since + chrono::Duration::seconds(1), since));
+    }
+}
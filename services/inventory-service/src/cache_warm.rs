@@ -0,0 +1,185 @@
+// =============================================================================
+// CACHE WARM MODULE
+// =============================================================================
+// Pre-populates the per-SKU Redis cache (inventory:item:<sku>) at startup, so
+// the first requests after a deploy don't all fall through to Postgres.
+//
+// STRATEGIES (CACHE_WARM):
+// - off:     no warming (default)
+// - recent:  the most recently updated SKUs
+// - popular: the SKUs reserved most often in the last POPULAR_WINDOW_DAYS,
+//            from the audit trail (reads aren't recorded anywhere, and
+//            reserved SKUs are the ones the order flow keeps looking up)
+//
+// CACHE_WARM_SKUS limits how many SKUs are loaded (default: 200).
+//
+// LEARNING NOTES:
+// - /ready reports not ready until warming has finished, so Kubernetes only
+//   routes traffic to a pod with a warm cache
+// - Warming is best effort: a failure or timeout is logged and the pod
+//   becomes ready anyway, with a cold cache
+// - The cache is shared, so replicas starting together take turns: one
+//   warms under the "cache-warm" lease (distributed_lock.rs) and the others
+//   skip warming and become ready straight away
+// =============================================================================
+
+use anyhow::{Context, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::db::Database;
+use crate::distributed_lock::DistributedLock;
+use crate::item_cache;
+use crate::metrics;
+
+/// Days of reservations considered by the popular strategy
+pub const POPULAR_WINDOW_DAYS: i32 = 7;
+
+/// Longest warming may hold back readiness
+const WARM_TIMEOUT: Duration = Duration::from_secs(30);
+
+// -----------------------------------------------------------------------------
+// STRATEGY
+// -----------------------------------------------------------------------------
+/// Which SKUs are loaded into the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarmStrategy {
+    Recent,
+    Popular,
+}
+
+impl WarmStrategy {
+    /// Parse a CACHE_WARM value; `None` when warming is off
+    pub fn parse(value: &str) -> Result<Option<Self>> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "off" => Ok(None),
+            "recent" => Ok(Some(Self::Recent)),
+            "popular" => Ok(Some(Self::Popular)),
+            other => anyhow::bail!("CACHE_WARM must be off, recent or popular, got '{}'", other),
+        }
+    }
+
+    /// Name used in logs
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Recent => "recent",
+            Self::Popular => "popular",
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// READINESS
+// -----------------------------------------------------------------------------
+/// Whether startup warming has finished (readiness gate)
+#[derive(Clone)]
+pub struct CacheWarmer {
+    done: Arc<AtomicBool>,
+}
+
+impl CacheWarmer {
+    /// A warmer that is already done when warming is off
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            done: Arc::new(AtomicBool::new(!enabled)),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done.load(Ordering::Acquire)
+    }
+
+    /// Warm the cache once, then open the readiness gate whatever happened
+    pub async fn run(
+        self,
+        db: Database,
+        mut redis: redis::aio::ConnectionManager,
+        strategy: WarmStrategy,
+        limit: i64,
+    ) {
+        let lock = DistributedLock::new(redis.clone(), "cache-warm", WARM_TIMEOUT);
+        let Some(lease) = lock.acquire().await else {
+            tracing::info!("Cache is being warmed by another replica, skipping");
+            self.done.store(true, Ordering::Release);
+            return;
+        };
+
+        let start = Instant::now();
+        let outcome =
+            tokio::time::timeout(WARM_TIMEOUT, warm(&db, &mut redis, strategy, limit)).await;
+        lock.release(lease).await;
+
+        match outcome {
+            Ok(Ok(warmed)) => tracing::info!(
+                strategy = strategy.as_str(),
+                warmed,
+                duration_ms = start.elapsed().as_millis() as u64,
+                "Cache warmed"
+            ),
+            Ok(Err(e)) => tracing::warn!(error = %e, "Cache warming failed, starting cold"),
+            Err(_) => tracing::warn!(
+                timeout_secs = WARM_TIMEOUT.as_secs(),
+                "Cache warming timed out, starting cold"
+            ),
+        }
+
+        self.done.store(true, Ordering::Release);
+    }
+}
+
+/// Load up to `limit` items and cache them; returns how many were cached
+async fn warm(
+    db: &Database,
+    redis: &mut redis::aio::ConnectionManager,
+    strategy: WarmStrategy,
+    limit: i64,
+) -> Result<usize> {
+    let start = Instant::now();
+    let items = db.warm_cache_candidates(strategy, limit).await?;
+    metrics::record_db_query("cache_warm", start.elapsed().as_secs_f64());
+
+    if items.is_empty() {
+        return Ok(0);
+    }
+
+    let start = Instant::now();
+    let mut pipe = redis::pipe();
+    for item in &items {
+        let json = serde_json::to_string(item).context("Failed to serialize inventory item")?;
+        pipe.cmd("SETEX")
+            .arg(item_cache::key(&item.sku))
+            .arg(item_cache::ITEM_CACHE_TTL_SECS)
+            .arg(json)
+            .ignore();
+    }
+    pipe.query_async::<_, ()>(redis)
+        .await
+        .context("Failed to write warmed items to Redis")?;
+    metrics::record_redis_operation("cache_warm", start.elapsed().as_secs_f64());
+
+    Ok(items.len())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_strategy() {
+        assert_eq!(WarmStrategy::parse("").unwrap(), None);
+        assert_eq!(WarmStrategy::parse("off").unwrap(), None);
+        assert_eq!(WarmStrategy::parse("Recent").unwrap(), Some(WarmStrategy::Recent));
+        assert_eq!(WarmStrategy::parse("popular").unwrap(), Some(WarmStrategy::Popular));
+        assert!(WarmStrategy::parse("hot").is_err());
+    }
+
+    #[test]
+    fn test_disabled_warmer_is_done() {
+        assert!(CacheWarmer::new(false).is_done());
+        assert!(!CacheWarmer::new(true).is_done());
+    }
+}
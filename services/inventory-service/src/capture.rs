@@ -0,0 +1,546 @@
+// =============================================================================
+// CAPTURE MODULE
+// =============================================================================
+// Opt-in recording of incoming API requests, producing the request logs that
+// the replay endpoint (replay.rs) plays back.
+//
+// CONFIGURATION:
+//   CAPTURE_MODE=off     (default) nothing is recorded
+//   CAPTURE_MODE=file    append JSON lines to CAPTURE_FILE
+//                        (default: <REPLAY_DIR>/capture.jsonl)
+//   CAPTURE_MODE=redis   XADD to the CAPTURE_STREAM Redis stream
+//                        (default: inventory:capture, capped at ~100k entries)
+//
+// RECORD FORMAT:
+// A replay record (offset_ms, method, path, body) plus what was observed:
+//   {"offset_ms":1200,"method":"POST","path":"/api/v1/inventory/reserve",
+//    "body":{...},"route":"/api/v1/inventory/reserve","status":200,"duration_ms":4}
+//
+// SANITIZING:
+// Bodies and query strings are checked against the same field list as the
+// logs (LOG_REDACT_FIELDS), so order IDs, customer references and
+// credentials never reach the capture:
+// - Identifiers (PSEUDONYM_FIELDS) become keyed-hash pseudonyms: the same
+//   order ID always maps to the same "anon-..." value and different ones
+//   stay different, so a replay still reserves per order, hits per-order
+//   caps and settles each order on its own
+// - Everything else on the list (API keys, passwords) is [REDACTED]
+//
+//   CAPTURE_PSEUDONYM_KEY   HMAC key; unset = a random key per process,
+//                           so pseudonyms only match within one run
+//
+// LEARNING NOTES:
+// - Only /api/v1 traffic is captured, minus admin endpoints; probes and
+//   scrapes are not worth replaying
+// - Requests sent by a replay carry X-Replayed-From and are skipped, so
+//   replaying while capturing doesn't record the same traffic twice
+// - A pseudonym can't be turned back into the ID without the key; set the
+//   key to join captures from several pods or restarts
+// - The middleware never blocks on I/O: records go through a bounded channel
+//   to a supervised writer task, and are dropped if the writer falls behind
+// =============================================================================
+
+use anyhow::{bail, Context, Result};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use axum::{
+    body::{to_bytes, Body},
+    extract::{MatchedPath, Request, State},
+    http::header::{CONTENT_LENGTH, TRANSFER_ENCODING},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::error::AppError;
+use crate::redact::{Redactor, REDACTED};
+use crate::replay::{ReplayRecord, REPLAYED_FROM_HEADER};
+use crate::AppState;
+
+/// Bodies larger than this are not captured (the request is skipped)
+const MAX_CAPTURE_BODY_BYTES: usize = 64 * 1024;
+
+/// Records buffered between the middleware and the writer
+const CHANNEL_CAPACITY: usize = 10_000;
+
+/// Approximate length cap for the Redis stream
+const STREAM_MAX_LEN: usize = 100_000;
+
+/// Redacted fields that identify something (an order, a customer) rather
+/// than grant access; captures keep them apart with pseudonyms
+const PSEUDONYM_FIELDS: &[&str] = &["order_id", "customer_id", "customer_ref"];
+
+// -----------------------------------------------------------------------------
+// TARGET
+// -----------------------------------------------------------------------------
+/// Where captured records are written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureTarget {
+    File(PathBuf),
+    RedisStream(String),
+}
+
+impl CaptureTarget {
+    /// Build the capture target from CAPTURE_MODE and its settings.
+    ///
+    /// Returns `None` when capture is off.
+    pub fn parse(mode: &str, file: &str, stream: &str) -> Result<Option<Self>> {
+        match mode.trim().to_ascii_lowercase().as_str() {
+            "" | "off" => Ok(None),
+            "file" => Ok(Some(CaptureTarget::File(PathBuf::from(file)))),
+            "redis" => Ok(Some(CaptureTarget::RedisStream(stream.to_string()))),
+            other => bail!("CAPTURE_MODE must be off, file or redis, got '{}'", other),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// SANITIZER
+// -----------------------------------------------------------------------------
+/// HMAC key for pseudonyms (CAPTURE_PSEUDONYM_KEY), kept out of Debug output
+#[derive(Clone, PartialEq, Eq)]
+pub struct PseudonymKey(Vec<u8>);
+
+impl fmt::Debug for PseudonymKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PseudonymKey(<redacted>)")
+    }
+}
+
+impl PseudonymKey {
+    /// The configured key, or a random one when `key` is empty
+    pub fn parse(key: &str) -> Self {
+        if key.trim().is_empty() {
+            let mut random = vec![0u8; 32];
+            rand::thread_rng().fill_bytes(&mut random);
+            Self(random)
+        } else {
+            Self(key.trim().as_bytes().to_vec())
+        }
+    }
+}
+
+/// Makes request bodies and query strings safe to store (see SANITIZING)
+#[derive(Clone)]
+pub struct Sanitizer {
+    redactor: Redactor,
+    key: PseudonymKey,
+}
+
+impl Sanitizer {
+    pub fn new(redactor: Redactor, key: PseudonymKey) -> Self {
+        Self { redactor, key }
+    }
+
+    /// Stable stand-in for the value of an identifier field
+    fn pseudonym(&self, field: &str, value: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key.0).expect("HMAC takes any key length");
+        mac.update(field.as_bytes());
+        mac.update(b":");
+        mac.update(value.as_bytes());
+        let digest = mac.finalize().into_bytes();
+        let hex: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        format!("anon-{}", hex)
+    }
+
+    /// What a sensitive field's value is stored as
+    fn replace(&self, field: &str, value: &str) -> String {
+        let field = field.to_ascii_lowercase();
+        if PSEUDONYM_FIELDS.contains(&field.as_str()) {
+            self.pseudonym(&field, value)
+        } else {
+            REDACTED.to_string()
+        }
+    }
+
+    /// Sanitize a JSON body in place, at any depth
+    pub fn sanitize_json(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if !self.redactor.is_sensitive(key) {
+                        self.sanitize_json(field);
+                        continue;
+                    }
+                    let raw = match &*field {
+                        Value::String(text) => text.clone(),
+                        Value::Null => continue,
+                        other => other.to_string(),
+                    };
+                    *field = Value::String(self.replace(key, &raw));
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.sanitize_json(item)),
+            _ => {}
+        }
+    }
+
+    /// Sanitize the query string of a path (and query)
+    pub fn sanitize_path(&self, path: &str) -> String {
+        let Some((path, query)) = path.split_once('?') else {
+            return path.to_string();
+        };
+        let pairs: Vec<(String, String)> = reqwest::Url::parse(&format!("http://capture/?{}", query))
+            .map(|url| url.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect())
+            .unwrap_or_default();
+        if !pairs.iter().any(|(key, _)| self.redactor.is_sensitive(key)) {
+            return format!("{}?{}", path, query);
+        }
+
+        let mut url = reqwest::Url::parse("http://capture/").expect("static URL");
+        url.query_pairs_mut().extend_pairs(pairs.iter().map(|(key, value)| {
+            let value = if self.redactor.is_sensitive(key) {
+                self.replace(key, value)
+            } else {
+                value.clone()
+            };
+            (key.clone(), value)
+        }));
+        format!("{}?{}", path, url.query().unwrap_or_default())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// RECORDS
+// -----------------------------------------------------------------------------
+/// A captured request: replayable fields plus what was observed
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureRecord {
+    #[serde(flatten)]
+    pub request: ReplayRecord,
+
+    /// Route template that matched, e.g. /api/v1/inventory/:sku
+    pub route: String,
+
+    /// Response status code
+    pub status: u16,
+
+    /// Time spent handling the request
+    pub duration_ms: u64,
+}
+
+// =============================================================================
+// RECORDER
+// =============================================================================
+/// Handle used by the middleware to hand records to the writer
+#[derive(Clone)]
+pub struct Capture {
+    sender: mpsc::Sender<CaptureRecord>,
+    receiver: Arc<Mutex<mpsc::Receiver<CaptureRecord>>>,
+    sanitizer: Sanitizer,
+    /// When the first record was captured (offsets are relative to it)
+    first_request: Arc<OnceLock<Instant>>,
+}
+
+impl Capture {
+    pub fn new(sanitizer: Sanitizer) -> Self {
+        let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+        Self {
+            sender,
+            receiver: Arc::new(Mutex::new(receiver)),
+            sanitizer,
+            first_request: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Whether a request should be captured
+    fn wants(request: &Request) -> bool {
+        let path = request.uri().path();
+        path.starts_with("/api/v1/")
+            && !path.starts_with("/api/v1/admin/")
+            && !request.headers().contains_key(REPLAYED_FROM_HEADER)
+    }
+
+    fn record(&self, record: CaptureRecord) {
+        if self.sender.try_send(record).is_err() {
+            tracing::debug!("Capture writer is behind, dropping record");
+        }
+    }
+}
+
+/// Middleware: record API requests while capture mode is on
+pub async fn capture_requests(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(capture) = state.capture.as_ref() else {
+        return next.run(request).await;
+    };
+
+    // Only buffer bodies of known, bounded size; skip everything else
+    let content_length = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let streamed = request.headers().contains_key(TRANSFER_ENCODING);
+    if !Capture::wants(&request) || streamed || content_length > MAX_CAPTURE_BODY_BYTES {
+        return next.run(request).await;
+    }
+
+    let start = Instant::now();
+    let offset = start.duration_since(*capture.first_request.get_or_init(|| start));
+
+    let method = request.method().to_string();
+    let path = request
+        .uri()
+        .path_and_query()
+        .map(|pq| capture.sanitizer.sanitize_path(pq.as_str()))
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_CAPTURE_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return AppError::BadRequest(format!("Failed to read request body: {}", e))
+                .into_response()
+        }
+    };
+
+    // Non-JSON bodies are not replayable, keep the request without one
+    let body = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .map(|mut value| {
+            capture.sanitizer.sanitize_json(&mut value);
+            value
+        });
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+
+    capture.record(CaptureRecord {
+        request: ReplayRecord {
+            offset_ms: offset.as_millis() as u64,
+            method,
+            path,
+            body,
+        },
+        route,
+        status: response.status().as_u16(),
+        duration_ms: start.elapsed().as_millis() as u64,
+    });
+
+    response
+}
+
+// =============================================================================
+// WRITER
+// =============================================================================
+/// Drain captured records into the target, forever.
+///
+/// The receiver is shared so a supervised restart picks up where the
+/// previous run stopped.
+pub async fn run_writer(
+    capture: Capture,
+    target: CaptureTarget,
+    mut redis: redis::aio::ConnectionManager,
+) -> Result<()> {
+    let mut receiver = capture.receiver.lock().await;
+
+    match target {
+        CaptureTarget::File(path) => {
+            if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                tokio::fs::create_dir_all(dir)
+                    .await
+                    .with_context(|| format!("Failed to create {}", dir.display()))?;
+            }
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await
+                .with_context(|| format!("Failed to open capture file {}", path.display()))?;
+
+            while let Some(record) = receiver.recv().await {
+                let mut line = serde_json::to_string(&record)?;
+                line.push('\n');
+                file.write_all(line.as_bytes())
+                    .await
+                    .context("Failed to write capture record")?;
+            }
+        }
+        CaptureTarget::RedisStream(stream) => {
+            while let Some(record) = receiver.recv().await {
+                let _: String = redis::cmd("XADD")
+                    .arg(&stream)
+                    .arg("MAXLEN")
+                    .arg("~")
+                    .arg(STREAM_MAX_LEN)
+                    .arg("*")
+                    .arg("record")
+                    .arg(serde_json::to_string(&record)?)
+                    .query_async(&mut redis)
+                    .await
+                    .context("Failed to append capture record to Redis stream")?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(CaptureTarget::parse("off", "f", "s").unwrap(), None);
+        assert_eq!(
+            CaptureTarget::parse("FILE", "replays/capture.jsonl", "s").unwrap(),
+            Some(CaptureTarget::File(PathBuf::from("replays/capture.jsonl")))
+        );
+        assert_eq!(
+            CaptureTarget::parse("redis", "f", "inventory:capture").unwrap(),
+            Some(CaptureTarget::RedisStream("inventory:capture".to_string()))
+        );
+        assert!(CaptureTarget::parse("kafka", "f", "s").is_err());
+    }
+
+    #[test]
+    fn test_record_is_replayable() {
+        let record = CaptureRecord {
+            request: ReplayRecord {
+                offset_ms: 120,
+                method: "GET".to_string(),
+                path: "/api/v1/inventory?page=2".to_string(),
+                body: None,
+            },
+            route: "/api/v1/inventory".to_string(),
+            status: 200,
+            duration_ms: 3,
+        };
+
+        let line = serde_json::to_string(&record).unwrap();
+        let replayed = crate::replay::parse(&line).unwrap();
+        assert_eq!(replayed[0].path, "/api/v1/inventory?page=2");
+        assert_eq!(replayed[0].offset_ms, 120);
+    }
+
+    fn sanitizer() -> Sanitizer {
+        Sanitizer::new(
+            Redactor::new(crate::redact::DEFAULT_REDACT_FIELDS),
+            PseudonymKey::parse("test-key"),
+        )
+    }
+
+    #[test]
+    fn test_identifiers_get_stable_pseudonyms() {
+        let sanitizer = sanitizer();
+        let mut body = serde_json::json!({
+            "sku": "SKU-1",
+            "order_id": "ORD-1",
+            "items": [{ "order_id": "ORD-2" }, { "order_id": "ORD-1" }],
+            "api_key": "secret"
+        });
+        sanitizer.sanitize_json(&mut body);
+
+        let first = body["order_id"].as_str().unwrap();
+        assert!(first.starts_with("anon-"), "{}", first);
+        assert_eq!(body["items"][1]["order_id"], first);
+        assert_ne!(body["items"][0]["order_id"], first);
+        assert_eq!(body["api_key"], REDACTED);
+        assert_eq!(body["sku"], "SKU-1");
+
+        // Same pseudonym in a query string; other parameters are untouched
+        let path = sanitizer.sanitize_path("/api/v1/reservations?order_id=ORD-1&limit=5");
+        assert_eq!(path, format!("/api/v1/reservations?order_id={}&limit=5", first));
+        assert_eq!(sanitizer.sanitize_path("/api/v1/inventory?page=2"), "/api/v1/inventory?page=2");
+
+        // Another key, other pseudonyms
+        let other = Sanitizer::new(Redactor::new("order_id"), PseudonymKey::parse("other-key"));
+        let mut body = serde_json::json!({ "order_id": "ORD-1" });
+        other.sanitize_json(&mut body);
+        assert_ne!(body["order_id"], first);
+    }
+
+    /// Serve `state` with the reserve route, capturing if it has capture on
+    async fn serve(state: Arc<AppState>) -> String {
+        let app = axum::Router::new()
+            .route("/api/v1/inventory/reserve", axum::routing::post(crate::handlers::reserve_stock))
+            .layer(axum::middleware::from_fn_with_state(state.clone(), capture_requests))
+            .with_state(state);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}", addr)
+    }
+
+    async fn stocked_state() -> Arc<AppState> {
+        let state = crate::test_support::state(&[]).await;
+        let item = crate::models::CreateItemRequest {
+            sku: "SKU-1".to_string(),
+            name: "Item".to_string(),
+            quantity: 10,
+            warehouse: "JKT-1".to_string(),
+            low_stock_threshold: 1,
+        };
+        state.repo.create_item(&item, None).await.unwrap().unwrap();
+        state
+    }
+
+    #[tokio::test]
+    async fn test_replay_keeps_captured_orders_apart() {
+        let mut state = stocked_state().await;
+        let capture = Capture::new(sanitizer());
+        Arc::get_mut(&mut state).unwrap().capture = Some(capture.clone());
+        let base_url = serve(state).await;
+
+        let client = reqwest::Client::new();
+        for order_id in ["ORD-1", "ORD-2"] {
+            let response = client
+                .post(format!("{}/api/v1/inventory/reserve", base_url))
+                .json(&serde_json::json!({ "sku": "SKU-1", "quantity": 3, "order_id": order_id }))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 200);
+        }
+
+        let mut lines = String::new();
+        let mut receiver = capture.receiver.lock().await;
+        for _ in 0..2 {
+            let record = receiver.recv().await.unwrap();
+            let order_id = record.request.body.as_ref().unwrap()["order_id"].as_str().unwrap().to_string();
+            assert!(order_id.starts_with("anon-"), "{}", order_id);
+            lines.push_str(&serde_json::to_string(&record).unwrap());
+            lines.push('\n');
+        }
+        let records = crate::replay::parse(&lines).unwrap();
+
+        // Replayed against a fresh copy of the stock: two orders, two
+        // reservations
+        let target = stocked_state().await;
+        let replay_url = serve(target.clone()).await;
+        let http = crate::http_client::HttpClient::new(crate::http_client::HttpClientPolicy {
+            timeout: std::time::Duration::from_secs(5),
+            max_retries: 0,
+            backoff_base: std::time::Duration::from_millis(10),
+        })
+        .unwrap();
+        let summary = crate::replay::run(http, replay_url, "capture.jsonl".to_string(), records, 100.0).await;
+        assert_eq!((summary.sent, summary.failed), (2, 0));
+
+        let item = target.repo.get_by_sku("SKU-1").await.unwrap().unwrap();
+        assert_eq!(item.reserved, 6);
+    }
+}
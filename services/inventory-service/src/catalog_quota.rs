@@ -0,0 +1,103 @@
+// =============================================================================
+// CATALOG QUOTA MODULE
+// =============================================================================
+// Caps on how many SKUs the catalog may hold, for shared lab clusters where
+// one team's seeding script shouldn't fill the database for everyone.
+//
+// LIMITS:
+// - CATALOG_SOFT_LIMIT: creating SKUs past it still works, but logs a
+//   warning and counts inventory_catalog_quota_warnings_total
+// - CATALOG_HARD_LIMIT: creates that would go past it answer 409
+//   CATALOG_QUOTA_EXCEEDED
+// Both are off by default. The soft limit must not exceed the hard one.
+//
+// METRICS:
+// - inventory_catalog_skus: SKUs in the catalog
+// - inventory_catalog_sku_limit{kind="soft"|"hard"}: configured limits, for
+//   alerts comparing the two (see CatalogQuotaSoftLimit in alert_rules.yml)
+// - inventory_catalog_quota_warnings_total: creates past the soft limit
+//
+// LEARNING NOTES:
+// - The count and the INSERT run in one transaction behind an advisory
+//   lock, so concurrent creates can't both take the last free slot
+// - Lowering a limit below the current size never deletes anything; it
+//   only blocks further creates
+// =============================================================================
+
+use anyhow::Result;
+
+/// Soft and hard caps on the number of SKUs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CatalogQuota {
+    /// Warn past this many SKUs
+    pub soft_limit: Option<i64>,
+    /// Reject creates past this many SKUs
+    pub hard_limit: Option<i64>,
+}
+
+/// Where a catalog size stands against the quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaStatus {
+    Within,
+    OverSoft,
+    OverHard,
+}
+
+impl CatalogQuota {
+    /// Build from the CATALOG_SOFT_LIMIT / CATALOG_HARD_LIMIT values
+    pub fn new(soft_limit: Option<i64>, hard_limit: Option<i64>) -> Result<Self> {
+        if soft_limit.is_some_and(|l| l < 1) || hard_limit.is_some_and(|l| l < 1) {
+            anyhow::bail!("CATALOG_SOFT_LIMIT and CATALOG_HARD_LIMIT must be at least 1");
+        }
+        if let (Some(soft), Some(hard)) = (soft_limit, hard_limit) {
+            if soft > hard {
+                anyhow::bail!(
+                    "CATALOG_SOFT_LIMIT ({}) must not exceed CATALOG_HARD_LIMIT ({})",
+                    soft,
+                    hard
+                );
+            }
+        }
+        Ok(Self {
+            soft_limit,
+            hard_limit,
+        })
+    }
+
+    /// Status of a catalog holding `skus` SKUs
+    pub fn status(&self, skus: i64) -> QuotaStatus {
+        if self.hard_limit.is_some_and(|limit| skus > limit) {
+            QuotaStatus::OverHard
+        } else if self.soft_limit.is_some_and(|limit| skus > limit) {
+            QuotaStatus::OverSoft
+        } else {
+            QuotaStatus::Within
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_status() {
+        let quota = CatalogQuota::new(Some(8), Some(10)).unwrap();
+        assert_eq!(quota.status(8), QuotaStatus::Within);
+        assert_eq!(quota.status(9), QuotaStatus::OverSoft);
+        assert_eq!(quota.status(10), QuotaStatus::OverSoft);
+        assert_eq!(quota.status(11), QuotaStatus::OverHard);
+
+        assert_eq!(CatalogQuota::default().status(1_000_000), QuotaStatus::Within);
+    }
+
+    #[test]
+    fn test_quota_validation() {
+        assert!(CatalogQuota::new(Some(11), Some(10)).is_err());
+        assert!(CatalogQuota::new(None, Some(0)).is_err());
+        assert!(CatalogQuota::new(Some(5), None).is_ok());
+    }
+}
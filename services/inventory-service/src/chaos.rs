@@ -0,0 +1,409 @@
+// =============================================================================
+// CHAOS MODULE
+// =============================================================================
+// Admin-triggered fault injection, so students can break the service on
+// purpose and watch the dashboards and alerts react: latency panels climb,
+// error-rate alerts fire, the DB pool saturates, memory and CPU graphs jump.
+//
+// FAULTS (any combination, per experiment):
+// - latency_ms:   every matching request waits this long before its handler
+// - db_delay_ms:  every matching request first holds a database connection
+//                 for this long (SELECT pg_sleep), like a slow query would
+// - cpu_burn_ms:  every matching request spins a CPU for this long
+// - error_rate:   this share of matching requests fail with error_status
+//                 (500, 502, 503 or 504; default 500), code FAULT_INJECTED
+// - memory_mb:    held once for the whole experiment, not per request
+//
+// USAGE:
+//   POST   /api/v1/chaos      {"endpoint":"/api/v1/inventory/reserve",
+//                              "latency_ms":300,"error_rate":0.2,
+//                              "duration_secs":120}
+//   GET    /api/v1/chaos      → running experiments
+//   DELETE /api/v1/chaos/:id  → stop one early
+//   DELETE /api/v1/chaos      → stop all
+//
+// LEARNING NOTES:
+// - `endpoint` is a route template prefix (`/api/v1/inventory/:sku`, not a
+//   SKU); without it every /api/ route is hit. The chaos API itself never is.
+// - Every experiment ends by itself after `duration_secs` (max 3600); at
+//   most 10 run at once, and faults of overlapping experiments add up
+// - Injected latency counts against the request deadline (deadline.rs), so
+//   enough of it turns into 504 DEADLINE_EXCEEDED
+// - chaos_faults_injected_total{endpoint, fault} shows what was injected,
+//   so a dashboard can tell an exercise from a real incident
+// - DISABLED_ENDPOINTS=chaos switches the API off; public read-only mode
+//   hides it
+// =============================================================================
+
+use anyhow::{bail, Result};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+use crate::error::AppError;
+use crate::metrics;
+use crate::validation::{self, Validate, Validator};
+use crate::AppState;
+
+/// Where the chaos API is served; requests to it are never injected
+pub const PATH: &str = "/api/v1/chaos";
+
+/// Experiments running at once
+const MAX_EXPERIMENTS: usize = 10;
+
+/// Upper bound on a single experiment
+const MAX_DURATION_SECS: u64 = 3600;
+
+/// Statuses an injected error may have
+const ERROR_STATUSES: [u16; 4] = [500, 502, 503, 504];
+
+// -----------------------------------------------------------------------------
+// REQUEST / STATUS
+// -----------------------------------------------------------------------------
+/// Faults to inject, and for how long
+///
+/// # Example JSON
+/// ```json
+/// { "endpoint": "/api/v1/inventory", "latency_ms": 500, "db_delay_ms": 200,
+///   "error_rate": 0.1, "error_status": 503, "duration_secs": 300 }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FaultRequest {
+    /// Route template prefix to hit (default: every /api/ route)
+    #[serde(default)]
+    pub endpoint: Option<String>,
+
+    /// Added delay per request (max: 30000)
+    #[serde(default)]
+    pub latency_ms: u64,
+
+    /// Database connection held per request (max: 30000)
+    #[serde(default)]
+    pub db_delay_ms: u64,
+
+    /// CPU time burnt per request (max: 5000)
+    #[serde(default)]
+    pub cpu_burn_ms: u64,
+
+    /// Share of requests that fail, 0.0-1.0
+    #[serde(default)]
+    pub error_rate: f64,
+
+    /// Status of failed requests: 500, 502, 503 or 504 (default: 500)
+    #[serde(default = "default_error_status")]
+    pub error_status: u16,
+
+    /// Memory held while the experiment runs (max: 2048)
+    #[serde(default)]
+    pub memory_mb: u64,
+
+    /// How long to run (default: 60, max: 3600)
+    #[serde(default = "default_duration_secs")]
+    pub duration_secs: u64,
+}
+
+fn default_error_status() -> u16 {
+    500
+}
+fn default_duration_secs() -> u64 {
+    60
+}
+
+impl Validate for FaultRequest {
+    fn check(&self, v: &mut Validator) {
+        if let Some(endpoint) = &self.endpoint {
+            v.text("endpoint", endpoint, 200);
+            if !endpoint.starts_with("/api/") {
+                v.add("endpoint", "must be a route template starting with /api/");
+            }
+        }
+        v.range("latency_ms", self.latency_ms, 0, 30_000);
+        v.range("db_delay_ms", self.db_delay_ms, 0, 30_000);
+        v.range("cpu_burn_ms", self.cpu_burn_ms, 0, 5_000);
+        v.range("error_rate", self.error_rate, 0.0, 1.0);
+        if !ERROR_STATUSES.contains(&self.error_status) {
+            v.add("error_status", "must be 500, 502, 503 or 504");
+        }
+        v.range("memory_mb", self.memory_mb, 0, 2048);
+        v.range("duration_secs", self.duration_secs, 1, MAX_DURATION_SECS);
+
+        let nothing = self.latency_ms == 0
+            && self.db_delay_ms == 0
+            && self.cpu_burn_ms == 0
+            && self.error_rate == 0.0
+            && self.memory_mb == 0;
+        if nothing {
+            v.add("faults", "set at least one of latency_ms, db_delay_ms, cpu_burn_ms, error_rate, memory_mb");
+        }
+    }
+}
+
+/// A running (or just stopped) experiment
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Experiment {
+    pub id: u64,
+    pub faults: FaultRequest,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// Requests it has injected faults into so far
+    pub requests_affected: u64,
+}
+
+// =============================================================================
+// EXPERIMENTS
+// =============================================================================
+struct Active {
+    experiment: Experiment,
+    /// memory_mb, held until the experiment ends
+    ballast: Vec<u8>,
+}
+
+/// Owner of the running experiments
+#[derive(Clone, Default)]
+pub struct Chaos {
+    active: Arc<Mutex<Vec<Active>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Chaos {
+    /// Start an experiment; fails if the request is invalid or too many run
+    pub fn start(&self, faults: FaultRequest) -> Result<Experiment> {
+        if let Err(errors) = faults.validate() {
+            bail!(validation::summary(&errors));
+        }
+
+        // Written, not just allocated, so the pages really count as used;
+        // filled before taking the lock, which every API request needs
+        let ballast = vec![0xA5; faults.memory_mb as usize * 1024 * 1024];
+
+        let mut active = self.active.lock().expect("chaos lock poisoned");
+        if active.len() >= MAX_EXPERIMENTS {
+            bail!("{} chaos experiments are already running, stop one first", MAX_EXPERIMENTS);
+        }
+
+        let started_at = Utc::now();
+        let experiment = Experiment {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            ends_at: started_at + chrono::Duration::seconds(faults.duration_secs as i64),
+            started_at,
+            requests_affected: 0,
+            faults,
+        };
+        tracing::warn!(
+            experiment = experiment.id,
+            endpoint = experiment.faults.endpoint.as_deref().unwrap_or("/api/"),
+            duration_secs = experiment.faults.duration_secs,
+            "Chaos experiment started"
+        );
+
+        // Ends by itself; a no-op when it was stopped early
+        let chaos = self.clone();
+        let (id, duration) = (experiment.id, Duration::from_secs(experiment.faults.duration_secs));
+        tokio::spawn(async move {
+            tokio::time::sleep(duration).await;
+            chaos.stop(id);
+        });
+
+        active.push(Active {
+            experiment: experiment.clone(),
+            ballast,
+        });
+        publish_gauges(&active);
+        Ok(experiment)
+    }
+
+    /// Stop an experiment early; returns its final status
+    pub fn stop(&self, id: u64) -> Option<Experiment> {
+        let mut active = self.active.lock().expect("chaos lock poisoned");
+        let index = active.iter().position(|a| a.experiment.id == id)?;
+        let stopped = active.remove(index).experiment;
+        publish_gauges(&active);
+
+        tracing::info!(
+            experiment = id,
+            requests_affected = stopped.requests_affected,
+            "Chaos experiment ended"
+        );
+        Some(stopped)
+    }
+
+    /// Stop every experiment
+    pub fn stop_all(&self) -> Vec<Experiment> {
+        let ids: Vec<u64> = self.list().iter().map(|e| e.id).collect();
+        ids.into_iter().filter_map(|id| self.stop(id)).collect()
+    }
+
+    /// Running experiments, oldest first
+    pub fn list(&self) -> Vec<Experiment> {
+        self.active
+            .lock()
+            .expect("chaos lock poisoned")
+            .iter()
+            .map(|a| a.experiment.clone())
+            .collect()
+    }
+
+    /// Experiments hitting a route; each counts the request as affected
+    fn hitting(&self, route: &str) -> Vec<Experiment> {
+        let mut active = self.active.lock().expect("chaos lock poisoned");
+        if active.is_empty() {
+            return Vec::new();
+        }
+
+        let now = Utc::now();
+        active
+            .iter_mut()
+            .map(|a| &mut a.experiment)
+            .filter(|e| e.ends_at > now)
+            .filter(|e| {
+                e.faults
+                    .endpoint
+                    .as_deref()
+                    .is_none_or(|prefix| route.starts_with(prefix))
+            })
+            .map(|e| {
+                e.requests_affected += 1;
+                e.clone()
+            })
+            .collect()
+    }
+}
+
+fn publish_gauges(active: &[Active]) {
+    let ballast = active.iter().map(|a| a.ballast.len()).sum();
+    metrics::set_chaos_experiments(active.len(), ballast);
+}
+
+// =============================================================================
+// MIDDLEWARE
+// =============================================================================
+/// Inject the faults of every experiment hitting the request's route
+pub async fn inject(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    if !path.starts_with("/api/") || path.starts_with(PATH) {
+        return next.run(request).await;
+    }
+    let Some(route) = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|route| route.as_str().to_string())
+    else {
+        return next.run(request).await;
+    };
+
+    for experiment in state.chaos.hitting(&route) {
+        if let Err(e) = apply(&state, &route, &experiment).await {
+            return e.into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Delay, slow down and maybe fail one request
+async fn apply(state: &AppState, route: &str, experiment: &Experiment) -> Result<(), AppError> {
+    let faults = &experiment.faults;
+
+    if faults.latency_ms > 0 {
+        tokio::time::sleep(Duration::from_millis(faults.latency_ms)).await;
+        metrics::record_chaos_fault(route, "latency");
+    }
+
+    if faults.db_delay_ms > 0 {
+        let start = Instant::now();
+        let result = state
+            .db
+            .chaos_sleep(Duration::from_millis(faults.db_delay_ms))
+            .await;
+        metrics::record_db_query("chaos_sleep", start.elapsed().as_secs_f64());
+        // A pool too busy to lend a connection is the point; the request
+        // itself goes on and finds out
+        if let Err(e) = result {
+            tracing::debug!(error = %e, "Chaos database delay failed");
+        }
+        metrics::record_chaos_fault(route, "db_delay");
+    }
+
+    if faults.cpu_burn_ms > 0 {
+        let burn = Duration::from_millis(faults.cpu_burn_ms);
+        let _ = tokio::task::spawn_blocking(move || burn_cpu(burn)).await;
+        metrics::record_chaos_fault(route, "cpu");
+    }
+
+    if faults.error_rate > 0.0 && rand::random::<f64>() < faults.error_rate {
+        metrics::record_chaos_fault(route, "error");
+        return Err(AppError::FaultInjected {
+            status: StatusCode::from_u16(faults.error_status)
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            reason: format!("Failure injected by chaos experiment {}", experiment.id),
+        });
+    }
+
+    Ok(())
+}
+
+/// Keep one CPU busy for `duration`
+fn burn_cpu(duration: Duration) {
+    let end = Instant::now() + duration;
+    let mut value = 1u64;
+    while Instant::now() < end {
+        for i in 0..10_000 {
+            value = value.wrapping_mul(6364136223846793005).wrapping_add(i);
+        }
+        std::hint::black_box(value);
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn faults(json: &str) -> FaultRequest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_request_defaults_and_validation() {
+        let request = faults(r#"{"latency_ms":200}"#);
+        assert_eq!(request.error_status, 500);
+        assert_eq!(request.duration_secs, 60);
+        assert!(request.validate().is_ok());
+
+        assert!(faults("{}").validate().is_err());
+        assert!(faults(r#"{"error_rate":1.5}"#).validate().is_err());
+        assert!(faults(r#"{"error_rate":0.5,"error_status":404}"#).validate().is_err());
+        assert!(faults(r#"{"latency_ms":10,"endpoint":"/health"}"#).validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_experiments_hit_matching_routes_until_stopped() {
+        let chaos = Chaos::default();
+        let reserve = chaos
+            .start(faults(r#"{"endpoint":"/api/v1/inventory/reserve","latency_ms":5}"#))
+            .unwrap();
+        let everywhere = chaos.start(faults(r#"{"error_rate":0.1}"#)).unwrap();
+
+        assert_eq!(chaos.hitting("/api/v1/inventory/reserve/batch").len(), 2);
+        assert_eq!(chaos.hitting("/api/v1/inventory/:sku").len(), 1);
+
+        let stopped = chaos.stop(reserve.id).unwrap();
+        assert_eq!(stopped.requests_affected, 1);
+        assert!(chaos.stop(reserve.id).is_none());
+        assert_eq!(chaos.hitting("/api/v1/inventory/reserve").len(), 1);
+
+        assert_eq!(chaos.stop_all()[0].id, everywhere.id);
+        assert!(chaos.list().is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+// =============================================================================
+// COMMITMENTS MODULE
+// =============================================================================
+// Two-phase stock commitments for external checkouts that need a firmer
+// contract than reserve + order callback: a coordinator prepares, then
+// either commits or aborts, and stock is never held past a timeout.
+//
+// ENDPOINTS:
+// - POST /api/v1/inventory/prepare   reserve every line (all or nothing)
+//                                    under the caller's transaction_id
+// - POST /api/v1/inventory/commit    confirm the lines: quantity and
+//                                    reserved both drop, the units are sold
+// - POST /api/v1/inventory/abort     release the lines
+//
+// STATES (stock_commitments.state):
+//   prepared ──commit──▶ committed
+//      │
+//      ├──abort──▶ aborted
+//      └──timeout─▶ expired
+// Repeating a step is harmless (`applied: false`); a step the state doesn't
+// allow (commit after abort or timeout, abort after commit) is 409
+// COMMITMENT_CONFLICT. Aborting an ID that was never prepared records it
+// as aborted, so a prepare that arrives late can't hold stock ("presumed
+// abort").
+//
+// TIMEOUTS:
+// Each prepare sets timeout_secs (default 60, max 900). A commit arriving
+// after the timeout expires the commitment instead. A supervised job
+// ("commitment-timeout-sweeper") releases timed-out commitments every
+// COMMITMENT_SWEEP_INTERVAL_SECS (default 5), so stock comes back even if
+// the coordinator never calls again. With several replicas, only the one
+// holding the sweeper's lease sweeps (distributed_lock.rs), and its writes
+// are fenced with the lease's token like the reservation expiry sweeper's.
+//
+// LEARNING NOTES:
+// - The stock itself goes through the usual audit trail and movement ledger
+//   (reserve, confirm, release) under order_id, with the transaction ID in
+//   the audit detail; webhooks and reports see it like any reservation
+// - Commit, abort and expiry take the order's lock, like cancel-by-order. If
+//   the order's units were released some other way meanwhile, commit fails
+//   and abort/expiry release only what is still held.
+// =============================================================================
+
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use std::time::Duration;
+
+use crate::db::Database;
+use crate::distributed_lock::{DistributedLock, StaleLease};
+use crate::fast_reserve::FastReserve;
+use crate::item_cache;
+use crate::list_cache;
+use crate::metrics;
+use crate::models::{CommitmentState, ReserveLine};
+
+/// Most timed-out commitments released per sweep
+const SWEEP_BATCH_SIZE: i64 = 200;
+
+/// What asking for a state does, given the current one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Move the stock and change the state
+    Apply,
+    /// Already there (or released either way): nothing to do
+    Repeat,
+    /// The transaction went the other way
+    Conflict,
+}
+
+/// Step from `current` towards `target`
+pub fn step(current: CommitmentState, target: CommitmentState) -> Step {
+    use CommitmentState::*;
+    match (current, target) {
+        (current, target) if current == target => Step::Repeat,
+        (Prepared, _) => Step::Apply,
+        // Released by abort or timeout, the caller doesn't care which
+        (Aborted, Expired) | (Expired, Aborted) => Step::Repeat,
+        _ => Step::Conflict,
+    }
+}
+
+/// Drop cached copies of SKUs a commitment changed
+pub async fn forget_cached(
+    redis: &ConnectionManager,
+    fast_reserve: Option<&FastReserve>,
+    lines: &[ReserveLine],
+) {
+    for line in lines {
+        item_cache::invalidate(redis, &line.sku).await;
+        // Postgres changed behind the fast path's counters
+        if let Some(fast) = fast_reserve {
+            fast.invalidate(&line.sku).await;
+        }
+    }
+    list_cache::invalidate(redis).await;
+}
+
+/// Release timed-out commitments every `interval`, forever
+pub async fn run_sweeper(
+    db: Database,
+    redis: ConnectionManager,
+    fast_reserve: Option<FastReserve>,
+    interval: Duration,
+) -> Result<()> {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let lock = DistributedLock::periodic(redis.clone(), "commitment-timeout-sweeper", interval);
+
+    loop {
+        ticker.tick().await;
+        let Some(lease) = lock.acquire().await else {
+            continue;
+        };
+
+        for transaction_id in db.due_commitments(SWEEP_BATCH_SIZE).await? {
+            // One bad commitment must not hold up the others
+            let commitment = match db
+                .finish_commitment(&transaction_id, CommitmentState::Expired, Some(lease))
+                .await
+            {
+                Ok(Some(commitment)) if commitment.applied => commitment,
+                Ok(_) => continue,
+                // A newer holder sweeps now; leave the rest to it
+                Err(e) if e.is::<StaleLease>() => {
+                    tracing::warn!(error = %e, "Sweeper lease taken over, stopping the sweep");
+                    if let Some(stale) = e.downcast_ref::<StaleLease>() {
+                        lock.resync(stale).await;
+                    }
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        transaction_id = %transaction_id,
+                        error = %e,
+                        "Failed to expire commitment"
+                    );
+                    continue;
+                }
+            };
+
+            forget_cached(&redis, fast_reserve.as_ref(), &commitment.lines).await;
+            metrics::record_commitment(CommitmentState::Expired);
+            tracing::info!(
+                transaction_id = %commitment.transaction_id,
+                order_id = %commitment.order_id,
+                lines = commitment.lines.len(),
+                fencing_token = lease.token,
+                "Prepared commitment timed out and was released"
+            );
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use CommitmentState::*;
+
+    #[test]
+    fn test_steps() {
+        assert_eq!(step(Prepared, Committed), Step::Apply);
+        assert_eq!(step(Prepared, Aborted), Step::Apply);
+        assert_eq!(step(Prepared, Expired), Step::Apply);
+
+        assert_eq!(step(Committed, Committed), Step::Repeat);
+        assert_eq!(step(Aborted, Aborted), Step::Repeat);
+        assert_eq!(step(Expired, Aborted), Step::Repeat);
+        assert_eq!(step(Aborted, Expired), Step::Repeat);
+
+        assert_eq!(step(Committed, Aborted), Step::Conflict);
+        assert_eq!(step(Committed, Expired), Step::Conflict);
+        assert_eq!(step(Aborted, Committed), Step::Conflict);
+        assert_eq!(step(Expired, Committed), Step::Conflict);
+    }
+}
@@ -0,0 +1,162 @@
+// =============================================================================
+// CONFIG FILE MODULE
+// =============================================================================
+// Optional configuration file under the environment variables, for setups
+// where a long flat list of variables gets unwieldy (pools, Kafka, alerts).
+//
+// HOW:
+// - CONFIG_FILE points at a TOML (.toml) or YAML (.yaml, .yml) file
+// - Every setting in it is the environment variable of the same name,
+//   nested by its underscore-separated parts and in any case:
+//
+//     # inventory.toml
+//     port = 8002
+//     database_url = "postgres://inventory@db/inventory"
+//     [kafka]
+//     brokers = ["kafka-1:9092", "kafka-2:9092"]   # KAFKA_BROKERS
+//     topic = "inventory.stock"                    # KAFKA_TOPIC
+//     [low_stock.alert]
+//     webhook_url = "http://alertmanager:9093/api/v2/alerts"
+//
+//   Lists become comma-separated values, booleans and numbers their text.
+// - A variable set in the environment wins over the file, so a container
+//   can override single settings of a shared file
+//
+// LEARNING NOTES:
+// - Config::from_env() still reads the environment alone; Config::load()
+//   adds the file. Without CONFIG_FILE both behave the same.
+// - Settings in the file that no option reads (typos, settings of another
+//   version) are logged as a warning at startup
+// - RUST_LOG, TOKIO_CONSOLE_ENABLED and LOG_REDACT_FIELDS are read before
+//   the configuration is loaded and only come from the environment
+// =============================================================================
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Settings of a config file, by environment variable name
+pub type FileSettings = BTreeMap<String, String>;
+
+/// Read and flatten a config file; the format follows the extension
+pub fn read(path: &Path) -> Result<FileSettings> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read CONFIG_FILE {}", path.display()))?;
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let value: Value = match extension.as_str() {
+        "toml" => toml::from_str(&text)
+            .with_context(|| format!("CONFIG_FILE {} is not valid TOML", path.display()))?,
+        "yaml" | "yml" => serde_yaml::from_str(&text)
+            .with_context(|| format!("CONFIG_FILE {} is not valid YAML", path.display()))?,
+        _ => anyhow::bail!(
+            "CONFIG_FILE {} must end in .toml, .yaml or .yml",
+            path.display()
+        ),
+    };
+
+    flatten(&value)
+}
+
+/// Turn nested tables into variable names: {"kafka": {"topic": x}} is
+/// KAFKA_TOPIC = x
+pub fn flatten(value: &Value) -> Result<FileSettings> {
+    let mut settings = FileSettings::new();
+    match value {
+        Value::Object(_) => add(&mut settings, "", value)?,
+        // An empty YAML file
+        Value::Null => {}
+        _ => anyhow::bail!("CONFIG_FILE must hold a table of settings"),
+    }
+    Ok(settings)
+}
+
+fn add(settings: &mut FileSettings, prefix: &str, value: &Value) -> Result<()> {
+    let text = match value {
+        Value::Object(table) => {
+            for (key, value) in table {
+                let name = if prefix.is_empty() {
+                    key.to_ascii_uppercase()
+                } else {
+                    format!("{}_{}", prefix, key.to_ascii_uppercase())
+                };
+                add(settings, &name, value)?;
+            }
+            return Ok(());
+        }
+        Value::Null => return Ok(()),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| scalar(prefix, item))
+            .collect::<Result<Vec<_>>>()?
+            .join(","),
+        scalar_value => scalar(prefix, scalar_value)?,
+    };
+
+    if settings.insert(prefix.to_string(), text).is_some() {
+        anyhow::bail!("CONFIG_FILE sets {} twice", prefix);
+    }
+    Ok(())
+}
+
+fn scalar(name: &str, value: &Value) -> Result<String> {
+    match value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Bool(flag) => Ok(flag.to_string()),
+        Value::Number(number) => Ok(number.to_string()),
+        _ => anyhow::bail!("CONFIG_FILE setting {} must be a value or a list of values", name),
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_is_flattened_to_variable_names() {
+        let value: Value = toml::from_str(
+            r#"
+            port = 9000
+            grpc_enabled = false
+            [kafka]
+            brokers = ["kafka-1:9092", "kafka-2:9092"]
+            [low_stock.alert]
+            webhook_url = "http://alertmanager:9093/api/v2/alerts"
+            "#,
+        )
+        .unwrap();
+
+        let settings = flatten(&value).unwrap();
+        assert_eq!(settings["PORT"], "9000");
+        assert_eq!(settings["GRPC_ENABLED"], "false");
+        assert_eq!(settings["KAFKA_BROKERS"], "kafka-1:9092,kafka-2:9092");
+        assert_eq!(
+            settings["LOW_STOCK_ALERT_WEBHOOK_URL"],
+            "http://alertmanager:9093/api/v2/alerts"
+        );
+    }
+
+    #[test]
+    fn test_yaml_and_conflicts() {
+        let value: Value = serde_yaml::from_str("kafka:\n  topic: stock\nlist_cache_ttl_secs: 5\n").unwrap();
+        let settings = flatten(&value).unwrap();
+        assert_eq!(settings["KAFKA_TOPIC"], "stock");
+        assert_eq!(settings["LIST_CACHE_TTL_SECS"], "5");
+
+        // The same variable spelled flat and nested
+        let twice: Value = serde_yaml::from_str("kafka_topic: a\nkafka:\n  topic: b\n").unwrap();
+        assert!(flatten(&twice).is_err());
+
+        let nested_list: Value = serde_yaml::from_str("kafka_brokers: [[a]]\n").unwrap();
+        assert!(flatten(&nested_list).is_err());
+        assert!(flatten(&Value::Null).unwrap().is_empty());
+    }
+}
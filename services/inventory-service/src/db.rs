@@ -11,13 +11,76 @@
 
 use anyhow::{Context, Result};
 use chrono::Utc;
-use sqlx::{postgres::PgPoolOptions, PgPool, Row};
-use uuid::Uuid;
+use sqlx::migrate::Migrator;
+use sqlx::postgres::{PgPoolOptions, PgRow};
+use sqlx::{PgConnection, PgExecutor, PgPool, Postgres, QueryBuilder, Row, Transaction};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
+use crate::cache_warm::{self, WarmStrategy};
+use crate::commitments;
+use crate::db_retry::{self, CircuitBreaker, RetryPolicy};
+use crate::deadline;
+use crate::distributed_lock::Lease;
+use crate::error::AppError;
+use crate::http_client::backoff_delay;
+use crate::ids::IdGenerator;
+use crate::import::{self, ImportAction, ImportRow, ImportTarget, IMPORT_BATCH_SIZE, IMPORT_REFERENCE};
+use crate::metrics;
 use crate::models::{
-    AdjustStockRequest, InventoryItem, LowStockAlert, ReleaseStockRequest,
-    ReservationResponse, ReserveStockRequest,
+    ActiveReservation, AdjustStockRequest, ApiUsage, AttributeSchema, AttributeSchemaRequest, AuditEvent, BulkChange,
+    BulkItemValues, BulkUpdateRequest, BulkUpdateResponse, BumpedHold, CatalogDetailsRequest, ConsumptionPoint,
+    CommitmentState, CreateItemRejection, CreateItemRequest, ExpiredReservation, HoldType,
+    ImportRowResult, ImportRowStatus, InventoryFilter, InventoryItem, InventorySnapshot, InventoryTotals, ItemIdentifier,
+    ItemIdentifierRequest, ItemRevision, LowStockAlert, MAX_BULK_ITEMS, NewAuditEvent,
+    NewStockMovement, OrderCallbackRequest, OrderCallbackResponse, PendingMigration, NamedPoolStats, OutboxBacklog, PoolStats,
+    PrepareCommitmentRequest, PurchaseOrder, PurchaseOrderLine, ReleaseStockRequest, ReleasedStock,
+    ReorderCandidate, ReservationDrift,
+    ReservationFilter, ReservationPolicy, ReservationRecord, ReservationResponse, ReserveBatchRequest, ReserveStockRequest, SkuDelta,
+    SortBy, SortOrder, StockCommitment, StockEvent, StockMovement, StockoutReportRow, TopMoverRow,
+    TransferStockRequest, TransferStockResponse, UpdateItemRequest, UpdateWarehouseRequest, Warehouse,
+    WarehouseStock, WarehouseThreshold, WarehouseTotals, WebhookSubscription, WebhookSubscriptionRequest,
 };
+use crate::reservation_expiry;
+use crate::snapshots::SnapshotRef;
+use crate::versioning;
+
+/// Migrations embedded from migrations/ at compile time
+///
+/// Schema changes go in a new `<version>_<description>.sql` file there;
+/// applied files must never be edited (sqlx checks their checksums).
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Version of the newest migration this build carries
+pub fn latest_migration_version() -> i64 {
+    MIGRATOR.iter().map(|migration| migration.version).max().unwrap_or(0)
+}
+
+// -----------------------------------------------------------------------------
+// DATABASE ERRORS
+// -----------------------------------------------------------------------------
+/// Expected failures of stock operations, returned inside anyhow::Error so
+/// handlers can tell them from query errors (error.rs maps them to
+/// 404 / 409)
+///
+/// The messages are the ones these failures always had, so audit records
+/// and logs read the same.
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    #[error("SKU not found: {0}")]
+    NotFound(String),
+
+    #[error("Insufficient stock. Available: {available}, Requested: {requested}")]
+    InsufficientStock { available: i32, requested: i32 },
+
+    /// The row changed between two reads of the same operation; retrying
+    /// usually succeeds
+    #[error("{0}")]
+    Conflict(String),
+}
 
 // -----------------------------------------------------------------------------
 // DATABASE WRAPPER
@@ -35,6 +98,217 @@ pub struct Database {
     /// SQLx PostgreSQL connection pool
     /// PgPool manages multiple connections automatically
     pool: PgPool,
+
+    /// How concurrent reservations of one SKU are serialized
+    reserve_strategy: ReserveStrategy,
+
+    /// Generator of reservation and movement IDs
+    ids: IdGenerator,
+
+    /// Read replicas (DATABASE_REPLICA_URLS), taken in turn by replica_read
+    replicas: Vec<Replica>,
+
+    /// Which replica the next replica read goes to
+    next_replica: Arc<AtomicUsize>,
+
+    /// How transient primary failures are retried (see db_retry.rs)
+    retry: RetryPolicy,
+
+    /// Fails primary calls fast while the primary is down
+    breaker: Arc<CircuitBreaker>,
+}
+
+// -----------------------------------------------------------------------------
+// READ REPLICAS
+// -----------------------------------------------------------------------------
+// Reads that only serve a response (the list, a single item, low-stock
+// alerts) can go to streaming replicas instead of the primary:
+// - Replicas are used round-robin; a replica that can't answer (down,
+//   out of connections, query cancelled by a recovery conflict) is skipped
+//   for that read, which then runs on the primary and is counted in
+//   db_replica_failovers_total{pool}
+// - Replica pools connect lazily, so a replica that is down at startup
+//   doesn't stop the service
+// - Everything that writes, or reads to decide a write, stays on the
+//   primary: a replica can be behind by its replication lag. The item
+//   cache isn't filled from replicas for the same reason; the list cache
+//   is, as its entries only live LIST_CACHE_TTL_SECS.
+/// A read replica's pool, named for metrics ("replica-1", ...)
+#[derive(Clone)]
+struct Replica {
+    name: String,
+    pool: PgPool,
+}
+
+/// How long a read waits for a replica connection before using the primary
+const REPLICA_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// One pool as seen by the pool sampler (pool_metrics.rs)
+pub struct PoolProbe {
+    /// "primary", or "replica-<n>" in DATABASE_REPLICA_URLS order
+    pub pool: String,
+    /// How long getting a connection took, also when it failed (an
+    /// exhausted pool gives up after its acquire timeout)
+    pub acquire_wait: Duration,
+    pub result: Result<()>,
+    /// Usage after the probe handed its connection back
+    pub stats: PoolStats,
+}
+
+// -----------------------------------------------------------------------------
+// RESERVE STRATEGY
+// -----------------------------------------------------------------------------
+// Ways to keep concurrent reservations of a hot SKU from overselling, so
+// they can be compared side by side in the lab (RESERVE_STRATEGY).
+//
+// - for_update:    SELECT ... FOR UPDATE, check, UPDATE (default). The row
+//                  lock is held for the whole transaction, blocking reads
+//                  with FOR UPDATE, releases and adjustments of that SKU.
+// - advisory:      pg_advisory_xact_lock(hashtext(sku)) serializes only the
+//                  reservations; the row itself is locked just for the UPDATE.
+// - atomic_update: a single conditional UPDATE ... WHERE available >= qty.
+//                  No explicit lock, shortest lock hold time.
+//
+// Reservations are taken from the home warehouse's `stock` row; every
+// strategy locks the product row before it (see change_stock).
+//
+// Sharded reserved-counters were evaluated and left out: until shards are
+// merged, `stock.reserved` under-counts, which breaks the release path
+// (`reserved >= qty`) and the valid_reserved constraint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReserveStrategy {
+    #[default]
+    ForUpdate,
+    Advisory,
+    AtomicUpdate,
+}
+
+impl ReserveStrategy {
+    /// Parse a RESERVE_STRATEGY value
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "for_update" => Ok(Self::ForUpdate),
+            "advisory" => Ok(Self::Advisory),
+            "atomic_update" => Ok(Self::AtomicUpdate),
+            other => anyhow::bail!(
+                "RESERVE_STRATEGY must be for_update, advisory or atomic_update, got '{}'",
+                other
+            ),
+        }
+    }
+
+    /// Name used in logs and metric labels
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ForUpdate => "for_update",
+            Self::Advisory => "advisory",
+            Self::AtomicUpdate => "atomic_update",
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// INVENTORY CURSOR
+// -----------------------------------------------------------------------------
+// Server-side cursor over the whole catalog, for exports that must not load
+// every item into memory (see export.rs). Rows come in batches of FETCH
+// from one read-only transaction, so the export is a consistent snapshot.
+// The transaction holds a pool connection until the cursor is dropped.
+pub struct InventoryCursor {
+    tx: Transaction<'static, Postgres>,
+}
+
+impl InventoryCursor {
+    /// Next `size` items by SKU; empty once the catalog is exhausted
+    pub async fn next_batch(&mut self, size: i64) -> Result<Vec<InventoryItem>> {
+        let items = sqlx::query_as::<_, InventoryItem>(&format!(
+            "FETCH FORWARD {} FROM inventory_export",
+            size.max(1)
+        ))
+        .fetch_all(&mut *self.tx)
+        .await
+        .context("Failed to fetch from the export cursor")?;
+
+        Ok(items)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// AUDIT CLAIM
+// -----------------------------------------------------------------------------
+// A follower's oldest unshipped audit events (see audit.rs), taken under
+// a lease on the follower's row: other replicas skip the follower until
+// the claim ends or the lease runs out. No connection is held while the
+// events are sent. Dropping the claim without finishing gives the lease
+// up and leaves the events queued for the next one.
+pub struct AuditClaim {
+    pool: PgPool,
+    follower: String,
+    token: i64,
+    finished: bool,
+    pub events: Vec<AuditEvent>,
+}
+
+impl AuditClaim {
+    /// Mark the claimed events as shipped and end the claim
+    ///
+    /// Nothing is marked once another replica has claimed the follower
+    /// (this one's lease ran out); it sends the events again.
+    pub async fn finish(mut self) -> Result<()> {
+        self.finished = true;
+        let ids: Vec<i64> = self.events.iter().map(|event| event.id).collect();
+        let kept: Option<String> = sqlx::query_scalar(
+            r#"
+            WITH released AS (
+                UPDATE audit_followers
+                SET claimed_until = NULL
+                WHERE name = $1 AND claim_token = $2
+                RETURNING name
+            ), shipped AS (
+                UPDATE audit_outbox o
+                SET shipped_at = NOW()
+                FROM released r
+                WHERE o.follower = r.name AND o.event_id = ANY($3)
+            )
+            SELECT name FROM released
+            "#,
+        )
+        .bind(&self.follower)
+        .bind(self.token)
+        .bind(&ids)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to mark audit events as shipped")?;
+
+        if kept.is_none() {
+            tracing::warn!(
+                follower = %self.follower,
+                events = ids.len(),
+                "Audit claim was taken over before the batch was marked shipped"
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Drop for AuditClaim {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let pool = self.pool.clone();
+        let follower = std::mem::take(&mut self.follower);
+        let token = self.token;
+        tokio::spawn(async move {
+            let _ = sqlx::query(
+                "UPDATE audit_followers SET claimed_until = NULL WHERE name = $1 AND claim_token = $2",
+            )
+            .bind(&follower)
+            .bind(token)
+            .execute(&pool)
+            .await;
+        });
+    }
 }
 
 impl Database {
@@ -75,78 +349,184 @@ impl Database {
             .await
             .context("Failed to connect to PostgreSQL")?;
 
-        Ok(Self { pool })
+        Ok(Self::with_pool(pool))
+    }
+
+    /// Set up the pool without connecting; connections are opened when
+    /// a query first needs one (for --in-memory, where Postgres may be
+    /// missing)
+    pub fn connect_lazy(database_url: &str) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(10)
+            .min_connections(0)
+            .acquire_timeout(std::time::Duration::from_secs(5))
+            .idle_timeout(std::time::Duration::from_secs(300))
+            .connect_lazy(database_url)
+            .context("Invalid DATABASE_URL")?;
+        Ok(Self::with_pool(pool))
+    }
+
+    fn with_pool(pool: PgPool) -> Self {
+        Self {
+            pool,
+            reserve_strategy: ReserveStrategy::default(),
+            ids: IdGenerator::default(),
+            replicas: Vec::new(),
+            next_replica: Arc::new(AtomicUsize::new(0)),
+            retry: RetryPolicy::default(),
+            breaker: Arc::new(CircuitBreaker::new(&RetryPolicy::default())),
+        }
+    }
+
+    /// Send list, item and low-stock reads to these replicas (see READ
+    /// REPLICAS); nothing connects until the first read
+    pub fn with_replicas(mut self, urls: &[String]) -> Result<Self> {
+        self.replicas = urls
+            .iter()
+            .enumerate()
+            .map(|(i, url)| {
+                let pool = PgPoolOptions::new()
+                    .max_connections(10)
+                    .min_connections(0)
+                    .acquire_timeout(REPLICA_ACQUIRE_TIMEOUT)
+                    .idle_timeout(Duration::from_secs(300))
+                    .connect_lazy(url)
+                    .with_context(|| format!("Invalid replica URL #{} in DATABASE_REPLICA_URLS", i + 1))?;
+                Ok(Replica {
+                    name: format!("replica-{}", i + 1),
+                    pool,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(self)
+    }
+
+    /// Number of read replicas
+    pub fn replica_count(&self) -> usize {
+        self.replicas.len()
+    }
+
+    /// Run a read on the next replica; on the primary when there is none or
+    /// the replica can't answer
+    async fn replica_read<T, F, Fut>(&self, read: F) -> Result<T>
+    where
+        F: Fn(PgPool) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        if !self.replicas.is_empty() {
+            let turn = self.next_replica.fetch_add(1, Ordering::Relaxed);
+            let replica = &self.replicas[turn % self.replicas.len()];
+            match read(replica.pool.clone()).await {
+                Err(e) if replica_unavailable(&e) => {
+                    metrics::record_replica_failover(&replica.name);
+                    tracing::warn!(
+                        replica = %replica.name,
+                        error = %e,
+                        "Replica read failed, using the primary"
+                    );
+                }
+                result => return Ok(result?),
+            }
+        }
+        self.on_primary("read", read).await
+    }
+
+    /// Run a call on the primary through the circuit breaker, retrying
+    /// transient failures (see db_retry.rs)
+    ///
+    /// Only for calls that are safe to run twice: reads, and starting a
+    /// transaction. Fails with 503 SERVICE_UNAVAILABLE while the breaker is
+    /// open or when the retries run out.
+    async fn on_primary<T, F, Fut>(&self, operation: &'static str, run: F) -> Result<T>
+    where
+        F: Fn(PgPool) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let mut attempt = 0;
+        loop {
+            if let Err(wait) = self.breaker.allow() {
+                return Err(AppError::ServiceUnavailable {
+                    reason: "Database unavailable, try again later".to_string(),
+                    retry_after_secs: wait.as_secs().max(1),
+                }
+                .into());
+            }
+            match run(self.pool.clone()).await {
+                Err(e) if db_retry::is_failure(&e) => {
+                    self.breaker.record_failure();
+                    if db_retry::is_transient(&e) && attempt < self.retry.max_retries {
+                        metrics::record_db_retry(operation);
+                        tokio::time::sleep(backoff_delay(self.retry.backoff_base, attempt)).await;
+                        attempt += 1;
+                        continue;
+                    }
+                    tracing::warn!(operation, attempts = attempt + 1, error = %e, "Database unavailable");
+                    return Err(AppError::ServiceUnavailable {
+                        reason: "Database temporarily unavailable".to_string(),
+                        retry_after_secs: 1,
+                    }
+                    .into());
+                }
+                // The database answered, even if with an error
+                result => {
+                    self.breaker.record_success();
+                    return Ok(result?);
+                }
+            }
+        }
+    }
+
+    /// Start a transaction on the primary, retrying transient failures
+    ///
+    /// Inside a request, its statements are held to what is left of the
+    /// request's deadline (apply_deadline).
+    async fn begin(&self) -> Result<Transaction<'static, Postgres>> {
+        let mut tx = self.on_primary("begin", |pool| async move { pool.begin().await }).await?;
+        apply_deadline(&mut tx).await?;
+        Ok(tx)
+    }
+
+    /// Retry transient failures and trip the breaker as configured
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self.breaker = Arc::new(CircuitBreaker::new(&policy));
+        self
+    }
+
+    /// Use a different reserve strategy (see ReserveStrategy)
+    pub fn with_reserve_strategy(mut self, strategy: ReserveStrategy) -> Self {
+        self.reserve_strategy = strategy;
+        self
+    }
+
+    /// The configured reserve strategy
+    pub fn reserve_strategy(&self) -> ReserveStrategy {
+        self.reserve_strategy
+    }
+
+    /// Use a different ID generator (see ids.rs)
+    pub fn with_id_generator(mut self, ids: IdGenerator) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// The configured ID generator
+    pub fn ids(&self) -> &IdGenerator {
+        &self.ids
     }
 
     // -------------------------------------------------------------------------
     // MIGRATIONS
     // -------------------------------------------------------------------------
-    /// Run database migrations to create/update tables
+    /// Apply pending migrations from migrations/, then seed sample data
     ///
-    /// This creates the inventory table if it doesn't exist and seeds
-    /// initial data for testing.
+    /// Applied versions are recorded in `_sqlx_migrations`. sqlx holds an
+    /// advisory lock while migrating, so pods starting together take turns.
     pub async fn run_migrations(&self) -> Result<()> {
-        // Create the inventory table
-        // IF NOT EXISTS ensures this is idempotent (safe to run multiple times)
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS inventory (
-                -- Primary key: UUID for global uniqueness
-                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-                
-                -- SKU must be unique (can't have duplicate products)
-                sku VARCHAR(50) UNIQUE NOT NULL,
-                
-                -- Product name for display
-                name VARCHAR(255) NOT NULL,
-                
-                -- Current stock quantity
-                quantity INTEGER NOT NULL DEFAULT 0,
-                
-                -- Reserved stock (for pending orders)
-                reserved INTEGER NOT NULL DEFAULT 0,
-                
-                -- Warehouse location code
-                warehouse VARCHAR(50) NOT NULL DEFAULT 'DEFAULT',
-                
-                -- Alert threshold
-                low_stock_threshold INTEGER NOT NULL DEFAULT 10,
-                
-                -- Timestamps for auditing
-                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-                
-                -- Ensure quantity is never negative
-                CONSTRAINT positive_quantity CHECK (quantity >= 0),
-                
-                -- Ensure reserved doesn't exceed quantity
-                CONSTRAINT valid_reserved CHECK (reserved >= 0 AND reserved <= quantity)
-            )
-            "#,
-        )
-        .execute(&self.pool)
-        .await
-        .context("Failed to create inventory table")?;
-
-        // Create index on SKU for fast lookups
-        sqlx::query(
-            r#"
-            CREATE INDEX IF NOT EXISTS idx_inventory_sku ON inventory(sku)
-            "#,
-        )
-        .execute(&self.pool)
-        .await
-        .context("Failed to create SKU index")?;
-
-        // Create index on warehouse for filtering
-        sqlx::query(
-            r#"
-            CREATE INDEX IF NOT EXISTS idx_inventory_warehouse ON inventory(warehouse)
-            "#,
-        )
-        .execute(&self.pool)
-        .await
-        .context("Failed to create warehouse index")?;
+        MIGRATOR
+            .run(&self.pool)
+            .await
+            .context("Failed to run database migrations")?;
 
         // Seed sample data if table is empty
         self.seed_sample_data().await?;
@@ -154,10 +534,38 @@ impl Database {
         Ok(())
     }
 
+    /// Migrations of this build not yet applied to the database, and the
+    /// highest applied version (None if the database was never migrated)
+    pub async fn pending_migrations(&self) -> Result<(Option<i64>, Vec<PendingMigration>)> {
+        let exists: bool =
+            sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+                .fetch_one(&self.pool)
+                .await?;
+        let applied: Vec<i64> = if exists {
+            sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success ORDER BY version")
+                .fetch_all(&self.pool)
+                .await
+                .context("Failed to list applied migrations")?
+        } else {
+            Vec::new()
+        };
+
+        let pending = MIGRATOR
+            .iter()
+            .filter(|migration| !applied.contains(&migration.version))
+            .map(|migration| PendingMigration {
+                version: migration.version,
+                description: migration.description.to_string(),
+            })
+            .collect();
+
+        Ok((applied.last().copied(), pending))
+    }
+
     /// Seed sample inventory data for testing
     async fn seed_sample_data(&self) -> Result<()> {
         // Check if data already exists
-        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM inventory")
+        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM products")
             .fetch_one(&self.pool)
             .await?;
 
@@ -182,7 +590,7 @@ impl Database {
         for (sku, name, quantity, warehouse, threshold) in sample_items {
             sqlx::query(
                 r#"
-                INSERT INTO inventory (sku, name, quantity, warehouse, low_stock_threshold)
+                INSERT INTO products (sku, name, quantity, warehouse, low_stock_threshold)
                 VALUES ($1, $2, $3, $4, $5)
                 ON CONFLICT (sku) DO NOTHING
                 "#,
@@ -208,195 +616,3781 @@ impl Database {
     /// # Arguments
     /// * `page` - Page number (1-indexed)
     /// * `per_page` - Items per page
+    /// * `filter` - Which items to include (see InventoryFilter)
+    /// * `sort_by`, `order` - Sort column and direction; ties are broken by
+    ///   SKU so pages stay stable
     ///
     /// # Returns
     /// Tuple of (items, total_count)
-    pub async fn list_items(&self, page: i32, per_page: i32) -> Result<(Vec<InventoryItem>, i64)> {
+    pub async fn list_items(
+        &self,
+        page: i32,
+        per_page: i32,
+        filter: &InventoryFilter<'_>,
+        sort_by: SortBy,
+        order: SortOrder,
+    ) -> Result<(Vec<InventoryItem>, i64)> {
         // Calculate offset for pagination
         // Page 1 = offset 0, Page 2 = offset per_page, etc.
         let offset = (page - 1) * per_page;
 
-        // Get paginated items
-        let items = sqlx::query_as::<_, InventoryItem>(
-            r#"
-            SELECT id, sku, name, quantity, reserved, warehouse, 
-                   low_stock_threshold, created_at, updated_at
-            FROM inventory
-            ORDER BY sku ASC
-            LIMIT $1 OFFSET $2
-            "#,
-        )
-        .bind(per_page)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await
-        .context("Failed to fetch inventory items")?;
+        // Page and count come from the same pool, so they agree
+        let (items, total) = self
+            .replica_read(|pool| async move {
+                // Get paginated items
+                let mut query = QueryBuilder::new(
+                    r#"
+                    SELECT i.id, i.sku, i.name, i.quantity, i.reserved, i.warehouse,
+                           i.low_stock_threshold, i.max_per_order, i.max_reserved_pct, i.threshold_manual, i.version,
+                           i.attributes, i.image_url, i.spec_url, i.unit_price::float8 AS unit_price,
+                           i.unit_cost::float8 AS unit_cost, (i.quantity * i.unit_cost)::float8 AS stock_value,
+                           i.created_at, i.updated_at
+                    FROM products i
+                    "#,
+                );
+                push_inventory_filter(&mut query, filter);
+                // Column and direction come from enums, never from the request
+                query.push(format_args!(" ORDER BY {} {}", sort_by.column(), order.keyword()));
+                if sort_by != SortBy::Sku {
+                    query.push(", i.sku ASC");
+                }
+                query.push(" LIMIT ").push_bind(per_page);
+                query.push(" OFFSET ").push_bind(offset);
+                let items = query.build_query_as::<InventoryItem>().fetch_all(&pool).await?;
+
+                // Get total count for pagination metadata
+                let mut count = QueryBuilder::new("SELECT COUNT(*) FROM products i");
+                push_inventory_filter(&mut count, filter);
+                let total: i64 = count.build_query_scalar().fetch_one(&pool).await?;
+
+                Ok((items, total))
+            })
+            .await
+            .context("Failed to fetch inventory items")?;
+
+        Ok((items, total))
+    }
 
-        // Get total count for pagination metadata
-        let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM inventory")
+    /// Number of SKUs in the catalog
+    pub async fn count_items(&self) -> Result<i64> {
+        let count = sqlx::query_scalar("SELECT COUNT(*) FROM products")
             .fetch_one(&self.pool)
             .await
             .context("Failed to count inventory items")?;
 
-        Ok((items, total.0))
+        Ok(count)
     }
 
-    /// Get a single inventory item by SKU
-    pub async fn get_by_sku(&self, sku: &str) -> Result<Option<InventoryItem>> {
-        let item = sqlx::query_as::<_, InventoryItem>(
+    /// Open a cursor over every item, ordered by SKU
+    pub async fn inventory_cursor(&self) -> Result<InventoryCursor> {
+        let mut tx = self.begin().await?;
+
+        sqlx::query("SET TRANSACTION READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
             r#"
+            DECLARE inventory_export NO SCROLL CURSOR FOR
             SELECT id, sku, name, quantity, reserved, warehouse,
-                   low_stock_threshold, created_at, updated_at
-            FROM inventory
-            WHERE sku = $1
+                   low_stock_threshold, max_per_order, max_reserved_pct, threshold_manual, version,
+                   attributes, image_url, spec_url, unit_price::float8 AS unit_price,
+                   unit_cost::float8 AS unit_cost, (quantity * unit_cost)::float8 AS stock_value,
+                   created_at, updated_at
+            FROM products
+            ORDER BY sku
             "#,
         )
-        .bind(sku)
-        .fetch_optional(&self.pool)
+        .execute(&mut *tx)
         .await
-        .context("Failed to fetch inventory item")?;
+        .context("Failed to open the export cursor")?;
 
-        Ok(item)
+        Ok(InventoryCursor { tx })
     }
 
-    /// Get all items with low stock
-    pub async fn get_low_stock_items(&self) -> Result<Vec<LowStockAlert>> {
-        // Query items where available stock (quantity - reserved) < threshold
-        let rows = sqlx::query(
-            r#"
-            SELECT sku, name, quantity - reserved as available, 
-                   low_stock_threshold as threshold, warehouse
-            FROM inventory
-            WHERE (quantity - reserved) < low_stock_threshold
-            ORDER BY (quantity - reserved) ASC
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await
-        .context("Failed to fetch low stock items")?;
-
-        // Map rows to LowStockAlert structs
-        let alerts = rows
-            .iter()
-            .map(|row| LowStockAlert {
-                sku: row.get("sku"),
-                name: row.get("name"),
-                available: row.get("available"),
-                threshold: row.get("threshold"),
-                warehouse: row.get("warehouse"),
-            })
-            .collect();
-
-        Ok(alerts)
-    }
+    /// Add an item to the catalog, unless that would exceed `hard_limit`
+    ///
+    /// Creates are serialized on an advisory lock while counting, so the
+    /// limit holds under concurrency. Initial stock is recorded as an
+    /// adjustment in the audit trail and the movement ledger.
+    ///
+    /// # Returns
+    /// - `Ok((item, skus))` with the catalog size after the insert
+    /// - `Err(rejection)` for a duplicate SKU or a full catalog
+    pub async fn create_item(
+        &self,
+        req: &CreateItemRequest,
+        hard_limit: Option<i64>,
+    ) -> Result<std::result::Result<(InventoryItem, i64), CreateItemRejection>> {
+        let mut tx = self.begin().await?;
 
-    // -------------------------------------------------------------------------
-    // WRITE OPERATIONS
-    // -------------------------------------------------------------------------
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('catalog-quota'))")
+            .execute(&mut *tx)
+            .await?;
 
-    /// Reserve stock for an order
-    ///
-    /// This atomically checks availability and reserves stock.
-    /// Uses a transaction to ensure consistency.
-    pub async fn reserve_stock(&self, req: &ReserveStockRequest) -> Result<ReservationResponse> {
-        // Start a transaction
-        // All operations inside will be atomic (all succeed or all fail)
-        let mut tx = self.pool.begin().await?;
+        let skus: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM products")
+            .fetch_one(&mut *tx)
+            .await?;
+        if hard_limit.is_some_and(|limit| skus >= limit) {
+            return Ok(Err(CreateItemRejection::QuotaExceeded { skus }));
+        }
 
-        // Lock the row for update to prevent race conditions
-        // FOR UPDATE prevents other transactions from modifying this row
         let item = sqlx::query_as::<_, InventoryItem>(
             r#"
-            SELECT id, sku, name, quantity, reserved, warehouse,
-                   low_stock_threshold, created_at, updated_at
-            FROM inventory
-            WHERE sku = $1
-            FOR UPDATE
+            INSERT INTO products (sku, name, quantity, warehouse, low_stock_threshold)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (sku) DO NOTHING
+            RETURNING id, sku, name, quantity, reserved, warehouse,
+                      low_stock_threshold, max_per_order, max_reserved_pct, threshold_manual, version,
+                      attributes, image_url, spec_url, unit_price::float8 AS unit_price,
+                      unit_cost::float8 AS unit_cost, (quantity * unit_cost)::float8 AS stock_value,
+                      created_at, updated_at
             "#,
         )
         .bind(&req.sku)
+        .bind(&req.name)
+        .bind(req.quantity)
+        .bind(&req.warehouse)
+        .bind(req.low_stock_threshold)
         .fetch_optional(&mut *tx)
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("SKU not found: {}", req.sku))?;
+        .await
+        .context("Failed to create inventory item")?;
 
-        // Check if enough stock is available
-        let available = item.quantity - item.reserved;
-        if available < req.quantity {
-            return Err(anyhow::anyhow!(
-                "Insufficient stock. Available: {}, Requested: {}",
-                available,
-                req.quantity
-            ));
-        }
+        let Some(item) = item else {
+            return Ok(Err(CreateItemRejection::DuplicateSku));
+        };
 
-        // Update reserved count
-        sqlx::query(
-            r#"
-            UPDATE inventory
-            SET reserved = reserved + $1, updated_at = NOW()
-            WHERE sku = $2
-            "#,
-        )
-        .bind(req.quantity)
-        .bind(&req.sku)
-        .execute(&mut *tx)
-        .await?;
+        if item.quantity > 0 {
+            insert_audit_event(
+                &mut *tx,
+                &NewAuditEvent {
+                    action: "adjust",
+                    outcome: "success",
+                    sku: &item.sku,
+                    quantity: item.quantity,
+                    reference: Some("initial stock"),
+                    detail: None,
+                    channel: None,
+                },
+            )
+            .await?;
+            insert_stock_movement(
+                &mut *tx,
+                &NewStockMovement {
+                    movement_id: &self.ids.next_id(),
+                    sku: &item.sku,
+                    movement_type: "adjust",
+                    quantity_delta: item.quantity,
+                    reserved_delta: 0,
+                    reference: Some("initial stock"),
+                    warehouse: None,
+                },
+            )
+            .await?;
+        }
 
-        // Commit the transaction
         tx.commit().await?;
 
-        // Return reservation confirmation
-        Ok(ReservationResponse {
-            reservation_id: Uuid::new_v4(),
-            sku: req.sku.clone(),
-            quantity: req.quantity,
-            created_at: Utc::now(),
-            expires_at: Some(Utc::now() + chrono::Duration::hours(24)),
-        })
+        Ok(Ok((item, skus + 1)))
     }
 
-    /// Release previously reserved stock
-    pub async fn release_stock(&self, req: &ReleaseStockRequest) -> Result<()> {
-        let result = sqlx::query(
-            r#"
-            UPDATE inventory
-            SET reserved = GREATEST(reserved - $1, 0), updated_at = NOW()
-            WHERE sku = $2 AND reserved >= $1
-            "#,
-        )
-        .bind(req.quantity)
-        .bind(&req.sku)
-        .execute(&self.pool)
-        .await?;
-
-        if result.rows_affected() == 0 {
-            return Err(anyhow::anyhow!(
-                "Failed to release stock. SKU not found or insufficient reserved quantity."
-            ));
+    /// Change an item's name, home warehouse and/or low-stock threshold
+    ///
+    /// Unset fields stay as they are. A threshold is pinned (manual), like
+    /// set_low_stock_threshold. A new home warehouse is where new stock and
+    /// reservations go from then on; the stock stays where it is (move it
+    /// with transfer_stock). An actual change gets an "update" audit event
+    /// listing the old and new values.
+    ///
+    /// # Returns
+    /// - `Ok(None)` when there is no such SKU
+    /// - `Err(AppError::VersionMismatch)` when `expected_version` is stale
+    pub async fn update_item(
+        &self,
+        sku: &str,
+        req: &UpdateItemRequest,
+        expected_version: Option<i64>,
+    ) -> Result<Option<InventoryItem>> {
+        let mut tx = self.begin().await?;
+        if !lock_at_version(&mut tx, sku, expected_version).await? {
+            return Ok(None);
         }
+        let Some(before) = fetch_item(&mut *tx, sku).await? else {
+            return Ok(None);
+        };
+        tag_revisions(&mut tx, "update", req.reason.as_deref()).await?;
 
-        Ok(())
-    }
-
-    /// Adjust stock quantity (for manual corrections, receiving shipments, etc.)
-    pub async fn adjust_stock(&self, req: &AdjustStockRequest) -> Result<InventoryItem> {
         let item = sqlx::query_as::<_, InventoryItem>(
             r#"
-            UPDATE inventory
-            SET quantity = GREATEST(quantity + $1, 0), updated_at = NOW()
-            WHERE sku = $2
+            UPDATE products
+            SET name = COALESCE($1, name),
+                warehouse = COALESCE($2, warehouse),
+                low_stock_threshold = COALESCE($3::int, low_stock_threshold),
+                threshold_manual = threshold_manual OR $3::int IS NOT NULL,
+                updated_at = NOW()
+            WHERE sku = $4
             RETURNING id, sku, name, quantity, reserved, warehouse,
-                      low_stock_threshold, created_at, updated_at
+                      low_stock_threshold, max_per_order, max_reserved_pct, threshold_manual, version,
+                      attributes, image_url, spec_url, unit_price::float8 AS unit_price,
+                      unit_cost::float8 AS unit_cost, (quantity * unit_cost)::float8 AS stock_value,
+                      created_at, updated_at
             "#,
         )
-        .bind(req.delta)
-        .bind(&req.sku)
-        .fetch_optional(&self.pool)
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("SKU not found: {}", req.sku))?;
-
-        Ok(item)
-    }
+        .bind(req.name.as_deref())
+        .bind(req.warehouse.as_deref())
+        .bind(req.low_stock_threshold)
+        .bind(sku)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to update item")?;
+
+        let detail = describe_update(&before, &item);
+        if !detail.is_empty() {
+            insert_audit_event(
+                &mut *tx,
+                &NewAuditEvent {
+                    action: "update",
+                    outcome: "success",
+                    sku,
+                    quantity: 0,
+                    reference: req.reason.as_deref(),
+                    detail: Some(&detail),
+                    channel: None,
+                },
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(Some(item))
+    }
+
+    /// Upsert validated CSV rows, in batches, in one transaction
+    ///
+    /// Runs behind the same advisory lock as create_item, so new SKUs are
+    /// counted against `hard_limit` like single creates. Each batch locks
+    /// the items it targets, plans every row with `import::plan` and writes
+    /// the creates and the updates with one statement each. Quantity
+    /// changes get an "adjust" audit event and movement, like
+    /// adjust_stock; a threshold given in the file is marked manual.
+    ///
+    /// # Returns
+    /// The result of every row and the catalog size after the import (or,
+    /// with `dry_run`, what it would be; nothing is committed)
+    pub async fn import_items(
+        &self,
+        rows: &[ImportRow],
+        hard_limit: Option<i64>,
+        dry_run: bool,
+    ) -> Result<(Vec<ImportRowResult>, i64)> {
+        let mut tx = self.begin().await?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('catalog-quota'))")
+            .execute(&mut *tx)
+            .await?;
+        let mut skus: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM products")
+            .fetch_one(&mut *tx)
+            .await?;
+        tag_revisions(&mut tx, "import", Some(IMPORT_REFERENCE)).await?;
+
+        let mut results = Vec::with_capacity(rows.len());
+        for batch in rows.chunks(IMPORT_BATCH_SIZE) {
+            let batch_skus: Vec<&str> = batch.iter().map(|row| row.sku.as_str()).collect();
+            let existing: HashMap<String, ImportTarget> = sqlx::query_as::<_, ImportTarget>(
+                r#"
+                SELECT i.sku, i.name, i.quantity, s.reserved, i.warehouse, i.low_stock_threshold,
+                       i.quantity - s.quantity AS elsewhere
+                FROM products i
+                JOIN stock s ON s.sku = i.sku AND s.warehouse = i.warehouse
+                WHERE i.sku = ANY($1)
+                FOR UPDATE OF i
+                "#,
+            )
+            .bind(&batch_skus)
+            .fetch_all(&mut *tx)
+            .await
+            .context("Failed to look up items to import")?
+            .into_iter()
+            .map(|item| (item.sku.clone(), item))
+            .collect();
+
+            let mut creates = Vec::new();
+            let mut updates = Vec::new();
+            for row in batch {
+                let planned = import::plan(row, existing.get(&row.sku));
+                let result = match planned {
+                    Ok(ImportAction::Create) if hard_limit.is_some_and(|limit| skus >= limit) => {
+                        ImportRowResult::error(
+                            row.line,
+                            &row.sku,
+                            format!(
+                                "Catalog quota exceeded: the catalog holds {} SKUs, the limit is {} (CATALOG_HARD_LIMIT)",
+                                skus,
+                                hard_limit.unwrap_or(skus)
+                            ),
+                        )
+                    }
+                    Ok(ImportAction::Create) => {
+                        skus += 1;
+                        creates.push(row.to_create_request());
+                        ImportRowResult::ok(row.line, &row.sku, ImportRowStatus::Created)
+                    }
+                    Ok(ImportAction::Update { quantity_delta }) => {
+                        updates.push((row, quantity_delta));
+                        ImportRowResult::ok(row.line, &row.sku, ImportRowStatus::Updated)
+                    }
+                    Ok(ImportAction::Unchanged) => {
+                        ImportRowResult::ok(row.line, &row.sku, ImportRowStatus::Unchanged)
+                    }
+                    Err(error) => ImportRowResult::error(row.line, &row.sku, error),
+                };
+                results.push(result);
+            }
+
+            if !creates.is_empty() {
+                sqlx::query(
+                    r#"
+                    INSERT INTO products (sku, name, quantity, warehouse, low_stock_threshold)
+                    SELECT * FROM UNNEST($1::text[], $2::text[], $3::int[], $4::text[], $5::int[])
+                    "#,
+                )
+                .bind(creates.iter().map(|c| c.sku.as_str()).collect::<Vec<_>>())
+                .bind(creates.iter().map(|c| c.name.as_str()).collect::<Vec<_>>())
+                .bind(creates.iter().map(|c| c.quantity).collect::<Vec<_>>())
+                .bind(creates.iter().map(|c| c.warehouse.as_str()).collect::<Vec<_>>())
+                .bind(creates.iter().map(|c| c.low_stock_threshold).collect::<Vec<_>>())
+                .execute(&mut *tx)
+                .await
+                .context("Failed to insert imported items")?;
+            }
+
+            if !updates.is_empty() {
+                sqlx::query(
+                    r#"
+                    UPDATE products i
+                    SET name = c.name,
+                        low_stock_threshold = COALESCE(c.threshold, i.low_stock_threshold),
+                        threshold_manual = i.threshold_manual OR c.threshold IS NOT NULL,
+                        updated_at = NOW()
+                    FROM UNNEST($1::text[], $2::text[], $3::int[])
+                        AS c(sku, name, threshold)
+                    WHERE i.sku = c.sku
+                    "#,
+                )
+                .bind(updates.iter().map(|(row, _)| row.sku.as_str()).collect::<Vec<_>>())
+                .bind(updates.iter().map(|(row, _)| row.name.as_str()).collect::<Vec<_>>())
+                .bind(updates.iter().map(|(row, _)| row.threshold).collect::<Vec<_>>())
+                .execute(&mut *tx)
+                .await
+                .context("Failed to update imported items")?;
+            }
+
+            // New quantities apply at the home warehouse, like adjust_stock
+            for (row, delta) in updates.iter().filter(|(_, delta)| *delta != 0) {
+                if change_stock(&mut tx, &row.sku, None, *delta, 0).await?.is_none() {
+                    return Err(anyhow::anyhow!("Failed to set the quantity of {}", row.sku));
+                }
+            }
+
+            let initial_stock = creates
+                .iter()
+                .map(|c| (c.sku.as_str(), c.quantity, "initial stock"));
+            let adjustments = updates
+                .iter()
+                .map(|(row, delta)| (row.sku.as_str(), *delta, IMPORT_REFERENCE));
+            for (sku, delta, reference) in initial_stock.chain(adjustments) {
+                if delta == 0 {
+                    continue;
+                }
+                insert_audit_event(
+                    &mut *tx,
+                    &NewAuditEvent {
+                        action: "adjust",
+                        outcome: "success",
+                        sku,
+                        quantity: delta,
+                        reference: Some(reference),
+                        detail: None,
+                        channel: None,
+                    },
+                )
+                .await?;
+                insert_stock_movement(
+                    &mut *tx,
+                    &NewStockMovement {
+                        movement_id: &self.ids.next_id(),
+                        sku,
+                        movement_type: "adjust",
+                        quantity_delta: delta,
+                        reserved_delta: 0,
+                        reference: Some(reference),
+                        warehouse: None,
+                    },
+                )
+                .await?;
+            }
+        }
+
+        commit_unless_dry_run(tx, dry_run).await?;
+
+        Ok((results, skus))
+    }
+
+    /// Remove an item and its per-warehouse thresholds
+    ///
+    /// Identifiers go with it (ON DELETE CASCADE); the audit trail and the
+    /// movement ledger keep their history. Returns false if no such SKU.
+    pub async fn delete_item(&self, sku: &str) -> Result<bool> {
+        let mut tx = self.begin().await?;
+
+        sqlx::query("DELETE FROM warehouse_thresholds WHERE sku = $1")
+            .bind(sku)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete warehouse thresholds")?;
+
+        let result = sqlx::query("DELETE FROM products WHERE sku = $1")
+            .bind(sku)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete inventory item")?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Get a single inventory item by SKU
+    pub async fn get_by_sku(&self, sku: &str) -> Result<Option<InventoryItem>> {
+        let item = self
+            .on_primary("read", |pool| async move {
+                sqlx::query_as::<_, InventoryItem>(
+                    r#"
+                    SELECT id, sku, name, quantity, reserved, warehouse,
+                           low_stock_threshold, max_per_order, max_reserved_pct, threshold_manual, version,
+                           attributes, image_url, spec_url, unit_price::float8 AS unit_price,
+                           unit_cost::float8 AS unit_cost, (quantity * unit_cost)::float8 AS stock_value,
+                           created_at, updated_at
+                    FROM products
+                    WHERE sku = $1
+                    "#,
+                )
+                .bind(sku)
+                .fetch_optional(&pool)
+                .await
+            })
+            .await
+            .context("Failed to fetch inventory item")?;
+
+        Ok(item)
+    }
+
+    /// Get a single item like get_by_sku, from a read replica when there
+    /// is one
+    ///
+    /// For reads that only serve a response: the item can be as old as the
+    /// replica's lag, so anything that decides a write uses get_by_sku.
+    pub async fn read_item(&self, sku: &str) -> Result<Option<InventoryItem>> {
+        let item = self
+            .replica_read(|pool| async move {
+                sqlx::query_as::<_, InventoryItem>(
+                    r#"
+                    SELECT id, sku, name, quantity, reserved, warehouse,
+                           low_stock_threshold, max_per_order, max_reserved_pct, threshold_manual, version,
+                           attributes, image_url, spec_url, unit_price::float8 AS unit_price,
+                           unit_cost::float8 AS unit_cost, (quantity * unit_cost)::float8 AS stock_value,
+                           created_at, updated_at
+                    FROM products
+                    WHERE sku = $1
+                    "#,
+                )
+                .bind(sku)
+                .fetch_optional(&pool)
+                .await
+            })
+            .await
+            .context("Failed to fetch inventory item")?;
+
+        Ok(item)
+    }
+
+    /// Items to pre-load into the cache at startup
+    ///
+    /// # Arguments
+    /// * `strategy` - Most recently updated, or most reserved lately
+    /// * `limit` - Maximum items returned
+    pub async fn warm_cache_candidates(
+        &self,
+        strategy: WarmStrategy,
+        limit: i64,
+    ) -> Result<Vec<InventoryItem>> {
+        let sql = match strategy {
+            WarmStrategy::Recent => {
+                r#"
+                SELECT id, sku, name, quantity, reserved, warehouse,
+                       low_stock_threshold, max_per_order, max_reserved_pct, threshold_manual, version,
+                       attributes, image_url, spec_url, unit_price::float8 AS unit_price,
+                       unit_cost::float8 AS unit_cost, (quantity * unit_cost)::float8 AS stock_value,
+                       created_at, updated_at
+                FROM products
+                ORDER BY updated_at DESC
+                LIMIT $1
+                "#
+            }
+            WarmStrategy::Popular => {
+                r#"
+                SELECT i.id, i.sku, i.name, i.quantity, i.reserved, i.warehouse,
+                       i.low_stock_threshold, i.max_per_order, i.max_reserved_pct,
+                       i.threshold_manual, i.version, i.attributes, i.image_url, i.spec_url,
+                       i.unit_price::float8 AS unit_price, i.unit_cost::float8 AS unit_cost,
+                       (i.quantity * i.unit_cost)::float8 AS stock_value, i.created_at, i.updated_at
+                FROM products i
+                LEFT JOIN (
+                    SELECT sku, COUNT(*) AS reservations
+                    FROM audit_events
+                    WHERE action = 'reserve'
+                      AND outcome = 'success'
+                      AND occurred_at >= NOW() - make_interval(days => $2)
+                    GROUP BY sku
+                ) a ON a.sku = i.sku
+                ORDER BY COALESCE(a.reservations, 0) DESC, i.updated_at DESC
+                LIMIT $1
+                "#
+            }
+        };
+
+        let mut query = sqlx::query_as::<_, InventoryItem>(sql).bind(limit);
+        if strategy == WarmStrategy::Popular {
+            query = query.bind(cache_warm::POPULAR_WINDOW_DAYS);
+        }
+
+        let items = query
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to fetch cache warming candidates")?;
+
+        Ok(items)
+    }
+
+    /// Get all items with low stock (from a read replica when there is one)
+    pub async fn get_low_stock_items(&self) -> Result<Vec<LowStockAlert>> {
+        // Query items where available stock (quantity - reserved) < threshold,
+        // using the (sku, warehouse) threshold when one is set
+        let rows = self
+            .replica_read(|pool| async move {
+                sqlx::query(
+                    r#"
+                    SELECT i.sku, i.name, i.quantity - i.reserved as available,
+                           COALESCE(t.threshold, i.low_stock_threshold) as threshold,
+                           i.warehouse
+                    FROM products i
+                    LEFT JOIN warehouse_thresholds t
+                           ON t.sku = i.sku AND t.warehouse = i.warehouse
+                    WHERE (i.quantity - i.reserved) < COALESCE(t.threshold, i.low_stock_threshold)
+                    ORDER BY (i.quantity - i.reserved) ASC
+                    "#,
+                )
+                .fetch_all(&pool)
+                .await
+            })
+            .await
+            .context("Failed to fetch low stock items")?;
+
+        // Map rows to LowStockAlert structs
+        let alerts = rows
+            .iter()
+            .map(|row| LowStockAlert {
+                sku: row.get("sku"),
+                name: row.get("name"),
+                available: row.get("available"),
+                threshold: row.get("threshold"),
+                warehouse: row.get("warehouse"),
+            })
+            .collect();
+
+        Ok(alerts)
+    }
+
+    /// SKUs with no available stock (quantity - reserved <= 0)
+    pub async fn out_of_stock_skus(&self) -> Result<Vec<String>> {
+        let skus = sqlx::query_scalar(
+            r#"
+            SELECT sku FROM products
+            WHERE quantity - reserved <= 0
+            ORDER BY sku
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch out of stock SKUs")?;
+
+        Ok(skus)
+    }
+
+    /// Value at cost of the stock held at each warehouse. Items without a
+    /// unit cost count for nothing.
+    pub async fn stock_value_by_warehouse(&self) -> Result<Vec<(String, f64)>> {
+        let values = sqlx::query_as(
+            r#"
+            WITH held AS (
+                SELECT s.warehouse, s.quantity, p.unit_cost
+                FROM stock s
+                JOIN products p ON p.sku = s.sku
+            )
+            SELECT warehouse, COALESCE(SUM(quantity * unit_cost), 0)::float8 AS value
+            FROM held
+            GROUP BY warehouse
+            ORDER BY warehouse
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to sum stock value")?;
+
+        Ok(values)
+    }
+
+    // -------------------------------------------------------------------------
+    // WRITE OPERATIONS
+    // -------------------------------------------------------------------------
+
+    /// Reserve stock for an order
+    ///
+    /// This atomically checks availability and reserves stock.
+    /// Uses a transaction to ensure consistency. An order reserves a SKU
+    /// once: while it holds the SKU, a repeat gets the first reservation
+    /// back with `replayed` set.
+    pub async fn reserve_stock(&self, req: &ReserveStockRequest) -> Result<ReservationResponse> {
+        self.reserve_stock_as(req, self.ids.next_id()).await
+    }
+
+    /// Reserve stock under an ID handed out earlier (fast-path write-behind)
+    pub async fn reserve_stock_as(
+        &self,
+        req: &ReserveStockRequest,
+        reservation_id: String,
+    ) -> Result<ReservationResponse> {
+        // Start a transaction
+        // All operations inside will be atomic (all succeed or all fail)
+        let mut tx = self.begin().await?;
+
+        let reservation = self.reserve_line(&mut tx, req, reservation_id).await?;
+
+        // Commit the transaction
+        tx.commit().await?;
+
+        Ok(reservation)
+    }
+
+    /// Reserve several SKUs for one order, all or nothing
+    ///
+    /// Lines are reserved in SKU order, so two batches sharing SKUs lock
+    /// them in the same order and can't deadlock. The first line that
+    /// can't be reserved rolls the whole batch back.
+    pub async fn reserve_batch(
+        &self,
+        req: &ReserveBatchRequest,
+    ) -> Result<Vec<ReservationResponse>> {
+        let mut tx = self.begin().await?;
+
+        let reservations = self.reserve_lines(&mut tx, req).await?;
+
+        tx.commit().await?;
+
+        Ok(reservations)
+    }
+
+    /// Reserve every line of a batch inside an open transaction, in SKU
+    /// order
+    async fn reserve_lines(
+        &self,
+        tx: &mut PgConnection,
+        req: &ReserveBatchRequest,
+    ) -> Result<Vec<ReservationResponse>> {
+        let mut lines: Vec<ReserveStockRequest> = req
+            .items
+            .iter()
+            .map(|line| ReserveStockRequest {
+                sku: line.sku.clone(),
+                quantity: line.quantity,
+                order_id: req.order_id.clone(),
+                channel: req.channel,
+                hold: req.hold,
+                priority: req.priority,
+                quota: req.quota.clone(),
+            })
+            .collect();
+        lines.sort_by(|a, b| a.sku.cmp(&b.sku));
+
+        let mut reservations = Vec::with_capacity(lines.len());
+        for line in &lines {
+            match self.reserve_line(&mut *tx, line, self.ids.next_id()).await {
+                Ok(reservation) => reservations.push(reservation),
+                // Policy violations keep their type (422); name the line
+                // for everything else
+                Err(e) if e.is::<AppError>() => return Err(e),
+                Err(e) => return Err(anyhow::anyhow!("{}: {:#}", line.sku, e)),
+            }
+        }
+
+        Ok(reservations)
+    }
+
+    /// Check availability and reserve one line inside an open transaction
+    async fn reserve_line(
+        &self,
+        tx: &mut PgConnection,
+        req: &ReserveStockRequest,
+        reservation_id: String,
+    ) -> Result<ReservationResponse> {
+        // A repeat of a reservation the order still holds (a retry) gets
+        // the first one back and reserves nothing
+        let created_at = Utc::now();
+        let expires_at = created_at + reservation_expiry::reservation_ttl();
+        if let Some(existing) =
+            claim_reservation(&mut *tx, req, &reservation_id, created_at, expires_at).await?
+        {
+            return Ok(existing);
+        }
+
+        // Make room first if the request may bump soft holds; that locks
+        // the row, whatever the strategy
+        let bumped = if req.priority > 0 {
+            self.bump_soft_holds(&mut *tx, req).await?
+        } else {
+            Vec::new()
+        };
+
+        // Check availability and reserve at the home warehouse, using the
+        // configured strategy to serialize concurrent reservations of the
+        // same SKU
+        let warehouse = match self.reserve_strategy {
+            ReserveStrategy::ForUpdate | ReserveStrategy::Advisory => {
+                let item = if self.reserve_strategy == ReserveStrategy::ForUpdate {
+                    // Lock the row for update to prevent race conditions
+                    // FOR UPDATE prevents other transactions from modifying this row
+                    sqlx::query_as::<_, InventoryItem>(
+                        r#"
+                        SELECT id, sku, name, quantity, reserved, warehouse,
+                               low_stock_threshold, max_per_order, max_reserved_pct, threshold_manual, version,
+                               attributes, image_url, spec_url, unit_price::float8 AS unit_price,
+                               unit_cost::float8 AS unit_cost, (quantity * unit_cost)::float8 AS stock_value,
+                               created_at, updated_at
+                        FROM products
+                        WHERE sku = $1
+                        FOR UPDATE
+                        "#,
+                    )
+                    .bind(&req.sku)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                } else {
+                    // Transaction-scoped advisory lock keyed by the SKU: other
+                    // reservations of this SKU wait here, without holding the
+                    // row lock that reads, releases and adjustments also need
+                    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+                        .bind(&req.sku)
+                        .execute(&mut *tx)
+                        .await?;
+                    fetch_item(&mut *tx, &req.sku).await?
+                };
+                let item = item.ok_or_else(|| DbError::NotFound(req.sku.clone()))?;
+
+                check_reservable(&item, home_available(&mut *tx, &req.sku).await?, req.quantity)?;
+
+                change_stock(&mut *tx, &req.sku, None, 0, req.quantity)
+                    .await?
+                    .ok_or_else(|| DbError::Conflict("Insufficient stock (changed concurrently), please retry".to_string()))?
+            }
+            ReserveStrategy::AtomicUpdate => {
+                // One statement checks and reserves; the rows are locked only
+                // from the UPDATE to commit instead of the whole transaction
+                match change_stock(&mut *tx, &req.sku, None, 0, req.quantity).await? {
+                    // Policy is checked against the product as it was
+                    // before; failing here rolls the UPDATE back
+                    Some(warehouse) => {
+                        let after = fetch_item(&mut *tx, &req.sku)
+                            .await?
+                            .ok_or_else(|| DbError::NotFound(req.sku.clone()))?;
+                        let before = InventoryItem {
+                            reserved: after.reserved - req.quantity,
+                            ..after
+                        };
+                        if let Err(violation) = before.check_reservation_policy(req.quantity) {
+                            return Err(AppError::ReservationLimit(violation).into());
+                        }
+                        warehouse
+                    }
+                    // Nothing updated: report why (missing SKU or no stock)
+                    None => {
+                        let item = fetch_item(&mut *tx, &req.sku)
+                            .await?
+                            .ok_or_else(|| DbError::NotFound(req.sku.clone()))?;
+                        check_reservable(&item, home_available(&mut *tx, &req.sku).await?, req.quantity)?;
+                        return Err(DbError::Conflict(
+                            "Insufficient stock (changed concurrently), please retry".to_string(),
+                        )
+                        .into());
+                    }
+                }
+            }
+        };
+
+        // Units go back to the warehouse they were reserved at, wherever
+        // home is by then
+        sqlx::query("UPDATE reservations SET warehouse = $3 WHERE order_id = $1 AND sku = $2")
+            .bind(&req.order_id)
+            .bind(&req.sku)
+            .bind(&warehouse)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to record reservation warehouse")?;
+
+        // The SKU is locked now, so the client's other reservations of it
+        // can't change under the check
+        check_client_share(&mut *tx, req).await?;
+
+        // Hard or soft, as requested
+        record_hold(&mut *tx, req).await?;
+
+        // Audit record is written in the same transaction as the change
+        insert_audit_event(
+            &mut *tx,
+            &NewAuditEvent {
+                action: "reserve",
+                outcome: "success",
+                sku: &req.sku,
+                quantity: req.quantity,
+                reference: Some(&req.order_id),
+                detail: None,
+                channel: Some(req.channel.as_str()),
+            },
+        )
+        .await?;
+        insert_stock_movement(
+            &mut *tx,
+            &NewStockMovement {
+                movement_id: &reservation_id,
+                sku: &req.sku,
+                movement_type: "reserve",
+                quantity_delta: 0,
+                reserved_delta: req.quantity,
+                reference: Some(&req.order_id),
+                warehouse: Some(&warehouse),
+            },
+        )
+        .await?;
+
+        // Return reservation confirmation
+        Ok(ReservationResponse {
+            reservation_id,
+            sku: req.sku.clone(),
+            quantity: req.quantity,
+            created_at,
+            expires_at: Some(expires_at),
+            bumped,
+            replayed: false,
+        })
+    }
+
+    /// Bump soft holds of a lower priority until `req` fits at the home
+    /// warehouse
+    ///
+    /// The SKU's row is locked first, so the room made here is still there
+    /// when the reservation lands. Only holdings reserved at the home
+    /// warehouse make room there. Holdings go lowest priority first and,
+    /// within a priority, most recently reserved first. Nothing is bumped
+    /// unless bumping makes enough room; the reservation then fails as
+    /// usual. Orders being cancelled or expired right now are skipped.
+    async fn bump_soft_holds(
+        &self,
+        tx: &mut PgConnection,
+        req: &ReserveStockRequest,
+    ) -> Result<Vec<BumpedHold>> {
+        if self.reserve_strategy == ReserveStrategy::Advisory {
+            sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+                .bind(&req.sku)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let available: Option<i32> = sqlx::query_scalar(
+            r#"
+            SELECT s.quantity - s.reserved
+            FROM products p
+            JOIN stock s ON s.sku = p.sku AND s.warehouse = p.warehouse
+            WHERE p.sku = $1
+            FOR UPDATE OF p
+            "#,
+        )
+        .bind(&req.sku)
+        .fetch_optional(&mut *tx)
+        .await?;
+        // A missing SKU is reported by the regular path
+        let shortfall = match available {
+            Some(available) if available < req.quantity => req.quantity - available,
+            _ => return Ok(Vec::new()),
+        };
+
+        let candidates = sqlx::query_as::<_, BumpedHold>(
+            r#"
+            SELECT h.order_id, r.held_quantity AS quantity, h.priority
+            FROM reservation_holds h
+            JOIN reservations r ON r.order_id = h.order_id AND r.sku = h.sku
+            JOIN products p ON p.sku = h.sku
+            WHERE h.sku = $1 AND h.priority < $2 AND h.order_id <> $3 AND r.held_quantity > 0
+              AND COALESCE(r.warehouse, p.warehouse) = p.warehouse
+            ORDER BY h.priority ASC, r.created_at DESC
+            "#,
+        )
+        .bind(&req.sku)
+        .bind(req.priority)
+        .bind(&req.order_id)
+        .fetch_all(&mut *tx)
+        .await
+        .context("Failed to look up soft holds")?;
+
+        // Same per-order lock as cancel-by-order and expiry, so a holding
+        // is never released twice
+        let mut victims = Vec::new();
+        let mut freed = 0;
+        for candidate in candidates {
+            if freed >= shortfall {
+                break;
+            }
+            let locked: bool =
+                sqlx::query_scalar("SELECT pg_try_advisory_xact_lock(hashtext('cancel-order:' || $1))")
+                    .bind(&candidate.order_id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+            if locked {
+                freed += candidate.quantity;
+                victims.push(candidate);
+            }
+        }
+        if freed < shortfall {
+            return Ok(Vec::new());
+        }
+
+        let detail = format!("bumped by {}", req.order_id);
+        for victim in &victims {
+            let Some(warehouse) = give_back_held(&mut *tx, &victim.order_id, &req.sku, victim.quantity, false).await?
+            else {
+                return Err(anyhow::anyhow!(
+                    "Failed to bump {} x {} of order {}: insufficient reserved quantity \
+                     (see /api/v1/admin/reconcile-reservations)",
+                    victim.quantity,
+                    req.sku,
+                    victim.order_id
+                ));
+            };
+
+            insert_audit_event(
+                &mut *tx,
+                &NewAuditEvent {
+                    action: "bump",
+                    outcome: "success",
+                    sku: &req.sku,
+                    quantity: victim.quantity,
+                    reference: Some(&victim.order_id),
+                    detail: Some(&detail),
+                    channel: None,
+                },
+            )
+            .await?;
+            insert_stock_movement(
+                &mut *tx,
+                &NewStockMovement {
+                    movement_id: &self.ids.next_id(),
+                    sku: &req.sku,
+                    movement_type: "bump",
+                    quantity_delta: 0,
+                    reserved_delta: -victim.quantity,
+                    reference: Some(&victim.order_id),
+                    warehouse: Some(&warehouse),
+                },
+            )
+            .await?;
+            take_from_holding(&mut *tx, &victim.order_id, &req.sku, victim.quantity, "released").await?;
+
+            sqlx::query("DELETE FROM reservation_holds WHERE order_id = $1 AND sku = $2")
+                .bind(&victim.order_id)
+                .bind(&req.sku)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        Ok(victims)
+    }
+
+    /// Release previously reserved stock
+    pub async fn release_stock(&self, req: &ReleaseStockRequest) -> Result<()> {
+        let mut tx = self.begin().await?;
+
+        let warehouse = give_back_held(&mut tx, &req.order_id, &req.sku, req.quantity, false)
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!("Failed to release stock. SKU not found or insufficient reserved quantity.")
+            })?;
+        take_from_holding(&mut tx, &req.order_id, &req.sku, req.quantity, "released").await?;
+
+        insert_audit_event(
+            &mut *tx,
+            &NewAuditEvent {
+                action: "release",
+                outcome: "success",
+                sku: &req.sku,
+                quantity: req.quantity,
+                reference: Some(&req.order_id),
+                detail: None,
+                channel: None,
+            },
+        )
+        .await?;
+        insert_stock_movement(
+            &mut *tx,
+            &NewStockMovement {
+                movement_id: &self.ids.next_id(),
+                sku: &req.sku,
+                movement_type: "release",
+                quantity_delta: 0,
+                reserved_delta: -req.quantity,
+                reference: Some(&req.order_id),
+                warehouse: Some(&warehouse),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Release everything an order still holds, in one transaction
+    ///
+    /// What an order holds comes from its rows in `reservations`. Either
+    /// every SKU is released or none is. Cancelling twice is harmless, the second call
+    /// finds nothing held.
+    pub async fn cancel_order_reservations(&self, order_id: &str) -> Result<Vec<ReleasedStock>> {
+        let mut tx = self.begin().await?;
+
+        // Concurrent cancels of the same order would both see the units as
+        // held; serialize them on the order ID
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('cancel-order:' || $1))")
+            .bind(order_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let held = sqlx::query_as::<_, ReleasedStock>(
+            r#"
+            SELECT sku, held_quantity AS quantity
+            FROM reservations
+            WHERE order_id = $1 AND held_quantity > 0
+            ORDER BY sku
+            FOR UPDATE
+            "#,
+        )
+        .bind(order_id)
+        .fetch_all(&mut *tx)
+        .await
+        .context("Failed to look up the order's reservations")?;
+
+        for line in &held {
+            let Some(warehouse) = give_back_held(&mut tx, order_id, &line.sku, line.quantity, false).await? else {
+                return Err(anyhow::anyhow!(
+                    "Failed to release {} x {} for order {}. SKU not found or insufficient \
+                     reserved quantity (see /api/v1/admin/reconcile-reservations).",
+                    line.quantity,
+                    line.sku,
+                    order_id
+                ));
+            };
+            take_from_holding(&mut tx, order_id, &line.sku, line.quantity, "released").await?;
+
+            insert_audit_event(
+                &mut *tx,
+                &NewAuditEvent {
+                    action: "release",
+                    outcome: "success",
+                    sku: &line.sku,
+                    quantity: line.quantity,
+                    reference: Some(order_id),
+                    detail: Some("cancel-by-order"),
+                    channel: None,
+                },
+            )
+            .await?;
+            insert_stock_movement(
+                &mut *tx,
+                &NewStockMovement {
+                    movement_id: &self.ids.next_id(),
+                    sku: &line.sku,
+                    movement_type: "release",
+                    quantity_delta: 0,
+                    reserved_delta: -line.quantity,
+                    reference: Some(order_id),
+                    warehouse: Some(&warehouse),
+                },
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(held)
+    }
+
+    /// Holdings still held, newest reserve first, optionally for one SKU
+    /// and/or one order
+    ///
+    /// Holdings are the reservations with units still held.
+    pub async fn active_reservations(
+        &self,
+        sku: Option<&str>,
+        order_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ActiveReservation>> {
+        let reservations = sqlx::query_as::<_, ActiveReservation>(
+            r#"
+            SELECT order_id, sku, held_quantity AS quantity, created_at AS last_reserved_at
+            FROM reservations
+            WHERE held_quantity > 0
+              AND ($1::text IS NULL OR sku = $1)
+              AND ($2::text IS NULL OR order_id = $2)
+            ORDER BY created_at DESC, order_id, sku
+            LIMIT $3
+            "#,
+        )
+        .bind(sku)
+        .bind(order_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to look up active reservations")?;
+
+        Ok(reservations)
+    }
+
+    /// Reservations matching `filter`, newest first, with their status
+    ///
+    /// Only the latest reservation of each (order, SKU) is kept. A holding
+    /// past its expiry stays active until the sweeper releases it.
+    ///
+    /// # Returns
+    /// Tuple of (reservations, total_count)
+    pub async fn list_reservations(
+        &self,
+        filter: &ReservationFilter<'_>,
+        page: i32,
+        per_page: i32,
+    ) -> Result<(Vec<ReservationRecord>, i64)> {
+        let offset = (page - 1) * per_page;
+
+        let reservations = sqlx::query_as::<_, ReservationRecord>(&format!(
+            r#"
+            {}
+            SELECT reservation_id, order_id, sku, quantity, held, status,
+                   created_at, expires_at, ended_at
+            FROM listed
+            WHERE ($5::text IS NULL OR status = $5)
+            ORDER BY created_at DESC, order_id, sku
+            LIMIT $6 OFFSET $7
+            "#,
+            LISTED_RESERVATIONS
+        ))
+        .bind(filter.sku)
+        .bind(filter.order_id)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.status)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch reservations")?;
+
+        let total: i64 = sqlx::query_scalar(&format!(
+            r#"
+            {}
+            SELECT COUNT(*) FROM listed
+            WHERE ($5::text IS NULL OR status = $5)
+            "#,
+            LISTED_RESERVATIONS
+        ))
+        .bind(filter.sku)
+        .bind(filter.order_id)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.status)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count reservations")?;
+
+        Ok((reservations, total))
+    }
+
+    /// Holdings reserved before `cutoff`, oldest first
+    pub async fn expired_reservations(
+        &self,
+        cutoff: chrono::DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<ExpiredReservation>> {
+        let expired = sqlx::query_as::<_, ExpiredReservation>(
+            r#"
+            SELECT order_id, sku, held_quantity AS quantity
+            FROM reservations
+            WHERE held_quantity > 0 AND created_at < $1
+            ORDER BY created_at
+            LIMIT $2
+            "#,
+        )
+        .bind(cutoff)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to look up expired reservations")?;
+
+        Ok(expired)
+    }
+
+    /// Release one expired holding
+    ///
+    /// The holding is looked up again under the order's lock, so a release,
+    /// confirmation or new reserve since `expired_reservations` is taken
+    /// into account. Refused with StaleLease once the sweeper's `lease` has
+    /// been taken over.
+    ///
+    /// # Returns
+    /// - `Some(quantity)` released, or `None` if it is no longer held or
+    ///   no longer expired
+    pub async fn expire_reservation(
+        &self,
+        holding: &ExpiredReservation,
+        cutoff: chrono::DateTime<Utc>,
+        lease: Lease,
+    ) -> Result<Option<i32>> {
+        let mut tx = self.begin().await?;
+        check_fence(&mut tx, lease).await?;
+
+        // Same lock as cancel-by-order
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('cancel-order:' || $1))")
+            .bind(&holding.order_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let held: Option<i32> = sqlx::query_scalar(
+            r#"
+            SELECT held_quantity
+            FROM reservations
+            WHERE order_id = $1 AND sku = $2 AND held_quantity > 0 AND created_at < $3
+            FOR UPDATE
+            "#,
+        )
+        .bind(&holding.order_id)
+        .bind(&holding.sku)
+        .bind(cutoff)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(quantity) = held else {
+            return Ok(None);
+        };
+
+        let Some(warehouse) = give_back_held(&mut tx, &holding.order_id, &holding.sku, quantity, false).await?
+        else {
+            return Err(anyhow::anyhow!(
+                "SKU not found or insufficient reserved quantity \
+                 (see /api/v1/admin/reconcile-reservations)"
+            ));
+        };
+        take_from_holding(&mut tx, &holding.order_id, &holding.sku, quantity, "expired").await?;
+
+        insert_audit_event(
+            &mut *tx,
+            &NewAuditEvent {
+                action: "release",
+                outcome: "success",
+                sku: &holding.sku,
+                quantity,
+                reference: Some(&holding.order_id),
+                detail: Some("expired"),
+                channel: None,
+            },
+        )
+        .await?;
+        insert_stock_movement(
+            &mut *tx,
+            &NewStockMovement {
+                movement_id: &self.ids.next_id(),
+                sku: &holding.sku,
+                movement_type: "release",
+                quantity_delta: 0,
+                reserved_delta: -quantity,
+                reference: Some(&holding.order_id),
+                warehouse: Some(&warehouse),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(quantity))
+    }
+
+    /// Adjust stock quantity (for manual corrections, receiving shipments, etc.)
+    ///
+    /// With `dry_run` everything runs and is rolled back, so the item comes
+    /// back as it would be.
+    pub async fn adjust_stock(
+        &self,
+        req: &AdjustStockRequest,
+        dry_run: bool,
+    ) -> Result<InventoryItem> {
+        let mut tx = self.begin().await?;
+
+        // Adjustments apply at the home warehouse, so its stock is clamped
+        // at 0; the ledger records what was applied
+        let version: i64 = sqlx::query_scalar("SELECT version FROM products WHERE sku = $1 FOR UPDATE")
+            .bind(&req.sku)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| DbError::NotFound(req.sku.clone()))?;
+        versioning::check(req.expected_version, version)?;
+        let (at_home, reserved) = home_stock(&mut *tx, &req.sku).await?.unwrap_or_default();
+
+        // Units reserved at home can't be taken away
+        let applied = req.delta.max(-at_home);
+        let warehouse = change_stock(&mut tx, &req.sku, None, applied, 0).await?.ok_or_else(|| {
+            DbError::InsufficientStock {
+                available: at_home - reserved,
+                requested: -req.delta,
+            }
+        })?;
+        let item = fetch_item(&mut *tx, &req.sku)
+            .await?
+            .ok_or_else(|| DbError::NotFound(req.sku.clone()))?;
+
+        insert_audit_event(
+            &mut *tx,
+            &NewAuditEvent {
+                action: "adjust",
+                outcome: "success",
+                sku: &req.sku,
+                quantity: req.delta,
+                reference: Some(&req.reason),
+                detail: None,
+                channel: None,
+            },
+        )
+        .await?;
+        insert_stock_movement(
+            &mut *tx,
+            &NewStockMovement {
+                movement_id: &self.ids.next_id(),
+                sku: &req.sku,
+                movement_type: "adjust",
+                quantity_delta: applied,
+                reserved_delta: 0,
+                reference: Some(&req.reason),
+                warehouse: Some(&warehouse),
+            },
+        )
+        .await?;
+
+        commit_unless_dry_run(tx, dry_run).await?;
+
+        Ok(item)
+    }
+
+    /// Move stock of a SKU from one warehouse to another
+    ///
+    /// The SKU's total doesn't change; units move from one stock row to
+    /// another. A warehouse can only give away what isn't reserved there,
+    /// and a warehouse other than home drops its row once empty. The ledger
+    /// gets a transfer_out and a transfer_in movement, which cancel out.
+    ///
+    /// With `dry_run` the transfer is rolled back after computing the result.
+    ///
+    /// # Errors
+    /// `AppError::InsufficientStock` when the source holds fewer available
+    /// units than requested
+    pub async fn transfer_stock(
+        &self,
+        req: &TransferStockRequest,
+        dry_run: bool,
+    ) -> Result<TransferStockResponse> {
+        let mut tx = self.begin().await?;
+
+        // Locking the product row serialises transfers, reservations and
+        // adjustments of the SKU
+        sqlx::query("SELECT 1 FROM products WHERE sku = $1 FOR UPDATE")
+            .bind(&req.sku)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| DbError::NotFound(req.sku.clone()))?;
+
+        let available: i32 = sqlx::query_scalar(
+            "SELECT quantity - reserved FROM stock WHERE sku = $1 AND warehouse = $2",
+        )
+        .bind(&req.sku)
+        .bind(&req.from_warehouse)
+        .fetch_optional(&mut *tx)
+        .await?
+        .unwrap_or(0);
+        if available < req.quantity {
+            return Err(DbError::InsufficientStock {
+                available,
+                requested: req.quantity,
+            }
+            .into());
+        }
+
+        change_stock(&mut tx, &req.sku, Some(&req.from_warehouse), -req.quantity, 0)
+            .await?
+            .context("Failed to take stock from warehouse")?;
+        sqlx::query(
+            r#"
+            INSERT INTO stock (sku, warehouse, quantity)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (sku, warehouse)
+            DO UPDATE SET quantity = stock.quantity + EXCLUDED.quantity, updated_at = NOW()
+            "#,
+        )
+        .bind(&req.sku)
+        .bind(&req.to_warehouse)
+        .bind(req.quantity)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to add stock to warehouse")?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM stock s
+            USING products p
+            WHERE s.sku = $1 AND p.sku = s.sku AND s.warehouse <> p.warehouse AND s.quantity = 0
+            "#,
+        )
+        .bind(&req.sku)
+        .execute(&mut *tx)
+        .await?;
+
+        let detail = format!("{} -> {}", req.from_warehouse, req.to_warehouse);
+        insert_audit_event(
+            &mut *tx,
+            &NewAuditEvent {
+                action: "transfer",
+                outcome: "success",
+                sku: &req.sku,
+                quantity: req.quantity,
+                reference: req.reason.as_deref(),
+                detail: Some(&detail),
+                channel: None,
+            },
+        )
+        .await?;
+        for (movement_type, quantity_delta, warehouse) in [
+            ("transfer_out", -req.quantity, &req.from_warehouse),
+            ("transfer_in", req.quantity, &req.to_warehouse),
+        ] {
+            insert_stock_movement(
+                &mut *tx,
+                &NewStockMovement {
+                    movement_id: &self.ids.next_id(),
+                    sku: &req.sku,
+                    movement_type,
+                    quantity_delta,
+                    reserved_delta: 0,
+                    reference: req.reason.as_deref(),
+                    warehouse: Some(warehouse),
+                },
+            )
+            .await?;
+        }
+
+        let warehouses = stock_by_warehouse(&mut *tx, &req.sku).await?;
+
+        commit_unless_dry_run(tx, dry_run).await?;
+
+        Ok(TransferStockResponse {
+            sku: req.sku.clone(),
+            from_warehouse: req.from_warehouse.clone(),
+            to_warehouse: req.to_warehouse.clone(),
+            quantity: req.quantity,
+            warehouses,
+        })
+    }
+
+    /// Apply a patch to every item matching a filter, in one transaction
+    ///
+    /// Matching rows are locked, the new values computed with
+    /// `BulkPatch::apply` and written in one statement; every changed item
+    /// gets a "bulk_update" audit event. A dry run does the same writes and
+    /// rolls back. Setting a threshold marks it manual, like
+    /// PUT /api/v1/inventory/:sku/threshold. A new home warehouse moves no
+    /// stock, as in update_item.
+    ///
+    /// # Errors
+    /// `AppError::BadRequest` when more than MAX_BULK_ITEMS items match
+    pub async fn bulk_update(&self, req: &BulkUpdateRequest) -> Result<BulkUpdateResponse> {
+        let mut tx = self.begin().await?;
+
+        let category = req
+            .filter
+            .category
+            .as_deref()
+            .filter(|c| !c.is_empty())
+            .map(|category| serde_json::json!({ "category": category }));
+        let filter = InventoryFilter {
+            warehouse: req.filter.warehouse.as_deref().filter(|w| !w.is_empty()),
+            sku_prefix: req.filter.sku_prefix.as_deref().filter(|p| !p.is_empty()),
+            attributes: category.as_ref(),
+            ..Default::default()
+        };
+
+        let mut query = QueryBuilder::new(
+            r#"
+            SELECT i.sku, i.low_stock_threshold, i.unit_price::float8 AS unit_price, i.warehouse
+            FROM products i
+            "#,
+        );
+        push_inventory_filter(&mut query, &filter);
+        query
+            .push(" ORDER BY i.sku LIMIT ")
+            .push_bind(MAX_BULK_ITEMS as i64 + 1)
+            .push(" FOR UPDATE");
+        let matched = query
+            .build_query_as::<BulkItemValues>()
+            .fetch_all(&mut *tx)
+            .await
+            .context("Failed to look up items to update")?;
+
+        if matched.len() > MAX_BULK_ITEMS {
+            return Err(AppError::BadRequest(format!(
+                "More than {} items match; narrow the filter",
+                MAX_BULK_ITEMS
+            ))
+            .into());
+        }
+
+        let changes: Vec<BulkChange> = matched
+            .iter()
+            .map(|before| BulkChange {
+                after: req.patch.apply(before),
+                before: before.clone(),
+            })
+            .filter(|change| change.after != change.before)
+            .collect();
+
+        if !changes.is_empty() {
+            let skus: Vec<&str> = changes.iter().map(|c| c.after.sku.as_str()).collect();
+            let thresholds: Vec<i32> = changes.iter().map(|c| c.after.low_stock_threshold).collect();
+            let prices: Vec<Option<f64>> = changes.iter().map(|c| c.after.unit_price).collect();
+            let warehouses: Vec<&str> =
+                changes.iter().map(|c| c.after.warehouse.as_str()).collect();
+            let threshold_set =
+                req.patch.low_stock_threshold.is_some() || req.patch.low_stock_threshold_pct.is_some();
+
+            tag_revisions(&mut tx, "bulk_update", Some(&req.reason)).await?;
+            sqlx::query(
+                r#"
+                UPDATE products i
+                SET low_stock_threshold = c.threshold,
+                    threshold_manual = i.threshold_manual OR $5,
+                    unit_price = c.price::numeric,
+                    warehouse = c.warehouse,
+                    updated_at = NOW()
+                FROM UNNEST($1::text[], $2::int[], $3::float8[], $4::text[])
+                    AS c(sku, threshold, price, warehouse)
+                WHERE i.sku = c.sku
+                "#,
+            )
+            .bind(&skus)
+            .bind(&thresholds)
+            .bind(&prices)
+            .bind(&warehouses)
+            .bind(threshold_set)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to apply bulk update")?;
+
+            for change in &changes {
+                insert_audit_event(
+                    &mut *tx,
+                    &NewAuditEvent {
+                        action: "bulk_update",
+                        outcome: "success",
+                        sku: &change.after.sku,
+                        quantity: 0,
+                        reference: Some(&req.reason),
+                        detail: Some(&describe_change(change)),
+                        channel: None,
+                    },
+                )
+                .await?;
+            }
+        }
+        commit_unless_dry_run(tx, req.dry_run).await?;
+
+        Ok(BulkUpdateResponse {
+            dry_run: req.dry_run,
+            matched: matched.len(),
+            changes,
+        })
+    }
+
+    // -------------------------------------------------------------------------
+    // TWO-PHASE COMMITMENTS
+    // -------------------------------------------------------------------------
+    // State lives in stock_commitments; the stock moves through the usual
+    // reserve / confirm / release records (see commitments.rs).
+
+    /// Prepare a commitment: reserve every line and record it, all or nothing
+    ///
+    /// Repeating the prepare of a transaction (same order and lines) returns
+    /// it as it is, with `applied: false`. Reusing the ID for anything else,
+    /// or after an abort or timeout, is a CommitmentConflict.
+    pub async fn prepare_commitment(
+        &self,
+        req: &PrepareCommitmentRequest,
+    ) -> Result<StockCommitment> {
+        let mut tx = self.begin().await?;
+        lock_commitment(&mut tx, &req.transaction_id).await?;
+
+        let mut lines = req.items.clone();
+        lines.sort_by(|a, b| a.sku.cmp(&b.sku));
+
+        if let Some(existing) = fetch_commitment(&mut tx, &req.transaction_id).await? {
+            let repeat = existing.order_id == req.order_id
+                && existing.lines == lines
+                && matches!(
+                    existing.state,
+                    CommitmentState::Prepared | CommitmentState::Committed
+                );
+            if repeat {
+                return Ok(existing);
+            }
+            return Err(AppError::CommitmentConflict(format!(
+                "Transaction {} is already {}",
+                req.transaction_id,
+                existing.state.as_str()
+            ))
+            .into());
+        }
+
+        self.reserve_lines(&mut tx, &req.to_batch()).await?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO stock_commitments (transaction_id, order_id, state, lines, expires_at)
+            VALUES ($1, $2, 'prepared', $3, NOW() + make_interval(secs => $4))
+            RETURNING transaction_id, order_id, state, lines, expires_at, created_at, updated_at
+            "#,
+        )
+        .bind(&req.transaction_id)
+        .bind(&req.order_id)
+        .bind(serde_json::to_value(&lines)?)
+        .bind(req.timeout_secs() as f64)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to record commitment")?;
+        let commitment = commitment_from_row(&row, true)?;
+
+        tx.commit().await?;
+
+        Ok(commitment)
+    }
+
+    /// Move a commitment to `target`: committed, aborted or expired
+    ///
+    /// Commit confirms every line (quantity and reserved drop); abort and
+    /// expiry release what the order still holds of each line. A commit
+    /// past the timeout expires the commitment instead. Steps the state
+    /// doesn't allow change nothing: the commitment comes back as it is,
+    /// with `applied: false`. Aborting an unknown ID records it as aborted.
+    /// The timeout sweeper passes its `lease`, so a sweeper that lost it
+    /// writes nothing (StaleLease).
+    ///
+    /// # Returns
+    /// - `None` for an unknown ID (commit and expiry)
+    pub async fn finish_commitment(
+        &self,
+        transaction_id: &str,
+        target: CommitmentState,
+        lease: Option<Lease>,
+    ) -> Result<Option<StockCommitment>> {
+        let mut tx = self.begin().await?;
+        if let Some(lease) = lease {
+            check_fence(&mut tx, lease).await?;
+        }
+        lock_commitment(&mut tx, transaction_id).await?;
+
+        let Some(current) = fetch_commitment(&mut tx, transaction_id).await? else {
+            if target != CommitmentState::Aborted {
+                return Ok(None);
+            }
+            // Presumed abort: a prepare arriving after this fails
+            let row = sqlx::query(
+                r#"
+                INSERT INTO stock_commitments (transaction_id, order_id, state, expires_at)
+                VALUES ($1, '', 'aborted', NOW())
+                RETURNING transaction_id, order_id, state, lines, expires_at, created_at, updated_at
+                "#,
+            )
+            .bind(transaction_id)
+            .fetch_one(&mut *tx)
+            .await
+            .context("Failed to record commitment")?;
+            let commitment = commitment_from_row(&row, true)?;
+            tx.commit().await?;
+            return Ok(Some(commitment));
+        };
+
+        let target = if target == CommitmentState::Committed
+            && current.state == CommitmentState::Prepared
+            && current.expires_at <= Utc::now()
+        {
+            CommitmentState::Expired
+        } else {
+            target
+        };
+        if commitments::step(current.state, target) != commitments::Step::Apply {
+            return Ok(Some(current));
+        }
+
+        // Same per-order lock as cancel-by-order and reservation expiry
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('cancel-order:' || $1))")
+            .bind(&current.order_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let detail = format!("transaction {} {}", transaction_id, target.as_str());
+        for line in &current.lines {
+            let held = order_holding(&mut tx, &current.order_id, &line.sku).await?;
+            let quantity = if target == CommitmentState::Committed {
+                if held < i64::from(line.quantity) {
+                    return Err(AppError::CommitmentConflict(format!(
+                        "Order {} holds {} of the {} x {} prepared in transaction {}; \
+                         the rest was released outside the transaction",
+                        current.order_id, held, line.quantity, line.sku, transaction_id
+                    ))
+                    .into());
+                }
+                line.quantity
+            } else {
+                // Never more than still held (an order cancel may have
+                // released some already)
+                line.quantity.min(held.max(0) as i32)
+            };
+            if quantity == 0 {
+                continue;
+            }
+
+            let (action, quantity_delta) = if target == CommitmentState::Committed {
+                ("confirm", -quantity)
+            } else {
+                ("release", 0)
+            };
+            let shipped = target == CommitmentState::Committed;
+            let Some(warehouse) = give_back_held(&mut tx, &current.order_id, &line.sku, quantity, shipped).await?
+            else {
+                return Err(anyhow::anyhow!(
+                    "Failed to {} {} x {}: SKU not found or insufficient reserved quantity \
+                     (see /api/v1/admin/reconcile-reservations)",
+                    action,
+                    quantity,
+                    line.sku
+                ));
+            };
+            let ending = if action == "confirm" { "confirmed" } else { "released" };
+            take_from_holding(&mut tx, &current.order_id, &line.sku, quantity, ending).await?;
+
+            insert_audit_event(
+                &mut *tx,
+                &NewAuditEvent {
+                    action,
+                    outcome: "success",
+                    sku: &line.sku,
+                    quantity,
+                    reference: Some(&current.order_id),
+                    detail: Some(&detail),
+                    channel: None,
+                },
+            )
+            .await?;
+            insert_stock_movement(
+                &mut *tx,
+                &NewStockMovement {
+                    movement_id: &self.ids.next_id(),
+                    sku: &line.sku,
+                    movement_type: action,
+                    quantity_delta,
+                    reserved_delta: -quantity,
+                    reference: Some(&current.order_id),
+                    warehouse: Some(&warehouse),
+                },
+            )
+            .await?;
+        }
+
+        let row = sqlx::query(
+            r#"
+            UPDATE stock_commitments
+            SET state = $2, updated_at = NOW()
+            WHERE transaction_id = $1
+            RETURNING transaction_id, order_id, state, lines, expires_at, created_at, updated_at
+            "#,
+        )
+        .bind(transaction_id)
+        .bind(target.as_str())
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to update commitment")?;
+        let commitment = commitment_from_row(&row, true)?;
+
+        tx.commit().await?;
+
+        Ok(Some(commitment))
+    }
+
+    /// IDs of prepared commitments past their timeout, oldest first
+    pub async fn due_commitments(&self, limit: i64) -> Result<Vec<String>> {
+        let ids = sqlx::query_scalar(
+            r#"
+            SELECT transaction_id
+            FROM stock_commitments
+            WHERE state = 'prepared' AND expires_at <= NOW()
+            ORDER BY expires_at
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to look up timed-out commitments")?;
+
+        Ok(ids)
+    }
+
+    /// Settle an order's reservations after a status change
+    ///
+    /// - confirm (paid/fulfilled): `quantity` and `reserved` both drop, the
+    ///   units have been sold. Each line must still be held in full,
+    ///   otherwise nothing is settled (DbError::Conflict).
+    /// - release (cancelled): only `reserved` drops, by no more than the
+    ///   order still holds of each line (an expiry or cancel-by-order may
+    ///   have released some already)
+    ///
+    /// Each order is settled at most once. A repeated callback with the same
+    /// outcome (e.g. "fulfilled" after "paid") returns `applied: false`; a
+    /// conflicting one (cancel after confirm) is a DbError::Conflict.
+    pub async fn settle_order(&self, req: &OrderCallbackRequest) -> Result<OrderCallbackResponse> {
+        let action = req.status.action();
+        let mut tx = self.begin().await?;
+
+        // Same per-order lock as cancel-by-order, expiry and commitments, so
+        // the holdings read below can't be released under the settlement
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('cancel-order:' || $1))")
+            .bind(&req.order_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO order_settlements (order_id, action, status)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (order_id) DO NOTHING
+            "#,
+        )
+        .bind(&req.order_id)
+        .bind(action)
+        .bind(req.status.as_str())
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if inserted == 0 {
+            let (previous,): (String,) =
+                sqlx::query_as("SELECT action FROM order_settlements WHERE order_id = $1")
+                    .bind(&req.order_id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+            if previous != action {
+                return Err(DbError::Conflict(format!(
+                    "Order {} was already settled with '{}', cannot {}",
+                    req.order_id, previous, action
+                ))
+                .into());
+            }
+
+            return Ok(OrderCallbackResponse {
+                order_id: req.order_id.clone(),
+                action: action.to_string(),
+                applied: false,
+            });
+        }
+
+        let (ending, confirm) = if action == "confirm" {
+            ("confirmed", true)
+        } else {
+            ("released", false)
+        };
+        for item in &req.items {
+            let held = order_holding(&mut tx, &req.order_id, &item.sku).await?;
+            let quantity = if confirm {
+                if held < i64::from(item.quantity) {
+                    return Err(DbError::Conflict(format!(
+                        "Order {} holds {} of the {} x {} to confirm",
+                        req.order_id, held, item.quantity, item.sku
+                    ))
+                    .into());
+                }
+                item.quantity
+            } else {
+                item.quantity.min(held.max(0) as i32)
+            };
+            if quantity == 0 {
+                continue;
+            }
+
+            let quantity_delta = if confirm { -quantity } else { 0 };
+            let Some(warehouse) = give_back_held(&mut tx, &req.order_id, &item.sku, quantity, confirm).await?
+            else {
+                return Err(anyhow::anyhow!(
+                    "Failed to {} {} x {}: SKU not found or insufficient reserved quantity \
+                     (see /api/v1/admin/reconcile-reservations)",
+                    action,
+                    quantity,
+                    item.sku
+                ));
+            };
+            take_from_holding(&mut tx, &req.order_id, &item.sku, quantity, ending).await?;
+
+            insert_audit_event(
+                &mut *tx,
+                &NewAuditEvent {
+                    action,
+                    outcome: "success",
+                    sku: &item.sku,
+                    quantity,
+                    reference: Some(&req.order_id),
+                    detail: None,
+                    channel: None,
+                },
+            )
+            .await?;
+            insert_stock_movement(
+                &mut *tx,
+                &NewStockMovement {
+                    movement_id: &self.ids.next_id(),
+                    sku: &item.sku,
+                    movement_type: action,
+                    quantity_delta,
+                    reserved_delta: -quantity,
+                    reference: Some(&req.order_id),
+                    warehouse: Some(&warehouse),
+                },
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(OrderCallbackResponse {
+            order_id: req.order_id.clone(),
+            action: action.to_string(),
+            applied: true,
+        })
+    }
+
+    /// Set (or clear, with None) a SKU's reservation policy
+    ///
+    /// With `expected_version`, fails with `AppError::VersionMismatch` if
+    /// the item has changed (as do the other setters below).
+    pub async fn set_reservation_policy(
+        &self,
+        sku: &str,
+        policy: &ReservationPolicy,
+        expected_version: Option<i64>,
+    ) -> Result<Option<InventoryItem>> {
+        let mut tx = self.begin().await?;
+        if !lock_at_version(&mut tx, sku, expected_version).await? {
+            return Ok(None);
+        }
+
+        let item = sqlx::query_as::<_, InventoryItem>(
+            r#"
+            UPDATE products
+            SET max_per_order = $1, max_reserved_pct = $2, updated_at = NOW()
+            WHERE sku = $3
+            RETURNING id, sku, name, quantity, reserved, warehouse,
+                      low_stock_threshold, max_per_order, max_reserved_pct, threshold_manual, version,
+                      attributes, image_url, spec_url, unit_price::float8 AS unit_price,
+                      unit_cost::float8 AS unit_cost, (quantity * unit_cost)::float8 AS stock_value,
+                      created_at, updated_at
+            "#,
+        )
+        .bind(policy.max_per_order)
+        .bind(policy.max_reserved_pct)
+        .bind(sku)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to update reservation policy")?;
+
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    /// Replace a SKU's catalog details (attributes, image and spec URLs,
+    /// unit price)
+    pub async fn set_catalog_details(
+        &self,
+        sku: &str,
+        details: &CatalogDetailsRequest,
+        expected_version: Option<i64>,
+    ) -> Result<Option<InventoryItem>> {
+        let mut tx = self.begin().await?;
+        if !lock_at_version(&mut tx, sku, expected_version).await? {
+            return Ok(None);
+        }
+        tag_revisions(&mut tx, "catalog", None).await?;
+
+        let item = sqlx::query_as::<_, InventoryItem>(
+            r#"
+            UPDATE products
+            SET attributes = $1, image_url = $2, spec_url = $3, unit_price = $4::float8,
+                unit_cost = $6::float8, updated_at = NOW()
+            WHERE sku = $5
+            RETURNING id, sku, name, quantity, reserved, warehouse,
+                      low_stock_threshold, max_per_order, max_reserved_pct, threshold_manual, version,
+                      attributes, image_url, spec_url, unit_price::float8 AS unit_price,
+                      unit_cost::float8 AS unit_cost, (quantity * unit_cost)::float8 AS stock_value,
+                      created_at, updated_at
+            "#,
+        )
+        .bind(&details.attributes)
+        .bind(&details.image_url)
+        .bind(&details.spec_url)
+        .bind(details.unit_price)
+        .bind(sku)
+        .bind(details.unit_cost)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to update catalog details")?;
+
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    /// Set a SKU's low-stock threshold by hand, or hand it back to dynamic
+    /// recalculation with `None` (the current value stays until then)
+    pub async fn set_low_stock_threshold(
+        &self,
+        sku: &str,
+        threshold: Option<i32>,
+        expected_version: Option<i64>,
+    ) -> Result<Option<InventoryItem>> {
+        let mut tx = self.begin().await?;
+        if !lock_at_version(&mut tx, sku, expected_version).await? {
+            return Ok(None);
+        }
+        tag_revisions(&mut tx, "threshold", None).await?;
+
+        let item = sqlx::query_as::<_, InventoryItem>(
+            r#"
+            UPDATE products
+            SET low_stock_threshold = COALESCE($1::int, low_stock_threshold),
+                threshold_manual = $1::int IS NOT NULL,
+                updated_at = NOW()
+            WHERE sku = $2
+            RETURNING id, sku, name, quantity, reserved, warehouse,
+                      low_stock_threshold, max_per_order, max_reserved_pct, threshold_manual, version,
+                      attributes, image_url, spec_url, unit_price::float8 AS unit_price,
+                      unit_cost::float8 AS unit_cost, (quantity * unit_cost)::float8 AS stock_value,
+                      created_at, updated_at
+            "#,
+        )
+        .bind(threshold)
+        .bind(sku)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to update low stock threshold")?;
+
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    /// Recompute automatic low-stock thresholds from recent outbound
+    /// movement: units sold (confirm) or removed (negative adjust) during the
+    /// last `lookback_days`, averaged per day, times `cover_days`.
+    ///
+    /// SKUs with `threshold_manual` are skipped. Returns how many changed.
+    pub async fn recalculate_thresholds(
+        &self,
+        cover_days: i32,
+        lookback_days: i32,
+        min_threshold: i32,
+    ) -> Result<u64> {
+        let mut tx = self.begin().await?;
+        tag_revisions(&mut tx, "threshold_recalculation", None).await?;
+
+        let result = sqlx::query(
+            r#"
+            WITH outbound AS (
+                SELECT sku, SUM(ABS(quantity))::numeric AS units
+                FROM audit_events
+                WHERE outcome = 'success'
+                  AND occurred_at > NOW() - make_interval(days => $2)
+                  AND (action = 'confirm' OR (action = 'adjust' AND quantity < 0))
+                GROUP BY sku
+            ),
+            target AS (
+                SELECT i.sku,
+                       GREATEST(CEIL(COALESCE(o.units, 0) * $1 / $2)::int, $3) AS threshold
+                FROM products i
+                LEFT JOIN outbound o ON o.sku = i.sku
+                WHERE NOT i.threshold_manual
+            )
+            UPDATE products i
+            SET low_stock_threshold = t.threshold, updated_at = NOW()
+            FROM target t
+            WHERE i.sku = t.sku AND i.low_stock_threshold <> t.threshold
+            "#,
+        )
+        .bind(cover_days)
+        .bind(lookback_days)
+        .bind(min_threshold)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to recalculate low stock thresholds")?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Stock of a SKU in each warehouse holding some, home warehouse first
+    ///
+    /// The `stock` rows of the SKU, keyed by (sku, warehouse); the home
+    /// warehouse is listed even when empty. None when the SKU doesn't
+    /// exist.
+    pub async fn list_warehouse_stock(&self, sku: &str) -> Result<Option<Vec<WarehouseStock>>> {
+        let warehouses = stock_by_warehouse(&self.pool, sku).await?;
+        Ok((!warehouses.is_empty()).then_some(warehouses))
+    }
+
+    /// Per-warehouse low-stock thresholds of a SKU
+    pub async fn list_warehouse_thresholds(&self, sku: &str) -> Result<Vec<WarehouseThreshold>> {
+        let thresholds = sqlx::query_as::<_, WarehouseThreshold>(
+            r#"
+            SELECT sku, warehouse, threshold, updated_at
+            FROM warehouse_thresholds
+            WHERE sku = $1
+            ORDER BY warehouse ASC
+            "#,
+        )
+        .bind(sku)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch warehouse thresholds")?;
+
+        Ok(thresholds)
+    }
+
+    /// Set the low-stock threshold of a SKU in one warehouse
+    pub async fn set_warehouse_threshold(
+        &self,
+        sku: &str,
+        warehouse: &str,
+        threshold: i32,
+    ) -> Result<WarehouseThreshold> {
+        let row = sqlx::query_as::<_, WarehouseThreshold>(
+            r#"
+            INSERT INTO warehouse_thresholds (sku, warehouse, threshold)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (sku, warehouse)
+            DO UPDATE SET threshold = EXCLUDED.threshold, updated_at = NOW()
+            RETURNING sku, warehouse, threshold, updated_at
+            "#,
+        )
+        .bind(sku)
+        .bind(warehouse)
+        .bind(threshold)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to set warehouse threshold")?;
+
+        Ok(row)
+    }
+
+    /// Remove a per-warehouse threshold; returns false if none was set
+    pub async fn delete_warehouse_threshold(&self, sku: &str, warehouse: &str) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM warehouse_thresholds WHERE sku = $1 AND warehouse = $2")
+                .bind(sku)
+                .bind(warehouse)
+                .execute(&self.pool)
+                .await
+                .context("Failed to delete warehouse threshold")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // -------------------------------------------------------------------------
+    // WAREHOUSES
+    // -------------------------------------------------------------------------
+
+    /// All registered warehouses, by code
+    pub async fn list_warehouses(&self) -> Result<Vec<Warehouse>> {
+        let warehouses = sqlx::query_as::<_, Warehouse>(
+            "SELECT code, name, region, capacity, created_at, updated_at FROM warehouses ORDER BY code",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list warehouses")?;
+
+        Ok(warehouses)
+    }
+
+    /// One warehouse; None when the code isn't registered
+    pub async fn get_warehouse(&self, code: &str) -> Result<Option<Warehouse>> {
+        let warehouse = sqlx::query_as::<_, Warehouse>(
+            "SELECT code, name, region, capacity, created_at, updated_at FROM warehouses WHERE code = $1",
+        )
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch warehouse")?;
+
+        Ok(warehouse)
+    }
+
+    /// Whether a warehouse code is registered
+    pub async fn warehouse_exists(&self, code: &str) -> Result<bool> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM warehouses WHERE code = $1)")
+            .bind(code)
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to look up warehouse")?;
+
+        Ok(exists)
+    }
+
+    /// Register a warehouse; None when the code is taken
+    pub async fn create_warehouse(
+        &self,
+        code: &str,
+        details: &UpdateWarehouseRequest,
+    ) -> Result<Option<Warehouse>> {
+        let warehouse = sqlx::query_as::<_, Warehouse>(
+            r#"
+            INSERT INTO warehouses (code, name, region, capacity)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (code) DO NOTHING
+            RETURNING code, name, region, capacity, created_at, updated_at
+            "#,
+        )
+        .bind(code)
+        .bind(&details.name)
+        .bind(&details.region)
+        .bind(details.capacity)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to create warehouse")?;
+
+        Ok(warehouse)
+    }
+
+    /// Replace a warehouse's name, region and capacity; None when the code
+    /// isn't registered
+    pub async fn update_warehouse(
+        &self,
+        code: &str,
+        details: &UpdateWarehouseRequest,
+    ) -> Result<Option<Warehouse>> {
+        let warehouse = sqlx::query_as::<_, Warehouse>(
+            r#"
+            UPDATE warehouses
+            SET name = $2, region = $3, capacity = $4, updated_at = NOW()
+            WHERE code = $1
+            RETURNING code, name, region, capacity, created_at, updated_at
+            "#,
+        )
+        .bind(code)
+        .bind(&details.name)
+        .bind(&details.region)
+        .bind(details.capacity)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to update warehouse")?;
+
+        Ok(warehouse)
+    }
+
+    /// Remove a warehouse; false when the code isn't registered
+    ///
+    /// Fails with DbError::Conflict while items are homed there or stock
+    /// was transferred there.
+    pub async fn delete_warehouse(&self, code: &str) -> Result<bool> {
+        let mut tx = self.begin().await?;
+
+        let found: Option<String> =
+            sqlx::query_scalar("SELECT code FROM warehouses WHERE code = $1 FOR UPDATE")
+                .bind(code)
+                .fetch_optional(&mut *tx)
+                .await
+                .context("Failed to lock warehouse")?;
+        if found.is_none() {
+            return Ok(false);
+        }
+
+        let (items, stocked): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT (SELECT COUNT(*) FROM products WHERE warehouse = $1),
+                   (SELECT COUNT(*) FROM stock WHERE warehouse = $1 AND quantity > 0)
+            "#,
+        )
+        .bind(code)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to check warehouse usage")?;
+        if items > 0 || stocked > 0 {
+            return Err(DbError::Conflict(format!(
+                "Warehouse {} is still in use: home of {} SKUs, holds transferred stock of {}",
+                code, items, stocked
+            ))
+            .into());
+        }
+
+        // Empty rows of the SKUs it used to be home of
+        sqlx::query("DELETE FROM stock WHERE warehouse = $1")
+            .bind(code)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete warehouse stock")?;
+        sqlx::query("DELETE FROM warehouses WHERE code = $1")
+            .bind(code)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete warehouse")?;
+        // Thresholds only mean something for a warehouse that exists
+        sqlx::query("DELETE FROM warehouse_thresholds WHERE warehouse = $1")
+            .bind(code)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete warehouse thresholds")?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Stock totals of one warehouse
+    ///
+    /// Only SKUs it is home of count as low on stock, against what it has
+    /// available of them.
+    pub async fn warehouse_totals(&self, code: &str) -> Result<WarehouseTotals> {
+        let totals = sqlx::query_as::<_, WarehouseTotals>(
+            r#"
+            WITH here AS (
+                SELECT s.sku, s.quantity, s.reserved,
+                       p.warehouse = s.warehouse AS home,
+                       p.warehouse = s.warehouse AND (s.quantity - s.reserved) < COALESCE(
+                           (SELECT t.threshold FROM warehouse_thresholds t
+                            WHERE t.sku = s.sku AND t.warehouse = s.warehouse),
+                           p.low_stock_threshold) AS low_stock
+                FROM stock s
+                JOIN products p ON p.sku = s.sku
+                WHERE s.warehouse = $1 AND (s.quantity > 0 OR p.warehouse = s.warehouse)
+            )
+            SELECT COUNT(DISTINCT sku) AS skus,
+                   COUNT(*) FILTER (WHERE home) AS home_skus,
+                   COALESCE(SUM(quantity), 0)::BIGINT AS quantity,
+                   COALESCE(SUM(reserved), 0)::BIGINT AS reserved,
+                   COUNT(*) FILTER (WHERE low_stock) AS low_stock_skus
+            FROM here
+            "#,
+        )
+        .bind(code)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to sum warehouse stock")?;
+
+        Ok(totals)
+    }
+
+    // -------------------------------------------------------------------------
+    // ATTRIBUTE SCHEMAS
+    // -------------------------------------------------------------------------
+
+    /// All attribute schemas, by name
+    pub async fn list_attribute_schemas(&self) -> Result<Vec<AttributeSchema>> {
+        let schemas = sqlx::query_as::<_, AttributeSchema>(
+            "SELECT name, attr_type, required, created_at FROM attribute_schemas ORDER BY name",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch attribute schemas")?;
+
+        Ok(schemas)
+    }
+
+    /// Define an attribute schema, replacing any previous one of that name
+    pub async fn upsert_attribute_schema(
+        &self,
+        name: &str,
+        request: &AttributeSchemaRequest,
+    ) -> Result<AttributeSchema> {
+        let schema = sqlx::query_as::<_, AttributeSchema>(
+            r#"
+            INSERT INTO attribute_schemas (name, attr_type, required)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (name) DO UPDATE
+                SET attr_type = EXCLUDED.attr_type, required = EXCLUDED.required
+            RETURNING name, attr_type, required, created_at
+            "#,
+        )
+        .bind(name)
+        .bind(request.attr_type.as_str())
+        .bind(request.required)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to save attribute schema")?;
+
+        Ok(schema)
+    }
+
+    /// Drop an attribute schema; returns false if there was none
+    ///
+    /// Items keep the attribute, it just becomes free-form again.
+    pub async fn delete_attribute_schema(&self, name: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM attribute_schemas WHERE name = $1")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete attribute schema")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // -------------------------------------------------------------------------
+    // ALTERNATE IDENTIFIERS
+    // -------------------------------------------------------------------------
+
+    /// Resolve a canonical SKU or alternate identifier to its item
+    ///
+    /// # Returns
+    /// `(matched_by, item)`: "sku" or the identifier kind, and the item
+    pub async fn lookup_item(&self, id: &str) -> Result<Option<(String, InventoryItem)>> {
+        if let Some(item) = self.get_by_sku(id).await? {
+            return Ok(Some(("sku".to_string(), item)));
+        }
+
+        let kind: Option<(String,)> =
+            sqlx::query_as("SELECT kind FROM item_identifiers WHERE identifier = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+                .context("Failed to look up item identifier")?;
+        let Some((kind,)) = kind else {
+            return Ok(None);
+        };
+
+        let item = sqlx::query_as::<_, InventoryItem>(
+            r#"
+            SELECT i.id, i.sku, i.name, i.quantity, i.reserved, i.warehouse,
+                   i.low_stock_threshold, i.max_per_order, i.max_reserved_pct,
+                   i.threshold_manual, i.version, i.attributes, i.image_url, i.spec_url,
+                   i.unit_price::float8 AS unit_price, i.unit_cost::float8 AS unit_cost,
+                   (i.quantity * i.unit_cost)::float8 AS stock_value, i.created_at, i.updated_at
+            FROM item_identifiers a
+            JOIN products i ON i.sku = a.sku
+            WHERE a.identifier = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch item by identifier")?;
+
+        Ok(item.map(|item| (kind, item)))
+    }
+
+    /// Alternate identifiers of a SKU
+    pub async fn list_item_identifiers(&self, sku: &str) -> Result<Vec<ItemIdentifier>> {
+        let identifiers = sqlx::query_as::<_, ItemIdentifier>(
+            r#"
+            SELECT identifier, kind, sku, created_at
+            FROM item_identifiers
+            WHERE sku = $1
+            ORDER BY kind ASC, identifier ASC
+            "#,
+        )
+        .bind(sku)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch item identifiers")?;
+
+        Ok(identifiers)
+    }
+
+    /// Add an alternate identifier to a SKU
+    ///
+    /// # Returns
+    /// - `Ok(Ok(row))` when added (or already assigned to this SKU)
+    /// - `Ok(Err(owner))` when the identifier belongs to another SKU
+    pub async fn add_item_identifier(
+        &self,
+        sku: &str,
+        request: &ItemIdentifierRequest,
+    ) -> Result<std::result::Result<ItemIdentifier, String>> {
+        let inserted = sqlx::query_as::<_, ItemIdentifier>(
+            r#"
+            INSERT INTO item_identifiers (identifier, kind, sku)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (identifier) DO NOTHING
+            RETURNING identifier, kind, sku, created_at
+            "#,
+        )
+        .bind(&request.identifier)
+        .bind(request.kind.as_str())
+        .bind(sku)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to add item identifier")?;
+
+        if let Some(row) = inserted {
+            return Ok(Ok(row));
+        }
+
+        let existing = sqlx::query_as::<_, ItemIdentifier>(
+            "SELECT identifier, kind, sku, created_at FROM item_identifiers WHERE identifier = $1",
+        )
+        .bind(&request.identifier)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to fetch existing item identifier")?;
+
+        if existing.sku == sku && existing.kind == request.kind.as_str() {
+            Ok(Ok(existing))
+        } else {
+            Ok(Err(existing.sku))
+        }
+    }
+
+    /// Remove an alternate identifier of a SKU; returns false if it had none
+    pub async fn delete_item_identifier(&self, sku: &str, identifier: &str) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM item_identifiers WHERE sku = $1 AND identifier = $2")
+                .bind(sku)
+                .bind(identifier)
+                .execute(&self.pool)
+                .await
+                .context("Failed to delete item identifier")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // -------------------------------------------------------------------------
+    // WEBHOOK SUBSCRIPTIONS
+    // -------------------------------------------------------------------------
+
+    /// All webhook subscriptions, oldest first
+    pub async fn list_webhook_subscriptions(&self) -> Result<Vec<WebhookSubscription>> {
+        let subscriptions = sqlx::query_as::<_, WebhookSubscription>(
+            r#"
+            SELECT id, url, event_types, sku_pattern, created_at
+            FROM webhook_subscriptions
+            ORDER BY id ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch webhook subscriptions")?;
+
+        Ok(subscriptions)
+    }
+
+    /// Store a new webhook subscription
+    pub async fn create_webhook_subscription(
+        &self,
+        request: &WebhookSubscriptionRequest,
+    ) -> Result<WebhookSubscription> {
+        let subscription = sqlx::query_as::<_, WebhookSubscription>(
+            r#"
+            INSERT INTO webhook_subscriptions (url, event_types, sku_pattern)
+            VALUES ($1, $2, $3)
+            RETURNING id, url, event_types, sku_pattern, created_at
+            "#,
+        )
+        .bind(&request.url)
+        .bind(&request.event_types)
+        .bind(&request.sku_pattern)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to create webhook subscription")?;
+
+        Ok(subscription)
+    }
+
+    /// Remove a webhook subscription; returns false if it didn't exist
+    pub async fn delete_webhook_subscription(&self, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM webhook_subscriptions WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete webhook subscription")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // -------------------------------------------------------------------------
+    // AUDIT TRAIL
+    // -------------------------------------------------------------------------
+
+    /// Record an audit event outside of a mutation transaction
+    ///
+    /// Used for failed operations, whose own transaction was rolled back.
+    pub async fn record_audit_event(&self, event: &NewAuditEvent<'_>) -> Result<()> {
+        insert_audit_event(&self.pool, event).await
+    }
+
+    /// Audit events with an ID greater than `after_id`, oldest first, up to
+    /// the newest one that has settled
+    ///
+    /// An event settles once its transaction started more than `settle`
+    /// ago (see audit.rs, export_settle): every event with a lower ID has
+    /// committed or rolled back by then, so a cursor moved past it can't
+    /// pass over one that commits later.
+    pub async fn list_audit_events(
+        &self,
+        after_id: i64,
+        limit: i64,
+        settle: Duration,
+    ) -> Result<Vec<AuditEvent>> {
+        let events = sqlx::query_as::<_, AuditEvent>(
+            r#"
+            SELECT id, occurred_at, action, outcome, sku, quantity, reference, detail, channel
+            FROM audit_events
+            WHERE id > $1
+              AND id <= COALESCE((
+                  SELECT id FROM audit_events
+                  WHERE occurred_at < NOW() - make_interval(secs => $3)
+                  ORDER BY id DESC
+                  LIMIT 1
+              ), 0)
+            ORDER BY id ASC
+            LIMIT $2
+            "#,
+        )
+        .bind(after_id)
+        .bind(limit)
+        .bind(settle.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch audit events")?;
+
+        Ok(events)
+    }
+
+    // -------------------------------------------------------------------------
+    // AUDIT OUTBOX
+    // -------------------------------------------------------------------------
+    // Rows are queued by the audit_events_outbox trigger, never here.
+
+    /// Queue every new audit event for `follower` from now on; a follower
+    /// registered before keeps its queue
+    pub async fn register_audit_follower(&self, follower: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_followers (name)
+            VALUES ($1)
+            ON CONFLICT (name) DO UPDATE SET last_seen_at = NOW()
+            "#,
+        )
+        .bind(follower)
+        .execute(&self.pool)
+        .await
+        .context("Failed to register audit follower")?;
+
+        Ok(())
+    }
+
+    /// Claim `follower` for `lease` and take up to `limit` of its
+    /// unshipped events, oldest first
+    ///
+    /// Claiming and reading are two short statements; the claim is a
+    /// lease on the follower's row, not a lock held while the events are
+    /// sent (see AuditClaim).
+    ///
+    /// # Returns
+    /// - `None` while another replica holds an unexpired claim
+    pub async fn claim_audit_events(
+        &self,
+        follower: &str,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Option<AuditClaim>> {
+        // The UPDATE locks the row FOR NO KEY UPDATE, for one statement;
+        // writers queueing outbox rows take KEY SHARE and don't wait
+        let token: Option<i64> = sqlx::query_scalar(
+            r#"
+            UPDATE audit_followers
+            SET claim_token = claim_token + 1,
+                claimed_until = NOW() + make_interval(secs => $2),
+                last_seen_at = NOW()
+            WHERE name = $1 AND (claimed_until IS NULL OR claimed_until < NOW())
+            RETURNING claim_token
+            "#,
+        )
+        .bind(follower)
+        .bind(lease.as_secs_f64())
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to claim audit follower")?;
+
+        let Some(token) = token else {
+            return Ok(None);
+        };
+        let mut claim = AuditClaim {
+            pool: self.pool.clone(),
+            follower: follower.to_string(),
+            token,
+            finished: false,
+            events: Vec::new(),
+        };
+
+        let events = sqlx::query_as::<_, AuditEvent>(
+            r#"
+            SELECT e.id, e.occurred_at, e.action, e.outcome, e.sku, e.quantity,
+                   e.reference, e.detail, e.channel
+            FROM audit_outbox o
+            JOIN audit_events e ON e.id = o.event_id
+            WHERE o.follower = $1 AND o.shipped_at IS NULL
+            ORDER BY o.event_id ASC
+            LIMIT $2
+            "#,
+        )
+        .bind(follower)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch queued audit events")?;
+
+        claim.events = events;
+        Ok(Some(claim))
+    }
+
+    /// Unshipped audit events per follower
+    pub async fn audit_outbox_backlog(&self) -> Result<Vec<OutboxBacklog>> {
+        let backlog = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT f.name, COUNT(o.event_id)
+            FROM audit_followers f
+            LEFT JOIN audit_outbox o ON o.follower = f.name AND o.shipped_at IS NULL
+            GROUP BY f.name
+            ORDER BY f.name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to count queued audit events")?;
+
+        Ok(backlog
+            .into_iter()
+            .map(|(follower, pending)| OutboxBacklog { follower, pending })
+            .collect())
+    }
+
+    /// Delete events `follower` shipped more than `keep_hours` ago, and
+    /// followers no replica has claimed for `forget_days` (with their queue)
+    pub async fn prune_audit_outbox(&self, follower: &str, keep_hours: i32, forget_days: i32) -> Result<u64> {
+        let shipped = sqlx::query(
+            r#"
+            DELETE FROM audit_outbox
+            WHERE follower = $1 AND shipped_at < NOW() - make_interval(hours => $2)
+            "#,
+        )
+        .bind(follower)
+        .bind(keep_hours)
+        .execute(&self.pool)
+        .await
+        .context("Failed to prune shipped audit events")?;
+
+        let forgotten = sqlx::query(
+            "DELETE FROM audit_followers WHERE last_seen_at < NOW() - make_interval(days => $1)",
+        )
+        .bind(forget_days)
+        .execute(&self.pool)
+        .await
+        .context("Failed to drop idle audit followers")?;
+        if forgotten.rows_affected() > 0 {
+            tracing::info!(followers = forgotten.rows_affected(), "Dropped idle audit followers");
+        }
+
+        Ok(shipped.rows_affected())
+    }
+
+    // -------------------------------------------------------------------------
+    // STOCK EVENTS
+    // -------------------------------------------------------------------------
+    // Rows are written by the inventory_stock_transition trigger, never here.
+
+    /// Undelivered stock events, oldest first
+    pub async fn pending_stock_events(&self, limit: i64) -> Result<Vec<StockEvent>> {
+        let events = sqlx::query_as::<_, StockEvent>(
+            r#"
+            SELECT id, event_type, sku, warehouse, available, occurred_at
+            FROM stock_events
+            WHERE delivered_at IS NULL
+            ORDER BY id ASC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch pending stock events")?;
+
+        Ok(events)
+    }
+
+    /// Mark one stock event as delivered
+    pub async fn mark_stock_event_delivered(&self, id: i64) -> Result<()> {
+        sqlx::query("UPDATE stock_events SET delivered_at = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to mark stock event as delivered")?;
+
+        Ok(())
+    }
+
+    /// Delete stock events delivered more than `days` ago
+    pub async fn prune_stock_events(&self, days: i32) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM stock_events
+            WHERE delivered_at < NOW() - make_interval(days => $1)
+            "#,
+        )
+        .bind(days)
+        .execute(&self.pool)
+        .await
+        .context("Failed to prune stock events")?;
+
+        Ok(result.rows_affected())
+    }
+
+    // -------------------------------------------------------------------------
+    // SNAPSHOTS
+    // -------------------------------------------------------------------------
+
+    /// Copy every item's stock into a new snapshot
+    pub async fn create_snapshot(&self, label: Option<&str>) -> Result<InventorySnapshot> {
+        let mut tx = self.begin().await?;
+
+        let (id, taken_at): (i64, chrono::DateTime<Utc>) = sqlx::query_as(
+            "INSERT INTO inventory_snapshots (label) VALUES ($1) RETURNING id, taken_at",
+        )
+        .bind(label)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to create snapshot")?;
+
+        let copied = sqlx::query(
+            r#"
+            INSERT INTO inventory_snapshot_items (snapshot_id, sku, quantity, reserved, unit_price)
+            SELECT $1, sku, quantity, reserved, unit_price
+            FROM products
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to copy inventory into snapshot")?;
+
+        tx.commit().await?;
+
+        Ok(InventorySnapshot {
+            id,
+            label: label.map(str::to_string),
+            taken_at,
+            items: copied.rows_affected() as i64,
+        })
+    }
+
+    /// Most recent snapshots, newest first
+    pub async fn list_snapshots(&self, limit: i64) -> Result<Vec<InventorySnapshot>> {
+        let snapshots = sqlx::query_as::<_, InventorySnapshot>(
+            r#"
+            SELECT s.id, s.label, s.taken_at,
+                   (SELECT COUNT(*) FROM inventory_snapshot_items i
+                    WHERE i.snapshot_id = s.id) AS items
+            FROM inventory_snapshots s
+            ORDER BY s.taken_at DESC, s.id DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list snapshots")?;
+
+        Ok(snapshots)
+    }
+
+    /// Find a snapshot by ID, or the newest one taken at or before a time
+    pub async fn find_snapshot(&self, snapshot: SnapshotRef) -> Result<Option<InventorySnapshot>> {
+        let (id, at) = match snapshot {
+            SnapshotRef::Id(id) => (Some(id), None),
+            SnapshotRef::At(at) => (None, Some(at)),
+        };
+
+        let found = sqlx::query_as::<_, InventorySnapshot>(
+            r#"
+            SELECT s.id, s.label, s.taken_at,
+                   (SELECT COUNT(*) FROM inventory_snapshot_items i
+                    WHERE i.snapshot_id = s.id) AS items
+            FROM inventory_snapshots s
+            WHERE ($1::bigint IS NULL OR s.id = $1)
+              AND ($2::timestamptz IS NULL OR s.taken_at <= $2)
+            ORDER BY s.taken_at DESC, s.id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(id)
+        .bind(at)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to look up snapshot")?;
+
+        Ok(found)
+    }
+
+    /// Per-SKU changes from snapshot `from` to snapshot `to`, or to current
+    /// stock when `to` is `None`
+    ///
+    /// SKUs missing on one side count as zero there; unchanged SKUs are left
+    /// out.
+    pub async fn snapshot_diff(&self, from: i64, to: Option<i64>) -> Result<Vec<SkuDelta>> {
+        let deltas = sqlx::query_as::<_, SkuDelta>(
+            r#"
+            WITH before AS (
+                SELECT sku, quantity, reserved, quantity * COALESCE(unit_price, 0) AS value
+                FROM inventory_snapshot_items
+                WHERE snapshot_id = $1
+            ),
+            after AS (
+                SELECT sku, quantity, reserved, quantity * COALESCE(unit_price, 0) AS value
+                FROM inventory_snapshot_items
+                WHERE snapshot_id = $2
+                UNION ALL
+                SELECT sku, quantity, reserved, quantity * COALESCE(unit_price, 0) AS value
+                FROM products
+                WHERE $2::bigint IS NULL
+            )
+            SELECT COALESCE(a.sku, b.sku) AS sku,
+                   COALESCE(b.quantity, 0) AS quantity_before,
+                   COALESCE(a.quantity, 0) AS quantity_after,
+                   COALESCE(a.quantity, 0) - COALESCE(b.quantity, 0) AS quantity_delta,
+                   COALESCE(b.reserved, 0) AS reserved_before,
+                   COALESCE(a.reserved, 0) AS reserved_after,
+                   COALESCE(a.reserved, 0) - COALESCE(b.reserved, 0) AS reserved_delta,
+                   (COALESCE(a.value, 0) - COALESCE(b.value, 0))::float8 AS value_delta
+            FROM before b
+            FULL OUTER JOIN after a ON a.sku = b.sku
+            WHERE b.quantity IS DISTINCT FROM a.quantity
+               OR b.reserved IS DISTINCT FROM a.reserved
+               OR b.value IS DISTINCT FROM a.value
+            ORDER BY 1
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to compare snapshots")?;
+
+        Ok(deltas)
+    }
+
+    // -------------------------------------------------------------------------
+    // REPORTS
+    // -------------------------------------------------------------------------
+
+    /// Stockouts and reservation fill rate per SKU since `since`
+    ///
+    /// Stockouts are the stock.out events with the stock.back that followed
+    /// them (LEAD over the SKU's events); an unanswered stock.out is still
+    /// open. Durations are clipped to [since, now].
+    pub async fn stockout_report(
+        &self,
+        since: chrono::DateTime<Utc>,
+    ) -> Result<Vec<StockoutReportRow>> {
+        let rows = sqlx::query_as::<_, StockoutReportRow>(
+            r#"
+            WITH transitions AS (
+                SELECT sku, event_type, occurred_at AS started_at,
+                       LEAD(occurred_at) OVER (PARTITION BY sku ORDER BY id) AS ended_at
+                FROM stock_events
+            ),
+            stockouts AS (
+                SELECT sku,
+                       COUNT(*) AS incidents,
+                       SUM(EXTRACT(EPOCH FROM
+                           COALESCE(ended_at, NOW()) - GREATEST(started_at, $1)
+                       ))::float8 AS seconds,
+                       BOOL_OR(ended_at IS NULL) AS currently_out
+                FROM transitions
+                WHERE event_type = 'stock.out'
+                  AND COALESCE(ended_at, NOW()) > $1
+                GROUP BY sku
+            ),
+            fills AS (
+                SELECT sku,
+                       COUNT(*) AS requested,
+                       COUNT(*) FILTER (WHERE outcome = 'success') AS filled,
+                       SUM(quantity) AS units_requested,
+                       COALESCE(SUM(quantity) FILTER (WHERE outcome = 'success'), 0)
+                           AS units_filled
+                FROM audit_events
+                WHERE action = 'reserve' AND occurred_at >= $1
+                GROUP BY sku
+            )
+            SELECT i.sku, i.warehouse,
+                   COALESCE(s.incidents, 0) AS incidents,
+                   COALESCE(s.seconds, 0)::float8 AS stockout_seconds,
+                   COALESCE(s.currently_out, FALSE) AS currently_out,
+                   COALESCE(f.requested, 0) AS reservations_requested,
+                   COALESCE(f.filled, 0) AS reservations_filled,
+                   COALESCE(f.units_requested, 0) AS units_requested,
+                   COALESCE(f.units_filled, 0) AS units_filled,
+                   f.units_filled::float8 / NULLIF(f.units_requested, 0) AS fill_rate
+            FROM products i
+            LEFT JOIN stockouts s ON s.sku = i.sku
+            LEFT JOIN fills f ON f.sku = i.sku
+            ORDER BY stockout_seconds DESC, i.sku
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to build stockout report")?;
+
+        Ok(rows)
+    }
+
+    /// Stock totals over all inventory rows
+    pub async fn inventory_totals(&self) -> Result<InventoryTotals> {
+        let totals = sqlx::query_as::<_, InventoryTotals>(
+            r#"
+            SELECT COUNT(*) AS total_skus,
+                   COALESCE(SUM(quantity), 0)::bigint AS total_units,
+                   COALESCE(SUM(reserved), 0)::bigint AS reserved_units,
+                   COALESCE(SUM(quantity - reserved), 0)::bigint AS available_units
+            FROM products
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to fetch inventory totals")?;
+
+        Ok(totals)
+    }
+
+    /// Orders still holding reserved units
+    ///
+    pub async fn active_reservation_count(&self) -> Result<i64> {
+        let count = sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT order_id) FROM reservations WHERE held_quantity > 0",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count active reservations")?;
+
+        Ok(count)
+    }
+
+    /// Stock rows whose `reserved` column disagrees with the active
+    /// reservations taken from them
+    ///
+    /// Active reservations are what the `reservations` rows still hold;
+    /// one without a warehouse counts at the SKU's home warehouse.
+    pub async fn reservation_drift(&self) -> Result<Vec<ReservationDrift>> {
+        let rows = sqlx::query_as::<_, ReservationDrift>(
+            r#"
+            WITH held AS (
+                SELECT r.sku, COALESCE(r.warehouse, p.warehouse) AS warehouse,
+                       SUM(r.held_quantity) AS units
+                FROM reservations r
+                JOIN products p ON p.sku = r.sku
+                WHERE r.held_quantity > 0
+                GROUP BY 1, 2
+            ), expected AS (
+                SELECT s.sku, s.warehouse, s.quantity, s.reserved,
+                       LEAST(COALESCE(h.units, 0), s.quantity)::int AS expected
+                FROM stock s
+                LEFT JOIN held h ON h.sku = s.sku AND h.warehouse = s.warehouse
+            )
+            SELECT sku, warehouse, quantity, reserved, expected, reserved - expected AS drift
+            FROM expected
+            WHERE reserved <> expected
+            ORDER BY ABS(reserved - expected) DESC, sku, warehouse
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to compute reservation drift")?;
+
+        Ok(rows)
+    }
+
+    /// Set `reserved` to the expected value for each drifted stock row
+    ///
+    /// A row is only touched if `reserved` still has the value that was
+    /// reported, so a reservation landing meanwhile is never overwritten
+    /// (and running the repair twice is harmless). Every correction is
+    /// audited as a "reconcile" event.
+    ///
+    /// # Returns
+    /// SKUs that were corrected
+    pub async fn repair_reservation_drift(
+        &self,
+        drifted: &[ReservationDrift],
+    ) -> Result<Vec<String>> {
+        let mut tx = self.begin().await?;
+        let mut repaired = Vec::new();
+
+        for row in drifted {
+            let result = sqlx::query(
+                r#"
+                WITH product AS (
+                    SELECT sku FROM products WHERE sku = $2 FOR NO KEY UPDATE
+                )
+                UPDATE stock s
+                SET reserved = $1, updated_at = NOW()
+                FROM product p
+                WHERE s.sku = p.sku AND s.warehouse = $4 AND s.reserved = $3
+                "#,
+            )
+            .bind(row.expected)
+            .bind(&row.sku)
+            .bind(row.reserved)
+            .bind(&row.warehouse)
+            .execute(&mut *tx)
+            .await?;
+
+            if result.rows_affected() == 0 {
+                continue;
+            }
+
+            let detail = format!("reserved at {} {} -> {}", row.warehouse, row.reserved, row.expected);
+            insert_audit_event(
+                &mut *tx,
+                &NewAuditEvent {
+                    action: "reconcile",
+                    outcome: "success",
+                    sku: &row.sku,
+                    quantity: -row.drift,
+                    reference: None,
+                    detail: Some(&detail),
+                    channel: None,
+                },
+            )
+            .await?;
+            insert_stock_movement(
+                &mut *tx,
+                &NewStockMovement {
+                    movement_id: &self.ids.next_id(),
+                    sku: &row.sku,
+                    movement_type: "reconcile",
+                    quantity_delta: 0,
+                    reserved_delta: row.expected - row.reserved,
+                    reference: None,
+                    warehouse: Some(&row.warehouse),
+                },
+            )
+            .await?;
+            repaired.push(row.sku.clone());
+        }
+
+        tx.commit().await?;
+
+        Ok(repaired)
+    }
+
+    /// Successful stock movements since `since`, per audit action
+    pub async fn movement_counts(
+        &self,
+        since: chrono::DateTime<Utc>,
+    ) -> Result<Vec<(String, i64)>> {
+        let counts = sqlx::query_as(
+            r#"
+            SELECT action, COUNT(*)
+            FROM audit_events
+            WHERE outcome = 'success' AND occurred_at >= $1
+            GROUP BY action
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to count stock movements")?;
+
+        Ok(counts)
+    }
+
+    /// A SKU's ledger entries, newest first
+    ///
+    /// `movement_type`, `from` (inclusive) and `to` (exclusive) narrow the
+    /// list when given.
+    ///
+    /// # Returns
+    /// Tuple of (movements, total_count)
+    pub async fn list_stock_movements(
+        &self,
+        sku: &str,
+        movement_type: Option<&str>,
+        from: Option<chrono::DateTime<Utc>>,
+        to: Option<chrono::DateTime<Utc>>,
+        page: i32,
+        per_page: i32,
+    ) -> Result<(Vec<StockMovement>, i64)> {
+        let offset = (page - 1) * per_page;
+
+        let movements = sqlx::query_as::<_, StockMovement>(
+            r#"
+            SELECT id, movement_id, sku, movement_type, quantity_delta, reserved_delta,
+                   quantity_after, reserved_after, reference, warehouse, created_at
+            FROM stock_movements
+            WHERE sku = $1
+              AND ($2::text IS NULL OR movement_type = $2)
+              AND ($3::timestamptz IS NULL OR created_at >= $3)
+              AND ($4::timestamptz IS NULL OR created_at < $4)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $5 OFFSET $6
+            "#,
+        )
+        .bind(sku)
+        .bind(movement_type)
+        .bind(from)
+        .bind(to)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch stock movements")?;
+
+        let total: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM stock_movements
+            WHERE sku = $1
+              AND ($2::text IS NULL OR movement_type = $2)
+              AND ($3::timestamptz IS NULL OR created_at >= $3)
+              AND ($4::timestamptz IS NULL OR created_at < $4)
+            "#,
+        )
+        .bind(sku)
+        .bind(movement_type)
+        .bind(from)
+        .bind(to)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count stock movements")?;
+
+        Ok((movements, total))
+    }
+
+    // -------------------------------------------------------------------------
+    // REORDER SUGGESTIONS
+    // -------------------------------------------------------------------------
+
+    /// Every SKU (of one home warehouse) with what is available and what
+    /// the ledger shows consumed over the last `lookback_days`: confirmed
+    /// reservations and negative adjustments (see reorder.rs)
+    pub async fn reorder_candidates(
+        &self,
+        lookback_days: i32,
+        warehouse: Option<&str>,
+    ) -> Result<Vec<ReorderCandidate>> {
+        let candidates = sqlx::query_as::<_, ReorderCandidate>(
+            r#"
+            WITH consumed AS (
+                SELECT sku, SUM(-quantity_delta)::int8 AS units
+                FROM stock_movements
+                WHERE movement_type IN ('confirm', 'adjust')
+                  AND quantity_delta < 0
+                  AND created_at > NOW() - make_interval(days => $1)
+                GROUP BY sku
+            )
+            SELECT i.sku, i.name, i.warehouse, i.quantity - i.reserved AS available,
+                   i.low_stock_threshold, COALESCE(c.units, 0) AS consumed,
+                   i.unit_cost::float8 AS unit_cost
+            FROM products i
+            LEFT JOIN consumed c ON c.sku = i.sku
+            WHERE ($2::text IS NULL OR i.warehouse = $2)
+            ORDER BY i.sku
+            "#,
+        )
+        .bind(lookback_days)
+        .bind(warehouse)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch reorder candidates")?;
+
+        Ok(candidates)
+    }
+
+    /// Store one draft purchase order per warehouse, in one transaction
+    pub async fn create_purchase_orders(
+        &self,
+        drafts: &[(String, Vec<PurchaseOrderLine>)],
+    ) -> Result<Vec<PurchaseOrder>> {
+        let mut tx = self.begin().await?;
+
+        let mut orders = Vec::with_capacity(drafts.len());
+        for (warehouse, lines) in drafts {
+            let row = sqlx::query(
+                r#"
+                INSERT INTO purchase_orders (warehouse, status, lines)
+                VALUES ($1, 'draft', $2)
+                RETURNING id, warehouse, status, lines, created_at
+                "#,
+            )
+            .bind(warehouse)
+            .bind(serde_json::to_value(lines)?)
+            .fetch_one(&mut *tx)
+            .await
+            .context("Failed to draft purchase order")?;
+            orders.push(purchase_order_from_row(&row)?);
+        }
+
+        tx.commit().await?;
+        Ok(orders)
+    }
+
+    /// One page of purchase orders, newest first, optionally of one status
+    pub async fn list_purchase_orders(
+        &self,
+        status: Option<&str>,
+        page: i32,
+        per_page: i32,
+    ) -> Result<(Vec<PurchaseOrder>, i64)> {
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, warehouse, status, lines, created_at
+            FROM purchase_orders
+            WHERE ($1::text IS NULL OR status = $1)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(status)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch purchase orders")?;
+        let orders = rows.iter().map(purchase_order_from_row).collect::<Result<Vec<_>>>()?;
+
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM purchase_orders WHERE ($1::text IS NULL OR status = $1)",
+        )
+        .bind(status)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count purchase orders")?;
+
+        Ok((orders, total))
+    }
+
+    // -------------------------------------------------------------------------
+    // ANALYTICS
+    // -------------------------------------------------------------------------
+    // Consumption series over the movement ledger (see analytics.rs). Both
+    // queries return every bucket from `since` to now: the buckets come
+    // from generate_series and the sums are joined onto them.
+
+    /// Units consumed and received per bucket since `since`, of one SKU or
+    /// of all of them
+    pub async fn consumption_series(
+        &self,
+        sku: Option<&str>,
+        bucket: &str,
+        since: chrono::DateTime<Utc>,
+    ) -> Result<Vec<ConsumptionPoint>> {
+        let points = sqlx::query_as::<_, ConsumptionPoint>(
+            r#"
+            WITH buckets AS (
+                SELECT generate_series(date_trunc($1, $2::timestamptz), date_trunc($1, NOW()),
+                                       ('1 ' || $1)::interval) AS start
+            ),
+            moved AS (
+                SELECT date_trunc($1, created_at) AS start,
+                       SUM(-quantity_delta) FILTER (
+                           WHERE movement_type IN ('confirm', 'adjust') AND quantity_delta < 0
+                       ) AS consumed,
+                       SUM(quantity_delta) FILTER (
+                           WHERE movement_type = 'adjust' AND quantity_delta > 0
+                       ) AS received
+                FROM stock_movements
+                WHERE created_at >= $2 AND ($3::text IS NULL OR sku = $3)
+                GROUP BY 1
+            )
+            SELECT b.start, COALESCE(m.consumed, 0)::int8 AS consumed,
+                   COALESCE(m.received, 0)::int8 AS received
+            FROM buckets b
+            LEFT JOIN moved m ON m.start = b.start
+            ORDER BY b.start
+            "#,
+        )
+        .bind(bucket)
+        .bind(since)
+        .bind(sku)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch consumption series")?;
+
+        Ok(points)
+    }
+
+    /// The `limit` SKUs that consumed the most units since `since`, one row
+    /// per SKU and bucket, most consumed first
+    pub async fn top_movers(
+        &self,
+        bucket: &str,
+        since: chrono::DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<TopMoverRow>> {
+        let rows = sqlx::query_as::<_, TopMoverRow>(
+            r#"
+            WITH top AS (
+                SELECT sku, SUM(-quantity_delta)::int8 AS total_consumed
+                FROM stock_movements
+                WHERE created_at >= $2
+                  AND movement_type IN ('confirm', 'adjust')
+                  AND quantity_delta < 0
+                GROUP BY sku
+                ORDER BY total_consumed DESC, sku
+                LIMIT $3
+            ),
+            buckets AS (
+                SELECT generate_series(date_trunc($1, $2::timestamptz), date_trunc($1, NOW()),
+                                       ('1 ' || $1)::interval) AS start
+            ),
+            moved AS (
+                SELECT sku, date_trunc($1, created_at) AS start,
+                       SUM(-quantity_delta) FILTER (
+                           WHERE movement_type IN ('confirm', 'adjust') AND quantity_delta < 0
+                       ) AS consumed,
+                       SUM(quantity_delta) FILTER (
+                           WHERE movement_type = 'adjust' AND quantity_delta > 0
+                       ) AS received
+                FROM stock_movements
+                WHERE created_at >= $2 AND sku IN (SELECT sku FROM top)
+                GROUP BY 1, 2
+            )
+            SELECT t.sku, COALESCE(i.name, '') AS name, t.total_consumed, b.start,
+                   COALESCE(m.consumed, 0)::int8 AS consumed,
+                   COALESCE(m.received, 0)::int8 AS received
+            FROM top t
+            CROSS JOIN buckets b
+            LEFT JOIN moved m ON m.sku = t.sku AND m.start = b.start
+            LEFT JOIN products i ON i.sku = t.sku
+            ORDER BY t.total_consumed DESC, t.sku, b.start
+            "#,
+        )
+        .bind(bucket)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch top movers")?;
+
+        Ok(rows)
+    }
+
+    // -------------------------------------------------------------------------
+    // ITEM REVISIONS
+    // -------------------------------------------------------------------------
+    // Rows are written by a trigger (see migrations/0007_item_revisions.sql);
+    // writers only label them with tag_revisions.
+
+    /// Metadata changes of an item, newest first, optionally of one field
+    pub async fn list_item_revisions(
+        &self,
+        sku: &str,
+        field: Option<&str>,
+        page: i32,
+        per_page: i32,
+    ) -> Result<(Vec<ItemRevision>, i64)> {
+        let offset = (page - 1) * per_page;
+
+        let revisions = sqlx::query_as::<_, ItemRevision>(
+            r#"
+            SELECT id, sku, field, old_value, new_value, source, reason, changed_at
+            FROM item_revisions
+            WHERE sku = $1 AND ($2::text IS NULL OR field = $2)
+            ORDER BY id DESC
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(sku)
+        .bind(field)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch item revisions")?;
+
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM item_revisions WHERE sku = $1 AND ($2::text IS NULL OR field = $2)",
+        )
+        .bind(sku)
+        .bind(field)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count item revisions")?;
+
+        Ok((revisions, total))
+    }
+
+    /// Set a field back to the value it had before a revision
+    ///
+    /// Only that field changes; later changes to other fields stay. The
+    /// revert is itself recorded as a revision (source "revert").
+    ///
+    /// # Returns
+    /// - `Ok(None)` if the SKU has no such revision or no longer exists
+    pub async fn revert_item_revision(&self, sku: &str, id: i64) -> Result<Option<InventoryItem>> {
+        let mut tx = self.begin().await?;
+
+        let revision = sqlx::query_as::<_, ItemRevision>(
+            r#"
+            SELECT id, sku, field, old_value, new_value, source, reason, changed_at
+            FROM item_revisions
+            WHERE id = $1 AND sku = $2
+            "#,
+        )
+        .bind(id)
+        .bind(sku)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to fetch item revision")?;
+        let Some(revision) = revision else {
+            return Ok(None);
+        };
+
+        // Column names can't be bound; only these fixed statements run
+        let assignment = match revision.field.as_str() {
+            "name" => "name = $1::jsonb #>> '{}'",
+            "warehouse" => "warehouse = $1::jsonb #>> '{}'",
+            // Chosen by hand, like a threshold set through the API
+            "low_stock_threshold" => {
+                "low_stock_threshold = ($1::jsonb #>> '{}')::int, threshold_manual = TRUE"
+            }
+            "unit_price" => "unit_price = ($1::jsonb #>> '{}')::numeric",
+            other => {
+                return Err(AppError::BadRequest(format!("Field {} can't be reverted", other)).into())
+            }
+        };
+
+        tag_revisions(&mut tx, "revert", Some(&format!("revert of revision {}", id))).await?;
+        let item = sqlx::query_as::<_, InventoryItem>(&format!(
+            r#"
+            UPDATE products
+            SET {}, updated_at = NOW()
+            WHERE sku = $2
+            RETURNING id, sku, name, quantity, reserved, warehouse,
+                      low_stock_threshold, max_per_order, max_reserved_pct, threshold_manual, version,
+                      attributes, image_url, spec_url, unit_price::float8 AS unit_price,
+                      unit_cost::float8 AS unit_cost, (quantity * unit_cost)::float8 AS stock_value,
+                      created_at, updated_at
+            "#,
+            assignment
+        ))
+        .bind(&revision.old_value)
+        .bind(sku)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to revert item revision")?;
+
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    // -------------------------------------------------------------------------
+    // API USAGE
+    // -------------------------------------------------------------------------
+    // Daily per-key counts rolled up from Redis (usage.rs).
+
+    /// Store one day's counts as read from Redis
+    ///
+    /// Counts only ever grow within a day, so a row keeps the larger of the
+    /// stored and new values: rolling up twice, from several replicas, or
+    /// after Redis lost its keys never lowers a total.
+    pub async fn upsert_api_usage(
+        &self,
+        day: chrono::NaiveDate,
+        counts: &[(String, i64, i64)],
+    ) -> Result<()> {
+        if counts.is_empty() {
+            return Ok(());
+        }
+        let key_ids: Vec<&str> = counts.iter().map(|(key_id, _, _)| key_id.as_str()).collect();
+        let requests: Vec<i64> = counts.iter().map(|(_, requests, _)| *requests).collect();
+        let reserved: Vec<i64> = counts.iter().map(|(_, _, reserved)| *reserved).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO api_usage_daily (day, key_id, requests, reserved_units)
+            SELECT $1, key_id, requests, reserved_units
+            FROM UNNEST($2::text[], $3::int8[], $4::int8[])
+                AS counts(key_id, requests, reserved_units)
+            ON CONFLICT (day, key_id) DO UPDATE
+            SET requests = GREATEST(api_usage_daily.requests, EXCLUDED.requests),
+                reserved_units = GREATEST(api_usage_daily.reserved_units, EXCLUDED.reserved_units),
+                updated_at = NOW()
+            "#,
+        )
+        .bind(day)
+        .bind(&key_ids)
+        .bind(&requests)
+        .bind(&reserved)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store API usage")?;
+
+        Ok(())
+    }
+
+    /// Daily usage rows between two days (inclusive), optionally for one key
+    pub async fn list_api_usage(
+        &self,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+        key_id: Option<&str>,
+    ) -> Result<Vec<ApiUsage>> {
+        let rows = sqlx::query_as::<_, ApiUsage>(
+            r#"
+            SELECT day, key_id, requests, reserved_units, updated_at
+            FROM api_usage_daily
+            WHERE day BETWEEN $1 AND $2
+              AND ($3::text IS NULL OR key_id = $3)
+            ORDER BY day, key_id
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .bind(key_id)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list API usage")?;
+
+        Ok(rows)
+    }
+
+    // -------------------------------------------------------------------------
+    // SYNTHETIC LOAD
+    // -------------------------------------------------------------------------
+    // Deliberately wasteful queries used by the load generator (loadgen.rs)
+    // and chaos experiments (chaos.rs), and the traffic generator's SKU
+    // sample (traffic.rs).
+
+    /// Up to `limit` SKUs of the catalog, in random order
+    pub async fn sample_skus(&self, limit: i64) -> Result<Vec<String>> {
+        let skus = sqlx::query_scalar("SELECT sku FROM products ORDER BY random() LIMIT $1")
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to sample SKUs")?;
+
+        Ok(skus)
+    }
+
+    /// Heavy scan: join every inventory row against `rows` generated rows
+    ///
+    /// Goes through the breaker and retries like any other primary read, so
+    /// the load it generates is what the API's queries would see.
+    pub async fn loadgen_scan(&self, rows: i32) -> Result<()> {
+        self.on_primary("loadgen_scan", |pool| async move {
+            sqlx::query(
+                r#"
+                SELECT COUNT(*), SUM(i.quantity * g)
+                FROM products i
+                CROSS JOIN generate_series(1, $1) AS g
+                WHERE i.quantity + g > i.reserved
+                "#,
+            )
+            .bind(rows)
+            .fetch_one(&pool)
+            .await
+        })
+        .await
+        .context("Load generator scan failed")?;
+
+        Ok(())
+    }
+
+    /// Lock a SKU's row and hold the lock, so concurrent callers queue up
+    ///
+    /// The transaction is rolled back; no data changes.
+    pub async fn loadgen_hold_lock(&self, sku: &str, hold: std::time::Duration) -> Result<()> {
+        let mut tx = self.begin().await?;
+
+        sqlx::query("SELECT id FROM products WHERE sku = $1 FOR UPDATE")
+            .bind(sku)
+            .fetch_optional(&mut *tx)
+            .await
+            .context("Load generator lock failed")?
+            .ok_or_else(|| DbError::NotFound(sku.to_string()))?;
+
+        sqlx::query("SELECT pg_sleep($1)")
+            .bind(hold.as_secs_f64())
+            .execute(&mut *tx)
+            .await?;
+
+        tx.rollback().await?;
+        Ok(())
+    }
+
+    /// Hold a primary connection for `delay`, as a slow query would
+    pub async fn chaos_sleep(&self, delay: std::time::Duration) -> Result<()> {
+        sqlx::query("SELECT pg_sleep($1)")
+            .bind(delay.as_secs_f64())
+            .execute(&self.pool)
+            .await
+            .context("Chaos database delay failed")?;
+
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------------
+    // POOL INTROSPECTION
+    // -------------------------------------------------------------------------
+
+    /// Snapshot of the primary's connection pool usage
+    pub fn pool_stats(&self) -> PoolStats {
+        pool_stats(&self.pool)
+    }
+
+    /// Usage of the primary and every replica pool, named like the probes
+    pub fn all_pool_stats(&self) -> Vec<NamedPoolStats> {
+        let mut pools = vec![NamedPoolStats { pool: "primary".to_string(), stats: pool_stats(&self.pool) }];
+        for replica in &self.replicas {
+            pools.push(NamedPoolStats { pool: replica.name.clone(), stats: pool_stats(&replica.pool) });
+        }
+        pools
+    }
+
+    /// Take a connection from the primary and every replica pool and hand
+    /// it back right away
+    pub async fn probe_pools(&self) -> Vec<PoolProbe> {
+        let mut probes = vec![probe_pool("primary", &self.pool).await];
+        for replica in &self.replicas {
+            probes.push(probe_pool(&replica.name, &replica.pool).await);
+        }
+        probes
+    }
 
     // -------------------------------------------------------------------------
     // HEALTH CHECK
@@ -410,3 +4404,729 @@ impl Database {
             .is_ok()
     }
 }
+
+// -----------------------------------------------------------------------------
+// POOL HELPERS
+// -----------------------------------------------------------------------------
+fn pool_stats(pool: &PgPool) -> PoolStats {
+    PoolStats {
+        size: pool.size(),
+        idle: pool.num_idle() as u32,
+        max_connections: pool.options().get_max_connections(),
+    }
+}
+
+async fn probe_pool(name: &str, pool: &PgPool) -> PoolProbe {
+    let start = std::time::Instant::now();
+    let result = pool.acquire().await;
+    let acquire_wait = start.elapsed();
+    PoolProbe {
+        pool: name.to_string(),
+        acquire_wait,
+        result: result.map(drop).context("Failed to acquire a connection"),
+        stats: pool_stats(pool),
+    }
+}
+
+/// Whether a replica failed to answer at all, as opposed to the query
+/// failing: then the primary should be asked instead
+fn replica_unavailable(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_)
+        | sqlx::Error::Protocol(_)
+        | sqlx::Error::PoolTimedOut
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::WorkerCrashed => true,
+        // 08: connection exception, 53: out of resources (too many
+        // connections), 57: shutdown or a query cancelled by a recovery
+        // conflict; 40001 is what some recovery conflicts report
+        sqlx::Error::Database(db) => db.code().is_some_and(|code| {
+            code.starts_with("08") || code.starts_with("53") || code.starts_with("57") || code == "40001"
+        }),
+        _ => false,
+    }
+}
+
+// -----------------------------------------------------------------------------
+// QUERY HELPERS
+// -----------------------------------------------------------------------------
+// Free functions generic over the executor, so the same query can run on the
+// pool or inside an open transaction.
+
+/// Shrink Postgres' own statement timeout to the caller's remaining budget.
+///
+/// Dropping the future alone doesn't stop a query that is already running
+/// (or waiting on a row lock) on the server.
+async fn apply_deadline(tx: &mut PgConnection) -> Result<()> {
+    if let Some(remaining) = deadline::remaining() {
+        let timeout_ms = remaining.as_millis().max(1);
+        sqlx::query(&format!("SET LOCAL statement_timeout = {}", timeout_ms))
+            .execute(&mut *tx)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Commit, or roll back a write that was only previewed (`?dry_run=true`)
+async fn commit_unless_dry_run(tx: Transaction<'_, Postgres>, dry_run: bool) -> Result<()> {
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+/// Say what the item changes of this transaction are, for item_revisions
+/// (read by the record_item_revision trigger; cleared at commit)
+async fn tag_revisions(tx: &mut PgConnection, source: &str, reason: Option<&str>) -> Result<()> {
+    sqlx::query(
+        "SELECT set_config('inventory.revision_source', $1, true), \
+                set_config('inventory.revision_reason', $2, true)",
+    )
+    .bind(source)
+    .bind(reason.unwrap_or(""))
+    .execute(&mut *tx)
+    .await
+    .context("Failed to tag item revisions")?;
+    Ok(())
+}
+
+/// Lock an item for an update made at `expected` version (None: any)
+///
+/// # Returns
+/// - `Ok(false)` when there is no such SKU
+/// - `Err(AppError::VersionMismatch)` when the item has changed since
+async fn lock_at_version(tx: &mut PgConnection, sku: &str, expected: Option<i64>) -> Result<bool> {
+    let version: Option<i64> =
+        sqlx::query_scalar("SELECT version FROM products WHERE sku = $1 FOR UPDATE")
+            .bind(sku)
+            .fetch_optional(&mut *tx)
+            .await
+            .context("Failed to lock item")?;
+
+    match version {
+        Some(version) => {
+            versioning::check(expected, version)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Fetch an item without locking it
+async fn fetch_item<'e, E>(executor: E, sku: &str) -> Result<Option<InventoryItem>>
+where
+    E: PgExecutor<'e>,
+{
+    let item = sqlx::query_as::<_, InventoryItem>(
+        r#"
+        SELECT id, sku, name, quantity, reserved, warehouse,
+               low_stock_threshold, max_per_order, max_reserved_pct, threshold_manual, version,
+               attributes, image_url, spec_url, unit_price::float8 AS unit_price,
+               unit_cost::float8 AS unit_cost, (quantity * unit_cost)::float8 AS stock_value,
+               created_at, updated_at
+        FROM products
+        WHERE sku = $1
+        "#,
+    )
+    .bind(sku)
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(item)
+}
+
+/// Check that `quantity` units of an item may be reserved: the SKU's policy
+/// (anti-hoarding limits) first, then what its home warehouse has
+/// available
+fn check_reservable(item: &InventoryItem, available: i32, quantity: i32) -> Result<()> {
+    if let Err(violation) = item.check_reservation_policy(quantity) {
+        return Err(AppError::ReservationLimit(violation).into());
+    }
+
+    if available < quantity {
+        return Err(DbError::InsufficientStock {
+            available,
+            requested: quantity,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Serialize the steps of one two-phase transaction (also covers its
+/// first prepare, before the row exists)
+async fn lock_commitment(tx: &mut PgConnection, transaction_id: &str) -> Result<()> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('commitment:' || $1))")
+        .bind(transaction_id)
+        .execute(&mut *tx)
+        .await?;
+    Ok(())
+}
+
+/// A commitment, if one exists with this ID (`applied: false`)
+async fn fetch_commitment(
+    tx: &mut PgConnection,
+    transaction_id: &str,
+) -> Result<Option<StockCommitment>> {
+    let row = sqlx::query(
+        r#"
+        SELECT transaction_id, order_id, state, lines, expires_at, created_at, updated_at
+        FROM stock_commitments
+        WHERE transaction_id = $1
+        "#,
+    )
+    .bind(transaction_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to look up commitment")?;
+
+    row.map(|row| commitment_from_row(&row, false)).transpose()
+}
+
+fn commitment_from_row(row: &PgRow, applied: bool) -> Result<StockCommitment> {
+    let state: String = row.try_get("state")?;
+    let lines: serde_json::Value = row.try_get("lines")?;
+    Ok(StockCommitment {
+        transaction_id: row.try_get("transaction_id")?,
+        order_id: row.try_get("order_id")?,
+        state: CommitmentState::parse(&state)
+            .ok_or_else(|| anyhow::anyhow!("Unknown commitment state: {}", state))?,
+        lines: serde_json::from_value(lines).context("Malformed commitment lines")?,
+        expires_at: row.try_get("expires_at")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        applied,
+    })
+}
+
+fn purchase_order_from_row(row: &PgRow) -> Result<PurchaseOrder> {
+    let lines: serde_json::Value = row.try_get("lines")?;
+    Ok(PurchaseOrder {
+        id: row.try_get("id")?,
+        warehouse: row.try_get("warehouse")?,
+        status: row.try_get("status")?,
+        lines: serde_json::from_value(lines).context("Malformed purchase order lines")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+/// Units an order holds on a SKU (0 without a reservation)
+async fn order_holding(tx: &mut PgConnection, order_id: &str, sku: &str) -> Result<i64> {
+    let held: Option<i32> = sqlx::query_scalar(
+        "SELECT held_quantity FROM reservations WHERE order_id = $1 AND sku = $2",
+    )
+    .bind(order_id)
+    .bind(sku)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    Ok(held.map_or(0, i64::from))
+}
+
+/// Take `quantity` units off an order's holding of a SKU, next to the
+/// matching change of its stock row's `reserved`
+///
+/// Once nothing is held the reservation ends with `ending` (released,
+/// expired or confirmed). Never goes below zero; an order without a
+/// reservation row is left alone.
+async fn take_from_holding(
+    tx: &mut PgConnection,
+    order_id: &str,
+    sku: &str,
+    quantity: i32,
+    ending: &str,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE reservations
+        SET held_quantity = GREATEST(held_quantity - $3, 0),
+            status = CASE WHEN held_quantity > $3 THEN 'active' ELSE $4 END,
+            ended_at = CASE WHEN held_quantity > $3 THEN NULL ELSE NOW() END
+        WHERE order_id = $1 AND sku = $2 AND held_quantity > 0
+        "#,
+    )
+    .bind(order_id)
+    .bind(sku)
+    .bind(quantity)
+    .bind(ending)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to update reservation")?;
+
+    Ok(())
+}
+
+/// Record a sweep's lease as the newest writer of its job, inside the
+/// sweep's transaction (see distributed_lock.rs)
+///
+/// The upsert keeps the larger token and holds the job's row until the
+/// transaction ends, so an older holder's write waits for a newer one's
+/// and is then refused.
+///
+/// # Returns
+/// - `Err(StaleLease)` if a newer token has written; roll back
+async fn check_fence(tx: &mut PgConnection, lease: Lease) -> Result<()> {
+    let newest: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO job_fences (name, token)
+        VALUES ($1, $2)
+        ON CONFLICT (name) DO UPDATE
+        SET token = GREATEST(job_fences.token, EXCLUDED.token), updated_at = NOW()
+        RETURNING token
+        "#,
+    )
+    .bind(lease.lock)
+    .bind(lease.token)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to check the job fence")?;
+
+    lease.check_fence(newest)?;
+    Ok(())
+}
+
+/// Reservations with their status, for list_reservations: the `listed` CTE,
+/// filtered by $1 sku, $2 order ID and $3/$4 creation time
+const LISTED_RESERVATIONS: &str = r#"
+    WITH listed AS (
+        SELECT reservation_id, order_id, sku, quantity, held_quantity AS held, status,
+               created_at, expires_at, ended_at
+        FROM reservations
+        WHERE ($1::text IS NULL OR sku = $1)
+          AND ($2::text IS NULL OR order_id = $2)
+          AND ($3::timestamptz IS NULL OR created_at >= $3)
+          AND ($4::timestamptz IS NULL OR created_at < $4)
+    )
+"#;
+
+/// Claim the (order, SKU) reservation for `reservation_id`
+///
+/// # Returns
+/// - `Some(existing)` when the order still holds the SKU: the reservation
+///   made the first time, marked as replayed
+/// - `None` when the caller should go on and reserve; the row already
+///   holds `req.quantity` and stays locked until its transaction ends, so
+///   a concurrent repeat waits and then gets this reservation back
+async fn claim_reservation(
+    tx: &mut PgConnection,
+    req: &ReserveStockRequest,
+    reservation_id: &str,
+    created_at: chrono::DateTime<Utc>,
+    expires_at: chrono::DateTime<Utc>,
+) -> Result<Option<ReservationResponse>> {
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO reservations
+            (order_id, sku, reservation_id, quantity, created_at, expires_at, client_id, held_quantity, status)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $4, 'active')
+        ON CONFLICT (order_id, sku) DO NOTHING
+        "#,
+    )
+    .bind(&req.order_id)
+    .bind(&req.sku)
+    .bind(reservation_id)
+    .bind(req.quantity)
+    .bind(created_at)
+    .bind(expires_at)
+    .bind(req.quota.as_ref().map(|quota| &quota.client))
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record reservation")?
+    .rows_affected();
+    if inserted == 1 {
+        return Ok(None);
+    }
+
+    let existing = sqlx::query(
+        r#"
+        SELECT reservation_id, quantity, held_quantity, created_at, expires_at
+        FROM reservations
+        WHERE order_id = $1 AND sku = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(&req.order_id)
+    .bind(&req.sku)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to look up reservation")?;
+
+    let held: i32 = existing.try_get("held_quantity")?;
+    if held > 0 {
+        let quantity: i32 = existing.try_get("quantity")?;
+        if quantity != req.quantity {
+            return Err(DbError::Conflict(format!(
+                "Order {} already reserved {} of {}; release it before reserving a different quantity",
+                req.order_id, quantity, req.sku
+            ))
+            .into());
+        }
+        return Ok(Some(ReservationResponse {
+            reservation_id: existing.try_get("reservation_id")?,
+            sku: req.sku.clone(),
+            quantity,
+            created_at: existing.try_get("created_at")?,
+            expires_at: existing.try_get("expires_at")?,
+            bumped: Vec::new(),
+            replayed: true,
+        }));
+    }
+
+    // The earlier reservation was released, confirmed or expired: this one
+    // takes its place
+    sqlx::query(
+        r#"
+        UPDATE reservations
+        SET reservation_id = $3, quantity = $4, created_at = $5, expires_at = $6, client_id = $7,
+            held_quantity = $4, status = 'active', ended_at = NULL
+        WHERE order_id = $1 AND sku = $2
+        "#,
+    )
+    .bind(&req.order_id)
+    .bind(&req.sku)
+    .bind(reservation_id)
+    .bind(req.quantity)
+    .bind(created_at)
+    .bind(expires_at)
+    .bind(req.quota.as_ref().map(|quota| &quota.client))
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record reservation")?;
+
+    Ok(None)
+}
+
+/// Check a reservation against its client's quota (fairness policy): the
+/// units the client's other orders hold of the SKU, plus the ones
+/// requested (this order's own holding was just claimed for them). Call it
+/// with the SKU locked.
+async fn check_client_share(tx: &mut PgConnection, req: &ReserveStockRequest) -> Result<()> {
+    let Some(quota) = &req.quota else {
+        return Ok(());
+    };
+
+    let held: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(held_quantity), 0)::int8
+        FROM reservations
+        WHERE client_id = $1
+          AND sku = $2
+          AND order_id <> $3
+          AND held_quantity > 0
+        "#,
+    )
+    .bind(&quota.client)
+    .bind(&req.sku)
+    .bind(&req.order_id)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to add up the client's reservations")?;
+
+    if let Err(violation) = quota.check(held, req.quantity) {
+        return Err(AppError::FairShareExceeded(violation).into());
+    }
+    Ok(())
+}
+
+/// Keep an order's hold on a SKU in step with a new reserve
+///
+/// A hard reserve makes the holding hard; a soft one makes it soft at the
+/// request's priority. Rows left from earlier holdings are replaced.
+async fn record_hold(tx: &mut PgConnection, req: &ReserveStockRequest) -> Result<()> {
+    if req.hold == HoldType::Hard {
+        sqlx::query("DELETE FROM reservation_holds WHERE order_id = $1 AND sku = $2")
+            .bind(&req.order_id)
+            .bind(&req.sku)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to record hard hold")?;
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO reservation_holds (order_id, sku, priority)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (order_id, sku) DO UPDATE
+        SET priority = EXCLUDED.priority, updated_at = NOW()
+        "#,
+    )
+    .bind(&req.order_id)
+    .bind(&req.sku)
+    .bind(req.priority)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record soft hold")?;
+
+    Ok(())
+}
+
+/// Insert an audit event
+async fn insert_audit_event<'e, E>(executor: E, event: &NewAuditEvent<'_>) -> Result<()>
+where
+    E: PgExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO audit_events (action, outcome, sku, quantity, reference, detail, channel)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(event.action)
+    .bind(event.outcome)
+    .bind(event.sku)
+    .bind(event.quantity)
+    .bind(event.reference)
+    .bind(event.detail)
+    .bind(event.channel)
+    .execute(executor)
+    .await
+    .context("Failed to record audit event")?;
+
+    Ok(())
+}
+
+/// Append the WHERE clause of an inventory listing; every value is a bind
+/// parameter
+fn push_inventory_filter<'a>(query: &mut QueryBuilder<'a, Postgres>, filter: &InventoryFilter<'a>) {
+    query.push(" WHERE TRUE");
+    if let Some(warehouse) = filter.warehouse {
+        query.push(" AND i.warehouse = ").push_bind(warehouse);
+    }
+    if let Some(prefix) = filter.sku_prefix {
+        query
+            .push(" AND i.sku LIKE ")
+            .push_bind(format!("{}%", escape_like(prefix)));
+    }
+    if let Some(needle) = filter.name_contains {
+        query
+            .push(" AND i.name ILIKE ")
+            .push_bind(format!("%{}%", escape_like(needle)));
+    }
+    if filter.low_stock_only {
+        query.push(
+            r#"
+             AND (i.quantity - i.reserved) < COALESCE(
+                 (SELECT t.threshold FROM warehouse_thresholds t
+                  WHERE t.sku = i.sku AND t.warehouse = i.warehouse),
+                 i.low_stock_threshold)
+            "#,
+        );
+    }
+    if let Some(attributes) = filter.attributes {
+        query.push(" AND i.attributes @> ").push_bind(attributes);
+    }
+}
+
+/// Escape LIKE wildcards so user input only matches literally
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Audit detail of a bulk update change, e.g. "low_stock_threshold 10 -> 12"
+fn describe_change(change: &BulkChange) -> String {
+    let (before, after) = (&change.before, &change.after);
+    let mut parts = Vec::new();
+    if before.low_stock_threshold != after.low_stock_threshold {
+        parts.push(format!(
+            "low_stock_threshold {} -> {}",
+            before.low_stock_threshold, after.low_stock_threshold
+        ));
+    }
+    if before.unit_price != after.unit_price {
+        let price = |p: Option<f64>| p.map_or("none".to_string(), |p| format!("{:.2}", p));
+        parts.push(format!(
+            "unit_price {} -> {}",
+            price(before.unit_price),
+            price(after.unit_price)
+        ));
+    }
+    if before.warehouse != after.warehouse {
+        parts.push(format!("warehouse {} -> {}", before.warehouse, after.warehouse));
+    }
+    parts.join("; ")
+}
+
+/// Audit detail of an item update: the fields that changed
+fn describe_update(before: &InventoryItem, after: &InventoryItem) -> String {
+    let mut parts = Vec::new();
+    if before.name != after.name {
+        parts.push(format!("name {:?} -> {:?}", before.name, after.name));
+    }
+    if before.warehouse != after.warehouse {
+        parts.push(format!("warehouse {} -> {}", before.warehouse, after.warehouse));
+    }
+    if before.low_stock_threshold != after.low_stock_threshold {
+        parts.push(format!(
+            "low_stock_threshold {} -> {}",
+            before.low_stock_threshold, after.low_stock_threshold
+        ));
+    }
+    parts.join("; ")
+}
+
+/// Stock of a SKU in each warehouse holding some, home warehouse first
+///
+/// Empty when the SKU doesn't exist.
+async fn stock_by_warehouse<'e, E>(executor: E, sku: &str) -> Result<Vec<WarehouseStock>>
+where
+    E: PgExecutor<'e>,
+{
+    let warehouses = sqlx::query_as::<_, WarehouseStock>(
+        r#"
+        SELECT s.warehouse, s.quantity, s.reserved, s.quantity - s.reserved AS available,
+               s.warehouse = p.warehouse AS home
+        FROM stock s
+        JOIN products p ON p.sku = s.sku
+        WHERE s.sku = $1 AND (s.quantity > 0 OR s.warehouse = p.warehouse)
+        ORDER BY home DESC, s.warehouse ASC
+        "#,
+    )
+    .bind(sku)
+    .fetch_all(executor)
+    .await
+    .context("Failed to fetch stock per warehouse")?;
+
+    Ok(warehouses)
+}
+
+/// Units of a SKU its home warehouse has on hand and reserved, as
+/// (quantity, reserved)
+async fn home_stock<'e, E>(executor: E, sku: &str) -> Result<Option<(i32, i32)>>
+where
+    E: PgExecutor<'e>,
+{
+    let stock = sqlx::query_as(
+        r#"
+        SELECT s.quantity, s.reserved
+        FROM stock s
+        JOIN products p ON p.sku = s.sku AND p.warehouse = s.warehouse
+        WHERE s.sku = $1
+        "#,
+    )
+    .bind(sku)
+    .fetch_optional(executor)
+    .await
+    .context("Failed to fetch home warehouse stock")?;
+
+    Ok(stock)
+}
+
+/// Units of a SKU its home warehouse can still reserve (0 without stock
+/// there)
+async fn home_available(tx: &mut PgConnection, sku: &str) -> Result<i32> {
+    let (quantity, reserved) = home_stock(&mut *tx, sku).await?.unwrap_or_default();
+    Ok(quantity - reserved)
+}
+
+/// Change a SKU's stock at `warehouse` (the home warehouse when None) by
+/// `quantity_delta` units on hand and `reserved_delta` reserved
+///
+/// The product row is locked first, in the same statement, so every writer
+/// of a SKU's stock locks product, then stock row (see migration 0018). The
+/// stock_totals trigger carries the change over to the product's totals.
+///
+/// # Returns
+/// - The warehouse changed, or `None` when the SKU has no stock there or
+///   the change would leave it with more reserved than on hand (or a
+///   negative count)
+async fn change_stock(
+    tx: &mut PgConnection,
+    sku: &str,
+    warehouse: Option<&str>,
+    quantity_delta: i32,
+    reserved_delta: i32,
+) -> Result<Option<String>> {
+    let changed = sqlx::query_scalar(
+        r#"
+        WITH product AS (
+            SELECT sku, warehouse FROM products WHERE sku = $1 FOR NO KEY UPDATE
+        )
+        UPDATE stock s
+        SET quantity = s.quantity + $3, reserved = s.reserved + $4, updated_at = NOW()
+        FROM product p
+        WHERE s.sku = p.sku AND s.warehouse = COALESCE($2, p.warehouse)
+          AND s.reserved + $4 >= 0 AND s.quantity + $3 >= s.reserved + $4
+        RETURNING s.warehouse
+        "#,
+    )
+    .bind(sku)
+    .bind(warehouse)
+    .bind(quantity_delta)
+    .bind(reserved_delta)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to change stock")?;
+
+    Ok(changed)
+}
+
+/// Give back `quantity` units an order holds of a SKU, at the warehouse its
+/// reservation took them from (home without a reservation row). `ship`
+/// takes them off the shelf too (a confirm) instead of making them
+/// available again.
+///
+/// # Returns
+/// - The warehouse, or `None` when it doesn't hold that many reserved units
+async fn give_back_held(
+    tx: &mut PgConnection,
+    order_id: &str,
+    sku: &str,
+    quantity: i32,
+    ship: bool,
+) -> Result<Option<String>> {
+    let warehouse: Option<String> = sqlx::query_scalar(
+        "SELECT warehouse FROM reservations WHERE order_id = $1 AND sku = $2",
+    )
+    .bind(order_id)
+    .bind(sku)
+    .fetch_optional(&mut *tx)
+    .await?
+    .flatten();
+
+    let shipped = if ship { -quantity } else { 0 };
+    change_stock(&mut *tx, sku, warehouse.as_deref(), shipped, -quantity).await
+}
+
+/// Record a stock movement
+///
+/// Call it after the UPDATE, in the same transaction: the "after" columns
+/// are read from the row as the transaction sees it.
+async fn insert_stock_movement<'e, E>(executor: E, movement: &NewStockMovement<'_>) -> Result<()>
+where
+    E: PgExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO stock_movements
+            (movement_id, sku, movement_type, quantity_delta, reserved_delta,
+             quantity_after, reserved_after, reference, warehouse)
+        SELECT $1, sku, $3, $4, $5, quantity, reserved, $6, COALESCE($7, warehouse)
+        FROM products
+        WHERE sku = $2
+        "#,
+    )
+    .bind(movement.movement_id)
+    .bind(movement.sku)
+    .bind(movement.movement_type)
+    .bind(movement.quantity_delta)
+    .bind(movement.reserved_delta)
+    .bind(movement.reference)
+    .bind(movement.warehouse)
+    .execute(executor)
+    .await
+    .context("Failed to record stock movement")?;
+
+    Ok(())
+}
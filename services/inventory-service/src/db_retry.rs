@@ -0,0 +1,265 @@
+// =============================================================================
+// DATABASE RETRY MODULE
+// =============================================================================
+// Retries of transient Postgres failures, and a circuit breaker that stops
+// sending work to a primary that keeps failing.
+//
+// WHY:
+// A primary failover or a brief network blip used to reach clients as
+// 500s. Most of these are over within a second: a retry a few milliseconds
+// later succeeds. When the outage lasts, retrying every request only adds
+// load and latency; the breaker answers 503 right away instead, with a
+// Retry-After.
+//
+// WHAT IS RETRIED (DB_MAX_RETRIES, default 2; DB_RETRY_BACKOFF_BASE_MS,
+// default 50, doubling with jitter):
+// - Starting a transaction (Database::begin), which every write goes
+//   through: nothing has run yet, so running it again is safe
+// - Reads on the primary (the replica read fallback, get_by_sku)
+// - Only transient errors: lost or refused connections, the server
+//   shutting down or starting up, too many connections. Constraint
+//   violations and the like fail at once.
+// A read or transaction that still fails is 503 SERVICE_UNAVAILABLE.
+//
+// CIRCUIT BREAKER (DB_BREAKER_THRESHOLD, default 5; DB_BREAKER_OPEN_SECS,
+// default 10):
+//   closed ──(THRESHOLD transient failures in a row)──▶ open
+//   open ──(OPEN_SECS later)──▶ half-open: one request is let through
+//   half-open ──(it succeeds)──▶ closed,  ──(it fails)──▶ open again
+// While open, database calls fail fast with 503. A pool timeout (every
+// connection busy for 5s) counts as a failure but isn't retried: the
+// database is overloaded, and retrying would make it worse.
+//
+// METRICS:
+// - db_retries_total{operation}           retries (begin, read)
+// - db_circuit_breaker_state              0 closed, 1 half-open, 2 open
+// - db_circuit_breaker_rejections_total   calls failed fast while open
+//
+// LEARNING NOTES:
+// - A transaction that fails halfway isn't retried: it may need a fresh
+//   decision (is there still stock?), which only the caller can make.
+//   That is also where serialization failures and deadlocks happen, so
+//   they aren't in the transient list.
+// - Single statements that write outside a transaction aren't retried
+//   either: after a lost connection nobody knows whether they ran
+// - Health checks and pool probes bypass the breaker, so /ready keeps
+//   reporting the database itself
+// =============================================================================
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::metrics;
+
+// -----------------------------------------------------------------------------
+// CONFIGURATION
+// -----------------------------------------------------------------------------
+/// How transient failures are retried and when the breaker opens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 = no retries)
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles for each one after it
+    pub backoff_base: Duration,
+    /// Transient failures in a row that open the breaker (0 = no breaker)
+    pub breaker_threshold: u32,
+    /// How long the breaker stays open before letting a request through
+    pub breaker_open_for: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            backoff_base: Duration::from_millis(50),
+            breaker_threshold: 5,
+            breaker_open_for: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Errors worth retrying: the database couldn't be reached, so the
+/// statement didn't take effect
+pub fn is_transient(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::WorkerCrashed => true,
+        // 08: connection exception, 53300: too many connections,
+        // 57P01-57P03: shutting down / starting up
+        sqlx::Error::Database(db) => db
+            .code()
+            .is_some_and(|code| code.starts_with("08") || matches!(code.as_ref(), "53300" | "57P01" | "57P02" | "57P03")),
+        _ => false,
+    }
+}
+
+/// Errors that count against the breaker: transient ones and pool timeouts
+pub fn is_failure(e: &sqlx::Error) -> bool {
+    is_transient(e) || matches!(e, sqlx::Error::PoolTimedOut)
+}
+
+// -----------------------------------------------------------------------------
+// CIRCUIT BREAKER
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    /// One trial request is out since `since`
+    HalfOpen { since: Instant },
+}
+
+impl State {
+    /// Value of the db_circuit_breaker_state gauge
+    fn gauge(self) -> f64 {
+        match self {
+            State::Closed { .. } => 0.0,
+            State::HalfOpen { .. } => 1.0,
+            State::Open { .. } => 2.0,
+        }
+    }
+}
+
+/// Breaker in front of the primary pool
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    open_for: Duration,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    pub fn new(policy: &RetryPolicy) -> Self {
+        Self {
+            threshold: policy.breaker_threshold,
+            open_for: policy.breaker_open_for,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// May a call go ahead? `Err` holds how long until the breaker lets
+    /// one through again.
+    pub fn allow(&self) -> Result<(), Duration> {
+        if self.threshold == 0 {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let mut state = self.state.lock().expect("breaker lock poisoned");
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } if now < until => {
+                metrics::record_db_breaker_rejection();
+                Err(until - now)
+            }
+            // The trial went missing (its request was cancelled); try again
+            State::HalfOpen { since } if now < since + self.open_for => {
+                metrics::record_db_breaker_rejection();
+                Err(since + self.open_for - now)
+            }
+            State::Open { .. } | State::HalfOpen { .. } => {
+                self.set(&mut state, State::HalfOpen { since: now });
+                Ok(())
+            }
+        }
+    }
+
+    /// The database answered (even if with an error of the query's own)
+    pub fn record_success(&self) {
+        if self.threshold == 0 {
+            return;
+        }
+        let mut state = self.state.lock().expect("breaker lock poisoned");
+        if *state != (State::Closed { failures: 0 }) {
+            if !matches!(*state, State::Closed { .. }) {
+                tracing::info!("Database circuit breaker closed");
+            }
+            self.set(&mut state, State::Closed { failures: 0 });
+        }
+    }
+
+    /// The database couldn't be reached (see is_failure)
+    pub fn record_failure(&self) {
+        if self.threshold == 0 {
+            return;
+        }
+        let mut state = self.state.lock().expect("breaker lock poisoned");
+        let next = match *state {
+            State::Closed { failures } if failures + 1 < self.threshold => State::Closed {
+                failures: failures + 1,
+            },
+            State::Open { until } => State::Open { until },
+            _ => {
+                tracing::warn!(
+                    open_for_secs = self.open_for.as_secs(),
+                    "Database circuit breaker opened"
+                );
+                State::Open {
+                    until: Instant::now() + self.open_for,
+                }
+            }
+        };
+        self.set(&mut state, next);
+    }
+
+    fn set(&self, state: &mut State, next: State) {
+        *state = next;
+        metrics::set_db_breaker_state(next.gauge());
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(threshold: u32, open_for: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(&RetryPolicy {
+            breaker_threshold: threshold,
+            breaker_open_for: open_for,
+            ..RetryPolicy::default()
+        })
+    }
+
+    #[test]
+    fn test_breaker_opens_after_threshold_and_recovers() {
+        let breaker = breaker(3, Duration::from_millis(20));
+
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success(); // resets the count
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(breaker.allow().is_ok());
+        breaker.record_failure();
+        assert!(breaker.allow().is_err());
+
+        // Half-open: one trial, the others still fail fast
+        std::thread::sleep(Duration::from_millis(25));
+        assert!(breaker.allow().is_ok());
+        assert!(breaker.allow().is_err());
+
+        // The trial fails: open again; then succeeds: closed
+        breaker.record_failure();
+        assert!(breaker.allow().is_err());
+        std::thread::sleep(Duration::from_millis(25));
+        assert!(breaker.allow().is_ok());
+        breaker.record_success();
+        assert!(breaker.allow().is_ok());
+        assert!(breaker.allow().is_ok());
+    }
+
+    #[test]
+    fn test_disabled_breaker_and_error_classes() {
+        let off = breaker(0, Duration::from_secs(10));
+        for _ in 0..10 {
+            off.record_failure();
+        }
+        assert!(off.allow().is_ok());
+
+        assert!(is_transient(&sqlx::Error::Io(std::io::ErrorKind::ConnectionReset.into())));
+        assert!(!is_transient(&sqlx::Error::PoolTimedOut));
+        assert!(is_failure(&sqlx::Error::PoolTimedOut));
+        assert!(!is_failure(&sqlx::Error::RowNotFound));
+    }
+}
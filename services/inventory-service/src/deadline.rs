@@ -0,0 +1,271 @@
+// =============================================================================
+// DEADLINE MODULE
+// =============================================================================
+// This module propagates request deadlines from upstream callers.
+//
+// WHY DEADLINES MATTER:
+// In a call chain (frontend → order-service → inventory-service), the caller
+// usually gives up after some timeout. If we keep working after that point,
+// we burn DB connections and Redis round-trips on a response nobody reads.
+//
+// SUPPORTED HEADERS:
+// - X-Request-Deadline: absolute deadline as Unix epoch milliseconds
+//   Example: X-Request-Deadline: 1760610000000
+// - grpc-timeout: relative budget in gRPC wire format (value + unit)
+//   Example: grpc-timeout: 250m  (250 milliseconds)
+//
+// LEARNING NOTES:
+// - The effective budget is min(header budget, REQUEST_TIMEOUT_MS)
+// - The whole handler future runs under tokio::time::timeout_at, so when the
+//   deadline passes every in-flight DB/Redis call is dropped with it
+// - The deadline is stored in a task-local so lower layers can ask how much
+//   time is left via `remaining()`. Postgres transactions shrink their
+//   statement_timeout to it (db.rs, Database::begin); Redis commands sent
+//   through `redis()` give up when it passes, as if Redis were down, so
+//   the caches fall through instead of holding the request.
+// - gRPC calls (grpc/) get the same budget from their grpc-timeout metadata
+// =============================================================================
+
+use axum::{
+    extract::{Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use redis::aio::{ConnectionLike, ConnectionManager};
+use redis::{Cmd, ErrorKind, Pipeline, RedisFuture, RedisResult, Value};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::Instant;
+
+use crate::error::AppError;
+use crate::AppState;
+
+/// Absolute deadline header (Unix epoch milliseconds)
+pub const DEADLINE_HEADER: &str = "x-request-deadline";
+
+/// Relative timeout header in gRPC format
+pub const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+// -----------------------------------------------------------------------------
+// TASK-LOCAL DEADLINE
+// -----------------------------------------------------------------------------
+// tokio::task_local! gives every request task its own copy of the deadline
+// without threading it through every function signature.
+tokio::task_local! {
+    static DEADLINE: Deadline;
+}
+
+#[derive(Clone, Copy)]
+struct Deadline {
+    at: Instant,
+    budget: Duration,
+}
+
+/// Time left before the current request's deadline.
+///
+/// Returns `None` when called outside a request (e.g. background tasks).
+pub fn remaining() -> Option<Duration> {
+    DEADLINE
+        .try_with(|deadline| deadline.at.saturating_duration_since(Instant::now()))
+        .ok()
+}
+
+/// The whole budget the current request started with, `None` outside one
+pub fn budget() -> Option<Duration> {
+    DEADLINE.try_with(|deadline| deadline.budget).ok()
+}
+
+// -----------------------------------------------------------------------------
+// HEADER PARSING
+// -----------------------------------------------------------------------------
+
+/// Parse a gRPC-style timeout value such as "250m" or "2S".
+///
+/// Units: H (hours), M (minutes), S (seconds), m (millis), u (micros), n (nanos).
+/// The gRPC spec limits the value to at most 8 digits.
+pub fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    if value.len() < 2 {
+        return None;
+    }
+    let (digits, unit) = value.split_at(value.len() - 1);
+    if digits.len() > 8 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let amount: u64 = digits.parse().ok()?;
+
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 3600)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// Convert an absolute epoch-millisecond deadline into a remaining budget.
+///
+/// A deadline already in the past yields `Duration::ZERO`.
+pub fn budget_from_epoch_millis(deadline_ms: u64, now: SystemTime) -> Duration {
+    let now_ms = now
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    Duration::from_millis(deadline_ms.saturating_sub(now_ms))
+}
+
+/// Extract the caller's budget from request headers, if any.
+///
+/// X-Request-Deadline wins over grpc-timeout when both are present.
+/// Malformed values are ignored rather than rejected.
+pub fn budget_from_headers(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(deadline_ms) = headers
+        .get(DEADLINE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+    {
+        return Some(budget_from_epoch_millis(deadline_ms, SystemTime::now()));
+    }
+
+    headers
+        .get(GRPC_TIMEOUT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_grpc_timeout(v.trim()))
+}
+
+/// The caller's budget capped at `configured`, or `configured` without one
+pub fn effective_budget(headers: &HeaderMap, configured: Duration) -> Duration {
+    match budget_from_headers(headers) {
+        Some(header_budget) => header_budget.min(configured),
+        None => configured,
+    }
+}
+
+/// Run `future` with a deadline `budget` from now, visible to `remaining()`
+///
+/// Returns `None` (and drops the future) once the deadline passes. Used by
+/// the middleware below and by the gRPC server.
+pub async fn run_within<F: Future>(budget: Duration, future: F) -> Option<F::Output> {
+    let at = Instant::now() + budget;
+    DEADLINE
+        .scope(Deadline { at, budget }, tokio::time::timeout_at(at, future))
+        .await
+        .ok()
+}
+
+// -----------------------------------------------------------------------------
+// REDIS
+// -----------------------------------------------------------------------------
+/// A Redis connection whose commands fail once the current request's
+/// deadline passes (see `redis()`)
+#[derive(Clone)]
+pub struct BoundedRedis(ConnectionManager);
+
+/// `conn` with every command bounded by `remaining()`; outside a request
+/// the commands wait as long as they take
+pub fn redis(conn: &ConnectionManager) -> BoundedRedis {
+    BoundedRedis(conn.clone())
+}
+
+async fn bounded<T>(call: impl Future<Output = RedisResult<T>>) -> RedisResult<T> {
+    match remaining() {
+        Some(left) => tokio::time::timeout(left, call)
+            .await
+            .unwrap_or_else(|_| Err((ErrorKind::IoError, "Request deadline exceeded").into())),
+        None => call.await,
+    }
+}
+
+impl ConnectionLike for BoundedRedis {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        Box::pin(bounded(self.0.req_packed_command(cmd)))
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        pipeline: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        Box::pin(bounded(self.0.req_packed_commands(pipeline, offset, count)))
+    }
+
+    fn get_db(&self) -> i64 {
+        self.0.get_db()
+    }
+}
+
+// =============================================================================
+// MIDDLEWARE
+// =============================================================================
+/// Run the request under the effective deadline.
+///
+/// - Budget already exhausted → 504 without touching the handler
+/// - Handler still running when the deadline passes → 504, work is cancelled
+pub async fn propagate_deadline(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let configured = Duration::from_millis(state.config.request_timeout_ms);
+    let budget = effective_budget(request.headers(), configured);
+    let budget_ms = budget.as_millis() as u64;
+
+    // Abort early: the caller has already given up on us
+    if budget.is_zero() {
+        tracing::warn!(path = %request.uri().path(), "Request arrived past its deadline");
+        return AppError::DeadlineExceeded(budget_ms).into_response();
+    }
+
+    let path = request.uri().path().to_string();
+
+    match run_within(budget, next.run(request)).await {
+        Some(response) => response,
+        None => {
+            tracing::warn!(path = %path, budget_ms, "Request deadline exceeded");
+            AppError::DeadlineExceeded(budget_ms).into_response()
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        assert_eq!(parse_grpc_timeout("250m"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_grpc_timeout("2S"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_grpc_timeout("1H"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_grpc_timeout("100"), None);
+        assert_eq!(parse_grpc_timeout("123456789m"), None);
+        assert_eq!(parse_grpc_timeout("m"), None);
+    }
+
+    #[tokio::test]
+    async fn test_redis_calls_bounded_by_deadline() {
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok::<_, redis::RedisError>(1)
+        };
+        let result = run_within(Duration::from_millis(50), bounded(slow)).await;
+        // The command gave up, not the request
+        assert!(matches!(result, Some(Err(e)) if e.kind() == ErrorKind::IoError));
+
+        let quick = async { Ok::<_, redis::RedisError>(1) };
+        assert_eq!(bounded(quick).await.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_budget_from_epoch_millis() {
+        let now = UNIX_EPOCH + Duration::from_millis(10_000);
+        assert_eq!(budget_from_epoch_millis(10_500, now), Duration::from_millis(500));
+        assert_eq!(budget_from_epoch_millis(9_000, now), Duration::ZERO);
+    }
+}
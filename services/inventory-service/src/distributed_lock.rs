@@ -0,0 +1,273 @@
+// =============================================================================
+// DISTRIBUTED LOCK MODULE
+// =============================================================================
+// Leases in Redis that let exactly one replica run a job every replica
+// starts, like the reservation expiry sweeper or startup cache warming.
+//
+// HOW:
+// - A lease is the key `lock:<name>`, set with SET NX PX to
+//   "<owner>:<token>". The owner identifies the process (HOSTNAME plus a
+//   random suffix); the token comes from INCR on `lock:<name>:fence`, so
+//   every new holder gets a larger token than the one before.
+// - acquire() takes a free lease, renews one its process already holds
+//   (same token) and otherwise returns None: another replica has it
+// - Periodic jobs acquire on every tick with a lease of a few intervals,
+//   so the job stays on one replica while it is alive and moves to
+//   another within a lease once it isn't
+//
+// METRICS:
+// - distributed_lock_acquisitions_total{lock, outcome}: acquired (new
+//   token), renewed, contended (held elsewhere), error (Redis failed)
+// - distributed_lock_held{lock}: 1 while this replica holds the lease
+//
+// LEARNING NOTES:
+// - Fencing tokens: a holder that stalls past its lease (GC pause, slow
+//   sweep) may find the lease taken by a newer token. Its next acquire()
+//   returns None, but writes it already started would still land. So the
+//   sweeps pass their lease to the store, which records the token in
+//   `job_fences` inside each write transaction and rolls the write back
+//   (StaleLease) once a newer token has written; the sweep then stops.
+// - The fence counter lives in Redis and can be lost (FLUSHALL, a Redis
+//   without persistence, eviction under an allkeys-* maxmemory policy).
+//   Tokens then start over below what `job_fences` holds and the next
+//   write is refused as stale. The sweep calls resync() with the refusal:
+//   it raises the counter to the newest token written and drops this
+//   process's lease, so the next tick takes a token above it. A lease
+//   that really moved on is left alone (the counter is already past it).
+// - A Redis failure counts as not holding the lease: the job skips a tick
+//   rather than run on every replica at once
+// - Periodic jobs never release: on shutdown or a crash the lease runs
+//   out on its own. One-off jobs (cache warming) release when done.
+// =============================================================================
+
+use redis::aio::ConnectionManager;
+use redis::Script;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::metrics;
+
+/// Takes or renews a lease
+/// KEYS[1] = lease, KEYS[2] = fence counter, ARGV[1] = owner, ARGV[2] = TTL ms
+/// Returns {token, renewed}, or nil when someone else holds the lease
+const ACQUIRE_LUA: &str = r#"
+local current = redis.call('GET', KEYS[1])
+if current then
+  local owner, token = string.match(current, '^(.*):(%d+)$')
+  if owner == ARGV[1] then
+    redis.call('PEXPIRE', KEYS[1], ARGV[2])
+    return {tonumber(token), 1}
+  end
+  return nil
+end
+local token = redis.call('INCR', KEYS[2])
+if redis.call('SET', KEYS[1], ARGV[1] .. ':' .. token, 'NX', 'PX', ARGV[2]) then
+  return {token, 0}
+end
+return nil
+"#;
+
+/// Deletes a lease if it still holds the given owner and token
+const RELEASE_LUA: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+  return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// After a write was refused as stale: raises the fence counter to the
+/// newest token written, if it is below, and deletes the lease if it still
+/// holds the refused owner and token
+/// KEYS[1] = lease, KEYS[2] = fence counter, ARGV[1] = "<owner>:<token>",
+/// ARGV[2] = newest token
+/// Returns 1 if the counter was raised
+const RESYNC_LUA: &str = r#"
+local raised = 0
+if (tonumber(redis.call('GET', KEYS[2])) or 0) < tonumber(ARGV[2]) then
+  redis.call('SET', KEYS[2], ARGV[2])
+  raised = 1
+end
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+  redis.call('DEL', KEYS[1])
+end
+return raised
+"#;
+
+/// Shortest lease handed to a periodic job
+const MIN_LEASE: Duration = Duration::from_secs(10);
+
+/// A held lease; `token` grows with every change of holder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lease {
+    /// Name of the lock the lease is for
+    pub lock: &'static str,
+    pub token: i64,
+}
+
+impl Lease {
+    /// Check the lease against the newest token its lock has written with
+    ///
+    /// # Returns
+    /// - `Err(StaleLease)` if a newer holder has written since
+    pub fn check_fence(&self, newest: i64) -> Result<(), StaleLease> {
+        if newest > self.token {
+            return Err(StaleLease {
+                lock: self.lock,
+                token: self.token,
+                newest,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A write refused because a newer holder of the lock has written since
+#[derive(Debug, thiserror::Error)]
+#[error("Lease {token} of lock {lock} was taken over by token {newest}")]
+pub struct StaleLease {
+    pub lock: &'static str,
+    pub token: i64,
+    pub newest: i64,
+}
+
+/// One named lock, as seen by this process
+#[derive(Clone)]
+pub struct DistributedLock {
+    redis: ConnectionManager,
+    name: &'static str,
+    ttl: Duration,
+}
+
+impl DistributedLock {
+    pub fn new(redis: ConnectionManager, name: &'static str, ttl: Duration) -> Self {
+        Self { redis, name, ttl }
+    }
+
+    /// A lock for a job that runs every `interval` (see lease_for)
+    pub fn periodic(redis: ConnectionManager, name: &'static str, interval: Duration) -> Self {
+        Self::new(redis, name, lease_for(interval))
+    }
+
+    /// Take or renew the lease
+    ///
+    /// # Returns
+    /// - `Some(lease)` while this process holds it
+    /// - `None` when another replica does, or Redis couldn't be asked
+    pub async fn acquire(&self) -> Option<Lease> {
+        let result: redis::RedisResult<Option<(i64, i64)>> = Script::new(ACQUIRE_LUA)
+            .key(key(self.name))
+            .key(fence_key(self.name))
+            .arg(owner_id())
+            .arg(self.ttl.as_millis() as u64)
+            .invoke_async(&mut self.redis.clone())
+            .await;
+
+        let (outcome, lease) = match result {
+            Ok(Some((token, 1))) => ("renewed", Some(Lease { lock: self.name, token })),
+            Ok(Some((token, _))) => ("acquired", Some(Lease { lock: self.name, token })),
+            Ok(None) => ("contended", None),
+            Err(e) => {
+                tracing::warn!(lock = self.name, error = %e, "Distributed lock unavailable");
+                ("error", None)
+            }
+        };
+
+        if outcome == "acquired" {
+            tracing::info!(lock = self.name, token = lease.map(|l| l.token), "Lock acquired");
+        }
+        metrics::record_lock_acquisition(self.name, outcome);
+        metrics::set_lock_held(self.name, lease.is_some());
+        lease
+    }
+
+    /// Give the lease up before it runs out, unless it has moved on
+    pub async fn release(&self, lease: Lease) {
+        let result: redis::RedisResult<i64> = Script::new(RELEASE_LUA)
+            .key(key(self.name))
+            .arg(format!("{}:{}", owner_id(), lease.token))
+            .invoke_async(&mut self.redis.clone())
+            .await;
+        if let Err(e) = result {
+            tracing::warn!(lock = self.name, error = %e, "Failed to release distributed lock");
+        }
+        metrics::set_lock_held(self.name, false);
+    }
+
+    /// Recover from a write refused with `stale`
+    ///
+    /// Only does something when the fence counter fell behind the tokens
+    /// already written (see the learning notes); the next acquire() then
+    /// hands out a token the fence accepts.
+    pub async fn resync(&self, stale: &StaleLease) {
+        let result: redis::RedisResult<i64> = Script::new(RESYNC_LUA)
+            .key(key(self.name))
+            .key(fence_key(self.name))
+            .arg(format!("{}:{}", owner_id(), stale.token))
+            .arg(stale.newest)
+            .invoke_async(&mut self.redis.clone())
+            .await;
+        match result {
+            Ok(1) => {
+                tracing::warn!(
+                    lock = self.name,
+                    newest = stale.newest,
+                    "Fence counter was behind the tokens written (lost from Redis?), raised it"
+                );
+                metrics::set_lock_held(self.name, false);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(lock = self.name, error = %e, "Failed to resync fence counter"),
+        }
+    }
+}
+
+/// Lease length for a job that runs every `interval`: three intervals, so
+/// one slow or missed tick doesn't hand the job over
+pub fn lease_for(interval: Duration) -> Duration {
+    (interval * 3).max(MIN_LEASE)
+}
+
+/// This process, as recorded in the leases it holds
+fn owner_id() -> &'static str {
+    static OWNER: OnceLock<String> = OnceLock::new();
+    OWNER.get_or_init(|| {
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string());
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        format!("{}-{}", host, &suffix[..8])
+    })
+}
+
+fn key(name: &str) -> String {
+    format!("lock:{}", name)
+}
+
+fn fence_key(name: &str) -> String {
+    format!("lock:{}:fence", name)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lease_for_interval() {
+        assert_eq!(lease_for(Duration::from_secs(60)), Duration::from_secs(180));
+        assert_eq!(lease_for(Duration::from_secs(1)), MIN_LEASE);
+        assert_eq!(owner_id(), owner_id());
+        assert_ne!(key("sweeper"), fence_key("sweeper"));
+    }
+
+    #[test]
+    fn test_stale_lease_is_fenced_off() {
+        let lease = Lease { lock: "sweeper", token: 7 };
+        assert!(lease.check_fence(7).is_ok());
+        // First write of a new holder: the stored token is still the old one
+        assert!(lease.check_fence(6).is_ok());
+
+        let stale = lease.check_fence(8).unwrap_err();
+        assert_eq!((stale.lock, stale.token, stale.newest), ("sweeper", 7, 8));
+    }
+}
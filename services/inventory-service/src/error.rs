@@ -15,13 +15,18 @@
 // =============================================================================
 
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use thiserror::Error;
 
-use crate::models::ErrorResponse;
+use crate::db::DbError;
+use crate::deadline;
+use crate::i18n;
+use crate::problem;
+use crate::models::{ErrorResponse, FieldError};
+use crate::validation;
 
 // =============================================================================
 // CUSTOM ERROR TYPE
@@ -42,12 +47,8 @@ pub enum AppError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
-    // -------------------------------------------------------------------------
-    // REDIS ERRORS
-    // -------------------------------------------------------------------------
-    /// Redis operation failed
-    #[error("Cache error: {0}")]
-    Redis(#[from] redis::RedisError),
+    // Redis errors have no variant on purpose: Redis is only a cache and a
+    // failed call never fails a request (see item_cache.rs)
 
     // -------------------------------------------------------------------------
     // BUSINESS LOGIC ERRORS
@@ -60,10 +61,67 @@ pub enum AppError {
     #[error("Insufficient stock: available {available}, requested {requested}")]
     InsufficientStock { available: i32, requested: i32 },
 
+    /// Reservation exceeds the SKU's policy or a global quantity cap
+    #[error("Reservation limit exceeded: {0}")]
+    ReservationLimit(String),
+
+    /// Reservation exceeds the service-wide per-order or per-client cap
+    /// (fairness policy)
+    #[error("Fair share exceeded: {0}")]
+    FairShareExceeded(String),
+
+    /// The item changed while the request was being handled; retrying
+    /// usually succeeds
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// Two-phase commit step that the transaction's state doesn't allow
+    /// (commit after abort, prepare reusing a finished ID, ...)
+    #[error("Commitment conflict: {0}")]
+    CommitmentConflict(String),
+
+    /// The item changed since the client read it (If-Match or
+    /// expected_version didn't match, see versioning.rs)
+    #[error("Version mismatch: expected {expected}, current {current}")]
+    VersionMismatch { expected: i64, current: i64 },
+
+    /// Creating an item would exceed CATALOG_HARD_LIMIT
+    #[error("Catalog quota exceeded: {0}")]
+    CatalogQuotaExceeded(String),
+
     /// Invalid request data
     #[error("Invalid request: {0}")]
     BadRequest(String),
 
+    /// Request body failed its field checks (see validation.rs)
+    #[error("Validation failed: {}", validation::summary(.0))]
+    Validation(Vec<FieldError>),
+
+    /// Service can't take the request right now (not ready, shedding load)
+    /// Clients should retry after the given number of seconds
+    #[error("Service unavailable: {reason}")]
+    ServiceUnavailable { reason: String, retry_after_secs: u64 },
+
+    /// Request ran out of time (caller deadline or server timeout)
+    #[error("Deadline exceeded after {0}ms")]
+    DeadlineExceeded(u64),
+
+    /// Write or admin request while in public read-only mode
+    #[error("Read-only mode: {0}")]
+    ReadOnly(String),
+
+    /// Endpoint group switched off via DISABLED_ENDPOINTS
+    #[error("Feature disabled: {0}")]
+    FeatureDisabled(String),
+
+    /// Client sent more requests than its rate limit allows
+    #[error("Rate limit exceeded")]
+    RateLimited { retry_after_secs: u64 },
+
+    /// Failure injected on purpose by a chaos experiment (chaos.rs)
+    #[error("Fault injected: {reason}")]
+    FaultInjected { status: StatusCode, reason: String },
+
     // -------------------------------------------------------------------------
     // INTERNAL ERRORS
     // -------------------------------------------------------------------------
@@ -82,10 +140,11 @@ pub enum AppError {
 // This pattern allows clean handler code like:
 //   async fn handler() -> Result<Json<T>, AppError> { ... }
 // Errors are automatically converted to proper HTTP responses.
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        // Determine HTTP status code based on error type
-        let (status, error_code, message) = match &self {
+impl AppError {
+    /// HTTP status, error code and message of this error, as every API
+    /// (REST, GraphQL) reports it
+    pub fn describe(&self) -> (StatusCode, &'static str, String) {
+        match self {
             // 404 Not Found: Resource doesn't exist
             AppError::NotFound(msg) => (
                 StatusCode::NOT_FOUND,
@@ -100,6 +159,14 @@ impl IntoResponse for AppError {
                 msg.clone(),
             ),
 
+            // 422 Unprocessable Entity: Well-formed JSON, but fields are
+            // missing or out of range; `fields` lists every one
+            AppError::Validation(errors) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "VALIDATION_FAILED",
+                validation::summary(errors),
+            ),
+
             // 409 Conflict: Business rule violation (not enough stock)
             AppError::InsufficientStock { available, requested } => (
                 StatusCode::CONFLICT,
@@ -107,6 +174,93 @@ impl IntoResponse for AppError {
                 format!("Available: {}, Requested: {}", available, requested),
             ),
 
+            // 422 Unprocessable Entity: Valid request, but the SKU's policy
+            // forbids it (retrying won't help)
+            AppError::ReservationLimit(msg) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "RESERVATION_LIMIT_EXCEEDED",
+                msg.clone(),
+            ),
+
+            // 422 Unprocessable Entity: The order or the client would hold
+            // more of the SKU than the fairness policy allows
+            AppError::FairShareExceeded(msg) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "FAIR_SHARE_EXCEEDED",
+                msg.clone(),
+            ),
+
+            // 409 Conflict: A concurrent change got in the way; retry
+            AppError::Conflict(msg) => (
+                StatusCode::CONFLICT,
+                "CONFLICT",
+                msg.clone(),
+            ),
+
+            // 409 Conflict: The transaction already went the other way (or
+            // timed out); the caller's coordinator has to sort it out
+            AppError::CommitmentConflict(msg) => (
+                StatusCode::CONFLICT,
+                "COMMITMENT_CONFLICT",
+                msg.clone(),
+            ),
+
+            // 412 Precondition Failed: Someone else changed the item first;
+            // refetch it and decide again
+            AppError::VersionMismatch { expected, current } => (
+                StatusCode::PRECONDITION_FAILED,
+                "VERSION_MISMATCH",
+                format!("Item is at version {}, not {}", current, expected),
+            ),
+
+            // 409 Conflict: The catalog is full; delete SKUs or raise the limit
+            AppError::CatalogQuotaExceeded(msg) => (
+                StatusCode::CONFLICT,
+                "CATALOG_QUOTA_EXCEEDED",
+                msg.clone(),
+            ),
+
+            // 503 Service Unavailable: Temporary, come back later
+            AppError::ServiceUnavailable { reason, .. } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "SERVICE_UNAVAILABLE",
+                reason.clone(),
+            ),
+
+            // 403 Forbidden: Public read-only mode doesn't serve this
+            AppError::ReadOnly(msg) => (
+                StatusCode::FORBIDDEN,
+                "READ_ONLY",
+                msg.clone(),
+            ),
+
+            // 403 Forbidden: This environment has the endpoint switched off
+            AppError::FeatureDisabled(msg) => (
+                StatusCode::FORBIDDEN,
+                "FEATURE_DISABLED",
+                msg.clone(),
+            ),
+
+            // 429 Too Many Requests: Slow down, then retry
+            AppError::RateLimited { .. } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "RATE_LIMITED",
+                "Too many requests, please slow down".to_string(),
+            ),
+
+            // 5xx chosen by a chaos experiment; the code tells it apart from
+            // real failures
+            AppError::FaultInjected { status, reason } => {
+                (*status, "FAULT_INJECTED", reason.clone())
+            }
+
+            // 504 Gateway Timeout: The deadline passed before we finished
+            AppError::DeadlineExceeded(_) => (
+                StatusCode::GATEWAY_TIMEOUT,
+                "DEADLINE_EXCEEDED",
+                "Request deadline exceeded".to_string(),
+            ),
+
             // 500 Internal Server Error: Something went wrong on our side
             // IMPORTANT: Don't expose internal details in production!
             AppError::Database(_) => (
@@ -115,32 +269,89 @@ impl IntoResponse for AppError {
                 "A database error occurred".to_string(),
             ),
 
-            AppError::Redis(_) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "CACHE_ERROR",
-                "A cache error occurred".to_string(),
-            ),
-
             AppError::Internal(msg) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "INTERNAL_ERROR",
                 msg.clone(),
             ),
-        };
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        // Determine HTTP status code based on error type
+        let (status, error_code, message) = self.describe();
 
         // Log the error for debugging
         // In production, this goes to your logging system (Loki)
+        let format = problem::current();
         tracing::error!(
             error_code = error_code,
             message = %message,
+            request_id = format.as_ref().map(|format| format.request_id.as_str()),
             "Request failed"
         );
 
         // Build the JSON response body
-        let body = ErrorResponse::new(error_code, message);
+        let mut body = match &self {
+            AppError::DeadlineExceeded(budget_ms) => ErrorResponse::with_details(
+                error_code,
+                message,
+                format!("budget_ms={}", budget_ms),
+            ),
+            AppError::VersionMismatch { current, .. } => ErrorResponse::with_details(
+                error_code,
+                message,
+                format!("current_version={}", current),
+            ),
+            AppError::Validation(errors) => ErrorResponse {
+                fields: errors.clone(),
+                ..ErrorResponse::new(error_code, message)
+            },
+            _ => ErrorResponse::new(error_code, message),
+        };
+
+        // Localize the message for the caller's Accept-Language. The error
+        // code stays the same; the English text moves to `details`.
+        let lang = i18n::current();
+        if let Some(localized) = i18n::error_message(error_code, lang) {
+            let english = std::mem::replace(&mut body.message, localized.to_string());
+            body.details = Some(match body.details.take() {
+                Some(details) => format!("{} ({})", english, details),
+                None => english,
+            });
+        }
+
+        // Combine status code and body into a response, in the Problem
+        // Details format when the caller asked for it
+        let mut response = match format.filter(|format| format.problem) {
+            Some(format) => {
+                let mut response =
+                    (status, Json(problem::from_error(status, body, &format.request_id))).into_response();
+                response.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(problem::CONTENT_TYPE),
+                );
+                response
+            }
+            None => (status, Json(body)).into_response(),
+        };
+        response.headers_mut().insert(
+            header::CONTENT_LANGUAGE,
+            HeaderValue::from_static(lang.tag()),
+        );
 
-        // Combine status code and body into a response
-        (status, Json(body)).into_response()
+        // Tell well-behaved clients when to come back
+        match &self {
+            AppError::ServiceUnavailable { retry_after_secs, .. }
+            | AppError::RateLimited { retry_after_secs } => {
+                crate::retry_after::set_retry_after(&mut response, *retry_after_secs);
+            }
+            _ => {}
+        }
+
+        response
     }
 }
 
@@ -156,8 +367,48 @@ pub type AppResult<T> = Result<T, AppError>;
 // =============================================================================
 // Sometimes we need to convert between error types.
 
+impl From<DbError> for AppError {
+    fn from(err: DbError) -> Self {
+        match err {
+            DbError::NotFound(sku) => AppError::NotFound(format!("SKU not found: {}", sku)),
+            DbError::InsufficientStock { available, requested } => {
+                AppError::InsufficientStock { available, requested }
+            }
+            DbError::Conflict(msg) => AppError::Conflict(msg),
+        }
+    }
+}
+
+impl AppError {
+    /// The AppError or DbError a db.rs call failed with, or the error
+    /// itself when it is neither (a query error, a bug)
+    pub fn from_db(err: anyhow::Error) -> Result<AppError, anyhow::Error> {
+        match err.downcast::<AppError>() {
+            Ok(app_error) => Ok(app_error),
+            Err(err) => match statement_deadline(&err) {
+                Some(app_error) => Ok(app_error),
+                None => err.downcast::<DbError>().map(AppError::from),
+            },
+        }
+    }
+}
+
+/// A statement Postgres cancelled at the request's deadline (the
+/// statement_timeout Database::begin sets) answers like the deadline
+/// middleware would
+fn statement_deadline(err: &anyhow::Error) -> Option<AppError> {
+    let budget = deadline::budget()?;
+    match err.downcast_ref::<sqlx::Error>()? {
+        // 57014: query_canceled
+        sqlx::Error::Database(db) if db.code().as_deref() == Some("57014") => {
+            Some(AppError::DeadlineExceeded(budget.as_millis() as u64))
+        }
+        _ => None,
+    }
+}
+
 impl From<anyhow::Error> for AppError {
     fn from(err: anyhow::Error) -> Self {
-        AppError::Internal(err.to_string())
+        AppError::from_db(err).unwrap_or_else(|err| AppError::Internal(err.to_string()))
     }
 }
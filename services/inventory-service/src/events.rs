@@ -0,0 +1,324 @@
+// =============================================================================
+// EVENTS MODULE
+// =============================================================================
+// Stock change events published to Kafka for the order and analytics
+// services, as CloudEvents 1.0 (structured JSON mode).
+//
+// EVENTS (`type`):
+// - com.grafana-lab.inventory.stock.reserved     stock reserved for an order
+// - com.grafana-lab.inventory.stock.released     reservation released
+// - com.grafana-lab.inventory.stock.adjusted     quantity adjusted
+// - com.grafana-lab.inventory.stock.low_stock    a (sku, warehouse) dropped
+//                                                to or below its threshold
+// `subject` is the SKU, which is also the message key, so all events of one
+// SKU land on the same partition in order.
+//
+// HOW:
+// - Reserve, release and adjust: a supervised job ("kafka-event-publisher")
+//   follows the audit trail the handlers and db layer write, like the
+//   webhook dispatcher, so REST, gRPC and background jobs are all covered
+// - Low stock: the low-stock evaluator publishes the (sku, warehouse) pairs
+//   that weren't low at its previous evaluation
+//
+// CONFIGURATION:
+// - KAFKA_BROKERS: bootstrap servers, e.g. kafka:9092 (default: unset,
+//   publishing is off)
+// - KAFKA_TOPIC: topic for every event type (default: inventory.stock)
+// - KAFKA_PUBLISH_INTERVAL_MS: how often the audit trail is polled
+//   (default: 1000)
+//
+// LEARNING NOTES:
+// - Audit-based events are at-least-once: the job follows the audit trail
+//   through its outbox queue (audit.rs) and a batch is only marked shipped
+//   once Kafka acknowledged all of it. A failed send stops the job and the
+//   supervisor restarts it with that batch. Consumers dedupe by `id`
+//   ("audit-<audit event id>").
+// - One replica publishes at a time (the outbox claim), so the events of
+//   a SKU reach its partition in the order they were claimed
+// - Low-stock events are best effort: a failed send is logged and counted,
+//   the next crossing of the same SKU is published again
+// - Like the other audit followers, publishing starts with the events
+//   written after the publisher first ran; history is never replayed into
+//   the topic
+// =============================================================================
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rdkafka::config::ClientConfig;
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+
+use crate::audit::AuditFollower;
+use crate::db::Database;
+use crate::metrics;
+use crate::models::{AuditEvent, LowStockAlert};
+
+/// Prefix of every event type
+const TYPE_PREFIX: &str = "com.grafana-lab.inventory.";
+
+/// Audit actions and the event type each one is published as
+pub const EVENT_TYPES: [(&str, &str); 3] = [
+    ("reserve", "stock.reserved"),
+    ("release", "stock.released"),
+    ("adjust", "stock.adjusted"),
+];
+
+/// Event type of a low-stock crossing
+const LOW_STOCK_TYPE: &str = "stock.low_stock";
+
+/// CloudEvents `source` of everything we publish
+const SOURCE: &str = "/services/inventory-service";
+
+/// Content type of a structured-mode CloudEvent (Kafka protocol binding)
+const CONTENT_TYPE: &str = "application/cloudevents+json";
+
+/// Maximum audit events handled per poll
+const PUBLISH_BATCH_SIZE: i64 = 500;
+
+/// How long librdkafka keeps retrying a message before reporting failure
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// -----------------------------------------------------------------------------
+// CONFIGURATION
+// -----------------------------------------------------------------------------
+/// Where events are published
+#[derive(Debug, Clone)]
+pub struct KafkaTarget {
+    pub brokers: String,
+    pub topic: String,
+    pub interval: Duration,
+}
+
+// -----------------------------------------------------------------------------
+// CLOUDEVENT
+// -----------------------------------------------------------------------------
+/// A CloudEvents 1.0 envelope, serialized as the message value
+#[derive(Debug, Clone, Serialize)]
+pub struct CloudEvent {
+    pub specversion: &'static str,
+    pub id: String,
+    pub source: &'static str,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// The SKU
+    pub subject: String,
+    pub time: DateTime<Utc>,
+    pub datacontenttype: &'static str,
+    pub data: serde_json::Value,
+}
+
+impl CloudEvent {
+    fn new(
+        id: String,
+        event_type: &str,
+        sku: &str,
+        time: DateTime<Utc>,
+        data: serde_json::Value,
+    ) -> Self {
+        Self {
+            specversion: "1.0",
+            id,
+            source: SOURCE,
+            event_type: format!("{}{}", TYPE_PREFIX, event_type),
+            subject: sku.to_string(),
+            time,
+            datacontenttype: "application/json",
+            data,
+        }
+    }
+
+    /// Event for an audit entry; `None` for failures and actions that
+    /// aren't published
+    pub fn from_audit(event: &AuditEvent) -> Option<Self> {
+        if event.outcome != "success" {
+            return None;
+        }
+        let (_, event_type) = EVENT_TYPES.iter().find(|(action, _)| *action == event.action)?;
+
+        Some(Self::new(
+            format!("audit-{}", event.id),
+            event_type,
+            &event.sku,
+            event.occurred_at,
+            json!({
+                "sku": event.sku,
+                "quantity": event.quantity,
+                "reference": event.reference,
+                "channel": event.channel,
+            }),
+        ))
+    }
+
+    /// Event for a (sku, warehouse) that just became low on stock
+    pub fn low_stock(alert: &LowStockAlert) -> Self {
+        Self::new(
+            uuid::Uuid::new_v4().to_string(),
+            LOW_STOCK_TYPE,
+            &alert.sku,
+            Utc::now(),
+            json!({
+                "sku": alert.sku,
+                "name": alert.name,
+                "warehouse": alert.warehouse,
+                "available": alert.available,
+                "threshold": alert.threshold,
+            }),
+        )
+    }
+
+    /// Type without the common prefix, for metric labels and logs
+    fn short_type(&self) -> &str {
+        self.event_type.strip_prefix(TYPE_PREFIX).unwrap_or(&self.event_type)
+    }
+}
+
+// =============================================================================
+// PUBLISHER
+// =============================================================================
+/// Kafka producer for one topic; cheap to clone
+#[derive(Clone)]
+pub struct EventPublisher {
+    producer: FutureProducer,
+    topic: String,
+}
+
+impl EventPublisher {
+    /// Create the producer. Brokers are only contacted on the first send, so
+    /// an unreachable Kafka doesn't stop startup.
+    pub fn new(target: &KafkaTarget) -> Result<Self> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &target.brokers)
+            .set("client.id", "inventory-service")
+            .set("message.timeout.ms", MESSAGE_TIMEOUT.as_millis().to_string())
+            // Retries keep per-partition order and don't duplicate
+            .set("enable.idempotence", "true")
+            .create()
+            .context("Failed to create Kafka producer")?;
+
+        Ok(Self {
+            producer,
+            topic: target.topic.clone(),
+        })
+    }
+
+    /// Send one event and wait for Kafka to acknowledge it
+    pub async fn publish(&self, event: &CloudEvent) -> Result<()> {
+        let payload = serde_json::to_vec(event).context("Failed to serialize event")?;
+        let headers = OwnedHeaders::new().insert(Header {
+            key: "content-type",
+            value: Some(CONTENT_TYPE),
+        });
+        let record = FutureRecord::to(&self.topic)
+            .key(&event.subject)
+            .payload(&payload)
+            .headers(headers);
+
+        let result = self.producer.send(record, MESSAGE_TIMEOUT).await;
+        metrics::record_event_published(event.short_type(), result.is_ok());
+
+        result
+            .map(|_| ())
+            .map_err(|(e, _)| e)
+            .with_context(|| format!("Failed to publish event {} to {}", event.id, self.topic))
+    }
+
+    /// Publish low-stock crossings, logging failures instead of returning them
+    pub async fn publish_low_stock(&self, alerts: &[&LowStockAlert]) {
+        for alert in alerts {
+            if let Err(e) = self.publish(&CloudEvent::low_stock(alert)).await {
+                tracing::warn!(sku = %alert.sku, error = %e, "Low stock event not published");
+            }
+        }
+    }
+}
+
+/// Publish new audit events to Kafka every `interval`, forever
+pub async fn run_publisher(db: Database, publisher: EventPublisher, interval: Duration) -> Result<()> {
+    let mut follower = AuditFollower::register(&db, "kafka").await?;
+
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        let Some(claim) = follower.claim(&db, PUBLISH_BATCH_SIZE).await? else {
+            continue;
+        };
+        for event in &claim.events {
+            if let Some(cloud_event) = CloudEvent::from_audit(event) {
+                publisher.publish(&cloud_event).await?;
+            }
+        }
+
+        let count = claim.events.len();
+        claim.finish().await?;
+        if count > 0 {
+            tracing::debug!(count, "Published audit events to Kafka");
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn audit_event(action: &str, outcome: &str) -> AuditEvent {
+        AuditEvent {
+            id: 42,
+            occurred_at: Utc::now(),
+            action: action.to_string(),
+            outcome: outcome.to_string(),
+            sku: "SKU-1".to_string(),
+            quantity: 3,
+            reference: Some("ORD-9".to_string()),
+            detail: None,
+            channel: None,
+        }
+    }
+
+    #[test]
+    fn test_audit_event_envelope() {
+        let event = CloudEvent::from_audit(&audit_event("reserve", "success")).unwrap();
+        let value = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(value["specversion"], "1.0");
+        assert_eq!(value["id"], "audit-42");
+        assert_eq!(value["type"], "com.grafana-lab.inventory.stock.reserved");
+        assert_eq!(value["subject"], "SKU-1");
+        assert_eq!(value["source"], SOURCE);
+        assert_eq!(value["data"]["quantity"], 3);
+        assert_eq!(value["data"]["reference"], "ORD-9");
+        assert_eq!(event.short_type(), "stock.reserved");
+    }
+
+    #[test]
+    fn test_only_successful_published_actions() {
+        assert!(CloudEvent::from_audit(&audit_event("adjust", "success")).is_some());
+        assert!(CloudEvent::from_audit(&audit_event("adjust", "failure")).is_none());
+        assert!(CloudEvent::from_audit(&audit_event("confirm", "success")).is_none());
+    }
+
+    #[test]
+    fn test_low_stock_envelope() {
+        let alert = LowStockAlert {
+            sku: "SKU-2".to_string(),
+            name: "Widget".to_string(),
+            available: 4,
+            threshold: 5,
+            warehouse: "WH-1".to_string(),
+        };
+        let value = serde_json::to_value(CloudEvent::low_stock(&alert)).unwrap();
+
+        assert_eq!(value["type"], "com.grafana-lab.inventory.stock.low_stock");
+        assert_eq!(value["subject"], "SKU-2");
+        assert_eq!(value["data"]["warehouse"], "WH-1");
+        assert_eq!(value["data"]["threshold"], 5);
+    }
+}
@@ -0,0 +1,246 @@
+// =============================================================================
+// FAIR QUEUE MODULE
+// =============================================================================
+// Optional per-SKU FIFO queue in front of reserve_stock.
+//
+// THE PROBLEM:
+// During a flash sale hundreds of reservations hit the same SKU. Each one
+// opens a transaction and waits on the row's FOR UPDATE lock. Postgres gives
+// no ordering guarantee among lock waiters, connections pile up in the pool,
+// and requests fail in random order as pool acquire or statement timeouts fire.
+//
+// THE FIX:
+// Reservations for the same SKU take turns *before* touching the database:
+// - One reservation per SKU is in flight at a time (per instance)
+// - Waiters are served in arrival order (tokio's Semaphore is FIFO-fair)
+// - Waiting is bounded: RESERVE_QUEUE_MAX_WAIT_MS, then 503 + Retry-After
+// - The queue is bounded: beyond RESERVE_QUEUE_MAX_DEPTH, reject right away
+//
+// CONFIGURATION:
+//   RESERVE_QUEUE_ENABLED=true       (default: false)
+//   RESERVE_QUEUE_MAX_WAIT_MS=2000
+//   RESERVE_QUEUE_MAX_DEPTH=100
+//
+// LEARNING NOTES:
+// - The queue is in-process: with several replicas each keeps its own order,
+//   but the DB lock queue per SKU is then at most one waiter per replica
+// - Depth is exported as reservation_queue_depth{sku}, wait time as
+//   reservation_queue_wait_seconds{outcome}
+// - A lane lives while requests are in it: it's created by the first and
+//   dropped by the last, and its depth series leaves /metrics with it.
+//   reserve_stock only queues SKUs that are in the catalog.
+// =============================================================================
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::metrics;
+
+// -----------------------------------------------------------------------------
+// QUEUE
+// -----------------------------------------------------------------------------
+/// Why a reservation didn't get its turn
+#[derive(Debug, thiserror::Error)]
+pub enum QueueRejected {
+    #[error("Too many pending reservations for {sku} ({depth} queued)")]
+    Full { sku: String, depth: usize },
+
+    #[error("Timed out after {waited_ms}ms waiting for a turn to reserve {sku}")]
+    Timeout { sku: String, waited_ms: u64 },
+}
+
+/// One SKU's queue
+struct Lane {
+    /// Single permit: whoever holds it may reserve this SKU
+    turn: Arc<Semaphore>,
+    /// Requests waiting for or holding the permit
+    depth: AtomicUsize,
+}
+
+/// Per-SKU FIFO queues for reservations
+///
+/// There is a lane per SKU with requests waiting or in progress; a lane is
+/// removed when its last request leaves.
+pub struct ReserveQueue {
+    max_wait: Duration,
+    max_depth: usize,
+    lanes: Arc<Mutex<HashMap<String, Arc<Lane>>>>,
+}
+
+impl ReserveQueue {
+    pub fn new(max_wait: Duration, max_depth: usize) -> Self {
+        Self {
+            max_wait,
+            max_depth,
+            lanes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Join `sku`'s lane, creating it if needed
+    ///
+    /// # Returns
+    /// - The slot and the depth including it
+    fn join(&self, sku: &str) -> (Slot, usize) {
+        let mut lanes = self.lanes.lock().expect("reserve queue lock poisoned");
+        let lane = lanes
+            .entry(sku.to_string())
+            .or_insert_with(|| {
+                Arc::new(Lane {
+                    turn: Arc::new(Semaphore::new(1)),
+                    depth: AtomicUsize::new(0),
+                })
+            })
+            .clone();
+        // Counted under the map lock, so a leaving slot can't drop the lane
+        // between our lookup and our increment
+        let depth = lane.depth.fetch_add(1, Ordering::SeqCst) + 1;
+        let slot = Slot {
+            lanes: self.lanes.clone(),
+            lane,
+            sku: sku.to_string(),
+        };
+        (slot, depth)
+    }
+
+    /// SKUs with a lane right now
+    #[cfg(test)]
+    fn lane_count(&self) -> usize {
+        self.lanes.lock().expect("reserve queue lock poisoned").len()
+    }
+
+    /// Wait for this request's turn to reserve `sku`.
+    ///
+    /// Hold the returned `Turn` for the whole reservation; dropping it lets
+    /// the next request in line go.
+    pub async fn enter(&self, sku: &str) -> Result<Turn, QueueRejected> {
+        let start = Instant::now();
+
+        // Count ourselves in; the guard counts us out on every exit path
+        let (slot, depth) = self.join(sku);
+        let lane = slot.lane.clone();
+        metrics::set_reservation_queue_depth(sku, depth);
+
+        if depth > self.max_depth {
+            metrics::record_reservation_queue_wait("full", 0.0);
+            return Err(QueueRejected::Full {
+                sku: sku.to_string(),
+                depth: depth - 1,
+            });
+        }
+
+        match tokio::time::timeout(self.max_wait, lane.turn.clone().acquire_owned()).await {
+            Ok(Ok(permit)) => {
+                metrics::record_reservation_queue_wait("acquired", start.elapsed().as_secs_f64());
+                Ok(Turn {
+                    _permit: permit,
+                    _slot: slot,
+                })
+            }
+            // The semaphore is never closed, so only the timeout can get here
+            _ => {
+                let waited = start.elapsed();
+                metrics::record_reservation_queue_wait("timeout", waited.as_secs_f64());
+                Err(QueueRejected::Timeout {
+                    sku: sku.to_string(),
+                    waited_ms: waited.as_millis() as u64,
+                })
+            }
+        }
+    }
+}
+
+/// Permission to reserve a SKU; the next waiter proceeds when dropped
+pub struct Turn {
+    // Field order matters: the permit is released before the depth drops
+    _permit: OwnedSemaphorePermit,
+    _slot: Slot,
+}
+
+/// Counts a request in a lane's depth for as long as it lives; the last
+/// one out removes the lane
+struct Slot {
+    lanes: Arc<Mutex<HashMap<String, Arc<Lane>>>>,
+    lane: Arc<Lane>,
+    sku: String,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let mut lanes = self.lanes.lock().unwrap_or_else(|e| e.into_inner());
+        let depth = self.lane.depth.fetch_sub(1, Ordering::SeqCst) - 1;
+        if depth == 0 {
+            lanes.remove(&self.sku);
+        }
+        // At 0 the series is left out of /metrics (see metrics.rs)
+        metrics::set_reservation_queue_depth(&self.sku, depth);
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_turns_are_exclusive_and_bounded() {
+        let queue = Arc::new(ReserveQueue::new(Duration::from_millis(50), 2));
+
+        let first = queue.enter("SKU-1").await.unwrap();
+
+        // Second waits and times out while the first holds its turn
+        assert!(matches!(
+            queue.enter("SKU-1").await,
+            Err(QueueRejected::Timeout { .. })
+        ));
+
+        // Other SKUs are independent
+        assert!(queue.enter("SKU-2").await.is_ok());
+
+        drop(first);
+        assert!(queue.enter("SKU-1").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_lanes_are_dropped_when_empty() {
+        let queue = Arc::new(ReserveQueue::new(Duration::from_millis(50), 2));
+
+        let first = queue.enter("SKU-1").await.unwrap();
+        let second = queue.enter("SKU-2").await.unwrap();
+        assert_eq!(queue.lane_count(), 2);
+
+        drop(first);
+        assert_eq!(queue.lane_count(), 1);
+        // Rejected requests leave no lane behind either
+        let held = queue.enter("SKU-3").await.unwrap();
+        assert!(queue.enter("SKU-3").await.is_err());
+        drop((second, held));
+        assert_eq!(queue.lane_count(), 0);
+    }
+
+    #[test]
+    fn test_dropped_lanes_leave_the_scrape() {
+        let scrape = "reservation_queue_depth{sku=\"SKU-1\"} 0\nreservation_queue_depth{sku=\"SKU-2\"} 3\nstock_level 0\n";
+        assert_eq!(
+            metrics::without_dropped_series(scrape),
+            "reservation_queue_depth{sku=\"SKU-2\"} 3\nstock_level 0\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_rejects_immediately() {
+        let queue = Arc::new(ReserveQueue::new(Duration::from_secs(5), 1));
+        let _held = queue.enter("SKU-1").await.unwrap();
+
+        let start = Instant::now();
+        assert!(matches!(
+            queue.enter("SKU-1").await,
+            Err(QueueRejected::Full { depth: 1, .. })
+        ));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,176 @@
+// =============================================================================
+// FAIRNESS MODULE
+// =============================================================================
+// Service-wide caps on how much of one SKU a single order or a single client
+// may hold, so one caller can't reserve a whole SKU and starve the others.
+//
+// HOW (the `policies` config section, both unset by default):
+// - POLICIES_MAX_PER_ORDER: most units of a SKU one order may reserve
+// - POLICIES_MAX_PER_CLIENT: most units of a SKU one client may hold over
+//   all its orders together
+//
+//     # inventory.toml
+//     [policies]
+//     max_per_order = 10
+//     max_per_client = 50
+//
+// - The client is the one rate limiting counts against: its X-API-Key (by
+//   fingerprint) when the key is one of API_KEYS, else its IP (see
+//   rate_limit.rs). Requests without either are only held to the per-order
+//   cap.
+// - Every way of reserving is held to the caps: POST /reserve,
+//   /reserve/batch and /prepare (line by line), the GraphQL reserve mutation
+//   and the gRPC ReserveStock call. GraphQL finds the client like REST does;
+//   gRPC from the call's metadata (x-api-key) and peer address.
+// - Past either cap they answer 422 FAIR_SHARE_EXCEEDED (gRPC:
+//   RESOURCE_EXHAUSTED), unlike the SKU's own reservation policy
+//   (RESERVATION_LIMIT_EXCEEDED)
+//
+// LEARNING NOTES:
+// - The per-order cap only needs the request, so it's checked before any
+//   store is touched. The per-client cap needs what the client's other
+//   orders hold: the store checks it while it has the SKU locked, so two
+//   concurrent reservations of one client can't both slip under the cap.
+// - The reservations table remembers the client of each reservation
+//   (migration 0011); holdings older than that count toward no client
+// - Requests with a client cap skip the Redis fast path, which can't see
+//   other orders' holdings
+// =============================================================================
+
+use anyhow::Result;
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::request::Parts,
+};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::rate_limit;
+use crate::AppState;
+
+/// Per-order and per-client caps on reserved units of one SKU
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FairnessPolicy {
+    /// Most units of a SKU one order may reserve
+    pub max_per_order: Option<i32>,
+    /// Most units of a SKU one client may hold
+    pub max_per_client: Option<i64>,
+}
+
+/// The cap a store applies to a reservation's client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientQuota {
+    /// Who the reservation is for (`key:<fingerprint>` or `ip:<address>`)
+    pub client: String,
+    /// Most units of the SKU the client may hold
+    pub max_units: i64,
+}
+
+impl FairnessPolicy {
+    /// Build from the POLICIES_MAX_PER_ORDER / POLICIES_MAX_PER_CLIENT values
+    pub fn new(max_per_order: Option<i32>, max_per_client: Option<i64>) -> Result<Self> {
+        if max_per_order.is_some_and(|m| m < 1) || max_per_client.is_some_and(|m| m < 1) {
+            anyhow::bail!("POLICIES_MAX_PER_ORDER and POLICIES_MAX_PER_CLIENT must be at least 1");
+        }
+        Ok(Self {
+            max_per_order,
+            max_per_client,
+        })
+    }
+
+    /// Check the quantity one order asks for
+    ///
+    /// # Returns
+    /// - `Err(reason)` if it exceeds the per-order cap
+    pub fn check_order(&self, quantity: i32) -> Result<(), String> {
+        match self.max_per_order {
+            Some(max) if quantity > max => Err(format!(
+                "An order may reserve at most {} units of a SKU, requested {}",
+                max, quantity
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The quota for `client`, when there is a per-client cap and a client
+    pub fn quota_for(&self, client: Option<String>) -> Option<ClientQuota> {
+        Some(ClientQuota {
+            client: client?,
+            max_units: self.max_per_client?,
+        })
+    }
+}
+
+/// Who sent a request, as rate limiting sees it; None without a known API
+/// key or an address
+#[derive(Debug, Clone, Default)]
+pub struct RequestClient(pub Option<String>);
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for RequestClient {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        Ok(Self(rate_limit::client_id(
+            &parts.headers,
+            peer,
+            &state.config.api_keys,
+            &state.config.trusted_proxies,
+        )))
+    }
+}
+
+impl ClientQuota {
+    /// Check a reservation of `requested` units while the client already
+    /// holds `held` units of the SKU
+    ///
+    /// # Returns
+    /// - `Err(reason)` if the client would end up over its cap
+    pub fn check(&self, held: i64, requested: i32) -> Result<(), String> {
+        let total = held + i64::from(requested);
+        if total > self.max_units {
+            return Err(format!(
+                "A client may hold at most {} units of a SKU, holds {} and requested {}",
+                self.max_units, held, requested
+            ));
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_cap() {
+        let policy = FairnessPolicy::new(Some(10), None).unwrap();
+        assert!(policy.check_order(10).is_ok());
+        assert!(policy.check_order(11).is_err());
+        assert!(FairnessPolicy::default().check_order(i32::MAX).is_ok());
+        assert!(FairnessPolicy::new(Some(0), None).is_err());
+    }
+
+    #[test]
+    fn test_client_quota() {
+        let policy = FairnessPolicy::new(None, Some(50)).unwrap();
+        assert_eq!(policy.quota_for(None), None);
+        assert_eq!(FairnessPolicy::default().quota_for(Some("ip:10.0.0.1".to_string())), None);
+
+        let quota = policy.quota_for(Some("key:abc".to_string())).unwrap();
+        assert!(quota.check(40, 10).is_ok());
+        assert!(quota.check(41, 10).is_err());
+    }
+}
@@ -0,0 +1,638 @@
+// =============================================================================
+// FAST RESERVE MODULE
+// =============================================================================
+// Optional Redis-first reservation path: availability is checked and
+// decremented by one Lua script in Redis, the client gets its answer right
+// away, and the reservation is written to Postgres shortly after.
+//
+// THE TRADE-OFF (on purpose, to demo it):
+// - Latency: a reserve is one Redis round trip instead of a transaction
+//   with a row lock
+// - Consistency: Postgres stays the source of truth and lags by the
+//   write-behind delay. A fast-path 200 is a promise, not a commit; if the
+//   write-behind is rejected (stock changed in Postgres meanwhile), the
+//   reservation is revoked: audited as a failure and given back in Redis.
+//
+// HOW IT FITS TOGETHER:
+//   reserve ──Lua──▶ inventory:avail:<sku>   DECRBY, if enough is available
+//                    inventory:writebehind   RPUSH job   (same script, atomic)
+//   worker  ──────▶  LMOVE job to ...:processing, apply in Postgres, LREM
+//   reconciler ───▶  reset counters from Postgres when nothing is in flight
+//
+// COUNTER SEEDING:
+// A missing counter is seeded from Postgres (quantity - reserved) on first
+// use. SKUs with a reservation policy (max_per_order / max_reserved_pct) are
+// marked "db" for a minute and always take the regular Postgres path, since
+// the policy needs the full row.
+//
+// CONFIGURATION:
+//   RESERVE_FAST_PATH=true                   (default: false)
+//   RESERVE_FAST_PATH_RECONCILE_SECS=30
+//
+// REDIS REQUIREMENT:
+// Redis must run with `maxmemory-policy noeviction`. The write-behind queue
+// and the counters are the only record of fast-path reservations until the
+// worker commits them; under an allkeys-* policy Redis may evict the queue
+// (reservations promised to clients vanish) or a counter (re-seeded from a
+// Postgres value that misses queued reservations, so stock is oversold).
+// At startup the service reads CONFIG GET maxmemory-policy and leaves the
+// fast path off, with an error in the log, unless it is noeviction. The
+// docker-compose Redis is shared by every service and runs allkeys-lru, so
+// the fast path needs its own Redis there.
+//
+// LEARNING NOTES:
+// - Counters are only (re)set while the write-behind queue is empty and no
+//   reservation happened since Postgres was read (checked with a sequence
+//   number inside the Lua script). Otherwise Redis could be reset to a
+//   Postgres value that doesn't include reservations still in flight.
+// - Jobs move to a processing list before being applied, so a crash doesn't
+//   lose them; they are re-queued when the worker starts. A crash between
+//   the Postgres commit and the LREM applies a job twice; Postgres keeps
+//   one reservation per (order, SKU), so the second apply changes nothing.
+// - An order reserves a SKU on the fast path once per reservation TTL;
+//   repeats take the Postgres path, which returns the existing reservation.
+//   A repeat arriving before the first is written behind reserves in
+//   Postgres, and the first is then given back (outcome "duplicate").
+// - Releases, adjustments and policy changes go through Postgres and drop
+//   the SKU's counter, so it is re-seeded. Anything else (order callbacks,
+//   manual SQL) is caught by the reconciler.
+// =============================================================================
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use redis::Script;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::audit;
+use crate::db::{Database, DbError};
+use crate::deadline;
+use crate::list_cache;
+use crate::metrics;
+use crate::models::{
+    HoldType, InventoryItem, ReservationResponse, ReserveStockRequest, SalesChannel,
+};
+use crate::reservation_expiry;
+
+/// Prefix of per-SKU availability counters
+const AVAIL_KEY_PREFIX: &str = "inventory:avail:";
+
+/// Prefix of (order, SKU) markers of reservations taken on the fast path
+const ORDER_KEY_PREFIX: &str = "inventory:fastpath:order:";
+
+/// Reservations accepted in Redis, waiting to be written to Postgres
+const QUEUE_KEY: &str = "inventory:writebehind";
+
+/// Jobs taken by the worker but not yet committed
+const PROCESSING_KEY: &str = "inventory:writebehind:processing";
+
+/// Bumped by every change made to a counter by the fast path
+const SEQ_KEY: &str = "inventory:fastpath:seq";
+
+/// Counter value marking a SKU that must use the Postgres path
+const DB_ONLY: &str = "db";
+
+/// How long a SKU stays marked as Postgres-only
+const DB_ONLY_TTL_SECS: u64 = 60;
+
+/// Worker poll interval when the queue is empty
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+// -----------------------------------------------------------------------------
+// LUA SCRIPTS
+// -----------------------------------------------------------------------------
+// Reply: {1, remaining} reserved, {0, available} not enough stock,
+//        {-1, 0} no counter, {-2, 0} SKU is Postgres-only or the order
+//        reserved it here before
+const RESERVE_LUA: &str = r#"
+local avail = redis.call('GET', KEYS[1])
+if not avail then return {-1, 0} end
+if avail == ARGV[3] then return {-2, 0} end
+if redis.call('EXISTS', KEYS[4]) == 1 then return {-2, 0} end
+avail = tonumber(avail)
+local qty = tonumber(ARGV[1])
+if avail < qty then return {0, avail} end
+local remaining = redis.call('DECRBY', KEYS[1], qty)
+redis.call('RPUSH', KEYS[2], ARGV[2])
+redis.call('INCR', KEYS[3])
+redis.call('SET', KEYS[4], '1', 'EX', ARGV[4])
+return {1, remaining}
+"#;
+
+// Set a counter, but only if nothing is in flight and nothing was reserved
+// since the caller read Postgres (ARGV[2] = sequence read before that).
+// Reply: {1, previous value or ""} set, {0, ""} skipped
+const SYNC_LUA: &str = r#"
+if redis.call('LLEN', KEYS[2]) > 0 or redis.call('LLEN', KEYS[3]) > 0 then
+  return {0, ''}
+end
+if (redis.call('GET', KEYS[4]) or '0') ~= ARGV[2] then return {0, ''} end
+local previous = redis.call('GET', KEYS[1]) or ''
+if tonumber(ARGV[3]) > 0 then
+  redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[3])
+else
+  redis.call('SET', KEYS[1], ARGV[1])
+end
+return {1, previous}
+"#;
+
+// Give a revoked reservation back to its counter (if it is still numeric)
+const REFUND_LUA: &str = r#"
+local avail = redis.call('GET', KEYS[1])
+if avail and avail ~= ARGV[2] then redis.call('INCRBY', KEYS[1], ARGV[1]) end
+redis.call('INCR', KEYS[2])
+return 1
+"#;
+
+fn avail_key(sku: &str) -> String {
+    format!("{}{}", AVAIL_KEY_PREFIX, sku)
+}
+
+fn order_key(order_id: &str, sku: &str) -> String {
+    format!("{}{}:{}", ORDER_KEY_PREFIX, order_id, sku)
+}
+
+// -----------------------------------------------------------------------------
+// JOBS
+// -----------------------------------------------------------------------------
+/// A reservation accepted in Redis, to be applied in Postgres
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WriteBehindJob {
+    /// Handed out by the fast path, kept when the job is applied
+    reservation_id: String,
+    sku: String,
+    quantity: i32,
+    order_id: String,
+    /// Missing in jobs queued before channels existed
+    #[serde(default)]
+    channel: SalesChannel,
+    accepted_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// FAST PATH
+// =============================================================================
+/// Redis-first reservations with write-behind to Postgres
+#[derive(Clone)]
+pub struct FastReserve {
+    redis: ConnectionManager,
+    reserve: Script,
+    sync: Script,
+    refund: Script,
+}
+
+/// Check that Redis won't evict the queue or the counters (see REDIS
+/// REQUIREMENT)
+///
+/// # Returns
+/// - `Err` when the policy isn't noeviction or can't be read
+pub async fn check_eviction_policy(redis: &ConnectionManager) -> Result<()> {
+    let config: HashMap<String, String> = redis::cmd("CONFIG")
+        .arg("GET")
+        .arg("maxmemory-policy")
+        .query_async(&mut deadline::redis(redis))
+        .await
+        .context("Failed to read the Redis maxmemory-policy")?;
+    match config.get("maxmemory-policy").map(String::as_str) {
+        Some("noeviction") => Ok(()),
+        Some(policy) => anyhow::bail!(
+            "Redis maxmemory-policy is {}, the fast path needs noeviction",
+            policy
+        ),
+        None => anyhow::bail!("Redis didn't report its maxmemory-policy"),
+    }
+}
+
+impl FastReserve {
+    pub fn new(redis: ConnectionManager) -> Self {
+        Self {
+            redis,
+            reserve: Script::new(RESERVE_LUA),
+            sync: Script::new(SYNC_LUA),
+            refund: Script::new(REFUND_LUA),
+        }
+    }
+
+    /// Try to reserve in Redis.
+    ///
+    /// Returns `Ok(None)` when the request should take the regular Postgres
+    /// path instead (Postgres-only SKU, counter can't be seeded right now,
+    /// Redis unavailable).
+    pub async fn reserve(
+        &self,
+        db: &Database,
+        req: &ReserveStockRequest,
+    ) -> Result<Option<ReservationResponse>> {
+        let start = Instant::now();
+        let result = self.try_reserve(db, req).await;
+        metrics::record_redis_operation("fast_reserve", start.elapsed().as_secs_f64());
+
+        match result {
+            Ok(Some(response)) => {
+                metrics::record_fast_reserve("reserved");
+                Ok(Some(response))
+            }
+            Ok(None) => {
+                metrics::record_fast_reserve("fallback");
+                Ok(None)
+            }
+            Err(e) if e.downcast_ref::<redis::RedisError>().is_some() => {
+                tracing::warn!(error = %e, "Fast reserve unavailable, using Postgres");
+                metrics::record_fast_reserve("fallback");
+                Ok(None)
+            }
+            Err(e) => {
+                metrics::record_fast_reserve("rejected");
+                Err(e)
+            }
+        }
+    }
+
+    async fn try_reserve(
+        &self,
+        db: &Database,
+        req: &ReserveStockRequest,
+    ) -> Result<Option<ReservationResponse>> {
+        let job = WriteBehindJob {
+            reservation_id: db.ids().next_id(),
+            sku: req.sku.clone(),
+            quantity: req.quantity,
+            order_id: req.order_id.clone(),
+            channel: req.channel,
+            accepted_at: Utc::now(),
+        };
+        let payload = serde_json::to_string(&job)?;
+
+        // Second round only happens after seeding a missing counter
+        for _ in 0..2 {
+            let (code, value): (i64, i64) = self
+                .reserve
+                .key(avail_key(&req.sku))
+                .key(QUEUE_KEY)
+                .key(SEQ_KEY)
+                .key(order_key(&req.order_id, &req.sku))
+                .arg(req.quantity)
+                .arg(&payload)
+                .arg(DB_ONLY)
+                .arg(reservation_expiry::reservation_ttl().num_seconds())
+                // Not bounded by the request deadline (deadline::redis): a
+                // Redis error falls back to Postgres, and a script cut off
+                // mid-way may already have reserved
+                .invoke_async(&mut self.redis.clone())
+                .await?;
+
+            match code {
+                1 => {
+                    return Ok(Some(ReservationResponse {
+                        reservation_id: job.reservation_id,
+                        sku: job.sku,
+                        quantity: job.quantity,
+                        created_at: job.accepted_at,
+                        // Same hold time as the Postgres path
+                        expires_at: Some(job.accepted_at + reservation_expiry::reservation_ttl()),
+                        bumped: Vec::new(),
+                        replayed: false,
+                    }))
+                }
+                // Same error as the Postgres path
+                0 => {
+                    return Err(DbError::InsufficientStock {
+                        available: value as i32,
+                        requested: req.quantity,
+                    }
+                    .into())
+                }
+                -1 => {
+                    if !self.seed(db, &req.sku).await? {
+                        return Ok(None);
+                    }
+                }
+                _ => return Ok(None),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Seed a SKU's counter from Postgres; false if it couldn't be set now
+    async fn seed(&self, db: &Database, sku: &str) -> Result<bool> {
+        let seq = self.seq().await?;
+        let item = db
+            .get_by_sku(sku)
+            .await?
+            .ok_or_else(|| DbError::NotFound(sku.to_string()))?;
+
+        let (value, ttl) = counter_value(&item);
+
+        Ok(self.sync(sku, &value, seq, ttl).await?.is_some())
+    }
+
+    /// Drop a SKU's counter so it is re-seeded from Postgres on next use
+    pub async fn invalidate(&self, sku: &str) {
+        let result: redis::RedisResult<()> = redis::cmd("DEL")
+            .arg(avail_key(sku))
+            .query_async(&mut deadline::redis(&self.redis))
+            .await;
+        if let Err(e) = result {
+            tracing::warn!(sku, error = %e, "Failed to drop fast reserve counter");
+        }
+    }
+
+    async fn seq(&self) -> Result<i64> {
+        let seq: Option<i64> = redis::cmd("GET")
+            .arg(SEQ_KEY)
+            .query_async(&mut deadline::redis(&self.redis))
+            .await?;
+        Ok(seq.unwrap_or(0))
+    }
+
+    /// Run SYNC_LUA; returns the previous value if the counter was set
+    async fn sync(&self, sku: &str, value: &str, seq: i64, ttl: u64) -> Result<Option<String>> {
+        let (set, previous): (i64, String) = self
+            .sync
+            .key(avail_key(sku))
+            .key(QUEUE_KEY)
+            .key(PROCESSING_KEY)
+            .key(SEQ_KEY)
+            .arg(value)
+            .arg(seq)
+            .arg(ttl)
+            .invoke_async(&mut deadline::redis(&self.redis))
+            .await?;
+
+        Ok((set == 1).then_some(previous))
+    }
+
+    // =========================================================================
+    // WRITE-BEHIND
+    // =========================================================================
+
+    /// Apply queued reservations to Postgres, forever.
+    ///
+    /// Returns an error when Postgres is unreachable, leaving the job in the
+    /// processing list; the supervised restart re-queues it.
+    pub async fn run_write_behind(self, db: Database) -> Result<()> {
+        let mut redis = self.redis.clone();
+
+        // Jobs left in processing by a previous run go back to the front
+        loop {
+            let moved: Option<String> = redis::cmd("LMOVE")
+                .arg(PROCESSING_KEY)
+                .arg(QUEUE_KEY)
+                .arg("RIGHT")
+                .arg("LEFT")
+                .query_async(&mut redis)
+                .await?;
+            if moved.is_none() {
+                break;
+            }
+        }
+
+        loop {
+            // Non-blocking LMOVE + poll: a blocking command would stall the
+            // multiplexed connection shared with request handlers
+            let payload: Option<String> = redis::cmd("LMOVE")
+                .arg(QUEUE_KEY)
+                .arg(PROCESSING_KEY)
+                .arg("LEFT")
+                .arg("RIGHT")
+                .query_async(&mut redis)
+                .await?;
+
+            let Some(payload) = payload else {
+                let pending: i64 = redis::cmd("LLEN")
+                    .arg(QUEUE_KEY)
+                    .query_async(&mut redis)
+                    .await?;
+                metrics::set_write_behind_pending(pending);
+                tokio::time::sleep(POLL_INTERVAL).await;
+                continue;
+            };
+
+            match serde_json::from_str::<WriteBehindJob>(&payload) {
+                Ok(job) => self.apply(&db, &job).await?,
+                Err(e) => tracing::error!(error = %e, "Dropping malformed write-behind job"),
+            }
+
+            let _: i64 = redis::cmd("LREM")
+                .arg(PROCESSING_KEY)
+                .arg(1)
+                .arg(&payload)
+                .query_async(&mut redis)
+                .await?;
+        }
+    }
+
+    /// Write one job to Postgres, revoking it if Postgres rejects it
+    async fn apply(&self, db: &Database, job: &WriteBehindJob) -> Result<()> {
+        let request = ReserveStockRequest {
+            sku: job.sku.clone(),
+            quantity: job.quantity,
+            order_id: job.order_id.clone(),
+            channel: job.channel,
+            // Only plain hard holds take the fast path
+            hold: HoldType::Hard,
+            priority: 0,
+            // Requests under a client quota don't take the fast path
+            quota: None,
+        };
+        let lag = || (Utc::now() - job.accepted_at).num_milliseconds().max(0) as f64 / 1000.0;
+
+        let start = Instant::now();
+        let result = db.reserve_stock_as(&request, job.reservation_id.clone()).await;
+        metrics::record_db_query("reserve_write_behind", start.elapsed().as_secs_f64());
+
+        match result {
+            // The order reserved the SKU on the Postgres path in the
+            // meantime; that reservation stands and this one is given back
+            Ok(existing) if existing.replayed && existing.reservation_id != job.reservation_id => {
+                metrics::record_write_behind("duplicate", lag());
+                tracing::info!(
+                    reservation_id = %job.reservation_id,
+                    kept = %existing.reservation_id,
+                    order_id = %job.order_id,
+                    "Fast-path reservation already made, giving it back"
+                );
+                self.refund(job).await
+            }
+            // Applied now, or applied before a crash kept the job queued
+            Ok(_) => {
+                metrics::record_write_behind("applied", lag());
+                list_cache::invalidate(&self.redis).await;
+                Ok(())
+            }
+            // Postgres unreachable: not the reservation's fault, retry later
+            Err(e) if is_transient(&e) => Err(e.context("Write-behind failed")),
+            Err(e) => {
+                metrics::record_write_behind("rejected", lag());
+                tracing::warn!(
+                    reservation_id = %job.reservation_id,
+                    sku = %job.sku,
+                    order_id = %job.order_id,
+                    error = %e,
+                    "Fast-path reservation revoked by Postgres"
+                );
+                audit::record_failure(db, "reserve", &job.sku, job.quantity, &job.order_id, &e)
+                    .await;
+                self.refund(job).await
+            }
+        }
+    }
+
+    /// Give a job's units back to its SKU's counter
+    async fn refund(&self, job: &WriteBehindJob) -> Result<()> {
+        let _: i64 = self
+            .refund
+            .key(avail_key(&job.sku))
+            .key(SEQ_KEY)
+            .arg(job.quantity)
+            .arg(DB_ONLY)
+            .invoke_async(&mut self.redis.clone())
+            .await?;
+        Ok(())
+    }
+
+    // =========================================================================
+    // RECONCILIATION
+    // =========================================================================
+
+    /// Reset drifted counters from Postgres every `interval`, forever
+    pub async fn run_reconciler(self, db: Database, interval: Duration) -> Result<()> {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            self.reconcile(&db).await?;
+        }
+    }
+
+    /// One pass over every seeded counter
+    async fn reconcile(&self, db: &Database) -> Result<()> {
+        let mut redis = self.redis.clone();
+        let keys: Vec<String> = {
+            let mut iter = redis::cmd("SCAN")
+                .cursor_arg(0)
+                .arg("MATCH")
+                .arg(format!("{}*", AVAIL_KEY_PREFIX))
+                .clone()
+                .iter_async::<String>(&mut redis)
+                .await
+                .context("Failed to list fast reserve counters")?;
+            let mut keys = Vec::new();
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+            keys
+        };
+
+        for key in keys {
+            let sku = &key[AVAIL_KEY_PREFIX.len()..];
+            let seq = self.seq().await?;
+
+            let Some(item) = db.get_by_sku(sku).await? else {
+                self.invalidate(sku).await;
+                continue;
+            };
+            let (expected, ttl) = counter_value(&item);
+            match self.sync(sku, &expected, seq, ttl).await? {
+                Some(previous) if previous != expected && previous != DB_ONLY => {
+                    metrics::record_fast_reserve_drift(sku);
+                    tracing::info!(
+                        sku,
+                        redis = %previous,
+                        postgres = %expected,
+                        "Corrected fast reserve counter"
+                    );
+                }
+                Some(_) => {}
+                // Reservations in flight: try again next round
+                None => return Ok(()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Counter value for an item: its availability, or the Postgres-only marker
+/// (with its TTL) when a reservation policy applies
+fn counter_value(item: &InventoryItem) -> (String, u64) {
+    if item.max_per_order.is_some() || item.max_reserved_pct.is_some() {
+        (DB_ONLY.to_string(), DB_ONLY_TTL_SECS)
+    } else {
+        (item.available().to_string(), 0)
+    }
+}
+
+/// Errors that say nothing about the reservation itself (pool/connection)
+fn is_transient(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<sqlx::Error>(),
+        Some(
+            sqlx::Error::Io(_)
+                | sqlx::Error::PoolTimedOut
+                | sqlx::Error::PoolClosed
+                | sqlx::Error::Tls(_)
+                | sqlx::Error::Protocol(_)
+        )
+    )
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_counter_value() {
+        let mut item = InventoryItem {
+            id: Uuid::nil(),
+            sku: "SKU-PHONE-001".to_string(),
+            name: "Phone".to_string(),
+            quantity: 10,
+            reserved: 3,
+            warehouse: "JKT-1".to_string(),
+            low_stock_threshold: 5,
+            max_per_order: None,
+            max_reserved_pct: None,
+            threshold_manual: false,
+            version: 1,
+            attributes: serde_json::json!({}),
+            image_url: None,
+            spec_url: None,
+            unit_price: None,
+            unit_cost: None,
+            stock_value: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        assert_eq!(counter_value(&item), ("7".to_string(), 0));
+
+        // A policy needs the full row, so the SKU stays on Postgres
+        item.max_reserved_pct = Some(50);
+        assert_eq!(counter_value(&item), (DB_ONLY.to_string(), DB_ONLY_TTL_SECS));
+    }
+
+    #[test]
+    fn test_job_roundtrip() {
+        let job = WriteBehindJob {
+            reservation_id: "01HMX3K8Q2ZJ7P4T9V6W5Y0RNA".to_string(),
+            sku: "SKU-PHONE-001".to_string(),
+            quantity: 2,
+            order_id: "ORD-1".to_string(),
+            channel: SalesChannel::Pos,
+            accepted_at: Utc::now(),
+        };
+        let parsed: WriteBehindJob =
+            serde_json::from_str(&serde_json::to_string(&job).unwrap()).unwrap();
+        assert_eq!(parsed.reservation_id, job.reservation_id);
+        assert_eq!(parsed.quantity, 2);
+        assert_eq!(parsed.channel, SalesChannel::Pos);
+    }
+}
@@ -0,0 +1,240 @@
+// =============================================================================
+// FEATURES MODULE
+// =============================================================================
+// Switches endpoint groups off by configuration, e.g. stock adjustments in a
+// shared demo environment where nobody should be able to zero out stock.
+//
+// CONFIGURATION:
+//   DISABLED_ENDPOINTS=adjust,replay,loadgen
+// Disabled groups answer 403 FEATURE_DISABLED, naming the group and the
+// variable that turned it off. Unknown group names fail startup.
+//
+// GROUPS:
+// - reserve       reserve, batch reserve, release, cancel-by-order,
+//                 prepare/commit/abort
+// - adjust        stock adjustments and transfers, reservation reconciliation
+// - catalog       policy, catalog details, thresholds, identifiers,
+//                 attribute schemas, warehouses
+// - snapshots     snapshot create/list/diff
+// - reports       stockout report, stats, API usage report
+// - integrations  order status callbacks
+// - webhooks      webhook subscriptions
+// - replay        traffic replay
+// - loadgen       synthetic database load
+// - chaos         chaos experiments (fault injection)
+//
+// LEARNING NOTES:
+// - Groups are matched on the route template (`/api/v1/inventory/:sku/...`),
+//   not the raw path, so a SKU can never be mistaken for an endpoint
+// - Reads of the item list, lookups, health, metrics and debug endpoints
+//   belong to no group and can't be disabled
+// =============================================================================
+
+use anyhow::Result;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+use crate::error::AppError;
+use crate::AppState;
+
+/// A set of endpoints that is switched on and off together
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointGroup {
+    Reserve,
+    Adjust,
+    Catalog,
+    Snapshots,
+    Reports,
+    Integrations,
+    Webhooks,
+    Replay,
+    LoadGen,
+    Chaos,
+}
+
+/// Route template prefixes of each group
+const ROUTES: [(&str, EndpointGroup); 28] = [
+    ("/api/v1/inventory/reserve", EndpointGroup::Reserve),
+    ("/api/v1/inventory/release", EndpointGroup::Reserve),
+    ("/api/v1/inventory/prepare", EndpointGroup::Reserve),
+    ("/api/v1/inventory/commit", EndpointGroup::Reserve),
+    ("/api/v1/inventory/abort", EndpointGroup::Reserve),
+    ("/api/v1/reservations/", EndpointGroup::Reserve),
+    ("/api/v1/inventory/adjust", EndpointGroup::Adjust),
+    ("/api/v1/inventory/transfer", EndpointGroup::Adjust),
+    ("/api/v1/admin/reconcile-reservations", EndpointGroup::Adjust),
+    ("/api/v1/inventory/:sku/policy", EndpointGroup::Catalog),
+    ("/api/v1/inventory/:sku/catalog", EndpointGroup::Catalog),
+    ("/api/v1/inventory/:sku/thresholds", EndpointGroup::Catalog),
+    ("/api/v1/inventory/:sku/identifiers", EndpointGroup::Catalog),
+    ("/api/v1/inventory/:sku/revisions", EndpointGroup::Catalog),
+    ("/api/v1/inventory/bulk-update", EndpointGroup::Catalog),
+    ("/api/v1/inventory/import", EndpointGroup::Catalog),
+    ("/api/v1/inventory/export", EndpointGroup::Reports),
+    ("/api/v1/admin/attribute-schemas", EndpointGroup::Catalog),
+    ("/api/v1/warehouses", EndpointGroup::Catalog),
+    ("/api/v1/snapshots", EndpointGroup::Snapshots),
+    ("/api/v1/reports/", EndpointGroup::Reports),
+    ("/api/v1/stats", EndpointGroup::Reports),
+    ("/api/v1/admin/usage", EndpointGroup::Reports),
+    ("/api/v1/integrations/", EndpointGroup::Integrations),
+    ("/api/v1/admin/webhooks", EndpointGroup::Webhooks),
+    ("/api/v1/admin/replay", EndpointGroup::Replay),
+    ("/api/v1/admin/load", EndpointGroup::LoadGen),
+    ("/api/v1/chaos", EndpointGroup::Chaos),
+];
+
+impl EndpointGroup {
+    /// Name used in DISABLED_ENDPOINTS and error messages
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Reserve => "reserve",
+            Self::Adjust => "adjust",
+            Self::Catalog => "catalog",
+            Self::Snapshots => "snapshots",
+            Self::Reports => "reports",
+            Self::Integrations => "integrations",
+            Self::Webhooks => "webhooks",
+            Self::Replay => "replay",
+            Self::LoadGen => "loadgen",
+            Self::Chaos => "chaos",
+        }
+    }
+
+    /// Parse a group name
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "reserve" => Some(Self::Reserve),
+            "adjust" => Some(Self::Adjust),
+            "catalog" => Some(Self::Catalog),
+            "snapshots" => Some(Self::Snapshots),
+            "reports" => Some(Self::Reports),
+            "integrations" => Some(Self::Integrations),
+            "webhooks" => Some(Self::Webhooks),
+            "replay" => Some(Self::Replay),
+            "loadgen" => Some(Self::LoadGen),
+            "chaos" => Some(Self::Chaos),
+            _ => None,
+        }
+    }
+
+    /// Group a route template belongs to, if any
+    pub fn for_route(route: &str) -> Option<Self> {
+        ROUTES
+            .iter()
+            .find(|(prefix, _)| route.starts_with(prefix))
+            .map(|(_, group)| *group)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// FEATURE FLAGS
+// -----------------------------------------------------------------------------
+/// Which endpoint groups are switched off
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureFlags {
+    disabled: Vec<EndpointGroup>,
+}
+
+impl FeatureFlags {
+    /// Parse a comma-separated DISABLED_ENDPOINTS list ("" disables nothing)
+    pub fn parse(value: &str) -> Result<Self> {
+        let mut disabled = Vec::new();
+        for name in value.split(',').map(|name| name.trim().to_ascii_lowercase()) {
+            if name.is_empty() {
+                continue;
+            }
+            let Some(group) = EndpointGroup::parse(&name) else {
+                anyhow::bail!("DISABLED_ENDPOINTS has an unknown endpoint group '{}'", name);
+            };
+            if !disabled.contains(&group) {
+                disabled.push(group);
+            }
+        }
+        Ok(Self { disabled })
+    }
+
+    /// Whether the group's endpoints are served
+    pub fn is_enabled(&self, group: EndpointGroup) -> bool {
+        !self.disabled.contains(&group)
+    }
+
+    /// FEATURE_DISABLED if the route template belongs to a disabled group
+    pub fn check_route(&self, route: &str) -> Result<(), AppError> {
+        match EndpointGroup::for_route(route).filter(|group| !self.is_enabled(*group)) {
+            Some(group) => Err(AppError::FeatureDisabled(format!(
+                "The '{}' endpoints are disabled in this environment (DISABLED_ENDPOINTS)",
+                group.as_str()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Names of the disabled groups (for startup logs)
+    pub fn disabled_names(&self) -> Vec<&'static str> {
+        self.disabled.iter().map(|group| group.as_str()).collect()
+    }
+}
+
+// =============================================================================
+// MIDDLEWARE
+// =============================================================================
+/// Answer 403 for routes whose endpoint group is disabled
+pub async fn enforce(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(route) = request.extensions().get::<MatchedPath>() {
+        if let Err(e) = state.config.features.check_route(route.as_str()) {
+            return e.into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feature_flags() {
+        let flags = FeatureFlags::parse(" Adjust, replay,,adjust ").unwrap();
+        assert_eq!(flags.disabled_names(), vec!["adjust", "replay"]);
+        assert!(!flags.is_enabled(EndpointGroup::Adjust));
+        assert!(flags.is_enabled(EndpointGroup::Reserve));
+
+        assert_eq!(FeatureFlags::parse("").unwrap(), FeatureFlags::default());
+        assert!(FeatureFlags::parse("adjust,import").is_err());
+    }
+
+    #[test]
+    fn test_group_for_route() {
+        assert_eq!(
+            EndpointGroup::for_route("/api/v1/inventory/adjust"),
+            Some(EndpointGroup::Adjust)
+        );
+        assert_eq!(
+            EndpointGroup::for_route("/api/v1/inventory/reserve/batch"),
+            Some(EndpointGroup::Reserve)
+        );
+        assert_eq!(
+            EndpointGroup::for_route("/api/v1/inventory/:sku/thresholds/:warehouse"),
+            Some(EndpointGroup::Catalog)
+        );
+        assert_eq!(
+            EndpointGroup::for_route("/api/v1/snapshots/diff"),
+            Some(EndpointGroup::Snapshots)
+        );
+        assert_eq!(EndpointGroup::for_route("/api/v1/inventory/:sku"), None);
+        assert_eq!(EndpointGroup::for_route("/health"), None);
+    }
+}
@@ -0,0 +1,667 @@
+// =============================================================================
+// GRAPHQL MODULE
+// =============================================================================
+// GraphQL API at /graphql, for dashboards that want items, movements,
+// reservations and alerts in one round trip instead of one REST call each.
+//
+// WHAT IT SERVES:
+// - POST /graphql: queries and mutations (JSON body: query, variables,
+//   operationName)
+// - GET /graphql: GraphiQL, an in-browser editor with the schema's docs
+//
+//   query {
+//     lowStockAlerts { sku available threshold }
+//     items(perPage: 5, sortBy: AVAILABLE) { total items { sku available } }
+//     movements(sku: "SKU-001", perPage: 3) { items { type quantityDelta } }
+//   }
+//
+// Queries: item, items, lowStockAlerts, movements, reservations
+// Mutations: reserve, release, adjust
+//
+// HOW:
+// Like the gRPC API (grpc/service.rs), every field runs the REST handler of
+// the same operation, so validation, limits, caches, events, the audit trail
+// and metrics are the same. `reservations` reads Database directly; there is
+// no REST endpoint for it yet.
+//
+// LEARNING NOTES:
+// - Errors carry the REST error code and status in `extensions`
+//   ({"code": "INSUFFICIENT_STOCK", "status": 409}); the HTTP status of a
+//   GraphQL response is 200 either way
+// - DISABLED_ENDPOINTS and public read-only mode are checked per field, by
+//   the REST route the field mirrors: in public mode queries work and
+//   mutations fail with READ_ONLY
+// - The whole request shares one deadline (REQUEST_TIMEOUT_MS) and counts
+//   once against rate limits and the concurrency limit
+// - Queries nested deeper than MAX_DEPTH are refused before they run
+// - With ADMIN_PORT, GraphiQL moves to the admin port (with a POST /graphql
+//   of its own) and the public POST /graphql answers no introspection
+//   queries: like the OpenAPI docs, the schema is browsed internally
+// =============================================================================
+
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{
+    Context, EmptySubscription, Enum, ErrorExtensions, InputObject, Json as GraphqlJson, Object,
+    Schema, SimpleObject,
+};
+use axum::extract::{Path, Query, State};
+use axum::http::Method;
+use axum::response::Html;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+
+use crate::error::AppError;
+use crate::fairness::RequestClient;
+use crate::handlers::{self, DryRunParams, ListParams, MovementParams};
+use crate::i18n;
+use crate::models;
+use crate::public_mode;
+use crate::validation::ValidJson;
+use crate::versioning::IfMatch;
+use crate::AppState;
+
+/// Where the API is served
+pub const PATH: &str = "/graphql";
+
+/// Deepest selection a query may have
+const MAX_DEPTH: usize = 8;
+
+/// Most reservations one `reservations` field returns
+const MAX_RESERVATIONS: i32 = 500;
+
+pub type InventorySchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+/// The schema, built once
+fn schema() -> &'static InventorySchema {
+    static SCHEMA: OnceLock<InventorySchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+            .limit_depth(MAX_DEPTH)
+            .finish()
+    })
+}
+
+/// The schema without __schema and __type, for the public port
+fn schema_without_introspection() -> &'static InventorySchema {
+    static SCHEMA: OnceLock<InventorySchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+            .limit_depth(MAX_DEPTH)
+            .disable_introspection()
+            .finish()
+    })
+}
+
+// =============================================================================
+// ENDPOINTS
+// =============================================================================
+/// POST /graphql
+pub async fn execute(
+    State(state): State<Arc<AppState>>,
+    client: RequestClient,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    // The caller, for the fairness caps of the reserve mutation
+    Json(schema().execute(request.data(state).data(client)).await)
+}
+
+/// POST /graphql on the public port when ADMIN_PORT is set
+pub async fn execute_without_introspection(
+    State(state): State<Arc<AppState>>,
+    client: RequestClient,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema_without_introspection().execute(request.data(state).data(client)).await)
+}
+
+/// GET /graphql
+pub async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint(PATH).finish())
+}
+
+// =============================================================================
+// FIELD EXECUTION
+// =============================================================================
+/// Run one field as the REST call `method route` would run
+async fn run<T, F, Fut>(
+    ctx: &Context<'_>,
+    (method, route): (Method, &'static str),
+    call: F,
+) -> async_graphql::Result<T>
+where
+    F: FnOnce(Arc<AppState>) -> Fut,
+    Fut: Future<Output = Result<T, AppError>>,
+{
+    let state = ctx.data_unchecked::<Arc<AppState>>().clone();
+    let checked = state.config.features.check_route(route).and_then(|()| {
+        match state.public_mode.as_ref().and(public_mode::rejection(&method, route)) {
+            Some(reason) => Err(AppError::ReadOnly(reason.to_string())),
+            None => Ok(()),
+        }
+    });
+
+    let result = match checked {
+        Ok(()) => call(state).await,
+        Err(e) => Err(e),
+    };
+    result.map_err(field_error)
+}
+
+/// A failed field, with the REST error code and status in its extensions
+fn field_error(error: AppError) -> async_graphql::Error {
+    let (status, code, message) = error.describe();
+    tracing::error!(error_code = code, message = %message, "GraphQL field failed");
+
+    let message = i18n::error_message(code, i18n::current())
+        .map(str::to_string)
+        .unwrap_or(message);
+    async_graphql::Error::new(message).extend_with(|_, extensions| {
+        extensions.set("code", code);
+        extensions.set("status", status.as_u16());
+    })
+}
+
+// =============================================================================
+// TYPES
+// =============================================================================
+/// An inventory item
+#[derive(SimpleObject)]
+pub struct Item {
+    sku: String,
+    name: String,
+    /// Units on hand
+    quantity: i32,
+    /// Units held by reservations
+    reserved: i32,
+    /// quantity - reserved
+    available: i32,
+    /// Home warehouse
+    warehouse: String,
+    low_stock_threshold: i32,
+    /// Bumped on every change; send it back as expectedVersion
+    version: i64,
+    unit_price: Option<f64>,
+    attributes: GraphqlJson<serde_json::Value>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<models::InventoryItem> for Item {
+    fn from(item: models::InventoryItem) -> Self {
+        Self {
+            available: item.available(),
+            sku: item.sku,
+            name: item.name,
+            quantity: item.quantity,
+            reserved: item.reserved,
+            warehouse: item.warehouse,
+            low_stock_threshold: item.low_stock_threshold,
+            version: item.version,
+            unit_price: item.unit_price,
+            attributes: GraphqlJson(item.attributes),
+            created_at: item.created_at,
+            updated_at: item.updated_at,
+        }
+    }
+}
+
+/// One page of items
+#[derive(SimpleObject)]
+pub struct ItemPage {
+    items: Vec<Item>,
+    /// Items matching the filters, on all pages
+    total: i64,
+    page: i32,
+    per_page: i32,
+}
+
+/// An item below its low-stock threshold
+#[derive(SimpleObject)]
+pub struct LowStockAlert {
+    sku: String,
+    name: String,
+    available: i32,
+    threshold: i32,
+    warehouse: String,
+}
+
+impl From<models::LowStockAlert> for LowStockAlert {
+    fn from(alert: models::LowStockAlert) -> Self {
+        Self {
+            sku: alert.sku,
+            name: alert.name,
+            available: alert.available,
+            threshold: alert.threshold,
+            warehouse: alert.warehouse,
+        }
+    }
+}
+
+/// One entry of an item's stock movement history
+#[derive(SimpleObject)]
+pub struct StockMovement {
+    movement_id: Option<String>,
+    sku: String,
+    /// reserve, release, confirm, adjust, reconcile, transfer_out, transfer_in
+    #[graphql(name = "type")]
+    movement_type: String,
+    quantity_delta: i32,
+    reserved_delta: i32,
+    quantity_after: i32,
+    reserved_after: i32,
+    /// Order ID or adjustment reason
+    reference: Option<String>,
+    warehouse: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<models::StockMovement> for StockMovement {
+    fn from(movement: models::StockMovement) -> Self {
+        Self {
+            movement_id: movement.movement_id,
+            sku: movement.sku,
+            movement_type: movement.movement_type,
+            quantity_delta: movement.quantity_delta,
+            reserved_delta: movement.reserved_delta,
+            quantity_after: movement.quantity_after,
+            reserved_after: movement.reserved_after,
+            reference: movement.reference,
+            warehouse: movement.warehouse,
+            created_at: movement.created_at,
+        }
+    }
+}
+
+/// One page of movements, newest first
+#[derive(SimpleObject)]
+pub struct MovementPage {
+    items: Vec<StockMovement>,
+    total: i64,
+    page: i32,
+    per_page: i32,
+}
+
+/// Units an order currently holds on one SKU
+#[derive(SimpleObject)]
+pub struct ActiveReservation {
+    order_id: String,
+    sku: String,
+    quantity: i32,
+    last_reserved_at: DateTime<Utc>,
+}
+
+impl From<models::ActiveReservation> for ActiveReservation {
+    fn from(reservation: models::ActiveReservation) -> Self {
+        Self {
+            order_id: reservation.order_id,
+            sku: reservation.sku,
+            quantity: reservation.quantity,
+            last_reserved_at: reservation.last_reserved_at,
+        }
+    }
+}
+
+/// A soft hold released to make room for a reservation
+#[derive(SimpleObject)]
+pub struct BumpedHold {
+    order_id: String,
+    quantity: i32,
+    priority: i32,
+}
+
+/// A reservation that was made
+#[derive(SimpleObject)]
+pub struct Reservation {
+    reservation_id: String,
+    sku: String,
+    quantity: i32,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    bumped: Vec<BumpedHold>,
+    /// The order already held this SKU; nothing was reserved
+    replayed: bool,
+}
+
+impl From<models::ReservationResponse> for Reservation {
+    fn from(reservation: models::ReservationResponse) -> Self {
+        Self {
+            reservation_id: reservation.reservation_id,
+            sku: reservation.sku,
+            quantity: reservation.quantity,
+            created_at: reservation.created_at,
+            expires_at: reservation.expires_at,
+            bumped: reservation
+                .bumped
+                .into_iter()
+                .map(|hold| BumpedHold {
+                    order_id: hold.order_id,
+                    quantity: hold.quantity,
+                    priority: hold.priority,
+                })
+                .collect(),
+            replayed: reservation.replayed,
+        }
+    }
+}
+
+/// Units given back by a release
+#[derive(SimpleObject)]
+pub struct Release {
+    sku: String,
+    quantity: i32,
+    order_id: String,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Sku,
+    Name,
+    Quantity,
+    Available,
+    Warehouse,
+    UpdatedAt,
+}
+
+impl From<SortField> for models::SortBy {
+    fn from(field: SortField) -> Self {
+        match field {
+            SortField::Sku => Self::Sku,
+            SortField::Name => Self::Name,
+            SortField::Quantity => Self::Quantity,
+            SortField::Available => Self::Available,
+            SortField::Warehouse => Self::Warehouse,
+            SortField::UpdatedAt => Self::UpdatedAt,
+        }
+    }
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Web,
+    Pos,
+    B2b,
+    Unknown,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum Hold {
+    /// Held until released, confirmed or expired
+    Hard,
+    /// May be bumped by a higher-priority reservation
+    Soft,
+}
+
+/// What POST /api/v1/inventory/reserve takes
+#[derive(InputObject)]
+pub struct ReserveInput {
+    sku: String,
+    quantity: i32,
+    order_id: String,
+    #[graphql(default_with = "Channel::Unknown")]
+    channel: Channel,
+    #[graphql(default_with = "Hold::Hard")]
+    hold: Hold,
+    #[graphql(default)]
+    priority: i32,
+}
+
+impl From<ReserveInput> for models::ReserveStockRequest {
+    fn from(input: ReserveInput) -> Self {
+        Self {
+            sku: input.sku,
+            quantity: input.quantity,
+            order_id: input.order_id,
+            channel: match input.channel {
+                Channel::Web => models::SalesChannel::Web,
+                Channel::Pos => models::SalesChannel::Pos,
+                Channel::B2b => models::SalesChannel::B2b,
+                Channel::Unknown => models::SalesChannel::Unknown,
+            },
+            hold: match input.hold {
+                Hold::Hard => models::HoldType::Hard,
+                Hold::Soft => models::HoldType::Soft,
+            },
+            priority: input.priority,
+            // Set by the handler from the fairness policy
+            quota: None,
+        }
+    }
+}
+
+// =============================================================================
+// QUERIES
+// =============================================================================
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// One item, or null when the SKU doesn't exist
+    async fn item(&self, ctx: &Context<'_>, sku: String) -> async_graphql::Result<Option<Item>> {
+        run(ctx, (Method::GET, "/api/v1/inventory/:sku"), |state| async move {
+            match handlers::get_item(State(state), Path(sku)).await {
+                Ok((_, _, Json(item))) => Ok(Some(item.into())),
+                Err(AppError::NotFound(_)) => Ok(None),
+                Err(e) => Err(e),
+            }
+        })
+        .await
+    }
+
+    /// One page of items, as GET /api/v1/inventory
+    #[allow(clippy::too_many_arguments)]
+    async fn items(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 1)] page: i32,
+        #[graphql(default = 20, desc = "1-100")] per_page: i32,
+        warehouse: Option<String>,
+        sku_prefix: Option<String>,
+        name_contains: Option<String>,
+        #[graphql(default)] low_stock_only: bool,
+        #[graphql(default_with = "SortField::Sku")] sort_by: SortField,
+        #[graphql(default_with = "SortDirection::Asc")] order: SortDirection,
+    ) -> async_graphql::Result<ItemPage> {
+        let params = ListParams {
+            page,
+            per_page,
+            warehouse,
+            sku_prefix,
+            name_contains,
+            low_stock_only,
+            sort_by: sort_by.into(),
+            order: match order {
+                SortDirection::Asc => models::SortOrder::Asc,
+                SortDirection::Desc => models::SortOrder::Desc,
+            },
+        };
+        run(ctx, (Method::GET, "/api/v1/inventory"), |state| async move {
+            let (_, Json(list)) =
+                handlers::list_inventory(State(state), Query(params), Query(HashMap::new())).await?;
+            Ok(ItemPage {
+                items: list.items.into_iter().map(Item::from).collect(),
+                total: list.total,
+                page: list.page,
+                per_page: list.per_page,
+            })
+        })
+        .await
+    }
+
+    /// Items below their low-stock threshold, as of the last evaluation
+    async fn low_stock_alerts(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<LowStockAlert>> {
+        run(ctx, (Method::GET, "/api/v1/inventory/alerts"), |state| async move {
+            let Json(alerts) = handlers::low_stock_alerts(State(state)).await?;
+            Ok(alerts.into_iter().map(LowStockAlert::from).collect())
+        })
+        .await
+    }
+
+    /// Stock movement history of an item, newest first
+    #[allow(clippy::too_many_arguments)]
+    async fn movements(
+        &self,
+        ctx: &Context<'_>,
+        sku: String,
+        #[graphql(name = "type")] movement_type: Option<String>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        #[graphql(default = 1)] page: i32,
+        #[graphql(default = 20, desc = "1-100")] per_page: i32,
+    ) -> async_graphql::Result<MovementPage> {
+        let params = MovementParams {
+            page,
+            per_page,
+            movement_type,
+            from,
+            to,
+        };
+        run(ctx, (Method::GET, "/api/v1/inventory/:sku/movements"), |state| async move {
+            let Json(list) =
+                handlers::list_stock_movements(State(state), Path(sku), Query(params)).await?;
+            Ok(MovementPage {
+                items: list.items.into_iter().map(StockMovement::from).collect(),
+                total: list.total,
+                page: list.page,
+                per_page: list.per_page,
+            })
+        })
+        .await
+    }
+
+    /// Units orders currently hold, newest reservation first
+    async fn reservations(
+        &self,
+        ctx: &Context<'_>,
+        sku: Option<String>,
+        order_id: Option<String>,
+        #[graphql(default = 50, desc = "1-500")] limit: i32,
+    ) -> async_graphql::Result<Vec<ActiveReservation>> {
+        run(ctx, (Method::GET, "/api/v1/reservations"), |state| async move {
+            let reservations = state
+                .db
+                .active_reservations(
+                    sku.as_deref(),
+                    order_id.as_deref(),
+                    i64::from(limit.clamp(1, MAX_RESERVATIONS)),
+                )
+                .await?;
+            Ok(reservations.into_iter().map(ActiveReservation::from).collect())
+        })
+        .await
+    }
+}
+
+// =============================================================================
+// MUTATIONS
+// =============================================================================
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Reserve stock for an order, as POST /api/v1/inventory/reserve
+    async fn reserve(&self, ctx: &Context<'_>, input: ReserveInput) -> async_graphql::Result<Reservation> {
+        let client = ctx.data_opt::<RequestClient>().cloned().unwrap_or_default();
+        run(ctx, (Method::POST, "/api/v1/inventory/reserve"), |state| async move {
+            let request = ValidJson::new(models::ReserveStockRequest::from(input))?;
+            let Json(reservation) = handlers::reserve_stock(State(state), client, request).await?;
+            Ok(reservation.into())
+        })
+        .await
+    }
+
+    /// Give back reserved units, as POST /api/v1/inventory/release
+    async fn release(
+        &self,
+        ctx: &Context<'_>,
+        sku: String,
+        quantity: i32,
+        order_id: String,
+    ) -> async_graphql::Result<Release> {
+        run(ctx, (Method::POST, "/api/v1/inventory/release"), |state| async move {
+            let released = Release {
+                sku: sku.clone(),
+                quantity,
+                order_id: order_id.clone(),
+            };
+            let request = ValidJson::new(models::ReleaseStockRequest {
+                sku,
+                quantity,
+                order_id,
+            })?;
+            let _ = handlers::release_stock(State(state), request).await?;
+            Ok(released)
+        })
+        .await
+    }
+
+    /// Change the quantity on hand, as POST /api/v1/inventory/adjust
+    async fn adjust(
+        &self,
+        ctx: &Context<'_>,
+        sku: String,
+        delta: i32,
+        reason: String,
+        #[graphql(desc = "Only adjust if the item is still at this version")] expected_version: Option<i64>,
+    ) -> async_graphql::Result<Item> {
+        run(ctx, (Method::POST, "/api/v1/inventory/adjust"), |state| async move {
+            let request = ValidJson::new(models::AdjustStockRequest {
+                sku,
+                delta,
+                reason,
+                expected_version,
+            })?;
+            let (_, _, Json(item)) = handlers::adjust_stock(
+                State(state),
+                Query(DryRunParams::default()),
+                IfMatch::default(),
+                request,
+            )
+            .await?;
+            Ok(item.into())
+        })
+        .await
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_exposes_operations() {
+        let sdl = schema().sdl();
+        for field in ["item(", "items(", "lowStockAlerts", "movements(", "reservations("] {
+            assert!(sdl.contains(field), "missing query {}", field);
+        }
+        for field in ["reserve(", "release(", "adjust("] {
+            assert!(sdl.contains(field), "missing mutation {}", field);
+        }
+        assert!(sdl.contains("type: String!"));
+    }
+
+    #[test]
+    fn test_field_error_carries_rest_code() {
+        let error = field_error(AppError::InsufficientStock {
+            available: 2,
+            requested: 5,
+        });
+        let extensions = error.extensions.unwrap();
+        assert_eq!(
+            extensions.get("code"),
+            Some(&async_graphql::Value::from("INSUFFICIENT_STOCK"))
+        );
+        assert_eq!(extensions.get("status"), Some(&async_graphql::Value::from(409)));
+    }
+}
@@ -0,0 +1,441 @@
+// =============================================================================
+// GRPC CONVERSIONS
+// =============================================================================
+// Between the protobuf messages (pb) and the REST models, and from AppError
+// to gRPC status codes.
+//
+// ERROR CODES:
+//   NOT_FOUND                        → NOT_FOUND
+//   BAD_REQUEST                      → INVALID_ARGUMENT
+//   INSUFFICIENT_STOCK,
+//   RESERVATION_LIMIT_EXCEEDED       → FAILED_PRECONDITION
+//   CONFLICT, COMMITMENT_CONFLICT    → ABORTED
+//   CATALOG_QUOTA_EXCEEDED,
+//   FAIR_SHARE_EXCEEDED,
+//   RATE_LIMITED                     → RESOURCE_EXHAUSTED
+//   READ_ONLY, FEATURE_DISABLED      → PERMISSION_DENIED
+//   SERVICE_UNAVAILABLE              → UNAVAILABLE
+//   DEADLINE_EXCEEDED                → DEADLINE_EXCEEDED
+//   DATABASE_ERROR, INTERNAL_ERROR   → INTERNAL
+// The REST error code travels in the `error-code` metadata entry, so clients
+// can tell INSUFFICIENT_STOCK from RESERVATION_LIMIT_EXCEEDED.
+// =============================================================================
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tonic::metadata::MetadataValue;
+use tonic::{Code, Request, Status};
+
+use super::pb;
+use crate::attributes;
+use crate::error::AppError;
+use crate::config::Config;
+use crate::fairness::RequestClient;
+use crate::handlers::{self, ListParams};
+use crate::rate_limit;
+use crate::validation;
+use crate::models::{
+    AdjustStockRequest, BumpedHold, HoldType, InventoryItem, InventoryListResponse,
+    ReleaseStockRequest, ReservationResponse, ReserveStockRequest, SalesChannel, SortBy, SortOrder,
+};
+
+/// Metadata entry carrying the REST error code of a failed call
+pub const ERROR_CODE_METADATA: &str = "error-code";
+
+// -----------------------------------------------------------------------------
+// ERRORS
+// -----------------------------------------------------------------------------
+impl From<AppError> for Status {
+    fn from(error: AppError) -> Self {
+        let (code, error_code, message) = match &error {
+            AppError::NotFound(msg) => (Code::NotFound, "NOT_FOUND", msg.clone()),
+            AppError::BadRequest(msg) => (Code::InvalidArgument, "BAD_REQUEST", msg.clone()),
+            AppError::Validation(errors) => (
+                Code::InvalidArgument,
+                "VALIDATION_FAILED",
+                validation::summary(errors),
+            ),
+            AppError::InsufficientStock {
+                available,
+                requested,
+            } => (
+                Code::FailedPrecondition,
+                "INSUFFICIENT_STOCK",
+                format!("Available: {}, Requested: {}", available, requested),
+            ),
+            AppError::ReservationLimit(msg) => (
+                Code::FailedPrecondition,
+                "RESERVATION_LIMIT_EXCEEDED",
+                msg.clone(),
+            ),
+            AppError::Conflict(msg) => (Code::Aborted, "CONFLICT", msg.clone()),
+            AppError::CommitmentConflict(msg) => {
+                (Code::Aborted, "COMMITMENT_CONFLICT", msg.clone())
+            }
+            AppError::VersionMismatch { expected, current } => (
+                Code::Aborted,
+                "VERSION_MISMATCH",
+                format!("Item is at version {}, not {}", current, expected),
+            ),
+            AppError::CatalogQuotaExceeded(msg) => (
+                Code::ResourceExhausted,
+                "CATALOG_QUOTA_EXCEEDED",
+                msg.clone(),
+            ),
+            AppError::FairShareExceeded(msg) => (
+                Code::ResourceExhausted,
+                "FAIR_SHARE_EXCEEDED",
+                msg.clone(),
+            ),
+            AppError::ServiceUnavailable { reason, .. } => {
+                (Code::Unavailable, "SERVICE_UNAVAILABLE", reason.clone())
+            }
+            AppError::DeadlineExceeded(_) => (
+                Code::DeadlineExceeded,
+                "DEADLINE_EXCEEDED",
+                "Request deadline exceeded".to_string(),
+            ),
+            AppError::ReadOnly(msg) => (Code::PermissionDenied, "READ_ONLY", msg.clone()),
+            AppError::FeatureDisabled(msg) => {
+                (Code::PermissionDenied, "FEATURE_DISABLED", msg.clone())
+            }
+            AppError::RateLimited { .. } => (
+                Code::ResourceExhausted,
+                "RATE_LIMITED",
+                "Too many requests, please slow down".to_string(),
+            ),
+            AppError::FaultInjected { status, reason } => {
+                let code = if *status == axum::http::StatusCode::SERVICE_UNAVAILABLE {
+                    Code::Unavailable
+                } else {
+                    Code::Internal
+                };
+                (code, "FAULT_INJECTED", reason.clone())
+            }
+            // Don't expose internal details, as on the REST side
+            AppError::Database(_) => (
+                Code::Internal,
+                "DATABASE_ERROR",
+                "A database error occurred".to_string(),
+            ),
+            AppError::Internal(msg) => (Code::Internal, "INTERNAL_ERROR", msg.clone()),
+        };
+
+        tracing::error!(error_code, message = %message, "gRPC call failed");
+
+        let mut status = Status::new(code, message);
+        status
+            .metadata_mut()
+            .insert(ERROR_CODE_METADATA, MetadataValue::from_static(error_code));
+        status
+    }
+}
+
+/// Status code name, as used in the grpc_requests_total `code` label
+pub fn code_name(code: Code) -> &'static str {
+    match code {
+        Code::Ok => "OK",
+        Code::Cancelled => "CANCELLED",
+        Code::Unknown => "UNKNOWN",
+        Code::InvalidArgument => "INVALID_ARGUMENT",
+        Code::DeadlineExceeded => "DEADLINE_EXCEEDED",
+        Code::NotFound => "NOT_FOUND",
+        Code::AlreadyExists => "ALREADY_EXISTS",
+        Code::PermissionDenied => "PERMISSION_DENIED",
+        Code::ResourceExhausted => "RESOURCE_EXHAUSTED",
+        Code::FailedPrecondition => "FAILED_PRECONDITION",
+        Code::Aborted => "ABORTED",
+        Code::OutOfRange => "OUT_OF_RANGE",
+        Code::Unimplemented => "UNIMPLEMENTED",
+        Code::Internal => "INTERNAL",
+        Code::Unavailable => "UNAVAILABLE",
+        Code::DataLoss => "DATA_LOSS",
+        Code::Unauthenticated => "UNAUTHENTICATED",
+    }
+}
+
+// -----------------------------------------------------------------------------
+// REQUESTS
+// -----------------------------------------------------------------------------
+/// Query parameters of the equivalent REST call: the typed ones, and the
+/// raw map the attribute filters are parsed from
+pub fn list_params(
+    request: pb::ListInventoryRequest,
+) -> Result<(ListParams, HashMap<String, String>), AppError> {
+    let sort_by = match enum_value::<pb::SortBy>(request.sort_by, "sort_by")? {
+        pb::SortBy::Unspecified | pb::SortBy::Sku => SortBy::Sku,
+        pb::SortBy::Name => SortBy::Name,
+        pb::SortBy::Quantity => SortBy::Quantity,
+        pb::SortBy::Available => SortBy::Available,
+        pb::SortBy::Warehouse => SortBy::Warehouse,
+        pb::SortBy::UpdatedAt => SortBy::UpdatedAt,
+    };
+    let order = match enum_value::<pb::SortOrder>(request.order, "order")? {
+        pb::SortOrder::Unspecified | pb::SortOrder::Asc => SortOrder::Asc,
+        pb::SortOrder::Desc => SortOrder::Desc,
+    };
+
+    let params = ListParams {
+        // proto3 can't tell 0 from unset
+        page: match request.page {
+            0 => handlers::default_page(),
+            page => page,
+        },
+        per_page: match request.per_page {
+            0 => handlers::default_per_page(),
+            per_page => per_page,
+        },
+        warehouse: request.warehouse,
+        sku_prefix: request.sku_prefix,
+        name_contains: request.name_contains,
+        low_stock_only: request.low_stock_only,
+        sort_by,
+        order,
+    };
+    let raw = request
+        .attributes
+        .into_iter()
+        .map(|(name, value)| (format!("{}{}", attributes::FILTER_PREFIX, name), value))
+        .collect();
+
+    Ok((params, raw))
+}
+
+impl TryFrom<pb::ReserveStockRequest> for ReserveStockRequest {
+    type Error = AppError;
+
+    fn try_from(request: pb::ReserveStockRequest) -> Result<Self, AppError> {
+        let channel = match enum_value::<pb::SalesChannel>(request.channel, "channel")? {
+            pb::SalesChannel::Unspecified => SalesChannel::Unknown,
+            pb::SalesChannel::Web => SalesChannel::Web,
+            pb::SalesChannel::Pos => SalesChannel::Pos,
+            pb::SalesChannel::B2b => SalesChannel::B2b,
+        };
+        let hold = match enum_value::<pb::HoldType>(request.hold, "hold")? {
+            pb::HoldType::Unspecified | pb::HoldType::Hard => HoldType::Hard,
+            pb::HoldType::Soft => HoldType::Soft,
+        };
+
+        Ok(Self {
+            sku: request.sku,
+            quantity: request.quantity,
+            order_id: request.order_id,
+            channel,
+            hold,
+            priority: request.priority,
+            // Set by the handler from the fairness policy
+            quota: None,
+        })
+    }
+}
+
+/// The calling client, as the REST extractor finds it: a known x-api-key
+/// metadata entry, else the peer address
+pub fn request_client<T>(request: &Request<T>, config: &Config) -> RequestClient {
+    let headers = request.metadata().clone().into_headers();
+    let peer = request.remote_addr().map(|addr| addr.ip());
+    RequestClient(rate_limit::client_id(&headers, peer, &config.api_keys, &config.trusted_proxies))
+}
+
+impl From<pb::ReleaseStockRequest> for ReleaseStockRequest {
+    fn from(request: pb::ReleaseStockRequest) -> Self {
+        Self {
+            sku: request.sku,
+            quantity: request.quantity,
+            order_id: request.order_id,
+        }
+    }
+}
+
+impl From<pb::AdjustStockRequest> for AdjustStockRequest {
+    fn from(request: pb::AdjustStockRequest) -> Self {
+        Self {
+            sku: request.sku,
+            delta: request.delta,
+            reason: request.reason,
+            expected_version: None,
+        }
+    }
+}
+
+/// Decode an enum field; values this build doesn't know are rejected
+/// rather than read as the default
+fn enum_value<E: TryFrom<i32>>(value: i32, field: &str) -> Result<E, AppError> {
+    E::try_from(value)
+        .map_err(|_| AppError::BadRequest(format!("Unknown {} value {}", field, value)))
+}
+
+// -----------------------------------------------------------------------------
+// RESPONSES
+// -----------------------------------------------------------------------------
+impl From<InventoryItem> for pb::Item {
+    fn from(item: InventoryItem) -> Self {
+        Self {
+            available: item.available(),
+            id: item.id.to_string(),
+            sku: item.sku,
+            name: item.name,
+            quantity: item.quantity,
+            reserved: item.reserved,
+            warehouse: item.warehouse,
+            low_stock_threshold: item.low_stock_threshold,
+            max_per_order: item.max_per_order,
+            max_reserved_pct: item.max_reserved_pct,
+            threshold_manual: item.threshold_manual,
+            attributes: match item.attributes {
+                serde_json::Value::Object(fields) => Some(to_struct(fields)),
+                _ => None,
+            },
+            image_url: item.image_url,
+            spec_url: item.spec_url,
+            unit_price: item.unit_price,
+            created_at: Some(timestamp(item.created_at)),
+            updated_at: Some(timestamp(item.updated_at)),
+        }
+    }
+}
+
+impl From<InventoryListResponse> for pb::ListInventoryResponse {
+    fn from(list: InventoryListResponse) -> Self {
+        Self {
+            items: list.items.into_iter().map(pb::Item::from).collect(),
+            total: list.total,
+            page: list.page,
+            per_page: list.per_page,
+        }
+    }
+}
+
+impl From<ReservationResponse> for pb::Reservation {
+    fn from(reservation: ReservationResponse) -> Self {
+        Self {
+            reservation_id: reservation.reservation_id,
+            sku: reservation.sku,
+            quantity: reservation.quantity,
+            created_at: Some(timestamp(reservation.created_at)),
+            expires_at: reservation.expires_at.map(timestamp),
+            bumped: reservation
+                .bumped
+                .into_iter()
+                .map(pb::BumpedHold::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<BumpedHold> for pb::BumpedHold {
+    fn from(hold: BumpedHold) -> Self {
+        Self {
+            order_id: hold.order_id,
+            quantity: hold.quantity,
+            priority: hold.priority,
+        }
+    }
+}
+
+fn timestamp(at: DateTime<Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: at.timestamp(),
+        nanos: at.timestamp_subsec_nanos() as i32,
+    }
+}
+
+/// JSON object → google.protobuf.Struct
+fn to_struct(fields: serde_json::Map<String, serde_json::Value>) -> prost_types::Struct {
+    prost_types::Struct {
+        fields: fields
+            .into_iter()
+            .map(|(name, value)| (name, to_value(value)))
+            .collect(),
+    }
+}
+
+fn to_value(value: serde_json::Value) -> prost_types::Value {
+    use prost_types::value::Kind;
+
+    let kind = match value {
+        serde_json::Value::Null => Kind::NullValue(0),
+        serde_json::Value::Bool(b) => Kind::BoolValue(b),
+        // Struct numbers are doubles, like JSON's
+        serde_json::Value::Number(n) => Kind::NumberValue(n.as_f64().unwrap_or_default()),
+        serde_json::Value::String(s) => Kind::StringValue(s),
+        serde_json::Value::Array(values) => Kind::ListValue(prost_types::ListValue {
+            values: values.into_iter().map(to_value).collect(),
+        }),
+        serde_json::Value::Object(fields) => Kind::StructValue(to_struct(fields)),
+    };
+    prost_types::Value { kind: Some(kind) }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_keeps_error_code() {
+        let status = Status::from(AppError::ReservationLimit("max 5 per order".to_string()));
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        assert_eq!(status.message(), "max 5 per order");
+        assert_eq!(
+            status.metadata().get(ERROR_CODE_METADATA).unwrap(),
+            "RESERVATION_LIMIT_EXCEEDED"
+        );
+
+        let status = Status::from(AppError::Internal("boom".to_string()));
+        assert_eq!(code_name(status.code()), "INTERNAL");
+    }
+
+    #[test]
+    fn test_reserve_request_enums() {
+        let request = pb::ReserveStockRequest {
+            sku: "SKU-1".to_string(),
+            quantity: 2,
+            order_id: "ORD-1".to_string(),
+            channel: pb::SalesChannel::Pos as i32,
+            hold: pb::HoldType::Unspecified as i32,
+            priority: 0,
+        };
+        let converted = ReserveStockRequest::try_from(request.clone()).unwrap();
+        assert_eq!(converted.channel, SalesChannel::Pos);
+        assert_eq!(converted.hold, HoldType::Hard);
+
+        let unknown = pb::ReserveStockRequest {
+            channel: 42,
+            ..request
+        };
+        assert!(ReserveStockRequest::try_from(unknown).is_err());
+    }
+
+    #[test]
+    fn test_list_params_defaults() {
+        let request = pb::ListInventoryRequest {
+            attributes: HashMap::from([("color".to_string(), "red".to_string())]),
+            ..Default::default()
+        };
+        let (params, raw) = list_params(request).unwrap();
+        assert_eq!(params.page, 1);
+        assert_eq!(params.per_page, 20);
+        assert_eq!(params.sort_by, SortBy::Sku);
+        assert_eq!(raw.get("attr.color").map(String::as_str), Some("red"));
+    }
+
+    #[test]
+    fn test_attributes_to_struct() {
+        let json = serde_json::json!({ "color": "red", "touch": true, "ports": [1, 2] });
+        let serde_json::Value::Object(fields) = json else {
+            unreachable!()
+        };
+        let converted = to_struct(fields);
+        assert_eq!(
+            converted.fields["touch"].kind,
+            Some(prost_types::value::Kind::BoolValue(true))
+        );
+        assert!(matches!(
+            &converted.fields["ports"].kind,
+            Some(prost_types::value::Kind::ListValue(list)) if list.values.len() == 2
+        ));
+    }
+}
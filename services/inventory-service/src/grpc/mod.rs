@@ -0,0 +1,55 @@
+// =============================================================================
+// GRPC MODULE
+// =============================================================================
+// gRPC API for the lab's gRPC-first services, next to the REST API.
+//
+// WHAT IT SERVES:
+// inventory.v1.InventoryService (proto/inventory/v1/inventory.proto) on
+// GRPC_PORT (default 50051), unless GRPC_ENABLED=false:
+// - ListInventory, GetItem
+// - ReserveStock, ReleaseStock
+// - AdjustStock
+// Each RPC runs the REST handler of the same operation (see service.rs), on
+// the same Database, caches and metrics.
+//
+// METRICS:
+// - grpc_requests_total{method, code}        one per call, by status code
+// - grpc_request_duration_seconds{method}    call latency
+// Handler metrics (reservations, stock levels, DB timings) count gRPC and
+// REST calls alike.
+//
+// LEARNING NOTES:
+// - The code in `pb` is generated from the .proto by build.rs at compile
+//   time (tonic-build); edit the .proto, not the generated code
+// - Errors carry the REST error code in `error-code` metadata (convert.rs)
+// - The server runs as a supervised task ("grpc-server"): if it stops, it
+//   is restarted and shows up in /api/v1/admin/tasks
+// =============================================================================
+
+mod convert;
+mod service;
+
+use anyhow::{Context, Result};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::AppState;
+
+/// Code generated from proto/inventory/v1/inventory.proto
+pub mod pb {
+    tonic::include_proto!("inventory.v1");
+}
+
+/// Serve the gRPC API on `addr` until the server fails
+pub async fn serve(state: Arc<AppState>, addr: SocketAddr) -> Result<()> {
+    let service = pb::inventory_service_server::InventoryServiceServer::new(
+        service::InventoryGrpc::new(state),
+    );
+
+    tracing::info!(address = %addr, "gRPC server is listening");
+    tonic::transport::Server::builder()
+        .add_service(service)
+        .serve(addr)
+        .await
+        .context("gRPC server failed")
+}
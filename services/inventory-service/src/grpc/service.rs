@@ -0,0 +1,209 @@
+// =============================================================================
+// GRPC SERVICE
+// =============================================================================
+// inventory.v1.InventoryService, implemented by calling the REST handlers.
+//
+// Every RPC converts its message to the REST request, runs the handler with
+// the same state, and converts the result back. The handler does the work
+// (validation, limits, fair queue, fast path, caches, audit trail, metrics),
+// so both APIs always behave the same.
+//
+// LEARNING NOTES:
+// - The REST middleware doesn't run here, so `call` applies the parts that
+//   matter to gRPC callers: DISABLED_ENDPOINTS, public read-only mode and
+//   the request deadline (grpc-timeout, capped at REQUEST_TIMEOUT_MS)
+// - Each RPC names the REST route it mirrors; feature groups and public
+//   mode rules are looked up by that route
+// =============================================================================
+
+use axum::extract::{Path, Query, State};
+use axum::http::Method;
+use axum::Json;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tonic::{Code, Request, Response, Status};
+
+use super::convert;
+use super::pb::{self, inventory_service_server::InventoryService};
+use crate::deadline;
+use crate::error::AppError;
+use crate::handlers;
+use crate::metrics;
+use crate::public_mode;
+use crate::validation::ValidJson;
+use crate::versioning::IfMatch;
+use crate::AppState;
+
+pub struct InventoryGrpc {
+    state: Arc<AppState>,
+}
+
+impl InventoryGrpc {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+
+    /// Run one RPC as the REST call `method route` would run, and record it
+    async fn call<T, R, F, Fut>(
+        &self,
+        rpc: &'static str,
+        (method, route): (Method, &'static str),
+        request: Request<T>,
+        handle: F,
+    ) -> Result<Response<R>, Status>
+    where
+        F: FnOnce(Arc<AppState>, T) -> Fut,
+        Fut: Future<Output = Result<R, AppError>>,
+    {
+        let start = Instant::now();
+        let result = self.guarded(&method, route, request, handle).await;
+
+        let code = match &result {
+            Ok(_) => Code::Ok,
+            Err(status) => status.code(),
+        };
+        metrics::record_grpc_request(rpc, convert::code_name(code), start.elapsed().as_secs_f64());
+
+        result.map(Response::new)
+    }
+
+    async fn guarded<T, R, F, Fut>(
+        &self,
+        method: &Method,
+        route: &'static str,
+        request: Request<T>,
+        handle: F,
+    ) -> Result<R, Status>
+    where
+        F: FnOnce(Arc<AppState>, T) -> Fut,
+        Fut: Future<Output = Result<R, AppError>>,
+    {
+        let state = &self.state;
+        state.config.features.check_route(route)?;
+        if state.public_mode.is_some() {
+            if let Some(reason) = public_mode::rejection(method, route) {
+                return Err(AppError::ReadOnly(reason.to_string()).into());
+            }
+        }
+
+        let configured = Duration::from_millis(state.config.request_timeout_ms);
+        let budget =
+            deadline::effective_budget(&request.metadata().clone().into_headers(), configured);
+        let budget_ms = budget.as_millis() as u64;
+        if budget.is_zero() {
+            return Err(AppError::DeadlineExceeded(budget_ms).into());
+        }
+
+        match deadline::run_within(budget, handle(state.clone(), request.into_inner())).await {
+            Some(result) => result.map_err(Status::from),
+            None => {
+                tracing::warn!(route, budget_ms, "gRPC call deadline exceeded");
+                Err(AppError::DeadlineExceeded(budget_ms).into())
+            }
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl InventoryService for InventoryGrpc {
+    async fn list_inventory(
+        &self,
+        request: Request<pb::ListInventoryRequest>,
+    ) -> Result<Response<pb::ListInventoryResponse>, Status> {
+        self.call(
+            "ListInventory",
+            (Method::GET, "/api/v1/inventory"),
+            request,
+            |state, request| async move {
+                let (params, raw) = convert::list_params(request)?;
+                let (_, Json(list)) =
+                    handlers::list_inventory(State(state), Query(params), Query(raw)).await?;
+                Ok(list.into())
+            },
+        )
+        .await
+    }
+
+    async fn get_item(
+        &self,
+        request: Request<pb::GetItemRequest>,
+    ) -> Result<Response<pb::Item>, Status> {
+        self.call(
+            "GetItem",
+            (Method::GET, "/api/v1/inventory/:sku"),
+            request,
+            |state, request| async move {
+                let (_, _, Json(item)) = handlers::get_item(State(state), Path(request.sku)).await?;
+                Ok(item.into())
+            },
+        )
+        .await
+    }
+
+    async fn reserve_stock(
+        &self,
+        request: Request<pb::ReserveStockRequest>,
+    ) -> Result<Response<pb::Reservation>, Status> {
+        let client = convert::request_client(&request, &self.state.config);
+        self.call(
+            "ReserveStock",
+            (Method::POST, "/api/v1/inventory/reserve"),
+            request,
+            |state, request| async move {
+                let request = request.try_into()?;
+                let Json(reservation) =
+                    handlers::reserve_stock(
+                        State(state),
+                        client,
+                        ValidJson::new(request)?,
+                    ).await?;
+                Ok(reservation.into())
+            },
+        )
+        .await
+    }
+
+    async fn release_stock(
+        &self,
+        request: Request<pb::ReleaseStockRequest>,
+    ) -> Result<Response<pb::ReleaseStockResponse>, Status> {
+        self.call(
+            "ReleaseStock",
+            (Method::POST, "/api/v1/inventory/release"),
+            request,
+            |state, request| async move {
+                let response = pb::ReleaseStockResponse {
+                    sku: request.sku.clone(),
+                    quantity: request.quantity,
+                };
+                // The REST body only echoes the request
+                let Json(_) = handlers::release_stock(State(state), ValidJson::new(request.into())?).await?;
+                Ok(response)
+            },
+        )
+        .await
+    }
+
+    async fn adjust_stock(
+        &self,
+        request: Request<pb::AdjustStockRequest>,
+    ) -> Result<Response<pb::Item>, Status> {
+        self.call(
+            "AdjustStock",
+            (Method::POST, "/api/v1/inventory/adjust"),
+            request,
+            |state, request| async move {
+                let (_, _, Json(item)) = handlers::adjust_stock(
+                    State(state),
+                    Query(handlers::DryRunParams::default()),
+                    IfMatch::default(),
+                    ValidJson::new(request.into())?,
+                )
+                .await?;
+                Ok(item.into())
+            },
+        )
+        .await
+    }
+}
@@ -16,17 +16,52 @@
 // =============================================================================
 
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    body::Body,
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     Json,
 };
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use utoipa::IntoParams;
 
+use crate::allocator;
+use crate::analytics;
+use crate::attributes;
+use crate::audit;
+use crate::cache_headers::{ContentETag, LastModified};
+use crate::catalog_quota;
+use crate::chaos::{Experiment, FaultRequest};
+use crate::commitments;
+use crate::db;
+use crate::deadline;
 use crate::error::{AppError, AppResult};
+use crate::export;
+use crate::fairness::RequestClient;
+use crate::import;
+use crate::item_cache;
+use crate::list_cache;
+use crate::live::{self, ChangeKind};
 use crate::metrics;
 use crate::models::*;
+use crate::loadgen::{LoadRequest, LoadStatus};
+use crate::openmetrics;
+use crate::replay;
+use crate::selftest;
+use crate::reorder;
+use crate::reports;
+use crate::snapshots;
+use crate::supervisor::TaskStatus;
+use crate::tls;
+use crate::usage;
+use crate::validation::ValidJson;
+use crate::versioning::{self, ETag, IfMatch};
 use crate::AppState;
 
 // =============================================================================
@@ -41,6 +76,14 @@ use crate::AppState;
 /// If this fails, the orchestrator will restart the container.
 ///
 /// GET /health
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "Service is alive", body = HealthResponse),
+    )
+)]
 pub async fn health_check() -> Json<HealthResponse> {
     // Simply return OK - if we can respond, we're alive
     Json(HealthResponse {
@@ -54,46 +97,92 @@ pub async fn health_check() -> Json<HealthResponse> {
 ///
 /// Checks if dependencies (database, Redis) are accessible.
 /// If this fails, the orchestrator won't send traffic to this instance.
+/// Redis is only a cache: without it the status is "degraded" and the
+/// instance stays ready, serving from Postgres (see item_cache.rs).
 ///
 /// GET /ready
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready, possibly without Redis (degraded)", body = ReadinessResponse),
+        (status = 503, description = "The database is down or the cache is still warming"),
+    )
+)]
 pub async fn readiness_check(
     State(state): State<Arc<AppState>>,
-) -> Result<Json<ReadinessResponse>, StatusCode> {
+) -> AppResult<Json<ReadinessResponse>> {
     // Check database connectivity
-    let db_healthy = state.db.health_check().await;
+    let db_healthy = state.repo.health_check().await;
 
     // Check Redis connectivity
     let redis_healthy = redis::cmd("PING")
-        .query_async::<_, String>(&mut state.redis.clone())
+        .query_async::<_, String>(&mut deadline::redis(&state.redis))
         .await
         .is_ok();
 
-    // Determine overall status
-    let all_healthy = db_healthy && redis_healthy;
-    let status = if all_healthy { "ready" } else { "not_ready" };
+    // Startup cache warming must be finished
+    let cache_warm = state.cache_warmer.is_done();
+
+    // Determine overall status; Redis being down only degrades service
+    let all_healthy = db_healthy && cache_warm;
+    let status = match (all_healthy, redis_healthy) {
+        (true, true) => "ready",
+        (true, false) => "degraded",
+        (false, _) => "not_ready",
+    };
 
     let response = ReadinessResponse {
         status: status.to_string(),
         checks: ReadinessChecks {
             database: db_healthy,
             redis: redis_healthy,
+            cache_warm,
         },
     };
 
     if all_healthy {
         Ok(Json(response))
     } else {
-        // Return 503 Service Unavailable if not ready
-        Err(StatusCode::SERVICE_UNAVAILABLE)
+        // Return 503 Service Unavailable (with Retry-After) if not ready
+        Err(AppError::ServiceUnavailable {
+            reason: format!(
+                "Dependencies not ready (database: {}, redis: {}, cache_warm: {})",
+                db_healthy, redis_healthy, cache_warm
+            ),
+            retry_after_secs: state.config.retry_after_secs,
+        })
     }
 }
 
+/// Startup probe - Has the service finished starting?
+///
+/// 503 with the boot steps while Postgres and Redis are being connected,
+/// 200 from then on. Until startup is done a smaller router answers this
+/// (see startup_probe.rs); once the handlers here run, it always is.
+///
+/// GET /startup
+#[utoipa::path(
+    get,
+    path = "/startup",
+    tag = "health",
+    responses(
+        (status = 200, description = "Started", body = StartupResponse),
+        (status = 503, description = "Still starting", body = StartupResponse),
+    )
+)]
+pub async fn startup_check(State(state): State<Arc<AppState>>) -> (StatusCode, Json<StartupResponse>) {
+    state.startup.respond()
+}
+
 // =============================================================================
 // METRICS ENDPOINT
 // =============================================================================
 /// Prometheus metrics endpoint
 ///
-/// Returns all metrics in Prometheus text format.
+/// Returns all metrics in Prometheus text format, or in OpenMetrics format
+/// when the scraper's Accept header asks for `application/openmetrics-text`.
 /// Prometheus server scrapes this endpoint periodically.
 ///
 /// GET /metrics
@@ -104,9 +193,110 @@ pub async fn readiness_check(
 /// # TYPE http_requests_total counter
 /// http_requests_total{method="GET",endpoint="/api/v1/inventory",status="200"} 42
 /// ```
-pub async fn metrics_handler(State(state): State<Arc<AppState>>) -> String {
+pub async fn metrics_handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+    // Gauges nothing else keeps current
+    if let Err(e) = allocator::sample() {
+        tracing::debug!(error = %e, "Failed to sample allocator statistics");
+    }
+
     // Render all metrics in Prometheus exposition format
-    state.metrics_handle.render()
+    let text = metrics::without_dropped_series(&state.metrics_handle.render());
+
+    let openmetrics = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(openmetrics::wants_openmetrics);
+
+    if openmetrics {
+        let created = state
+            .config
+            .openmetrics_created
+            .then_some(state.metrics_created.as_ref());
+        (
+            [(header::CONTENT_TYPE, openmetrics::OPENMETRICS_CONTENT_TYPE)],
+            openmetrics::from_prometheus_text(&text, created),
+        )
+            .into_response()
+    } else {
+        (
+            [(header::CONTENT_TYPE, openmetrics::PROMETHEUS_CONTENT_TYPE)],
+            text,
+        )
+            .into_response()
+    }
+}
+
+// =============================================================================
+// DEBUG ENDPOINTS
+// =============================================================================
+/// Runtime introspection
+///
+/// A quick look at the service's internals between full dashboards:
+/// pool usage, cache size, live stream clients, the audit outbox and
+/// background task status.
+///
+/// GET /debug/vars
+///
+/// # Example Response
+/// ```json
+/// {
+///   "uptime_seconds": 3600,
+///   "database_pools": [
+///     { "pool": "primary", "size": 4, "idle": 3, "max_connections": 10 },
+///     { "pool": "replica-1", "size": 2, "idle": 2, "max_connections": 10 }
+///   ],
+///   "cache_entries": 42,
+///   "live_subscribers": 3,
+///   "audit_outbox": [{ "follower": "webhooks", "pending": 0 }],
+///   "background_tasks": [],
+///   "allocator": "system"
+/// }
+/// ```
+pub async fn debug_vars(State(state): State<Arc<AppState>>) -> Json<DebugVarsResponse> {
+    // Both counts are best effort: a slow Redis or an unreachable Postgres
+    // leaves theirs out instead of holding up the page
+    let (cache_entries, audit_outbox) = tokio::join!(
+        tokio::time::timeout(DEBUG_COUNT_TIMEOUT, item_cache::count(&state.redis)),
+        tokio::time::timeout(DEBUG_COUNT_TIMEOUT, state.db.audit_outbox_backlog()),
+    );
+
+    Json(DebugVarsResponse {
+        uptime_seconds: (chrono::Utc::now() - state.started_at).num_seconds(),
+        database_pools: state.db.all_pool_stats(),
+        cache_entries: cache_entries.ok().and_then(Result::ok),
+        live_subscribers: state.live.subscribers(),
+        audit_outbox: audit_outbox.ok().and_then(Result::ok),
+        background_tasks: state.supervisor.statuses(),
+        allocator: allocator::ALLOCATOR_NAME.to_string(),
+    })
+}
+
+/// How long /debug/vars waits for each of its counts
+const DEBUG_COUNT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Memory allocator statistics
+///
+/// Returns jemalloc heap statistics when built with the `jemalloc` feature
+/// (the same numbers /metrics exports as `allocator_bytes`). With the system
+/// allocator `stats` is null.
+///
+/// GET /debug/allocator
+///
+/// # Example Response
+/// ```json
+/// {
+///   "allocator": "jemalloc",
+///   "stats": { "allocated": 5242880, "active": 6291456, "metadata": 1048576,
+///              "resident": 8388608, "mapped": 12582912, "retained": 2097152 }
+/// }
+/// ```
+pub async fn debug_allocator() -> AppResult<Json<AllocatorResponse>> {
+    let stats = allocator::sample()?;
+
+    Ok(Json(AllocatorResponse {
+        allocator: allocator::ALLOCATOR_NAME.to_string(),
+        stats,
+    }))
 }
 
 // =============================================================================
@@ -119,8 +309,9 @@ pub async fn metrics_handler(State(state): State<Arc<AppState>>) -> String {
 /// Query parameters for list endpoint
 ///
 /// # Example
-/// GET /api/v1/inventory?page=2&per_page=20
-#[derive(Debug, Deserialize)]
+/// GET /api/v1/inventory?page=2&per_page=20&warehouse=JKT-1&sort_by=available&order=desc
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListParams {
     /// Page number (1-indexed, default: 1)
     #[serde(default = "default_page")]
@@ -129,12 +320,36 @@ pub struct ListParams {
     /// Items per page (default: 20, max: 100)
     #[serde(default = "default_per_page")]
     pub per_page: i32,
+
+    /// Only list items in this warehouse (default: all)
+    #[serde(default)]
+    pub warehouse: Option<String>,
+
+    /// Only SKUs starting with this (case-sensitive)
+    #[serde(default)]
+    pub sku_prefix: Option<String>,
+
+    /// Only names containing this (case-insensitive)
+    #[serde(default)]
+    pub name_contains: Option<String>,
+
+    /// Only items below their low-stock threshold (default: false)
+    #[serde(default)]
+    pub low_stock_only: bool,
+
+    /// Sort column (default: sku)
+    #[serde(default)]
+    pub sort_by: SortBy,
+
+    /// Sort direction (default: asc)
+    #[serde(default)]
+    pub order: SortOrder,
 }
 
-fn default_page() -> i32 {
+pub fn default_page() -> i32 {
     1
 }
-fn default_per_page() -> i32 {
+pub fn default_per_page() -> i32 {
     20
 }
 
@@ -145,10 +360,25 @@ fn default_per_page() -> i32 {
 ///
 /// GET /api/v1/inventory
 /// GET /api/v1/inventory?page=2&per_page=50
+/// GET /api/v1/inventory?warehouse=JKT-1
+/// GET /api/v1/inventory?attr.color=red&attr.touch=true
+///
+/// The first pages are served from a short-lived Redis cache
+/// (see list_cache.rs). Responses carry a weak ETag of the page; send it
+/// back as If-None-Match to get 304 Not Modified while the page is
+/// unchanged.
 ///
 /// # Query Parameters
 /// - `page`: Page number (default: 1)
 /// - `per_page`: Items per page (default: 20, max: 100)
+/// - `warehouse`: Only items in this warehouse (default: all)
+/// - `sku_prefix`: Only SKUs starting with this
+/// - `name_contains`: Only names containing this (case-insensitive)
+/// - `low_stock_only`: Only items below their low-stock threshold
+/// - `sort_by`: sku, name, quantity, available, warehouse or updated_at
+///   (default: sku)
+/// - `order`: asc or desc (default: asc)
+/// - `attr.<name>`: Only items with this attribute value (see attributes.rs)
 ///
 /// # Response
 /// ```json
@@ -159,23 +389,74 @@ fn default_per_page() -> i32 {
 ///   "per_page": 20
 /// }
 /// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/inventory",
+    tag = "inventory",
+    params(ListParams),
+    responses(
+        (status = 200, description = "One page of items", body = InventoryListResponse,
+            headers(("ETag" = String, description = "Weak tag of the page, for If-None-Match"))),
+        (status = 304, description = "Unchanged since the If-None-Match tag"),
+        (status = 400, description = "Invalid filter or sort", body = ErrorResponse),
+    )
+)]
 pub async fn list_inventory(
     State(state): State<Arc<AppState>>,
     Query(params): Query<ListParams>,
-) -> AppResult<Json<InventoryListResponse>> {
+    Query(raw_params): Query<HashMap<String, String>>,
+) -> AppResult<(ContentETag, Json<InventoryListResponse>)> {
     // Start timing for metrics
     let start = Instant::now();
 
+    // Schemas are only needed to type the values of attribute filters
+    let filter = if raw_params.keys().any(|k| k.starts_with(attributes::FILTER_PREFIX)) {
+        let schemas = state.db.list_attribute_schemas().await?;
+        attributes::parse_filters(&raw_params, &schemas).map_err(AppError::BadRequest)?
+    } else {
+        None
+    };
+    let filter_key = filter.as_ref().map(|f| f.to_string());
+
     // Validate pagination parameters
     let page = params.page.max(1); // Minimum page is 1
     let per_page = params.per_page.clamp(1, 100); // Between 1 and 100
+    let query = list_cache::ListQuery {
+        warehouse: params.warehouse.as_deref().filter(|w| !w.is_empty()),
+        sku_prefix: params.sku_prefix.as_deref().filter(|p| !p.is_empty()),
+        name_contains: params.name_contains.as_deref().filter(|n| !n.is_empty()),
+        low_stock_only: params.low_stock_only,
+        attributes: filter_key.as_deref(),
+        sort_by: params.sort_by,
+        order: params.order,
+        page,
+        per_page,
+    };
+
+    // Try the cache first
+    let generation = match state.list_cache.get(&state.redis, &query).await {
+        list_cache::Lookup::Hit(response) => {
+            return Ok((ContentETag::of(&response), Json(response)))
+        }
+        list_cache::Lookup::Miss(generation) => Some(generation),
+        list_cache::Lookup::Bypass => None,
+    };
 
     // Fetch items from database
-    let (items, total) = state.db.list_items(page, per_page).await?;
+    let inventory_filter = InventoryFilter {
+        warehouse: query.warehouse,
+        sku_prefix: query.sku_prefix,
+        name_contains: query.name_contains,
+        low_stock_only: query.low_stock_only,
+        attributes: filter.as_ref(),
+    };
+    let (items, total) = state
+        .repo
+        .list_items(page, per_page, &inventory_filter, query.sort_by, query.order)
+        .await?;
 
     // Record metrics
     let duration = start.elapsed().as_secs_f64();
-    metrics::record_http_request("GET", "/api/v1/inventory", 200, duration);
     metrics::record_db_query("select", duration);
 
     // Update stock level gauges for each item
@@ -183,12 +464,21 @@ pub async fn list_inventory(
         metrics::set_stock_level(&item.sku, &item.warehouse, item.available());
     }
 
-    Ok(Json(InventoryListResponse {
+    let response = InventoryListResponse {
         items,
         total,
         page,
         per_page,
-    }))
+    };
+
+    if let Some(generation) = generation {
+        state
+            .list_cache
+            .put(&state.redis, generation, &query, &response)
+            .await;
+    }
+
+    Ok((ContentETag::of(&response), Json(response)))
 }
 
 // -----------------------------------------------------------------------------
@@ -202,53 +492,377 @@ pub async fn list_inventory(
 /// - `sku`: Stock Keeping Unit identifier
 ///
 /// # Response
-/// - 200 OK: Item found, returns item JSON
+/// - 200 OK: Item found, returns item JSON, with Last-Modified from
+///   `updated_at` and the item version as ETag
+/// - 304 Not Modified: If-None-Match names the current version, or
+///   unchanged since If-Modified-Since
 /// - 404 Not Found: Item doesn't exist
+#[utoipa::path(
+    get,
+    path = "/api/v1/inventory/{sku}",
+    tag = "inventory",
+    params(("sku" = String, Path, description = "Product SKU")),
+    responses(
+        (status = 200, description = "The item", body = InventoryItem,
+            headers(("ETag" = String, description = "Item version, for If-Match and If-None-Match"))),
+        (status = 304, description = "Unchanged (If-None-Match or If-Modified-Since)"),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+    )
+)]
 pub async fn get_item(
     State(state): State<Arc<AppState>>,
     Path(sku): Path<String>,
-) -> AppResult<Json<InventoryItem>> {
+) -> AppResult<(LastModified, ETag, Json<InventoryItem>)> {
     let start = Instant::now();
 
-    // Try to get from cache first (Redis)
-    let cache_key = format!("inventory:{}", sku);
-    let cached: Option<String> = redis::cmd("GET")
-        .arg(&cache_key)
-        .query_async(&mut state.redis.clone())
-        .await
-        .ok();
+    // Try to get from cache first (Redis), where recently looked-up SKUs
+    // that don't exist are remembered too; a Redis outage reads as a miss
+    match item_cache::get(&state.redis, &sku).await {
+        item_cache::Lookup::Item(item) => {
+            return Ok((LastModified(item.updated_at), ETag(item.version), Json(*item)));
+        }
+        item_cache::Lookup::Missing => {
+            return Err(AppError::NotFound(format!("SKU not found: {}", sku)));
+        }
+        item_cache::Lookup::Miss => {}
+    }
+
+    // Cache miss - fetch from database (a replica, when there are any) and
+    // store in cache (5 minutes). Not from replicas: one lagging behind a
+    // write would put the old version back in right after the write
+    // invalidated it. Concurrent misses for the SKU wait for one fetch
+    // instead of each querying Postgres (see item_cache::fill).
+    let fill = {
+        let (repo, redis) = (state.repo.clone(), state.redis.clone());
+        let (key, lock) = (sku.clone(), state.config.item_cache_fill_lock);
+        let missing_ttl = state.config.item_cache_missing_ttl_secs;
+        move || item_cache::fill(repo, redis, key, lock, missing_ttl)
+    };
+    let (item, joined) = state.item_fills.run(&sku, fill).await;
+    if joined {
+        metrics::record_cache_fill_coalesced("item", "process");
+    }
+    let item = item
+        .map_err(AppError::Internal)?
+        .ok_or_else(|| AppError::NotFound(format!("SKU not found: {}", sku)))?;
+
+    let duration = start.elapsed().as_secs_f64();
+    metrics::record_db_query("select", duration);
+
+    Ok((LastModified(item.updated_at), ETag(item.version), Json(item)))
+}
+
+// -----------------------------------------------------------------------------
+// CREATE ITEM
+// -----------------------------------------------------------------------------
+/// Add an item to the catalog
+///
+/// POST /api/v1/inventory
+///
+/// Subject to the catalog quota (see catalog_quota.rs): past the soft
+/// limit the item is created with a warning in the logs, at the hard limit
+/// it is rejected.
+///
+/// # Request Body
+/// ```json
+/// { "sku": "SKU-DOCK-001", "name": "USB-C Dock", "quantity": 20, "warehouse": "JKT-1" }
+/// ```
+///
+/// # Response
+/// - 201 Created: the new item
+/// - 400 Bad Request: invalid fields, or the SKU already exists
+/// - 409 Conflict: CATALOG_QUOTA_EXCEEDED
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory",
+    tag = "inventory",
+    request_body = CreateItemRequest,
+    responses(
+        (status = 201, description = "Item created", body = InventoryItem),
+        (status = 400, description = "Duplicate SKU", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+        (status = 409, description = "Catalog hard limit reached (CATALOG_QUOTA_EXCEEDED)", body = ErrorResponse),
+    )
+)]
+pub async fn create_item(
+    State(state): State<Arc<AppState>>,
+    ValidJson(request): ValidJson<CreateItemRequest>,
+) -> AppResult<(StatusCode, Json<InventoryItem>)> {
+    check_warehouse(&state, &request.warehouse).await?;
 
-    if let Some(cached_json) = cached {
-        // Cache hit! Parse and return
-        if let Ok(item) = serde_json::from_str::<InventoryItem>(&cached_json) {
-            let duration = start.elapsed().as_secs_f64();
-            metrics::record_http_request("GET", "/api/v1/inventory/:sku", 200, duration);
-            metrics::record_redis_operation("get", duration);
-            return Ok(Json(item));
+    let quota = state.config.catalog_quota;
+    let (item, skus) = match state.repo.create_item(&request, quota.hard_limit).await? {
+        Ok(created) => created,
+        Err(CreateItemRejection::DuplicateSku) => {
+            return Err(AppError::BadRequest(format!("SKU already exists: {}", request.sku)));
         }
+        Err(CreateItemRejection::QuotaExceeded { skus }) => {
+            metrics::set_catalog_size(skus, &quota);
+            return Err(AppError::CatalogQuotaExceeded(format!(
+                "The catalog holds {} SKUs, the limit is {} (CATALOG_HARD_LIMIT)",
+                skus,
+                quota.hard_limit.unwrap_or(skus)
+            )));
+        }
+    };
+
+    metrics::set_catalog_size(skus, &quota);
+    if quota.status(skus) == catalog_quota::QuotaStatus::OverSoft {
+        metrics::record_catalog_quota_warning();
+        tracing::warn!(
+            sku = %item.sku,
+            skus,
+            soft_limit = quota.soft_limit,
+            hard_limit = quota.hard_limit,
+            "Catalog is past its soft limit"
+        );
+    }
+
+    // Drop a cached not-found for the SKU
+    item_cache::invalidate(&state.redis, &item.sku).await;
+    list_cache::invalidate(&state.redis).await;
+    state.live.publish(ChangeKind::Created, &item.sku, item.quantity);
+    tracing::info!(sku = %item.sku, quantity = item.quantity, "Item created");
+
+    Ok((StatusCode::CREATED, Json(item)))
+}
+
+// -----------------------------------------------------------------------------
+// UPDATE ITEM
+// -----------------------------------------------------------------------------
+/// Change an item's name, home warehouse and/or low-stock threshold
+///
+/// PATCH /api/v1/inventory/:sku
+///
+/// Fields left out stay as they are. A threshold set here is pinned, like
+/// PUT /api/v1/inventory/:sku/thresholds; a new home warehouse takes over
+/// any stock the item kept there. Changes are recorded in the audit trail
+/// as "update" and in the item's revisions.
+///
+/// # Request Body
+/// ```json
+/// { "name": "USB-C Dock (Gen 2)", "warehouse": "SBY-1", "low_stock_threshold": 15,
+///   "reason": "Moved to Surabaya" }
+/// ```
+#[utoipa::path(
+    patch,
+    path = "/api/v1/inventory/{sku}",
+    tag = "inventory",
+    params(
+        ("sku" = String, Path, description = "Product SKU"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the item is at this version"),
+    ),
+    request_body = UpdateItemRequest,
+    responses(
+        (status = 200, description = "Updated item", body = InventoryItem,
+            headers(("ETag" = String, description = "Item version, for If-Match"))),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+        (status = 412, description = "Item changed since the If-Match version", body = ErrorResponse),
+    )
+)]
+pub async fn update_item(
+    State(state): State<Arc<AppState>>,
+    Path(sku): Path<String>,
+    if_match: IfMatch,
+    ValidJson(request): ValidJson<UpdateItemRequest>,
+) -> AppResult<(ETag, Json<InventoryItem>)> {
+    if let Some(warehouse) = &request.warehouse {
+        check_warehouse(&state, warehouse).await?;
     }
 
-    // Cache miss - fetch from database
     let item = state
-        .db
-        .get_by_sku(&sku)
-        .await?
+        .repo
+        .update_item(&sku, &request, if_match.0)
+        .await
+        .map_err(precondition_error)?
         .ok_or_else(|| AppError::NotFound(format!("SKU not found: {}", sku)))?;
 
-    // Store in cache for 5 minutes
-    let item_json = serde_json::to_string(&item).unwrap_or_default();
-    let _: Result<(), _> = redis::cmd("SETEX")
-        .arg(&cache_key)
-        .arg(300) // 5 minutes TTL
-        .arg(&item_json)
-        .query_async(&mut state.redis.clone())
-        .await;
+    tracing::info!(
+        sku = %sku,
+        name = %item.name,
+        warehouse = %item.warehouse,
+        threshold = item.low_stock_threshold,
+        "Item updated"
+    );
+
+    item_cache::invalidate(&state.redis, &sku).await;
+    list_cache::invalidate(&state.redis).await;
+    state.live.publish(ChangeKind::Updated, &sku, 0);
+    if request.warehouse.is_some() || request.low_stock_threshold.is_some() {
+        refresh_low_stock(&state).await;
+    }
+
+    Ok((ETag(item.version), Json(item)))
+}
+
+// -----------------------------------------------------------------------------
+// ALTERNATE IDENTIFIERS
+// -----------------------------------------------------------------------------
+/// Query parameters for identifier lookup
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LookupParams {
+    /// Canonical SKU, vendor SKU, legacy code or GTIN
+    pub id: String,
+}
+
+/// Resolve any known identifier to the canonical item
+///
+/// GET /api/v1/inventory/lookup?id=4006381333931
+///
+/// # Response
+/// - 200 OK: See LookupResponse
+/// - 404 Not Found: No item has this identifier
+#[utoipa::path(
+    get,
+    path = "/api/v1/inventory/lookup",
+    tag = "inventory",
+    params(LookupParams),
+    responses(
+        (status = 200, description = "Item the identifier belongs to", body = LookupResponse),
+        (status = 404, description = "No item has this identifier", body = ErrorResponse),
+    )
+)]
+pub async fn lookup_item(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LookupParams>,
+) -> AppResult<Json<LookupResponse>> {
+    let start = Instant::now();
+
+    let (matched_by, item) = state
+        .db
+        .lookup_item(&params.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No item known as: {}", params.id)))?;
 
     let duration = start.elapsed().as_secs_f64();
-    metrics::record_http_request("GET", "/api/v1/inventory/:sku", 200, duration);
-    metrics::record_db_query("select", duration);
+    metrics::record_db_query("lookup", duration);
 
-    Ok(Json(item))
+    Ok(Json(LookupResponse {
+        id: params.id,
+        matched_by,
+        item,
+    }))
+}
+
+/// Alternate identifiers of an item
+///
+/// GET /api/v1/inventory/:sku/identifiers
+#[utoipa::path(
+    get,
+    path = "/api/v1/inventory/{sku}/identifiers",
+    tag = "catalog",
+    params(("sku" = String, Path, description = "Product SKU")),
+    responses(
+        (status = 200, description = "Alternate identifiers of the item", body = Vec<ItemIdentifier>),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+    )
+)]
+pub async fn list_item_identifiers(
+    State(state): State<Arc<AppState>>,
+    Path(sku): Path<String>,
+) -> AppResult<Json<Vec<ItemIdentifier>>> {
+    if state.db.get_by_sku(&sku).await?.is_none() {
+        return Err(AppError::NotFound(format!("SKU not found: {}", sku)));
+    }
+
+    Ok(Json(state.db.list_item_identifiers(&sku).await?))
+}
+
+/// Add an alternate identifier to an item
+///
+/// POST /api/v1/inventory/:sku/identifiers
+///
+/// # Request Body
+/// ```json
+/// { "kind": "vendor_sku", "identifier": "ACME-LT-15" }
+/// ```
+///
+/// # Response
+/// - 201 Created: identifier added (repeating the call is harmless)
+/// - 400 Bad Request: invalid identifier, or already used by another item
+/// - 404 Not Found: SKU doesn't exist
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/{sku}/identifiers",
+    tag = "catalog",
+    params(("sku" = String, Path, description = "Product SKU")),
+    request_body = ItemIdentifierRequest,
+    responses(
+        (status = 201, description = "Identifier added", body = ItemIdentifier),
+        (status = 400, description = "Identifier already in use", body = ErrorResponse),
+        (status = 422, description = "Invalid identifier", body = ErrorResponse),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+    )
+)]
+pub async fn add_item_identifier(
+    State(state): State<Arc<AppState>>,
+    Path(sku): Path<String>,
+    ValidJson(request): ValidJson<ItemIdentifierRequest>,
+) -> AppResult<(StatusCode, Json<ItemIdentifier>)> {
+    if state.db.get_by_sku(&sku).await?.is_none() {
+        return Err(AppError::NotFound(format!("SKU not found: {}", sku)));
+    }
+    // Canonical SKUs always win a lookup, so an alias equal to one would
+    // never resolve
+    if state.db.get_by_sku(&request.identifier).await?.is_some() {
+        return Err(AppError::BadRequest(format!(
+            "'{}' is already a canonical SKU",
+            request.identifier
+        )));
+    }
+
+    let identifier = state
+        .db
+        .add_item_identifier(&sku, &request)
+        .await?
+        .map_err(|owner| {
+            AppError::BadRequest(format!(
+                "'{}' is already an identifier of {}",
+                request.identifier, owner
+            ))
+        })?;
+
+    tracing::info!(
+        sku = %sku,
+        kind = request.kind.as_str(),
+        identifier = %identifier.identifier,
+        "Alternate identifier added"
+    );
+
+    Ok((StatusCode::CREATED, Json(identifier)))
+}
+
+/// Remove an alternate identifier from an item
+///
+/// DELETE /api/v1/inventory/:sku/identifiers/:identifier
+#[utoipa::path(
+    delete,
+    path = "/api/v1/inventory/{sku}/identifiers/{identifier}",
+    tag = "catalog",
+    params(
+        ("sku" = String, Path, description = "Product SKU"),
+        ("identifier" = String, Path, description = "Identifier to remove")
+    ),
+    responses(
+        (status = 204, description = "Identifier removed"),
+        (status = 404, description = "No such identifier", body = ErrorResponse),
+    )
+)]
+pub async fn delete_item_identifier(
+    State(state): State<Arc<AppState>>,
+    Path((sku, identifier)): Path<(String, String)>,
+) -> AppResult<StatusCode> {
+    if !state.db.delete_item_identifier(&sku, &identifier).await? {
+        return Err(AppError::NotFound(format!(
+            "{} has no identifier {}",
+            sku, identifier
+        )));
+    }
+
+    tracing::info!(sku = %sku, identifier = %identifier, "Alternate identifier removed");
+    Ok(StatusCode::NO_CONTENT)
 }
 
 // -----------------------------------------------------------------------------
@@ -263,45 +877,142 @@ pub async fn get_item(
 /// {
 ///   "sku": "SKU-LAPTOP-001",
 ///   "quantity": 5,
-///   "order_id": "ORD-12345"
+///   "order_id": "ORD-12345",
+///   "channel": "web"
 /// }
 /// ```
 ///
+/// `channel` (or `source`) is optional: web, pos, b2b or unknown (default).
+///
+/// `hold` is "hard" (default) or "soft", `priority` 0-100 (default 0). When
+/// stock runs short, soft holds of other orders with a lower priority are
+/// bumped to make room; they are listed under `bumped` and published as
+/// reservation.bumped events. Soft or prioritised requests always take the
+/// Postgres path.
+///
+/// Reserving is idempotent per (order_id, sku): while the order still holds
+/// the SKU, the same request returns the first reservation with
+/// `replayed: true` and reserves nothing. The same order asking for a
+/// different quantity gets 409 CONFLICT.
+///
 /// # Response
-/// - 200 OK: Stock reserved successfully
-/// - 409 Conflict: Insufficient stock
+/// - 200 OK: Stock reserved successfully, or the order's existing reservation
+/// - 409 Conflict: Insufficient stock, or the order holds a different quantity
 /// - 404 Not Found: SKU doesn't exist
+/// - 422 Unprocessable Entity: Exceeds the SKU's reservation policy or the
+///   global MAX_RESERVE_QUANTITY cap (RESERVATION_LIMIT_EXCEEDED), or the
+///   per-order / per-client cap of the fairness policy (FAIR_SHARE_EXCEEDED)
+/// - 503 Service Unavailable: Fair queue for the SKU is full or the wait
+///   timed out (only with RESERVE_QUEUE_ENABLED)
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/reserve",
+    tag = "reservations",
+    request_body = ReserveStockRequest,
+    responses(
+        (status = 200, description = "Stock reserved", body = ReservationResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+        (status = 409, description = "Not enough stock, or a reservation limit was hit", body = ErrorResponse),
+        (status = 503, description = "Reservation queue full or timed out", body = ErrorResponse),
+    )
+)]
 pub async fn reserve_stock(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<ReserveStockRequest>,
+    RequestClient(client): RequestClient,
+    ValidJson(mut request): ValidJson<ReserveStockRequest>,
 ) -> AppResult<Json<ReservationResponse>> {
-    let start = Instant::now();
-
     // Log the reservation attempt
     tracing::info!(
         sku = %request.sku,
         quantity = request.quantity,
         order_id = %request.order_id,
+        channel = request.channel.as_str(),
         "Attempting to reserve stock"
     );
 
-    // Perform the reservation
-    let result = state.db.reserve_stock(&request).await;
+    // Global hard caps apply before any SKU-specific policy
+    if let Err(reason) = state.config.reserve_limits.check(&[request.quantity]) {
+        metrics::record_reservation(&request.sku, request.channel, false);
+        return Err(AppError::ReservationLimit(reason));
+    }
 
-    let duration = start.elapsed().as_secs_f64();
+    // Fairness policy: the per-order cap here, the per-client one in the
+    // store, which knows what the client's other orders hold
+    if let Err(reason) = state.config.policies.check_order(request.quantity) {
+        metrics::record_reservation(&request.sku, request.channel, false);
+        return Err(AppError::FairShareExceeded(reason));
+    }
+    request.quota = state.config.policies.quota_for(client);
+
+    // With fair queueing on, wait for this SKU's turn (arrival order).
+    // The turn is held until the reservation finishes. Only SKUs in the
+    // catalog get a lane (looked up through the item cache), so made-up
+    // SKUs can't grow the queue.
+    let _turn = match &state.reserve_queue {
+        Some(queue) => {
+            if let Err(e) = get_item(State(state.clone()), Path(request.sku.clone())).await {
+                metrics::record_reservation(&request.sku, request.channel, false);
+                return Err(e);
+            }
+            match queue.enter(&request.sku).await {
+                Ok(turn) => Some(turn),
+                Err(rejected) => {
+                    metrics::record_reservation(&request.sku, request.channel, false);
+                    tracing::warn!(sku = %request.sku, reason = %rejected, "Reservation not queued");
+                    return Err(AppError::ServiceUnavailable {
+                        reason: rejected.to_string(),
+                        retry_after_secs: 1,
+                    });
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Try the Redis fast path first when it's on; it hands back to the
+    // Postgres path when it can't decide on its own
+    let fast = match &state.fast_reserve {
+        Some(fast) if !request.needs_hold_handling() && request.quota.is_none() => {
+            fast.reserve(&state.db, &request).await.transpose()
+        }
+        _ => None,
+    };
+
+    let result = match fast {
+        Some(result) => result,
+        None => {
+            // Perform the reservation (timed per strategy, so they can be compared)
+            let db_start = Instant::now();
+            let result = state.repo.reserve_stock(&request).await;
+            metrics::record_db_query(
+                &format!("reserve_{}", state.db.reserve_strategy().as_str()),
+                db_start.elapsed().as_secs_f64(),
+            );
+            result
+        }
+    };
 
     match result {
+        // A retry of a reservation the order still holds: nothing changed
+        Ok(reservation) if reservation.replayed => {
+            tracing::info!(
+                reservation_id = %reservation.reservation_id,
+                order_id = %request.order_id,
+                "Reservation already held, returning it"
+            );
+            Ok(Json(reservation))
+        }
         Ok(reservation) => {
-            // Success - record metrics
-            metrics::record_http_request("POST", "/api/v1/inventory/reserve", 200, duration);
-            metrics::record_reservation(&request.sku, true);
-
-            // Invalidate cache for this SKU
-            let cache_key = format!("inventory:{}", request.sku);
-            let _: Result<(), _> = redis::cmd("DEL")
-                .arg(&cache_key)
-                .query_async(&mut state.redis.clone())
-                .await;
+            metrics::record_reservation(&request.sku, request.channel, true);
+            usage::record_reserved(i64::from(reservation.quantity));
+            note_bumped_holds(&state, &reservation).await;
+
+            // Cache the SKU as reserved; list pages are dropped
+            item_cache::refresh(state.repo.as_ref(), &state.redis, &request.sku).await;
+            list_cache::invalidate(&state.redis).await;
+            state.live.publish(ChangeKind::Reserved, &reservation.sku, reservation.quantity);
 
             tracing::info!(
                 reservation_id = %reservation.reservation_id,
@@ -311,9 +1022,17 @@ pub async fn reserve_stock(
             Ok(Json(reservation))
         }
         Err(e) => {
-            // Failure - record metrics and return error
-            metrics::record_http_request("POST", "/api/v1/inventory/reserve", 409, duration);
-            metrics::record_reservation(&request.sku, false);
+            // Failure - record metrics, audit, and return error
+            metrics::record_reservation(&request.sku, request.channel, false);
+            audit::record_failure(
+                state.repo.as_ref(),
+                "reserve",
+                &request.sku,
+                request.quantity,
+                &request.order_id,
+                &e,
+            )
+            .await;
 
             tracing::warn!(
                 sku = %request.sku,
@@ -321,128 +1040,3519 @@ pub async fn reserve_stock(
                 "Failed to reserve stock"
             );
 
-            Err(AppError::BadRequest(e.to_string()))
+            // Policy violations (422), missing SKUs (404) and stock
+            // shortfalls (409) keep their own status; everything else is
+            // reported as a bad request
+            match AppError::from_db(e) {
+                Ok(app_error) => Err(app_error),
+                Err(e) => Err(AppError::BadRequest(e.to_string())),
+            }
         }
     }
 }
 
-// -----------------------------------------------------------------------------
-// RELEASE STOCK
-// -----------------------------------------------------------------------------
-/// Release previously reserved stock
-///
-/// POST /api/v1/inventory/release
+/// Count and log soft holds a reservation bumped
+async fn note_bumped_holds(state: &AppState, reservation: &ReservationResponse) {
+    if reservation.bumped.is_empty() {
+        return;
+    }
+    metrics::record_reservations_bumped(&reservation.sku, reservation.bumped.len());
+    for hold in &reservation.bumped {
+        tracing::info!(
+            sku = %reservation.sku,
+            order_id = %hold.order_id,
+            quantity = hold.quantity,
+            priority = hold.priority,
+            "Soft hold bumped"
+        );
+    }
+
+    // Postgres released units behind the fast path's counter
+    if let Some(fast) = &state.fast_reserve {
+        fast.invalidate(&reservation.sku).await;
+    }
+}
+
+/// Reserve several SKUs for one order, all or nothing
 ///
-/// Called when an order is cancelled or expires.
+/// POST /api/v1/inventory/reserve/batch
 ///
 /// # Request Body
 /// ```json
 /// {
-///   "sku": "SKU-LAPTOP-001",
-///   "quantity": 5,
-///   "order_id": "ORD-12345"
+///   "order_id": "ORD-12345",
+///   "channel": "web",
+///   "items": [
+///     { "sku": "SKU-LAPTOP-001", "quantity": 1 },
+///     { "sku": "SKU-MOUSE-001", "quantity": 2 }
+///   ]
 /// }
 /// ```
-pub async fn release_stock(
+///
+/// All lines are reserved in one Postgres transaction; if any line can't
+/// be reserved, none is. Batches always take the Postgres path (not the
+/// Redis fast path) and skip the fair queue. Lines the order already holds
+/// come back as they were reserved, with `replayed: true`.
+///
+/// # Response
+/// - 200 OK: one reservation per line, by SKU
+/// - 400 Bad Request: invalid batch, unknown SKU or insufficient stock
+///   (the message names the line)
+/// - 422 Unprocessable Entity: a line exceeds its SKU's reservation policy,
+///   the batch exceeds MAX_RESERVE_QUANTITY / MAX_RESERVE_BATCH_QUANTITY, or
+///   a line exceeds the per-order / per-client cap of the fairness policy
+///   (FAIR_SHARE_EXCEEDED)
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/reserve/batch",
+    tag = "reservations",
+    request_body = ReserveBatchRequest,
+    responses(
+        (status = 200, description = "Every line reserved", body = ReserveBatchResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+        (status = 409, description = "A line could not be reserved; nothing was", body = ErrorResponse),
+    )
+)]
+pub async fn reserve_batch(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<ReleaseStockRequest>,
-) -> AppResult<Json<serde_json::Value>> {
-    let start = Instant::now();
+    RequestClient(client): RequestClient,
+    ValidJson(mut request): ValidJson<ReserveBatchRequest>,
+) -> AppResult<Json<ReserveBatchResponse>> {
+    request.quota = state.config.policies.quota_for(client);
+    let quantities: Vec<i32> = request.items.iter().map(|line| line.quantity).collect();
+    let record_failed = || {
+        for line in &request.items {
+            metrics::record_reservation(&line.sku, request.channel, false);
+        }
+    };
 
     tracing::info!(
-        sku = %request.sku,
-        quantity = request.quantity,
         order_id = %request.order_id,
-        "Releasing reserved stock"
+        lines = request.items.len(),
+        channel = request.channel.as_str(),
+        "Attempting to reserve batch"
     );
 
-    state.db.release_stock(&request).await?;
+    if let Err(reason) = state.config.reserve_limits.check(&quantities) {
+        record_failed();
+        return Err(AppError::ReservationLimit(reason));
+    }
 
-    // Invalidate cache
-    let cache_key = format!("inventory:{}", request.sku);
-    let _: Result<(), _> = redis::cmd("DEL")
-        .arg(&cache_key)
-        .query_async(&mut state.redis.clone())
-        .await;
+    // Fairness policy, line by line as in reserve_stock
+    if let Some(reason) = quantities.iter().find_map(|&q| state.config.policies.check_order(q).err()) {
+        record_failed();
+        return Err(AppError::FairShareExceeded(reason));
+    }
 
-    let duration = start.elapsed().as_secs_f64();
-    metrics::record_http_request("POST", "/api/v1/inventory/release", 200, duration);
+    let db_start = Instant::now();
+    let result = state.db.reserve_batch(&request).await;
+    metrics::record_db_query("reserve_batch", db_start.elapsed().as_secs_f64());
 
-    Ok(Json(serde_json::json!({
-        "status": "released",
-        "sku": request.sku,
-        "quantity": request.quantity
-    })))
+    let reservations = match result {
+        Ok(reservations) => reservations,
+        Err(e) => {
+            record_failed();
+            let total: i32 = quantities.iter().sum();
+            audit::record_failure(&state.db, "reserve", "*", total, &request.order_id, &e).await;
+            tracing::warn!(order_id = %request.order_id, error = %e, "Failed to reserve batch");
+
+            return match AppError::from_db(e) {
+                Ok(app_error) => Err(app_error),
+                Err(e) => Err(AppError::BadRequest(e.to_string())),
+            };
+        }
+    };
+
+    // Lines the order already held were returned as they were
+    for reservation in reservations.iter().filter(|r| !r.replayed) {
+        metrics::record_reservation(&reservation.sku, request.channel, true);
+        usage::record_reserved(i64::from(reservation.quantity));
+        note_bumped_holds(&state, reservation).await;
+        state.live.publish(ChangeKind::Reserved, &reservation.sku, reservation.quantity);
+
+        item_cache::refresh(state.repo.as_ref(), &state.redis, &reservation.sku).await;
+        // Postgres changed behind the fast path's counters
+        if let Some(fast) = &state.fast_reserve {
+            fast.invalidate(&reservation.sku).await;
+        }
+    }
+    list_cache::invalidate(&state.redis).await;
+
+    tracing::info!(
+        order_id = %request.order_id,
+        lines = reservations.len(),
+        "Batch reserved successfully"
+    );
+
+    Ok(Json(ReserveBatchResponse {
+        order_id: request.order_id,
+        reservations,
+    }))
 }
 
 // -----------------------------------------------------------------------------
-// ADJUST STOCK
+// TWO-PHASE COMMITMENTS
 // -----------------------------------------------------------------------------
-/// Manually adjust stock quantity
-///
-/// POST /api/v1/inventory/adjust
+/// Prepare a two-phase commitment: reserve every line, all or nothing
 ///
-/// Used for inventory corrections, receiving shipments, etc.
+/// POST /api/v1/inventory/prepare
 ///
 /// # Request Body
 /// ```json
 /// {
-///   "sku": "SKU-LAPTOP-001",
-///   "delta": 10,
-///   "reason": "Received shipment from supplier"
+///   "transaction_id": "chk-7f3a9c",
+///   "order_id": "ORD-12345",
+///   "items": [{ "sku": "SKU-LAPTOP-001", "quantity": 1 }],
+///   "timeout_secs": 120
 /// }
 /// ```
-pub async fn adjust_stock(
+///
+/// The stock stays reserved until /commit, /abort or the timeout. Repeating
+/// the same prepare returns the commitment with `applied: false`. See
+/// commitments.rs for the full contract.
+///
+/// # Response
+/// - 200 OK: the prepared commitment
+/// - 400 Bad Request: invalid request, unknown SKU or insufficient stock
+/// - 409 Conflict: the transaction ID was used for something else
+/// - 422 Unprocessable Entity: a line exceeds a reservation limit or the
+///   fairness policy (FAIR_SHARE_EXCEEDED)
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/prepare",
+    tag = "reservations",
+    request_body = PrepareCommitmentRequest,
+    responses(
+        (status = 200, description = "Every line reserved", body = StockCommitment),
+        (status = 400, description = "Invalid request or not enough stock", body = ErrorResponse),
+        (status = 409, description = "Transaction ID already used", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, or a reservation limit was hit", body = ErrorResponse),
+    )
+)]
+pub async fn prepare_commitment(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<AdjustStockRequest>,
-) -> AppResult<Json<InventoryItem>> {
-    let start = Instant::now();
+    RequestClient(client): RequestClient,
+    ValidJson(mut request): ValidJson<PrepareCommitmentRequest>,
+) -> AppResult<Json<StockCommitment>> {
+    request.quota = state.config.policies.quota_for(client);
+    let quantities: Vec<i32> = request.items.iter().map(|line| line.quantity).collect();
+    let record_failed = || {
+        for line in &request.items {
+            metrics::record_reservation(&line.sku, request.channel, false);
+        }
+    };
 
     tracing::info!(
-        sku = %request.sku,
-        delta = request.delta,
-        reason = %request.reason,
-        "Adjusting stock"
+        transaction_id = %request.transaction_id,
+        order_id = %request.order_id,
+        lines = request.items.len(),
+        timeout_secs = request.timeout_secs(),
+        "Preparing commitment"
     );
 
-    let item = state.db.adjust_stock(&request).await?;
+    if let Err(reason) = state.config.reserve_limits.check(&quantities) {
+        record_failed();
+        return Err(AppError::ReservationLimit(reason));
+    }
 
-    // Update metrics
-    metrics::set_stock_level(&item.sku, &item.warehouse, item.available());
+    if let Some(reason) = quantities.iter().find_map(|&q| state.config.policies.check_order(q).err()) {
+        record_failed();
+        return Err(AppError::FairShareExceeded(reason));
+    }
+
+    let db_start = Instant::now();
+    let result = state.db.prepare_commitment(&request).await;
+    metrics::record_db_query("prepare_commitment", db_start.elapsed().as_secs_f64());
+
+    let commitment = match result {
+        Ok(commitment) => commitment,
+        Err(e) => {
+            record_failed();
+            let total: i32 = quantities.iter().sum();
+            audit::record_failure(&state.db, "reserve", "*", total, &request.order_id, &e).await;
+            tracing::warn!(
+                transaction_id = %request.transaction_id,
+                error = %e,
+                "Failed to prepare commitment"
+            );
+
+            return match AppError::from_db(e) {
+                Ok(app_error) => Err(app_error),
+                Err(e) => Err(AppError::BadRequest(e.to_string())),
+            };
+        }
+    };
+
+    if commitment.applied {
+        for line in &commitment.lines {
+            metrics::record_reservation(&line.sku, request.channel, true);
+            usage::record_reserved(i64::from(line.quantity));
+            state.live.publish(ChangeKind::Reserved, &line.sku, line.quantity);
+        }
+        metrics::record_commitment(CommitmentState::Prepared);
+        commitments::forget_cached(&state.redis, state.fast_reserve.as_ref(), &commitment.lines)
+            .await;
+    }
+
+    Ok(Json(commitment))
+}
+
+/// Commit a prepared commitment: the reserved units are sold
+///
+/// POST /api/v1/inventory/commit
+///
+/// # Request Body
+/// ```json
+/// { "transaction_id": "chk-7f3a9c" }
+/// ```
+///
+/// Committing twice is harmless (`applied: false` the second time).
+///
+/// # Response
+/// - 200 OK: the committed commitment
+/// - 404 Not Found: no such transaction
+/// - 409 Conflict: aborted, timed out, or its stock was released meanwhile
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/commit",
+    tag = "reservations",
+    request_body = CommitmentRequest,
+    responses(
+        (status = 200, description = "Committed", body = StockCommitment),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+        (status = 404, description = "No such transaction", body = ErrorResponse),
+        (status = 409, description = "Aborted or timed out", body = ErrorResponse),
+    )
+)]
+pub async fn commit_commitment(
+    State(state): State<Arc<AppState>>,
+    ValidJson(request): ValidJson<CommitmentRequest>,
+) -> AppResult<Json<StockCommitment>> {
+    finish_commitment(&state, &request.transaction_id, CommitmentState::Committed)
+        .await
+        .map(Json)
+}
+
+/// Abort a commitment: its reserved stock is released
+///
+/// POST /api/v1/inventory/abort
+///
+/// # Request Body
+/// ```json
+/// { "transaction_id": "chk-7f3a9c" }
+/// ```
+///
+/// Aborting twice, or after the timeout, is harmless. Aborting an ID that
+/// was never prepared records it, so a late prepare with it fails.
+///
+/// # Response
+/// - 200 OK: the aborted (or expired) commitment
+/// - 409 Conflict: already committed
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/abort",
+    tag = "reservations",
+    request_body = CommitmentRequest,
+    responses(
+        (status = 200, description = "Aborted", body = StockCommitment),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+        (status = 409, description = "Already committed", body = ErrorResponse),
+    )
+)]
+pub async fn abort_commitment(
+    State(state): State<Arc<AppState>>,
+    ValidJson(request): ValidJson<CommitmentRequest>,
+) -> AppResult<Json<StockCommitment>> {
+    finish_commitment(&state, &request.transaction_id, CommitmentState::Aborted)
+        .await
+        .map(Json)
+}
+
+/// Commit or abort, and report a commitment that ended up elsewhere as a
+/// conflict
+async fn finish_commitment(
+    state: &AppState,
+    transaction_id: &str,
+    target: CommitmentState,
+) -> AppResult<StockCommitment> {
+    validate_transaction_id(transaction_id).map_err(AppError::BadRequest)?;
+
+    let db_start = Instant::now();
+    let result = state.db.finish_commitment(transaction_id, target, None).await;
+    metrics::record_db_query("finish_commitment", db_start.elapsed().as_secs_f64());
+
+    let commitment = match result {
+        Ok(Some(commitment)) => commitment,
+        Ok(None) => {
+            return Err(AppError::NotFound(format!(
+                "No transaction {}",
+                transaction_id
+            )))
+        }
+        Err(e) => {
+            tracing::warn!(transaction_id, error = %e, "Failed to finish commitment");
+            return match AppError::from_db(e) {
+                Ok(app_error) => Err(app_error),
+                Err(e) => Err(e.into()),
+            };
+        }
+    };
+
+    if commitment.applied {
+        metrics::record_commitment(commitment.state);
+        commitments::forget_cached(&state.redis, state.fast_reserve.as_ref(), &commitment.lines)
+            .await;
+        // A commit keeps the units reserved for the order
+        if commitment.state != CommitmentState::Committed {
+            for line in &commitment.lines {
+                state.live.publish(ChangeKind::Released, &line.sku, line.quantity);
+            }
+        }
+        tracing::info!(
+            transaction_id,
+            order_id = %commitment.order_id,
+            state = commitment.state.as_str(),
+            "Commitment finished"
+        );
+    }
+
+    // An abort is satisfied by a timeout too
+    let reached = commitment.state == target
+        || (target == CommitmentState::Aborted && commitment.state == CommitmentState::Expired);
+    if !reached {
+        return Err(AppError::CommitmentConflict(
+            if commitment.state == CommitmentState::Expired {
+                format!(
+                    "Transaction {} timed out at {}; its stock was released",
+                    transaction_id, commitment.expires_at
+                )
+            } else {
+                format!(
+                    "Transaction {} is already {}",
+                    transaction_id,
+                    commitment.state.as_str()
+                )
+            },
+        ));
+    }
+
+    Ok(commitment)
+}
+
+// -----------------------------------------------------------------------------
+// RESERVATION POLICY
+// -----------------------------------------------------------------------------
+/// Set a SKU's reservation limits
+///
+/// PUT /api/v1/inventory/:sku/policy
+///
+/// # Request Body
+/// ```json
+/// { "max_per_order": 2, "max_reserved_pct": 50 }
+/// ```
+///
+/// Omitted or null fields remove that limit.
+#[utoipa::path(
+    put,
+    path = "/api/v1/inventory/{sku}/policy",
+    tag = "catalog",
+    params(
+        ("sku" = String, Path, description = "Product SKU"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the item is at this version"),
+    ),
+    request_body = ReservationPolicy,
+    responses(
+        (status = 200, description = "Updated item", body = InventoryItem),
+        (status = 422, description = "Invalid limits", body = ErrorResponse),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+        (status = 412, description = "Item changed since the If-Match version", body = ErrorResponse),
+    )
+)]
+pub async fn set_reservation_policy(
+    State(state): State<Arc<AppState>>,
+    Path(sku): Path<String>,
+    if_match: IfMatch,
+    ValidJson(policy): ValidJson<ReservationPolicy>,
+) -> AppResult<(ETag, Json<InventoryItem>)> {
+    let item = state
+        .db
+        .set_reservation_policy(&sku, &policy, if_match.0)
+        .await
+        .map_err(precondition_error)?
+        .ok_or_else(|| AppError::NotFound(format!("SKU not found: {}", sku)))?;
+
+    tracing::info!(
+        sku = %sku,
+        max_per_order = ?policy.max_per_order,
+        max_reserved_pct = ?policy.max_reserved_pct,
+        "Reservation policy updated"
+    );
 
     // Invalidate cache
-    let cache_key = format!("inventory:{}", request.sku);
-    let _: Result<(), _> = redis::cmd("DEL")
-        .arg(&cache_key)
-        .query_async(&mut state.redis.clone())
-        .await;
+    item_cache::invalidate(&state.redis, &sku).await;
+    list_cache::invalidate(&state.redis).await;
 
-    let duration = start.elapsed().as_secs_f64();
-    metrics::record_http_request("POST", "/api/v1/inventory/adjust", 200, duration);
+    // Re-seed the fast-path counter from Postgres on next use
+    if let Some(fast) = &state.fast_reserve {
+        fast.invalidate(&sku).await;
+    }
 
-    Ok(Json(item))
+    Ok((ETag(item.version), Json(item)))
 }
 
 // -----------------------------------------------------------------------------
-// LOW STOCK ALERTS
+// CATALOG DETAILS
 // -----------------------------------------------------------------------------
-/// Get all items with low stock
+/// Replace a SKU's catalog details
 ///
-/// GET /api/v1/inventory/alerts
+/// PUT /api/v1/inventory/:sku/catalog
 ///
-/// Returns items where available stock is below the threshold.
-pub async fn low_stock_alerts(
+/// # Request Body
+/// ```json
+/// {
+///   "attributes": { "color": "silver", "screen_inches": 14 },
+///   "image_url": "https://cdn.example.com/laptop-001.jpg",
+///   "spec_url": null,
+///   "unit_price": 1299.99,
+///   "unit_cost": 1040.00
+/// }
+/// ```
+///
+/// Omitted fields are cleared.
+///
+/// # Response
+/// - 200 OK: the updated item
+/// - 400 Bad Request: attributes don't match their schemas
+/// - 404 Not Found: SKU doesn't exist
+/// - 412 Precondition Failed: the item changed since the If-Match version
+/// - 422 Unprocessable Entity: attributes aren't a valid object, a URL
+///   isn't http(s), or the unit price or cost is negative
+#[utoipa::path(
+    put,
+    path = "/api/v1/inventory/{sku}/catalog",
+    tag = "catalog",
+    params(
+        ("sku" = String, Path, description = "Product SKU"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the item is at this version"),
+    ),
+    request_body = CatalogDetailsRequest,
+    responses(
+        (status = 200, description = "Updated item", body = InventoryItem),
+        (status = 400, description = "Attributes don't match their schemas", body = ErrorResponse),
+        (status = 422, description = "Invalid details", body = ErrorResponse),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+        (status = 412, description = "Item changed since the If-Match version", body = ErrorResponse),
+    )
+)]
+pub async fn set_catalog_details(
     State(state): State<Arc<AppState>>,
-) -> AppResult<Json<Vec<LowStockAlert>>> {
+    Path(sku): Path<String>,
+    if_match: IfMatch,
+    ValidJson(details): ValidJson<CatalogDetailsRequest>,
+) -> AppResult<(ETag, Json<InventoryItem>)> {
+    let schemas = state.db.list_attribute_schemas().await?;
+    attributes::validate(&details.attributes, &schemas).map_err(AppError::BadRequest)?;
+
+    let item = state
+        .db
+        .set_catalog_details(&sku, &details, if_match.0)
+        .await
+        .map_err(precondition_error)?
+        .ok_or_else(|| AppError::NotFound(format!("SKU not found: {}", sku)))?;
+
+    tracing::info!(
+        sku = %sku,
+        attributes = details.attributes.as_object().map_or(0, |a| a.len()),
+        "Catalog details updated"
+    );
+
+    // Invalidate cache
+    item_cache::invalidate(&state.redis, &sku).await;
+    list_cache::invalidate(&state.redis).await;
+
+    Ok((ETag(item.version), Json(item)))
+}
+
+// -----------------------------------------------------------------------------
+// ATTRIBUTE SCHEMAS
+// -----------------------------------------------------------------------------
+/// List attribute schemas
+///
+/// GET /api/v1/admin/attribute-schemas
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/attribute-schemas",
+    tag = "catalog",
+    responses(
+        (status = 200, description = "All attribute schemas", body = Vec<AttributeSchema>),
+    )
+)]
+pub async fn list_attribute_schemas(
+    State(state): State<Arc<AppState>>,
+) -> AppResult<Json<Vec<AttributeSchema>>> {
+    Ok(Json(state.db.list_attribute_schemas().await?))
+}
+
+/// Define or redefine the schema of an attribute
+///
+/// PUT /api/v1/admin/attribute-schemas/:name
+///
+/// # Request Body
+/// ```json
+/// { "type": "string", "required": true }
+/// ```
+///
+/// # Response
+/// - 200 OK: the stored schema
+/// - 400 Bad Request: invalid name or unknown type
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/attribute-schemas/{name}",
+    tag = "catalog",
+    params(("name" = String, Path, description = "Attribute name")),
+    request_body = AttributeSchemaRequest,
+    responses(
+        (status = 200, description = "Schema created or replaced", body = AttributeSchema),
+        (status = 400, description = "Invalid schema", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+    )
+)]
+pub async fn put_attribute_schema(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    ValidJson(request): ValidJson<AttributeSchemaRequest>,
+) -> AppResult<Json<AttributeSchema>> {
+    attributes::validate_name(&name).map_err(AppError::BadRequest)?;
+
+    let schema = state.db.upsert_attribute_schema(&name, &request).await?;
+    tracing::info!(
+        name = %schema.name,
+        attr_type = %schema.attr_type,
+        required = schema.required,
+        "Attribute schema saved"
+    );
+
+    Ok(Json(schema))
+}
+
+/// Remove the schema of an attribute (the attribute becomes free-form)
+///
+/// DELETE /api/v1/admin/attribute-schemas/:name
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/attribute-schemas/{name}",
+    tag = "catalog",
+    params(("name" = String, Path, description = "Attribute name")),
+    responses(
+        (status = 204, description = "Schema removed"),
+        (status = 404, description = "No such schema", body = ErrorResponse),
+    )
+)]
+pub async fn delete_attribute_schema(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> AppResult<StatusCode> {
+    if !state.db.delete_attribute_schema(&name).await? {
+        return Err(AppError::NotFound(format!("Attribute schema not found: {}", name)));
+    }
+
+    tracing::info!(name = %name, "Attribute schema removed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// -----------------------------------------------------------------------------
+// WAREHOUSES
+// -----------------------------------------------------------------------------
+/// List the registered warehouses
+///
+/// GET /api/v1/warehouses
+///
+/// Items, transfers and bulk updates may only name these codes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/warehouses",
+    tag = "warehouses",
+    responses(
+        (status = 200, description = "Warehouses by code", body = Vec<Warehouse>),
+    )
+)]
+pub async fn list_warehouses(State(state): State<Arc<AppState>>) -> AppResult<Json<Vec<Warehouse>>> {
+    Ok(Json(state.db.list_warehouses().await?))
+}
+
+/// Register a warehouse
+///
+/// POST /api/v1/warehouses
+///
+/// # Request Body
+/// ```json
+/// { "code": "MDN-1", "name": "Medan 1", "region": "Sumatra", "capacity": 20000 }
+/// ```
+///
+/// # Response
+/// - 201 Created: the warehouse
+/// - 400 Bad Request: the code is taken
+#[utoipa::path(
+    post,
+    path = "/api/v1/warehouses",
+    tag = "warehouses",
+    request_body = CreateWarehouseRequest,
+    responses(
+        (status = 201, description = "Warehouse registered", body = Warehouse),
+        (status = 400, description = "Duplicate code", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+    )
+)]
+pub async fn create_warehouse(
+    State(state): State<Arc<AppState>>,
+    ValidJson(request): ValidJson<CreateWarehouseRequest>,
+) -> AppResult<(StatusCode, Json<Warehouse>)> {
+    let warehouse = state
+        .db
+        .create_warehouse(&request.code, &request.details())
+        .await?
+        .ok_or_else(|| AppError::BadRequest(format!("Warehouse already exists: {}", request.code)))?;
+
+    tracing::info!(code = %warehouse.code, name = %warehouse.name, "Warehouse registered");
+    Ok((StatusCode::CREATED, Json(warehouse)))
+}
+
+/// Get one warehouse
+///
+/// GET /api/v1/warehouses/:code
+#[utoipa::path(
+    get,
+    path = "/api/v1/warehouses/{code}",
+    tag = "warehouses",
+    params(("code" = String, Path, description = "Warehouse code")),
+    responses(
+        (status = 200, description = "The warehouse", body = Warehouse),
+        (status = 404, description = "No such warehouse", body = ErrorResponse),
+    )
+)]
+pub async fn get_warehouse(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+) -> AppResult<Json<Warehouse>> {
+    state
+        .db
+        .get_warehouse(&code)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Warehouse not found: {}", code)))
+}
+
+/// Replace a warehouse's name, region and capacity
+///
+/// PUT /api/v1/warehouses/:code
+///
+/// # Request Body
+/// ```json
+/// { "name": "Medan 1", "region": "Sumatra", "capacity": 25000 }
+/// ```
+#[utoipa::path(
+    put,
+    path = "/api/v1/warehouses/{code}",
+    tag = "warehouses",
+    params(("code" = String, Path, description = "Warehouse code")),
+    request_body = UpdateWarehouseRequest,
+    responses(
+        (status = 200, description = "Updated warehouse", body = Warehouse),
+        (status = 404, description = "No such warehouse", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+    )
+)]
+pub async fn update_warehouse(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+    ValidJson(request): ValidJson<UpdateWarehouseRequest>,
+) -> AppResult<Json<Warehouse>> {
+    let warehouse = state
+        .db
+        .update_warehouse(&code, &request)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Warehouse not found: {}", code)))?;
+
+    tracing::info!(code = %warehouse.code, "Warehouse updated");
+    Ok(Json(warehouse))
+}
+
+/// Remove a warehouse that holds no stock
+///
+/// DELETE /api/v1/warehouses/:code
+///
+/// Its per-warehouse thresholds go with it. Move the items homed there
+/// (PATCH /api/v1/inventory/:sku) and transfer out what other SKUs keep
+/// there first.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/warehouses/{code}",
+    tag = "warehouses",
+    params(("code" = String, Path, description = "Warehouse code")),
+    responses(
+        (status = 204, description = "Warehouse removed"),
+        (status = 404, description = "No such warehouse", body = ErrorResponse),
+        (status = 409, description = "Items or stock are still there", body = ErrorResponse),
+    )
+)]
+pub async fn delete_warehouse(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+) -> AppResult<StatusCode> {
+    if !state.db.delete_warehouse(&code).await? {
+        return Err(AppError::NotFound(format!("Warehouse not found: {}", code)));
+    }
+
+    tracing::info!(code = %code, "Warehouse removed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Stock held at one warehouse
+///
+/// GET /api/v1/warehouses/:code/summary
+///
+/// SKUs homed here count with what they didn't transfer elsewhere, other
+/// SKUs with what they transferred here (as in
+/// GET /api/v1/inventory/:sku/warehouses).
+///
+/// # Response
+/// ```json
+/// { "code": "JKT-1", "name": "Jakarta 1", "region": "Jakarta", "capacity": 50000,
+///   "skus": 6, "home_skus": 5, "quantity": 930, "reserved": 12, "available": 918,
+///   "utilization_pct": 1.86, "low_stock_skus": 1 }
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/warehouses/{code}/summary",
+    tag = "warehouses",
+    params(("code" = String, Path, description = "Warehouse code")),
+    responses(
+        (status = 200, description = "Stock totals of the warehouse", body = WarehouseSummary),
+        (status = 404, description = "No such warehouse", body = ErrorResponse),
+    )
+)]
+pub async fn warehouse_summary(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+) -> AppResult<Json<WarehouseSummary>> {
     let start = Instant::now();
 
-    let alerts = state.db.get_low_stock_items().await?;
+    let warehouse = state
+        .db
+        .get_warehouse(&code)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Warehouse not found: {}", code)))?;
+    let totals = state.db.warehouse_totals(&code).await?;
+
+    metrics::record_db_query("select", start.elapsed().as_secs_f64());
+
+    Ok(Json(WarehouseSummary::new(warehouse, totals)))
+}
+
+/// 400 unless a warehouse code is registered (see GET /api/v1/warehouses)
+async fn check_warehouse(state: &AppState, code: &str) -> AppResult<()> {
+    if state.repo.warehouse_exists(code).await? {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(format!(
+            "Unknown warehouse: {} (register it with POST /api/v1/warehouses)",
+            code
+        )))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// WAREHOUSE STOCK
+// -----------------------------------------------------------------------------
+/// Stock of a SKU in each warehouse
+///
+/// GET /api/v1/inventory/:sku/warehouses
+///
+/// The home warehouse comes first and holds the reservations; the others
+/// hold what transfers moved there. Warehouses without stock are left out.
+///
+/// # Response
+/// ```json
+/// [
+///   { "warehouse": "JKT-1", "quantity": 80, "reserved": 5, "available": 75, "home": true },
+///   { "warehouse": "SBY-1", "quantity": 20, "reserved": 0, "available": 20, "home": false }
+/// ]
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/inventory/{sku}/warehouses",
+    tag = "inventory",
+    params(("sku" = String, Path, description = "Product SKU")),
+    responses(
+        (status = 200, description = "Stock per warehouse, home first", body = Vec<WarehouseStock>),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+    )
+)]
+pub async fn list_warehouse_stock(
+    State(state): State<Arc<AppState>>,
+    Path(sku): Path<String>,
+) -> AppResult<Json<Vec<WarehouseStock>>> {
+    let start = Instant::now();
 
-    // Update low stock count metric
-    metrics::set_low_stock_count(alerts.len() as i64);
+    let warehouses = state
+        .db
+        .list_warehouse_stock(&sku)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("SKU not found: {}", sku)))?;
 
     let duration = start.elapsed().as_secs_f64();
-    metrics::record_http_request("GET", "/api/v1/inventory/alerts", 200, duration);
+    metrics::record_db_query("select", duration);
 
-    Ok(Json(alerts))
+    Ok(Json(warehouses))
+}
+
+// -----------------------------------------------------------------------------
+// WAREHOUSE THRESHOLDS
+// -----------------------------------------------------------------------------
+/// List a SKU's per-warehouse low-stock thresholds
+///
+/// GET /api/v1/inventory/:sku/thresholds
+///
+/// # Response
+/// ```json
+/// [
+///   { "sku": "SKU-PHONE-001", "warehouse": "JKT-1", "threshold": 25,
+///     "updated_at": "2024-01-01T00:00:00Z" }
+/// ]
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/inventory/{sku}/thresholds",
+    tag = "catalog",
+    params(("sku" = String, Path, description = "Product SKU")),
+    responses(
+        (status = 200, description = "Per-warehouse thresholds", body = Vec<WarehouseThreshold>),
+    )
+)]
+pub async fn list_warehouse_thresholds(
+    State(state): State<Arc<AppState>>,
+    Path(sku): Path<String>,
+) -> AppResult<Json<Vec<WarehouseThreshold>>> {
+    Ok(Json(state.db.list_warehouse_thresholds(&sku).await?))
+}
+
+/// Set a SKU's low-stock threshold (all warehouses)
+///
+/// PUT /api/v1/inventory/:sku/thresholds
+///
+/// A number pins the threshold, so dynamic recalculation (DYNAMIC_THRESHOLDS)
+/// skips this SKU; null hands it back to recalculation.
+///
+/// # Request Body
+/// ```json
+/// { "threshold": 15 }
+/// ```
+#[utoipa::path(
+    put,
+    path = "/api/v1/inventory/{sku}/thresholds",
+    tag = "catalog",
+    params(
+        ("sku" = String, Path, description = "Product SKU"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the item is at this version"),
+    ),
+    request_body = LowStockThresholdRequest,
+    responses(
+        (status = 200, description = "Updated item", body = InventoryItem),
+        (status = 422, description = "Invalid threshold", body = ErrorResponse),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+        (status = 412, description = "Item changed since the If-Match version", body = ErrorResponse),
+    )
+)]
+pub async fn set_low_stock_threshold(
+    State(state): State<Arc<AppState>>,
+    Path(sku): Path<String>,
+    if_match: IfMatch,
+    ValidJson(request): ValidJson<LowStockThresholdRequest>,
+) -> AppResult<(ETag, Json<InventoryItem>)> {
+    let item = state
+        .db
+        .set_low_stock_threshold(&sku, request.threshold, if_match.0)
+        .await
+        .map_err(precondition_error)?
+        .ok_or_else(|| AppError::NotFound(format!("SKU not found: {}", sku)))?;
+
+    tracing::info!(
+        sku = %sku,
+        threshold = item.low_stock_threshold,
+        manual = item.threshold_manual,
+        "Low stock threshold updated"
+    );
+
+    // Invalidate cache
+    item_cache::invalidate(&state.redis, &sku).await;
+    list_cache::invalidate(&state.redis).await;
+    refresh_low_stock(&state).await;
+
+    Ok((ETag(item.version), Json(item)))
+}
+
+/// Set a SKU's low-stock threshold in one warehouse
+///
+/// PUT /api/v1/inventory/:sku/thresholds/:warehouse
+///
+/// Overrides the item's `low_stock_threshold` for stock held in that
+/// warehouse. Alerts and gauges are re-evaluated right away.
+///
+/// # Request Body
+/// ```json
+/// { "threshold": 25 }
+/// ```
+#[utoipa::path(
+    put,
+    path = "/api/v1/inventory/{sku}/thresholds/{warehouse}",
+    tag = "catalog",
+    params(
+        ("sku" = String, Path, description = "Product SKU"),
+        ("warehouse" = String, Path, description = "Warehouse code")
+    ),
+    request_body = WarehouseThresholdRequest,
+    responses(
+        (status = 200, description = "Threshold set", body = WarehouseThreshold),
+        (status = 422, description = "Invalid threshold", body = ErrorResponse),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+    )
+)]
+pub async fn set_warehouse_threshold(
+    State(state): State<Arc<AppState>>,
+    Path((sku, warehouse)): Path<(String, String)>,
+    ValidJson(request): ValidJson<WarehouseThresholdRequest>,
+) -> AppResult<Json<WarehouseThreshold>> {
+    if state.db.get_by_sku(&sku).await?.is_none() {
+        return Err(AppError::NotFound(format!("SKU not found: {}", sku)));
+    }
+
+    let threshold = state
+        .db
+        .set_warehouse_threshold(&sku, &warehouse, request.threshold)
+        .await?;
+
+    tracing::info!(
+        sku = %sku,
+        warehouse = %warehouse,
+        threshold = request.threshold,
+        "Warehouse low stock threshold set"
+    );
+    refresh_low_stock(&state).await;
+
+    Ok(Json(threshold))
+}
+
+/// Remove a per-warehouse threshold (the item's own threshold applies again)
+///
+/// DELETE /api/v1/inventory/:sku/thresholds/:warehouse
+#[utoipa::path(
+    delete,
+    path = "/api/v1/inventory/{sku}/thresholds/{warehouse}",
+    tag = "catalog",
+    params(
+        ("sku" = String, Path, description = "Product SKU"),
+        ("warehouse" = String, Path, description = "Warehouse code")
+    ),
+    responses(
+        (status = 204, description = "Threshold removed"),
+        (status = 404, description = "No threshold set", body = ErrorResponse),
+    )
+)]
+pub async fn delete_warehouse_threshold(
+    State(state): State<Arc<AppState>>,
+    Path((sku, warehouse)): Path<(String, String)>,
+) -> AppResult<StatusCode> {
+    if !state.db.delete_warehouse_threshold(&sku, &warehouse).await? {
+        return Err(AppError::NotFound(format!(
+            "No threshold set for {} in {}",
+            sku, warehouse
+        )));
+    }
+
+    tracing::info!(sku = %sku, warehouse = %warehouse, "Warehouse low stock threshold removed");
+    refresh_low_stock(&state).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Re-evaluate low stock after a threshold change instead of waiting for
+/// the next scheduled run
+async fn refresh_low_stock(state: &AppState) {
+    if let Err(e) = state.low_stock.evaluate(&state.db).await {
+        tracing::warn!(error = %e, "Failed to re-evaluate low stock");
+    }
+}
+
+// -----------------------------------------------------------------------------
+// RELEASE STOCK
+// -----------------------------------------------------------------------------
+/// Release previously reserved stock
+///
+/// POST /api/v1/inventory/release
+///
+/// Called when an order is cancelled or expires.
+///
+/// # Request Body
+/// ```json
+/// {
+///   "sku": "SKU-LAPTOP-001",
+///   "quantity": 5,
+///   "order_id": "ORD-12345"
+/// }
+/// ```
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/release",
+    tag = "reservations",
+    request_body = ReleaseStockRequest,
+    responses(
+        (status = 200, description = "Stock released", body = serde_json::Value),
+        (status = 400, description = "Invalid request, or less reserved than released", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+    )
+)]
+pub async fn release_stock(
+    State(state): State<Arc<AppState>>,
+    ValidJson(request): ValidJson<ReleaseStockRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    tracing::info!(
+        sku = %request.sku,
+        quantity = request.quantity,
+        order_id = %request.order_id,
+        "Releasing reserved stock"
+    );
+
+    if let Err(e) = state.repo.release_stock(&request).await {
+        audit::record_failure(
+            state.repo.as_ref(),
+            "release",
+            &request.sku,
+            request.quantity,
+            &request.order_id,
+            &e,
+        )
+        .await;
+        return Err(e.into());
+    }
+
+    // Invalidate cache
+    item_cache::invalidate(&state.redis, &request.sku).await;
+    list_cache::invalidate(&state.redis).await;
+    state.live.publish(ChangeKind::Released, &request.sku, request.quantity);
+
+    // Re-seed the fast-path counter from Postgres on next use
+    if let Some(fast) = &state.fast_reserve {
+        fast.invalidate(&request.sku).await;
+    }
+
+    Ok(Json(serde_json::json!({
+        "status": "released",
+        "sku": request.sku,
+        "quantity": request.quantity
+    })))
+}
+
+// -----------------------------------------------------------------------------
+// CANCEL BY ORDER
+// -----------------------------------------------------------------------------
+/// Cancel every active reservation of an order at once
+///
+/// POST /api/v1/reservations/cancel-by-order
+///
+/// Used by the order service when checkout is aborted. All SKUs are
+/// released in one transaction; retries are safe (nothing is left to
+/// release the second time). Fast-path reservations still waiting for
+/// write-behind aren't visible yet and are not cancelled.
+///
+/// # Request Body
+/// ```json
+/// { "order_id": "ORD-12345" }
+/// ```
+///
+/// # Response
+/// See CancelOrderResponse
+#[utoipa::path(
+    post,
+    path = "/api/v1/reservations/cancel-by-order",
+    tag = "reservations",
+    request_body = CancelOrderRequest,
+    responses(
+        (status = 200, description = "Everything the order held, now released", body = CancelOrderResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+    )
+)]
+pub async fn cancel_order_reservations(
+    State(state): State<Arc<AppState>>,
+    ValidJson(request): ValidJson<CancelOrderRequest>,
+) -> AppResult<Json<CancelOrderResponse>> {
+    let released = match state.db.cancel_order_reservations(&request.order_id).await {
+        Ok(released) => released,
+        Err(e) => {
+            audit::record_failure(&state.db, "release", "*", 0, &request.order_id, &e).await;
+            return Err(e.into());
+        }
+    };
+
+    for line in &released {
+        item_cache::invalidate(&state.redis, &line.sku).await;
+        if let Some(fast) = &state.fast_reserve {
+            fast.invalidate(&line.sku).await;
+        }
+        state.live.publish(ChangeKind::Released, &line.sku, line.quantity);
+    }
+    if !released.is_empty() {
+        list_cache::invalidate(&state.redis).await;
+    }
+
+    let total_units = released.iter().map(|line| i64::from(line.quantity)).sum();
+    tracing::info!(
+        order_id = %request.order_id,
+        skus = released.len(),
+        total_units,
+        "Cancelled order reservations"
+    );
+
+    Ok(Json(CancelOrderResponse {
+        order_id: request.order_id,
+        released,
+        total_units,
+    }))
+}
+
+// -----------------------------------------------------------------------------
+// LIST RESERVATIONS
+// -----------------------------------------------------------------------------
+/// Query parameters for the reservation listing
+///
+/// # Example
+/// GET /api/v1/reservations?sku=SKU-001&status=active&from=2024-01-01T00:00:00Z
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReservationParams {
+    /// Page number (1-indexed, default: 1)
+    #[serde(default = "default_page")]
+    pub page: i32,
+
+    /// Reservations per page (default: 20, max: 100)
+    #[serde(default = "default_per_page")]
+    pub per_page: i32,
+
+    /// Only reservations of this SKU
+    pub sku: Option<String>,
+
+    /// Only reservations of this order
+    pub order_id: Option<String>,
+
+    /// Only this status (active, expired, released, confirmed)
+    pub status: Option<String>,
+
+    /// Only reservations made at or after this time (RFC 3339)
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Only reservations made before this time (RFC 3339)
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Reservations, newest first, with what holds stock and what became of
+/// the rest
+///
+/// GET /api/v1/reservations
+/// GET /api/v1/reservations?status=active&sku=SKU-001
+///
+/// One entry per order and SKU: a new reservation after the old one is
+/// gone takes its place. Reservations made before reservation IDs were
+/// recorded (migration 0009) aren't listed; the audit export still has
+/// them.
+///
+/// # Response
+/// - 200 OK: ReservationListResponse
+/// - 400 Bad Request: unknown status, or `from` not before `to`
+#[utoipa::path(
+    get,
+    path = "/api/v1/reservations",
+    tag = "reservations",
+    params(ReservationParams),
+    responses(
+        (status = 200, description = "Reservations, newest first", body = ReservationListResponse),
+        (status = 400, description = "Invalid filter", body = ErrorResponse),
+    )
+)]
+pub async fn list_reservations(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ReservationParams>,
+) -> AppResult<Json<ReservationListResponse>> {
+    let start = Instant::now();
+
+    let status = params.status.as_deref().filter(|s| !s.is_empty());
+    if let Some(status) = status {
+        if !RESERVATION_STATUSES.contains(&status) {
+            return Err(AppError::BadRequest(format!(
+                "Unknown status '{}', expected one of: {}",
+                status,
+                RESERVATION_STATUSES.join(", ")
+            )));
+        }
+    }
+    if let (Some(from), Some(to)) = (params.from, params.to) {
+        if from >= to {
+            return Err(AppError::BadRequest("`from` must be before `to`".to_string()));
+        }
+    }
+
+    let filter = ReservationFilter {
+        sku: params.sku.as_deref().filter(|s| !s.is_empty()),
+        order_id: params.order_id.as_deref().filter(|o| !o.is_empty()),
+        status,
+        from: params.from,
+        to: params.to,
+    };
+    let page = params.page.max(1);
+    let per_page = params.per_page.clamp(1, 100);
+    let (items, total) = state.db.list_reservations(&filter, page, per_page).await?;
+
+    let duration = start.elapsed().as_secs_f64();
+    metrics::record_db_query("reservations", duration);
+
+    Ok(Json(ReservationListResponse {
+        items,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+// -----------------------------------------------------------------------------
+// DRY RUN
+// -----------------------------------------------------------------------------
+/// `?dry_run=true` on adjust, transfer and bulk update
+///
+/// The operation runs in full (validation, locks, limits, audit trail) in
+/// a transaction that is rolled back, and the would-be result is returned
+/// with an `X-Dry-Run: true` header. Nothing is written, cached or counted.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DryRunParams {
+    /// Preview the result without applying it (default: false)
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Header marking a response as a preview
+const DRY_RUN_HEADER: HeaderName = HeaderName::from_static("x-dry-run");
+
+/// Response headers of a mutation that may have been a dry run
+fn dry_run_headers(dry_run: bool) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if dry_run {
+        headers.insert(DRY_RUN_HEADER, header::HeaderValue::from_static("true"));
+    }
+    headers
+}
+
+/// Error of a conditional update: a version mismatch raised in db.rs keeps
+/// its 412 (a missing SKU its 404), anything else is internal
+fn precondition_error(e: anyhow::Error) -> AppError {
+    AppError::from(e)
+}
+
+// -----------------------------------------------------------------------------
+// ADJUST STOCK
+// -----------------------------------------------------------------------------
+/// Manually adjust stock quantity
+///
+/// POST /api/v1/inventory/adjust
+///
+/// Used for inventory corrections, receiving shipments, etc.
+/// `?dry_run=true` returns the item as it would be, without changing it.
+///
+/// # Request Body
+/// ```json
+/// {
+///   "sku": "SKU-LAPTOP-001",
+///   "delta": 10,
+///   "reason": "Received shipment from supplier"
+/// }
+/// ```
+///
+/// To apply the adjustment only if nobody changed the item since it was
+/// read, send its version as `If-Match` or `"expected_version"`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/adjust",
+    tag = "inventory",
+    params(
+        DryRunParams,
+        ("If-Match" = Option<String>, Header, description = "Only update if the item is at this version"),
+    ),
+    request_body = AdjustStockRequest,
+    responses(
+        (status = 200, description = "Updated item", body = InventoryItem),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+        (status = 412, description = "Item changed since the expected version", body = ErrorResponse),
+    )
+)]
+pub async fn adjust_stock(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DryRunParams>,
+    if_match: IfMatch,
+    ValidJson(mut request): ValidJson<AdjustStockRequest>,
+) -> AppResult<(HeaderMap, ETag, Json<InventoryItem>)> {
+    request.expected_version = versioning::expected_version(if_match, request.expected_version)?;
+
+    tracing::info!(
+        sku = %request.sku,
+        delta = request.delta,
+        reason = %request.reason,
+        dry_run = params.dry_run,
+        "Adjusting stock"
+    );
+
+    let item = match state.repo.adjust_stock(&request, params.dry_run).await {
+        Ok(item) => item,
+        Err(e) if params.dry_run => return Err(precondition_error(e)),
+        Err(e) => {
+            audit::record_failure(
+                state.repo.as_ref(),
+                "adjust",
+                &request.sku,
+                request.delta,
+                &request.reason,
+                &e,
+            )
+            .await;
+            return Err(precondition_error(e));
+        }
+    };
+    if params.dry_run {
+        return Ok((dry_run_headers(true), ETag(item.version), Json(item)));
+    }
+
+    // Update metrics
+    metrics::set_stock_level(&item.sku, &item.warehouse, item.available());
+
+    // Cache the adjusted item; list pages are dropped
+    item_cache::write_through(&state.redis, &item).await;
+    list_cache::invalidate(&state.redis).await;
+    state.live.publish(ChangeKind::Adjusted, &request.sku, request.delta);
+
+    // Re-seed the fast-path counter from Postgres on next use
+    if let Some(fast) = &state.fast_reserve {
+        fast.invalidate(&request.sku).await;
+    }
+
+    Ok((dry_run_headers(false), ETag(item.version), Json(item)))
+}
+
+// -----------------------------------------------------------------------------
+// BULK UPDATE
+// -----------------------------------------------------------------------------
+/// Change thresholds, prices or the warehouse of many items at once
+///
+/// POST /api/v1/inventory/bulk-update
+///
+/// # Request Body
+/// ```json
+/// {
+///   "filter": { "warehouse": "JKT-1", "sku_prefix": "LAPTOP-", "category": "laptops" },
+///   "patch": { "low_stock_threshold_pct": 20, "unit_price": 899.0 },
+///   "reason": "Holiday season",
+///   "dry_run": true
+/// }
+/// ```
+///
+/// The filter must name at least one criterion. All changes land in one
+/// transaction with an audit event per item; `dry_run` (in the body or as
+/// `?dry_run=true`) only reports them.
+///
+/// # Response
+/// - 200 OK: the items changed (or that would change), before and after
+/// - 400 Bad Request: invalid filter or patch, or more than MAX_BULK_ITEMS
+///   (1000) items match
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/bulk-update",
+    tag = "catalog",
+    params(DryRunParams),
+    request_body = BulkUpdateRequest,
+    responses(
+        (status = 200, description = "Changed items, before and after", body = BulkUpdateResponse),
+        (status = 400, description = "Invalid request or too many matches", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+    )
+)]
+pub async fn bulk_update(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DryRunParams>,
+    ValidJson(mut request): ValidJson<BulkUpdateRequest>,
+) -> AppResult<(HeaderMap, Json<BulkUpdateResponse>)> {
+    request.dry_run |= params.dry_run;
+    if let Some(warehouse) = &request.patch.warehouse {
+        check_warehouse(&state, warehouse).await?;
+    }
+
+    let response = match state.db.bulk_update(&request).await {
+        Ok(response) => response,
+        Err(e) => {
+            if !request.dry_run {
+                audit::record_failure(&state.db, "bulk_update", "*", 0, &request.reason, &e).await;
+            }
+            return match AppError::from_db(e) {
+                Ok(app_error) => Err(app_error),
+                Err(e) => Err(e.into()),
+            };
+        }
+    };
+
+    tracing::info!(
+        matched = response.matched,
+        changed = response.changes.len(),
+        dry_run = response.dry_run,
+        reason = %request.reason,
+        "Bulk update"
+    );
+
+    if !response.dry_run && !response.changes.is_empty() {
+        for change in &response.changes {
+            item_cache::invalidate(&state.redis, &change.after.sku).await;
+            state.live.publish(ChangeKind::Updated, &change.after.sku, 0);
+        }
+        list_cache::invalidate(&state.redis).await;
+    }
+
+    Ok((dry_run_headers(response.dry_run), Json(response)))
+}
+
+// -----------------------------------------------------------------------------
+// EXPORT
+// -----------------------------------------------------------------------------
+/// Query parameters for the catalog export
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportParams {
+    /// "csv" (default) or "ndjson"
+    #[serde(default)]
+    pub format: String,
+}
+
+/// Download every item as CSV or NDJSON
+///
+/// GET /api/v1/inventory/export?format=csv
+///
+/// The catalog is streamed from a database cursor in batches, so large
+/// catalogs aren't held in memory (see export.rs). The CSV columns start
+/// with the ones POST /api/v1/inventory/import reads.
+///
+/// # Response
+/// - 200 OK: the file, as an attachment
+/// - 400 Bad Request: unsupported format
+#[utoipa::path(
+    get,
+    path = "/api/v1/inventory/export",
+    tag = "catalog",
+    params(ExportParams),
+    responses(
+        (status = 200, description = "Every item, CSV or one JSON object per line", body = String, content_type = "text/csv"),
+        (status = 400, description = "Unsupported format", body = ErrorResponse),
+    )
+)]
+pub async fn export_inventory(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ExportParams>,
+) -> AppResult<Response> {
+    let format = export::ExportFormat::parse(&params.format).map_err(AppError::BadRequest)?;
+    let cursor = state.db.inventory_cursor().await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, export::content_disposition(format)),
+        ],
+        Body::from_stream(export::stream(cursor, format)),
+    )
+        .into_response())
+}
+
+// -----------------------------------------------------------------------------
+// LIVE STREAM
+// -----------------------------------------------------------------------------
+/// Query parameters for the live stream
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StreamParams {
+    /// Only changes of this SKU (default: all)
+    pub sku: Option<String>,
+}
+
+/// Stock changes as they happen, as Server-Sent Events
+///
+/// GET /api/v1/inventory/stream?sku=SKU-LAPTOP-001
+///
+/// ```text
+/// data: {"type":"reserved","sku":"SKU-LAPTOP-001","quantity":2,"at":"2024-05-01T12:00:00Z"}
+/// ```
+///
+/// A client that falls too far behind gets `event: resync` and should
+/// refetch. See live.rs for the event types.
+#[utoipa::path(
+    get,
+    path = "/api/v1/inventory/stream",
+    tag = "inventory",
+    params(StreamParams),
+    responses(
+        (status = 200, description = "Stream of stock changes", body = String, content_type = "text/event-stream"),
+    )
+)]
+pub async fn stream_inventory(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<StreamParams>,
+) -> Sse<impl futures_util::Stream<Item = Result<Event, std::convert::Infallible>>> {
+    let sku = params.sku.filter(|sku| !sku.trim().is_empty());
+    tracing::debug!(sku = ?sku, "Live stream opened");
+
+    Sse::new(state.live.subscribe(sku)).keep_alive(KeepAlive::new().interval(live::KEEP_ALIVE))
+}
+
+// -----------------------------------------------------------------------------
+// CSV IMPORT
+// -----------------------------------------------------------------------------
+/// Create or update many items from a CSV file
+///
+/// POST /api/v1/inventory/import (multipart/form-data, CSV in the `file`
+/// field)
+///
+/// ```text
+/// sku,name,quantity,warehouse,threshold
+/// SKU-DOCK-001,USB-C Dock,20,JKT-1,5
+/// ```
+///
+/// Invalid rows are reported and skipped; the others are imported in one
+/// transaction (see import.rs). New SKUs count against the catalog quota.
+/// `?dry_run=true` reports what would happen without writing anything.
+///
+/// # Response
+/// - 200 OK: counts and the result of every row
+/// - 400 Bad Request: no file, a bad header or too many rows
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/import",
+    tag = "catalog",
+    params(DryRunParams),
+    request_body(
+        content = String,
+        content_type = "multipart/form-data",
+        description = "CSV file in the `file` field, header sku,name,quantity[,warehouse][,threshold]"
+    ),
+    responses(
+        (status = 200, description = "Result of every row", body = ImportResponse),
+        (status = 400, description = "Missing or unusable file", body = ErrorResponse),
+    )
+)]
+pub async fn import_items(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DryRunParams>,
+    mut multipart: Multipart,
+) -> AppResult<(HeaderMap, Json<ImportResponse>)> {
+    let mut file = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Invalid multipart body: {}", e)))?
+    {
+        if field.name() == Some("file") {
+            let data = field
+                .bytes()
+                .await
+                .map_err(|e| AppError::BadRequest(format!("Failed to read the file: {}", e)))?;
+            file = Some(data);
+            break;
+        }
+    }
+    let file = file.ok_or_else(|| AppError::BadRequest("Missing the `file` field".to_string()))?;
+
+    let (rows, mut results) = import::parse_csv(&file).map_err(AppError::BadRequest)?;
+
+    // Rows naming an unregistered warehouse fail on their own
+    let known: HashSet<String> = state.db.list_warehouses().await?.into_iter().map(|w| w.code).collect();
+    let (rows, unknown): (Vec<_>, Vec<_>) = rows
+        .into_iter()
+        .partition(|row| row.warehouse.as_ref().is_none_or(|warehouse| known.contains(warehouse)));
+    results.extend(unknown.iter().map(|row| {
+        ImportRowResult::error(
+            row.line,
+            &row.sku,
+            format!("Unknown warehouse: {}", row.warehouse.as_deref().unwrap_or_default()),
+        )
+    }));
+
+    let quota = state.config.catalog_quota;
+    let start = Instant::now();
+    let (imported, skus) = state
+        .db
+        .import_items(&rows, quota.hard_limit, params.dry_run)
+        .await?;
+    metrics::record_db_query("import", start.elapsed().as_secs_f64());
+    results.extend(imported);
+
+    let response = ImportResponse::new(params.dry_run, results);
+    tracing::info!(
+        created = response.created,
+        updated = response.updated,
+        unchanged = response.unchanged,
+        failed = response.failed,
+        dry_run = response.dry_run,
+        "CSV import"
+    );
+
+    if !response.dry_run {
+        metrics::set_catalog_size(skus, &quota);
+        if response.created > 0 && quota.status(skus) == catalog_quota::QuotaStatus::OverSoft {
+            metrics::record_catalog_quota_warning();
+            tracing::warn!(
+                skus,
+                soft_limit = quota.soft_limit,
+                hard_limit = quota.hard_limit,
+                "Catalog is past its soft limit"
+            );
+        }
+
+        let changed = response.rows.iter().filter(|row| {
+            matches!(row.status, ImportRowStatus::Created | ImportRowStatus::Updated)
+        });
+        for row in changed.clone() {
+            item_cache::invalidate(&state.redis, &row.sku).await;
+            state.live.publish(ChangeKind::Updated, &row.sku, 0);
+        }
+        if changed.count() > 0 {
+            list_cache::invalidate(&state.redis).await;
+        }
+    }
+
+    Ok((dry_run_headers(response.dry_run), Json(response)))
+}
+
+// -----------------------------------------------------------------------------
+// WAREHOUSE TRANSFER
+// -----------------------------------------------------------------------------
+/// Move stock between warehouses
+///
+/// POST /api/v1/inventory/transfer
+///
+/// # Request Body
+/// ```json
+/// {
+///   "sku": "SKU-LAPTOP-001",
+///   "from_warehouse": "JKT-1",
+///   "to_warehouse": "SBY-1",
+///   "quantity": 5,
+///   "reason": "Rebalancing for the Surabaya launch"
+/// }
+/// ```
+///
+/// Both sides change in one transaction and the ledger records a
+/// transfer_out and a transfer_in movement. Reserved units can't leave the
+/// home warehouse. `?dry_run=true` returns the stock per warehouse as it
+/// would be, without moving anything.
+///
+/// # Response
+/// - 200 OK: the SKU's stock per warehouse after the transfer
+/// - 400 Bad Request: invalid request or unknown SKU
+/// - 409 Conflict: the source warehouse doesn't have enough available stock
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/transfer",
+    tag = "inventory",
+    params(DryRunParams),
+    request_body = TransferStockRequest,
+    responses(
+        (status = 200, description = "Stock per warehouse after the transfer", body = TransferStockResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+        (status = 409, description = "Not enough available stock at the source", body = ErrorResponse),
+    )
+)]
+pub async fn transfer_stock(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DryRunParams>,
+    ValidJson(request): ValidJson<TransferStockRequest>,
+) -> AppResult<(HeaderMap, Json<TransferStockResponse>)> {
+    check_warehouse(&state, &request.from_warehouse).await?;
+    check_warehouse(&state, &request.to_warehouse).await?;
+
+    tracing::info!(
+        sku = %request.sku,
+        from = %request.from_warehouse,
+        to = %request.to_warehouse,
+        quantity = request.quantity,
+        dry_run = params.dry_run,
+        "Transferring stock"
+    );
+
+    let response = match state.db.transfer_stock(&request, params.dry_run).await {
+        Ok(response) => response,
+        Err(e) => {
+            if !params.dry_run {
+                audit::record_failure(
+                    &state.db,
+                    "transfer",
+                    &request.sku,
+                    request.quantity,
+                    request.reason.as_deref().unwrap_or(""),
+                    &e,
+                )
+                .await;
+            }
+
+            return match AppError::from_db(e) {
+                Ok(app_error) => Err(app_error),
+                Err(e) => Err(AppError::BadRequest(e.to_string())),
+            };
+        }
+    };
+    if params.dry_run {
+        return Ok((dry_run_headers(true), Json(response)));
+    }
+
+    // Invalidate cache
+    item_cache::invalidate(&state.redis, &request.sku).await;
+    list_cache::invalidate(&state.redis).await;
+    state.live.publish(ChangeKind::Transferred, &request.sku, request.quantity);
+
+    Ok((dry_run_headers(false), Json(response)))
+}
+
+// -----------------------------------------------------------------------------
+// STOCK MOVEMENTS
+// -----------------------------------------------------------------------------
+/// Query parameters for the movement history
+///
+/// # Example
+/// GET /api/v1/inventory/SKU-001/movements?type=adjust&from=2024-01-01T00:00:00Z
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MovementParams {
+    /// Page number (1-indexed, default: 1)
+    #[serde(default = "default_page")]
+    pub page: i32,
+
+    /// Movements per page (default: 20, max: 100)
+    #[serde(default = "default_per_page")]
+    pub per_page: i32,
+
+    /// Only this movement type (reserve, release, confirm, adjust, reconcile,
+    /// transfer_out, transfer_in)
+    #[serde(rename = "type")]
+    pub movement_type: Option<String>,
+
+    /// Only movements at or after this time (RFC 3339)
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Only movements before this time (RFC 3339)
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Movement history of an item, newest first
+///
+/// GET /api/v1/inventory/:sku/movements
+/// GET /api/v1/inventory/:sku/movements?type=reserve&page=2
+///
+/// Every reserve, release, confirmation, adjustment and reconciliation
+/// leaves one entry, with the deltas and the stock right after it.
+///
+/// # Response
+/// - 200 OK: StockMovementListResponse
+/// - 400 Bad Request: unknown type, or `from` not before `to`
+/// - 404 Not Found: SKU doesn't exist
+#[utoipa::path(
+    get,
+    path = "/api/v1/inventory/{sku}/movements",
+    tag = "inventory",
+    params(
+        ("sku" = String, Path, description = "Product SKU"),
+        MovementParams
+    ),
+    responses(
+        (status = 200, description = "Movements, newest first", body = StockMovementListResponse),
+        (status = 400, description = "Invalid filter", body = ErrorResponse),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+    )
+)]
+pub async fn list_stock_movements(
+    State(state): State<Arc<AppState>>,
+    Path(sku): Path<String>,
+    Query(params): Query<MovementParams>,
+) -> AppResult<Json<StockMovementListResponse>> {
+    let start = Instant::now();
+
+    let movement_type = params.movement_type.as_deref().filter(|t| !t.is_empty());
+    if let Some(movement_type) = movement_type {
+        if !MOVEMENT_TYPES.contains(&movement_type) {
+            return Err(AppError::BadRequest(format!(
+                "Unknown movement type '{}', expected one of: {}",
+                movement_type,
+                MOVEMENT_TYPES.join(", ")
+            )));
+        }
+    }
+    if let (Some(from), Some(to)) = (params.from, params.to) {
+        if from >= to {
+            return Err(AppError::BadRequest("`from` must be before `to`".to_string()));
+        }
+    }
+
+    if state.db.get_by_sku(&sku).await?.is_none() {
+        return Err(AppError::NotFound(format!("SKU not found: {}", sku)));
+    }
+
+    let page = params.page.max(1);
+    let per_page = params.per_page.clamp(1, 100);
+    let (items, total) = state
+        .db
+        .list_stock_movements(&sku, movement_type, params.from, params.to, page, per_page)
+        .await?;
+
+    let duration = start.elapsed().as_secs_f64();
+    metrics::record_db_query("movements", duration);
+
+    Ok(Json(StockMovementListResponse {
+        items,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+// -----------------------------------------------------------------------------
+// REORDER SUGGESTIONS
+// -----------------------------------------------------------------------------
+/// Query parameters for reorder suggestions and drafts
+///
+/// # Example
+/// GET /api/v1/inventory/reorder-suggestions?warehouse=JKT-1&lead_time_days=14
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReorderParams {
+    /// Only SKUs homed at this warehouse
+    pub warehouse: Option<String>,
+
+    /// Days of consumption to average (default: 30, max: 365)
+    #[serde(default = "default_lookback_days")]
+    pub lookback_days: i32,
+
+    /// Days until an order placed now arrives (default: 7, max: 365)
+    #[serde(default = "default_lead_time_days")]
+    pub lead_time_days: i32,
+
+    /// Days of consumption an order should cover (default: 30, max: 365)
+    #[serde(default = "default_cover_days")]
+    pub cover_days: i32,
+}
+
+fn default_lookback_days() -> i32 {
+    30
+}
+fn default_lead_time_days() -> i32 {
+    7
+}
+fn default_cover_days() -> i32 {
+    30
+}
+
+impl ReorderParams {
+    fn policy(&self) -> AppResult<reorder::ReorderPolicy> {
+        reorder::ReorderPolicy::new(self.lookback_days, self.lead_time_days, self.cover_days)
+            .map_err(AppError::BadRequest)
+    }
+
+    fn warehouse(&self) -> Option<&str> {
+        self.warehouse.as_deref().filter(|w| !w.is_empty())
+    }
+}
+
+/// SKUs to reorder and how many units, most urgent first
+///
+/// GET /api/v1/inventory/reorder-suggestions
+///
+/// A SKU is suggested when its available stock is at or below its
+/// threshold plus what it consumes during the lead time; consumption is
+/// averaged from the movement ledger (confirmations and negative
+/// adjustments). See reorder.rs.
+///
+/// # Response
+/// - 200 OK: ReorderSuggestionsResponse
+/// - 400 Bad Request: a window out of range
+#[utoipa::path(
+    get,
+    path = "/api/v1/inventory/reorder-suggestions",
+    tag = "inventory",
+    params(ReorderParams),
+    responses(
+        (status = 200, description = "Reorder suggestions, fewest days of cover first", body = ReorderSuggestionsResponse),
+        (status = 400, description = "Invalid window", body = ErrorResponse),
+    )
+)]
+pub async fn reorder_suggestions(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ReorderParams>,
+) -> AppResult<Json<ReorderSuggestionsResponse>> {
+    let policy = params.policy()?;
+    let suggestions = load_reorder_suggestions(&state, &params, &policy).await?;
+
+    Ok(Json(ReorderSuggestionsResponse {
+        suggestions,
+        lookback_days: policy.lookback_days,
+        lead_time_days: policy.lead_time_days,
+        cover_days: policy.cover_days,
+    }))
+}
+
+/// Draft purchase orders from the current reorder suggestions
+///
+/// POST /api/v1/inventory/reorder-suggestions?warehouse=JKT-1
+///
+/// Takes the same parameters as the GET and drafts one purchase order per
+/// home warehouse with suggestions. Nothing is drafted when nothing needs
+/// reordering.
+///
+/// # Response
+/// - 201 Created: PurchaseOrderDrafts
+/// - 400 Bad Request: a window out of range
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/reorder-suggestions",
+    tag = "inventory",
+    params(ReorderParams),
+    responses(
+        (status = 201, description = "Purchase orders drafted", body = PurchaseOrderDrafts),
+        (status = 400, description = "Invalid window", body = ErrorResponse),
+    )
+)]
+pub async fn draft_purchase_orders(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ReorderParams>,
+) -> AppResult<(StatusCode, Json<PurchaseOrderDrafts>)> {
+    let policy = params.policy()?;
+    let suggestions = load_reorder_suggestions(&state, &params, &policy).await?;
+
+    let mut by_warehouse: std::collections::BTreeMap<String, Vec<PurchaseOrderLine>> = Default::default();
+    for suggestion in suggestions {
+        by_warehouse.entry(suggestion.warehouse).or_default().push(PurchaseOrderLine {
+            sku: suggestion.sku,
+            quantity: suggestion.suggested_quantity,
+            unit_cost: suggestion.unit_cost,
+        });
+    }
+    let drafts: Vec<(String, Vec<PurchaseOrderLine>)> = by_warehouse.into_iter().collect();
+    let purchase_orders = state.db.create_purchase_orders(&drafts).await?;
+
+    tracing::info!(count = purchase_orders.len(), "Purchase orders drafted");
+    Ok((StatusCode::CREATED, Json(PurchaseOrderDrafts { purchase_orders })))
+}
+
+async fn load_reorder_suggestions(
+    state: &AppState,
+    params: &ReorderParams,
+    policy: &reorder::ReorderPolicy,
+) -> AppResult<Vec<ReorderSuggestion>> {
+    let start = Instant::now();
+    let candidates = state
+        .db
+        .reorder_candidates(policy.lookback_days, params.warehouse())
+        .await?;
+    metrics::record_db_query("reorder_candidates", start.elapsed().as_secs_f64());
+
+    Ok(reorder::suggest_all(&candidates, policy))
+}
+
+/// Query parameters for the purchase order list
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PurchaseOrderParams {
+    /// Page number (1-indexed, default: 1)
+    #[serde(default = "default_page")]
+    pub page: i32,
+
+    /// Purchase orders per page (default: 20, max: 100)
+    #[serde(default = "default_per_page")]
+    pub per_page: i32,
+
+    /// Only this status (e.g. "draft")
+    pub status: Option<String>,
+}
+
+/// Purchase orders, newest first
+///
+/// GET /api/v1/purchase-orders
+/// GET /api/v1/purchase-orders?status=draft
+///
+/// # Response
+/// - 200 OK: PurchaseOrderListResponse
+#[utoipa::path(
+    get,
+    path = "/api/v1/purchase-orders",
+    tag = "inventory",
+    params(PurchaseOrderParams),
+    responses(
+        (status = 200, description = "Purchase orders, newest first", body = PurchaseOrderListResponse),
+    )
+)]
+pub async fn list_purchase_orders(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PurchaseOrderParams>,
+) -> AppResult<Json<PurchaseOrderListResponse>> {
+    let page = params.page.max(1);
+    let per_page = params.per_page.clamp(1, 100);
+    let status = params.status.as_deref().filter(|s| !s.is_empty());
+    let (items, total) = state.db.list_purchase_orders(status, page, per_page).await?;
+
+    Ok(Json(PurchaseOrderListResponse {
+        items,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+// -----------------------------------------------------------------------------
+// ITEM REVISIONS
+// -----------------------------------------------------------------------------
+/// Query parameters for the revision history
+///
+/// # Example
+/// GET /api/v1/inventory/SKU-001/revisions?field=unit_price
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RevisionParams {
+    /// Page number (1-indexed, default: 1)
+    #[serde(default = "default_page")]
+    pub page: i32,
+
+    /// Revisions per page (default: 20, max: 100)
+    #[serde(default = "default_per_page")]
+    pub per_page: i32,
+
+    /// Only this field (name, warehouse, low_stock_threshold, unit_price)
+    pub field: Option<String>,
+}
+
+/// Metadata change history of an item, newest first
+///
+/// GET /api/v1/inventory/:sku/revisions
+/// GET /api/v1/inventory/:sku/revisions?field=warehouse
+///
+/// One entry per changed field, whichever way it was changed (API, bulk
+/// update, threshold recalculation or plain SQL). Stock levels are in the
+/// movement history instead.
+///
+/// # Response
+/// - 200 OK: ItemRevisionListResponse
+/// - 400 Bad Request: unknown field
+/// - 404 Not Found: SKU doesn't exist
+#[utoipa::path(
+    get,
+    path = "/api/v1/inventory/{sku}/revisions",
+    tag = "catalog",
+    params(
+        ("sku" = String, Path, description = "Product SKU"),
+        RevisionParams
+    ),
+    responses(
+        (status = 200, description = "Revisions, newest first", body = ItemRevisionListResponse),
+        (status = 400, description = "Unknown field", body = ErrorResponse),
+        (status = 404, description = "No such SKU", body = ErrorResponse),
+    )
+)]
+pub async fn list_item_revisions(
+    State(state): State<Arc<AppState>>,
+    Path(sku): Path<String>,
+    Query(params): Query<RevisionParams>,
+) -> AppResult<Json<ItemRevisionListResponse>> {
+    let field = params.field.as_deref().filter(|f| !f.is_empty());
+    if let Some(field) = field {
+        if !REVISION_FIELDS.contains(&field) {
+            return Err(AppError::BadRequest(format!(
+                "Unknown field '{}', expected one of: {}",
+                field,
+                REVISION_FIELDS.join(", ")
+            )));
+        }
+    }
+
+    if state.db.get_by_sku(&sku).await?.is_none() {
+        return Err(AppError::NotFound(format!("SKU not found: {}", sku)));
+    }
+
+    let page = params.page.max(1);
+    let per_page = params.per_page.clamp(1, 100);
+    let (items, total) = state
+        .db
+        .list_item_revisions(&sku, field, page, per_page)
+        .await?;
+
+    Ok(Json(ItemRevisionListResponse {
+        items,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+/// Undo one revision
+///
+/// POST /api/v1/inventory/:sku/revisions/:id/revert
+///
+/// Sets the revision's field back to its old value; other fields keep
+/// their current values. The revert shows up as a new revision, so it can
+/// be reverted in turn. Reverting a threshold marks it as set by hand.
+///
+/// # Response
+/// - 200 OK: the item after the revert
+/// - 404 Not Found: no such revision for this SKU
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/{sku}/revisions/{id}/revert",
+    tag = "catalog",
+    params(
+        ("sku" = String, Path, description = "Product SKU"),
+        ("id" = i64, Path, description = "Revision ID")
+    ),
+    responses(
+        (status = 200, description = "Item after the revert", body = InventoryItem),
+        (status = 404, description = "No such revision", body = ErrorResponse),
+    )
+)]
+pub async fn revert_item_revision(
+    State(state): State<Arc<AppState>>,
+    Path((sku, id)): Path<(String, i64)>,
+) -> AppResult<Json<InventoryItem>> {
+    let item = match state.db.revert_item_revision(&sku, id).await {
+        Ok(Some(item)) => item,
+        Ok(None) => {
+            return Err(AppError::NotFound(format!("No revision {} for SKU {}", id, sku)));
+        }
+        Err(e) => {
+            return match AppError::from_db(e) {
+                Ok(app_error) => Err(app_error),
+                Err(e) => Err(e.into()),
+            };
+        }
+    };
+
+    tracing::info!(sku = %sku, revision = id, "Item revision reverted");
+
+    // Invalidate cache; the threshold or warehouse may have changed
+    item_cache::invalidate(&state.redis, &sku).await;
+    list_cache::invalidate(&state.redis).await;
+    refresh_low_stock(&state).await;
+
+    Ok(Json(item))
+}
+
+// -----------------------------------------------------------------------------
+// ORDER STATUS CALLBACK
+// -----------------------------------------------------------------------------
+/// Settle an order's reservations when its status changes
+///
+/// POST /api/v1/integrations/orders/callback
+///
+/// HTTP alternative to consuming order events from Kafka:
+/// - paid / fulfilled: reserved stock is confirmed (deducted from on-hand)
+/// - cancelled: reserved stock is released
+///
+/// Safe to retry: an order is settled once, duplicates return
+/// `"applied": false`.
+///
+/// # Request Body
+/// ```json
+/// {
+///   "order_id": "ORD-12345",
+///   "status": "cancelled",
+///   "items": [{ "sku": "SKU-LAPTOP-001", "quantity": 2 }]
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// { "order_id": "ORD-12345", "action": "release", "applied": true }
+/// ```
+#[utoipa::path(
+    post,
+    path = "/api/v1/integrations/orders/callback",
+    tag = "integrations",
+    request_body = OrderCallbackRequest,
+    responses(
+        (status = 200, description = "Callback applied (or already applied)", body = OrderCallbackResponse),
+        (status = 400, description = "Invalid callback", body = ErrorResponse),
+        (status = 409, description = "Order settled the other way already, or not holding what it confirms", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+        (status = 503, description = "Database unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn order_status_callback(
+    State(state): State<Arc<AppState>>,
+    ValidJson(request): ValidJson<OrderCallbackRequest>,
+) -> AppResult<Json<OrderCallbackResponse>> {
+    tracing::info!(
+        order_id = %request.order_id,
+        status = request.status.as_str(),
+        items = request.items.len(),
+        "Received order status callback"
+    );
+
+    let response = match state.db.settle_order(&request).await {
+        Ok(response) => response,
+        Err(e) => {
+            for item in &request.items {
+                audit::record_failure(
+                    &state.db,
+                    request.status.action(),
+                    &item.sku,
+                    item.quantity,
+                    &request.order_id,
+                    &e,
+                )
+                .await;
+            }
+            tracing::warn!(order_id = %request.order_id, error = %e, "Failed to settle order");
+            return match AppError::from_db(e) {
+                Ok(app_error) => Err(app_error),
+                Err(e) => Err(e.into()),
+            };
+        }
+    };
+
+    if response.applied {
+        let kind = match request.status {
+            OrderCallbackStatus::Paid | OrderCallbackStatus::Fulfilled => ChangeKind::Confirmed,
+            OrderCallbackStatus::Cancelled => ChangeKind::Released,
+        };
+        for item in &request.items {
+            item_cache::invalidate(&state.redis, &item.sku).await;
+            state.live.publish(kind, &item.sku, item.quantity);
+        }
+        list_cache::invalidate(&state.redis).await;
+    }
+
+    Ok(Json(response))
+}
+
+// -----------------------------------------------------------------------------
+// LOW STOCK ALERTS
+// -----------------------------------------------------------------------------
+/// Get all items with low stock
+///
+/// GET /api/v1/inventory/alerts
+///
+/// Returns items where available stock is below the threshold, as of the
+/// last scheduled evaluation (see low_stock.rs).
+#[utoipa::path(
+    get,
+    path = "/api/v1/inventory/alerts",
+    tag = "inventory",
+    responses(
+        (status = 200, description = "Items below their low-stock threshold", body = Vec<LowStockAlert>),
+    )
+)]
+pub async fn low_stock_alerts(
+    State(state): State<Arc<AppState>>,
+) -> AppResult<Json<Vec<LowStockAlert>>> {
+    // Before the first scheduled run, evaluate now
+    let alerts = match state.low_stock.latest() {
+        Some(evaluation) => evaluation.alerts,
+        None => state.low_stock.evaluate(&state.db).await?.alerts,
+    };
+
+    Ok(Json(alerts))
+}
+
+// =============================================================================
+// REPORTS
+// =============================================================================
+
+/// Query parameters for the stockout report
+///
+/// # Example
+/// GET /api/v1/reports/stockouts?period=24h
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StockoutReportParams {
+    /// "<n>h" or "<n>d" (default: 7d, max: 30d)
+    pub period: Option<String>,
+}
+
+/// Stockout incidents, their duration and reservation fill rate per SKU
+///
+/// GET /api/v1/reports/stockouts?period=7d
+///
+/// See reports.rs for how the numbers are computed.
+///
+/// # Response
+/// - 200 OK: StockoutReport
+/// - 400 Bad Request: invalid period
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/stockouts",
+    tag = "reports",
+    params(StockoutReportParams),
+    responses(
+        (status = 200, description = "Stockouts and fill rate per SKU", body = StockoutReport),
+        (status = 400, description = "Invalid period", body = ErrorResponse),
+    )
+)]
+pub async fn stockout_report(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<StockoutReportParams>,
+) -> AppResult<Json<StockoutReport>> {
+    let start = Instant::now();
+
+    let period = params
+        .period
+        .unwrap_or_else(|| reports::DEFAULT_PERIOD.to_string());
+    let length = reports::parse_period(&period).map_err(AppError::BadRequest)?;
+    let generated_at = chrono::Utc::now();
+    let since = generated_at - length;
+
+    let items = state.db.stockout_report(since).await?;
+    metrics::record_db_query("stockout_report", start.elapsed().as_secs_f64());
+
+    Ok(Json(StockoutReport {
+        period,
+        since,
+        generated_at,
+        items,
+    }))
+}
+
+/// Business KPIs in one JSON document
+///
+/// GET /api/v1/stats
+///
+/// For simple dashboards and smoke tests that shouldn't need PromQL.
+/// The low-stock count comes from the latest scheduled evaluation.
+///
+/// # Response
+/// See StatsResponse
+#[utoipa::path(
+    get,
+    path = "/api/v1/stats",
+    tag = "reports",
+    responses(
+        (status = 200, description = "Inventory totals", body = StatsResponse),
+    )
+)]
+pub async fn stats(State(state): State<Arc<AppState>>) -> AppResult<Json<StatsResponse>> {
+    let start = Instant::now();
+    let generated_at = chrono::Utc::now();
+
+    let totals = state.db.inventory_totals().await?;
+    let active_reservations = state.db.active_reservation_count().await?;
+    let movements_last_24h = state
+        .db
+        .movement_counts(generated_at - chrono::Duration::hours(24))
+        .await?
+        .into_iter()
+        .collect();
+    metrics::record_db_query("stats", start.elapsed().as_secs_f64());
+
+    let low_stock_items = match state.low_stock.latest() {
+        Some(evaluation) => evaluation.alerts.len(),
+        None => state.low_stock.evaluate(&state.db).await?.alerts.len(),
+    };
+
+    Ok(Json(StatsResponse {
+        totals,
+        active_reservations,
+        low_stock_items,
+        movements_last_24h,
+        generated_at,
+    }))
+}
+
+// -----------------------------------------------------------------------------
+// SNAPSHOTS
+// -----------------------------------------------------------------------------
+/// Take a snapshot of every item's stock
+///
+/// POST /api/v1/snapshots
+///
+/// # Request Body
+/// ```json
+/// { "label": "before flash-sale scenario" }
+/// ```
+///
+/// # Response
+/// - 201 Created: the snapshot (ID, time, number of SKUs)
+/// - 400 Bad Request: label too long
+#[utoipa::path(
+    post,
+    path = "/api/v1/snapshots",
+    tag = "snapshots",
+    request_body = SnapshotRequest,
+    responses(
+        (status = 201, description = "Snapshot taken", body = InventorySnapshot),
+        (status = 422, description = "Invalid label", body = ErrorResponse),
+    )
+)]
+pub async fn create_snapshot(
+    State(state): State<Arc<AppState>>,
+    ValidJson(request): ValidJson<SnapshotRequest>,
+) -> AppResult<(StatusCode, Json<InventorySnapshot>)> {
+    let label = request.label.as_deref().filter(|l| !l.trim().is_empty());
+
+    let snapshot = state.db.create_snapshot(label).await?;
+    tracing::info!(
+        snapshot_id = snapshot.id,
+        label = ?snapshot.label,
+        items = snapshot.items,
+        "Inventory snapshot taken"
+    );
+
+    Ok((StatusCode::CREATED, Json(snapshot)))
+}
+
+/// List recent snapshots, newest first
+///
+/// GET /api/v1/snapshots
+#[utoipa::path(
+    get,
+    path = "/api/v1/snapshots",
+    tag = "snapshots",
+    responses(
+        (status = 200, description = "Snapshots, newest first", body = Vec<InventorySnapshot>),
+    )
+)]
+pub async fn list_snapshots(
+    State(state): State<Arc<AppState>>,
+) -> AppResult<Json<Vec<InventorySnapshot>>> {
+    Ok(Json(state.db.list_snapshots(snapshots::LIST_LIMIT).await?))
+}
+
+/// Query parameters for the snapshot diff
+///
+/// # Example
+/// GET /api/v1/snapshots/diff?from=3&to=2024-01-15T12:00:00Z
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SnapshotDiffParams {
+    /// Snapshot ID or RFC 3339 timestamp
+    pub from: String,
+
+    /// Snapshot ID, RFC 3339 timestamp or "now" (default: current stock)
+    pub to: Option<String>,
+}
+
+/// Per-SKU changes between two snapshots, or a snapshot and current stock
+///
+/// GET /api/v1/snapshots/diff?from=3&to=5
+///
+/// # Response
+/// - 200 OK: SnapshotDiffResponse (only SKUs that changed)
+/// - 400 Bad Request: `from` or `to` is neither an ID nor a timestamp
+/// - 404 Not Found: no such snapshot (or none taken before the timestamp)
+#[utoipa::path(
+    get,
+    path = "/api/v1/snapshots/diff",
+    tag = "snapshots",
+    params(SnapshotDiffParams),
+    responses(
+        (status = 200, description = "SKUs that changed", body = SnapshotDiffResponse),
+        (status = 400, description = "Invalid snapshot reference", body = ErrorResponse),
+        (status = 404, description = "No such snapshot", body = ErrorResponse),
+    )
+)]
+pub async fn snapshot_diff(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SnapshotDiffParams>,
+) -> AppResult<Json<SnapshotDiffResponse>> {
+    let start = Instant::now();
+
+    let from_ref = snapshots::SnapshotRef::parse(&params.from).map_err(AppError::BadRequest)?;
+    let to_ref =
+        snapshots::SnapshotRef::parse_to(params.to.as_deref()).map_err(AppError::BadRequest)?;
+
+    let not_found = |value: &str| AppError::NotFound(format!("Snapshot not found: {}", value));
+    let from = state
+        .db
+        .find_snapshot(from_ref)
+        .await?
+        .ok_or_else(|| not_found(&params.from))?;
+    let to = match to_ref {
+        Some(to_ref) => Some(
+            state
+                .db
+                .find_snapshot(to_ref)
+                .await?
+                .ok_or_else(|| not_found(params.to.as_deref().unwrap_or_default()))?,
+        ),
+        None => None,
+    };
+
+    let items = state
+        .db
+        .snapshot_diff(from.id, to.as_ref().map(|s| s.id))
+        .await?;
+    metrics::record_db_query("snapshot_diff", start.elapsed().as_secs_f64());
+
+    Ok(Json(SnapshotDiffResponse {
+        totals: snapshots::totals(&items),
+        from,
+        to,
+        items,
+    }))
+}
+
+// =============================================================================
+// ANALYTICS
+// =============================================================================
+
+/// Query parameters for the consumption series
+///
+/// # Example
+/// GET /api/v1/analytics/consumption?sku=SKU-001&window=30d
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ConsumptionParams {
+    /// Only this SKU (default: all SKUs together)
+    pub sku: Option<String>,
+
+    /// "<n>h" or "<n>d" (default: 7d, max: 90d)
+    pub window: Option<String>,
+
+    /// "hour" or "day" (default: hour up to 2d, day beyond)
+    pub bucket: Option<String>,
+}
+
+/// Units consumed and received over time
+///
+/// GET /api/v1/analytics/consumption?window=7d
+/// GET /api/v1/analytics/consumption?sku=SKU-001&window=24h
+///
+/// Summed from the movement ledger; see analytics.rs for what counts as
+/// consumed. A SKU without movements gets a series of zeros.
+///
+/// # Response
+/// - 200 OK: ConsumptionSeries
+/// - 400 Bad Request: invalid window or bucket
+#[utoipa::path(
+    get,
+    path = "/api/v1/analytics/consumption",
+    tag = "analytics",
+    params(ConsumptionParams),
+    responses(
+        (status = 200, description = "Consumption per bucket, oldest first", body = ConsumptionSeries),
+        (status = 400, description = "Invalid window or bucket", body = ErrorResponse),
+    )
+)]
+pub async fn consumption_series(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConsumptionParams>,
+) -> AppResult<Json<ConsumptionSeries>> {
+    let window = params
+        .window
+        .unwrap_or_else(|| analytics::DEFAULT_WINDOW.to_string());
+    let spec = analytics::SeriesSpec::new(&window, params.bucket.as_deref())
+        .map_err(AppError::BadRequest)?;
+    let sku = params.sku.filter(|s| !s.is_empty());
+    let since = chrono::Utc::now() - spec.window;
+
+    let start = Instant::now();
+    let points = state
+        .db
+        .consumption_series(sku.as_deref(), spec.bucket.as_str(), since)
+        .await?;
+    metrics::record_db_query("consumption_series", start.elapsed().as_secs_f64());
+
+    Ok(Json(ConsumptionSeries {
+        sku,
+        window,
+        bucket: spec.bucket.as_str().to_string(),
+        since,
+        consumed: points.iter().map(|p| p.consumed).sum(),
+        received: points.iter().map(|p| p.received).sum(),
+        points,
+    }))
+}
+
+/// Query parameters for the top movers
+///
+/// # Example
+/// GET /api/v1/analytics/top-movers?window=24h&limit=5
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TopMoversParams {
+    /// "<n>h" or "<n>d" (default: 7d, max: 90d)
+    pub window: Option<String>,
+
+    /// "hour" or "day" (default: hour up to 2d, day beyond)
+    pub bucket: Option<String>,
+
+    /// SKUs to return (default: 10, max: 50)
+    #[serde(default = "default_top_movers")]
+    pub limit: i64,
+}
+
+fn default_top_movers() -> i64 {
+    analytics::DEFAULT_TOP_MOVERS
+}
+
+/// The SKUs that consumed the most units, each with its series
+///
+/// GET /api/v1/analytics/top-movers?window=7d&limit=10
+///
+/// # Response
+/// - 200 OK: TopMoversResponse
+/// - 400 Bad Request: invalid window or bucket
+#[utoipa::path(
+    get,
+    path = "/api/v1/analytics/top-movers",
+    tag = "analytics",
+    params(TopMoversParams),
+    responses(
+        (status = 200, description = "Most consumed SKUs first", body = TopMoversResponse),
+        (status = 400, description = "Invalid window or bucket", body = ErrorResponse),
+    )
+)]
+pub async fn top_movers(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<TopMoversParams>,
+) -> AppResult<Json<TopMoversResponse>> {
+    let window = params
+        .window
+        .unwrap_or_else(|| analytics::DEFAULT_WINDOW.to_string());
+    let spec = analytics::SeriesSpec::new(&window, params.bucket.as_deref())
+        .map_err(AppError::BadRequest)?;
+    let limit = params.limit.clamp(1, analytics::MAX_TOP_MOVERS);
+    let since = chrono::Utc::now() - spec.window;
+
+    let start = Instant::now();
+    let rows = state.db.top_movers(spec.bucket.as_str(), since, limit).await?;
+    metrics::record_db_query("top_movers", start.elapsed().as_secs_f64());
+
+    // Rows come one per SKU and bucket, grouped by SKU in rank order
+    let mut items: Vec<TopMover> = Vec::new();
+    for row in rows {
+        if items.last().is_none_or(|mover| mover.sku != row.sku) {
+            items.push(TopMover {
+                sku: row.sku.clone(),
+                name: row.name.clone(),
+                consumed: row.total_consumed,
+                points: Vec::new(),
+            });
+        }
+        if let Some(mover) = items.last_mut() {
+            mover.points.push(ConsumptionPoint {
+                start: row.start,
+                consumed: row.consumed,
+                received: row.received,
+            });
+        }
+    }
+
+    Ok(Json(TopMoversResponse {
+        window,
+        bucket: spec.bucket.as_str().to_string(),
+        since,
+        items,
+    }))
+}
+
+// =============================================================================
+// ADMIN ENDPOINTS
+// =============================================================================
+
+// -----------------------------------------------------------------------------
+// BACKGROUND TASKS
+// -----------------------------------------------------------------------------
+/// Status of all supervised background tasks
+///
+/// GET /api/v1/admin/tasks
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "name": "reservation-sweeper",
+///     "state": "running",
+///     "restarts": 0,
+///     "last_started_at": "2024-01-01T00:00:00Z",
+///     "last_exit_at": null,
+///     "last_error": null
+///   }
+/// ]
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/tasks",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Supervised background tasks", body = Vec<TaskStatus>),
+    )
+)]
+pub async fn background_tasks(State(state): State<Arc<AppState>>) -> Json<Vec<TaskStatus>> {
+    Json(state.supervisor.statuses())
+}
+
+// -----------------------------------------------------------------------------
+// AUDIT EXPORT
+// -----------------------------------------------------------------------------
+/// Query parameters for the audit export
+///
+/// # Example
+/// GET /api/v1/admin/audit/export?format=cef&after_id=1200&limit=500
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditExportParams {
+    /// Output format: "jsonl" (default) or "cef"
+    #[serde(default = "default_audit_format")]
+    pub format: String,
+
+    /// Only return events with a greater ID (resume cursor, default: 0)
+    #[serde(default)]
+    pub after_id: i64,
+
+    /// Maximum events to return (default: 1000, max: 10000)
+    #[serde(default = "default_audit_limit")]
+    pub limit: i64,
+}
+
+fn default_audit_format() -> String {
+    "jsonl".to_string()
+}
+fn default_audit_limit() -> i64 {
+    1000
+}
+
+/// Export the audit trail for SIEM ingestion
+///
+/// GET /api/v1/admin/audit/export
+///
+/// Returns one event per line, oldest first. The `X-Next-Cursor` header
+/// holds the last returned ID; pass it as `after_id` to continue. Events
+/// younger than twice REQUEST_TIMEOUT_MS are left for a later call, so the
+/// cursor never moves past an event that is still being committed.
+///
+/// # Response (format=cef)
+/// ```text
+/// CEF:0|grafana-lab|inventory-service|1.0.0|inventory.reserve|Stock reserve success|3|rt=... cs1=SKU-LAPTOP-001 cnt=5
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/audit/export",
+    tag = "admin",
+    params(AuditExportParams),
+    responses(
+        (status = 200, description = "One event per line (JSON lines or CEF)", body = String, content_type = "text/plain"),
+        (status = 400, description = "Invalid format or limit", body = ErrorResponse),
+    )
+)]
+pub async fn export_audit_events(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AuditExportParams>,
+) -> AppResult<Response> {
+    let limit = params.limit.clamp(1, 10_000);
+    let settle = audit::export_settle(Duration::from_millis(state.config.request_timeout_ms));
+    let events = state.db.list_audit_events(params.after_id.max(0), limit, settle).await?;
+    let next_cursor = events.last().map(|e| e.id).unwrap_or(params.after_id);
+
+    let (content_type, body) = match params.format.as_str() {
+        "jsonl" | "ndjson" => (
+            "application/x-ndjson",
+            events.iter().map(audit::to_json_line).collect::<String>(),
+        ),
+        "cef" => (
+            "text/plain; charset=utf-8",
+            events
+                .iter()
+                .map(|e| audit::to_cef(e) + "\n")
+                .collect::<String>(),
+        ),
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "Unsupported format '{}', expected jsonl or cef",
+                other
+            )))
+        }
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (HeaderName::from_static("x-next-cursor"), next_cursor.to_string()),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+// -----------------------------------------------------------------------------
+// API USAGE
+// -----------------------------------------------------------------------------
+/// Query parameters for the usage report
+///
+/// # Example
+/// GET /api/v1/admin/usage?from=2024-05-01&to=2024-05-31&key_id=9f2c41d07ab3e615
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UsageReportParams {
+    /// First day, UTC (default: 6 days before `to`)
+    pub from: Option<chrono::NaiveDate>,
+
+    /// Last day, UTC (default: today)
+    pub to: Option<chrono::NaiveDate>,
+
+    /// Only this key (its X-Usage-Key-Id)
+    pub key_id: Option<String>,
+}
+
+/// Requests and reserved units per API key and day
+///
+/// GET /api/v1/admin/usage
+///
+/// Counts come from the usage rollup (see usage.rs), so today's numbers
+/// trail live traffic by up to USAGE_ROLLUP_INTERVAL_SECS.
+///
+/// # Response
+/// - 200 OK: UsageReport
+/// - 400 Bad Request: reversed period or longer than 92 days
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/usage",
+    tag = "admin",
+    params(UsageReportParams),
+    responses(
+        (status = 200, description = "Usage per key and day", body = UsageReport),
+        (status = 400, description = "Invalid period", body = ErrorResponse),
+    )
+)]
+pub async fn usage_report(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<UsageReportParams>,
+) -> AppResult<Json<UsageReport>> {
+    let (from, to) = usage::report_period(params.from, params.to, chrono::Utc::now().date_naive())
+        .map_err(AppError::BadRequest)?;
+
+    let start = Instant::now();
+    let days = state
+        .db
+        .list_api_usage(from, to, params.key_id.as_deref())
+        .await?;
+    metrics::record_db_query("list_api_usage", start.elapsed().as_secs_f64());
+
+    Ok(Json(UsageReport {
+        from,
+        to,
+        keys: usage::totals(&days),
+        days,
+    }))
+}
+
+// -----------------------------------------------------------------------------
+// TRAFFIC REPLAY
+// -----------------------------------------------------------------------------
+/// Replay a recorded request log against this service
+///
+/// POST /api/v1/admin/replay
+///
+/// The log is validated up front, then replayed in the background; the
+/// response returns immediately. Progress shows up in the usual HTTP
+/// dashboards, and a summary is logged when the replay finishes.
+///
+/// # Request Body
+/// ```json
+/// { "file": "morning-spike.jsonl", "speed": 2.0 }
+/// ```
+///
+/// # Response
+/// - 202 Accepted: replay started
+/// - 400 Bad Request: invalid file name, speed or log contents
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/replay",
+    tag = "admin",
+    request_body = ReplayRequest,
+    responses(
+        (status = 202, description = "Replay started", body = ReplayStartedResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+    )
+)]
+pub async fn start_replay(
+    State(state): State<Arc<AppState>>,
+    ValidJson(request): ValidJson<ReplayRequest>,
+) -> AppResult<(StatusCode, Json<ReplayStartedResponse>)> {
+    let path = replay::resolve(&state.config.replay_dir, &request.file).ok_or_else(|| {
+        AppError::BadRequest("file must be a plain file name inside REPLAY_DIR".to_string())
+    })?;
+    let records = replay::load(&path)
+        .await
+        .map_err(|e| AppError::BadRequest(format!("{:#}", e)))?;
+
+    let last_offset = records.last().map(|r| r.offset_ms).unwrap_or(0);
+    let response = ReplayStartedResponse {
+        file: request.file.clone(),
+        records: records.len(),
+        speed: request.speed,
+        duration_ms: (last_offset as f64 / request.speed) as u64,
+    };
+
+    tracing::info!(
+        file = %request.file,
+        records = records.len(),
+        speed = request.speed,
+        "Starting traffic replay"
+    );
+
+    let base_url = tls::loopback_url(state.config.port, state.config.tls.as_ref());
+    let http = state.http.loopback(state.config.tls.is_some())?;
+    let file = request.file;
+    tokio::spawn(async move {
+        let summary = replay::run(http, base_url, file.clone(), records, request.speed).await;
+        tracing::info!(
+            file = %file,
+            sent = summary.sent,
+            failed = summary.failed,
+            "Traffic replay finished"
+        );
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+// -----------------------------------------------------------------------------
+// RESERVATION RECONCILIATION
+// -----------------------------------------------------------------------------
+/// Query parameters for reservation reconciliation
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReconcileParams {
+    /// Write the expected values back (default: false, report only)
+    #[serde(default)]
+    pub repair: bool,
+}
+
+/// Compare each SKU's `reserved` column with its active reservations
+///
+/// POST /api/v1/admin/reconcile-reservations?repair=true
+///
+/// Active reservations are what the `reservations` rows still hold.
+/// Drift is left behind by releases that never arrived, or arrived twice,
+/// before holdings were tracked per reservation. Holdings older than the
+/// audit trail are invisible here, so look at the dry-run report before
+/// repairing.
+///
+/// # Response
+/// See ReconcileResponse
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/reconcile-reservations",
+    tag = "admin",
+    params(ReconcileParams),
+    responses(
+        (status = 200, description = "Drift report", body = ReconcileResponse),
+    )
+)]
+pub async fn reconcile_reservations(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ReconcileParams>,
+) -> AppResult<Json<ReconcileResponse>> {
+    let start = Instant::now();
+    let mut drifted = state.db.reservation_drift().await?;
+
+    let mut repaired = 0;
+    if params.repair && !drifted.is_empty() {
+        let fixed = state.db.repair_reservation_drift(&drifted).await?;
+        for row in drifted.iter_mut().filter(|row| fixed.contains(&row.sku)) {
+            row.repaired = true;
+            item_cache::invalidate(&state.redis, &row.sku).await;
+            state.live.publish(ChangeKind::Updated, &row.sku, 0);
+        }
+        list_cache::invalidate(&state.redis).await;
+        repaired = fixed.len();
+    }
+    metrics::record_db_query("reconcile_reservations", start.elapsed().as_secs_f64());
+
+    if !drifted.is_empty() {
+        tracing::warn!(
+            drifted = drifted.len(),
+            repaired,
+            "Reserved stock drifted from active reservations"
+        );
+    }
+
+    Ok(Json(ReconcileResponse {
+        repair: params.repair,
+        checked_at: chrono::Utc::now(),
+        drifted,
+        repaired,
+    }))
+}
+
+// -----------------------------------------------------------------------------
+// MIGRATION STATUS
+// -----------------------------------------------------------------------------
+/// Migrations of this build not yet applied to the database
+///
+/// GET /api/v1/admin/migrations
+///
+/// Check this after running `inventory-service --migrate-only` and before
+/// shifting traffic to new pods started with AUTO_MIGRATE=false.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/migrations",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Expected and applied schema versions", body = MigrationStatus),
+    )
+)]
+pub async fn migration_status(
+    State(state): State<Arc<AppState>>,
+) -> AppResult<Json<MigrationStatus>> {
+    let (applied_version, pending) = state.db.pending_migrations().await?;
+
+    Ok(Json(MigrationStatus {
+        expected_version: db::latest_migration_version(),
+        applied_version,
+        pending,
+        auto_migrate: state.config.auto_migrate,
+    }))
+}
+
+// -----------------------------------------------------------------------------
+// SELFTEST
+// -----------------------------------------------------------------------------
+/// Run the end-to-end selftest (see selftest.rs)
+///
+/// POST /api/v1/admin/selftest
+///
+/// # Response
+/// - 200 OK: every step passed
+/// - 503 Service Unavailable: a step failed; the report says which
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/selftest",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Every step passed", body = SelftestReport),
+        (status = 503, description = "A step failed", body = SelftestReport),
+    )
+)]
+pub async fn run_selftest(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, Json<selftest::SelftestReport>) {
+    let report = selftest::run(&state.db).await;
+
+    if report.passed {
+        tracing::debug!(sku = %report.sku, total_ms = report.total_ms, "Selftest passed");
+        (StatusCode::OK, Json(report))
+    } else {
+        let failed: Vec<&str> = report
+            .steps
+            .iter()
+            .filter(|step| !step.passed)
+            .map(|step| step.name)
+            .collect();
+        tracing::warn!(sku = %report.sku, ?failed, "Selftest failed");
+        (StatusCode::SERVICE_UNAVAILABLE, Json(report))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// WEBHOOK SUBSCRIPTIONS
+// -----------------------------------------------------------------------------
+/// Subscribe a third party to inventory events
+///
+/// POST /api/v1/admin/webhooks
+///
+/// # Request Body
+/// ```json
+/// {
+///   "url": "https://wms.example.com/hooks/inventory",
+///   "event_types": ["reservation.created", "stock.adjusted"],
+///   "sku_pattern": "LAPTOP-*"
+/// }
+/// ```
+///
+/// # Response
+/// - 201 Created: the stored subscription
+/// - 400 Bad Request: invalid URL, unknown event type or empty SKU pattern
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/webhooks",
+    tag = "admin",
+    request_body = WebhookSubscriptionRequest,
+    responses(
+        (status = 201, description = "Subscription created", body = WebhookSubscription),
+        (status = 422, description = "Invalid URL or events", body = ErrorResponse),
+    )
+)]
+pub async fn create_webhook_subscription(
+    State(state): State<Arc<AppState>>,
+    ValidJson(request): ValidJson<WebhookSubscriptionRequest>,
+) -> AppResult<(StatusCode, Json<WebhookSubscription>)> {
+
+    let subscription = state.db.create_webhook_subscription(&request).await?;
+    tracing::info!(
+        subscription_id = subscription.id,
+        event_types = ?subscription.event_types,
+        sku_pattern = ?subscription.sku_pattern,
+        "Webhook subscription created"
+    );
+
+    Ok((StatusCode::CREATED, Json(subscription)))
+}
+
+/// List webhook subscriptions
+///
+/// GET /api/v1/admin/webhooks
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/webhooks",
+    tag = "admin",
+    responses(
+        (status = 200, description = "All subscriptions", body = Vec<WebhookSubscription>),
+    )
+)]
+pub async fn list_webhook_subscriptions(
+    State(state): State<Arc<AppState>>,
+) -> AppResult<Json<Vec<WebhookSubscription>>> {
+    Ok(Json(state.db.list_webhook_subscriptions().await?))
+}
+
+/// Remove a webhook subscription
+///
+/// DELETE /api/v1/admin/webhooks/:id
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/webhooks/{id}",
+    tag = "admin",
+    params(("id" = i64, Path, description = "Subscription ID")),
+    responses(
+        (status = 204, description = "Subscription removed"),
+        (status = 404, description = "No such subscription", body = ErrorResponse),
+    )
+)]
+pub async fn delete_webhook_subscription(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> AppResult<StatusCode> {
+    if !state.db.delete_webhook_subscription(id).await? {
+        return Err(AppError::NotFound(format!("Webhook subscription not found: {}", id)));
+    }
+
+    tracing::info!(subscription_id = id, "Webhook subscription removed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// -----------------------------------------------------------------------------
+// DATABASE LOAD GENERATION
+// -----------------------------------------------------------------------------
+/// Start synthetic database load
+///
+/// POST /api/v1/admin/load/db
+///
+/// # Request Body
+/// ```json
+/// { "scenario": "heavy_scan", "concurrency": 4, "duration_secs": 60 }
+/// ```
+///
+/// # Response
+/// - 202 Accepted: run started (status of the new run)
+/// - 400 Bad Request: invalid parameters or a run is already active
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/load/db",
+    tag = "admin",
+    request_body = LoadRequest,
+    responses(
+        (status = 202, description = "Load started", body = LoadStatus),
+        (status = 400, description = "Invalid request, or a run is already going", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+    )
+)]
+pub async fn start_db_load(
+    State(state): State<Arc<AppState>>,
+    ValidJson(request): ValidJson<LoadRequest>,
+) -> AppResult<(StatusCode, Json<LoadStatus>)> {
+    let status = state
+        .loadgen
+        .start(state.db.clone(), request)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    Ok((StatusCode::ACCEPTED, Json(status)))
+}
+
+/// Status of the current or last load run
+///
+/// GET /api/v1/admin/load/db
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/load/db",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Current or last run", body = LoadStatus),
+        (status = 404, description = "No load run has been started", body = ErrorResponse),
+    )
+)]
+pub async fn db_load_status(State(state): State<Arc<AppState>>) -> AppResult<Json<LoadStatus>> {
+    state
+        .loadgen
+        .status()
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("No load run has been started".to_string()))
+}
+
+/// Stop the active load run early
+///
+/// DELETE /api/v1/admin/load/db
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/load/db",
+    tag = "admin",
+    responses(
+        (status = 200, description = "The stopped run", body = LoadStatus),
+        (status = 404, description = "No load run has been started", body = ErrorResponse),
+    )
+)]
+pub async fn stop_db_load(State(state): State<Arc<AppState>>) -> AppResult<Json<LoadStatus>> {
+    state
+        .loadgen
+        .stop()
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("No load run has been started".to_string()))
+}
+
+// -----------------------------------------------------------------------------
+// CHAOS EXPERIMENTS
+// -----------------------------------------------------------------------------
+/// Start a chaos experiment
+///
+/// POST /api/v1/chaos
+///
+/// # Request Body
+/// ```json
+/// { "endpoint": "/api/v1/inventory/reserve", "latency_ms": 300,
+///   "error_rate": 0.2, "duration_secs": 120 }
+/// ```
+///
+/// # Response
+/// - 201 Created: experiment started
+/// - 400 Bad Request: 10 experiments are already running
+/// - 422 Unprocessable Entity: invalid faults, or none at all
+#[utoipa::path(
+    post,
+    path = "/api/v1/chaos",
+    tag = "admin",
+    request_body = FaultRequest,
+    responses(
+        (status = 201, description = "Experiment started", body = Experiment),
+        (status = 400, description = "Too many experiments running", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+    )
+)]
+pub async fn start_chaos(
+    State(state): State<Arc<AppState>>,
+    ValidJson(faults): ValidJson<FaultRequest>,
+) -> AppResult<(StatusCode, Json<Experiment>)> {
+    let experiment = state
+        .chaos
+        .start(faults)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    Ok((StatusCode::CREATED, Json(experiment)))
+}
+
+/// Running chaos experiments
+///
+/// GET /api/v1/chaos
+#[utoipa::path(
+    get,
+    path = "/api/v1/chaos",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Running experiments, oldest first", body = [Experiment]),
+    )
+)]
+pub async fn list_chaos(State(state): State<Arc<AppState>>) -> Json<Vec<Experiment>> {
+    Json(state.chaos.list())
+}
+
+/// Stop every chaos experiment
+///
+/// DELETE /api/v1/chaos
+#[utoipa::path(
+    delete,
+    path = "/api/v1/chaos",
+    tag = "admin",
+    responses(
+        (status = 200, description = "The stopped experiments", body = [Experiment]),
+    )
+)]
+pub async fn stop_all_chaos(State(state): State<Arc<AppState>>) -> Json<Vec<Experiment>> {
+    Json(state.chaos.stop_all())
+}
+
+/// Stop one chaos experiment early
+///
+/// DELETE /api/v1/chaos/:id
+#[utoipa::path(
+    delete,
+    path = "/api/v1/chaos/{id}",
+    tag = "admin",
+    params(("id" = u64, Path, description = "Experiment ID")),
+    responses(
+        (status = 200, description = "The stopped experiment", body = Experiment),
+        (status = 404, description = "No such experiment running", body = ErrorResponse),
+    )
+)]
+pub async fn stop_chaos(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> AppResult<Json<Experiment>> {
+    state
+        .chaos
+        .stop(id)
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("No chaos experiment {} is running", id)))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+// Handlers over the in-memory repository (see test_support.rs)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn create(sku: &str, quantity: i32) -> ValidJson<CreateItemRequest> {
+        ValidJson::new(CreateItemRequest {
+            sku: sku.to_string(),
+            name: format!("Item {}", sku),
+            quantity,
+            warehouse: "JKT-1".to_string(),
+            low_stock_threshold: 5,
+        })
+        .unwrap()
+    }
+
+    fn reserve(sku: &str, quantity: i32, order_id: &str) -> ValidJson<ReserveStockRequest> {
+        ValidJson::new(ReserveStockRequest {
+            sku: sku.to_string(),
+            quantity,
+            order_id: order_id.to_string(),
+            channel: Default::default(),
+            hold: Default::default(),
+            priority: 0,
+            quota: None,
+        })
+        .unwrap()
+    }
+
+    #[cfg(feature = "jemalloc")]
+    #[tokio::test]
+    async fn test_metrics_scrape_includes_allocator_gauges() {
+        let recorder = metrics_exporter_prometheus::PrometheusBuilder::new().build_recorder();
+        let mut state = Arc::into_inner(test_support::state(&[]).await).unwrap();
+        state.metrics_handle = recorder.handle();
+        let _recorder = ::metrics::set_default_local_recorder(&recorder);
+
+        let response = metrics_handler(State(Arc::new(state)), HeaderMap::new()).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        for kind in ["allocated", "resident", "retained"] {
+            let series = format!("{}{{kind=\"{}\"}}", metrics::ALLOCATOR_BYTES, kind);
+            assert!(text.contains(&series), "{} missing from:\n{}", series, text);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_get_and_list_in_memory() {
+        let state = test_support::state(&[]).await;
+
+        let (status, Json(item)) = create_item(State(state.clone()), create("SKU-B", 8)).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(item.quantity, 8);
+        let (status, _) = create_item(State(state.clone()), create("SKU-A", 3)).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        let duplicate = create_item(State(state.clone()), create("SKU-A", 1)).await.unwrap_err();
+        assert!(matches!(duplicate, AppError::BadRequest(_)), "{:?}", duplicate);
+
+        let (_, _, Json(item)) = get_item(State(state.clone()), Path("SKU-B".to_string())).await.unwrap();
+        assert_eq!((item.sku.as_str(), item.quantity), ("SKU-B", 8));
+        let missing = get_item(State(state.clone()), Path("NOPE".to_string())).await.unwrap_err();
+        assert!(matches!(missing, AppError::NotFound(_)), "{:?}", missing);
+
+        let params: ListParams = serde_json::from_str("{}").unwrap();
+        let (_, Json(list)) = list_inventory(State(state), Query(params), Query(HashMap::new())).await.unwrap();
+        assert_eq!(list.total, 2);
+        let skus: Vec<&str> = list.items.iter().map(|item| item.sku.as_str()).collect();
+        assert_eq!(skus, ["SKU-A", "SKU-B"]);
+    }
+
+    #[tokio::test]
+    async fn test_reserve_in_memory() {
+        let state = test_support::state(&[]).await;
+        let (status, _) = create_item(State(state.clone()), create("SKU-1", 10)).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let client = || RequestClient(None);
+        let Json(first) = reserve_stock(State(state.clone()), client(), reserve("SKU-1", 6, "ORD-1")).await.unwrap();
+        assert!(!first.replayed);
+        let Json(again) = reserve_stock(State(state.clone()), client(), reserve("SKU-1", 6, "ORD-1")).await.unwrap();
+        assert!(again.replayed);
+        assert_eq!(again.reservation_id, first.reservation_id);
+
+        let short = reserve_stock(State(state.clone()), client(), reserve("SKU-1", 5, "ORD-2")).await.unwrap_err();
+        assert!(
+            matches!(short, AppError::InsufficientStock { available: 4, requested: 5 }),
+            "{:?}",
+            short
+        );
+
+        let (_, _, Json(item)) = get_item(State(state), Path("SKU-1".to_string())).await.unwrap();
+        assert_eq!(item.reserved, 6);
+    }
+
+    #[tokio::test]
+    async fn test_batch_and_prepare_held_to_fairness_policy() {
+        let state = test_support::state(&[("POLICIES_MAX_PER_ORDER", "5")]).await;
+        let items = serde_json::json!([
+            { "sku": "SKU-1", "quantity": 3 },
+            { "sku": "SKU-2", "quantity": 6 }
+        ]);
+
+        let batch: ReserveBatchRequest =
+            serde_json::from_value(serde_json::json!({ "order_id": "ORD-1", "items": items })).unwrap();
+        let rejected = reserve_batch(State(state.clone()), RequestClient(None), ValidJson::new(batch).unwrap())
+            .await
+            .unwrap_err();
+        assert!(matches!(rejected, AppError::FairShareExceeded(_)), "{:?}", rejected);
+
+        let prepare: PrepareCommitmentRequest = serde_json::from_value(serde_json::json!({
+            "transaction_id": "chk-1",
+            "order_id": "ORD-1",
+            "items": items
+        }))
+        .unwrap();
+        let rejected = prepare_commitment(State(state), RequestClient(None), ValidJson::new(prepare).unwrap())
+            .await
+            .unwrap_err();
+        assert!(matches!(rejected, AppError::FairShareExceeded(_)), "{:?}", rejected);
+    }
+
+    #[tokio::test]
+    async fn test_debug_vars_without_redis_or_postgres() {
+        let state = test_support::state(&[]).await;
+        let _client = state.live.subscribe(None);
+
+        let Json(vars) = debug_vars(State(state)).await;
+        assert_eq!(vars.live_subscribers, 1);
+        assert_eq!(vars.database_pools[0].pool, "primary");
+        // Neither count is made up when its store is unavailable
+        assert_eq!(vars.cache_entries, None);
+        assert!(vars.audit_outbox.is_none());
+    }
 }
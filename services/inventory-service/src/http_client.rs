@@ -0,0 +1,257 @@
+// =============================================================================
+// HTTP CLIENT MODULE
+// =============================================================================
+// The single place where this service makes outbound HTTP calls (webhooks,
+// audit shipping, integrations). Every call gets the same treatment:
+//
+// - Connection pooling: one shared reqwest::Client (cheap to clone)
+// - Timeouts: per-request timeout, shortened to the caller's deadline
+// - Retries: transport errors, 429 and 5xx retried with exponential backoff
+//   and jitter (Retry-After is honored when the server sends one)
+// - Metrics: requests, latency and retries per *destination* (a short
+//   logical name like "audit-http", never the raw URL, to bound cardinality)
+// - Trace context: `traceparent` header so downstream spans join our trace,
+//   plus X-Request-Deadline so downstream services stop when we would
+//
+// LEARNING NOTES:
+// - Only retry requests that are safe to repeat; POSTs to endpoints that
+//   aren't idempotent should be sent with `send_once`
+// - RequestBuilder::try_clone() fails for streaming bodies, so those can't
+//   be retried either
+// =============================================================================
+
+use anyhow::{Context, Result};
+use rand::Rng;
+use reqwest::{header::RETRY_AFTER, RequestBuilder, Response, StatusCode};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::deadline;
+use crate::metrics;
+use crate::trace_context::{self, TraceParent, TRACEPARENT_HEADER};
+
+// -----------------------------------------------------------------------------
+// CONFIGURATION
+// -----------------------------------------------------------------------------
+/// Outbound call policy
+#[derive(Debug, Clone, Copy)]
+pub struct HttpClientPolicy {
+    /// Timeout for a single attempt
+    pub timeout: Duration,
+    /// Retries after the first attempt (0 = no retries)
+    pub max_retries: u32,
+    /// First retry delay; doubles with each retry
+    pub backoff_base: Duration,
+}
+
+/// Longest we ever wait between retries, whatever the server asks for
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+fn builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .user_agent(concat!("inventory-service/", env!("CARGO_PKG_VERSION")))
+        .pool_idle_timeout(Duration::from_secs(90))
+        .connect_timeout(Duration::from_secs(5))
+}
+
+// =============================================================================
+// CLIENT
+// =============================================================================
+/// Shared outbound HTTP client
+#[derive(Clone)]
+pub struct HttpClient {
+    inner: reqwest::Client,
+    policy: HttpClientPolicy,
+}
+
+impl HttpClient {
+    pub fn new(policy: HttpClientPolicy) -> Result<Self> {
+        let inner = builder().build().context("Failed to build HTTP client")?;
+
+        Ok(Self { inner, policy })
+    }
+
+    /// A client with this one's policy for this service's own API port
+    /// (`tls::loopback_url`), as traffic replay and the traffic generator
+    /// use it.
+    ///
+    /// With TLS on it accepts whatever certificate the port presents: the
+    /// certificate is issued for the service's public name, not 127.0.0.1,
+    /// and may be rotated on SIGHUP. The port is one this process bound.
+    pub fn loopback(&self, tls: bool) -> Result<Self> {
+        let inner = builder()
+            .danger_accept_invalid_certs(tls)
+            .build()
+            .context("Failed to build loopback HTTP client")?;
+
+        Ok(Self { inner, policy: self.policy })
+    }
+
+    /// The underlying reqwest client, for building requests
+    pub fn client(&self) -> &reqwest::Client {
+        &self.inner
+    }
+
+    /// Send a request with retries.
+    ///
+    /// Returns the final response (which may still be an error status if
+    /// retries ran out) or the last transport error.
+    ///
+    /// # Arguments
+    /// * `destination` - Low-cardinality name used as the metrics label
+    /// * `request` - Request to send; must be cloneable for retries
+    pub async fn send(&self, destination: &str, request: RequestBuilder) -> Result<Response> {
+        // Every attempt belongs to the same outbound span
+        let traceparent = trace_context::outbound();
+        let mut attempt: u32 = 0;
+
+        loop {
+            let Some(this_attempt) = request.try_clone() else {
+                // Streaming body: can't be replayed, send exactly once
+                return self
+                    .attempt(destination, request, &traceparent)
+                    .await
+                    .with_context(|| format!("Outbound request to {} failed", destination));
+            };
+
+            let result = self.attempt(destination, this_attempt, &traceparent).await;
+
+            let retry_after = match &result {
+                Ok(response) if is_retryable_status(response.status()) => {
+                    Some(retry_after_header(response))
+                }
+                Ok(_) => None,
+                Err(e) if e.is_timeout() || e.is_connect() || e.is_request() => Some(None),
+                Err(_) => None,
+            };
+
+            match retry_after {
+                Some(server_hint) if attempt < self.policy.max_retries => {
+                    let delay = server_hint
+                        .unwrap_or_else(|| backoff_delay(self.policy.backoff_base, attempt))
+                        .min(MAX_BACKOFF);
+
+                    // Don't sleep past the caller's deadline
+                    if deadline::remaining().is_some_and(|left| left <= delay) {
+                        return result.map_err(Into::into);
+                    }
+
+                    metrics::record_http_client_retry(destination);
+                    tracing::debug!(
+                        destination,
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        "Retrying outbound request"
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                _ => {
+                    return result
+                        .with_context(|| format!("Outbound request to {} failed", destination));
+                }
+            }
+        }
+    }
+
+    /// Send a request exactly once (no retries), with metrics and headers
+    pub async fn send_once(&self, destination: &str, request: RequestBuilder) -> Result<Response> {
+        self.attempt(destination, request, &trace_context::outbound())
+            .await
+            .with_context(|| format!("Outbound request to {} failed", destination))
+    }
+
+    /// One attempt: decorate, send, record metrics
+    async fn attempt(
+        &self,
+        destination: &str,
+        request: RequestBuilder,
+        traceparent: &TraceParent,
+    ) -> reqwest::Result<Response> {
+        let start = Instant::now();
+
+        // Never wait longer than the caller is willing to
+        let timeout = match deadline::remaining() {
+            Some(left) => left.min(self.policy.timeout),
+            None => self.policy.timeout,
+        };
+
+        let mut request = request
+            .timeout(timeout)
+            .header(TRACEPARENT_HEADER, traceparent.to_header_value());
+        if let Some(left) = deadline::remaining() {
+            request = request.header(deadline::DEADLINE_HEADER, epoch_millis_after(left).to_string());
+        }
+
+        let result = request.send().await;
+
+        let status = match &result {
+            Ok(response) => response.status().as_u16().to_string(),
+            Err(e) if e.is_timeout() => "timeout".to_string(),
+            Err(_) => "error".to_string(),
+        };
+        metrics::record_http_client_request(destination, &status, start.elapsed().as_secs_f64());
+
+        result
+    }
+}
+
+// -----------------------------------------------------------------------------
+// HELPERS
+// -----------------------------------------------------------------------------
+
+/// Statuses worth retrying: throttling and server-side failures
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// base * 2^attempt with ±20% jitter so retrying clients don't synchronize
+pub fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    let exp = base.saturating_mul(2u32.saturating_pow(attempt.min(16)));
+    let jitter = rand::thread_rng().gen_range(0.8..1.2);
+    exp.mul_f64(jitter).min(MAX_BACKOFF)
+}
+
+/// Retry-After in seconds, if the server sent one
+fn retry_after_header(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Absolute Unix epoch milliseconds `after` from now
+fn epoch_millis_after(after: Duration) -> u64 {
+    (SystemTime::now() + after)
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay_grows_with_jitter() {
+        let base = Duration::from_millis(100);
+        let first = backoff_delay(base, 0);
+        let third = backoff_delay(base, 2);
+
+        assert!(first >= Duration::from_millis(80) && first <= Duration::from_millis(120));
+        assert!(third >= Duration::from_millis(320) && third <= Duration::from_millis(480));
+        assert_eq!(backoff_delay(base, 30), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable_status(StatusCode::OK));
+    }
+}
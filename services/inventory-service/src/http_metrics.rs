@@ -0,0 +1,56 @@
+// =============================================================================
+// HTTP METRICS MODULE
+// =============================================================================
+// Records http_requests_total and http_request_duration_seconds for every
+// request, so handlers don't have to.
+//
+// LABELS:
+// - method:   GET, POST, ...
+// - endpoint: the route template (`/api/v1/inventory/:sku`), never the raw
+//             path, so one series covers every SKU
+// - status:   the status code actually sent, including errors raised by
+//             extractors and by the other middleware layers (403, 429, 504)
+//
+// LEARNING NOTES:
+// - Requests that match no route are labeled endpoint="unmatched"; labeling
+//   them by path would let any scanner create unbounded series
+// - The duration covers everything inside this layer, i.e. the handler and
+//   the layers added before it in main.rs
+// =============================================================================
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::time::Instant;
+
+use crate::metrics;
+
+/// Endpoint label of requests that matched no route
+const UNMATCHED_ENDPOINT: &str = "unmatched";
+
+// =============================================================================
+// MIDDLEWARE
+// =============================================================================
+/// Count and time every request by method, route template and status
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = request.method().clone();
+    let endpoint = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ENDPOINT.to_string());
+
+    let response = next.run(request).await;
+
+    metrics::record_http_request(
+        method.as_str(),
+        &endpoint,
+        response.status().as_u16(),
+        start.elapsed().as_secs_f64(),
+    );
+
+    response
+}
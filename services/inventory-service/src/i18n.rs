@@ -0,0 +1,159 @@
+// =============================================================================
+// I18N MODULE
+// =============================================================================
+// Localizes user-facing error messages based on the Accept-Language header.
+//
+// SUPPORTED LANGUAGES:
+// - English (en) - default
+// - Indonesian (id) - our warehouses are in Jakarta (JKT) and Surabaya (SBY)
+//
+// WHAT GETS TRANSLATED:
+// Only the human-readable `message` of error responses. The machine-readable
+// `error` code (NOT_FOUND, INSUFFICIENT_STOCK, ...) never changes, so clients
+// can keep matching on it. For non-English responses the original English
+// message is kept in `details` so nothing is lost when debugging.
+//
+// LEARNING NOTES:
+// - Accept-Language lists preferences with quality weights:
+//     Accept-Language: id-ID,id;q=0.9,en;q=0.8
+// - The chosen language lives in a task-local, like the request deadline,
+//   so AppError::into_response can read it without access to the request
+// =============================================================================
+
+use axum::{extract::Request, http::header::ACCEPT_LANGUAGE, middleware::Next, response::Response};
+
+// -----------------------------------------------------------------------------
+// LANGUAGES
+// -----------------------------------------------------------------------------
+/// Languages we have translations for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    En,
+    Id,
+}
+
+impl Lang {
+    /// BCP 47 tag, used for the Content-Language header
+    pub fn tag(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Id => "id",
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        // Only the primary subtag matters: "id-ID" → "id"
+        let primary = tag.split('-').next().unwrap_or("").trim().to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Some(Lang::En),
+            "id" | "in" => Some(Lang::Id), // "in" is the legacy code for Indonesian
+            _ => None,
+        }
+    }
+}
+
+/// Pick the best supported language from an Accept-Language header value.
+///
+/// Unsupported or malformed entries are skipped; falls back to English.
+pub fn negotiate(accept_language: &str) -> Lang {
+    let mut best: Option<(Lang, f32)> = None;
+
+    for entry in accept_language.split(',') {
+        let mut parts = entry.split(';');
+        let tag = parts.next().unwrap_or("").trim();
+        let quality = parts
+            .find_map(|p| p.trim().strip_prefix("q="))
+            .and_then(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        if let Some(lang) = Lang::from_tag(tag) {
+            if quality > 0.0 && best.is_none_or(|(_, q)| quality > q) {
+                best = Some((lang, quality));
+            }
+        }
+    }
+
+    best.map(|(lang, _)| lang).unwrap_or_default()
+}
+
+// -----------------------------------------------------------------------------
+// REQUEST LANGUAGE
+// -----------------------------------------------------------------------------
+tokio::task_local! {
+    static LANG: Lang;
+}
+
+/// Language negotiated for the current request (English outside requests)
+pub fn current() -> Lang {
+    LANG.try_with(|lang| *lang).unwrap_or_default()
+}
+
+/// Middleware: negotiate the language and make it available to error handling
+pub async fn negotiate_language(request: Request, next: Next) -> Response {
+    let lang = request
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .map(negotiate)
+        .unwrap_or_default();
+
+    LANG.scope(lang, next.run(request)).await
+}
+
+// =============================================================================
+// MESSAGE CATALOG
+// =============================================================================
+/// Localized message for an error code, `None` if there is no translation
+/// (callers then keep the English message).
+pub fn error_message(code: &str, lang: Lang) -> Option<&'static str> {
+    match lang {
+        Lang::En => None,
+        Lang::Id => Some(match code {
+            "NOT_FOUND" => "Data tidak ditemukan",
+            "BAD_REQUEST" => "Permintaan tidak valid",
+            "VALIDATION_FAILED" => "Beberapa isian permintaan tidak valid",
+            "INSUFFICIENT_STOCK" => "Stok tidak mencukupi",
+            "RESERVATION_LIMIT_EXCEEDED" => "Batas reservasi untuk produk ini terlampaui",
+            "FAIR_SHARE_EXCEEDED" => "Batas reservasi per pesanan atau per klien terlampaui",
+            "CONFLICT" => "Data sedang diubah bersamaan, silakan coba lagi",
+            "COMMITMENT_CONFLICT" => "Status transaksi stok tidak mengizinkan langkah ini",
+            "VERSION_MISMATCH" => "Data produk sudah diubah pihak lain, muat ulang lalu coba lagi",
+            "CATALOG_QUOTA_EXCEEDED" => "Kuota jumlah produk dalam katalog sudah penuh",
+            "SERVICE_UNAVAILABLE" => "Layanan sedang tidak tersedia, silakan coba lagi nanti",
+            "DEADLINE_EXCEEDED" => "Batas waktu permintaan terlampaui",
+            "READ_ONLY" => "Layanan sedang dalam mode hanya-baca",
+            "FEATURE_DISABLED" => "Fitur ini dinonaktifkan di lingkungan ini",
+            "RATE_LIMITED" => "Terlalu banyak permintaan, silakan coba lagi nanti",
+            "FAULT_INJECTED" => "Gangguan sengaja dari eksperimen chaos",
+            "DATABASE_ERROR" => "Terjadi kesalahan pada database",
+            "INTERNAL_ERROR" => "Terjadi kesalahan internal",
+            _ => return None,
+        }),
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_prefers_highest_quality() {
+        assert_eq!(negotiate("id-ID,id;q=0.9,en;q=0.8"), Lang::Id);
+        assert_eq!(negotiate("en;q=0.5, id;q=0.7"), Lang::Id);
+        assert_eq!(negotiate("fr-FR, en;q=0.3"), Lang::En);
+        assert_eq!(negotiate("fr-FR"), Lang::En);
+        assert_eq!(negotiate("id;q=0"), Lang::En);
+        assert_eq!(negotiate(""), Lang::En);
+    }
+
+    #[test]
+    fn test_error_codes_are_translated() {
+        assert_eq!(error_message("INSUFFICIENT_STOCK", Lang::Id), Some("Stok tidak mencukupi"));
+        assert_eq!(error_message("INSUFFICIENT_STOCK", Lang::En), None);
+        assert_eq!(error_message("UNKNOWN_CODE", Lang::Id), None);
+    }
+}
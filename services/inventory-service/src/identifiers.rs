@@ -0,0 +1,112 @@
+// =============================================================================
+// IDENTIFIERS MODULE
+// =============================================================================
+// Alternate identifiers of an item: vendor SKUs, legacy codes and GTINs
+// (barcodes), kept in the `item_identifiers` side table so any of them can
+// be resolved to the canonical SKU.
+//
+// ENDPOINTS:
+// - GET    /api/v1/inventory/lookup?id=<sku or alternate identifier>
+// - GET    /api/v1/inventory/:sku/identifiers
+// - POST   /api/v1/inventory/:sku/identifiers   {kind, identifier}
+// - DELETE /api/v1/inventory/:sku/identifiers/:identifier
+//
+// RULES:
+// - An identifier resolves to exactly one item, and never shadows a
+//   canonical SKU (the canonical SKU always wins in a lookup)
+// - GTINs must be 8, 12, 13 or 14 digits with a valid check digit, so a
+//   mistyped barcode is rejected instead of silently never matching
+// =============================================================================
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Longest identifier accepted (matches the column)
+const MAX_IDENTIFIER_LEN: usize = 100;
+
+/// Kind of alternate identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentifierKind {
+    /// The supplier's own SKU
+    VendorSku,
+    /// Code from a previous system
+    Legacy,
+    /// GTIN-8/12/13/14 (EAN, UPC barcodes)
+    Gtin,
+}
+
+impl IdentifierKind {
+    /// Name stored in the database
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::VendorSku => "vendor_sku",
+            Self::Legacy => "legacy",
+            Self::Gtin => "gtin",
+        }
+    }
+
+    /// Check an identifier of this kind
+    ///
+    /// # Returns
+    /// - `Err(reason)` if it is empty, too long, or an invalid GTIN
+    pub fn validate(self, identifier: &str) -> Result<(), String> {
+        if identifier.trim().is_empty() || identifier.trim() != identifier {
+            return Err("identifier must not be empty or padded with spaces".to_string());
+        }
+        if identifier.len() > MAX_IDENTIFIER_LEN {
+            return Err(format!("identifier is longer than {} characters", MAX_IDENTIFIER_LEN));
+        }
+        if self == Self::Gtin && !is_valid_gtin(identifier) {
+            return Err(format!("'{}' is not a valid GTIN", identifier));
+        }
+        Ok(())
+    }
+}
+
+/// GS1 check digit validation for GTIN-8, -12, -13 and -14
+fn is_valid_gtin(gtin: &str) -> bool {
+    if !matches!(gtin.len(), 8 | 12 | 13 | 14) || !gtin.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+
+    // Weights alternate 3, 1, 3, ... starting from the rightmost data digit
+    let digits: Vec<u32> = gtin.bytes().map(|b| u32::from(b - b'0')).collect();
+    let (check, data) = digits.split_last().expect("length checked above");
+    let sum: u32 = data
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d * 3 } else { *d })
+        .sum();
+
+    (10 - sum % 10) % 10 == *check
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gtin_check_digit() {
+        assert!(is_valid_gtin("4006381333931")); // EAN-13
+        assert!(is_valid_gtin("036000291452")); // UPC-A
+        assert!(is_valid_gtin("96385074")); // EAN-8
+        assert!(is_valid_gtin("10036000291459")); // GTIN-14
+        assert!(!is_valid_gtin("4006381333932"));
+        assert!(!is_valid_gtin("40063813339"));
+        assert!(!is_valid_gtin("400638133393A"));
+    }
+
+    #[test]
+    fn test_validate_identifier() {
+        assert!(IdentifierKind::Legacy.validate("OLD-0042").is_ok());
+        assert!(IdentifierKind::VendorSku.validate("").is_err());
+        assert!(IdentifierKind::VendorSku.validate(" V-1").is_err());
+        assert!(IdentifierKind::Gtin.validate("4006381333931").is_ok());
+        assert!(IdentifierKind::Gtin.validate("OLD-0042").is_err());
+    }
+}
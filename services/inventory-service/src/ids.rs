@@ -0,0 +1,202 @@
+// =============================================================================
+// IDS MODULE
+// =============================================================================
+// Generates reservation and stock movement IDs in the configured format.
+//
+// FORMATS (ID_FORMAT):
+// - uuid (default)  random UUID v4, e.g. 0b7c9d2e-5f1a-4c3b-9e8d-7a6f5e4d3c2b
+// - ulid            26-char ULID, e.g. 01HMX3K8Q2ZJ7P4T9V6W5Y0RNA
+// - snowflake       64-bit integer as a decimal string, e.g. 123456789012345678
+//
+// ULIDs and Snowflake IDs start with the creation time, so sorting them as
+// strings (ULID) or numbers (Snowflake) sorts by time, and the time can be
+// read back from an ID found in a log line.
+//
+// SNOWFLAKE LAYOUT (63 bits used, always positive):
+//   41 bits  milliseconds since 2024-01-01T00:00:00Z (good for ~69 years)
+//   10 bits  node ID (SNOWFLAKE_NODE_ID, 0-1023; give each replica its own)
+//   12 bits  sequence within the millisecond
+//
+// LEARNING NOTES:
+// - Two replicas with the same node ID can hand out the same Snowflake ID;
+//   ULIDs rely on 80 random bits instead and need no coordination
+// - More than 4096 Snowflake IDs in one millisecond borrow the next
+//   millisecond, so IDs stay unique and increasing without sleeping
+// =============================================================================
+
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Custom epoch of Snowflake IDs (2024-01-01T00:00:00Z), in Unix ms
+const SNOWFLAKE_EPOCH_MS: u64 = 1_704_067_200_000;
+
+/// Largest SNOWFLAKE_NODE_ID (10 bits)
+pub const MAX_NODE_ID: u16 = 1023;
+
+/// Sequence numbers per millisecond (12 bits)
+const SEQUENCE_MASK: u64 = 0xfff;
+
+/// Crockford base32 alphabet used by ULIDs
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Format of generated IDs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdFormat {
+    #[default]
+    Uuid,
+    Ulid,
+    Snowflake,
+}
+
+impl IdFormat {
+    /// Parse an ID_FORMAT value
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "uuid" => Ok(Self::Uuid),
+            "ulid" => Ok(Self::Ulid),
+            "snowflake" => Ok(Self::Snowflake),
+            other => anyhow::bail!("ID_FORMAT must be uuid, ulid or snowflake, got '{}'", other),
+        }
+    }
+
+    /// Name used in logs
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Uuid => "uuid",
+            Self::Ulid => "ulid",
+            Self::Snowflake => "snowflake",
+        }
+    }
+}
+
+/// Last Snowflake timestamp and sequence handed out
+#[derive(Debug, Default)]
+struct SnowflakeClock {
+    last_ms: u64,
+    sequence: u64,
+}
+
+impl SnowflakeClock {
+    /// Timestamp and sequence of the next ID, never going backwards
+    fn advance(&mut self, now_ms: u64) -> (u64, u64) {
+        if now_ms > self.last_ms {
+            self.last_ms = now_ms;
+            self.sequence = 0;
+        } else {
+            // Same millisecond, or the wall clock stepped back
+            self.sequence = (self.sequence + 1) & SEQUENCE_MASK;
+            if self.sequence == 0 {
+                self.last_ms += 1;
+            }
+        }
+        (self.last_ms, self.sequence)
+    }
+}
+
+// =============================================================================
+// GENERATOR
+// =============================================================================
+/// Generator of reservation and movement IDs (cheap to clone)
+#[derive(Debug, Clone, Default)]
+pub struct IdGenerator {
+    format: IdFormat,
+    node_id: u16,
+    clock: Arc<Mutex<SnowflakeClock>>,
+}
+
+impl IdGenerator {
+    pub fn new(format: IdFormat, node_id: u16) -> Self {
+        Self {
+            format,
+            node_id: node_id.min(MAX_NODE_ID),
+            clock: Arc::default(),
+        }
+    }
+
+    /// A new ID
+    pub fn next_id(&self) -> String {
+        match self.format {
+            IdFormat::Uuid => Uuid::new_v4().to_string(),
+            IdFormat::Ulid => ulid(now_ms(), rand::random::<u128>()),
+            IdFormat::Snowflake => {
+                let (ms, sequence) = self
+                    .clock
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .advance(now_ms());
+                snowflake(ms, self.node_id, sequence).to_string()
+            }
+        }
+    }
+}
+
+/// Milliseconds since the Unix epoch
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis().max(0) as u64
+}
+
+/// Encode a ULID: 48-bit timestamp, then the low 80 bits of `random`
+fn ulid(ms: u64, random: u128) -> String {
+    let value = (u128::from(ms & 0xffff_ffff_ffff) << 80) | (random & ((1u128 << 80) - 1));
+
+    // 26 characters of 5 bits each; the first one only carries 3 bits
+    (0..26)
+        .rev()
+        .map(|i| CROCKFORD[((value >> (i * 5)) & 0x1f) as usize] as char)
+        .collect()
+}
+
+/// Pack a Snowflake ID
+fn snowflake(ms: u64, node_id: u16, sequence: u64) -> u64 {
+    let elapsed = ms.saturating_sub(SNOWFLAKE_EPOCH_MS) & ((1 << 41) - 1);
+    (elapsed << 22) | (u64::from(node_id) << 12) | (sequence & SEQUENCE_MASK)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ulid_encoding() {
+        assert_eq!(ulid(0, 0), "00000000000000000000000000");
+        assert_eq!(ulid(0xffff_ffff_ffff, u128::MAX), "7ZZZZZZZZZZZZZZZZZZZZZZZZZ");
+
+        // Later timestamps sort after earlier ones, whatever the random part
+        assert!(ulid(1_700_000_000_001, 0) > ulid(1_700_000_000_000, u128::MAX));
+    }
+
+    #[test]
+    fn test_snowflake_layout() {
+        let id = snowflake(SNOWFLAKE_EPOCH_MS + 5, 3, 7);
+        assert_eq!(id >> 22, 5);
+        assert_eq!((id >> 12) & 0x3ff, 3);
+        assert_eq!(id & 0xfff, 7);
+    }
+
+    #[test]
+    fn test_snowflake_clock_never_repeats() {
+        let mut clock = SnowflakeClock::default();
+        assert_eq!(clock.advance(1000), (1000, 0));
+        assert_eq!(clock.advance(1000), (1000, 1));
+        // Clock stepping back keeps counting from the last timestamp
+        assert_eq!(clock.advance(990), (1000, 2));
+
+        clock.sequence = SEQUENCE_MASK;
+        assert_eq!(clock.advance(1000), (1001, 0));
+    }
+
+    #[test]
+    fn test_generated_ids_increase() {
+        let ids = IdGenerator::new(IdFormat::Snowflake, 1);
+        let first: u64 = ids.next_id().parse().unwrap();
+        let second: u64 = ids.next_id().parse().unwrap();
+        assert!(second > first);
+
+        assert_eq!(IdGenerator::new(IdFormat::Ulid, 0).next_id().len(), 26);
+        assert!(IdFormat::parse("ksuid").is_err());
+    }
+}
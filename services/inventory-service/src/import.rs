@@ -0,0 +1,352 @@
+// =============================================================================
+// IMPORT MODULE
+// =============================================================================
+// Bulk load of the catalog from a CSV file, for seeding and for teams that
+// keep their stock list in a spreadsheet (POST /api/v1/inventory/import).
+//
+// FILE FORMAT (multipart/form-data, field "file"):
+//   sku,name,quantity,warehouse,threshold
+//   SKU-DOCK-001,USB-C Dock,20,JKT-1,5
+//   SKU-CABLE-002,HDMI Cable,150,,
+// - The header is required; columns may come in any order
+// - sku, name and quantity are required; warehouse and threshold may be
+//   left out or empty (new items get DEFAULT and 10, like POST
+//   /api/v1/inventory; existing items keep theirs)
+// - quantity is the SKU's total stock, as GET /api/v1/inventory/:sku
+//   reports it
+//
+// HOW:
+// - Rows are read and validated here; bad rows are reported, the rest are
+//   still imported
+// - Valid rows are upserted in batches of IMPORT_BATCH_SIZE, all in one
+//   transaction (see Database::import_items). New SKUs count against the
+//   catalog quota; past CATALOG_HARD_LIMIT they are rejected row by row.
+// - Quantity changes are recorded like adjustments (audit trail, movement
+//   ledger, reference "csv import")
+// - `?dry_run=true` reports what would happen and rolls everything back
+//
+// LEARNING NOTES:
+// - An import can't move an item to another home warehouse (that's
+//   bulk-update's job), nor set quantity below what is reserved plus
+//   what other warehouses hold; such rows are errors
+// - A SKU appearing twice is imported from its first row only
+// - The file is limited by axum's default body limit (2 MB), roughly
+//   30,000 typical rows; MAX_IMPORT_ROWS is lower still
+// =============================================================================
+
+use std::collections::HashMap;
+
+use crate::models::{CreateItemRequest, ImportRowResult};
+use crate::validation::{self, Validate};
+
+/// Most data rows accepted in one file
+pub const MAX_IMPORT_ROWS: usize = 10_000;
+
+/// Rows upserted per statement
+pub const IMPORT_BATCH_SIZE: usize = 500;
+
+/// Reference on the audit events and movements of an import
+pub const IMPORT_REFERENCE: &str = "csv import";
+
+/// Threshold given to new items when the row has none
+const DEFAULT_THRESHOLD: i32 = 10;
+
+/// Warehouse given to new items when the row has none
+const DEFAULT_WAREHOUSE: &str = "DEFAULT";
+
+/// Columns every file must have
+const REQUIRED_COLUMNS: [&str; 3] = ["sku", "name", "quantity"];
+
+// -----------------------------------------------------------------------------
+// PARSING
+// -----------------------------------------------------------------------------
+/// A valid data row
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    /// Line in the file (the header is line 1)
+    pub line: u64,
+    pub sku: String,
+    pub name: String,
+    pub quantity: i32,
+    pub warehouse: Option<String>,
+    pub threshold: Option<i32>,
+}
+
+impl ImportRow {
+    /// The row as a create request, with defaults for the empty columns
+    pub fn to_create_request(&self) -> CreateItemRequest {
+        CreateItemRequest {
+            sku: self.sku.clone(),
+            name: self.name.clone(),
+            quantity: self.quantity,
+            warehouse: self.warehouse.clone().unwrap_or_else(|| DEFAULT_WAREHOUSE.to_string()),
+            low_stock_threshold: self.threshold.unwrap_or(DEFAULT_THRESHOLD),
+        }
+    }
+}
+
+/// Read a CSV file into valid rows and the errors of the invalid ones
+///
+/// # Returns
+/// - `Err(reason)` when the file as a whole is unusable (no or bad header,
+///   too many rows)
+pub fn parse_csv(data: &[u8]) -> Result<(Vec<ImportRow>, Vec<ImportRowResult>), String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(data);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Unreadable header: {}", e))?
+        .clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(name))
+    };
+    if let Some(missing) = REQUIRED_COLUMNS.iter().find(|name| column(name).is_none()) {
+        return Err(format!(
+            "Header must name the columns sku, name and quantity (missing '{}')",
+            missing
+        ));
+    }
+    let columns = Columns {
+        sku: column("sku").unwrap_or_default(),
+        name: column("name").unwrap_or_default(),
+        quantity: column("quantity").unwrap_or_default(),
+        warehouse: column("warehouse"),
+        threshold: column("threshold"),
+    };
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    let mut first_line: HashMap<String, u64> = HashMap::new();
+
+    for (index, record) in reader.records().enumerate() {
+        if index == MAX_IMPORT_ROWS {
+            return Err(format!("More than {} rows; split the file", MAX_IMPORT_ROWS));
+        }
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map_or(index as u64 + 2, |p| p.line());
+                errors.push(ImportRowResult::error(line, "", format!("Unreadable row: {}", e)));
+                continue;
+            }
+        };
+        let line = record.position().map_or(index as u64 + 2, |p| p.line());
+        let sku = record.get(columns.sku).unwrap_or_default();
+
+        match columns.read(&record, line) {
+            Ok(row) => {
+                if let Some(first) = first_line.get(&row.sku) {
+                    let error = format!("Duplicate SKU, already on line {}", first);
+                    errors.push(ImportRowResult::error(line, sku, error));
+                } else {
+                    first_line.insert(row.sku.clone(), line);
+                    rows.push(row);
+                }
+            }
+            Err(error) => errors.push(ImportRowResult::error(line, sku, error)),
+        }
+    }
+
+    Ok((rows, errors))
+}
+
+/// Positions of the columns in the header
+struct Columns {
+    sku: usize,
+    name: usize,
+    quantity: usize,
+    warehouse: Option<usize>,
+    threshold: Option<usize>,
+}
+
+impl Columns {
+    /// Read and validate one record
+    fn read(&self, record: &csv::StringRecord, line: u64) -> Result<ImportRow, String> {
+        // Missing trailing cells read as empty, like in a spreadsheet export
+        let cell = |index: usize| record.get(index).unwrap_or_default();
+        let optional = |index: Option<usize>| index.map(cell).filter(|value| !value.is_empty());
+
+        let quantity = cell(self.quantity)
+            .parse()
+            .map_err(|_| format!("quantity must be a whole number, got '{}'", cell(self.quantity)))?;
+        let threshold = optional(self.threshold)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("threshold must be a whole number, got '{}'", value))
+            })
+            .transpose()?;
+
+        let row = ImportRow {
+            line,
+            sku: cell(self.sku).to_string(),
+            name: cell(self.name).to_string(),
+            quantity,
+            warehouse: optional(self.warehouse).map(str::to_string),
+            threshold,
+        };
+        row.to_create_request()
+            .validate()
+            .map_err(|errors| validation::summary(&errors))?;
+
+        Ok(row)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// PLANNING
+// -----------------------------------------------------------------------------
+/// An item an import row may update, as it is before the import
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ImportTarget {
+    pub sku: String,
+    pub name: String,
+    pub quantity: i32,
+    /// Units reserved at the home warehouse
+    pub reserved: i32,
+    pub warehouse: String,
+    pub low_stock_threshold: i32,
+    /// Units held at warehouses other than the home one
+    pub elsewhere: i32,
+}
+
+/// What an import does with a valid row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportAction {
+    Create,
+    /// Change the item; `quantity_delta` may be 0 for metadata changes
+    Update { quantity_delta: i32 },
+    Unchanged,
+}
+
+/// Decide what to do with a row, given the item it targets (if any)
+///
+/// # Returns
+/// - `Err(reason)` when the row can't be applied to the existing item
+pub fn plan(row: &ImportRow, existing: Option<&ImportTarget>) -> Result<ImportAction, String> {
+    let Some(item) = existing else {
+        return Ok(ImportAction::Create);
+    };
+
+    if let Some(warehouse) = row.warehouse.as_deref().filter(|w| *w != item.warehouse) {
+        return Err(format!(
+            "Item's home warehouse is {}, not {}; move it with bulk-update",
+            item.warehouse, warehouse
+        ));
+    }
+    let minimum = item.reserved + item.elsewhere;
+    if row.quantity < minimum {
+        return Err(format!(
+            "quantity {} is below the {} units reserved or held at other warehouses",
+            row.quantity, minimum
+        ));
+    }
+
+    let changed = row.name != item.name
+        || row.quantity != item.quantity
+        || row.threshold.is_some_and(|threshold| threshold != item.low_stock_threshold);
+    if !changed {
+        return Ok(ImportAction::Unchanged);
+    }
+
+    Ok(ImportAction::Update {
+        quantity_delta: row.quantity - item.quantity,
+    })
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> ImportTarget {
+        ImportTarget {
+            sku: "SKU-1".to_string(),
+            name: "Dock".to_string(),
+            quantity: 20,
+            reserved: 5,
+            warehouse: "JKT-1".to_string(),
+            low_stock_threshold: 10,
+            elsewhere: 3,
+        }
+    }
+
+    fn row(quantity: i32) -> ImportRow {
+        ImportRow {
+            line: 2,
+            sku: "SKU-1".to_string(),
+            name: "Dock".to_string(),
+            quantity,
+            warehouse: None,
+            threshold: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_csv_rows_and_errors() {
+        let data = b"SKU,Name,Quantity,Warehouse,Threshold\n\
+            SKU-1, Dock ,20,JKT-1,5\n\
+            SKU-2,Cable,150,,\n\
+            SKU-3,Broken,lots,,\n\
+            SKU-1,Dock again,1,,\n\
+            ,No SKU,1,,\n\
+            SKU-4,Short,7\n";
+        let (rows, errors) = parse_csv(data).unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].name, "Dock");
+        assert_eq!(rows[0].warehouse.as_deref(), Some("JKT-1"));
+        assert_eq!(rows[0].threshold, Some(5));
+        assert_eq!(rows[1].warehouse, None);
+        assert_eq!(rows[1].threshold, None);
+        assert_eq!(rows[2].sku, "SKU-4");
+        assert_eq!(rows[2].line, 7);
+
+        let lines: Vec<u64> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![4, 5, 6]);
+        assert!(errors[1].error.as_deref().unwrap().contains("line 2"));
+    }
+
+    #[test]
+    fn test_parse_csv_rejects_bad_files() {
+        assert!(parse_csv(b"sku,quantity\nSKU-1,5\n").is_err());
+        assert!(parse_csv(b"").is_err());
+
+        let mut data = String::from("sku,name,quantity\n");
+        for i in 0..=MAX_IMPORT_ROWS {
+            data.push_str(&format!("SKU-{},Item,1\n", i));
+        }
+        assert!(parse_csv(data.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_plan() {
+        let item = target();
+        assert_eq!(plan(&row(20), None), Ok(ImportAction::Create));
+        assert_eq!(plan(&row(20), Some(&item)), Ok(ImportAction::Unchanged));
+        assert_eq!(
+            plan(&row(25), Some(&item)),
+            Ok(ImportAction::Update { quantity_delta: 5 })
+        );
+
+        let renamed = ImportRow { name: "USB-C Dock".to_string(), ..row(20) };
+        assert_eq!(
+            plan(&renamed, Some(&item)),
+            Ok(ImportAction::Update { quantity_delta: 0 })
+        );
+
+        // 5 reserved + 3 elsewhere
+        assert!(plan(&row(8), Some(&item)).is_ok());
+        assert!(plan(&row(7), Some(&item)).is_err());
+
+        let moved = ImportRow { warehouse: Some("SBY-1".to_string()), ..row(20) };
+        assert!(plan(&moved, Some(&item)).is_err());
+    }
+}
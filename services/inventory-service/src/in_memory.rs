@@ -0,0 +1,505 @@
+// =============================================================================
+// IN-MEMORY REPOSITORY MODULE
+// =============================================================================
+// An InventoryRepository (see repository.rs) that keeps items in a map in
+// the process: for unit tests of code built on the trait, and for
+// `inventory-service --in-memory`, which serves the core item endpoints
+// without Postgres.
+//
+// HOW IT DIFFERS FROM POSTGRES:
+// - Nothing survives a restart
+// - No audit trail, stock movements or item revisions are written
+// - One home warehouse per item: no per-warehouse stock or thresholds, and
+//   any warehouse code is accepted (there is no registry to check)
+// - Reservations don't expire and know no soft holds or priorities; a
+//   repeated reserve of an order still holding the SKU is replayed, like
+//   in Postgres
+// - An adjustment that would leave less stock than is reserved is refused
+//   as insufficient stock (Postgres refuses it with a constraint error)
+//
+// --in-memory MODE:
+// Redis is still needed (caches, live feed). Endpoints that aren't behind
+// the repository keep using Postgres at DATABASE_URL, which isn't connected
+// to until they run, so they fail while it's down. Background jobs that
+// poll Postgres (low-stock evaluation, event and webhook dispatch, audit
+// shipping, reservation and commitment sweeps, stockout tracking, stock
+// valuation, usage rollups) aren't started.
+//
+// LEARNING NOTES:
+// - One std Mutex guards everything; no lock is held across an .await,
+//   so a blocking lock is fine (and cheaper than an async one)
+// - Errors are the ones db.rs uses, so handlers answer with the same
+//   statuses as with Postgres
+// =============================================================================
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use crate::db::DbError;
+use crate::error::AppError;
+use crate::ids::IdGenerator;
+use crate::models::{
+    AdjustStockRequest, CreateItemRejection, CreateItemRequest, InventoryFilter, InventoryItem, NewAuditEvent,
+    ReleaseStockRequest, ReservationResponse, ReserveStockRequest, SortBy, SortOrder, UpdateItemRequest,
+};
+use crate::repository::InventoryRepository;
+use crate::reservation_expiry;
+use crate::versioning;
+
+/// Items and reservations, kept in memory
+#[derive(Debug, Default)]
+pub struct InMemoryRepository {
+    ids: IdGenerator,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Items by SKU
+    items: BTreeMap<String, InventoryItem>,
+    /// Latest reservation of each (order, SKU) and how much of it is held
+    reservations: HashMap<(String, String), Held>,
+}
+
+#[derive(Debug, Clone)]
+struct Held {
+    reservation: ReservationResponse,
+    holding: i32,
+    client: Option<String>,
+}
+
+impl InMemoryRepository {
+    pub fn new(ids: IdGenerator) -> Self {
+        Self {
+            ids,
+            state: Mutex::default(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("in-memory repository lock poisoned")
+    }
+}
+
+/// Record a change to an item
+fn touch(item: &mut InventoryItem) {
+    item.version += 1;
+    item.updated_at = Utc::now();
+    item.revalue();
+}
+
+/// Whether an item passes a list filter
+fn matches(item: &InventoryItem, filter: &InventoryFilter<'_>) -> bool {
+    filter.warehouse.is_none_or(|w| item.warehouse == w)
+        && filter.sku_prefix.is_none_or(|p| item.sku.starts_with(p))
+        && filter
+            .name_contains
+            .is_none_or(|n| item.name.to_lowercase().contains(&n.to_lowercase()))
+        && (!filter.low_stock_only || item.is_low_stock())
+        && filter.attributes.is_none_or(|wanted| {
+            wanted.as_object().is_some_and(|wanted| {
+                wanted
+                    .iter()
+                    .all(|(name, value)| item.attributes.get(name) == Some(value))
+            })
+        })
+}
+
+/// List order of two items; ties are broken by SKU, as in db.rs
+fn compare(a: &InventoryItem, b: &InventoryItem, sort_by: SortBy, order: SortOrder) -> Ordering {
+    let primary = match sort_by {
+        SortBy::Sku => Ordering::Equal,
+        SortBy::Name => a.name.cmp(&b.name),
+        SortBy::Quantity => a.quantity.cmp(&b.quantity),
+        SortBy::Available => a.available().cmp(&b.available()),
+        SortBy::Warehouse => a.warehouse.cmp(&b.warehouse),
+        SortBy::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+    };
+    let primary = match order {
+        SortOrder::Asc => primary,
+        SortOrder::Desc => primary.reverse(),
+    };
+    let sku = a.sku.cmp(&b.sku);
+    primary.then(if sort_by == SortBy::Sku && order == SortOrder::Desc {
+        sku.reverse()
+    } else {
+        sku
+    })
+}
+
+#[async_trait]
+impl InventoryRepository for InMemoryRepository {
+    async fn list_items(
+        &self,
+        page: i32,
+        per_page: i32,
+        filter: &InventoryFilter<'_>,
+        sort_by: SortBy,
+        order: SortOrder,
+    ) -> Result<(Vec<InventoryItem>, i64)> {
+        let state = self.lock();
+        let mut items: Vec<&InventoryItem> =
+            state.items.values().filter(|item| matches(item, filter)).collect();
+        items.sort_by(|a, b| compare(a, b, sort_by, order));
+
+        let offset = ((page.max(1) - 1) * per_page).max(0) as usize;
+        let page_items = items
+            .iter()
+            .skip(offset)
+            .take(per_page.max(0) as usize)
+            .map(|item| (*item).clone())
+            .collect();
+        Ok((page_items, items.len() as i64))
+    }
+
+    async fn count_items(&self) -> Result<i64> {
+        Ok(self.lock().items.len() as i64)
+    }
+
+    async fn get_by_sku(&self, sku: &str) -> Result<Option<InventoryItem>> {
+        Ok(self.lock().items.get(sku).cloned())
+    }
+
+    async fn create_item(
+        &self,
+        req: &CreateItemRequest,
+        hard_limit: Option<i64>,
+    ) -> Result<std::result::Result<(InventoryItem, i64), CreateItemRejection>> {
+        let mut state = self.lock();
+        let skus = state.items.len() as i64;
+        if hard_limit.is_some_and(|limit| skus >= limit) {
+            return Ok(Err(CreateItemRejection::QuotaExceeded { skus }));
+        }
+        if state.items.contains_key(&req.sku) {
+            return Ok(Err(CreateItemRejection::DuplicateSku));
+        }
+
+        let now = Utc::now();
+        let item = InventoryItem {
+            id: uuid::Uuid::new_v4(),
+            sku: req.sku.clone(),
+            name: req.name.clone(),
+            quantity: req.quantity,
+            reserved: 0,
+            warehouse: req.warehouse.clone(),
+            low_stock_threshold: req.low_stock_threshold,
+            max_per_order: None,
+            max_reserved_pct: None,
+            threshold_manual: false,
+            version: 1,
+            attributes: serde_json::Value::Object(serde_json::Map::new()),
+            image_url: None,
+            spec_url: None,
+            unit_price: None,
+            unit_cost: None,
+            stock_value: None,
+            created_at: now,
+            updated_at: now,
+        };
+        state.items.insert(item.sku.clone(), item.clone());
+        Ok(Ok((item, skus + 1)))
+    }
+
+    async fn update_item(
+        &self,
+        sku: &str,
+        req: &UpdateItemRequest,
+        expected_version: Option<i64>,
+    ) -> Result<Option<InventoryItem>> {
+        let mut state = self.lock();
+        let Some(item) = state.items.get_mut(sku) else {
+            return Ok(None);
+        };
+        versioning::check(expected_version, item.version)?;
+
+        if let Some(name) = &req.name {
+            item.name = name.clone();
+        }
+        if let Some(warehouse) = &req.warehouse {
+            item.warehouse = warehouse.clone();
+        }
+        if let Some(threshold) = req.low_stock_threshold {
+            item.low_stock_threshold = threshold;
+            item.threshold_manual = true;
+        }
+        touch(item);
+        Ok(Some(item.clone()))
+    }
+
+    async fn adjust_stock(&self, req: &AdjustStockRequest, dry_run: bool) -> Result<InventoryItem> {
+        let mut state = self.lock();
+        let item = state
+            .items
+            .get_mut(&req.sku)
+            .ok_or_else(|| DbError::NotFound(req.sku.clone()))?;
+        versioning::check(req.expected_version, item.version)?;
+
+        let quantity = item.quantity.saturating_add(req.delta).max(0);
+        if quantity < item.reserved {
+            return Err(DbError::InsufficientStock {
+                available: item.available(),
+                requested: -req.delta,
+            }
+            .into());
+        }
+
+        if dry_run {
+            let mut preview = item.clone();
+            preview.quantity = quantity;
+            touch(&mut preview);
+            return Ok(preview);
+        }
+        item.quantity = quantity;
+        touch(item);
+        Ok(item.clone())
+    }
+
+    async fn reserve_stock(&self, req: &ReserveStockRequest) -> Result<ReservationResponse> {
+        let mut state = self.lock();
+        let key = (req.order_id.clone(), req.sku.clone());
+
+        // A repeat of a reservation the order still holds gets it back
+        if let Some(held) = state.reservations.get(&key).filter(|held| held.holding > 0) {
+            if held.reservation.quantity != req.quantity {
+                return Err(DbError::Conflict(format!(
+                    "Order {} already reserved {} of {}; release it before reserving a different quantity",
+                    req.order_id, held.reservation.quantity, req.sku
+                ))
+                .into());
+            }
+            return Ok(ReservationResponse {
+                replayed: true,
+                ..held.reservation.clone()
+            });
+        }
+
+        if let Some(quota) = &req.quota {
+            let held: i64 = state
+                .reservations
+                .iter()
+                .filter(|((_, sku), held)| *sku == req.sku && held.client.as_ref() == Some(&quota.client))
+                .map(|(_, held)| i64::from(held.holding))
+                .sum();
+            if let Err(violation) = quota.check(held, req.quantity) {
+                return Err(AppError::FairShareExceeded(violation).into());
+            }
+        }
+
+        let item = state
+            .items
+            .get_mut(&req.sku)
+            .ok_or_else(|| DbError::NotFound(req.sku.clone()))?;
+        if let Err(violation) = item.check_reservation_policy(req.quantity) {
+            return Err(AppError::ReservationLimit(violation).into());
+        }
+        if item.available() < req.quantity {
+            return Err(DbError::InsufficientStock {
+                available: item.available(),
+                requested: req.quantity,
+            }
+            .into());
+        }
+        item.reserved += req.quantity;
+        touch(item);
+
+        let created_at = Utc::now();
+        let reservation = ReservationResponse {
+            reservation_id: self.ids.next_id(),
+            sku: req.sku.clone(),
+            quantity: req.quantity,
+            created_at,
+            expires_at: Some(created_at + reservation_expiry::reservation_ttl()),
+            bumped: Vec::new(),
+            replayed: false,
+        };
+        state.reservations.insert(
+            key,
+            Held {
+                reservation: reservation.clone(),
+                holding: req.quantity,
+                client: req.quota.as_ref().map(|quota| quota.client.clone()),
+            },
+        );
+        Ok(reservation)
+    }
+
+    async fn release_stock(&self, req: &ReleaseStockRequest) -> Result<()> {
+        let mut state = self.lock();
+        let item = state
+            .items
+            .get_mut(&req.sku)
+            .filter(|item| item.reserved >= req.quantity)
+            .ok_or_else(|| {
+                anyhow::anyhow!("Failed to release stock. SKU not found or insufficient reserved quantity.")
+            })?;
+        item.reserved -= req.quantity;
+        touch(item);
+
+        if let Some(held) = state.reservations.get_mut(&(req.order_id.clone(), req.sku.clone())) {
+            held.holding = (held.holding - req.quantity).max(0);
+        }
+        Ok(())
+    }
+
+    /// Not kept (see the module notes)
+    async fn record_audit_event(&self, _event: &NewAuditEvent<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    /// No warehouse registry in memory: any code will do
+    async fn warehouse_exists(&self, _code: &str) -> Result<bool> {
+        Ok(true)
+    }
+
+    async fn health_check(&self) -> bool {
+        true
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::fairness::ClientQuota;
+
+    fn create(sku: &str, name: &str, quantity: i32) -> CreateItemRequest {
+        CreateItemRequest {
+            sku: sku.to_string(),
+            name: name.to_string(),
+            quantity,
+            warehouse: "JKT-1".to_string(),
+            low_stock_threshold: 10,
+        }
+    }
+
+    fn reserve(sku: &str, quantity: i32, order_id: &str) -> ReserveStockRequest {
+        ReserveStockRequest {
+            sku: sku.to_string(),
+            quantity,
+            order_id: order_id.to_string(),
+            channel: Default::default(),
+            hold: Default::default(),
+            priority: 0,
+            quota: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_list_and_update() {
+        let repo: Arc<dyn InventoryRepository> = Arc::new(InMemoryRepository::default());
+        repo.create_item(&create("SKU-B", "Mouse", 50), None).await.unwrap().unwrap();
+        repo.create_item(&create("SKU-A", "USB Dock", 5), None).await.unwrap().unwrap();
+        let (_, skus) = repo.create_item(&create("SKU-C", "Dock stand", 20), None).await.unwrap().unwrap();
+        assert_eq!(skus, 3);
+
+        assert_eq!(
+            repo.create_item(&create("SKU-A", "Again", 1), None).await.unwrap().unwrap_err(),
+            CreateItemRejection::DuplicateSku
+        );
+        assert_eq!(
+            repo.create_item(&create("SKU-D", "Full", 1), Some(3)).await.unwrap().unwrap_err(),
+            CreateItemRejection::QuotaExceeded { skus: 3 }
+        );
+
+        let filter = InventoryFilter {
+            name_contains: Some("dock"),
+            ..Default::default()
+        };
+        let (items, total) = repo
+            .list_items(1, 1, &filter, SortBy::Quantity, SortOrder::Desc)
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(items[0].sku, "SKU-C");
+
+        let low = InventoryFilter {
+            low_stock_only: true,
+            ..Default::default()
+        };
+        let (items, _) = repo.list_items(1, 20, &low, SortBy::Sku, SortOrder::Asc).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].sku, "SKU-A");
+
+        let update = UpdateItemRequest {
+            name: Some("USB-C Dock".to_string()),
+            ..Default::default()
+        };
+        let item = repo.update_item("SKU-A", &update, Some(1)).await.unwrap().unwrap();
+        assert_eq!((item.name.as_str(), item.version), ("USB-C Dock", 2));
+        assert!(repo.update_item("SKU-A", &update, Some(1)).await.is_err());
+        assert!(repo.update_item("NOPE", &update, None).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reserve_release_and_adjust() {
+        let repo = InMemoryRepository::default();
+        repo.create_item(&create("SKU-1", "Widget", 10), None).await.unwrap().unwrap();
+
+        let first = repo.reserve_stock(&reserve("SKU-1", 4, "ORD-1")).await.unwrap();
+        assert!(!first.replayed);
+        let again = repo.reserve_stock(&reserve("SKU-1", 4, "ORD-1")).await.unwrap();
+        assert!(again.replayed);
+        assert_eq!(again.reservation_id, first.reservation_id);
+        assert_eq!(repo.get_by_sku("SKU-1").await.unwrap().unwrap().reserved, 4);
+
+        let short = repo.reserve_stock(&reserve("SKU-1", 7, "ORD-2")).await.unwrap_err();
+        assert!(matches!(
+            short.downcast_ref::<DbError>(),
+            Some(DbError::InsufficientStock { available: 6, requested: 7 })
+        ));
+
+        // Can't drop below what is reserved
+        let adjust = |delta| AdjustStockRequest {
+            sku: "SKU-1".to_string(),
+            delta,
+            reason: "count".to_string(),
+            expected_version: None,
+        };
+        assert!(repo.adjust_stock(&adjust(-7), false).await.is_err());
+        assert_eq!(repo.adjust_stock(&adjust(-6), true).await.unwrap().quantity, 4);
+        assert_eq!(repo.get_by_sku("SKU-1").await.unwrap().unwrap().quantity, 10);
+
+        let release = ReleaseStockRequest {
+            sku: "SKU-1".to_string(),
+            quantity: 4,
+            order_id: "ORD-1".to_string(),
+        };
+        repo.release_stock(&release).await.unwrap();
+        assert!(repo.release_stock(&release).await.is_err());
+
+        // Released: the order may reserve again, for a fresh reservation
+        let next = repo.reserve_stock(&reserve("SKU-1", 2, "ORD-1")).await.unwrap();
+        assert!(!next.replayed);
+        assert_ne!(next.reservation_id, first.reservation_id);
+    }
+
+    #[tokio::test]
+    async fn test_client_quota() {
+        let repo = InMemoryRepository::default();
+        repo.create_item(&create("SKU-1", "Widget", 100), None).await.unwrap().unwrap();
+        let quota = |client: &str| {
+            Some(ClientQuota {
+                client: client.to_string(),
+                max_units: 10,
+            })
+        };
+
+        let first = ReserveStockRequest { quota: quota("key:a"), ..reserve("SKU-1", 6, "ORD-1") };
+        repo.reserve_stock(&first).await.unwrap();
+
+        // Another order of the same client counts against the same cap
+        let second = ReserveStockRequest { quota: quota("key:a"), ..reserve("SKU-1", 5, "ORD-2") };
+        let over = repo.reserve_stock(&second).await.unwrap_err();
+        assert!(matches!(over.downcast_ref::<AppError>(), Some(AppError::FairShareExceeded(_))));
+
+        let other = ReserveStockRequest { quota: quota("key:b"), ..reserve("SKU-1", 5, "ORD-2") };
+        repo.reserve_stock(&other).await.unwrap();
+    }
+}
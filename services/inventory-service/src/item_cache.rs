@@ -0,0 +1,437 @@
+// =============================================================================
+// ITEM CACHE MODULE
+// =============================================================================
+// Per-SKU Redis cache (inventory:item:<sku>) behind GET /api/v1/inventory/:sku,
+// and the one place that reads, writes and drops its entries.
+//
+// WRITE-THROUGH:
+// Stock adjustments and reservations change the hottest SKUs. Instead of
+// dropping their entry (so the next read misses and queries Postgres), they
+// store the item as written: `write_through` when the write returned it,
+// `refresh` (one primary read) when it didn't. Other writes invalidate.
+// An entry is only replaced by the same or a newer `version`, so of two
+// concurrent writes the older one can't land last.
+//
+// REDIS IS OPTIONAL:
+// Postgres is the source of truth; Redis only saves reads. Every helper here
+// swallows Redis errors after counting them, so when Redis is down the
+// service keeps answering from Postgres (DB-only mode): reads are slower,
+// writes still succeed, and /ready reports "degraded" instead of failing.
+//
+// NEGATIVE CACHING:
+// A SKU that doesn't exist is cached too, as MISSING in its entry, for
+// ITEM_CACHE_MISSING_TTL_SECS (default 30, 0 = off): bots probing random
+// SKUs then get their 404 from Redis instead of Postgres. The entry is the
+// SKU's own, so creating the item (and every other write) drops it.
+//
+// METRICS:
+// - cache_hits_total{cache}               entry found
+// - cache_negative_hits_total{cache}      entry says the SKU doesn't exist
+// - cache_misses_total{cache}             entry absent (or unreadable)
+// - cache_errors_total{cache, operation}  Redis call failed
+// - cache_fills_coalesced_total{cache, scope}  miss served by another fill
+// `cache` is "item" here and "list" for list_cache.rs.
+//
+// STAMPEDE PROTECTION:
+// When a hot SKU's entry expires, every request for it misses at once.
+// `fill` makes sure only one of them queries Postgres:
+// - within this process, concurrent misses share one fetch (single_flight.rs)
+// - with ITEM_CACHE_FILL_LOCK=true, replicas also take a short Redis lock
+//   (inventory:fill-lock:<sku>); the others poll the cache for the entry the
+//   lock holder writes, and read Postgres themselves if it doesn't show up
+//   within FILL_WAIT
+//
+// LEARNING NOTES:
+// - An invalidation that fails leaves a stale entry for at most
+//   ITEM_CACHE_TTL_SECS; that's the price of not failing the write
+// - The ConnectionManager reconnects on its own, so the cache comes back
+//   without a restart once Redis does. It needs one successful connection
+//   to be created, though, so startup waits for Redis (startup_probe.rs).
+// - Errors are logged at debug level only: with Redis down every request
+//   would log one, cache_errors_total is the signal to alert on
+// - A SKU created while a miss for it is being filled, or inserted behind
+//   the service's back, can read as missing until its entry expires
+// - Entries have a prefix of their own (KEY_PREFIX), so counting them is a
+//   SCAN of that prefix and nothing else stored under inventory: needs to
+//   be known here. Entries written by versions that used inventory:<sku>
+//   are no longer read and expire within ITEM_CACHE_TTL_SECS.
+// =============================================================================
+
+use redis::aio::ConnectionManager;
+use redis::Script;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::deadline;
+use crate::metrics;
+use crate::models::InventoryItem;
+use crate::repository::InventoryRepository;
+
+/// How long cached items live
+pub const ITEM_CACHE_TTL_SECS: u64 = 300;
+
+/// Entry of a SKU that doesn't exist. Not JSON, so write-through (and
+/// anything else expecting an item) replaces it.
+const MISSING: &str = "missing";
+
+/// Label of this cache in the cache_* metrics
+const CACHE: &str = "item";
+
+/// How long a fill lock is held at most (a crashed holder frees it)
+const FILL_LOCK_TTL_MS: u64 = 2000;
+
+/// How long a miss waits for another replica's fill
+const FILL_WAIT: Duration = Duration::from_millis(500);
+
+/// How often the cache is checked while waiting
+const FILL_POLL: Duration = Duration::from_millis(25);
+
+/// Drops a fill lock only if it is still ours (it may have expired and been
+/// taken by another replica)
+const UNLOCK_LUA: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+  return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Stores an item unless the entry holds a newer version of it
+/// KEYS[1] = entry, ARGV[1] = version, ARGV[2] = item JSON, ARGV[3] = TTL
+///
+/// `script_replaces` in the tests follows it step by step; change both.
+const WRITE_THROUGH_LUA: &str = r#"
+local cached = redis.call('GET', KEYS[1])
+if cached then
+  local ok, item = pcall(cjson.decode, cached)
+  if ok and type(item) == 'table' and type(item.version) == 'number'
+      and item.version > tonumber(ARGV[1]) then
+    return 0
+  end
+end
+redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[3])
+return 1
+"#;
+
+/// Outcome of a fill: the item, `None` for an unknown SKU, or the database
+/// error as text (shared by every caller waiting on the fill)
+pub type Fill = Result<Option<InventoryItem>, String>;
+
+/// What the cache knows about a SKU
+#[derive(Debug)]
+pub enum Lookup {
+    Item(Box<InventoryItem>),
+    /// The SKU was looked up recently and doesn't exist
+    Missing,
+    /// Nothing cached (or Redis unavailable): ask Postgres
+    Miss,
+}
+
+/// Read an entry; unreadable ones (written by an older version) are a miss
+fn decode(json: &str) -> Lookup {
+    if json == MISSING {
+        return Lookup::Missing;
+    }
+    serde_json::from_str(json).map_or(Lookup::Miss, |item| Lookup::Item(Box::new(item)))
+}
+
+/// Prefix of every entry key, and of nothing else
+pub const KEY_PREFIX: &str = "inventory:item:";
+
+/// Redis key of a SKU's entry
+pub fn key(sku: &str) -> String {
+    format!("{}{}", KEY_PREFIX, sku)
+}
+
+/// Number of cached items (found and not-found entries).
+///
+/// SCANs the KEY_PREFIX keys, so it takes time in proportion to the whole
+/// keyspace; meant for /debug/vars, not for the request path.
+pub async fn count(redis: &ConnectionManager) -> redis::RedisResult<u64> {
+    let mut conn = redis.clone();
+    let mut keys = redis::cmd("SCAN")
+        .cursor_arg(0)
+        .arg("MATCH")
+        .arg(format!("{}*", KEY_PREFIX))
+        .arg("COUNT")
+        .arg(1000)
+        .clone()
+        .iter_async::<String>(&mut conn)
+        .await?;
+    let mut entries = 0;
+    while keys.next_item().await.is_some() {
+        entries += 1;
+    }
+    Ok(entries)
+}
+
+/// Cached item or not-found; a miss when Redis is unavailable
+pub async fn get(redis: &ConnectionManager, sku: &str) -> Lookup {
+    let start = Instant::now();
+    let result: redis::RedisResult<Option<String>> = redis::cmd("GET")
+        .arg(key(sku))
+        .query_async(&mut deadline::redis(redis))
+        .await;
+    metrics::record_redis_operation("get", start.elapsed().as_secs_f64());
+
+    let lookup = match result {
+        Ok(Some(json)) => decode(&json),
+        Ok(None) => Lookup::Miss,
+        Err(e) => {
+            record_error("get", &e);
+            return Lookup::Miss;
+        }
+    };
+    match &lookup {
+        Lookup::Item(_) => metrics::record_cache_lookup(CACHE, true),
+        Lookup::Missing => metrics::record_cache_negative_hit(CACHE),
+        // An unreadable entry is replaced on the way out
+        Lookup::Miss => metrics::record_cache_lookup(CACHE, false),
+    }
+    lookup
+}
+
+/// Cache an item for ITEM_CACHE_TTL_SECS
+pub async fn put(redis: &ConnectionManager, item: &InventoryItem) {
+    let Ok(json) = serde_json::to_string(item) else {
+        return;
+    };
+    let result: redis::RedisResult<()> = redis::cmd("SETEX")
+        .arg(key(&item.sku))
+        .arg(ITEM_CACHE_TTL_SECS)
+        .arg(json)
+        .query_async(&mut deadline::redis(redis))
+        .await;
+    if let Err(e) = result {
+        record_error("set", &e);
+    }
+}
+
+/// Remember for `ttl_secs` that a SKU doesn't exist (0 = don't)
+async fn put_missing(redis: &ConnectionManager, sku: &str, ttl_secs: u64) {
+    if ttl_secs == 0 {
+        return;
+    }
+    let result: redis::RedisResult<()> = redis::cmd("SETEX")
+        .arg(key(sku))
+        .arg(ttl_secs)
+        .arg(MISSING)
+        .query_async(&mut deadline::redis(redis))
+        .await;
+    if let Err(e) = result {
+        record_error("set", &e);
+    }
+}
+
+/// Store the item a write just produced, unless a newer version is cached
+pub async fn write_through(redis: &ConnectionManager, item: &InventoryItem) {
+    let Ok(json) = serde_json::to_string(item) else {
+        return invalidate(redis, &item.sku).await;
+    };
+    let result: redis::RedisResult<i64> = Script::new(WRITE_THROUGH_LUA)
+        .key(key(&item.sku))
+        .arg(item.version)
+        .arg(json)
+        .arg(ITEM_CACHE_TTL_SECS)
+        .invoke_async(&mut deadline::redis(redis))
+        .await;
+    if let Err(e) = result {
+        record_error("write_through", &e);
+    }
+}
+
+/// Write-through for writes that don't return the item: read it back from
+/// the primary (a replica may not have the write yet) and store it
+pub async fn refresh(repo: &dyn InventoryRepository, redis: &ConnectionManager, sku: &str) {
+    match repo.get_by_sku(sku).await {
+        Ok(Some(item)) => write_through(redis, &item).await,
+        Ok(None) => invalidate(redis, sku).await,
+        Err(e) => {
+            tracing::debug!(sku, error = %e, "Couldn't read item back for the cache");
+            invalidate(redis, sku).await;
+        }
+    }
+}
+
+/// Drop a SKU's entry (call after any write to the SKU)
+pub async fn invalidate(redis: &ConnectionManager, sku: &str) {
+    let result: redis::RedisResult<()> = redis::cmd("DEL")
+        .arg(key(sku))
+        .query_async(&mut deadline::redis(redis))
+        .await;
+    if let Err(e) = result {
+        record_error("del", &e);
+    }
+}
+
+/// Read a SKU from the database and cache it (or that it doesn't exist,
+/// for `missing_ttl_secs`), unless another replica holding the fill lock
+/// does so first
+///
+/// Run through `AppState::item_fills`, so concurrent misses in this process
+/// share one call.
+pub async fn fill(
+    repo: Arc<dyn InventoryRepository>,
+    redis: ConnectionManager,
+    sku: String,
+    lock: bool,
+    missing_ttl_secs: u64,
+) -> Fill {
+    // Entries aren't cached from replicas (see get_item), so there's no
+    // fill to wait for
+    let cacheable = repo.replica_count() == 0;
+
+    let token = uuid::Uuid::new_v4().to_string();
+    let locked = lock && cacheable && lock_fill(&redis, &sku, &token).await;
+    if lock && cacheable && !locked {
+        let found = match wait_for_fill(&redis, &sku).await {
+            Lookup::Item(item) => Some(Some(*item)),
+            Lookup::Missing => Some(None),
+            Lookup::Miss => None,
+        };
+        if let Some(item) = found {
+            metrics::record_cache_fill_coalesced(CACHE, "cluster");
+            return Ok(item);
+        }
+    }
+
+    let item = repo.read_item(&sku).await.map_err(|e| e.to_string());
+    match (cacheable, &item) {
+        (true, Ok(Some(item))) => put(&redis, item).await,
+        (true, Ok(None)) => put_missing(&redis, &sku, missing_ttl_secs).await,
+        _ => {}
+    }
+    if locked {
+        unlock_fill(&redis, &sku, &token).await;
+    }
+    item
+}
+
+/// Take a SKU's fill lock; true when taken, or when Redis can't tell
+async fn lock_fill(redis: &ConnectionManager, sku: &str, token: &str) -> bool {
+    let result: redis::RedisResult<Option<String>> = redis::cmd("SET")
+        .arg(fill_lock_key(sku))
+        .arg(token)
+        .arg("NX")
+        .arg("PX")
+        .arg(FILL_LOCK_TTL_MS)
+        .query_async(&mut deadline::redis(redis))
+        .await;
+    match result {
+        Ok(reply) => reply.is_some(),
+        Err(e) => {
+            record_error("lock", &e);
+            true
+        }
+    }
+}
+
+async fn unlock_fill(redis: &ConnectionManager, sku: &str, token: &str) {
+    let result: redis::RedisResult<i64> = Script::new(UNLOCK_LUA)
+        .key(fill_lock_key(sku))
+        .arg(token)
+        .invoke_async(&mut deadline::redis(redis))
+        .await;
+    if let Err(e) = result {
+        record_error("unlock", &e);
+    }
+}
+
+/// Poll for the entry the lock holder writes, for up to FILL_WAIT
+///
+/// Plain GETs that don't count as cache hits or misses: the request
+/// already counted its miss.
+async fn wait_for_fill(redis: &ConnectionManager, sku: &str) -> Lookup {
+    let give_up_at = Instant::now() + FILL_WAIT;
+    while Instant::now() < give_up_at {
+        tokio::time::sleep(FILL_POLL).await;
+        let cached: redis::RedisResult<Option<String>> = redis::cmd("GET")
+            .arg(key(sku))
+            .query_async(&mut deadline::redis(redis))
+            .await;
+        match cached {
+            Ok(Some(json)) => return decode(&json),
+            Ok(None) => continue,
+            Err(_) => return Lookup::Miss,
+        }
+    }
+    Lookup::Miss
+}
+
+fn fill_lock_key(sku: &str) -> String {
+    format!("inventory:fill-lock:{}", sku)
+}
+
+/// Count a failed Redis call
+fn record_error(operation: &'static str, error: &redis::RedisError) {
+    tracing::debug!(cache = CACHE, operation, error = %error, "Cache unavailable");
+    metrics::record_cache_error(CACHE, operation);
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_entries() {
+        assert!(matches!(decode(MISSING), Lookup::Missing));
+        assert!(matches!(decode("{\"sku\":\"old format\"}"), Lookup::Miss));
+        // Write-through's Lua decodes entries as JSON; MISSING must not be
+        assert!(serde_json::from_str::<serde_json::Value>(MISSING).is_err());
+    }
+
+    /// WRITE_THROUGH_LUA's decision in Rust: whether an item at `version`
+    /// replaces the entry `cached` (Lua numbers are doubles, hence f64)
+    fn script_replaces(cached: Option<&str>, version: i64) -> bool {
+        let newer = cached
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok())
+            .filter(|item| item.is_object() || item.is_array())
+            .and_then(|item| item.get("version").and_then(|v| v.as_f64()))
+            .is_some_and(|cached| cached > version as f64);
+        !newer
+    }
+
+    fn entry(version: i64) -> String {
+        let item: InventoryItem = serde_json::from_value(serde_json::json!({
+            "id": uuid::Uuid::nil(),
+            "sku": "SKU-1",
+            "name": "Item",
+            "quantity": 10,
+            "reserved": 0,
+            "warehouse": "JKT-1",
+            "low_stock_threshold": 1,
+            "version": version,
+            "created_at": "2024-05-01T12:00:00Z",
+            "updated_at": "2024-05-01T12:00:00Z"
+        }))
+        .unwrap();
+        serde_json::to_string(&item).unwrap()
+    }
+
+    #[test]
+    fn test_write_through_never_goes_back_a_version() {
+        // Of two concurrent writes, the older one landing last changes nothing
+        assert!(!script_replaces(Some(&entry(8)), 7));
+        assert!(script_replaces(Some(&entry(7)), 7));
+        assert!(script_replaces(Some(&entry(7)), 8));
+        assert!(script_replaces(None, 1));
+
+        // Entries that carry no version are replaced: not-found, unreadable,
+        // or from a version of the service that didn't store one
+        assert!(script_replaces(Some(MISSING), 1));
+        assert!(script_replaces(Some("{\"sku\":"), 1));
+        assert!(script_replaces(Some("{\"sku\":\"SKU-1\"}"), 1));
+        assert!(script_replaces(Some("{\"version\":\"9\"}"), 1));
+        assert!(script_replaces(Some("9"), 1));
+    }
+
+    #[test]
+    fn test_only_item_entries_are_under_the_prefix() {
+        assert_eq!(key("SKU-1"), "inventory:item:SKU-1");
+        assert!(!fill_lock_key("SKU-1").starts_with(KEY_PREFIX));
+        // A SKU can't make its entry collide with another cache's keys
+        assert!(key("list:7:*:1:20").starts_with(KEY_PREFIX));
+    }
+}
@@ -0,0 +1,259 @@
+// =============================================================================
+// LIST CACHE MODULE
+// =============================================================================
+// Read-through Redis cache for GET /api/v1/inventory. The lab frontend polls
+// page 1 constantly; without this every poll is two queries (page + count).
+//
+// WHAT GETS CACHED:
+// - Pages 1..=LIST_CACHE_MAX_PAGE (default 3), any per_page, filter and
+//   sort order
+// - For LIST_CACHE_TTL_SECS (default 5); 0 turns the cache off
+//
+// INVALIDATION (generation keys):
+// Keys embed a generation number, inventory:list:gen. Every write bumps it,
+// so all cached pages become unreachable at once and simply expire.
+//   inventory:list:<gen>:<warehouse|*>:<page>:<per_page>[:<options>][:<attribute filter>]
+//
+// LEARNING NOTES:
+// - A page is stored under the generation read *before* querying Postgres.
+//   If a write lands meanwhile, the page goes to an old generation nobody
+//   reads, so a slow reader can't resurrect stale data.
+// - Writes that bypass this service (manual SQL, other tools) are only
+//   picked up when the TTL runs out, hence the short TTL
+// =============================================================================
+
+use redis::aio::ConnectionManager;
+use std::time::Instant;
+
+use crate::deadline;
+use crate::metrics;
+use crate::models::{InventoryListResponse, SortBy, SortOrder};
+
+/// Generation counter bumped by every write
+const GENERATION_KEY: &str = "inventory:list:gen";
+
+/// Label of this cache in the cache_* metrics (see item_cache.rs)
+const CACHE: &str = "list";
+
+// -----------------------------------------------------------------------------
+// CACHE
+// -----------------------------------------------------------------------------
+/// Settings of the list cache
+#[derive(Debug, Clone, Copy)]
+pub struct ListCache {
+    /// Seconds a page stays cached (0 = cache disabled)
+    ttl_secs: u64,
+    /// Highest page number that is cached
+    max_page: i32,
+}
+
+/// Which listing a request is for
+#[derive(Debug, Clone, Copy)]
+pub struct ListQuery<'a> {
+    pub warehouse: Option<&'a str>,
+    pub sku_prefix: Option<&'a str>,
+    pub name_contains: Option<&'a str>,
+    pub low_stock_only: bool,
+    /// Attribute filter as compact JSON (keys sorted, so equal filters
+    /// share a key)
+    pub attributes: Option<&'a str>,
+    pub sort_by: SortBy,
+    pub order: SortOrder,
+    pub page: i32,
+    pub per_page: i32,
+}
+
+/// Result of a cache lookup
+pub enum Lookup {
+    /// Served from Redis
+    Hit(InventoryListResponse),
+    /// Not cached; store the page under this generation once loaded
+    Miss(i64),
+    /// Not cacheable (deep page, cache off, Redis down)
+    Bypass,
+}
+
+impl ListCache {
+    pub fn new(ttl_secs: u64, max_page: i32) -> Self {
+        Self { ttl_secs, max_page }
+    }
+
+    fn cacheable(&self, query: &ListQuery) -> bool {
+        self.ttl_secs > 0 && query.page <= self.max_page
+    }
+
+    /// Look a page up
+    pub async fn get(&self, redis: &ConnectionManager, query: &ListQuery<'_>) -> Lookup {
+        if !self.cacheable(query) {
+            metrics::record_list_cache_lookup("bypass");
+            return Lookup::Bypass;
+        }
+
+        let start = Instant::now();
+        let mut redis = deadline::redis(redis);
+
+        let generation: i64 = match redis::cmd("GET")
+            .arg(GENERATION_KEY)
+            .query_async::<_, Option<i64>>(&mut redis)
+            .await
+        {
+            Ok(generation) => generation.unwrap_or(0),
+            Err(e) => {
+                tracing::debug!(error = %e, "List cache unavailable");
+                metrics::record_list_cache_lookup("bypass");
+                metrics::record_cache_error(CACHE, "get");
+                return Lookup::Bypass;
+            }
+        };
+
+        let cached: Option<String> = match redis::cmd("GET")
+            .arg(cache_key(generation, query))
+            .query_async(&mut redis)
+            .await
+        {
+            Ok(cached) => cached,
+            Err(_) => {
+                metrics::record_cache_error(CACHE, "get");
+                None
+            }
+        };
+        metrics::record_redis_operation("list_cache_get", start.elapsed().as_secs_f64());
+
+        match cached.and_then(|json| serde_json::from_str(&json).ok()) {
+            Some(page) => {
+                metrics::record_list_cache_lookup("hit");
+                metrics::record_cache_lookup(CACHE, true);
+                Lookup::Hit(page)
+            }
+            None => {
+                metrics::record_list_cache_lookup("miss");
+                metrics::record_cache_lookup(CACHE, false);
+                Lookup::Miss(generation)
+            }
+        }
+    }
+
+    /// Store a page loaded after a miss
+    pub async fn put(
+        &self,
+        redis: &ConnectionManager,
+        generation: i64,
+        query: &ListQuery<'_>,
+        page: &InventoryListResponse,
+    ) {
+        let Ok(json) = serde_json::to_string(page) else {
+            return;
+        };
+        let result: redis::RedisResult<()> = redis::cmd("SETEX")
+            .arg(cache_key(generation, query))
+            .arg(self.ttl_secs)
+            .arg(json)
+            .query_async(&mut deadline::redis(redis))
+            .await;
+        if result.is_err() {
+            metrics::record_cache_error(CACHE, "set");
+        }
+    }
+}
+
+/// Drop every cached page (call after any inventory write)
+pub async fn invalidate(redis: &ConnectionManager) {
+    let result: redis::RedisResult<i64> = redis::cmd("INCR")
+        .arg(GENERATION_KEY)
+        .query_async(&mut deadline::redis(redis))
+        .await;
+    if let Err(e) = result {
+        tracing::debug!(error = %e, "Failed to invalidate list cache");
+        metrics::record_cache_error(CACHE, "invalidate");
+    }
+}
+
+fn cache_key(generation: i64, query: &ListQuery) -> String {
+    let mut key = format!(
+        "inventory:list:{}:{}:{}:{}",
+        generation,
+        query.warehouse.unwrap_or("*"),
+        query.page,
+        query.per_page
+    );
+    // Only non-default options extend the key; free text is quoted so a
+    // ':' inside it can't be mistaken for a separator
+    if (query.sort_by, query.order) != (SortBy::Sku, SortOrder::Asc) {
+        key.push_str(&format!(":sort={}.{}", query.sort_by.as_str(), query.order.as_str()));
+    }
+    if let Some(prefix) = query.sku_prefix {
+        key.push_str(&format!(":prefix={:?}", prefix));
+    }
+    if let Some(name) = query.name_contains {
+        key.push_str(&format!(":name={:?}", name));
+    }
+    if query.low_stock_only {
+        key.push_str(":low");
+    }
+    if let Some(attributes) = query.attributes {
+        key.push(':');
+        key.push_str(attributes);
+    }
+    key
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_and_cacheable() {
+        let cache = ListCache::new(5, 3);
+        let first = ListQuery {
+            warehouse: None,
+            sku_prefix: None,
+            name_contains: None,
+            low_stock_only: false,
+            attributes: None,
+            sort_by: SortBy::Sku,
+            order: SortOrder::Asc,
+            page: 1,
+            per_page: 20,
+        };
+        let deep = ListQuery { page: 4, ..first };
+        let jakarta = ListQuery {
+            warehouse: Some("JKT-1"),
+            ..first
+        };
+        let red = ListQuery {
+            attributes: Some(r#"{"color":"red"}"#),
+            ..first
+        };
+
+        assert!(cache.cacheable(&first));
+        assert!(!cache.cacheable(&deep));
+        assert!(!ListCache::new(0, 3).cacheable(&first));
+
+        assert_eq!(cache_key(7, &first), "inventory:list:7:*:1:20");
+        assert_eq!(cache_key(7, &jakarta), "inventory:list:7:JKT-1:1:20");
+        assert_eq!(cache_key(7, &red), r#"inventory:list:7:*:1:20:{"color":"red"}"#);
+
+        let sorted_low = ListQuery {
+            sort_by: SortBy::Available,
+            order: SortOrder::Desc,
+            low_stock_only: true,
+            ..first
+        };
+        assert_eq!(cache_key(7, &sorted_low), "inventory:list:7:*:1:20:sort=available.desc:low");
+
+        // A ':' in free text must not collide with the other options
+        let tricky = ListQuery {
+            name_contains: Some("a:low"),
+            ..first
+        };
+        let plain_low = ListQuery {
+            name_contains: Some("a"),
+            low_stock_only: true,
+            ..first
+        };
+        assert_ne!(cache_key(7, &tricky), cache_key(7, &plain_low));
+    }
+}
@@ -0,0 +1,217 @@
+// =============================================================================
+// LIVE MODULE
+// =============================================================================
+// Live feed of stock changes for the lab frontends, so dashboards update as
+// stock moves instead of polling the list endpoint
+// (GET /api/v1/inventory/stream, Server-Sent Events).
+//
+// HOW:
+// - The mutation handlers publish a `StockChange` after each successful
+//   change to a tokio broadcast channel (LiveFeed)
+// - Every stream subscribes to the channel and forwards the changes as SSE
+//   messages, optionally only those of one SKU (?sku=)
+//
+// MESSAGES:
+//   data: {"type":"reserved","sku":"SKU-LAPTOP-001","quantity":2,"at":"..."}
+// type is reserved, released, confirmed, adjusted, transferred or created,
+// with quantity the units involved (negative when an adjustment removed
+// stock); or updated, with quantity 0, after other changes (item update,
+// bulk update, import, reservation repair) where clients should refetch
+// the item.
+// A comment line is sent every KEEP_ALIVE so proxies don't close idle
+// streams.
+//
+// CONFIGURATION:
+//   LIVE_STREAM_BUFFER=1024   changes a slow client may fall behind by
+//
+// LEARNING NOTES:
+// - A client further behind than the buffer gets `event: resync` and
+//   should refetch what it shows; changes in between are lost to it
+// - Changes are per process: with several replicas, a client only sees the
+//   changes made through the replica it is connected to
+// - Background jobs (reservation expiry, write-behind) and the gRPC API
+//   don't publish; the audit trail (and Kafka, see events.rs) has those
+// - inventory_live_stream_clients counts the connected streams
+// =============================================================================
+
+use axum::response::sse::Event;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::metrics;
+
+/// Interval of the keep-alive comments
+pub const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+// -----------------------------------------------------------------------------
+// CHANGES
+// -----------------------------------------------------------------------------
+/// What happened to a SKU
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Reserved,
+    Released,
+    /// Reserved units left the warehouse (order paid or fulfilled)
+    Confirmed,
+    Adjusted,
+    Transferred,
+    Created,
+    /// Details changed (item update, bulk update, import, reservation
+    /// repair)
+    Updated,
+}
+
+/// One change, as sent to the clients
+#[derive(Debug, Clone, Serialize)]
+pub struct StockChange {
+    #[serde(rename = "type")]
+    pub kind: ChangeKind,
+    pub sku: String,
+    pub quantity: i32,
+    pub at: DateTime<Utc>,
+}
+
+// -----------------------------------------------------------------------------
+// FEED
+// -----------------------------------------------------------------------------
+/// Broadcast channel between the mutation handlers and the streams; cheap
+/// to clone
+#[derive(Clone)]
+pub struct LiveFeed {
+    sender: broadcast::Sender<StockChange>,
+}
+
+impl LiveFeed {
+    /// Create a feed keeping up to `buffer` changes per slow client
+    pub fn new(buffer: usize) -> Self {
+        let (sender, _) = broadcast::channel(buffer.max(1));
+        Self { sender }
+    }
+
+    /// Clients connected to the stream right now
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Tell the connected clients about a change (nothing happens when none
+    /// are connected)
+    pub fn publish(&self, kind: ChangeKind, sku: &str, quantity: i32) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        let _ = self.sender.send(StockChange {
+            kind,
+            sku: sku.to_string(),
+            quantity,
+            at: Utc::now(),
+        });
+    }
+
+    /// SSE events for a new client, only for `sku` when given
+    pub fn subscribe(&self, sku: Option<String>) -> impl Stream<Item = Result<Event, Infallible>> {
+        metrics::add_live_stream_clients(1.0);
+        let client = Client {
+            receiver: self.sender.subscribe(),
+            sku,
+        };
+
+        stream::unfold(client, |mut client| async move {
+            loop {
+                let event = match client.receiver.recv().await {
+                    Ok(change) if client.wants(&change) => to_event(&change),
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => {
+                        tracing::debug!(missed, "Live stream client fell behind");
+                        Event::default().event("resync").data(missed.to_string())
+                    }
+                    Err(RecvError::Closed) => return None,
+                };
+                return Some((Ok(event), client));
+            }
+        })
+    }
+}
+
+/// A connected stream; counted in inventory_live_stream_clients while alive
+struct Client {
+    receiver: broadcast::Receiver<StockChange>,
+    sku: Option<String>,
+}
+
+impl Client {
+    fn wants(&self, change: &StockChange) -> bool {
+        self.sku.as_ref().is_none_or(|sku| *sku == change.sku)
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        metrics::add_live_stream_clients(-1.0);
+    }
+}
+
+/// SSE message of a change
+fn to_event(change: &StockChange) -> Event {
+    Event::default()
+        .json_data(change)
+        .unwrap_or_else(|_| Event::default().event("resync").data("0"))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn test_subscribers_get_their_changes() {
+        let feed = LiveFeed::new(8);
+        feed.publish(ChangeKind::Adjusted, "SKU-0", 1);
+
+        let all = feed.subscribe(None);
+        let one = feed.subscribe(Some("SKU-2".to_string()));
+        tokio::pin!(all, one);
+
+        feed.publish(ChangeKind::Reserved, "SKU-1", 2);
+        feed.publish(ChangeKind::Released, "SKU-2", 3);
+
+        let first = all.next().await.unwrap().unwrap();
+        assert!(format!("{:?}", first).contains("SKU-1"));
+        let filtered = one.next().await.unwrap().unwrap();
+        assert!(format!("{:?}", filtered).contains("SKU-2"));
+    }
+
+    #[tokio::test]
+    async fn test_lagging_client_gets_resync() {
+        let feed = LiveFeed::new(2);
+        let events = feed.subscribe(None);
+        tokio::pin!(events);
+
+        for i in 0..5 {
+            feed.publish(ChangeKind::Adjusted, &format!("SKU-{}", i), 1);
+        }
+
+        let event = events.next().await.unwrap().unwrap();
+        assert!(format!("{:?}", event).contains("resync"));
+    }
+
+    #[test]
+    fn test_change_json() {
+        let change = StockChange {
+            kind: ChangeKind::Transferred,
+            sku: "SKU-1".to_string(),
+            quantity: 5,
+            at: Utc::now(),
+        };
+        let value = serde_json::to_value(&change).unwrap();
+        assert_eq!(value["type"], "transferred");
+        assert_eq!(value["quantity"], 5);
+    }
+}
@@ -0,0 +1,174 @@
+// =============================================================================
+// LOAD SHED MODULE
+// =============================================================================
+// A cap on API requests handled at once, so a slow dependency (Postgres
+// stuck on a lock, a saturated pool) turns into quick 503s instead of an
+// ever-growing pile of requests that all wait and all time out.
+//
+// HOW (MAX_CONCURRENT_REQUESTS > 0, default 512):
+// - Every /api/ request takes one of MAX_CONCURRENT_REQUESTS slots and
+//   gives it back when its response is ready
+// - With all slots taken, a request waits up to LOAD_SHED_QUEUE_TIMEOUT_MS
+//   (default 100) for one to free up
+// - Still none: 503 SERVICE_UNAVAILABLE with Retry-After (RETRY_AFTER_SECS),
+//   counted in http_requests_shed_total{endpoint}
+// - http_requests_in_flight shows how many slots are taken
+//
+// LEARNING NOTES:
+// - Timeouts are the deadline layer's job (deadline.rs): no request runs
+//   longer than REQUEST_TIMEOUT_MS and then gets 504 DEADLINE_EXCEEDED. This
+//   layer sits inside it, so time spent waiting for a slot counts against
+//   the request's deadline.
+// - Probes and scrapes (/health, /ready, /metrics) never take a slot: an
+//   overloaded replica should still be seen, and scraped
+// - A streamed response (export, live stream) gives its slot back once the
+//   headers are sent, not when the body ends
+// - The limit is per process; with N replicas the service takes N times
+//   as many requests
+// =============================================================================
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::error::AppError;
+use crate::graphql;
+use crate::metrics;
+use crate::AppState;
+
+/// Settings of the concurrency limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadShedConfig {
+    /// API requests handled at once
+    pub max_in_flight: usize,
+    /// How long a request may wait for a free slot
+    pub queue_timeout: Duration,
+}
+
+/// Slots for requests being handled
+pub struct LoadShedder {
+    config: LoadShedConfig,
+    slots: Semaphore,
+}
+
+impl LoadShedder {
+    pub fn new(config: LoadShedConfig) -> Self {
+        Self {
+            slots: Semaphore::new(config.max_in_flight.max(1)),
+            config,
+        }
+    }
+
+    /// Take a slot, waiting up to the queue timeout for one
+    ///
+    /// # Returns
+    /// - `None` when every slot stayed taken
+    async fn admit(&self) -> Option<SemaphorePermit<'_>> {
+        if let Ok(slot) = self.slots.try_acquire() {
+            return Some(slot);
+        }
+        tokio::time::timeout(self.config.queue_timeout, self.slots.acquire())
+            .await
+            .ok()?
+            .ok()
+    }
+}
+
+// =============================================================================
+// MIDDLEWARE
+// =============================================================================
+/// Hold a slot while an /api/ request is handled; shed it when none frees up
+pub async fn enforce(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let Some(shedder) = &state.load_shed else {
+        return next.run(request).await;
+    };
+    let path = request.uri().path();
+    if !path.starts_with("/api/") && path != graphql::PATH {
+        return next.run(request).await;
+    }
+
+    let Some(_slot) = shedder.admit().await else {
+        let endpoint = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str())
+            .unwrap_or("unmatched");
+        metrics::record_request_shed(endpoint);
+        tracing::debug!(endpoint, "Request shed at the concurrency limit");
+        return AppError::ServiceUnavailable {
+            reason: format!(
+                "Server is busy ({} requests in flight), try again shortly",
+                shedder.config.max_in_flight
+            ),
+            retry_after_secs: state.config.retry_after_secs,
+        }
+        .into_response();
+    };
+
+    let _in_flight = InFlight::start();
+    next.run(request).await
+}
+
+/// Counts a request in http_requests_in_flight until dropped, which also
+/// covers requests cut off by their deadline
+struct InFlight;
+
+impl InFlight {
+    fn start() -> Self {
+        metrics::add_requests_in_flight(1.0);
+        Self
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        metrics::add_requests_in_flight(-1.0);
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shedder(max_in_flight: usize) -> LoadShedder {
+        LoadShedder::new(LoadShedConfig {
+            max_in_flight,
+            queue_timeout: Duration::from_millis(20),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_sheds_when_slots_stay_taken() {
+        let shedder = shedder(2);
+        let first = shedder.admit().await;
+        let second = shedder.admit().await;
+        assert!(first.is_some() && second.is_some());
+        assert!(shedder.admit().await.is_none());
+
+        drop(first);
+        assert!(shedder.admit().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_waiting_request_gets_freed_slot() {
+        let shedder = Arc::new(shedder(1));
+        let slot = shedder.admit().await.unwrap();
+
+        let waiter = {
+            let shedder = shedder.clone();
+            tokio::spawn(async move { shedder.admit().await.is_some() })
+        };
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        drop(slot);
+
+        assert!(waiter.await.unwrap());
+    }
+}
@@ -0,0 +1,300 @@
+// =============================================================================
+// LOAD GENERATOR MODULE
+// =============================================================================
+// Admin-triggered synthetic load straight against the database pool, to light
+// up the DB latency, pool saturation and lock-wait panels without external
+// tooling (k6, pgbench, ...).
+//
+// SCENARIOS:
+// - heavy_scan:   every worker runs a large CROSS JOIN scan in a loop
+// - hot_sku_lock: every worker locks the same SKU row (SELECT ... FOR UPDATE)
+//                 and holds it for `hold_ms`, so the others queue on the lock.
+//                 Real reservations for that SKU queue up too, which is the
+//                 point: it shows what contention looks like on dashboards.
+//
+// USAGE:
+//   POST   /api/v1/admin/load/db  {"scenario":"hot_sku_lock","concurrency":6}
+//   GET    /api/v1/admin/load/db  → current/last run
+//   DELETE /api/v1/admin/load/db  → stop early
+//
+// LEARNING NOTES:
+// - Only one run at a time; every run stops by itself after `duration_secs`
+// - Queries are timed into db_query_duration_seconds with operation
+//   "loadgen_scan" / "loadgen_lock", next to the real queries
+// - Both scenarios start their work through the database's retry and
+//   circuit breaker wrapper (scan as a primary read, lock via its
+//   transaction begin), so transient failures are retried and counted in
+//   db_retries_total, and an open breaker turns workers away like requests
+// - Workers share the service's pool (max 10 connections), so high
+//   concurrency also starves API requests of connections
+// =============================================================================
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::{JoinHandle, JoinSet};
+use utoipa::ToSchema;
+
+use crate::db::Database;
+use crate::metrics;
+use crate::validation::{self, Validate, Validator};
+
+/// Upper bound on workers (the pool has 10 connections)
+const MAX_CONCURRENCY: u32 = 10;
+
+/// Upper bound on a single run
+const MAX_DURATION_SECS: u64 = 600;
+
+// -----------------------------------------------------------------------------
+// REQUEST / STATUS
+// -----------------------------------------------------------------------------
+/// Kind of synthetic load
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Scenario {
+    HeavyScan,
+    HotSkuLock,
+}
+
+/// Parameters of a load run
+///
+/// # Example JSON
+/// ```json
+/// { "scenario": "hot_sku_lock", "concurrency": 6, "duration_secs": 120,
+///   "sku": "SKU-PHONE-001", "hold_ms": 250 }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct LoadRequest {
+    pub scenario: Scenario,
+
+    /// Parallel workers (default: 4, max: 10)
+    #[serde(default = "default_concurrency")]
+    pub concurrency: u32,
+
+    /// How long to run (default: 60, max: 600)
+    #[serde(default = "default_duration_secs")]
+    pub duration_secs: u64,
+
+    /// heavy_scan: generated rows joined per scan (default: 50000)
+    #[serde(default = "default_scan_rows")]
+    pub scan_rows: i32,
+
+    /// hot_sku_lock: SKU whose row is locked (default: SKU-PHONE-001)
+    #[serde(default = "default_sku")]
+    pub sku: String,
+
+    /// hot_sku_lock: how long each worker holds the lock (default: 200)
+    #[serde(default = "default_hold_ms")]
+    pub hold_ms: u64,
+}
+
+fn default_concurrency() -> u32 {
+    4
+}
+fn default_duration_secs() -> u64 {
+    60
+}
+fn default_scan_rows() -> i32 {
+    50_000
+}
+fn default_sku() -> String {
+    "SKU-PHONE-001".to_string()
+}
+fn default_hold_ms() -> u64 {
+    200
+}
+
+impl Validate for LoadRequest {
+    fn check(&self, v: &mut Validator) {
+        v.range("concurrency", self.concurrency, 1, MAX_CONCURRENCY);
+        v.range("duration_secs", self.duration_secs, 1, MAX_DURATION_SECS);
+        v.range("scan_rows", self.scan_rows, 1, 1_000_000);
+        v.range("hold_ms", self.hold_ms, 1, 10_000);
+    }
+}
+
+/// Snapshot of the current (or last) load run
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LoadStatus {
+    pub scenario: Scenario,
+    pub concurrency: u32,
+    pub running: bool,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// Queries completed so far
+    pub queries: u64,
+    /// Queries that failed
+    pub errors: u64,
+}
+
+// =============================================================================
+// GENERATOR
+// =============================================================================
+struct Run {
+    request: LoadRequest,
+    started_at: DateTime<Utc>,
+    queries: Arc<AtomicU64>,
+    errors: Arc<AtomicU64>,
+    handle: JoinHandle<()>,
+}
+
+impl Run {
+    fn status(&self) -> LoadStatus {
+        LoadStatus {
+            scenario: self.request.scenario,
+            concurrency: self.request.concurrency,
+            running: !self.handle.is_finished(),
+            started_at: self.started_at,
+            ends_at: self.started_at + chrono::Duration::seconds(self.request.duration_secs as i64),
+            queries: self.queries.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Owner of the (at most one) active load run
+#[derive(Clone, Default)]
+pub struct LoadGenerator {
+    current: Arc<Mutex<Option<Run>>>,
+}
+
+impl LoadGenerator {
+    /// Start a run; fails if the request is invalid or a run is active
+    pub fn start(&self, db: Database, request: LoadRequest) -> Result<LoadStatus> {
+        if let Err(errors) = request.validate() {
+            bail!(validation::summary(&errors));
+        }
+
+        let mut current = self.current.lock().expect("loadgen lock poisoned");
+        if current
+            .as_ref()
+            .is_some_and(|run| !run.handle.is_finished())
+        {
+            bail!("A load run is already active, stop it first");
+        }
+
+        let queries = Arc::new(AtomicU64::new(0));
+        let errors = Arc::new(AtomicU64::new(0));
+        let handle = tokio::spawn(run_workers(
+            db,
+            request.clone(),
+            queries.clone(),
+            errors.clone(),
+        ));
+
+        tracing::warn!(
+            scenario = ?request.scenario,
+            concurrency = request.concurrency,
+            duration_secs = request.duration_secs,
+            "Synthetic database load started"
+        );
+
+        let run = Run {
+            request,
+            started_at: Utc::now(),
+            queries,
+            errors,
+            handle,
+        };
+        let status = run.status();
+        *current = Some(run);
+        Ok(status)
+    }
+
+    /// Stop the active run early; returns its final status
+    pub fn stop(&self) -> Option<LoadStatus> {
+        let current = self.current.lock().expect("loadgen lock poisoned");
+        let run = current.as_ref()?;
+        run.handle.abort();
+        tracing::info!("Synthetic database load stopped");
+
+        Some(LoadStatus {
+            running: false,
+            ..run.status()
+        })
+    }
+
+    /// Status of the current or last run
+    pub fn status(&self) -> Option<LoadStatus> {
+        self.current
+            .lock()
+            .expect("loadgen lock poisoned")
+            .as_ref()
+            .map(Run::status)
+    }
+}
+
+/// Run `concurrency` workers until the duration is up.
+///
+/// Aborting this task drops the JoinSet, which aborts the workers too.
+async fn run_workers(
+    db: Database,
+    request: LoadRequest,
+    queries: Arc<AtomicU64>,
+    errors: Arc<AtomicU64>,
+) {
+    let deadline = Instant::now() + Duration::from_secs(request.duration_secs);
+    let mut workers = JoinSet::new();
+
+    for _ in 0..request.concurrency {
+        let db = db.clone();
+        let request = request.clone();
+        let queries = queries.clone();
+        let errors = errors.clone();
+
+        workers.spawn(async move {
+            while Instant::now() < deadline {
+                let start = Instant::now();
+                let (operation, result) = match request.scenario {
+                    Scenario::HeavyScan => {
+                        ("loadgen_scan", db.loadgen_scan(request.scan_rows).await)
+                    }
+                    Scenario::HotSkuLock => (
+                        "loadgen_lock",
+                        db.loadgen_hold_lock(&request.sku, Duration::from_millis(request.hold_ms))
+                            .await,
+                    ),
+                };
+                metrics::record_db_query(operation, start.elapsed().as_secs_f64());
+
+                queries.fetch_add(1, Ordering::Relaxed);
+                if let Err(e) = result {
+                    errors.fetch_add(1, Ordering::Relaxed);
+                    tracing::debug!(error = %e, "Load generator query failed");
+                    // Don't spin on a persistent error (e.g. unknown SKU)
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        });
+    }
+
+    while workers.join_next().await.is_some() {}
+    tracing::info!(
+        queries = queries.load(Ordering::Relaxed),
+        errors = errors.load(Ordering::Relaxed),
+        "Synthetic database load finished"
+    );
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_defaults_and_validation() {
+        let request: LoadRequest = serde_json::from_str(r#"{"scenario":"hot_sku_lock"}"#).unwrap();
+        assert_eq!(request.scenario, Scenario::HotSkuLock);
+        assert_eq!(request.concurrency, 4);
+        assert!(request.validate().is_ok());
+
+        let too_many: LoadRequest =
+            serde_json::from_str(r#"{"scenario":"heavy_scan","concurrency":50}"#).unwrap();
+        assert!(too_many.validate().is_err());
+    }
+}
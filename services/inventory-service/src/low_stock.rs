@@ -0,0 +1,348 @@
+// =============================================================================
+// LOW STOCK MODULE
+// =============================================================================
+// Low-stock state evaluated by a background job instead of per request.
+//
+// BEFORE:
+// GET /api/v1/inventory/alerts ran the query and was the only thing that
+// updated `inventory_low_stock_items`. With nobody calling the endpoint the
+// gauge froze, so low-stock alert rules in Grafana never fired.
+//
+// NOW:
+// - A supervised job ("low-stock-evaluator") re-runs the query every
+//   LOW_STOCK_EVAL_INTERVAL_SECS (default 30) and updates the gauges
+// - The endpoint serves the latest evaluation; only before the first one
+//   finishes does it query Postgres itself
+//
+// LEARNING NOTES:
+// - The endpoint's answer can be up to one interval old; the
+//   `low_stock_last_evaluated_timestamp_seconds` gauge shows how old
+// - If the job keeps failing the supervisor restarts it with backoff and
+//   the endpoint keeps serving the last good evaluation
+// - Thresholds can be overridden per (sku, warehouse); counts are also
+//   exported per warehouse (inventory_low_stock_items_by_warehouse)
+// - With KAFKA_BROKERS set, each (sku, warehouse) that wasn't low at the
+//   previous evaluation is published as a low-stock event (events.rs)
+// - With LOW_STOCK_ALERT_WEBHOOK_URL set, each evaluation also pushes new,
+//   repeated and resolved alerts to Alertmanager or Slack (alert_notifier.rs)
+//
+// DYNAMIC THRESHOLDS (opt-in, DYNAMIC_THRESHOLDS=true):
+// A second job ("threshold-recalculator") sets each SKU's
+// low_stock_threshold to DYNAMIC_THRESHOLD_COVER_DAYS (default 7) days of
+// average outbound movement, averaged over the last
+// DYNAMIC_THRESHOLD_LOOKBACK_DAYS (default 28) of the audit trail. SKUs whose
+// threshold was set by hand (PUT /api/v1/inventory/:sku/thresholds) are
+// skipped until they are handed back with {"threshold": null}.
+// =============================================================================
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::alert_notifier::AlertNotifier;
+use crate::db::Database;
+use crate::events::EventPublisher;
+use crate::metrics;
+use crate::models::LowStockAlert;
+
+// -----------------------------------------------------------------------------
+// STATE
+// -----------------------------------------------------------------------------
+/// Result of one evaluation
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    pub alerts: Vec<LowStockAlert>,
+    pub evaluated_at: DateTime<Utc>,
+}
+
+/// Latest low-stock evaluation, shared by the job and the endpoint
+#[derive(Clone, Default)]
+pub struct LowStockMonitor {
+    latest: Arc<RwLock<Option<Evaluation>>>,
+    events: Option<EventPublisher>,
+    notifier: Option<AlertNotifier>,
+}
+
+impl LowStockMonitor {
+    /// Monitor that publishes new low-stock crossings to `events`
+    pub fn with_events(events: EventPublisher) -> Self {
+        Self {
+            events: Some(events),
+            ..Self::default()
+        }
+    }
+
+    /// Also push the alerts of each evaluation to a webhook
+    pub fn with_notifier(self, notifier: AlertNotifier) -> Self {
+        Self {
+            notifier: Some(notifier),
+            ..self
+        }
+    }
+
+    /// The latest evaluation, if one has finished
+    pub fn latest(&self) -> Option<Evaluation> {
+        self.latest.read().expect("low stock lock poisoned").clone()
+    }
+
+    /// Query Postgres, update the gauges and keep the result
+    pub async fn evaluate(&self, db: &Database) -> Result<Evaluation> {
+        let start = Instant::now();
+        let alerts = db.get_low_stock_items().await?;
+        metrics::record_db_query("low_stock_eval", start.elapsed().as_secs_f64());
+
+        let evaluation = Evaluation {
+            alerts,
+            evaluated_at: Utc::now(),
+        };
+
+        let previous_evaluation = self.replace(evaluation.clone());
+        let previous = previous_evaluation
+            .as_ref()
+            .map(|p| p.alerts.as_slice())
+            .unwrap_or_default();
+
+        // Items that left the low-stock set get their current level; their
+        // gauge would keep the last low one otherwise
+        let mut recovered = Vec::new();
+        for alert in newly_low(&evaluation.alerts, previous) {
+            match db.get_by_sku(&alert.sku).await {
+                Ok(Some(item)) if item.warehouse == alert.warehouse => {
+                    recovered.push((alert, item.available()));
+                }
+                // Deleted, or moved to another home warehouse
+                Ok(_) => recovered.push((alert, 0)),
+                Err(e) => {
+                    tracing::warn!(sku = %alert.sku, error = %e, "Failed to read recovered item");
+                }
+            }
+        }
+        update_gauges(&evaluation, previous, &recovered);
+
+        // The first evaluation has nothing to compare with; publishing it
+        // would report every low SKU again on each restart
+        if let (Some(events), Some(previous)) = (&self.events, &previous_evaluation) {
+            events
+                .publish_low_stock(&newly_low(&previous.alerts, &evaluation.alerts))
+                .await;
+        }
+        // The notifier keeps its own record of what it sent, so the first
+        // evaluation goes out too
+        if let Some(notifier) = &self.notifier {
+            notifier.notify(&evaluation.alerts, evaluation.evaluated_at).await;
+        }
+
+        Ok(evaluation)
+    }
+
+    /// Store a new evaluation, returning the previous one
+    fn replace(&self, evaluation: Evaluation) -> Option<Evaluation> {
+        self.latest
+            .write()
+            .expect("low stock lock poisoned")
+            .replace(evaluation)
+    }
+
+    /// Re-evaluate every `interval`, forever
+    pub async fn run(self, db: Database, interval: Duration) -> Result<()> {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            let evaluation = self.evaluate(&db).await?;
+            tracing::debug!(low_stock = evaluation.alerts.len(), "Low stock evaluated");
+        }
+    }
+}
+
+// =============================================================================
+// DYNAMIC THRESHOLDS
+// =============================================================================
+/// How automatic thresholds are computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DynamicThresholdPolicy {
+    /// Days of average outbound movement the threshold should cover
+    pub cover_days: i32,
+    /// Days of history the average is taken over
+    pub lookback_days: i32,
+    /// Lowest automatic threshold (keeps empty slow movers alerting)
+    pub min_threshold: i32,
+    /// Time between recalculations
+    pub interval: Duration,
+}
+
+/// Recalculate automatic thresholds every `policy.interval`, forever
+pub async fn run_recalculation(
+    monitor: LowStockMonitor,
+    db: Database,
+    policy: DynamicThresholdPolicy,
+) -> Result<()> {
+    let mut ticker = tokio::time::interval(policy.interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        let start = Instant::now();
+        let changed = db
+            .recalculate_thresholds(
+                policy.cover_days,
+                policy.lookback_days,
+                policy.min_threshold,
+            )
+            .await?;
+        metrics::record_db_query("threshold_recalc", start.elapsed().as_secs_f64());
+
+        if changed > 0 {
+            tracing::info!(changed, "Low stock thresholds recalculated");
+            // New thresholds change what counts as low stock
+            monitor.evaluate(&db).await?;
+        }
+    }
+}
+
+/// Set the low-stock gauges from an evaluation
+///
+/// `recovered` are the items low in `previous` but not anymore, with their
+/// current available stock.
+fn update_gauges(evaluation: &Evaluation, previous: &[LowStockAlert], recovered: &[(&LowStockAlert, i32)]) {
+    metrics::set_low_stock_count(evaluation.alerts.len() as i64);
+    for alert in &evaluation.alerts {
+        metrics::set_stock_level(&alert.sku, &alert.warehouse, alert.available);
+    }
+    for (alert, available) in recovered {
+        metrics::set_stock_level(&alert.sku, &alert.warehouse, *available);
+    }
+    metrics::set_low_stock_evaluated_at(evaluation.evaluated_at.timestamp());
+
+    // Warehouses that recovered drop to 0 instead of keeping their count
+    let current = count_by_warehouse(&evaluation.alerts);
+    for warehouse in count_by_warehouse(previous).keys().filter(|w| !current.contains_key(*w)) {
+        metrics::set_low_stock_count_by_warehouse(warehouse, 0);
+    }
+    for (warehouse, count) in &current {
+        metrics::set_low_stock_count_by_warehouse(warehouse, *count);
+    }
+}
+
+/// Number of low-stock items per warehouse
+fn count_by_warehouse(alerts: &[LowStockAlert]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for alert in alerts {
+        *counts.entry(alert.warehouse.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Alerts in `current` for a (sku, warehouse) that had none in `previous`
+fn newly_low<'a>(previous: &[LowStockAlert], current: &'a [LowStockAlert]) -> Vec<&'a LowStockAlert> {
+    let before: HashSet<(&str, &str)> = previous
+        .iter()
+        .map(|a| (a.sku.as_str(), a.warehouse.as_str()))
+        .collect();
+    current
+        .iter()
+        .filter(|a| !before.contains(&(a.sku.as_str(), a.warehouse.as_str())))
+        .collect()
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(sku: &str, warehouse: &str) -> LowStockAlert {
+        LowStockAlert {
+            sku: sku.to_string(),
+            name: sku.to_string(),
+            available: 1,
+            threshold: 10,
+            warehouse: warehouse.to_string(),
+        }
+    }
+
+    fn evaluation(alerts: Vec<LowStockAlert>) -> Evaluation {
+        Evaluation {
+            alerts,
+            evaluated_at: Utc::now(),
+        }
+    }
+
+    /// Value of one series in a rendered scrape
+    fn gauge(scrape: &str, series: &str) -> Option<f64> {
+        scrape
+            .lines()
+            .find_map(|line| line.strip_prefix(series)?.strip_prefix(' ')?.parse().ok())
+    }
+
+    #[test]
+    fn test_count_by_warehouse() {
+        let counts = count_by_warehouse(&[
+            alert("A", "JKT-1"),
+            alert("B", "SBY-1"),
+            alert("C", "JKT-1"),
+        ]);
+        assert_eq!(counts.get("JKT-1"), Some(&2));
+        assert_eq!(counts.get("SBY-1"), Some(&1));
+        assert_eq!(counts.len(), 2);
+    }
+
+    #[test]
+    fn test_gauges_follow_crossings_both_ways() {
+        let recorder = metrics_exporter_prometheus::PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let level_a = r#"inventory_stock_level{sku="A",warehouse="JKT-1"}"#;
+        let level_b = r#"inventory_stock_level{sku="B",warehouse="SBY-1"}"#;
+        let low_jkt = r#"inventory_low_stock_items_by_warehouse{warehouse="JKT-1"}"#;
+
+        // A is low against its JKT-1 threshold (20, over its own 5), B in SBY-1
+        let a_low = LowStockAlert {
+            available: 12,
+            threshold: 20,
+            ..alert("A", "JKT-1")
+        };
+        let first = evaluation(vec![a_low.clone(), alert("B", "SBY-1")]);
+        ::metrics::with_local_recorder(&recorder, || update_gauges(&first, &[], &[]));
+        let scrape = handle.render();
+        assert_eq!(gauge(&scrape, level_a), Some(12.0));
+        assert_eq!(gauge(&scrape, low_jkt), Some(1.0));
+        assert_eq!(gauge(&scrape, "inventory_low_stock_items"), Some(2.0));
+
+        // A was restocked to 25 and is no longer low
+        let second = evaluation(vec![alert("B", "SBY-1")]);
+        let recovered = newly_low(&second.alerts, &first.alerts);
+        assert_eq!(recovered.len(), 1);
+        let recovered = vec![(recovered[0], 25)];
+        ::metrics::with_local_recorder(&recorder, || update_gauges(&second, &first.alerts, &recovered));
+        let scrape = handle.render();
+        assert_eq!(gauge(&scrape, level_a), Some(25.0));
+        assert_eq!(gauge(&scrape, level_b), Some(1.0));
+        assert_eq!(gauge(&scrape, low_jkt), Some(0.0));
+        assert_eq!(gauge(&scrape, "inventory_low_stock_items"), Some(1.0));
+
+        // And drops below the JKT-1 threshold again
+        let third = evaluation(vec![LowStockAlert { available: 15, ..a_low }, alert("B", "SBY-1")]);
+        assert!(newly_low(&third.alerts, &second.alerts).is_empty());
+        ::metrics::with_local_recorder(&recorder, || update_gauges(&third, &second.alerts, &[]));
+        let scrape = handle.render();
+        assert_eq!(gauge(&scrape, level_a), Some(15.0));
+        assert_eq!(gauge(&scrape, low_jkt), Some(1.0));
+    }
+
+    #[test]
+    fn test_newly_low() {
+        let previous = [alert("A", "JKT-1"), alert("B", "JKT-1")];
+        let current = [alert("A", "JKT-1"), alert("A", "SBY-1"), alert("C", "JKT-1")];
+
+        let crossed: Vec<(&str, &str)> = newly_low(&previous, &current)
+            .iter()
+            .map(|a| (a.sku.as_str(), a.warehouse.as_str()))
+            .collect();
+        assert_eq!(crossed, vec![("A", "SBY-1"), ("C", "JKT-1")]);
+    }
+}
@@ -21,12 +21,81 @@
 // -----------------------------------------------------------------------------
 // In Rust, we organize code into modules. Each `mod` statement tells the
 // compiler to look for a file or directory with that name.
+mod alert_notifier; // Low-stock alerts pushed to Alertmanager/Slack (alert_notifier.rs)
+mod allocator;   // Global allocator and heap stats (allocator.rs)
+mod analytics;   // Consumption series from the movement ledger (analytics.rs)
+mod attributes;  // Typed catalog attribute schemas and filters (attributes.rs)
+mod audit;       // Audit trail export and SIEM shipping (audit.rs)
+mod cache_headers; // Cache-Control / Last-Modified on reads (cache_headers.rs)
+mod cache_warm;  // Startup cache warming (cache_warm.rs)
+mod capture;     // Request capture for replay fixtures (capture.rs)
+mod catalog_quota; // Soft/hard limits on catalog size (catalog_quota.rs)
+mod chaos;       // Fault injection experiments (chaos.rs)
+mod commitments; // Two-phase prepare/commit/abort (commitments.rs)
 mod config;      // Configuration loading (config.rs)
+mod config_file; // Optional TOML/YAML settings under the env vars (config_file.rs)
 mod db;          // Database operations (db.rs)
+mod db_retry;    // Database retries and circuit breaker (db_retry.rs)
+mod deadline;    // Request deadline propagation (deadline.rs)
+mod distributed_lock; // Redis leases for singleton background jobs (distributed_lock.rs)
 mod handlers;    // HTTP request handlers (handlers.rs)
+mod http_client; // Outbound HTTP calls with retries (http_client.rs)
+mod http_metrics; // Per-route request metrics (http_metrics.rs)
 mod metrics;     // Prometheus metrics setup (metrics.rs)
 mod models;      // Data structures (models.rs)
+mod openapi;     // OpenAPI spec and Swagger UI (openapi.rs)
+mod openmetrics; // OpenMetrics exposition format (openmetrics.rs)
 mod error;       // Error types (error.rs)
+mod events;      // Stock change events to Kafka (events.rs)
+mod export;      // Streamed CSV / NDJSON catalog export (export.rs)
+mod fair_queue;  // Per-SKU FIFO reservation queue (fair_queue.rs)
+mod fairness;    // Per-order and per-client reservation caps (fairness.rs)
+mod fast_reserve; // Redis-first reservations with write-behind (fast_reserve.rs)
+mod features;    // Per-endpoint-group feature flags (features.rs)
+mod graphql;     // GraphQL API at /graphql (graphql.rs)
+mod grpc;        // gRPC API on its own port (grpc/)
+mod i18n;        // Localized error messages (i18n.rs)
+mod identifiers; // Alternate item identifiers (identifiers.rs)
+mod ids;         // Reservation and movement ID generation (ids.rs)
+mod import;      // CSV catalog import (import.rs)
+mod in_memory;   // In-memory item repository, --in-memory (in_memory.rs)
+mod item_cache;  // Per-SKU Redis cache, optional at runtime (item_cache.rs)
+mod list_cache;  // Read-through cache for the list endpoint (list_cache.rs)
+mod live;        // Live stock change stream over SSE (live.rs)
+mod load_shed;   // Concurrency limit with 503 load shedding (load_shed.rs)
+mod loadgen;     // Synthetic database load (loadgen.rs)
+mod low_stock;   // Scheduled low-stock evaluation (low_stock.rs)
+mod pool_metrics; // Connection pool gauges (pool_metrics.rs)
+mod problem;     // Problem Details error bodies, request IDs (problem.rs)
+mod public_mode; // Public read-only mode with rate limits (public_mode.rs)
+mod rate_limit;  // Per-client rate limiting in Redis (rate_limit.rs)
+mod redact;      // Sensitive-field redaction in logs (redact.rs)
+mod remote_write; // Prometheus remote-write pusher (remote_write.rs)
+mod reorder;     // Reorder suggestions and draft purchase orders (reorder.rs)
+mod replay;      // Traffic replay from request logs (replay.rs)
+mod reports;     // Stockout and fill-rate reports (reports.rs)
+mod repository;  // Item storage trait behind the core endpoints (repository.rs)
+mod reservation_expiry; // Expired reservation sweeper (reservation_expiry.rs)
+mod retry_after; // Retry-After on 429/503 responses (retry_after.rs)
+mod sampling;    // Per-route trace sampling (sampling.rs)
+mod selftest;    // End-to-end write path selftest (selftest.rs)
+mod single_flight; // Coalescing of concurrent cache fills (single_flight.rs)
+mod snapshots;   // Inventory snapshots and diffs (snapshots.rs)
+mod startup;     // Config validation, preflight checks, summary (startup.rs)
+mod startup_probe; // /startup progress and dependency retries at boot (startup_probe.rs)
+mod stock_events; // stock.out / stock.back events and webhook (stock_events.rs)
+mod stock_value; // Stock value per warehouse gauges (stock_value.rs)
+mod stockouts;   // Stockout duration tracking (stockouts.rs)
+mod supervisor;  // Background task supervision (supervisor.rs)
+#[cfg(test)]
+mod test_support; // AppState for handler tests (test_support.rs)
+mod tls;         // Optional HTTPS with reload on SIGHUP (tls.rs)
+mod traffic;     // Built-in synthetic API traffic (traffic.rs)
+mod trace_context; // W3C traceparent propagation (trace_context.rs)
+mod usage;       // Per-API-key usage metering (usage.rs)
+mod validation;  // Request body validation, ValidJson extractor (validation.rs)
+mod versioning;  // Item versions, If-Match and ETag (versioning.rs)
+mod webhooks;    // Third-party webhook subscriptions (webhooks.rs)
 
 // -----------------------------------------------------------------------------
 // IMPORTS (use statements)
@@ -35,8 +104,12 @@ mod error;       // Error types (error.rs)
 // other languages.
 
 use axum::{
+    // Request type and route template (used for trace sampling)
+    extract::{MatchedPath, Request},
+    // Middleware helpers for our own request layers
+    middleware,
     // Router is used to define URL routes
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 
@@ -51,12 +124,15 @@ use tower_http::{
 
 // Tracing is Rust's logging framework
 use tracing::info;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
 // Our custom modules
 use crate::config::Config;
 use crate::db::Database;
+use crate::http_client::{HttpClient, HttpClientPolicy};
 use crate::metrics::setup_metrics;
+use crate::redact::Redactor;
+use crate::supervisor::{Backoff, Supervisor};
 
 // -----------------------------------------------------------------------------
 // APPLICATION STATE
@@ -73,6 +149,10 @@ pub struct AppState {
     // Database connection pool
     // Pool manages multiple connections for concurrent requests
     pub db: Database,
+
+    // Storage of the core item endpoints: `db` itself, or memory with
+    // --in-memory (see repository.rs)
+    pub repo: Arc<dyn repository::InventoryRepository>,
     
     // Redis connection for caching
     pub redis: redis::aio::ConnectionManager,
@@ -80,6 +160,60 @@ pub struct AppState {
     // Prometheus metrics handle
     // Used to render metrics in Prometheus format
     pub metrics_handle: metrics_exporter_prometheus::PrometheusHandle,
+
+    // Loaded configuration (timeouts, limits, feature switches)
+    pub config: Config,
+
+    // Owner of all background workers (restarts them when they crash)
+    pub supervisor: Supervisor,
+
+    // When the service started (for uptime reporting)
+    pub started_at: chrono::DateTime<chrono::Utc>,
+
+    // First-seen times of counter series, for OpenMetrics `_created` samples
+    pub metrics_created: Arc<openmetrics::CreatedTracker>,
+
+    // Shared client for every outbound HTTP call (retries, metrics, tracing)
+    pub http: HttpClient,
+
+    // Request recorder, present only when CAPTURE_MODE is on
+    pub capture: Option<capture::Capture>,
+
+    // Admin-triggered synthetic database load
+    pub loadgen: loadgen::LoadGenerator,
+
+    // Running chaos experiments (latency, errors, DB slowness, CPU, memory)
+    pub chaos: chaos::Chaos,
+
+    // Per-SKU FIFO queue for reservations, present when RESERVE_QUEUE_ENABLED
+    pub reserve_queue: Option<Arc<fair_queue::ReserveQueue>>,
+
+    // Redis-first reservation path, present when RESERVE_FAST_PATH is on
+    pub fast_reserve: Option<fast_reserve::FastReserve>,
+
+    // Read-through cache for the first pages of the inventory list
+    pub list_cache: list_cache::ListCache,
+
+    // Item cache fills in flight, shared by concurrent misses of a SKU
+    pub item_fills: single_flight::SingleFlight<item_cache::Fill>,
+
+    // Latest low-stock evaluation (refreshed by a background job)
+    pub low_stock: low_stock::LowStockMonitor,
+
+    // Readiness gate held closed until startup cache warming finishes
+    pub cache_warmer: cache_warm::CacheWarmer,
+
+    // Read-only profile with per-client rate limits, when PUBLIC_READ_ONLY
+    pub public_mode: Option<Arc<public_mode::PublicMode>>,
+
+    // Concurrency limit for API requests, unless MAX_CONCURRENT_REQUESTS=0
+    pub load_shed: Option<Arc<load_shed::LoadShedder>>,
+
+    // Stock changes pushed to GET /api/v1/inventory/stream clients
+    pub live: live::LiveFeed,
+
+    // Boot progress reported by /startup (done once handlers run)
+    pub startup: startup_probe::StartupProgress,
 }
 
 // -----------------------------------------------------------------------------
@@ -100,20 +234,54 @@ async fn main() -> anyhow::Result<()> {
     // This is useful for local development
     dotenvy::dotenv().ok();  // .ok() ignores errors (file might not exist)
 
+    // `inventory-service migrate` (or `--migrate-only`) migrates the
+    // database and exits without serving, for a deploy job that runs before
+    // new pods roll out
+    let migrate_only = std::env::args()
+        .skip(1)
+        .any(|arg| arg == "migrate" || arg == "--migrate-only");
+
+    // `--in-memory` serves the core item endpoints from memory instead of
+    // Postgres, to try the API without a database (see in_memory.rs)
+    let in_memory = std::env::args().skip(1).any(|arg| arg == "--in-memory");
+    if migrate_only && in_memory {
+        anyhow::bail!("--in-memory has no database to migrate");
+    }
+
     // -------------------------------------------------------------------------
     // STEP 2: Initialize logging/tracing
     // -------------------------------------------------------------------------
     // Set up structured logging with JSON output
     // RUST_LOG environment variable controls log levels
     // Example: RUST_LOG=info,inventory_service=debug
+    //
+    // The filter is attached to the JSON layer only (a per-layer filter), so
+    // the optional tokio-console layer still sees Tokio's own trace events.
+    let log_filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| "info,inventory_service=debug".into());
+
+    // tokio-console: with the `console` cargo feature, TOKIO_CONSOLE_ENABLED=true
+    // starts the console-subscriber server (default 127.0.0.1:6669).
+    // Without the feature this is always None.
+    #[cfg(feature = "console")]
+    let console_layer = std::env::var("TOKIO_CONSOLE_ENABLED")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false)
+        .then(console_subscriber::spawn);
+    #[cfg(not(feature = "console"))]
+    let console_layer: Option<tracing_subscriber::layer::Identity> = None;
+
     tracing_subscriber::registry()
-        // Add filter layer (reads RUST_LOG env var)
+        // tokio-console layer (no-op unless enabled)
+        .with(console_layer)
+        // Add JSON formatting layer, filtered by RUST_LOG. Every line goes
+        // through the redactor so LOG_REDACT_FIELDS never reach the logs.
         .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "info,inventory_service=debug".into()),
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(Redactor::from_env())
+                .with_filter(log_filter),
         )
-        // Add JSON formatting layer
-        .with(tracing_subscriber::fmt::layer().json())
         // Initialize as the global default
         .init();
 
@@ -122,11 +290,15 @@ async fn main() -> anyhow::Result<()> {
     // -------------------------------------------------------------------------
     // STEP 3: Load configuration
     // -------------------------------------------------------------------------
-    // Config::from_env() reads environment variables and returns a Config struct
+    // Config::load() reads environment variables (over CONFIG_FILE, if set)
+    // and returns a Config struct
     // The ? operator propagates errors (returns early if there's an error)
-    let config = Config::from_env()?;
+    let config = Config::load()?;
     info!(port = config.port, "Configuration loaded");
 
+    // Report every configuration problem at once
+    startup::validate(&config, !migrate_only)?;
+
     // -------------------------------------------------------------------------
     // STEP 4: Set up Prometheus metrics
     // -------------------------------------------------------------------------
@@ -134,36 +306,439 @@ async fn main() -> anyhow::Result<()> {
     let metrics_handle = setup_metrics()?;
     info!("Prometheus metrics initialized");
 
+    // Bind the port before reaching Postgres and Redis, so probes get an
+    // answer while they come up: /health passes, /startup shows progress
+    // (see startup_probe.rs). A migrate run doesn't listen.
+    // With TLS configured the certificate is loaded first, so the probes
+    // are served over HTTPS too (see tls.rs).
+    let progress = startup_probe::StartupProgress::new();
+    let addr = format!("0.0.0.0:{}", config.port);
+    let rustls = match config.tls.as_ref().filter(|_| !migrate_only) {
+        Some(tls) => Some(tls::load(tls).await?),
+        None => None,
+    };
+    let boot = if migrate_only {
+        None
+    } else {
+        // With ADMIN_PORT the probes are answered there, in plain HTTP
+        let (probe_addr, probe_tls) = match config.admin_port {
+            Some(admin_port) => (format!("0.0.0.0:{}", admin_port), None),
+            None => (addr.clone(), rustls.clone()),
+        };
+        let listener = std::net::TcpListener::bind(&probe_addr)?;
+        listener.set_nonblocking(true)?;
+        let server = startup_probe::BootServer::start(
+            listener.try_clone()?,
+            probe_tls,
+            progress.clone(),
+            metrics_handle.clone(),
+        )?;
+        Some((listener, server))
+    };
+    startup::preflight(&config).await;
+
+    // Connection attempts are retried until STARTUP_CONNECT_DEADLINE_SECS
+    let connect_timeout = std::time::Duration::from_secs(config.startup_check_timeout_secs.max(1));
+    let connect_deadline = (config.startup_connect_deadline_secs > 0)
+        .then(|| std::time::Duration::from_secs(config.startup_connect_deadline_secs));
+
     // -------------------------------------------------------------------------
     // STEP 5: Connect to PostgreSQL database
     // -------------------------------------------------------------------------
     // Database::connect() creates a connection pool
     // Connection pools reuse connections for better performance
-    let db = Database::connect(&config.database_url).await?;
-    info!("Connected to PostgreSQL");
+    let db = if in_memory {
+        progress.complete("postgres");
+        Database::connect_lazy(&config.database_url)?
+    } else {
+        startup_probe::connect_with_retry(&progress, "postgres", connect_timeout, connect_deadline, || async {
+            startup_probe::ping_postgres(&config.database_url).await?;
+            Database::connect(&config.database_url).await
+        })
+        .await?
+    };
+    let db = db
+        .with_reserve_strategy(config.reserve_strategy)
+        .with_id_generator(ids::IdGenerator::new(config.id_format, config.snowflake_node_id))
+        .with_replicas(&config.database_replica_urls)?
+        .with_retry(config.db_retry);
+    info!(
+        reserve_strategy = config.reserve_strategy.as_str(),
+        id_format = config.id_format.as_str(),
+        replicas = db.replica_count(),
+        lazy = in_memory,
+        "Connected to PostgreSQL"
+    );
+
+    // Apply the migrations embedded from migrations/ (see db.rs).
+    // With AUTO_MIGRATE=false a separate `migrate` run does this before the
+    // rollout, and pods only check that it happened.
+    progress.begin("migrations");
+    if in_memory {
+        info!("Skipping migrations (--in-memory)");
+    } else if migrate_only || config.auto_migrate {
+        db.run_migrations().await?;
+        info!(
+            schema_version = db::latest_migration_version(),
+            "Database migrations completed"
+        );
+    } else {
+        let (applied, pending) = db.pending_migrations().await?;
+        if let Some(first) = pending.first() {
+            anyhow::bail!(
+                "{} migration(s) not applied, starting with {} ({}); run `inventory-service migrate` first",
+                pending.len(),
+                first.version,
+                first.description
+            );
+        }
+        info!(schema_version = applied, "Skipping migrations (AUTO_MIGRATE=false)");
+    }
+    progress.complete("migrations");
 
-    // Run database migrations (create tables if they don't exist)
-    db.run_migrations().await?;
-    info!("Database migrations completed");
+    if migrate_only {
+        info!("Exiting after migrations (--migrate-only)");
+        return Ok(());
+    }
+
+    let repo: Arc<dyn repository::InventoryRepository> = if in_memory {
+        tracing::warn!("Serving items from memory (--in-memory); nothing is kept across restarts");
+        Arc::new(in_memory::InMemoryRepository::new(db.ids().clone()))
+    } else {
+        Arc::new(db.clone())
+    };
+
+    // Seed the catalog size gauge; creates keep it current from here on
+    let catalog_skus = repo.count_items().await?;
+    crate::metrics::set_catalog_size(catalog_skus, &config.catalog_quota);
+    if config.catalog_quota.status(catalog_skus) != catalog_quota::QuotaStatus::Within {
+        tracing::warn!(
+            skus = catalog_skus,
+            soft_limit = config.catalog_quota.soft_limit,
+            hard_limit = config.catalog_quota.hard_limit,
+            "Catalog is already past its quota"
+        );
+    }
 
     // -------------------------------------------------------------------------
     // STEP 6: Connect to Redis
     // -------------------------------------------------------------------------
-    // ConnectionManager handles reconnection automatically
+    // ConnectionManager handles reconnection automatically, once it has
+    // connected the first time
     let redis_client = redis::Client::open(config.redis_url.as_str())?;
-    let redis_conn = redis::aio::ConnectionManager::new(redis_client).await?;
+    let redis_conn = startup_probe::connect_with_retry(&progress, "redis", connect_timeout, connect_deadline, || {
+        let client = redis_client.clone();
+        async move { Ok(redis::aio::ConnectionManager::new(client).await?) }
+    })
+    .await?;
     info!("Connected to Redis");
+    progress.begin("app");
 
     // -------------------------------------------------------------------------
     // STEP 7: Create application state
     // -------------------------------------------------------------------------
-    // Arc wraps the state so it can be safely shared across request handlers
+    // The supervisor owns background workers; they are registered on it
+    // so crashes get restarted and show up under /api/v1/admin/tasks
+    let supervisor = Supervisor::new(Backoff {
+        base: std::time::Duration::from_millis(config.supervisor_backoff_base_ms),
+        max: std::time::Duration::from_millis(config.supervisor_backoff_max_ms),
+    });
+
+    // One pooled client for all outbound HTTP calls
+    let http = HttpClient::new(HttpClientPolicy {
+        timeout: std::time::Duration::from_millis(config.http_client_timeout_ms),
+        max_retries: config.http_client_max_retries,
+        backoff_base: std::time::Duration::from_millis(config.http_client_backoff_base_ms),
+    })?;
+
+    // Jobs that poll Postgres don't run with --in-memory: there is no
+    // database behind them, only errors every interval.
+    //
+    // Ship audit events to a syslog collector, if one is configured
+    if let Some(addr) = config.audit_syslog_addr.clone().filter(|_| !in_memory) {
+        let db = db.clone();
+        let interval = std::time::Duration::from_secs(config.audit_ship_interval_secs.max(1));
+        info!(address = %addr, "Audit syslog shipping enabled");
+        supervisor.spawn("audit-syslog-shipper", move || {
+            audit::run_syslog_shipper(db.clone(), addr.clone(), interval)
+        });
+    }
+
+    // ...and/or to an HTTP collector
+    if let Some(url) = config.audit_http_url.clone().filter(|_| !in_memory) {
+        let db = db.clone();
+        let http = http.clone();
+        let interval = std::time::Duration::from_secs(config.audit_ship_interval_secs.max(1));
+        info!("Audit HTTP shipping enabled");
+        supervisor.spawn("audit-http-shipper", move || {
+            audit::run_http_shipper(db.clone(), http.clone(), url.clone(), interval)
+        });
+    }
+
+    // Record API requests for later replay, if capture mode is on
+    let capture = config.capture.clone().map(|target| {
+        let capture = capture::Capture::new(capture::Sanitizer::new(
+            Redactor::from_env(),
+            config.capture_pseudonym_key.clone(),
+        ));
+        let writer = capture.clone();
+        let redis = redis_conn.clone();
+        info!(target = ?target, "Request capture enabled");
+        supervisor.spawn("request-capture", move || {
+            capture::run_writer(writer.clone(), target.clone(), redis.clone())
+        });
+        capture
+    });
+
+    // Redis-first reservations: write-behind worker and reconciler. Only
+    // with a Redis that never evicts the queue or the counters.
+    let fast_path_safe = if config.reserve_fast_path {
+        match fast_reserve::check_eviction_policy(&redis_conn).await {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::error!(error = %e, "Reservation fast path left off (RESERVE_FAST_PATH)");
+                false
+            }
+        }
+    } else {
+        false
+    };
+    let fast_reserve = fast_path_safe.then(|| {
+        let fast = fast_reserve::FastReserve::new(redis_conn.clone());
+        let interval =
+            std::time::Duration::from_secs(config.reserve_fast_path_reconcile_secs.max(1));
+        info!("Reservation fast path enabled (Redis-first, write-behind)");
+
+        let (worker, worker_db) = (fast.clone(), db.clone());
+        supervisor.spawn("reserve-write-behind", move || {
+            worker.clone().run_write_behind(worker_db.clone())
+        });
+        let (reconciler, reconciler_db) = (fast.clone(), db.clone());
+        supervisor.spawn("reserve-reconciler", move || {
+            reconciler.clone().run_reconciler(reconciler_db.clone(), interval)
+        });
+        fast
+    });
+
+    // Publish stock change events to Kafka, if configured
+    let event_publisher = match &config.kafka {
+        Some(target) => {
+            let publisher = events::EventPublisher::new(target)?;
+            info!(
+                brokers = %target.brokers,
+                topic = %target.topic,
+                "Kafka event publishing enabled"
+            );
+            let (db, publisher_job) = (db.clone(), publisher.clone());
+            let interval = target.interval;
+            if !in_memory {
+                supervisor.spawn("kafka-event-publisher", move || {
+                    events::run_publisher(db.clone(), publisher_job.clone(), interval)
+                });
+            }
+            Some(publisher)
+        }
+        None => None,
+    };
+
+    // Keep low-stock gauges current even when nobody calls /alerts
+    let low_stock = match event_publisher {
+        Some(publisher) => low_stock::LowStockMonitor::with_events(publisher),
+        None => low_stock::LowStockMonitor::default(),
+    };
+    let low_stock = match config.low_stock_alerts.clone() {
+        Some(target) => {
+            info!(format = target.format.as_str(), "Low stock alert notifications enabled");
+            low_stock.with_notifier(alert_notifier::AlertNotifier::new(http.clone(), target))
+        }
+        None => low_stock,
+    };
+    if !in_memory {
+        let (monitor, db) = (low_stock.clone(), db.clone());
+        let interval = std::time::Duration::from_secs(config.low_stock_eval_interval_secs.max(1));
+        supervisor.spawn("low-stock-evaluator", move || {
+            monitor.clone().run(db.clone(), interval)
+        });
+    }
+    // Pool gauges, to tell a saturated pool from a slow query on dashboards
+    {
+        let (db, redis) = (db.clone(), redis_conn.clone());
+        let interval = std::time::Duration::from_secs(config.pool_metrics_interval_secs.max(1));
+        supervisor.spawn("pool-sampler", move || {
+            pool_metrics::run_sampler(db.clone(), redis.clone(), interval)
+        });
+    }
+    if let Some(policy) = config.dynamic_thresholds.filter(|_| !in_memory) {
+        let (monitor, db) = (low_stock.clone(), db.clone());
+        info!(?policy, "Dynamic low stock thresholds enabled");
+        supervisor.spawn("threshold-recalculator", move || {
+            low_stock::run_recalculation(monitor.clone(), db.clone(), policy)
+        });
+    }
+
+    // Emit stock.out / stock.back events (logged, and POSTed if configured)
+    if !in_memory {
+        let (db, http) = (db.clone(), http.clone());
+        let url = config.stock_events_webhook_url.clone();
+        let interval = std::time::Duration::from_millis(config.stock_events_interval_ms.max(100));
+        if url.is_some() {
+            info!("Stock event webhook enabled");
+        }
+        supervisor.spawn("stock-event-dispatcher", move || {
+            stock_events::run_dispatcher(db.clone(), http.clone(), url.clone(), interval)
+        });
+    }
+
+    // Deliver reservation and adjustment events to webhook subscribers
+    if !in_memory {
+        let (db, http) = (db.clone(), http.clone());
+        let interval =
+            std::time::Duration::from_millis(config.webhook_dispatch_interval_ms.max(100));
+        supervisor.spawn("webhook-dispatcher", move || {
+            webhooks::run_dispatcher(db.clone(), http.clone(), interval)
+        });
+    }
+
+    // Give back stock held by reservations past their expiry
+    if !in_memory {
+        let (db, redis, fast) = (db.clone(), redis_conn.clone(), fast_reserve.clone());
+        let interval =
+            std::time::Duration::from_secs(config.reservation_expiry_interval_secs.max(1));
+        supervisor.spawn("reservation-expiry-sweeper", move || {
+            reservation_expiry::run_sweeper(db.clone(), redis.clone(), fast.clone(), interval)
+        });
+    }
+
+    // Release two-phase commitments nobody committed in time
+    if !in_memory {
+        let (db, redis, fast) = (db.clone(), redis_conn.clone(), fast_reserve.clone());
+        let interval =
+            std::time::Duration::from_secs(config.commitment_sweep_interval_secs.max(1));
+        supervisor.spawn("commitment-timeout-sweeper", move || {
+            commitments::run_sweeper(db.clone(), redis.clone(), fast.clone(), interval)
+        });
+    }
+
+    // Track how long SKUs stay out of stock
+    if !in_memory {
+        let db = db.clone();
+        let interval = std::time::Duration::from_secs(config.stockout_track_interval_secs.max(1));
+        supervisor.spawn("stockout-tracker", move || {
+            stockouts::run_tracker(db.clone(), interval)
+        });
+    }
+
+    // Recompute what the stock held at each warehouse is worth
+    if !in_memory {
+        let db = db.clone();
+        let interval = std::time::Duration::from_secs(config.stock_value_interval_secs.max(1));
+        supervisor.spawn("stock-valuer", move || {
+            stock_value::run_valuer(db.clone(), interval)
+        });
+    }
+
+    // Push metrics to Prometheus / Mimir for pods nobody can scrape
+    if let Some(target) = config.remote_write.clone() {
+        let (handle, http) = (metrics_handle.clone(), http.clone());
+        info!(url = %target.url, interval = ?target.interval, "Remote write enabled");
+        supervisor.spawn("remote-write-pusher", move || {
+            remote_write::run_pusher(handle.clone(), http.clone(), target.clone())
+        });
+    }
+
+    // Roll per-key API usage up from Redis into Postgres
+    if config.usage_metering && !in_memory {
+        let (db, redis) = (db.clone(), redis_conn.clone());
+        let interval = std::time::Duration::from_secs(config.usage_rollup_interval_secs.max(1));
+        supervisor.spawn("usage-rollup", move || {
+            usage::run_rollup(db.clone(), redis.clone(), interval)
+        });
+    }
+
+    // Synthetic read/reserve/release traffic against our own HTTP port
+    if let Some(load_gen) = config.load_gen {
+        let (db, http) = (db.clone(), http.loopback(config.tls.is_some())?);
+        let base_url = tls::loopback_url(config.port, config.tls.as_ref());
+        info!(rps = load_gen.rps, mix = ?load_gen.mix, "Traffic generator enabled");
+        supervisor.spawn("traffic-generator", move || {
+            traffic::run(http.clone(), db.clone(), base_url.clone(), load_gen)
+        });
+    }
+
+    // Warm the item cache once; /ready waits for it (not supervised: it
+    // runs to completion instead of forever)
+    let cache_warmer = cache_warm::CacheWarmer::new(config.cache_warm.is_some());
+    if let Some(strategy) = config.cache_warm {
+        info!(strategy = strategy.as_str(), skus = config.cache_warm_skus, "Cache warming enabled");
+        tokio::spawn(cache_warmer.clone().run(
+            db.clone(),
+            redis_conn.clone(),
+            strategy,
+            config.cache_warm_skus,
+        ));
+    }
+
+    let disabled_endpoints = config.features.disabled_names();
+    if !disabled_endpoints.is_empty() {
+        info!(groups = ?disabled_endpoints, "Endpoint groups disabled");
+    }
+
     let state = Arc::new(AppState {
         db,
+        repo,
         redis: redis_conn,
         metrics_handle,
+        config: config.clone(),
+        supervisor,
+        started_at: chrono::Utc::now(),
+        metrics_created: Arc::new(openmetrics::CreatedTracker::default()),
+        http,
+        capture,
+        loadgen: loadgen::LoadGenerator::default(),
+        chaos: chaos::Chaos::default(),
+        reserve_queue: config.reserve_queue_enabled.then(|| {
+            Arc::new(fair_queue::ReserveQueue::new(
+                std::time::Duration::from_millis(config.reserve_queue_max_wait_ms),
+                config.reserve_queue_max_depth,
+            ))
+        }),
+        fast_reserve,
+        list_cache: list_cache::ListCache::new(
+            config.list_cache_ttl_secs,
+            config.list_cache_max_page,
+        ),
+        item_fills: single_flight::SingleFlight::default(),
+        low_stock,
+        cache_warmer,
+        public_mode: config.public_mode.map(|public| {
+            info!(
+                rate_per_sec = public.rate_per_sec,
+                burst = public.burst,
+                "Public read-only mode enabled"
+            );
+            Arc::new(public_mode::PublicMode::new(public))
+        }),
+        load_shed: config.load_shed.map(|limit| Arc::new(load_shed::LoadShedder::new(limit))),
+        live: live::LiveFeed::new(config.live_stream_buffer),
+        startup: progress.clone(),
     });
 
+    // Serve the gRPC API next to REST, on its own port
+    if config.grpc_enabled {
+        let grpc_state = state.clone();
+        let addr = std::net::SocketAddr::from(([0, 0, 0, 0], config.grpc_port));
+        state.supervisor.spawn("grpc-server", move || {
+            grpc::serve(grpc_state.clone(), addr)
+        });
+    }
+
+    // Pick up renewed certificates on SIGHUP
+    if let (Some(tls), Some(rustls)) = (config.tls.clone(), rustls.clone()) {
+        state.supervisor.spawn("tls-reloader", move || {
+            tls::run_reloader(tls.clone(), rustls.clone())
+        });
+    }
+
     // -------------------------------------------------------------------------
     // STEP 8: Define routes
     // -------------------------------------------------------------------------
@@ -172,56 +747,379 @@ async fn main() -> anyhow::Result<()> {
     // LEARNING NOTE:
     // Axum uses a type-safe routing system. The handler function signatures
     // determine what data is extracted from requests automatically.
-    let app = Router::new()
+
+    // Sampling rules are moved into the trace layer's span factory below
+    let trace_sampling = config.trace_sampling.clone();
+
+    // Probes, metrics, debug, admin and chaos endpoints: on ADMIN_PORT when
+    // it is set, next to the API otherwise
+    let admin_routes = Router::new()
         // ----- Health & Readiness Endpoints -----
         // These are used by Kubernetes/Docker for health checks
         .route("/health", get(handlers::health_check))
         .route("/ready", get(handlers::readiness_check))
+        .route("/startup", get(handlers::startup_check))
         
         // ----- Metrics Endpoint -----
         // Prometheus scrapes this endpoint to collect metrics
         .route("/metrics", get(handlers::metrics_handler))
-        
+
+        // ----- Debug Endpoints -----
+        // Allocator heap statistics (populated with the `jemalloc` feature)
+        .route("/debug/allocator", get(handlers::debug_allocator))
+        // Internal counters: pools, cache, background tasks
+        .route("/debug/vars", get(handlers::debug_vars))
+
+        // ----- Admin Endpoints -----
+        .route("/api/v1/admin/tasks", get(handlers::background_tasks))
+        .route("/api/v1/admin/audit/export", get(handlers::export_audit_events))
+        .route("/api/v1/admin/usage", get(handlers::usage_report))
+        .route(
+            "/api/v1/admin/webhooks",
+            get(handlers::list_webhook_subscriptions).post(handlers::create_webhook_subscription),
+        )
+        .route(
+            "/api/v1/admin/webhooks/:id",
+            delete(handlers::delete_webhook_subscription),
+        )
+        .route("/api/v1/admin/attribute-schemas", get(handlers::list_attribute_schemas))
+        .route(
+            "/api/v1/admin/attribute-schemas/:name",
+            put(handlers::put_attribute_schema).delete(handlers::delete_attribute_schema),
+        )
+        .route("/api/v1/admin/replay", post(handlers::start_replay))
+        .route(
+            "/api/v1/admin/reconcile-reservations",
+            post(handlers::reconcile_reservations),
+        )
+        .route("/api/v1/admin/migrations", get(handlers::migration_status))
+        .route("/api/v1/admin/selftest", post(handlers::run_selftest))
+        .route(
+            "/api/v1/admin/load/db",
+            post(handlers::start_db_load)
+                .get(handlers::db_load_status)
+                .delete(handlers::stop_db_load),
+        )
+        .route(
+            chaos::PATH,
+            post(handlers::start_chaos)
+                .get(handlers::list_chaos)
+                .delete(handlers::stop_all_chaos),
+        )
+        .route("/api/v1/chaos/:id", delete(handlers::stop_chaos));
+
+    // API documentation and GraphQL with its editor: on ADMIN_PORT when it
+    // is set (the public port then keeps a POST /graphql without
+    // introspection), next to the API otherwise
+    let docs_routes = Router::new()
+        // ----- API Documentation -----
+        // Generated OpenAPI spec, and Swagger UI to browse it
+        .route("/api-docs/openapi.json", get(openapi::openapi_json))
+        .route("/swagger", get(openapi::swagger_ui))
+
+        // ----- GraphQL API -----
+        // Items, movements, reservations and stock changes in one round trip;
+        // GET serves the GraphiQL editor
+        .route(graphql::PATH, get(graphql::graphiql).post(graphql::execute));
+
+    // The API: /api/v1
+    let api_routes = Router::new()
         // ----- Inventory API Endpoints -----
         // RESTful API for inventory management
-        .route("/api/v1/inventory", get(handlers::list_inventory))
-        .route("/api/v1/inventory/:sku", get(handlers::get_item))
+        .route(
+            "/api/v1/inventory",
+            get(handlers::list_inventory).post(handlers::create_item),
+        )
+        .route("/api/v1/inventory/lookup", get(handlers::lookup_item))
+        .route(
+            "/api/v1/inventory/:sku",
+            get(handlers::get_item).patch(handlers::update_item),
+        )
+        .route("/api/v1/inventory/:sku/policy", put(handlers::set_reservation_policy))
+        .route("/api/v1/inventory/:sku/catalog", put(handlers::set_catalog_details))
+        .route("/api/v1/inventory/:sku/movements", get(handlers::list_stock_movements))
+        .route("/api/v1/inventory/:sku/revisions", get(handlers::list_item_revisions))
+        .route("/api/v1/inventory/:sku/warehouses", get(handlers::list_warehouse_stock))
+        .route(
+            "/api/v1/inventory/:sku/revisions/:id/revert",
+            post(handlers::revert_item_revision),
+        )
+        .route(
+            "/api/v1/inventory/:sku/thresholds",
+            get(handlers::list_warehouse_thresholds).put(handlers::set_low_stock_threshold),
+        )
+        .route(
+            "/api/v1/inventory/:sku/thresholds/:warehouse",
+            put(handlers::set_warehouse_threshold).delete(handlers::delete_warehouse_threshold),
+        )
+        .route(
+            "/api/v1/inventory/:sku/identifiers",
+            get(handlers::list_item_identifiers).post(handlers::add_item_identifier),
+        )
+        .route(
+            "/api/v1/inventory/:sku/identifiers/:identifier",
+            delete(handlers::delete_item_identifier),
+        )
         .route("/api/v1/inventory/reserve", post(handlers::reserve_stock))
+        .route("/api/v1/inventory/reserve/batch", post(handlers::reserve_batch))
         .route("/api/v1/inventory/release", post(handlers::release_stock))
+        .route("/api/v1/inventory/prepare", post(handlers::prepare_commitment))
+        .route("/api/v1/inventory/commit", post(handlers::commit_commitment))
+        .route("/api/v1/inventory/abort", post(handlers::abort_commitment))
         .route("/api/v1/inventory/adjust", post(handlers::adjust_stock))
+        .route("/api/v1/inventory/transfer", post(handlers::transfer_stock))
+        .route("/api/v1/inventory/bulk-update", post(handlers::bulk_update))
+        .route("/api/v1/inventory/import", post(handlers::import_items))
+        .route("/api/v1/inventory/export", get(handlers::export_inventory))
+        .route("/api/v1/inventory/stream", get(handlers::stream_inventory))
         .route("/api/v1/inventory/alerts", get(handlers::low_stock_alerts))
-        
-        // ----- Middleware Layers -----
-        // Layers wrap the entire application and process every request
-        
-        // CORS layer: Allow cross-origin requests
-        // This is necessary for the frontend to call this API
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)  // Allow any origin (configure for production!)
-                .allow_methods(Any) // Allow any HTTP method
-                .allow_headers(Any), // Allow any headers
+        .route(
+            "/api/v1/inventory/reorder-suggestions",
+            get(handlers::reorder_suggestions).post(handlers::draft_purchase_orders),
+        )
+        .route("/api/v1/purchase-orders", get(handlers::list_purchase_orders))
+        .route("/api/v1/reservations", get(handlers::list_reservations))
+        .route(
+            "/api/v1/reservations/cancel-by-order",
+            post(handlers::cancel_order_reservations),
+        )
+
+        // ----- Warehouses -----
+        .route(
+            "/api/v1/warehouses",
+            get(handlers::list_warehouses).post(handlers::create_warehouse),
+        )
+        .route(
+            "/api/v1/warehouses/:code",
+            get(handlers::get_warehouse)
+                .put(handlers::update_warehouse)
+                .delete(handlers::delete_warehouse),
         )
+        .route("/api/v1/warehouses/:code/summary", get(handlers::warehouse_summary))
+
+        // ----- Reports -----
+        .route("/api/v1/reports/stockouts", get(handlers::stockout_report))
+        .route("/api/v1/stats", get(handlers::stats))
+        .route("/api/v1/analytics/consumption", get(handlers::consumption_series))
+        .route("/api/v1/analytics/top-movers", get(handlers::top_movers))
+        .route(
+            "/api/v1/snapshots",
+            get(handlers::list_snapshots).post(handlers::create_snapshot),
+        )
+        .route("/api/v1/snapshots/diff", get(handlers::snapshot_diff))
+
+        // ----- Integration Endpoints -----
+        // Order status changes over HTTP (for labs without Kafka)
+        .route(
+            "/api/v1/integrations/orders/callback",
+            post(handlers::order_status_callback),
+        );
+
+    // With ADMIN_PORT the admin routes get a router of their own (served in
+    // STEP 9); without it everything stays on PORT
+    let (api_routes, admin_routes) = match config.admin_port {
+        Some(_) => (
+            api_routes.route(graphql::PATH, post(graphql::execute_without_introspection)),
+            Some(admin_routes.merge(docs_routes)),
+        ),
+        None => (api_routes.merge(docs_routes).merge(admin_routes), None),
+    };
+
+    // Request span, subject to per-route sampling. Unsampled requests get a
+    // disabled span, so nothing is recorded.
+    let make_span = move |request: &Request| {
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str())
+            .unwrap_or_else(|| request.uri().path());
+
+        if trace_sampling.should_sample(route) {
+            tracing::info_span!(
+                "request",
+                method = %request.method(),
+                route = %route,
+                uri = %request.uri(),
+            )
+        } else {
+            tracing::Span::none()
+        }
+    };
+
+    // The public router: every check and fault injection applies
+    let with_layers = |routes: Router<Arc<AppState>>| {
+        routes
+            // ----- Middleware Layers -----
+            // Layers wrap the entire application and process every request
         
-        // Trace layer: Log every request
-        .layer(TraceLayer::new_for_http())
+            // Chaos layer (innermost): Faults of running chaos experiments, so
+            // injected latency and errors show up like real ones in every layer
+            // around it
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                chaos::inject,
+            ))
+
+            // Usage layer: Count requests and reserved units per X-API-Key, for
+            // requests that get past the checks below
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                usage::meter,
+            ))
+
+            // Public mode layer: Read-only, rate-limited access (PUBLIC_READ_ONLY).
+            // Inside CORS, so rejections still carry CORS headers for browsers.
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                public_mode::enforce,
+            ))
+
+            // Rate limit layer: Per-client token buckets shared through Redis
+            // (RATE_LIMIT_ENABLED); 429 RATE_LIMITED past the limit
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                rate_limit::enforce,
+            ))
+
+            // Cache headers layer: Cache-Control and Vary on successful reads,
+            // 304 for If-Modified-Since. Outside public mode, whose own
+            // Cache-Control wins.
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                cache_headers::apply_cache_headers,
+            ))
+
+            // Feature layer: Endpoint groups switched off via DISABLED_ENDPOINTS
+            // answer 403 FEATURE_DISABLED
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                features::enforce,
+            ))
+
+            // CORS layer: Allow cross-origin requests
+            // This is necessary for the frontend to call this API
+            .layer(
+                CorsLayer::new()
+                    .allow_origin(Any)  // Allow any origin (configure for production!)
+                    .allow_methods(Any) // Allow any HTTP method
+                    .allow_headers(Any), // Allow any headers
+            )
         
-        // Share application state with all handlers
-        // with_state() makes state available via State<Arc<AppState>> extractor
-        .with_state(state);
+            // Capture layer: Record API requests for replay (CAPTURE_MODE)
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                capture::capture_requests,
+            ))
+
+            // Load shed layer: At most MAX_CONCURRENT_REQUESTS API requests at
+            // once; 503 when no slot frees up. Inside the deadline, so waiting
+            // for a slot uses up the request's budget.
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                load_shed::enforce,
+            ))
+
+            // Deadline layer: Honor X-Request-Deadline / grpc-timeout and
+            // answer 504 once the budget runs out
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                deadline::propagate_deadline,
+            ))
+
+            // Retry-After layer: Every 429/503 tells clients when to retry
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                retry_after::ensure_retry_after,
+            ))
+
+            // Trace layer: Create a request span (see make_span)
+            .layer(TraceLayer::new_for_http().make_span_with(make_span.clone()))
+
+            // HTTP metrics layer: Count and time every request by route template
+            // and final status, including responses from the layers above
+            .layer(middleware::from_fn(http_metrics::track_requests))
+
+            // Trace context layer: Remember the caller's traceparent so outbound
+            // calls made while handling the request continue the same trace
+            .layer(middleware::from_fn(trace_context::capture_trace_context))
+
+            // Problem Details layer: Assign the request ID (X-Request-Id) and
+            // pick the error body format from Accept / PROBLEM_JSON
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                problem::negotiate,
+            ))
+
+            // Language layer (outermost): Negotiate Accept-Language so every
+            // error response, including those from the layers above, is localized
+            .layer(middleware::from_fn(i18n::negotiate_language))
+        
+            // Share application state with all handlers
+            // with_state() makes state available via State<Arc<AppState>> extractor
+            .with_state(state.clone())
+    };
+    let app = with_layers(api_routes);
+
+    // The admin router is only traced and measured: probes, scrapes and
+    // operators must get through whatever chaos, rate limits or public mode
+    // are doing to the API
+    let admin_app = admin_routes.map(|routes| {
+        routes
+            .layer(TraceLayer::new_for_http().make_span_with(make_span.clone()))
+            .layer(middleware::from_fn(http_metrics::track_requests))
+            .layer(middleware::from_fn(trace_context::capture_trace_context))
+            .with_state(state.clone())
+    });
 
     // -------------------------------------------------------------------------
     // STEP 9: Start the HTTP server
     // -------------------------------------------------------------------------
-    // Bind to all network interfaces (0.0.0.0) on the configured port
-    let addr = format!("0.0.0.0:{}", config.port);
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    // The port was bound at boot (0.0.0.0, all network interfaces); the
+    // full router takes it over from the startup probe router. With
+    // ADMIN_PORT that port is the admin one, and PORT opens only now.
+    startup::log_summary(&config);
+    let Some((boot_listener, boot_server)) = boot else {
+        unreachable!("--migrate-only returns after migrating");
+    };
+    progress.complete("app");
+    boot_server.stop().await;
+
+    // Connect info gives middleware the peer address (public mode limits)
+    let listener = match admin_app {
+        Some(admin_app) => {
+            let admin_app = admin_app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+            state.supervisor.spawn("admin-server", move || {
+                let listener = boot_listener.try_clone();
+                let admin_app = admin_app.clone();
+                async move {
+                    let listener = tokio::net::TcpListener::from_std(listener?)?;
+                    axum::serve(listener, admin_app).await?;
+                    Ok(())
+                }
+            });
+            info!(port = config.admin_port, "Admin endpoints are listening");
+
+            let listener = std::net::TcpListener::bind(&addr)?;
+            listener.set_nonblocking(true)?;
+            listener
+        }
+        None => boot_listener,
+    };
     
-    info!(address = %addr, "Inventory Service is listening");
+    info!(address = %addr, tls = rustls.is_some(), "Inventory Service is listening");
     
     // Start accepting connections
     // This runs forever until the process is terminated
-    axum::serve(listener, app).await?;
+    let app = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+    match rustls {
+        Some(rustls) => {
+            axum_server::from_tcp_rustls(listener, rustls).serve(app).await?;
+        }
+        None => {
+            let listener = tokio::net::TcpListener::from_std(listener)?;
+            axum::serve(listener, app).await?;
+        }
+    }
 
     Ok(())
 }
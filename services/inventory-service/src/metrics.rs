@@ -19,6 +19,9 @@ use anyhow::Result;
 use metrics::{counter, gauge, histogram, describe_counter, describe_gauge, describe_histogram};
 use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
 
+use crate::catalog_quota::CatalogQuota;
+use crate::models::{CommitmentState, PoolStats, SalesChannel};
+
 // =============================================================================
 // METRIC NAMES (Constants)
 // =============================================================================
@@ -31,24 +34,77 @@ use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
 // - Be descriptive but not too long
 
 /// HTTP request counter
-/// Labels: method (GET/POST), endpoint (/api/v1/inventory), status (200/500)
+/// Labels: method (GET/POST), endpoint (route template, e.g.
+/// /api/v1/inventory/:sku), status (200/500)
 pub const HTTP_REQUESTS_TOTAL: &str = "http_requests_total";
 
 /// HTTP request duration histogram
 /// Labels: method, endpoint
 pub const HTTP_REQUEST_DURATION_SECONDS: &str = "http_request_duration_seconds";
 
+/// API requests being handled under the concurrency limit
+pub const HTTP_REQUESTS_IN_FLIGHT: &str = "http_requests_in_flight";
+
+/// API requests refused with 503 because the concurrency limit was reached
+/// Labels: endpoint (route template)
+pub const HTTP_REQUESTS_SHED_TOTAL: &str = "http_requests_shed_total";
+
+/// gRPC call counter
+/// Labels: method (ListInventory, ReserveStock, ...), code (OK, NOT_FOUND, ...)
+pub const GRPC_REQUESTS_TOTAL: &str = "grpc_requests_total";
+
+/// gRPC call duration histogram
+/// Labels: method
+pub const GRPC_REQUEST_DURATION_SECONDS: &str = "grpc_request_duration_seconds";
+
 /// Inventory stock level gauge
 /// Labels: sku, warehouse
 pub const INVENTORY_STOCK_LEVEL: &str = "inventory_stock_level";
 
 /// Inventory reservations counter
-/// Labels: sku, status (success/failed)
+/// Labels: sku, channel (web/pos/b2b/unknown), status (success/failed)
 pub const INVENTORY_RESERVATIONS_TOTAL: &str = "inventory_reservations_total";
 
+/// Expired reservations released by the sweeper
+/// Labels: sku
+pub const INVENTORY_RESERVATIONS_EXPIRED_TOTAL: &str = "inventory_reservations_expired_total";
+
+/// Soft holds bumped by higher-priority reservations
+/// Labels: sku
+pub const INVENTORY_RESERVATIONS_BUMPED_TOTAL: &str = "inventory_reservations_bumped_total";
+
+/// Two-phase commitment steps applied
+/// Labels: outcome (prepared/committed/aborted/expired)
+pub const INVENTORY_COMMITMENTS_TOTAL: &str = "inventory_commitments_total";
+
 /// Low stock items gauge (current count of items below threshold)
 pub const INVENTORY_LOW_STOCK_ITEMS: &str = "inventory_low_stock_items";
 
+/// Items below their low-stock threshold, per warehouse
+/// Labels: warehouse
+pub const INVENTORY_LOW_STOCK_ITEMS_BY_WAREHOUSE: &str = "inventory_low_stock_items_by_warehouse";
+
+/// Seconds each SKU has spent with no available stock
+/// Labels: sku
+pub const INVENTORY_STOCKOUT_SECONDS_TOTAL: &str = "inventory_stockout_seconds_total";
+
+/// SKUs currently without available stock
+pub const INVENTORY_CURRENT_STOCKOUTS: &str = "inventory_current_stockouts";
+
+/// Value at cost (quantity × unit_cost) of the stock held at a warehouse
+/// Labels: warehouse
+pub const INVENTORY_STOCK_VALUE: &str = "inventory_stock_value";
+
+/// SKUs in the catalog
+pub const INVENTORY_CATALOG_SKUS: &str = "inventory_catalog_skus";
+
+/// Configured catalog size limits
+/// Labels: kind (soft/hard)
+pub const INVENTORY_CATALOG_SKU_LIMIT: &str = "inventory_catalog_sku_limit";
+
+/// Items created past the catalog's soft limit
+pub const INVENTORY_CATALOG_QUOTA_WARNINGS_TOTAL: &str = "inventory_catalog_quota_warnings_total";
+
 /// Database query duration histogram
 /// Labels: operation (select/insert/update)
 pub const DB_QUERY_DURATION_SECONDS: &str = "db_query_duration_seconds";
@@ -57,6 +113,179 @@ pub const DB_QUERY_DURATION_SECONDS: &str = "db_query_duration_seconds";
 /// Labels: operation (get/set/delete)
 pub const REDIS_OPERATION_DURATION_SECONDS: &str = "redis_operation_duration_seconds";
 
+/// Background task liveness gauge (1 = running, 0 = backing off)
+/// Labels: task
+pub const BACKGROUND_TASK_UP: &str = "background_task_up";
+
+/// Background task restart counter
+/// Labels: task
+pub const BACKGROUND_TASK_RESTARTS_TOTAL: &str = "background_task_restarts_total";
+
+/// Attempts to take or renew a distributed lock
+/// Labels: lock, outcome (acquired/renewed/contended/error)
+pub const DISTRIBUTED_LOCK_ACQUISITIONS_TOTAL: &str = "distributed_lock_acquisitions_total";
+
+/// Whether this replica holds a distributed lock (1) or not (0)
+/// Labels: lock
+pub const DISTRIBUTED_LOCK_HELD: &str = "distributed_lock_held";
+
+/// TLS certificate reloads triggered by SIGHUP
+/// Labels: outcome (success/error)
+pub const TLS_CERTIFICATE_RELOADS_TOTAL: &str = "tls_certificate_reloads_total";
+
+/// Allocator heap statistics gauge (only populated with jemalloc)
+/// Labels: kind (allocated/active/metadata/resident/mapped/retained)
+pub const ALLOCATOR_BYTES: &str = "allocator_bytes";
+
+/// Reservations waiting for (or holding) their turn on a SKU
+/// Labels: sku
+pub const RESERVATION_QUEUE_DEPTH: &str = "reservation_queue_depth";
+
+/// Time spent waiting for a reservation turn
+/// Labels: outcome (acquired/timeout/full)
+pub const RESERVATION_QUEUE_WAIT_SECONDS: &str = "reservation_queue_wait_seconds";
+
+/// Outbound HTTP request counter (one per attempt)
+/// Labels: destination, status (HTTP code, "timeout" or "error")
+pub const HTTP_CLIENT_REQUESTS_TOTAL: &str = "http_client_requests_total";
+
+/// Outbound HTTP request latency histogram
+/// Labels: destination
+pub const HTTP_CLIENT_REQUEST_DURATION_SECONDS: &str = "http_client_request_duration_seconds";
+
+/// Outbound HTTP retry counter
+/// Labels: destination
+pub const HTTP_CLIENT_RETRIES_TOTAL: &str = "http_client_retries_total";
+
+/// Reservations attempted on the Redis fast path
+/// Labels: outcome (reserved/rejected/fallback)
+pub const FAST_RESERVE_TOTAL: &str = "fast_reserve_total";
+
+/// Fast-path reservations written behind to Postgres
+/// Labels: outcome (applied/rejected/duplicate)
+pub const WRITE_BEHIND_TOTAL: &str = "write_behind_total";
+
+/// Time from a fast-path reservation to its Postgres commit
+pub const WRITE_BEHIND_LAG_SECONDS: &str = "write_behind_lag_seconds";
+
+/// Fast-path reservations not yet written to Postgres
+pub const WRITE_BEHIND_PENDING: &str = "write_behind_pending";
+
+/// Redis availability counters corrected by reconciliation
+/// Labels: sku
+pub const FAST_RESERVE_DRIFT_CORRECTIONS_TOTAL: &str = "fast_reserve_drift_corrections_total";
+
+/// List endpoint cache lookups
+/// Labels: result (hit/miss/bypass)
+pub const LIST_CACHE_LOOKUPS_TOTAL: &str = "list_cache_lookups_total";
+
+/// Cache lookups that found an entry
+/// Labels: cache (item/list)
+pub const CACHE_HITS_TOTAL: &str = "cache_hits_total";
+
+/// Cache lookups that found nothing
+/// Labels: cache (item/list)
+pub const CACHE_MISSES_TOTAL: &str = "cache_misses_total";
+
+/// Cache lookups that found a remembered "doesn't exist"
+/// Labels: cache (item)
+pub const CACHE_NEGATIVE_HITS_TOTAL: &str = "cache_negative_hits_total";
+
+/// Redis calls of a cache that failed (the request went on without Redis)
+/// Labels: cache (item/list), operation (get/set/del/...)
+pub const CACHE_ERRORS_TOTAL: &str = "cache_errors_total";
+
+/// Cache misses that waited for another caller's fill instead of querying
+/// Postgres
+/// Labels: cache (item), scope (process = same instance, cluster = Redis lock)
+pub const CACHE_FILLS_COALESCED_TOTAL: &str = "cache_fills_coalesced_total";
+
+/// Requests refused by a rate limiter
+/// Labels: limiter (client/public), route
+pub const RATE_LIMITED_REQUESTS_TOTAL: &str = "rate_limited_requests_total";
+
+/// Requests let through unchecked because the rate limiter's Redis failed
+pub const RATE_LIMIT_ERRORS_TOTAL: &str = "rate_limit_errors_total";
+
+/// Usage increments lost because Redis was unavailable
+pub const USAGE_RECORD_ERRORS_TOTAL: &str = "usage_record_errors_total";
+
+/// When API usage was last rolled up into Postgres (Unix seconds)
+pub const USAGE_LAST_ROLLUP: &str = "usage_last_rollup_timestamp_seconds";
+
+/// When low-stock state was last evaluated (Unix seconds)
+pub const LOW_STOCK_LAST_EVALUATED: &str = "low_stock_last_evaluated_timestamp_seconds";
+
+/// Stock events emitted (logged and, if configured, delivered to the webhook)
+/// Labels: type (stock.out/stock.back)
+pub const STOCK_EVENTS_TOTAL: &str = "stock_events_total";
+
+/// Stock events waiting for delivery
+pub const STOCK_EVENTS_PENDING: &str = "stock_events_pending";
+
+/// Events sent to Kafka
+/// Labels: type (stock.reserved/stock.released/stock.adjusted/stock.low_stock),
+/// outcome (success/failure)
+pub const EVENTS_PUBLISHED_TOTAL: &str = "events_published_total";
+
+/// Low-stock alerts sent to the alert webhook (LOW_STOCK_ALERT_WEBHOOK_URL)
+/// Labels: status (firing/resolved), outcome (success/failure)
+pub const LOW_STOCK_NOTIFICATIONS_TOTAL: &str = "low_stock_notifications_total";
+
+/// Open live stock change streams (GET /api/v1/inventory/stream)
+pub const INVENTORY_LIVE_STREAM_CLIENTS: &str = "inventory_live_stream_clients";
+
+/// PostgreSQL pool connections, sampled by pool_metrics.rs
+/// Labels: pool (primary/replica-<n>), state (idle/in_use)
+pub const DB_POOL_CONNECTIONS: &str = "db_pool_connections";
+
+/// Configured PostgreSQL pool capacity
+/// Labels: pool
+pub const DB_POOL_MAX_CONNECTIONS: &str = "db_pool_max_connections";
+
+/// Time the latest sample waited to get a pool connection
+/// Labels: pool
+pub const DB_POOL_ACQUIRE_WAIT_SECONDS: &str = "db_pool_acquire_wait_seconds";
+
+/// Replica reads that failed over to the primary
+/// Labels: pool (the replica that couldn't answer)
+pub const DB_REPLICA_FAILOVERS_TOTAL: &str = "db_replica_failovers_total";
+
+/// Primary calls retried after a transient failure
+/// Labels: operation (begin/read)
+pub const DB_RETRIES_TOTAL: &str = "db_retries_total";
+
+/// State of the primary's circuit breaker (0 closed, 1 half-open, 2 open)
+pub const DB_CIRCUIT_BREAKER_STATE: &str = "db_circuit_breaker_state";
+
+/// Primary calls failed fast by the open circuit breaker
+pub const DB_CIRCUIT_BREAKER_REJECTIONS_TOTAL: &str = "db_circuit_breaker_rejections_total";
+
+/// Whether Redis answered the latest PING (1) or not (0)
+pub const REDIS_CONNECTION_UP: &str = "redis_connection_up";
+
+/// Round trip of the latest Redis PING
+pub const REDIS_PING_SECONDS: &str = "redis_ping_seconds";
+
+/// Selftest runs
+/// Labels: outcome (passed/failed)
+pub const INVENTORY_SELFTEST_RUNS_TOTAL: &str = "inventory_selftest_runs_total";
+
+/// Duration of each selftest step
+/// Labels: step (create/reserve/release/adjust/delete)
+pub const INVENTORY_SELFTEST_STEP_DURATION_SECONDS: &str =
+    "inventory_selftest_step_duration_seconds";
+
+/// Faults chaos experiments injected into API requests
+/// Labels: endpoint (route template), fault (latency/db_delay/cpu/error)
+pub const CHAOS_FAULTS_INJECTED_TOTAL: &str = "chaos_faults_injected_total";
+
+/// Chaos experiments running right now
+pub const CHAOS_EXPERIMENTS_ACTIVE: &str = "chaos_experiments_active";
+
+/// Memory held on purpose by chaos experiments
+pub const CHAOS_MEMORY_BALLAST_BYTES: &str = "chaos_memory_ballast_bytes";
+
 // =============================================================================
 // SETUP FUNCTION
 // =============================================================================
@@ -110,6 +339,11 @@ pub fn setup_metrics() -> Result<PrometheusHandle> {
             Matcher::Full(HTTP_REQUEST_DURATION_SECONDS.to_string()),
             latency_buckets,
         )?
+        // Configure buckets for gRPC call duration
+        .set_buckets_for_metric(
+            Matcher::Full(GRPC_REQUEST_DURATION_SECONDS.to_string()),
+            latency_buckets,
+        )?
         // Configure buckets for database queries
         .set_buckets_for_metric(
             Matcher::Full(DB_QUERY_DURATION_SECONDS.to_string()),
@@ -120,6 +354,26 @@ pub fn setup_metrics() -> Result<PrometheusHandle> {
             Matcher::Full(REDIS_OPERATION_DURATION_SECONDS.to_string()),
             latency_buckets,
         )?
+        // Configure buckets for reservation queue waits
+        .set_buckets_for_metric(
+            Matcher::Full(RESERVATION_QUEUE_WAIT_SECONDS.to_string()),
+            latency_buckets,
+        )?
+        // Configure buckets for outbound HTTP calls
+        .set_buckets_for_metric(
+            Matcher::Full(HTTP_CLIENT_REQUEST_DURATION_SECONDS.to_string()),
+            latency_buckets,
+        )?
+        // Configure buckets for write-behind lag
+        .set_buckets_for_metric(
+            Matcher::Full(WRITE_BEHIND_LAG_SECONDS.to_string()),
+            latency_buckets,
+        )?
+        // Configure buckets for selftest steps
+        .set_buckets_for_metric(
+            Matcher::Full(INVENTORY_SELFTEST_STEP_DURATION_SECONDS.to_string()),
+            latency_buckets,
+        )?
         // Install as the global metrics recorder
         .install_recorder()?;
 
@@ -139,6 +393,26 @@ pub fn setup_metrics() -> Result<PrometheusHandle> {
         "HTTP request latency in seconds"
     );
 
+    describe_gauge!(
+        HTTP_REQUESTS_IN_FLIGHT,
+        "Number of API requests currently holding a concurrency slot"
+    );
+
+    describe_counter!(
+        HTTP_REQUESTS_SHED_TOTAL,
+        "Total number of API requests refused with 503 at the concurrency limit"
+    );
+
+    describe_counter!(
+        GRPC_REQUESTS_TOTAL,
+        "Total number of gRPC calls handled"
+    );
+
+    describe_histogram!(
+        GRPC_REQUEST_DURATION_SECONDS,
+        "gRPC call latency in seconds"
+    );
+
     describe_gauge!(
         INVENTORY_STOCK_LEVEL,
         "Current stock level for each SKU"
@@ -149,11 +423,38 @@ pub fn setup_metrics() -> Result<PrometheusHandle> {
         "Total number of stock reservation attempts"
     );
 
+    describe_counter!(
+        INVENTORY_RESERVATIONS_EXPIRED_TOTAL,
+        "Total number of expired reservations released"
+    );
+
+    describe_counter!(
+        INVENTORY_RESERVATIONS_BUMPED_TOTAL,
+        "Total number of soft holds bumped by higher-priority reservations"
+    );
+
+    describe_counter!(
+        INVENTORY_COMMITMENTS_TOTAL,
+        "Total number of two-phase commitments by outcome"
+    );
+
     describe_gauge!(
         INVENTORY_LOW_STOCK_ITEMS,
         "Number of items currently below low stock threshold"
     );
 
+    describe_gauge!(INVENTORY_CATALOG_SKUS, "Number of SKUs in the catalog");
+
+    describe_gauge!(
+        INVENTORY_CATALOG_SKU_LIMIT,
+        "Configured soft and hard limits on the number of SKUs"
+    );
+
+    describe_counter!(
+        INVENTORY_CATALOG_QUOTA_WARNINGS_TOTAL,
+        "Total number of items created past the catalog soft limit"
+    );
+
     describe_histogram!(
         DB_QUERY_DURATION_SECONDS,
         "Database query latency in seconds"
@@ -164,6 +465,250 @@ pub fn setup_metrics() -> Result<PrometheusHandle> {
         "Redis operation latency in seconds"
     );
 
+    describe_gauge!(
+        BACKGROUND_TASK_UP,
+        "Whether a supervised background task is currently running (1) or backing off (0)"
+    );
+
+    describe_counter!(
+        BACKGROUND_TASK_RESTARTS_TOTAL,
+        "Total number of supervised background task restarts"
+    );
+
+    describe_counter!(
+        DISTRIBUTED_LOCK_ACQUISITIONS_TOTAL,
+        "Attempts to take or renew a distributed lock in Redis, by outcome"
+    );
+
+    describe_gauge!(
+        DISTRIBUTED_LOCK_HELD,
+        "Whether this replica holds a distributed lock (1) or not (0)"
+    );
+
+    describe_counter!(
+        TLS_CERTIFICATE_RELOADS_TOTAL,
+        "TLS certificate reloads on SIGHUP, by outcome"
+    );
+
+    describe_gauge!(
+        ALLOCATOR_BYTES,
+        "Heap statistics reported by the memory allocator, in bytes"
+    );
+
+    describe_gauge!(
+        RESERVATION_QUEUE_DEPTH,
+        "Reservations queued or in progress per SKU (fair queueing)"
+    );
+
+    describe_histogram!(
+        RESERVATION_QUEUE_WAIT_SECONDS,
+        "Time reservations waited for their turn on a SKU, in seconds"
+    );
+
+    describe_counter!(
+        HTTP_CLIENT_REQUESTS_TOTAL,
+        "Total number of outbound HTTP request attempts"
+    );
+
+    describe_histogram!(
+        HTTP_CLIENT_REQUEST_DURATION_SECONDS,
+        "Outbound HTTP request latency in seconds"
+    );
+
+    describe_counter!(
+        HTTP_CLIENT_RETRIES_TOTAL,
+        "Total number of outbound HTTP request retries"
+    );
+
+    describe_counter!(
+        FAST_RESERVE_TOTAL,
+        "Total number of reservations attempted on the Redis fast path"
+    );
+
+    describe_counter!(
+        WRITE_BEHIND_TOTAL,
+        "Total number of fast-path reservations written behind to Postgres"
+    );
+
+    describe_histogram!(
+        WRITE_BEHIND_LAG_SECONDS,
+        "Time from a fast-path reservation to its Postgres commit, in seconds"
+    );
+
+    describe_gauge!(
+        WRITE_BEHIND_PENDING,
+        "Fast-path reservations not yet written to Postgres"
+    );
+
+    describe_counter!(
+        FAST_RESERVE_DRIFT_CORRECTIONS_TOTAL,
+        "Total number of Redis availability counters corrected by reconciliation"
+    );
+
+    describe_counter!(
+        LIST_CACHE_LOOKUPS_TOTAL,
+        "Total number of inventory list cache lookups"
+    );
+
+    describe_counter!(CACHE_HITS_TOTAL, "Total number of cache hits");
+
+    describe_counter!(CACHE_MISSES_TOTAL, "Total number of cache misses");
+
+    describe_counter!(
+        CACHE_NEGATIVE_HITS_TOTAL,
+        "Total number of cache lookups answered with a cached not-found"
+    );
+
+    describe_counter!(
+        CACHE_ERRORS_TOTAL,
+        "Total number of failed cache operations (served without Redis)"
+    );
+
+    describe_counter!(
+        CACHE_FILLS_COALESCED_TOTAL,
+        "Total number of cache misses served by another caller's fill"
+    );
+
+    describe_counter!(
+        RATE_LIMITED_REQUESTS_TOTAL,
+        "Total number of requests refused with 429 by a rate limiter"
+    );
+
+    describe_counter!(
+        RATE_LIMIT_ERRORS_TOTAL,
+        "Total number of requests let through because the rate limiter failed"
+    );
+
+    describe_counter!(
+        USAGE_RECORD_ERRORS_TOTAL,
+        "Total number of API usage increments lost to Redis errors"
+    );
+
+    describe_gauge!(
+        USAGE_LAST_ROLLUP,
+        "Unix time of the last API usage rollup into Postgres"
+    );
+
+    describe_gauge!(
+        INVENTORY_LOW_STOCK_ITEMS_BY_WAREHOUSE,
+        "Number of items below their low stock threshold, per warehouse"
+    );
+
+    describe_gauge!(
+        LOW_STOCK_LAST_EVALUATED,
+        "Unix time of the last low-stock evaluation"
+    );
+
+    describe_counter!(
+        INVENTORY_STOCKOUT_SECONDS_TOTAL,
+        "Total seconds each SKU has spent with no available stock"
+    );
+
+    describe_gauge!(
+        INVENTORY_CURRENT_STOCKOUTS,
+        "Number of SKUs currently without available stock"
+    );
+
+    describe_gauge!(
+        INVENTORY_STOCK_VALUE,
+        "Value at cost of the stock held at each warehouse"
+    );
+
+    describe_counter!(
+        STOCK_EVENTS_TOTAL,
+        "Total number of stock.out / stock.back events emitted"
+    );
+
+    describe_gauge!(
+        STOCK_EVENTS_PENDING,
+        "Stock events waiting for delivery"
+    );
+
+    describe_counter!(
+        EVENTS_PUBLISHED_TOTAL,
+        "Total number of stock change events sent to Kafka"
+    );
+
+    describe_counter!(
+        LOW_STOCK_NOTIFICATIONS_TOTAL,
+        "Total number of low-stock alerts sent to the alert webhook"
+    );
+
+    describe_gauge!(
+        INVENTORY_LIVE_STREAM_CLIENTS,
+        "Clients connected to the live stock change stream"
+    );
+
+    describe_gauge!(
+        DB_POOL_CONNECTIONS,
+        "PostgreSQL pool connections by state"
+    );
+
+    describe_gauge!(
+        DB_POOL_MAX_CONNECTIONS,
+        "Maximum number of PostgreSQL pool connections"
+    );
+
+    describe_gauge!(
+        DB_POOL_ACQUIRE_WAIT_SECONDS,
+        "Seconds the latest pool sample waited for a PostgreSQL connection"
+    );
+
+    describe_counter!(
+        DB_REPLICA_FAILOVERS_TOTAL,
+        "Total number of replica reads retried on the primary"
+    );
+
+    describe_counter!(
+        DB_RETRIES_TOTAL,
+        "Total number of primary calls retried after a transient failure"
+    );
+
+    describe_gauge!(
+        DB_CIRCUIT_BREAKER_STATE,
+        "Database circuit breaker state (0 = closed, 1 = half-open, 2 = open)"
+    );
+
+    describe_counter!(
+        DB_CIRCUIT_BREAKER_REJECTIONS_TOTAL,
+        "Total number of database calls failed fast by the circuit breaker"
+    );
+
+    describe_gauge!(
+        REDIS_CONNECTION_UP,
+        "Whether Redis answered the latest PING (1 = yes)"
+    );
+
+    describe_gauge!(
+        REDIS_PING_SECONDS,
+        "Round trip of the latest Redis PING in seconds"
+    );
+
+    describe_counter!(
+        INVENTORY_SELFTEST_RUNS_TOTAL,
+        "Total number of end-to-end selftest runs"
+    );
+
+    describe_histogram!(
+        INVENTORY_SELFTEST_STEP_DURATION_SECONDS,
+        "Duration of each selftest step in seconds"
+    );
+
+    describe_counter!(
+        CHAOS_FAULTS_INJECTED_TOTAL,
+        "Total number of faults injected into API requests by chaos experiments"
+    );
+
+    describe_gauge!(
+        CHAOS_EXPERIMENTS_ACTIVE,
+        "Number of chaos experiments currently running"
+    );
+
+    describe_gauge!(
+        CHAOS_MEMORY_BALLAST_BYTES,
+        "Bytes of memory held on purpose by chaos experiments"
+    );
+
     Ok(handle)
 }
 
@@ -173,11 +718,11 @@ pub fn setup_metrics() -> Result<PrometheusHandle> {
 // These functions provide a convenient API for recording metrics.
 // They wrap the raw metrics macros with proper labels.
 
-/// Record an HTTP request
+/// Record an HTTP request (called for every request by http_metrics.rs)
 ///
 /// # Arguments
 /// * `method` - HTTP method (GET, POST, etc.)
-/// * `endpoint` - Request path (/api/v1/inventory)
+/// * `endpoint` - Route template (/api/v1/inventory/:sku)
 /// * `status` - Response status code (200, 404, 500)
 /// * `duration_secs` - Request duration in seconds
 pub fn record_http_request(method: &str, endpoint: &str, status: u16, duration_secs: f64) {
@@ -199,6 +744,17 @@ pub fn record_http_request(method: &str, endpoint: &str, status: u16, duration_s
     .record(duration_secs);
 }
 
+/// Record a gRPC call (called for every call by grpc/service.rs)
+///
+/// # Arguments
+/// * `method` - RPC name (GetItem, ReserveStock, ...)
+/// * `code` - Final status code name (OK, NOT_FOUND, ...)
+/// * `duration_secs` - Call duration in seconds
+pub fn record_grpc_request(method: &'static str, code: &'static str, duration_secs: f64) {
+    counter!(GRPC_REQUESTS_TOTAL, "method" => method, "code" => code).increment(1);
+    histogram!(GRPC_REQUEST_DURATION_SECONDS, "method" => method).record(duration_secs);
+}
+
 /// Update stock level gauge for a SKU
 ///
 /// # Arguments
@@ -218,17 +774,45 @@ pub fn set_stock_level(sku: &str, warehouse: &str, level: i32) {
 ///
 /// # Arguments
 /// * `sku` - Stock Keeping Unit identifier
+/// * `channel` - Sales channel of the order
 /// * `success` - Whether the reservation succeeded
-pub fn record_reservation(sku: &str, success: bool) {
+pub fn record_reservation(sku: &str, channel: SalesChannel, success: bool) {
     let status = if success { "success" } else { "failed" };
     counter!(
         INVENTORY_RESERVATIONS_TOTAL,
         "sku" => sku.to_string(),
+        "channel" => channel.as_str(),
         "status" => status.to_string()
     )
     .increment(1);
 }
 
+/// Record an expired reservation released by the sweeper
+///
+/// # Arguments
+/// * `sku` - Stock Keeping Unit identifier
+pub fn record_reservation_expired(sku: &str) {
+    counter!(INVENTORY_RESERVATIONS_EXPIRED_TOTAL, "sku" => sku.to_string()).increment(1);
+}
+
+/// Record soft holds bumped to make room for a reservation
+///
+/// # Arguments
+/// * `sku` - Stock Keeping Unit identifier
+/// * `holds` - Number of holdings bumped
+pub fn record_reservations_bumped(sku: &str, holds: usize) {
+    counter!(INVENTORY_RESERVATIONS_BUMPED_TOTAL, "sku" => sku.to_string())
+        .increment(holds as u64);
+}
+
+/// Record a two-phase commitment step that changed stock
+///
+/// # Arguments
+/// * `outcome` - State the commitment moved to
+pub fn record_commitment(outcome: CommitmentState) {
+    counter!(INVENTORY_COMMITMENTS_TOTAL, "outcome" => outcome.as_str()).increment(1);
+}
+
 /// Update low stock items count
 ///
 /// # Arguments
@@ -237,6 +821,24 @@ pub fn set_low_stock_count(count: i64) {
     gauge!(INVENTORY_LOW_STOCK_ITEMS).set(count as f64);
 }
 
+/// Update the low stock items count of one warehouse
+///
+/// # Arguments
+/// * `warehouse` - Warehouse code
+/// * `count` - Number of items below threshold there
+pub fn set_low_stock_count_by_warehouse(warehouse: &str, count: usize) {
+    gauge!(INVENTORY_LOW_STOCK_ITEMS_BY_WAREHOUSE, "warehouse" => warehouse.to_string())
+        .set(count as f64);
+}
+
+/// Record when low-stock state was last evaluated
+///
+/// # Arguments
+/// * `timestamp` - Unix time in seconds
+pub fn set_low_stock_evaluated_at(timestamp: i64) {
+    gauge!(LOW_STOCK_LAST_EVALUATED).set(timestamp as f64);
+}
+
 /// Record database query duration
 ///
 /// # Arguments
@@ -262,3 +864,385 @@ pub fn record_redis_operation(operation: &str, duration_secs: f64) {
     )
     .record(duration_secs);
 }
+
+/// Update the liveness gauge of a supervised background task
+///
+/// # Arguments
+/// * `task` - Task name as registered with the supervisor
+/// * `up` - Whether the task is currently running
+pub fn set_background_task_up(task: &str, up: bool) {
+    gauge!(BACKGROUND_TASK_UP, "task" => task.to_string()).set(if up { 1.0 } else { 0.0 });
+}
+
+/// Record a restart of a supervised background task
+///
+/// # Arguments
+/// * `task` - Task name as registered with the supervisor
+pub fn record_background_task_restart(task: &str) {
+    counter!(BACKGROUND_TASK_RESTARTS_TOTAL, "task" => task.to_string()).increment(1);
+}
+
+/// Record an attempt to take or renew a distributed lock
+///
+/// # Arguments
+/// * `lock` - Lock name (the job it guards)
+/// * `outcome` - acquired, renewed, contended or error
+pub fn record_lock_acquisition(lock: &str, outcome: &'static str) {
+    counter!(DISTRIBUTED_LOCK_ACQUISITIONS_TOTAL, "lock" => lock.to_string(), "outcome" => outcome)
+        .increment(1);
+}
+
+/// Update whether this replica holds a distributed lock
+pub fn set_lock_held(lock: &str, held: bool) {
+    gauge!(DISTRIBUTED_LOCK_HELD, "lock" => lock.to_string()).set(if held { 1.0 } else { 0.0 });
+}
+
+/// Record a TLS certificate reload ("success" or "error")
+pub fn record_tls_reload(outcome: &'static str) {
+    counter!(TLS_CERTIFICATE_RELOADS_TOTAL, "outcome" => outcome).increment(1);
+}
+
+/// Update the PostgreSQL pool gauges
+///
+/// # Arguments
+/// * `pool` - "primary" or "replica-<n>"
+/// * `stats` - Pool usage snapshot
+/// * `acquire_wait_secs` - How long getting a connection just took
+pub fn set_db_pool(pool: &str, stats: &PoolStats, acquire_wait_secs: f64) {
+    let in_use = stats.size.saturating_sub(stats.idle);
+    let pool = pool.to_string();
+    gauge!(DB_POOL_CONNECTIONS, "pool" => pool.clone(), "state" => "idle").set(stats.idle as f64);
+    gauge!(DB_POOL_CONNECTIONS, "pool" => pool.clone(), "state" => "in_use").set(in_use as f64);
+    gauge!(DB_POOL_MAX_CONNECTIONS, "pool" => pool.clone()).set(stats.max_connections as f64);
+    gauge!(DB_POOL_ACQUIRE_WAIT_SECONDS, "pool" => pool).set(acquire_wait_secs);
+}
+
+/// Record a replica read that was retried on the primary
+///
+/// # Arguments
+/// * `replica` - The replica's pool name ("replica-<n>")
+pub fn record_replica_failover(replica: &str) {
+    counter!(DB_REPLICA_FAILOVERS_TOTAL, "pool" => replica.to_string()).increment(1);
+}
+
+/// Record a retried primary call
+///
+/// # Arguments
+/// * `operation` - "begin" (starting a transaction) or "read"
+pub fn record_db_retry(operation: &'static str) {
+    counter!(DB_RETRIES_TOTAL, "operation" => operation).increment(1);
+}
+
+/// Update the database circuit breaker gauge (0 closed, 1 half-open, 2 open)
+pub fn set_db_breaker_state(state: f64) {
+    gauge!(DB_CIRCUIT_BREAKER_STATE).set(state);
+}
+
+/// Record a call failed fast by the open circuit breaker
+pub fn record_db_breaker_rejection() {
+    counter!(DB_CIRCUIT_BREAKER_REJECTIONS_TOTAL).increment(1);
+}
+
+/// Update the Redis connection gauges
+///
+/// # Arguments
+/// * `up` - Whether the PING was answered
+/// * `ping_secs` - How long the PING took (or until it gave up)
+pub fn set_redis_connection(up: bool, ping_secs: f64) {
+    gauge!(REDIS_CONNECTION_UP).set(if up { 1.0 } else { 0.0 });
+    gauge!(REDIS_PING_SECONDS).set(ping_secs);
+}
+
+/// Update an allocator heap statistic gauge
+///
+/// # Arguments
+/// * `kind` - Statistic name (allocated, resident, ...)
+/// * `bytes` - Current value in bytes
+pub fn set_allocator_bytes(kind: &str, bytes: u64) {
+    gauge!(ALLOCATOR_BYTES, "kind" => kind.to_string()).set(bytes as f64);
+}
+
+/// Update the fair-queue depth for a SKU
+///
+/// # Arguments
+/// * `sku` - Stock Keeping Unit identifier
+/// * `depth` - Reservations waiting or in progress
+pub fn set_reservation_queue_depth(sku: &str, depth: usize) {
+    gauge!(RESERVATION_QUEUE_DEPTH, "sku" => sku.to_string()).set(depth as f64);
+}
+
+/// Leave series out of a rendered scrape that stand for something that no
+/// longer exists: queue depths of SKUs whose lane was dropped (at 0)
+///
+/// The exporter keeps every series it has seen; this keeps the scrape from
+/// listing a SKU for every one ever queued.
+pub fn without_dropped_series(text: &str) -> String {
+    let prefix = format!("{}{{", RESERVATION_QUEUE_DEPTH);
+    text.lines()
+        .filter(|line| !(line.starts_with(&prefix) && line.ends_with("} 0")))
+        .fold(String::with_capacity(text.len()), |mut out, line| {
+            out.push_str(line);
+            out.push('\n');
+            out
+        })
+}
+
+/// Record how long a reservation waited for its turn
+///
+/// # Arguments
+/// * `outcome` - "acquired", "timeout" or "full"
+/// * `duration_secs` - Wait time in seconds
+pub fn record_reservation_queue_wait(outcome: &str, duration_secs: f64) {
+    histogram!(RESERVATION_QUEUE_WAIT_SECONDS, "outcome" => outcome.to_string())
+        .record(duration_secs);
+}
+
+/// Record one outbound HTTP attempt
+///
+/// # Arguments
+/// * `destination` - Logical destination name (audit-http, ...)
+/// * `status` - HTTP status code, "timeout" or "error"
+/// * `duration_secs` - Attempt duration in seconds
+pub fn record_http_client_request(destination: &str, status: &str, duration_secs: f64) {
+    counter!(
+        HTTP_CLIENT_REQUESTS_TOTAL,
+        "destination" => destination.to_string(),
+        "status" => status.to_string()
+    )
+    .increment(1);
+
+    histogram!(
+        HTTP_CLIENT_REQUEST_DURATION_SECONDS,
+        "destination" => destination.to_string()
+    )
+    .record(duration_secs);
+}
+
+/// Record a retry of an outbound HTTP request
+///
+/// # Arguments
+/// * `destination` - Logical destination name
+pub fn record_http_client_retry(destination: &str) {
+    counter!(HTTP_CLIENT_RETRIES_TOTAL, "destination" => destination.to_string()).increment(1);
+}
+
+/// Record a fast-path reservation attempt
+///
+/// # Arguments
+/// * `outcome` - "reserved", "rejected" or "fallback"
+pub fn record_fast_reserve(outcome: &str) {
+    counter!(FAST_RESERVE_TOTAL, "outcome" => outcome.to_string()).increment(1);
+}
+
+/// Record a write-behind of a fast-path reservation
+///
+/// # Arguments
+/// * `outcome` - "applied", "rejected" or "duplicate"
+/// * `lag_secs` - Time since the reservation was accepted
+pub fn record_write_behind(outcome: &str, lag_secs: f64) {
+    counter!(WRITE_BEHIND_TOTAL, "outcome" => outcome.to_string()).increment(1);
+    histogram!(WRITE_BEHIND_LAG_SECONDS).record(lag_secs);
+}
+
+/// Set the number of reservations waiting to be written behind
+pub fn set_write_behind_pending(pending: i64) {
+    gauge!(WRITE_BEHIND_PENDING).set(pending as f64);
+}
+
+/// Record a reconciliation correction of a SKU's Redis counter
+pub fn record_fast_reserve_drift(sku: &str) {
+    counter!(FAST_RESERVE_DRIFT_CORRECTIONS_TOTAL, "sku" => sku.to_string()).increment(1);
+}
+
+/// Record an inventory list cache lookup
+///
+/// # Arguments
+/// * `result` - "hit", "miss" or "bypass"
+pub fn record_list_cache_lookup(result: &str) {
+    counter!(LIST_CACHE_LOOKUPS_TOTAL, "result" => result.to_string()).increment(1);
+}
+
+/// Record a cache hit or miss
+///
+/// # Arguments
+/// * `cache` - "item" or "list"
+/// * `hit` - Whether an entry was found
+pub fn record_cache_lookup(cache: &'static str, hit: bool) {
+    let name = if hit { CACHE_HITS_TOTAL } else { CACHE_MISSES_TOTAL };
+    counter!(name, "cache" => cache).increment(1);
+}
+
+/// Record a lookup answered by a cached not-found (not counted as a hit)
+pub fn record_cache_negative_hit(cache: &'static str) {
+    counter!(CACHE_NEGATIVE_HITS_TOTAL, "cache" => cache).increment(1);
+}
+
+/// Record a failed Redis call of a cache
+///
+/// # Arguments
+/// * `cache` - "item" or "list"
+/// * `operation` - e.g. "get", "set", "del"
+pub fn record_cache_error(cache: &'static str, operation: &'static str) {
+    counter!(CACHE_ERRORS_TOTAL, "cache" => cache, "operation" => operation).increment(1);
+}
+
+/// Record a cache miss that reused another caller's fill
+///
+/// # Arguments
+/// * `cache` - "item"
+/// * `scope` - "process" (in-flight fetch) or "cluster" (Redis fill lock)
+pub fn record_cache_fill_coalesced(cache: &'static str, scope: &'static str) {
+    counter!(CACHE_FILLS_COALESCED_TOTAL, "cache" => cache, "scope" => scope).increment(1);
+}
+
+/// Record a request refused by a rate limiter
+///
+/// # Arguments
+/// * `limiter` - "client" (per-client, Redis) or "public" (public mode)
+/// * `route` - Route template
+pub fn record_rate_limited(limiter: &'static str, route: &str) {
+    counter!(RATE_LIMITED_REQUESTS_TOTAL, "limiter" => limiter, "route" => route.to_string())
+        .increment(1);
+}
+
+/// Track API requests holding a concurrency slot
+///
+/// # Arguments
+/// * `delta` - 1 when a request takes a slot, -1 when it gives it back
+pub fn add_requests_in_flight(delta: f64) {
+    gauge!(HTTP_REQUESTS_IN_FLIGHT).increment(delta);
+}
+
+/// Record a request shed at the concurrency limit
+///
+/// # Arguments
+/// * `endpoint` - Route template
+pub fn record_request_shed(endpoint: &str) {
+    counter!(HTTP_REQUESTS_SHED_TOTAL, "endpoint" => endpoint.to_string()).increment(1);
+}
+
+/// Record a request the rate limiter couldn't check
+pub fn record_rate_limit_error() {
+    counter!(RATE_LIMIT_ERRORS_TOTAL).increment(1);
+}
+
+/// Record a usage increment lost to a Redis error
+pub fn record_usage_error() {
+    counter!(USAGE_RECORD_ERRORS_TOTAL).increment(1);
+}
+
+/// Record a completed usage rollup
+///
+/// # Arguments
+/// * `timestamp` - Unix seconds
+pub fn set_usage_rolled_up(timestamp: i64) {
+    gauge!(USAGE_LAST_ROLLUP).set(timestamp as f64);
+}
+
+/// Record an emitted stock event
+///
+/// # Arguments
+/// * `event_type` - "stock.out" or "stock.back"
+pub fn record_stock_event(event_type: &str) {
+    counter!(STOCK_EVENTS_TOTAL, "type" => event_type.to_string()).increment(1);
+}
+
+/// Update the number of stock events waiting for delivery
+pub fn set_stock_events_pending(count: usize) {
+    gauge!(STOCK_EVENTS_PENDING).set(count as f64);
+}
+
+/// Record an event sent to Kafka
+///
+/// # Arguments
+/// * `event_type` - e.g. "stock.reserved"
+/// * `success` - Whether Kafka acknowledged it
+pub fn record_event_published(event_type: &str, success: bool) {
+    let outcome = if success { "success" } else { "failure" };
+    counter!(EVENTS_PUBLISHED_TOTAL, "type" => event_type.to_string(), "outcome" => outcome)
+        .increment(1);
+}
+
+/// Record a low-stock alert sent to the alert webhook
+///
+/// # Arguments
+/// * `status` - "firing" or "resolved"
+/// * `success` - Whether the webhook accepted it
+pub fn record_low_stock_notification(status: &'static str, success: bool) {
+    let outcome = if success { "success" } else { "failure" };
+    counter!(LOW_STOCK_NOTIFICATIONS_TOTAL, "status" => status, "outcome" => outcome).increment(1);
+}
+
+/// Count live stream clients connecting (+1) or going away (-1)
+pub fn add_live_stream_clients(delta: f64) {
+    gauge!(INVENTORY_LIVE_STREAM_CLIENTS).increment(delta);
+}
+
+/// Add time a SKU spent without available stock
+///
+/// # Arguments
+/// * `sku` - Product SKU
+/// * `seconds` - Time to add (0 creates the series)
+pub fn record_stockout_seconds(sku: &str, seconds: u64) {
+    counter!(INVENTORY_STOCKOUT_SECONDS_TOTAL, "sku" => sku.to_string()).increment(seconds);
+}
+
+/// Update the number of SKUs currently out of stock
+pub fn set_current_stockouts(count: usize) {
+    gauge!(INVENTORY_CURRENT_STOCKOUTS).set(count as f64);
+}
+
+/// Update the value at cost of the stock held at a warehouse
+pub fn set_stock_value(warehouse: &str, value: f64) {
+    gauge!(INVENTORY_STOCK_VALUE, "warehouse" => warehouse.to_string()).set(value);
+}
+
+/// Update the catalog size and its configured limits
+///
+/// # Arguments
+/// * `skus` - SKUs in the catalog
+/// * `quota` - Soft/hard limits; unset limits are not exported
+pub fn set_catalog_size(skus: i64, quota: &CatalogQuota) {
+    gauge!(INVENTORY_CATALOG_SKUS).set(skus as f64);
+    for (kind, limit) in [("soft", quota.soft_limit), ("hard", quota.hard_limit)] {
+        if let Some(limit) = limit {
+            gauge!(INVENTORY_CATALOG_SKU_LIMIT, "kind" => kind).set(limit as f64);
+        }
+    }
+}
+
+/// Record an item created past the catalog's soft limit
+pub fn record_catalog_quota_warning() {
+    counter!(INVENTORY_CATALOG_QUOTA_WARNINGS_TOTAL).increment(1);
+}
+
+/// Record a finished selftest run
+pub fn record_selftest_run(passed: bool) {
+    let outcome = if passed { "passed" } else { "failed" };
+    counter!(INVENTORY_SELFTEST_RUNS_TOTAL, "outcome" => outcome).increment(1);
+}
+
+/// Record how long a selftest step took
+///
+/// # Arguments
+/// * `step` - "create", "reserve", "release", "adjust" or "delete"
+/// * `duration_secs` - Step duration in seconds
+pub fn record_selftest_step(step: &'static str, duration_secs: f64) {
+    histogram!(INVENTORY_SELFTEST_STEP_DURATION_SECONDS, "step" => step).record(duration_secs);
+}
+
+/// Record a fault injected by a chaos experiment
+///
+/// # Arguments
+/// * `endpoint` - Route template
+/// * `fault` - "latency", "db_delay", "cpu" or "error"
+pub fn record_chaos_fault(endpoint: &str, fault: &'static str) {
+    counter!(CHAOS_FAULTS_INJECTED_TOTAL, "endpoint" => endpoint.to_string(), "fault" => fault)
+        .increment(1);
+}
+
+/// Set the running chaos experiments and the memory they hold
+pub fn set_chaos_experiments(active: usize, ballast_bytes: usize) {
+    gauge!(CHAOS_EXPERIMENTS_ACTIVE).set(active as f64);
+    gauge!(CHAOS_MEMORY_BALLAST_BYTES).set(ballast_bytes as f64);
+}
@@ -9,11 +9,17 @@
 // - Serde handles JSON serialization/deserialization
 // =============================================================================
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::attributes::AttributeType;
+use crate::fairness::ClientQuota;
+use crate::identifiers::IdentifierKind;
+use crate::validation::{Validate, Validator, MAX_TEXT_LEN};
+
 // =============================================================================
 // INVENTORY ITEM
 // =============================================================================
@@ -26,7 +32,7 @@ use uuid::Uuid;
 // - Deserialize: Converts JSON to struct (for API requests)
 // - FromRow: Allows SQLx to map database rows to this struct
 // -----------------------------------------------------------------------------
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct InventoryItem {
     /// Unique identifier for the inventory record
     /// UUID v4 is randomly generated and globally unique
@@ -53,6 +59,50 @@ pub struct InventoryItem {
     
     /// Minimum stock level before triggering low stock alert
     pub low_stock_threshold: i32,
+
+    /// Most units a single order may reserve (None = no limit)
+    #[serde(default)]
+    pub max_per_order: Option<i32>,
+
+    /// Most of the stock that may be reserved at once, in percent
+    /// (None = no limit). Protects promotional items from hoarding.
+    #[serde(default)]
+    pub max_reserved_pct: Option<i32>,
+
+    /// low_stock_threshold was set by hand; dynamic threshold
+    /// recalculation leaves it alone
+    #[serde(default)]
+    pub threshold_manual: bool,
+
+    /// Bumped on every change; send it back as If-Match (or
+    /// expected_version) to update only the item you read
+    #[serde(default)]
+    pub version: i64,
+
+    /// Free-form catalog attributes (color, size, specs); always an object
+    #[serde(default = "empty_attributes")]
+    pub attributes: serde_json::Value,
+
+    /// Product image for catalog pages
+    #[serde(default)]
+    pub image_url: Option<String>,
+
+    /// Product specification sheet (PDF, page)
+    #[serde(default)]
+    pub spec_url: Option<String>,
+
+    /// Price of one unit, for stock value (None = not priced)
+    #[serde(default)]
+    pub unit_price: Option<f64>,
+
+    /// What one unit cost to buy or make (None = no cost recorded)
+    #[serde(default)]
+    pub unit_cost: Option<f64>,
+
+    /// quantity × unit_cost: what the stock on hand is worth at cost
+    /// (None without a unit cost)
+    #[serde(default)]
+    pub stock_value: Option<f64>,
     
     /// When this record was created
     pub created_at: DateTime<Utc>,
@@ -61,6 +111,10 @@ pub struct InventoryItem {
     pub updated_at: DateTime<Utc>,
 }
 
+fn empty_attributes() -> serde_json::Value {
+    serde_json::Value::Object(serde_json::Map::new())
+}
+
 // -----------------------------------------------------------------------------
 // COMPUTED PROPERTIES (impl block)
 // -----------------------------------------------------------------------------
@@ -83,6 +137,41 @@ impl InventoryItem {
     pub fn is_low_stock(&self) -> bool {
         self.available() < self.low_stock_threshold
     }
+
+    /// Recompute stock_value after quantity or unit_cost changed (the
+    /// database computes it when it returns an item)
+    pub fn revalue(&mut self) {
+        self.stock_value = self.unit_cost.map(|cost| cost * f64::from(self.quantity));
+    }
+
+    /// Check a reservation of `quantity` units against this item's policy
+    ///
+    /// # Returns
+    /// - `Ok(())` if the reservation is allowed
+    /// - `Err(reason)` describing the violated limit
+    pub fn check_reservation_policy(&self, quantity: i32) -> Result<(), String> {
+        if let Some(max) = self.max_per_order {
+            if quantity > max {
+                return Err(format!(
+                    "At most {} units of {} can be reserved per order, requested {}",
+                    max, self.sku, quantity
+                ));
+            }
+        }
+
+        if let Some(pct) = self.max_reserved_pct {
+            // Integer math: reserved / quantity <= pct / 100
+            let reserved_after = i64::from(self.reserved) + i64::from(quantity);
+            if reserved_after * 100 > i64::from(self.quantity) * i64::from(pct) {
+                return Err(format!(
+                    "At most {}% of {} stock can be reserved at once",
+                    pct, self.sku
+                ));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 // =============================================================================
@@ -104,10 +193,13 @@ impl InventoryItem {
 /// {
 ///   "sku": "LAPTOP-001",
 ///   "quantity": 5,
-///   "order_id": "ORD-12345"
+///   "order_id": "ORD-12345",
+///   "channel": "web",
+///   "hold": "soft",
+///   "priority": 10
 /// }
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ReserveStockRequest {
     /// SKU of the product to reserve
     pub sku: String,
@@ -117,6 +209,178 @@ pub struct ReserveStockRequest {
     
     /// Order ID this reservation is for (for tracking)
     pub order_id: String,
+
+    /// Sales channel the order came from ("source" is accepted too)
+    #[serde(default, alias = "source")]
+    pub channel: SalesChannel,
+
+    /// Strength of the hold (default: hard)
+    #[serde(default)]
+    pub hold: HoldType,
+
+    /// 0-100 (default: 0). When stock runs short, soft holds of a lower
+    /// priority are bumped to make room.
+    #[serde(default)]
+    pub priority: i32,
+
+    /// Cap on what the requesting client may hold of the SKU, set by the
+    /// handler from the fairness policy (see fairness.rs)
+    #[serde(skip)]
+    pub quota: Option<ClientQuota>,
+}
+
+impl Validate for ReserveStockRequest {
+    fn check(&self, v: &mut Validator) {
+        v.text("sku", &self.sku, MAX_SKU_LEN);
+        v.positive("quantity", self.quantity);
+        v.text("order_id", &self.order_id, MAX_TEXT_LEN);
+        v.range("priority", self.priority, 0, MAX_HOLD_PRIORITY);
+    }
+}
+
+impl ReserveStockRequest {
+    /// Whether this request needs more than a plain hard hold, which the
+    /// Redis fast path can't give
+    pub fn needs_hold_handling(&self) -> bool {
+        self.hold == HoldType::Soft || self.priority > 0
+    }
+}
+
+/// Highest reservation priority
+pub const MAX_HOLD_PRIORITY: i32 = 100;
+
+/// How firmly a reservation holds its stock
+///
+/// The strength applies to an order's whole holding of a SKU: a hard
+/// reserve makes the holding hard, a soft one only adds to a holding that
+/// is empty or already soft.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HoldType {
+    /// May be bumped by a higher-priority reservation
+    Soft,
+    /// Held until released, confirmed or expired
+    #[default]
+    Hard,
+}
+
+impl HoldType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Soft => "soft",
+            Self::Hard => "hard",
+        }
+    }
+}
+
+/// A soft holding released to make room for another reservation
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct BumpedHold {
+    pub order_id: String,
+    pub quantity: i32,
+    pub priority: i32,
+}
+
+/// Most lines one batch reservation may carry
+pub const MAX_BATCH_LINES: usize = 100;
+
+/// Request body for reserving several SKUs for one order, all or nothing
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "order_id": "ORD-12345",
+///   "channel": "web",
+///   "items": [
+///     { "sku": "SKU-LAPTOP-001", "quantity": 1 },
+///     { "sku": "SKU-MOUSE-001", "quantity": 2 }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReserveBatchRequest {
+    pub order_id: String,
+
+    /// Sales channel the order came from ("source" is accepted too)
+    #[serde(default, alias = "source")]
+    pub channel: SalesChannel,
+
+    /// Strength of every line's hold (default: hard)
+    #[serde(default)]
+    pub hold: HoldType,
+
+    /// Priority of every line (default: 0), see ReserveStockRequest
+    #[serde(default)]
+    pub priority: i32,
+
+    pub items: Vec<ReserveLine>,
+
+    /// Cap on what the requesting client may hold of each SKU, set by the
+    /// handler from the fairness policy (see fairness.rs)
+    #[serde(skip)]
+    pub quota: Option<ClientQuota>,
+}
+
+/// One line of a batch reservation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ReserveLine {
+    pub sku: String,
+    pub quantity: i32,
+}
+
+/// Check the shape of the batch before touching stock: 1-100 lines with a
+/// positive quantity each, no SKU listed twice
+impl Validate for ReserveBatchRequest {
+    fn check(&self, v: &mut Validator) {
+        v.text("order_id", &self.order_id, MAX_TEXT_LEN);
+        v.range("priority", self.priority, 0, MAX_HOLD_PRIORITY);
+        if self.items.is_empty() || self.items.len() > MAX_BATCH_LINES {
+            v.add("items", format!("must have 1-{} lines", MAX_BATCH_LINES));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for (i, line) in self.items.iter().enumerate() {
+            v.text(format!("items[{}].sku", i), &line.sku, MAX_SKU_LEN);
+            v.positive(format!("items[{}].quantity", i), line.quantity);
+            if !seen.insert(line.sku.as_str()) {
+                v.add(format!("items[{}].sku", i), format!("{} is listed more than once", line.sku));
+            }
+        }
+    }
+}
+
+/// Response of a batch reservation: one reservation per line, by SKU
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReserveBatchResponse {
+    pub order_id: String,
+    pub reservations: Vec<ReservationResponse>,
+}
+
+/// Sales channel of a reservation.
+///
+/// A closed set, because it is used as a metric label; anything else is
+/// rejected when the request is parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SalesChannel {
+    Web,
+    Pos,
+    B2b,
+    /// Caller didn't say
+    #[default]
+    Unknown,
+}
+
+impl SalesChannel {
+    /// Name used in metric labels and the audit trail
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Web => "web",
+            Self::Pos => "pos",
+            Self::B2b => "b2b",
+            Self::Unknown => "unknown",
+        }
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -124,7 +388,7 @@ pub struct ReserveStockRequest {
 // -----------------------------------------------------------------------------
 /// Request body for releasing reserved stock
 /// Used when an order is cancelled or expired
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ReleaseStockRequest {
     /// SKU of the product
     pub sku: String,
@@ -136,152 +400,2763 @@ pub struct ReleaseStockRequest {
     pub order_id: String,
 }
 
+impl Validate for ReleaseStockRequest {
+    fn check(&self, v: &mut Validator) {
+        v.text("sku", &self.sku, MAX_SKU_LEN);
+        v.positive("quantity", self.quantity);
+        v.text("order_id", &self.order_id, MAX_TEXT_LEN);
+    }
+}
+
 // -----------------------------------------------------------------------------
-// STOCK ADJUSTMENT REQUEST
+// CANCEL BY ORDER
 // -----------------------------------------------------------------------------
-/// Request body for manual stock adjustments
-/// Used for inventory corrections, receiving shipments, etc.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AdjustStockRequest {
-    /// SKU of the product
+/// Request body for cancelling every active reservation of an order
+///
+/// # Example JSON
+/// ```json
+/// { "order_id": "ORD-12345" }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CancelOrderRequest {
+    pub order_id: String,
+}
+
+impl Validate for CancelOrderRequest {
+    fn check(&self, v: &mut Validator) {
+        v.text("order_id", &self.order_id, MAX_TEXT_LEN);
+    }
+}
+
+/// What cancelling an order's reservations gave back
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "order_id": "ORD-12345",
+///   "released": [
+///     { "sku": "SKU-LAPTOP-001", "quantity": 2 },
+///     { "sku": "SKU-MOUSE-001", "quantity": 5 }
+///   ],
+///   "total_units": 7
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CancelOrderResponse {
+    pub order_id: String,
+
+    /// Units released per SKU (empty when nothing was held)
+    pub released: Vec<ReleasedStock>,
+
+    pub total_units: i64,
+}
+
+/// Units of one SKU released for an order
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ReleasedStock {
     pub sku: String,
-    
-    /// Amount to adjust (positive to add, negative to remove)
-    pub delta: i32,
-    
-    /// Reason for adjustment (for audit trail)
-    pub reason: String,
+    pub quantity: i32,
 }
 
-// -----------------------------------------------------------------------------
-// RESERVATION RESPONSE
-// -----------------------------------------------------------------------------
-/// Response after successfully reserving stock
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ReservationResponse {
-    /// Unique ID for this reservation
-    pub reservation_id: Uuid,
-    
-    /// SKU that was reserved
+/// Units an order has held on one SKU past the reservation hold time
+#[derive(Debug, Clone, FromRow)]
+pub struct ExpiredReservation {
+    pub order_id: String,
     pub sku: String,
-    
-    /// Quantity reserved
     pub quantity: i32,
-    
-    /// When the reservation was made
+}
+
+/// Units an order currently holds on one SKU
+#[derive(Debug, Clone, FromRow)]
+pub struct ActiveReservation {
+    pub order_id: String,
+    pub sku: String,
+    pub quantity: i32,
+    /// When the order last reserved units of the SKU
+    pub last_reserved_at: DateTime<Utc>,
+}
+
+/// Statuses a listed reservation can have
+pub const RESERVATION_STATUSES: [&str; 4] = ["active", "expired", "released", "confirmed"];
+
+/// One reservation (row in `reservations`) and what became of it
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ReservationRecord {
+    pub reservation_id: String,
+    pub order_id: String,
+    pub sku: String,
+
+    /// Units reserved
+    pub quantity: i32,
+
+    /// Units the order still holds; less than `quantity` after a partial
+    /// release, 0 once the reservation is no longer active
+    pub held: i32,
+
+    /// One of RESERVATION_STATUSES: active while units are held, otherwise
+    /// how the last of them went (expired by the sweeper, released, bumped
+    /// by a higher priority = released, or confirmed)
+    pub status: String,
+
     pub created_at: DateTime<Utc>,
-    
-    /// When the reservation expires (optional)
     pub expires_at: Option<DateTime<Utc>>,
+
+    /// When the last units went (null while active)
+    pub ended_at: Option<DateTime<Utc>>,
 }
 
-// -----------------------------------------------------------------------------
-// INVENTORY LIST RESPONSE
-// -----------------------------------------------------------------------------
-/// Response for listing inventory items with pagination metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct InventoryListResponse {
-    /// List of inventory items
-    pub items: Vec<InventoryItem>,
-    
-    /// Total count (for pagination)
+/// Which reservations GET /api/v1/reservations returns; unset fields don't
+/// filter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReservationFilter<'a> {
+    pub sku: Option<&'a str>,
+    pub order_id: Option<&'a str>,
+    /// One of RESERVATION_STATUSES
+    pub status: Option<&'a str>,
+    /// Only reservations made at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only reservations made before this time
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Response of GET /api/v1/reservations
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReservationListResponse {
+    /// Newest first
+    pub items: Vec<ReservationRecord>,
     pub total: i64,
-    
-    /// Current page number
     pub page: i32,
-    
-    /// Items per page
     pub per_page: i32,
 }
 
 // -----------------------------------------------------------------------------
-// LOW STOCK ALERT
+// TWO-PHASE COMMITMENTS
 // -----------------------------------------------------------------------------
-/// Represents a low stock alert for monitoring
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LowStockAlert {
-    /// Product SKU
-    pub sku: String,
-    
-    /// Product name
-    pub name: String,
-    
-    /// Current available quantity
-    pub available: i32,
-    
-    /// Configured threshold
-    pub threshold: i32,
-    
-    /// Warehouse location
-    pub warehouse: String,
+// prepare / commit / abort for external checkouts (see commitments.rs).
+
+/// Prepare timeout when the request doesn't set one, in seconds
+pub const DEFAULT_COMMITMENT_TIMEOUT_SECS: u64 = 60;
+
+/// Longest a prepared commitment may hold stock, in seconds
+pub const MAX_COMMITMENT_TIMEOUT_SECS: u64 = 900;
+
+/// Longest transaction ID accepted
+pub const MAX_TRANSACTION_ID_LEN: usize = 64;
+
+/// Request body for POST /api/v1/inventory/prepare
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "transaction_id": "chk-7f3a9c",
+///   "order_id": "ORD-12345",
+///   "channel": "web",
+///   "items": [{ "sku": "SKU-LAPTOP-001", "quantity": 1 }],
+///   "timeout_secs": 120
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PrepareCommitmentRequest {
+    /// Chosen by the caller's coordinator; commit and abort refer to it,
+    /// and repeating a prepare with it is harmless
+    pub transaction_id: String,
+
+    pub order_id: String,
+
+    /// Sales channel the order came from ("source" is accepted too)
+    #[serde(default, alias = "source")]
+    pub channel: SalesChannel,
+
+    pub items: Vec<ReserveLine>,
+
+    /// Seconds until the stock is released unless committed
+    /// (default: 60, max: 900)
+    pub timeout_secs: Option<u64>,
+
+    /// Cap on what the requesting client may hold of each SKU, set by the
+    /// handler from the fairness policy (see fairness.rs)
+    #[serde(skip)]
+    pub quota: Option<ClientQuota>,
 }
 
-// =============================================================================
-// HEALTH CHECK RESPONSES
-// =============================================================================
-// Standard health check response structures
+/// Check the request before touching stock: a well-formed transaction ID, a
+/// timeout in range, and anything a batch reservation would check
+impl Validate for PrepareCommitmentRequest {
+    fn check(&self, v: &mut Validator) {
+        v.check("transaction_id", validate_transaction_id(&self.transaction_id));
+        v.range("timeout_secs", self.timeout_secs(), 1, MAX_COMMITMENT_TIMEOUT_SECS);
+        self.to_batch().check(v);
+    }
+}
 
-/// Simple health check response
-#[derive(Debug, Serialize)]
-pub struct HealthResponse {
-    pub status: String,
-    pub service: String,
-    pub version: String,
+impl PrepareCommitmentRequest {
+    pub fn timeout_secs(&self) -> u64 {
+        self.timeout_secs.unwrap_or(DEFAULT_COMMITMENT_TIMEOUT_SECS)
+    }
+
+    /// The lines as a hard, all-or-nothing batch reservation
+    pub fn to_batch(&self) -> ReserveBatchRequest {
+        ReserveBatchRequest {
+            order_id: self.order_id.clone(),
+            channel: self.channel,
+            hold: HoldType::Hard,
+            priority: 0,
+            items: self.items.clone(),
+            quota: self.quota.clone(),
+        }
+    }
 }
 
-/// Detailed readiness check response
-#[derive(Debug, Serialize)]
-pub struct ReadinessResponse {
-    pub status: String,
-    pub checks: ReadinessChecks,
+/// Request body for POST /api/v1/inventory/commit and /abort
+///
+/// # Example JSON
+/// ```json
+/// { "transaction_id": "chk-7f3a9c" }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CommitmentRequest {
+    pub transaction_id: String,
 }
 
-/// Individual dependency health checks
-#[derive(Debug, Serialize)]
-pub struct ReadinessChecks {
-    pub database: bool,
-    pub redis: bool,
+impl Validate for CommitmentRequest {
+    fn check(&self, v: &mut Validator) {
+        v.check("transaction_id", validate_transaction_id(&self.transaction_id));
+    }
 }
 
-// =============================================================================
-// ERROR RESPONSES
-// =============================================================================
-// Standardized error response format for API
+/// Check a caller-chosen transaction ID: 1-64 letters, digits, '-', '_',
+/// '.' or ':'
+pub fn validate_transaction_id(id: &str) -> Result<(), String> {
+    let valid_chars = id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    if id.is_empty() || id.len() > MAX_TRANSACTION_ID_LEN || !valid_chars {
+        return Err(format!(
+            "must be 1-{} letters, digits, '-', '_', '.' or ':'",
+            MAX_TRANSACTION_ID_LEN
+        ));
+    }
+    Ok(())
+}
 
-/// API error response body
-#[derive(Debug, Serialize)]
-pub struct ErrorResponse {
-    /// Error type/code
-    pub error: String,
-    
-    /// Human-readable error message
-    pub message: String,
-    
-    /// Optional additional details
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub details: Option<String>,
+/// Where a two-phase commitment stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitmentState {
+    /// Stock reserved, waiting for commit or abort
+    Prepared,
+    /// Units sold (quantity and reserved dropped)
+    Committed,
+    /// Stock released on request
+    Aborted,
+    /// Stock released because the timeout passed first
+    Expired,
 }
 
-impl ErrorResponse {
-    /// Create a new error response
-    pub fn new(error: impl Into<String>, message: impl Into<String>) -> Self {
-        Self {
-            error: error.into(),
-            message: message.into(),
-            details: None,
+impl CommitmentState {
+    /// Value stored in stock_commitments.state
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Prepared => "prepared",
+            Self::Committed => "committed",
+            Self::Aborted => "aborted",
+            Self::Expired => "expired",
         }
     }
-    
-    /// Create an error response with details
-    pub fn with_details(
-        error: impl Into<String>,
-        message: impl Into<String>,
-        details: impl Into<String>,
-    ) -> Self {
-        Self {
-            error: error.into(),
-            message: message.into(),
-            details: Some(details.into()),
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "prepared" => Some(Self::Prepared),
+            "committed" => Some(Self::Committed),
+            "aborted" => Some(Self::Aborted),
+            "expired" => Some(Self::Expired),
+            _ => None,
+        }
+    }
+}
+
+/// A two-phase commitment, as returned by prepare, commit and abort
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "transaction_id": "chk-7f3a9c",
+///   "order_id": "ORD-12345",
+///   "state": "prepared",
+///   "lines": [{ "sku": "SKU-LAPTOP-001", "quantity": 1 }],
+///   "expires_at": "2024-05-01T12:02:00Z",
+///   "applied": true
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StockCommitment {
+    pub transaction_id: String,
+    pub order_id: String,
+    pub state: CommitmentState,
+
+    /// Reserved lines, by SKU
+    pub lines: Vec<ReserveLine>,
+
+    /// When a prepared commitment times out
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+
+    /// False when the call changed nothing (a repeated prepare, commit or
+    /// abort)
+    pub applied: bool,
+}
+
+// -----------------------------------------------------------------------------
+// STOCK ADJUSTMENT REQUEST
+// -----------------------------------------------------------------------------
+/// Request body for manual stock adjustments
+/// Used for inventory corrections, receiving shipments, etc.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AdjustStockRequest {
+    /// SKU of the product
+    pub sku: String,
+    
+    /// Amount to adjust (positive to add, negative to remove)
+    pub delta: i32,
+    
+    /// Reason for adjustment (for audit trail)
+    pub reason: String,
+
+    /// Apply only if the item is still at this version (like If-Match)
+    #[serde(default)]
+    pub expected_version: Option<i64>,
+}
+
+impl Validate for AdjustStockRequest {
+    fn check(&self, v: &mut Validator) {
+        v.text("sku", &self.sku, MAX_SKU_LEN);
+        v.text("reason", &self.reason, MAX_TEXT_LEN);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// BULK UPDATE
+// -----------------------------------------------------------------------------
+/// Most items one bulk update may touch
+pub const MAX_BULK_ITEMS: usize = 1000;
+
+/// Request body for changing many items at once
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "filter": { "warehouse": "JKT-1", "category": "laptops" },
+///   "patch": { "low_stock_threshold_pct": 20 },
+///   "reason": "Holiday season",
+///   "dry_run": true
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct BulkUpdateRequest {
+    pub filter: BulkFilter,
+    pub patch: BulkPatch,
+
+    /// Why the items changed (for the audit trail)
+    pub reason: String,
+
+    /// Only report what would change (default: false)
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Items a bulk update applies to; every given criterion must match
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct BulkFilter {
+    pub warehouse: Option<String>,
+    pub sku_prefix: Option<String>,
+    /// Matches the `category` attribute
+    pub category: Option<String>,
+}
+
+/// Changes of a bulk update; unset fields stay as they are
+///
+/// A field and its `_pct` variant can't be combined. Percentages scale the
+/// current value (20 = +20%, -10 = -10%).
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct BulkPatch {
+    pub low_stock_threshold: Option<i32>,
+    pub low_stock_threshold_pct: Option<f64>,
+    pub unit_price: Option<f64>,
+    /// Items without a price keep none
+    pub unit_price_pct: Option<f64>,
+    /// New home warehouse
+    pub warehouse: Option<String>,
+}
+
+/// Check that the filter narrows something and the patch makes sense
+impl Validate for BulkUpdateRequest {
+    fn check(&self, v: &mut Validator) {
+        let filter = &self.filter;
+        if [&filter.warehouse, &filter.sku_prefix, &filter.category]
+            .iter()
+            .all(|criterion| criterion.as_deref().is_none_or(|c| c.is_empty()))
+        {
+            v.add("filter", "needs a warehouse, sku_prefix or category");
+        }
+        v.text("reason", &self.reason, MAX_TEXT_LEN);
+
+        let patch = &self.patch;
+        if patch.low_stock_threshold.is_none()
+            && patch.low_stock_threshold_pct.is_none()
+            && patch.unit_price.is_none()
+            && patch.unit_price_pct.is_none()
+            && patch.warehouse.is_none()
+        {
+            v.add("patch", "changes nothing");
+        }
+        if patch.low_stock_threshold.is_some() && patch.low_stock_threshold_pct.is_some() {
+            v.add("patch.low_stock_threshold_pct", "can't be combined with low_stock_threshold");
+        }
+        if patch.unit_price.is_some() && patch.unit_price_pct.is_some() {
+            v.add("patch.unit_price_pct", "can't be combined with unit_price");
+        }
+        if let Some(threshold) = patch.low_stock_threshold {
+            v.non_negative("patch.low_stock_threshold", threshold);
+        }
+        if patch.unit_price.is_some_and(|p| !p.is_finite() || p < 0.0) {
+            v.add("patch.unit_price", "must not be negative");
+        }
+        for (field, pct) in [
+            ("patch.low_stock_threshold_pct", patch.low_stock_threshold_pct),
+            ("patch.unit_price_pct", patch.unit_price_pct),
+        ] {
+            if pct.is_some_and(|pct| !pct.is_finite() || pct <= -100.0) {
+                v.add(field, "must be above -100");
+            }
+        }
+        if let Some(warehouse) = &patch.warehouse {
+            v.text("patch.warehouse", warehouse, MAX_SKU_LEN);
+        }
+    }
+}
+
+/// Fields of an item a bulk update can change
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct BulkItemValues {
+    pub sku: String,
+    pub low_stock_threshold: i32,
+    pub unit_price: Option<f64>,
+    pub warehouse: String,
+}
+
+impl BulkPatch {
+    /// Values of `item` after the patch
+    pub fn apply(&self, item: &BulkItemValues) -> BulkItemValues {
+        let scale = |value: f64, pct: f64| value * (1.0 + pct / 100.0);
+
+        let low_stock_threshold = match (self.low_stock_threshold, self.low_stock_threshold_pct) {
+            (Some(threshold), _) => threshold,
+            (None, Some(pct)) => scale(item.low_stock_threshold as f64, pct).round().max(0.0) as i32,
+            (None, None) => item.low_stock_threshold,
+        };
+        // Prices are stored with two decimals
+        let unit_price = match (self.unit_price, self.unit_price_pct) {
+            (Some(price), _) => Some(price),
+            (None, Some(pct)) => item.unit_price.map(|price| scale(price, pct)),
+            (None, None) => item.unit_price,
+        }
+        .map(|price| (price * 100.0).round() / 100.0);
+
+        BulkItemValues {
+            sku: item.sku.clone(),
+            low_stock_threshold,
+            unit_price,
+            warehouse: self.warehouse.clone().unwrap_or_else(|| item.warehouse.clone()),
+        }
+    }
+}
+
+/// One item a bulk update changes
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BulkChange {
+    pub before: BulkItemValues,
+    pub after: BulkItemValues,
+}
+
+/// Response of POST /api/v1/inventory/bulk-update
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BulkUpdateResponse {
+    /// Nothing was written
+    pub dry_run: bool,
+
+    /// Items matching the filter
+    pub matched: usize,
+
+    /// Items the patch changes (or would change), by SKU
+    pub changes: Vec<BulkChange>,
+}
+
+// -----------------------------------------------------------------------------
+// WAREHOUSE TRANSFER
+// -----------------------------------------------------------------------------
+/// Request body for moving stock between warehouses
+///
+/// # Example JSON
+/// ```json
+/// { "sku": "SKU-LAPTOP-001", "from_warehouse": "JKT-1", "to_warehouse": "SBY-1", "quantity": 5 }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct TransferStockRequest {
+    pub sku: String,
+    pub from_warehouse: String,
+    pub to_warehouse: String,
+
+    /// Units to move (must be positive)
+    pub quantity: i32,
+
+    /// Why the stock moved (for the ledger and audit trail)
+    pub reason: Option<String>,
+}
+
+/// Check the warehouse codes and that something actually moves
+impl Validate for TransferStockRequest {
+    fn check(&self, v: &mut Validator) {
+        v.text("sku", &self.sku, MAX_SKU_LEN);
+        v.text("from_warehouse", &self.from_warehouse, MAX_SKU_LEN);
+        v.text("to_warehouse", &self.to_warehouse, MAX_SKU_LEN);
+        if self.from_warehouse == self.to_warehouse {
+            v.add("to_warehouse", "must differ from from_warehouse");
+        }
+        v.positive("quantity", self.quantity);
+        v.optional_text("reason", self.reason.as_deref(), MAX_TEXT_LEN);
+    }
+}
+
+/// Units of a SKU held at one warehouse
+///
+/// One `stock` row. New reservations are taken from home; a former home
+/// warehouse keeps the reservations it had until they are released or
+/// confirmed.
+///
+/// # Example JSON
+/// ```json
+/// { "warehouse": "JKT-1", "quantity": 80, "reserved": 5, "available": 75, "home": true }
+/// ```
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct WarehouseStock {
+    pub warehouse: String,
+    pub quantity: i32,
+    pub reserved: i32,
+    pub available: i32,
+
+    /// Whether this is the SKU's home warehouse, where reservations and
+    /// adjustments apply
+    pub home: bool,
+}
+
+/// Response of POST /api/v1/inventory/transfer
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TransferStockResponse {
+    pub sku: String,
+    pub from_warehouse: String,
+    pub to_warehouse: String,
+    pub quantity: i32,
+
+    /// Where the SKU's stock is after the transfer, home warehouse first
+    pub warehouses: Vec<WarehouseStock>,
+}
+
+// -----------------------------------------------------------------------------
+// CREATE ITEM
+// -----------------------------------------------------------------------------
+/// Longest SKU and warehouse code (VARCHAR(50) columns)
+pub const MAX_SKU_LEN: usize = 50;
+
+/// Longest item name
+pub const MAX_NAME_LEN: usize = 255;
+
+/// Request body for adding an item to the catalog
+///
+/// # Example JSON
+/// ```json
+/// { "sku": "SKU-DOCK-001", "name": "USB-C Dock", "quantity": 20, "warehouse": "JKT-1" }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateItemRequest {
+    pub sku: String,
+    pub name: String,
+
+    /// Initial stock (default: 0)
+    #[serde(default)]
+    pub quantity: i32,
+
+    /// Warehouse code (default: "DEFAULT")
+    #[serde(default = "default_warehouse")]
+    pub warehouse: String,
+
+    /// Low-stock alert threshold (default: 10)
+    #[serde(default = "default_low_stock_threshold")]
+    pub low_stock_threshold: i32,
+}
+
+fn default_warehouse() -> String {
+    "DEFAULT".to_string()
+}
+
+fn default_low_stock_threshold() -> i32 {
+    10
+}
+
+/// Check lengths and that numbers aren't negative
+impl Validate for CreateItemRequest {
+    fn check(&self, v: &mut Validator) {
+        v.text("sku", &self.sku, MAX_SKU_LEN);
+        v.text("name", &self.name, MAX_NAME_LEN);
+        v.text("warehouse", &self.warehouse, MAX_SKU_LEN);
+        v.non_negative("quantity", self.quantity);
+        v.non_negative("low_stock_threshold", self.low_stock_threshold);
+    }
+}
+
+/// Why an item wasn't created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateItemRejection {
+    /// The SKU is already in the catalog
+    DuplicateSku,
+    /// The catalog already holds `skus` SKUs, the hard limit
+    QuotaExceeded { skus: i64 },
+}
+
+// -----------------------------------------------------------------------------
+// UPDATE ITEM
+// -----------------------------------------------------------------------------
+/// Request body for PATCH /api/v1/inventory/:sku; unset fields stay as
+/// they are
+///
+/// # Example JSON
+/// ```json
+/// { "name": "USB-C Dock (Gen 2)", "low_stock_threshold": 15, "reason": "New model" }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateItemRequest {
+    pub name: Option<String>,
+
+    /// New home warehouse
+    pub warehouse: Option<String>,
+
+    /// Pins the threshold, like PUT /api/v1/inventory/:sku/thresholds
+    pub low_stock_threshold: Option<i32>,
+
+    /// Why the item changed (for the audit trail)
+    pub reason: Option<String>,
+}
+
+impl Validate for UpdateItemRequest {
+    fn check(&self, v: &mut Validator) {
+        if self.name.is_none() && self.warehouse.is_none() && self.low_stock_threshold.is_none() {
+            v.add("body", "needs a name, warehouse or low_stock_threshold");
+        }
+        if let Some(name) = &self.name {
+            v.text("name", name, MAX_NAME_LEN);
+        }
+        if let Some(warehouse) = &self.warehouse {
+            v.text("warehouse", warehouse, MAX_SKU_LEN);
+        }
+        if let Some(threshold) = self.low_stock_threshold {
+            v.non_negative("low_stock_threshold", threshold);
+        }
+        v.optional_text("reason", self.reason.as_deref(), MAX_TEXT_LEN);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// CSV IMPORT
+// -----------------------------------------------------------------------------
+/// What happened to one row of an import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportRowStatus {
+    Created,
+    Updated,
+    /// The item already matched the row
+    Unchanged,
+    Error,
+}
+
+/// Result of one row of an import
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportRowResult {
+    /// Line in the file (the header is line 1)
+    pub line: u64,
+
+    /// Empty when the row couldn't be read
+    pub sku: String,
+
+    pub status: ImportRowStatus,
+
+    /// Why the row was rejected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ImportRowResult {
+    pub fn ok(line: u64, sku: &str, status: ImportRowStatus) -> Self {
+        Self {
+            line,
+            sku: sku.to_string(),
+            status,
+            error: None,
+        }
+    }
+
+    pub fn error(line: u64, sku: &str, error: String) -> Self {
+        Self {
+            line,
+            sku: sku.to_string(),
+            status: ImportRowStatus::Error,
+            error: Some(error),
+        }
+    }
+}
+
+/// Response of POST /api/v1/inventory/import
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportResponse {
+    /// Nothing was written
+    pub dry_run: bool,
+
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+
+    /// Every data row, in file order
+    pub rows: Vec<ImportRowResult>,
+}
+
+impl ImportResponse {
+    /// Count the outcomes of `rows`
+    pub fn new(dry_run: bool, mut rows: Vec<ImportRowResult>) -> Self {
+        rows.sort_by_key(|row| row.line);
+        let count = |status| rows.iter().filter(|row| row.status == status).count();
+
+        Self {
+            dry_run,
+            created: count(ImportRowStatus::Created),
+            updated: count(ImportRowStatus::Updated),
+            unchanged: count(ImportRowStatus::Unchanged),
+            failed: count(ImportRowStatus::Error),
+            rows,
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// RESERVATION POLICY
+// -----------------------------------------------------------------------------
+/// Request body for setting a SKU's reservation limits
+///
+/// Omitted or null fields remove that limit.
+///
+/// # Example JSON
+/// ```json
+/// { "max_per_order": 2, "max_reserved_pct": 50 }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ReservationPolicy {
+    /// Most units a single order may reserve
+    #[serde(default)]
+    pub max_per_order: Option<i32>,
+
+    /// Most of the stock that may be reserved at once (1-100)
+    #[serde(default)]
+    pub max_reserved_pct: Option<i32>,
+}
+
+impl Validate for ReservationPolicy {
+    fn check(&self, v: &mut Validator) {
+        if let Some(max) = self.max_per_order {
+            v.positive("max_per_order", max);
+        }
+        if let Some(pct) = self.max_reserved_pct {
+            v.range("max_reserved_pct", pct, 1, 100);
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// LOW STOCK THRESHOLD
+// -----------------------------------------------------------------------------
+/// Request body for setting a SKU's low-stock threshold
+///
+/// A number pins the threshold (manual override); null hands it back to
+/// dynamic recalculation.
+///
+/// # Example JSON
+/// ```json
+/// { "threshold": 15 }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct LowStockThresholdRequest {
+    pub threshold: Option<i32>,
+}
+
+impl Validate for LowStockThresholdRequest {
+    fn check(&self, v: &mut Validator) {
+        if let Some(threshold) = self.threshold {
+            v.non_negative("threshold", threshold);
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// WAREHOUSE THRESHOLD
+// -----------------------------------------------------------------------------
+/// Low-stock threshold for a SKU in one warehouse, overriding the item's
+/// `low_stock_threshold` there
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct WarehouseThreshold {
+    pub sku: String,
+    pub warehouse: String,
+    pub threshold: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request body for setting a per-warehouse threshold
+///
+/// # Example JSON
+/// ```json
+/// { "threshold": 25 }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct WarehouseThresholdRequest {
+    pub threshold: i32,
+}
+
+impl Validate for WarehouseThresholdRequest {
+    fn check(&self, v: &mut Validator) {
+        v.non_negative("threshold", self.threshold);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// WAREHOUSES
+// -----------------------------------------------------------------------------
+/// Longest warehouse region
+pub const MAX_REGION_LEN: usize = 100;
+
+/// A registered warehouse (GET /api/v1/warehouses)
+///
+/// # Example JSON
+/// ```json
+/// { "code": "JKT-1", "name": "Jakarta 1", "region": "Jakarta", "capacity": 50000,
+///   "created_at": "2024-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z" }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Warehouse {
+    /// Code items and transfers refer to, e.g. "JKT-1"
+    pub code: String,
+    pub name: String,
+    pub region: Option<String>,
+
+    /// Units the warehouse can hold (None = not tracked)
+    pub capacity: Option<i32>,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request body for registering a warehouse
+///
+/// # Example JSON
+/// ```json
+/// { "code": "MDN-1", "name": "Medan 1", "region": "Sumatra", "capacity": 20000 }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateWarehouseRequest {
+    pub code: String,
+    pub name: String,
+    pub region: Option<String>,
+    pub capacity: Option<i32>,
+}
+
+impl Validate for CreateWarehouseRequest {
+    fn check(&self, v: &mut Validator) {
+        v.text("code", &self.code, MAX_SKU_LEN);
+        self.details().check(v);
+    }
+}
+
+impl CreateWarehouseRequest {
+    /// Everything but the code
+    pub fn details(&self) -> UpdateWarehouseRequest {
+        UpdateWarehouseRequest {
+            name: self.name.clone(),
+            region: self.region.clone(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+/// Request body for PUT /api/v1/warehouses/:code; replaces every field
+/// (the code can't change)
+///
+/// # Example JSON
+/// ```json
+/// { "name": "Medan 1", "region": "Sumatra", "capacity": 25000 }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdateWarehouseRequest {
+    pub name: String,
+    pub region: Option<String>,
+    pub capacity: Option<i32>,
+}
+
+impl Validate for UpdateWarehouseRequest {
+    fn check(&self, v: &mut Validator) {
+        v.text("name", &self.name, MAX_NAME_LEN);
+        v.optional_text("region", self.region.as_deref(), MAX_REGION_LEN);
+        if let Some(capacity) = self.capacity {
+            v.positive("capacity", capacity);
+        }
+    }
+}
+
+/// Stock held at one warehouse (GET /api/v1/warehouses/:code/summary)
+///
+/// Counts the warehouse's share of each SKU like
+/// GET /api/v1/inventory/:sku/warehouses: SKUs homed here with what they
+/// didn't transfer away, plus what other SKUs transferred here.
+///
+/// # Example JSON
+/// ```json
+/// { "code": "JKT-1", "name": "Jakarta 1", "region": "Jakarta", "capacity": 50000,
+///   "skus": 6, "home_skus": 5, "quantity": 930, "reserved": 12, "available": 918,
+///   "utilization_pct": 1.86, "low_stock_skus": 1 }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WarehouseSummary {
+    pub code: String,
+    pub name: String,
+    pub region: Option<String>,
+    pub capacity: Option<i32>,
+
+    /// SKUs with stock here or homed here
+    pub skus: i64,
+
+    /// SKUs whose home warehouse this is
+    pub home_skus: i64,
+
+    /// Units held here
+    pub quantity: i64,
+
+    /// Units reserved (always taken from the home warehouse)
+    pub reserved: i64,
+    pub available: i64,
+
+    /// quantity / capacity in percent; None without a capacity
+    pub utilization_pct: Option<f64>,
+
+    /// SKUs homed here below their low-stock threshold for this warehouse
+    pub low_stock_skus: i64,
+}
+
+impl WarehouseSummary {
+    pub fn new(warehouse: Warehouse, totals: WarehouseTotals) -> Self {
+        Self {
+            utilization_pct: warehouse
+                .capacity
+                .map(|capacity| (totals.quantity as f64 * 10000.0 / capacity as f64).round() / 100.0),
+            code: warehouse.code,
+            name: warehouse.name,
+            region: warehouse.region,
+            capacity: warehouse.capacity,
+            skus: totals.skus,
+            home_skus: totals.home_skus,
+            quantity: totals.quantity,
+            reserved: totals.reserved,
+            available: totals.quantity - totals.reserved,
+            low_stock_skus: totals.low_stock_skus,
+        }
+    }
+}
+
+/// Stock totals of one warehouse, as summed by the database
+#[derive(Debug, Clone, Copy, Default, FromRow)]
+pub struct WarehouseTotals {
+    pub skus: i64,
+    pub home_skus: i64,
+    pub quantity: i64,
+    pub reserved: i64,
+    pub low_stock_skus: i64,
+}
+
+// -----------------------------------------------------------------------------
+// CATALOG DETAILS
+// -----------------------------------------------------------------------------
+/// Most attributes one item may carry
+pub const MAX_ATTRIBUTES: usize = 50;
+
+/// Longest attribute name
+pub const MAX_ATTRIBUTE_NAME_LEN: usize = 64;
+
+/// Largest serialized attributes object, in bytes
+pub const MAX_ATTRIBUTES_BYTES: usize = 16 * 1024;
+
+/// Longest image or spec URL
+pub const MAX_URL_LEN: usize = 2048;
+
+/// Highest unit price or cost (the columns are NUMERIC(12, 2))
+pub const MAX_UNIT_PRICE: f64 = 9_999_999_999.99;
+
+/// Request body replacing an item's catalog details
+///
+/// Omitted fields are cleared, so send the full set.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "attributes": { "color": "silver", "screen_inches": 14, "touch": false },
+///   "image_url": "https://cdn.example.com/laptop-001.jpg",
+///   "spec_url": "https://cdn.example.com/laptop-001.pdf",
+///   "unit_price": 1299.99,
+///   "unit_cost": 1040.00
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CatalogDetailsRequest {
+    #[serde(default = "empty_attributes")]
+    pub attributes: serde_json::Value,
+
+    pub image_url: Option<String>,
+
+    pub spec_url: Option<String>,
+
+    pub unit_price: Option<f64>,
+
+    /// What one unit cost; stock value (GET responses, the
+    /// inventory_stock_value metric) is quantity × unit_cost
+    pub unit_cost: Option<f64>,
+}
+
+/// Check the details before storing them: attributes a small JSON object
+/// with short, non-empty names, http(s) URLs, a price and cost in range
+impl Validate for CatalogDetailsRequest {
+    fn check(&self, v: &mut Validator) {
+        match self.attributes.as_object() {
+            None => v.add("attributes", "must be a JSON object"),
+            Some(attributes) => {
+                if attributes.len() > MAX_ATTRIBUTES {
+                    v.add("attributes", format!("at most {} attributes are allowed", MAX_ATTRIBUTES));
+                }
+                for name in attributes
+                    .keys()
+                    .filter(|name| name.trim().is_empty() || name.len() > MAX_ATTRIBUTE_NAME_LEN)
+                {
+                    v.add(
+                        format!("attributes.{}", name),
+                        format!("attribute names must be 1-{} characters", MAX_ATTRIBUTE_NAME_LEN),
+                    );
+                }
+                if self.attributes.to_string().len() > MAX_ATTRIBUTES_BYTES {
+                    v.add("attributes", format!("must be at most {} bytes", MAX_ATTRIBUTES_BYTES));
+                }
+            }
+        }
+
+        for (field, url) in [("image_url", &self.image_url), ("spec_url", &self.spec_url)] {
+            let Some(url) = url else { continue };
+            let http = url.starts_with("https://") || url.starts_with("http://");
+            if !http || url.len() > MAX_URL_LEN || url.contains(char::is_whitespace) {
+                v.add(field, format!("must be an http(s) URL of at most {} characters", MAX_URL_LEN));
+            }
+        }
+
+        if let Some(price) = self.unit_price {
+            v.range("unit_price", price, 0.0, MAX_UNIT_PRICE);
+        }
+        if let Some(cost) = self.unit_cost {
+            v.range("unit_cost", cost, 0.0, MAX_UNIT_PRICE);
+        }
+    }
+}
+
+/// Typed schema of a catalog attribute (row in `attribute_schemas`)
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct AttributeSchema {
+    pub name: String,
+
+    /// "string", "number" or "boolean"
+    #[serde(rename = "type")]
+    pub attr_type: String,
+
+    /// Whether every item must carry the attribute
+    pub required: bool,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request body for defining (or redefining) an attribute schema
+///
+/// # Example JSON
+/// ```json
+/// { "type": "number", "required": false }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AttributeSchemaRequest {
+    #[serde(rename = "type")]
+    pub attr_type: AttributeType,
+
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// Nothing to check beyond the types; the name comes from the path
+impl Validate for AttributeSchemaRequest {
+    fn check(&self, _v: &mut Validator) {}
+}
+
+// -----------------------------------------------------------------------------
+// ALTERNATE IDENTIFIERS
+// -----------------------------------------------------------------------------
+/// An alternate identifier of an item (row in `item_identifiers`)
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ItemIdentifier {
+    pub identifier: String,
+    /// "vendor_sku", "legacy" or "gtin"
+    pub kind: String,
+    /// Canonical SKU it resolves to
+    pub sku: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request body for adding an alternate identifier
+///
+/// # Example JSON
+/// ```json
+/// { "kind": "gtin", "identifier": "4006381333931" }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ItemIdentifierRequest {
+    pub kind: IdentifierKind,
+    pub identifier: String,
+}
+
+impl Validate for ItemIdentifierRequest {
+    fn check(&self, v: &mut Validator) {
+        v.check("identifier", self.kind.validate(&self.identifier));
+    }
+}
+
+/// Result of GET /api/v1/inventory/lookup
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "id": "4006381333931",
+///   "matched_by": "gtin",
+///   "item": { "sku": "SKU-LAPTOP-001", "...": "..." }
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LookupResponse {
+    /// Identifier that was looked up
+    pub id: String,
+    /// "sku" for a canonical SKU, otherwise the identifier kind
+    pub matched_by: String,
+    pub item: InventoryItem,
+}
+
+// -----------------------------------------------------------------------------
+// RESERVATION RESPONSE
+// -----------------------------------------------------------------------------
+/// Response after successfully reserving stock
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReservationResponse {
+    /// Unique ID for this reservation, in the configured format (ID_FORMAT)
+    pub reservation_id: String,
+    
+    /// SKU that was reserved
+    pub sku: String,
+    
+    /// Quantity reserved
+    pub quantity: i32,
+    
+    /// When the reservation was made
+    pub created_at: DateTime<Utc>,
+    
+    /// When the reservation expires; the stock is then released by the
+    /// expiry sweeper
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Soft holds of other orders this reservation bumped
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bumped: Vec<BumpedHold>,
+
+    /// True when the order already held this SKU: nothing was reserved and
+    /// this is the reservation made the first time
+    #[serde(default)]
+    pub replayed: bool,
+}
+
+// -----------------------------------------------------------------------------
+// INVENTORY LIST RESPONSE
+// -----------------------------------------------------------------------------
+/// Response for listing inventory items with pagination metadata
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct InventoryListResponse {
+    /// List of inventory items
+    pub items: Vec<InventoryItem>,
+    
+    /// Total count (for pagination)
+    pub total: i64,
+    
+    /// Current page number
+    pub page: i32,
+    
+    /// Items per page
+    pub per_page: i32,
+}
+
+/// Which items GET /api/v1/inventory returns; unset fields don't filter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InventoryFilter<'a> {
+    pub warehouse: Option<&'a str>,
+    /// SKU starts with this (case-sensitive)
+    pub sku_prefix: Option<&'a str>,
+    /// Name contains this (case-insensitive)
+    pub name_contains: Option<&'a str>,
+    /// Only items whose available stock is below their threshold, as in
+    /// GET /api/v1/inventory/alerts
+    pub low_stock_only: bool,
+    /// Attributes contain this object (see attributes.rs)
+    pub attributes: Option<&'a serde_json::Value>,
+}
+
+/// Column the inventory list is sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    #[default]
+    Sku,
+    Name,
+    Quantity,
+    /// quantity - reserved
+    Available,
+    Warehouse,
+    UpdatedAt,
+}
+
+impl SortBy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sku => "sku",
+            Self::Name => "name",
+            Self::Quantity => "quantity",
+            Self::Available => "available",
+            Self::Warehouse => "warehouse",
+            Self::UpdatedAt => "updated_at",
+        }
+    }
+
+    /// SQL expression to sort on. Only these fixed strings ever reach the
+    /// ORDER BY clause, never user input.
+    pub fn column(self) -> &'static str {
+        match self {
+            Self::Sku => "i.sku",
+            Self::Name => "i.name",
+            Self::Quantity => "i.quantity",
+            Self::Available => "(i.quantity - i.reserved)",
+            Self::Warehouse => "i.warehouse",
+            Self::UpdatedAt => "i.updated_at",
+        }
+    }
+}
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Asc => "asc",
+            Self::Desc => "desc",
+        }
+    }
+
+    /// SQL keyword
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// LOW STOCK ALERT
+// -----------------------------------------------------------------------------
+/// Represents a low stock alert for monitoring
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LowStockAlert {
+    /// Product SKU
+    pub sku: String,
+    
+    /// Product name
+    pub name: String,
+    
+    /// Current available quantity
+    pub available: i32,
+    
+    /// Configured threshold
+    pub threshold: i32,
+    
+    /// Warehouse location
+    pub warehouse: String,
+}
+
+// =============================================================================
+// ORDER CALLBACKS
+// =============================================================================
+// Order status changes pushed by the order service (or any HTTP caller) for
+// labs that don't run Kafka.
+
+/// Order status values we act on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderCallbackStatus {
+    Paid,
+    Cancelled,
+    Fulfilled,
+}
+
+impl OrderCallbackStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrderCallbackStatus::Paid => "paid",
+            OrderCallbackStatus::Cancelled => "cancelled",
+            OrderCallbackStatus::Fulfilled => "fulfilled",
+        }
+    }
+
+    /// What happens to the order's reservations
+    ///
+    /// - paid / fulfilled → "confirm": reserved units leave on-hand stock
+    /// - cancelled → "release": reserved units become available again
+    pub fn action(self) -> &'static str {
+        match self {
+            OrderCallbackStatus::Paid | OrderCallbackStatus::Fulfilled => "confirm",
+            OrderCallbackStatus::Cancelled => "release",
+        }
+    }
+}
+
+/// One line of the order whose reservation should be settled
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderCallbackItem {
+    pub sku: String,
+    pub quantity: i32,
+}
+
+/// Request body for the order status callback
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "order_id": "ORD-12345",
+///   "status": "paid",
+///   "items": [{ "sku": "SKU-LAPTOP-001", "quantity": 2 }]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderCallbackRequest {
+    /// Order whose status changed
+    pub order_id: String,
+
+    /// New order status
+    pub status: OrderCallbackStatus,
+
+    /// Reserved lines of the order
+    pub items: Vec<OrderCallbackItem>,
+}
+
+impl Validate for OrderCallbackRequest {
+    fn check(&self, v: &mut Validator) {
+        v.text("order_id", &self.order_id, MAX_TEXT_LEN);
+        if self.items.is_empty() {
+            v.add("items", "must not be empty");
+        }
+        for (i, item) in self.items.iter().enumerate() {
+            v.text(format!("items[{}].sku", i), &item.sku, MAX_SKU_LEN);
+            v.positive(format!("items[{}].quantity", i), item.quantity);
+        }
+    }
+}
+
+/// Result of processing an order status callback
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderCallbackResponse {
+    pub order_id: String,
+
+    /// "confirm" or "release"
+    pub action: String,
+
+    /// false when the order was already settled the same way (duplicate
+    /// delivery); nothing was changed
+    pub applied: bool,
+}
+
+// =============================================================================
+// AUDIT EVENTS
+// =============================================================================
+// Security-relevant record of every stock mutation attempt.
+
+/// A recorded audit event (row in `audit_events`)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct AuditEvent {
+    /// Monotonic event ID (used as export cursor)
+    pub id: i64,
+
+    /// When the event happened
+    pub occurred_at: DateTime<Utc>,
+
+    /// Operation: "reserve", "release", "bump", "adjust", "confirm",
+    /// "reconcile", "transfer", "update"
+    pub action: String,
+
+    /// Whether the operation succeeded: "success" or "failure"
+    pub outcome: String,
+
+    /// Affected SKU
+    pub sku: String,
+
+    /// Units involved (positive or negative for adjustments)
+    pub quantity: i32,
+
+    /// Order ID or adjustment reason
+    pub reference: Option<String>,
+
+    /// Failure reason, if any
+    pub detail: Option<String>,
+
+    /// Sales channel (successful reservations only)
+    pub channel: Option<String>,
+}
+
+/// Data needed to record a new audit event
+#[derive(Debug, Clone)]
+pub struct NewAuditEvent<'a> {
+    pub action: &'a str,
+    pub outcome: &'a str,
+    pub sku: &'a str,
+    pub quantity: i32,
+    pub reference: Option<&'a str>,
+    pub detail: Option<&'a str>,
+    pub channel: Option<&'a str>,
+}
+
+// =============================================================================
+// STOCK MOVEMENTS
+// =============================================================================
+// Ledger of every change to a SKU's quantity or reserved count, written in
+// the same transaction as the change itself.
+
+/// Movement types recorded in the ledger
+pub const MOVEMENT_TYPES: [&str; 8] = [
+    "reserve",
+    "release",
+    "bump",
+    "confirm",
+    "adjust",
+    "reconcile",
+    "transfer_out",
+    "transfer_in",
+];
+
+/// One ledger entry (row in `stock_movements`)
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct StockMovement {
+    pub id: i64,
+
+    /// Sortable ID in the configured format (see ids.rs); the reservation
+    /// ID for reserve movements. Null for movements recorded before IDs.
+    pub movement_id: Option<String>,
+
+    pub sku: String,
+
+    /// One of MOVEMENT_TYPES
+    pub movement_type: String,
+
+    /// Change to `quantity` (adjustments and confirmations), or to the
+    /// warehouse's share of it for transfers
+    pub quantity_delta: i32,
+
+    /// Change to `reserved`
+    pub reserved_delta: i32,
+
+    /// Stock right after the movement
+    pub quantity_after: i32,
+    pub reserved_after: i32,
+
+    /// Order ID, or the reason of an adjustment or transfer
+    pub reference: Option<String>,
+
+    /// Warehouse the movement happened at. Null for movements recorded
+    /// before warehouses were tracked.
+    pub warehouse: Option<String>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// Data needed to record a new stock movement
+#[derive(Debug, Clone)]
+pub struct NewStockMovement<'a> {
+    pub movement_id: &'a str,
+    pub sku: &'a str,
+    pub movement_type: &'a str,
+    pub quantity_delta: i32,
+    pub reserved_delta: i32,
+    pub reference: Option<&'a str>,
+    /// Defaults to the SKU's home warehouse
+    pub warehouse: Option<&'a str>,
+}
+
+/// Response of GET /api/v1/inventory/:sku/movements
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StockMovementListResponse {
+    /// Newest first
+    pub items: Vec<StockMovement>,
+    pub total: i64,
+    pub page: i32,
+    pub per_page: i32,
+}
+
+// =============================================================================
+// REORDER SUGGESTIONS
+// =============================================================================
+// How much of each SKU to order, from its threshold, what is available and
+// what the movement ledger says it consumes (see reorder.rs).
+
+/// Stock and recent consumption of one SKU, as summed by the database
+#[derive(Debug, Clone, FromRow)]
+pub struct ReorderCandidate {
+    pub sku: String,
+    pub name: String,
+    pub warehouse: String,
+    pub available: i32,
+    pub low_stock_threshold: i32,
+    /// Units confirmed or adjusted away over the lookback window
+    pub consumed: i64,
+    pub unit_cost: Option<f64>,
+}
+
+/// A SKU that should be reordered
+///
+/// # Example JSON
+/// ```json
+/// { "sku": "SKU-001", "name": "Laptop", "warehouse": "JKT-1", "available": 12,
+///   "low_stock_threshold": 10, "daily_consumption": 2.5, "days_of_cover": 4.8,
+///   "reorder_point": 28, "suggested_quantity": 91, "unit_cost": 1040.0,
+///   "estimated_cost": 94640.0 }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ReorderSuggestion {
+    pub sku: String,
+    pub name: String,
+    /// Home warehouse, where the order should be delivered
+    pub warehouse: String,
+    pub available: i32,
+    pub low_stock_threshold: i32,
+
+    /// Units consumed per day over the lookback window
+    pub daily_consumption: f64,
+
+    /// Days the available stock lasts at that rate; None without
+    /// consumption
+    pub days_of_cover: Option<f64>,
+
+    /// Threshold plus what is consumed during the lead time: at or below
+    /// it, the SKU is suggested
+    pub reorder_point: i64,
+
+    /// Units that bring the SKU back to the reorder point plus the cover
+    /// days' consumption
+    pub suggested_quantity: i64,
+
+    pub unit_cost: Option<f64>,
+
+    /// suggested_quantity × unit_cost; None without a unit cost
+    pub estimated_cost: Option<f64>,
+}
+
+/// Response of GET /api/v1/inventory/reorder-suggestions
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReorderSuggestionsResponse {
+    /// Fewest days of cover first
+    pub suggestions: Vec<ReorderSuggestion>,
+    pub lookback_days: i32,
+    pub lead_time_days: i32,
+    pub cover_days: i32,
+}
+
+/// One line of a purchase order
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PurchaseOrderLine {
+    pub sku: String,
+    pub quantity: i64,
+    /// Unit cost when the order was drafted
+    pub unit_cost: Option<f64>,
+}
+
+/// A purchase order (row in `purchase_orders`); the service only drafts
+/// them, buyers take it from there
+///
+/// # Example JSON
+/// ```json
+/// { "id": 7, "warehouse": "JKT-1", "status": "draft",
+///   "lines": [{ "sku": "SKU-001", "quantity": 91, "unit_cost": 1040.0 }],
+///   "created_at": "2024-01-15T10:30:00Z" }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PurchaseOrder {
+    pub id: i64,
+    /// Where the goods should be delivered
+    pub warehouse: String,
+    pub status: String,
+    pub lines: Vec<PurchaseOrderLine>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Response of POST /api/v1/inventory/reorder-suggestions
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PurchaseOrderDrafts {
+    /// One draft per warehouse with suggestions
+    pub purchase_orders: Vec<PurchaseOrder>,
+}
+
+/// Response of GET /api/v1/purchase-orders
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PurchaseOrderListResponse {
+    /// Newest first
+    pub items: Vec<PurchaseOrder>,
+    pub total: i64,
+    pub page: i32,
+    pub per_page: i32,
+}
+
+// =============================================================================
+// ANALYTICS
+// =============================================================================
+// Consumption series summed from the movement ledger (see analytics.rs).
+
+/// Units that left and arrived during one bucket
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct ConsumptionPoint {
+    /// Start of the bucket
+    pub start: DateTime<Utc>,
+    /// Units confirmed or adjusted away
+    pub consumed: i64,
+    /// Units adjusted in
+    pub received: i64,
+}
+
+/// Response of GET /api/v1/analytics/consumption
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "sku": "SKU-001",
+///   "window": "7d",
+///   "bucket": "day",
+///   "since": "2024-01-08T10:30:00Z",
+///   "consumed": 42,
+///   "received": 50,
+///   "points": [
+///     { "start": "2024-01-08T00:00:00Z", "consumed": 6, "received": 0 },
+///     { "start": "2024-01-09T00:00:00Z", "consumed": 4, "received": 50 }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ConsumptionSeries {
+    /// The SKU, or null for all SKUs together
+    pub sku: Option<String>,
+    pub window: String,
+    /// "hour" or "day"
+    pub bucket: String,
+    pub since: DateTime<Utc>,
+
+    /// Totals over the window
+    pub consumed: i64,
+    pub received: i64,
+
+    /// Every bucket of the window, oldest first
+    pub points: Vec<ConsumptionPoint>,
+}
+
+/// One bucket of one top mover, as returned by the database
+#[derive(Debug, Clone, FromRow)]
+pub struct TopMoverRow {
+    pub sku: String,
+    pub name: String,
+    /// Units consumed over the whole window
+    pub total_consumed: i64,
+    pub start: DateTime<Utc>,
+    pub consumed: i64,
+    pub received: i64,
+}
+
+/// A SKU among the most consumed, with its series
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TopMover {
+    pub sku: String,
+    pub name: String,
+    /// Units consumed over the window
+    pub consumed: i64,
+    pub points: Vec<ConsumptionPoint>,
+}
+
+/// Response of GET /api/v1/analytics/top-movers
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TopMoversResponse {
+    pub window: String,
+    /// "hour" or "day"
+    pub bucket: String,
+    pub since: DateTime<Utc>,
+
+    /// Most units consumed first
+    pub items: Vec<TopMover>,
+}
+
+// =============================================================================
+// ITEM REVISIONS
+// =============================================================================
+// Column-level history of item metadata, written by a trigger on `products`
+// (migrations/0007_item_revisions.sql), so every write path is covered.
+
+/// Item columns whose changes are recorded
+pub const REVISION_FIELDS: [&str; 4] = ["name", "warehouse", "low_stock_threshold", "unit_price"];
+
+/// One changed column (row in `item_revisions`)
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ItemRevision {
+    pub id: i64,
+    pub sku: String,
+
+    /// One of REVISION_FIELDS
+    pub field: String,
+
+    /// Value before and after the change (null for an unset price)
+    pub old_value: serde_json::Value,
+    pub new_value: serde_json::Value,
+
+    /// What made the change: catalog, threshold, bulk_update,
+    /// threshold_recalculation, revert, or sql for writes from outside the
+    /// service
+    pub source: String,
+
+    /// Reason given with the change (bulk updates, reverts)
+    pub reason: Option<String>,
+
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Response of GET /api/v1/inventory/:sku/revisions
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ItemRevisionListResponse {
+    /// Newest first
+    pub items: Vec<ItemRevision>,
+    pub total: i64,
+    pub page: i32,
+    pub per_page: i32,
+}
+
+// =============================================================================
+// WEBHOOK SUBSCRIPTIONS
+// =============================================================================
+// Third parties (WMS, ERP) subscribing to reservation and adjustment events.
+
+/// A webhook subscription (row in `webhook_subscriptions`)
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct WebhookSubscription {
+    pub id: i64,
+
+    /// Receiver of the events (POST, JSON)
+    pub url: String,
+
+    /// Event types delivered; empty means all of them
+    pub event_types: Vec<String>,
+
+    /// Glob on the SKU (`*` and `?`); unset means every SKU
+    pub sku_pattern: Option<String>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request body for creating a webhook subscription
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "url": "https://wms.example.com/hooks/inventory",
+///   "event_types": ["reservation.created", "reservation.released"],
+///   "sku_pattern": "LAPTOP-*"
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct WebhookSubscriptionRequest {
+    pub url: String,
+
+    #[serde(default)]
+    pub event_types: Vec<String>,
+
+    pub sku_pattern: Option<String>,
+}
+
+// =============================================================================
+// STOCK EVENTS
+// =============================================================================
+// Emitted when an item sells out and when it comes back in stock.
+
+/// A stock.out / stock.back event (row in `stock_events`)
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "id": 17,
+///   "type": "stock.back",
+///   "sku": "SKU-LAPTOP-001",
+///   "warehouse": "JKT-1",
+///   "available": 20,
+///   "occurred_at": "2024-01-15T10:30:00Z"
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StockEvent {
+    /// Monotonic event ID (receivers can use it to drop duplicates)
+    pub id: i64,
+
+    /// "stock.out" or "stock.back"
+    #[serde(rename = "type")]
+    pub event_type: String,
+
+    pub sku: String,
+    pub warehouse: String,
+
+    /// Available stock (quantity - reserved) right after the change
+    pub available: i32,
+
+    /// When the transition happened
+    pub occurred_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// SNAPSHOTS
+// =============================================================================
+// Point-in-time copies of every item's stock, compared with the diff endpoint.
+
+/// A stored inventory snapshot (row in `inventory_snapshots`)
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct InventorySnapshot {
+    pub id: i64,
+
+    /// Free text, e.g. "before flash-sale scenario"
+    pub label: Option<String>,
+
+    pub taken_at: DateTime<Utc>,
+
+    /// Number of SKUs captured
+    pub items: i64,
+}
+
+/// Request body for taking a snapshot
+///
+/// # Example JSON
+/// ```json
+/// { "label": "stock take 2024-01" }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct SnapshotRequest {
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl Validate for SnapshotRequest {
+    fn check(&self, v: &mut Validator) {
+        v.optional_text("label", self.label.as_deref(), crate::snapshots::MAX_LABEL_LEN);
+    }
+}
+
+/// Change of one SKU between two snapshots
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct SkuDelta {
+    pub sku: String,
+    pub quantity_before: i32,
+    pub quantity_after: i32,
+    pub quantity_delta: i32,
+    pub reserved_before: i32,
+    pub reserved_after: i32,
+    pub reserved_delta: i32,
+
+    /// Change of quantity × unit price (unpriced items count as 0)
+    pub value_delta: f64,
+}
+
+/// Sum of the per-SKU deltas
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct DiffTotals {
+    pub quantity_delta: i64,
+    pub reserved_delta: i64,
+    pub value_delta: f64,
+}
+
+/// Response of the snapshot diff endpoint
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "from": { "id": 3, "label": "before", "taken_at": "2024-01-15T10:00:00Z", "items": 5 },
+///   "to": null,
+///   "items": [
+///     { "sku": "SKU-LAPTOP-001", "quantity_before": 50, "quantity_after": 45,
+///       "quantity_delta": -5, "reserved_before": 5, "reserved_after": 0,
+///       "reserved_delta": -5, "value_delta": -6499.95 }
+///   ],
+///   "totals": { "quantity_delta": -5, "reserved_delta": -5, "value_delta": -6499.95 }
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SnapshotDiffResponse {
+    pub from: InventorySnapshot,
+
+    /// `null` when compared against current stock
+    pub to: Option<InventorySnapshot>,
+
+    /// SKUs that changed, by SKU
+    pub items: Vec<SkuDelta>,
+
+    pub totals: DiffTotals,
+}
+
+// =============================================================================
+// REPORTS
+// =============================================================================
+
+/// Stockout report for a period
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "period": "7d",
+///   "since": "2024-01-08T10:30:00Z",
+///   "generated_at": "2024-01-15T10:30:00Z",
+///   "items": [
+///     {
+///       "sku": "SKU-MONITOR-001",
+///       "warehouse": "SBY-1",
+///       "incidents": 2,
+///       "stockout_seconds": 5400.0,
+///       "currently_out": false,
+///       "reservations_requested": 40,
+///       "reservations_filled": 31,
+///       "units_requested": 52,
+///       "units_filled": 39,
+///       "fill_rate": 0.75
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StockoutReport {
+    pub period: String,
+    pub since: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+
+    /// One row per SKU, longest stockout time first
+    pub items: Vec<StockoutReportRow>,
+}
+
+/// Stockouts and fill rate of one SKU/warehouse
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct StockoutReportRow {
+    pub sku: String,
+    pub warehouse: String,
+
+    /// Stockouts overlapping the period
+    pub incidents: i64,
+
+    /// Time without available stock within the period
+    pub stockout_seconds: f64,
+
+    /// Whether the SKU is out of stock right now
+    pub currently_out: bool,
+
+    /// Reservation attempts and the ones that succeeded
+    pub reservations_requested: i64,
+    pub reservations_filled: i64,
+
+    /// Units asked for and units reserved
+    pub units_requested: i64,
+    pub units_filled: i64,
+
+    /// units_filled / units_requested (null without reservations)
+    pub fill_rate: Option<f64>,
+}
+
+/// Result of POST /api/v1/admin/reconcile-reservations
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "repair": true,
+///   "checked_at": "2024-01-15T10:30:00Z",
+///   "drifted": [
+///     {
+///       "sku": "SKU-LAPTOP-001",
+///       "warehouse": "JKT-1",
+///       "quantity": 50,
+///       "reserved": 12,
+///       "expected": 7,
+///       "drift": 5,
+///       "repaired": true
+///     }
+///   ],
+///   "repaired": 1
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReconcileResponse {
+    /// Whether drift was written back (false = report only)
+    pub repair: bool,
+    pub checked_at: DateTime<Utc>,
+
+    /// Stock rows whose `reserved` column disagrees with their reservations
+    pub drifted: Vec<ReservationDrift>,
+
+    /// How many of them were corrected
+    pub repaired: usize,
+}
+
+/// One stock row (SKU at a warehouse) whose `reserved` column doesn't
+/// match the active reservations taken from it
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ReservationDrift {
+    pub sku: String,
+    pub warehouse: String,
+    pub quantity: i32,
+
+    /// Current `reserved` column
+    pub reserved: i32,
+
+    /// Units held by active reservations (capped at `quantity`)
+    pub expected: i32,
+
+    /// reserved - expected; positive means units are stuck as reserved
+    pub drift: i32,
+
+    /// Corrected by this run (false for a dry run, or when the row changed
+    /// while reconciling)
+    #[sqlx(skip)]
+    pub repaired: bool,
+}
+
+/// Business KPIs at a glance
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "total_skus": 10,
+///   "total_units": 1230,
+///   "reserved_units": 45,
+///   "available_units": 1185,
+///   "active_reservations": 3,
+///   "low_stock_items": 2,
+///   "movements_last_24h": { "adjust": 1, "confirm": 5, "release": 2, "reserve": 12 },
+///   "generated_at": "2024-01-15T10:30:00Z"
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StatsResponse {
+    #[serde(flatten)]
+    pub totals: InventoryTotals,
+
+    /// Orders still holding reserved units
+    pub active_reservations: i64,
+
+    /// Items below their low-stock threshold (latest evaluation)
+    pub low_stock_items: usize,
+
+    /// Successful stock movements in the last 24 hours, per action
+    pub movements_last_24h: std::collections::BTreeMap<String, i64>,
+
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Stock totals over all inventory rows
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct InventoryTotals {
+    pub total_skus: i64,
+    pub total_units: i64,
+    pub reserved_units: i64,
+    pub available_units: i64,
+}
+
+// =============================================================================
+// API USAGE
+// =============================================================================
+// Per-API-key request and reserved-unit counts (see usage.rs).
+
+/// Usage of one API key on one UTC day
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ApiUsage {
+    pub day: NaiveDate,
+
+    /// Fingerprint of the API key (also sent back as X-Usage-Key-Id)
+    pub key_id: String,
+
+    /// Requests to /api/ endpoints
+    pub requests: i64,
+
+    /// Units successfully reserved
+    pub reserved_units: i64,
+
+    /// Last rollup from Redis
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Usage of one API key over the whole report period
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ApiUsageTotal {
+    pub key_id: String,
+    pub requests: i64,
+    pub reserved_units: i64,
+}
+
+/// Response of GET /api/v1/admin/usage
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "from": "2024-05-01",
+///   "to": "2024-05-07",
+///   "keys": [{ "key_id": "9f2c41d07ab3e615", "requests": 18234, "reserved_units": 912 }],
+///   "days": [{ "day": "2024-05-01", "key_id": "9f2c41d07ab3e615", "requests": 2571, ... }]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UsageReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+
+    /// Totals per key, most requests first
+    pub keys: Vec<ApiUsageTotal>,
+
+    /// Daily rows, oldest first
+    pub days: Vec<ApiUsage>,
+}
+
+// =============================================================================
+// TRAFFIC REPLAY
+// =============================================================================
+
+/// Request body for starting a traffic replay
+///
+/// # Example JSON
+/// ```json
+/// { "file": "morning-spike.jsonl", "speed": 2.0 }
+/// ```
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ReplayRequest {
+    /// Log file name inside REPLAY_DIR
+    pub file: String,
+
+    /// Timing multiplier: 1.0 = original timing, 2.0 = twice as fast
+    #[serde(default = "default_replay_speed")]
+    pub speed: f64,
+}
+
+fn default_replay_speed() -> f64 {
+    1.0
+}
+
+/// The file name is checked against REPLAY_DIR by the handler
+impl Validate for ReplayRequest {
+    fn check(&self, v: &mut Validator) {
+        if self.file.trim().is_empty() {
+            v.add("file", "must not be empty");
+        }
+        if !(self.speed > 0.0 && self.speed <= crate::replay::MAX_SPEED) {
+            v.add("speed", format!("must be greater than 0 and at most {}", crate::replay::MAX_SPEED));
+        }
+    }
+}
+
+/// Response when a replay has been started
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReplayStartedResponse {
+    pub file: String,
+    pub records: usize,
+    pub speed: f64,
+
+    /// Expected wall-clock duration of the replay
+    pub duration_ms: u64,
+}
+
+// =============================================================================
+// MIGRATIONS
+// =============================================================================
+
+/// Response for the migration status endpoint
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "expected_version": 3,
+///   "applied_version": 2,
+///   "pending": [{ "version": 3, "description": "add item barcodes" }],
+///   "auto_migrate": false
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MigrationStatus {
+    /// Newest migration this build carries
+    pub expected_version: i64,
+
+    /// Newest migration applied to the database (null: never migrated)
+    pub applied_version: Option<i64>,
+
+    /// Migrations of this build the database doesn't have yet
+    pub pending: Vec<PendingMigration>,
+
+    /// Whether this pod migrates at startup (AUTO_MIGRATE)
+    pub auto_migrate: bool,
+}
+
+/// A migration not yet applied to the database
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PendingMigration {
+    pub version: i64,
+    pub description: String,
+}
+
+// =============================================================================
+// HEALTH CHECK RESPONSES
+// =============================================================================
+// Standard health check response structures
+
+/// Simple health check response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthResponse {
+    pub status: String,
+    pub service: String,
+    pub version: String,
+}
+
+/// Startup probe response (GET /startup)
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "status": "starting",
+///   "elapsed_secs": 12.4,
+///   "steps": [
+///     { "name": "postgres", "state": "running", "attempts": 4,
+///       "last_error": "Failed to connect to PostgreSQL: connection refused" },
+///     { "name": "migrations", "state": "pending", "attempts": 0, "last_error": null }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StartupResponse {
+    /// "starting" or "started"
+    pub status: String,
+    /// Seconds since the process started
+    pub elapsed_secs: f64,
+    /// Boot steps, in order
+    pub steps: Vec<StartupStep>,
+}
+
+/// One boot step (see startup_probe.rs)
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StartupStep {
+    pub name: String,
+    /// "pending", "running" or "done"
+    pub state: String,
+    /// Attempts so far (connections are retried)
+    pub attempts: u32,
+    /// Why the latest attempt failed, while the step is still running
+    pub last_error: Option<String>,
+}
+
+/// Detailed readiness check response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// "ready", "degraded" (no Redis, served from Postgres) or "not_ready"
+    pub status: String,
+    pub checks: ReadinessChecks,
+}
+
+/// Individual dependency health checks
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessChecks {
+    pub database: bool,
+    pub redis: bool,
+    /// Startup cache warming finished (always true when CACHE_WARM is off)
+    pub cache_warm: bool,
+}
+
+// =============================================================================
+// DEBUG RESPONSES
+// =============================================================================
+
+/// Internal counters for a quick human-readable health overview
+#[derive(Debug, Serialize)]
+pub struct DebugVarsResponse {
+    /// Seconds since the service started
+    pub uptime_seconds: i64,
+
+    /// Connection pool usage of the primary and each read replica
+    pub database_pools: Vec<NamedPoolStats>,
+
+    /// Items in the Redis item cache, not-found entries included (None if
+    /// Redis is down or too slow to count them)
+    pub cache_entries: Option<u64>,
+
+    /// Clients connected to the live stock stream
+    /// (GET /api/v1/inventory/stream)
+    pub live_subscribers: usize,
+
+    /// Audit events queued for each follower and not shipped yet (None if
+    /// Postgres is unavailable)
+    pub audit_outbox: Option<Vec<OutboxBacklog>>,
+
+    /// Supervised background tasks and their last run times
+    pub background_tasks: Vec<crate::supervisor::TaskStatus>,
+
+    /// Allocator compiled into the binary
+    pub allocator: String,
+}
+
+/// Connection pool usage snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStats {
+    /// Open connections (idle + in use)
+    pub size: u32,
+    /// Connections currently idle in the pool
+    pub idle: u32,
+    /// Configured pool capacity
+    pub max_connections: u32,
+}
+
+/// A pool's usage, by name ("primary" or "replica-<n>")
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedPoolStats {
+    pub pool: String,
+    #[serde(flatten)]
+    pub stats: PoolStats,
+}
+
+/// Queued audit events of one outbox follower
+#[derive(Debug, Clone, Serialize)]
+pub struct OutboxBacklog {
+    /// Follower name, e.g. "audit-http" or "webhooks"
+    pub follower: String,
+    pub pending: i64,
+}
+
+/// Allocator statistics response
+#[derive(Debug, Serialize)]
+pub struct AllocatorResponse {
+    /// Allocator compiled into the binary ("jemalloc" or "system")
+    pub allocator: String,
+
+    /// Heap statistics, absent when the allocator doesn't provide them
+    pub stats: Option<crate::allocator::AllocatorStats>,
+}
+
+// =============================================================================
+// ERROR RESPONSES
+// =============================================================================
+// Standardized error response format for API
+
+/// API error response body
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    /// Error type/code
+    pub error: String,
+    
+    /// Human-readable error message
+    pub message: String,
+    
+    /// Optional additional details
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+
+    /// Invalid fields of the request body (VALIDATION_FAILED only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+}
+
+/// API error response body in the Problem Details format (RFC 7807),
+/// sent as `application/problem+json` to clients that ask for it or when
+/// PROBLEM_JSON is on (see problem.rs)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    /// URN identifying the kind of problem
+    #[serde(rename = "type")]
+    pub problem_type: String,
+
+    /// Short summary of the kind of problem
+    pub title: String,
+
+    /// HTTP status code
+    pub status: u16,
+
+    /// Human-readable explanation of this occurrence
+    pub detail: String,
+
+    /// This occurrence: urn:request-id:<X-Request-Id>
+    pub instance: String,
+
+    /// Error code, as in ErrorResponse `error`
+    pub code: String,
+
+    /// Optional additional details
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+
+    /// Invalid fields of the request body (VALIDATION_FAILED only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+}
+
+/// One invalid field of a request body
+///
+/// # Example JSON
+/// ```json
+/// { "field": "items[0].quantity", "message": "must be positive" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Path of the field in the request body
+    pub field: String,
+    pub message: String,
+}
+
+impl ErrorResponse {
+    /// Create a new error response
+    pub fn new(error: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            message: message.into(),
+            details: None,
+            fields: Vec::new(),
+        }
+    }
+    
+    /// Create an error response with details
+    pub fn with_details(
+        error: impl Into<String>,
+        message: impl Into<String>,
+        details: impl Into<String>,
+    ) -> Self {
+        Self {
+            error: error.into(),
+            message: message.into(),
+            details: Some(details.into()),
+            fields: Vec::new(),
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(quantity: i32, reserved: i32) -> InventoryItem {
+        InventoryItem {
+            id: Uuid::nil(),
+            sku: "SKU-PROMO-001".to_string(),
+            name: "Promo".to_string(),
+            quantity,
+            reserved,
+            warehouse: "JKT-1".to_string(),
+            low_stock_threshold: 10,
+            max_per_order: None,
+            max_reserved_pct: None,
+            threshold_manual: false,
+            version: 1,
+            attributes: empty_attributes(),
+            image_url: None,
+            spec_url: None,
+            unit_price: None,
+            unit_cost: None,
+            stock_value: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_reservation_policy_limits() {
+        let mut promo = item(100, 40);
+        assert!(promo.check_reservation_policy(60).is_ok());
+
+        promo.max_per_order = Some(5);
+        assert!(promo.check_reservation_policy(5).is_ok());
+        assert!(promo.check_reservation_policy(6).is_err());
+
+        promo.max_per_order = None;
+        promo.max_reserved_pct = Some(50);
+        assert!(promo.check_reservation_policy(10).is_ok());
+        assert!(promo.check_reservation_policy(11).is_err());
+    }
+
+    #[test]
+    fn test_batch_reservation_validation() {
+        let batch = |items: &[(&str, i32)]| ReserveBatchRequest {
+            order_id: "ORD-1".to_string(),
+            channel: SalesChannel::Web,
+            hold: HoldType::Hard,
+            priority: 0,
+            quota: None,
+            items: items
+                .iter()
+                .map(|(sku, quantity)| ReserveLine {
+                    sku: sku.to_string(),
+                    quantity: *quantity,
+                })
+                .collect(),
+        };
+
+        assert!(batch(&[("A", 1), ("B", 2)]).validate().is_ok());
+        assert!(batch(&[]).validate().is_err());
+        assert!(batch(&[("A", 0)]).validate().is_err());
+        assert!(batch(&[("A", 1), ("B", 1), ("A", 2)]).validate().is_err());
+
+        let mut urgent = batch(&[("A", 1)]);
+        urgent.priority = MAX_HOLD_PRIORITY + 1;
+        assert!(urgent.validate().is_err());
+    }
+
+    #[test]
+    fn test_prepare_commitment_validation() {
+        let prepare: PrepareCommitmentRequest = serde_json::from_value(serde_json::json!({
+            "transaction_id": "chk-7f3a9c",
+            "order_id": "ORD-1",
+            "items": [{ "sku": "A", "quantity": 2 }]
+        }))
+        .unwrap();
+        assert!(prepare.validate().is_ok());
+        assert_eq!(prepare.timeout_secs(), DEFAULT_COMMITMENT_TIMEOUT_SECS);
+        assert_eq!(prepare.to_batch().hold, HoldType::Hard);
+
+        let mut bad = prepare.clone();
+        bad.transaction_id = "chk 1".to_string();
+        assert!(bad.validate().is_err());
+        bad.transaction_id = "x".repeat(MAX_TRANSACTION_ID_LEN + 1);
+        assert!(bad.validate().is_err());
+
+        let mut bad = prepare.clone();
+        bad.timeout_secs = Some(MAX_COMMITMENT_TIMEOUT_SECS + 1);
+        assert!(bad.validate().is_err());
+        bad.timeout_secs = Some(0);
+        assert!(bad.validate().is_err());
+
+        // Lines are checked like a batch reservation
+        let mut bad = prepare;
+        bad.items.clear();
+        assert!(bad.validate().is_err());
+
+        for state in ["prepared", "committed", "aborted", "expired"] {
+            assert_eq!(CommitmentState::parse(state).unwrap().as_str(), state);
+        }
+    }
+
+    #[test]
+    fn test_reservation_hold_defaults() {
+        let plain: ReserveStockRequest = serde_json::from_value(serde_json::json!({
+            "sku": "A", "quantity": 1, "order_id": "ORD-1"
+        }))
+        .unwrap();
+        assert_eq!((plain.hold, plain.priority), (HoldType::Hard, 0));
+        assert!(!plain.needs_hold_handling());
+
+        let soft: ReserveStockRequest = serde_json::from_value(serde_json::json!({
+            "sku": "A", "quantity": 1, "order_id": "ORD-1", "hold": "soft"
+        }))
+        .unwrap();
+        assert!(soft.needs_hold_handling());
+        assert!(ReserveStockRequest { priority: -1, ..soft }.validate().is_err());
+    }
+
+    #[test]
+    fn test_catalog_details_validation() {
+        let details = |body: serde_json::Value| -> CatalogDetailsRequest {
+            serde_json::from_value(body).unwrap()
+        };
+
+        assert!(details(serde_json::json!({})).validate().is_ok());
+        assert!(details(serde_json::json!({
+            "attributes": { "color": "red", "sizes": [38, 39] },
+            "image_url": "https://cdn.example.com/a.jpg"
+        }))
+        .validate()
+        .is_ok());
+
+        assert!(details(serde_json::json!({ "attributes": [1, 2] })).validate().is_err());
+        assert!(details(serde_json::json!({ "attributes": { "": 1 } })).validate().is_err());
+        assert!(details(serde_json::json!({ "image_url": "javascript:alert(1)" }))
+            .validate()
+            .is_err());
+        assert!(details(serde_json::json!({ "unit_price": -1.0 })).validate().is_err());
+    }
+
+    #[test]
+    fn test_transfer_validation() {
+        let transfer = |from: &str, to: &str, quantity: i32| TransferStockRequest {
+            sku: "SKU-001".to_string(),
+            from_warehouse: from.to_string(),
+            to_warehouse: to.to_string(),
+            quantity,
+            reason: None,
+        };
+
+        assert!(transfer("JKT-1", "SBY-1", 5).validate().is_ok());
+        assert!(transfer("JKT-1", "JKT-1", 5).validate().is_err());
+        assert!(transfer("JKT-1", "SBY-1", 0).validate().is_err());
+        assert!(transfer("JKT-1", " ", 5).validate().is_err());
+        assert!(transfer(&"W".repeat(MAX_SKU_LEN + 1), "SBY-1", 5).validate().is_err());
+    }
+
+    #[test]
+    fn test_bulk_patch() {
+        let laptop = BulkItemValues {
+            sku: "LAPTOP-001".to_string(),
+            low_stock_threshold: 10,
+            unit_price: Some(999.99),
+            warehouse: "JKT-1".to_string(),
+        };
+
+        let raise = BulkPatch {
+            low_stock_threshold_pct: Some(20.0),
+            unit_price_pct: Some(-10.0),
+            ..Default::default()
+        };
+        let after = raise.apply(&laptop);
+        assert_eq!(after.low_stock_threshold, 12);
+        assert_eq!(after.unit_price, Some(899.99));
+        assert_eq!(after.warehouse, "JKT-1");
+
+        let relocate = BulkPatch {
+            warehouse: Some("SBY-1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(relocate.apply(&laptop).warehouse, "SBY-1");
+
+        let unpriced = BulkItemValues { unit_price: None, ..laptop };
+        assert_eq!(raise.apply(&unpriced).unit_price, None);
+    }
+
+    #[test]
+    fn test_bulk_update_validation() {
+        let request = |body: serde_json::Value| -> BulkUpdateRequest {
+            serde_json::from_value(body).unwrap()
+        };
+
+        assert!(request(serde_json::json!({
+            "filter": { "category": "laptops" },
+            "patch": { "low_stock_threshold_pct": 20 },
+            "reason": "Holiday season"
+        }))
+        .validate()
+        .is_ok());
+
+        // Everything would be touched
+        assert!(request(serde_json::json!({
+            "filter": {}, "patch": { "low_stock_threshold": 5 }, "reason": "x"
+        }))
+        .validate()
+        .is_err());
+        assert!(request(serde_json::json!({
+            "filter": { "warehouse": "JKT-1" },
+            "patch": { "unit_price": 5, "unit_price_pct": 10 },
+            "reason": "x"
+        }))
+        .validate()
+        .is_err());
+        assert!(request(serde_json::json!({
+            "filter": { "warehouse": "JKT-1" }, "patch": { "unit_price_pct": -100 }, "reason": "x"
+        }))
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_update_item_validation() {
+        let request = |body: serde_json::Value| -> UpdateItemRequest {
+            serde_json::from_value(body).unwrap()
+        };
+
+        assert!(request(serde_json::json!({ "low_stock_threshold": 0 })).validate().is_ok());
+        assert!(request(serde_json::json!({ "name": "Dock", "warehouse": "SBY-1" }))
+            .validate()
+            .is_ok());
+
+        // Nothing to change
+        assert!(request(serde_json::json!({ "reason": "x" })).validate().is_err());
+
+        let errors = request(serde_json::json!({ "name": " ", "low_stock_threshold": -1 }))
+            .validate()
+            .unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["name", "low_stock_threshold"]);
+    }
+
+    #[test]
+    fn test_warehouse_validation_and_summary() {
+        let request = |body: serde_json::Value| -> CreateWarehouseRequest {
+            serde_json::from_value(body).unwrap()
+        };
+
+        assert!(request(serde_json::json!({ "code": "MDN-1", "name": "Medan 1" }))
+            .validate()
+            .is_ok());
+        let errors = request(serde_json::json!({ "code": "", "name": "Medan 1", "capacity": 0 }))
+            .validate()
+            .unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["code", "capacity"]);
+
+        let warehouse = Warehouse {
+            code: "JKT-1".to_string(),
+            name: "Jakarta 1".to_string(),
+            region: None,
+            capacity: Some(400),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let totals = WarehouseTotals {
+            quantity: 150,
+            reserved: 20,
+            ..WarehouseTotals::default()
+        };
+        let summary = WarehouseSummary::new(warehouse, totals);
+        assert_eq!(summary.available, 130);
+        assert_eq!(summary.utilization_pct, Some(37.5));
+    }
+
+    #[test]
+    fn test_revision_fields_match_trigger() {
+        // The trigger has its own copy of the list
+        let migration = include_str!("../migrations/0007_item_revisions.sql");
+        for field in REVISION_FIELDS {
+            assert!(migration.contains(&format!("'{}'", field)), "{} not tracked", field);
         }
     }
 }
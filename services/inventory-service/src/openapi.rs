@@ -0,0 +1,297 @@
+// =============================================================================
+// OPENAPI MODULE
+// =============================================================================
+// Machine-readable API description, generated from the annotations on the
+// handlers (`#[utoipa::path]`) and models (`#[derive(ToSchema)]`).
+//
+// ENDPOINTS:
+// - GET /api-docs/openapi.json   OpenAPI 3 document
+// - GET /swagger                 Swagger UI for browsing and trying the API
+//
+// LEARNING NOTES:
+// - A handler without `#[utoipa::path]`, or missing from `paths(...)` below,
+//   is simply absent from the spec; nothing fails at compile time
+// - The spec is generated from the code itself, so it can't drift from the
+//   handlers the way a hand-written YAML file would
+// - The Swagger UI page is served from here, its scripts and styles come
+//   from the unpkg CDN (pinned below), so browsing it needs internet access
+// - /metrics and /debug are left out on purpose: they're for Prometheus
+//   and people, not API clients
+// =============================================================================
+
+use axum::{response::Html, Json};
+use utoipa::OpenApi;
+
+use crate::handlers;
+use crate::models::*;
+
+/// swagger-ui-dist release the UI page loads
+const SWAGGER_UI_VERSION: &str = "5.17.14";
+
+/// The generated OpenAPI document
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Inventory Service API",
+        description = "Stock levels, reservations and catalog data for the Order Management System"
+    ),
+    paths(
+        handlers::health_check,
+        handlers::readiness_check,
+        handlers::startup_check,
+        handlers::list_inventory,
+        handlers::create_item,
+        handlers::update_item,
+        handlers::lookup_item,
+        handlers::get_item,
+        handlers::adjust_stock,
+        handlers::transfer_stock,
+        handlers::bulk_update,
+        handlers::import_items,
+        handlers::export_inventory,
+        handlers::stream_inventory,
+        handlers::list_stock_movements,
+        handlers::reorder_suggestions,
+        handlers::draft_purchase_orders,
+        handlers::list_purchase_orders,
+        handlers::list_item_revisions,
+        handlers::revert_item_revision,
+        handlers::low_stock_alerts,
+        handlers::reserve_stock,
+        handlers::reserve_batch,
+        handlers::prepare_commitment,
+        handlers::commit_commitment,
+        handlers::abort_commitment,
+        handlers::release_stock,
+        handlers::cancel_order_reservations,
+        handlers::list_reservations,
+        handlers::set_reservation_policy,
+        handlers::set_catalog_details,
+        handlers::list_warehouse_stock,
+        handlers::list_warehouse_thresholds,
+        handlers::set_low_stock_threshold,
+        handlers::set_warehouse_threshold,
+        handlers::delete_warehouse_threshold,
+        handlers::list_warehouses,
+        handlers::create_warehouse,
+        handlers::get_warehouse,
+        handlers::update_warehouse,
+        handlers::delete_warehouse,
+        handlers::warehouse_summary,
+        handlers::list_item_identifiers,
+        handlers::add_item_identifier,
+        handlers::delete_item_identifier,
+        handlers::list_attribute_schemas,
+        handlers::put_attribute_schema,
+        handlers::delete_attribute_schema,
+        handlers::stockout_report,
+        handlers::stats,
+        handlers::consumption_series,
+        handlers::top_movers,
+        handlers::create_snapshot,
+        handlers::list_snapshots,
+        handlers::snapshot_diff,
+        handlers::order_status_callback,
+        handlers::background_tasks,
+        handlers::export_audit_events,
+        handlers::usage_report,
+        handlers::start_replay,
+        handlers::reconcile_reservations,
+        handlers::migration_status,
+        handlers::run_selftest,
+        handlers::create_webhook_subscription,
+        handlers::list_webhook_subscriptions,
+        handlers::delete_webhook_subscription,
+        handlers::start_db_load,
+        handlers::db_load_status,
+        handlers::stop_db_load,
+        handlers::start_chaos,
+        handlers::list_chaos,
+        handlers::stop_all_chaos,
+        handlers::stop_chaos,
+    ),
+    components(schemas(
+        InventoryItem,
+        InventoryListResponse,
+        SortBy,
+        SortOrder,
+        CreateItemRequest,
+        UpdateItemRequest,
+        LookupResponse,
+        AdjustStockRequest,
+        TransferStockRequest,
+        TransferStockResponse,
+        WarehouseStock,
+        Warehouse,
+        CreateWarehouseRequest,
+        UpdateWarehouseRequest,
+        WarehouseSummary,
+        StockMovement,
+        StockMovementListResponse,
+        ReorderSuggestion,
+        ReorderSuggestionsResponse,
+        PurchaseOrderLine,
+        PurchaseOrder,
+        PurchaseOrderDrafts,
+        PurchaseOrderListResponse,
+        ItemRevision,
+        ItemRevisionListResponse,
+        LowStockAlert,
+        ReserveStockRequest,
+        HoldType,
+        BumpedHold,
+        ReserveBatchRequest,
+        ReserveLine,
+        ReserveBatchResponse,
+        ReservationResponse,
+        PrepareCommitmentRequest,
+        CommitmentRequest,
+        CommitmentState,
+        StockCommitment,
+        SalesChannel,
+        ReleaseStockRequest,
+        CancelOrderRequest,
+        CancelOrderResponse,
+        ReleasedStock,
+        ReservationRecord,
+        ReservationListResponse,
+        ReservationPolicy,
+        CatalogDetailsRequest,
+        BulkUpdateRequest,
+        BulkFilter,
+        BulkPatch,
+        BulkItemValues,
+        BulkChange,
+        BulkUpdateResponse,
+        ImportRowStatus,
+        ImportRowResult,
+        ImportResponse,
+        LowStockThresholdRequest,
+        WarehouseThreshold,
+        WarehouseThresholdRequest,
+        ItemIdentifier,
+        ItemIdentifierRequest,
+        crate::identifiers::IdentifierKind,
+        AttributeSchema,
+        AttributeSchemaRequest,
+        crate::attributes::AttributeType,
+        StockoutReport,
+        StockoutReportRow,
+        ConsumptionPoint,
+        ConsumptionSeries,
+        TopMover,
+        TopMoversResponse,
+        StatsResponse,
+        InventoryTotals,
+        InventorySnapshot,
+        SnapshotRequest,
+        SnapshotDiffResponse,
+        SkuDelta,
+        DiffTotals,
+        OrderCallbackStatus,
+        OrderCallbackItem,
+        OrderCallbackRequest,
+        OrderCallbackResponse,
+        AuditEvent,
+        ApiUsage,
+        ApiUsageTotal,
+        UsageReport,
+        ReplayRequest,
+        ReplayStartedResponse,
+        ReconcileResponse,
+        ReservationDrift,
+        MigrationStatus,
+        PendingMigration,
+        crate::selftest::SelftestReport,
+        crate::selftest::SelftestStep,
+        WebhookSubscription,
+        WebhookSubscriptionRequest,
+        crate::supervisor::TaskStatus,
+        crate::supervisor::TaskState,
+        crate::loadgen::LoadRequest,
+        crate::loadgen::LoadStatus,
+        crate::loadgen::Scenario,
+        crate::chaos::FaultRequest,
+        crate::chaos::Experiment,
+        HealthResponse,
+        ReadinessResponse,
+        ReadinessChecks,
+        StartupResponse,
+        StartupStep,
+        ErrorResponse,
+        ProblemDetails,
+        FieldError,
+    )),
+    tags(
+        (name = "health", description = "Liveness and readiness probes"),
+        (name = "inventory", description = "Items, stock levels and stock history"),
+        (name = "reservations", description = "Reserving and releasing stock for orders"),
+        (name = "catalog", description = "Reservation policy, catalog details, thresholds and identifiers"),
+        (name = "warehouses", description = "Registered warehouses and the stock they hold"),
+        (name = "reports", description = "Stockout report and totals"),
+        (name = "analytics", description = "Consumption series from the movement ledger"),
+        (name = "snapshots", description = "Point-in-time copies of stock and their diffs"),
+        (name = "integrations", description = "Callbacks from other services"),
+        (name = "admin", description = "Operations: tasks, audit export, usage, replay, selftest, webhooks, load, chaos"),
+    )
+)]
+pub struct ApiDoc;
+
+// =============================================================================
+// HANDLERS
+// =============================================================================
+/// Serve the OpenAPI document
+///
+/// GET /api-docs/openapi.json
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Serve Swagger UI, pointed at our OpenAPI document
+///
+/// GET /swagger
+pub async fn swagger_ui() -> Html<String> {
+    Html(format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>Inventory Service API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@{version}/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@{version}/swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({{ url: "/api-docs/openapi.json", dom_id: "#swagger-ui" }});
+  </script>
+</body>
+</html>"##,
+        version = SWAGGER_UI_VERSION
+    ))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_covers_routes_and_schemas() {
+        let spec = ApiDoc::openapi();
+
+        assert!(spec.paths.paths.contains_key("/api/v1/inventory/{sku}"));
+        assert!(spec.paths.paths.contains_key("/api/v1/admin/selftest"));
+
+        // Every referenced schema must be registered, or the UI shows
+        // broken $refs
+        let json = serde_json::to_string(&spec).unwrap();
+        let schemas = spec.components.expect("components").schemas;
+        for reference in json.split("\"$ref\":\"#/components/schemas/").skip(1) {
+            let name = &reference[..reference.find('"').unwrap()];
+            assert!(schemas.contains_key(name), "unregistered schema {}", name);
+        }
+    }
+}
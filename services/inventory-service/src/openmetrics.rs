@@ -0,0 +1,196 @@
+// =============================================================================
+// OPENMETRICS MODULE
+// =============================================================================
+// Converts the Prometheus text exposition into OpenMetrics 1.0 when the
+// scraper asks for it.
+//
+// WHY:
+// Prometheus negotiates the format with the Accept header. Features such as
+// exemplars and `_created` series only exist in OpenMetrics, so serving it
+// keeps the door open for them end-to-end (Prometheus → Grafana).
+//
+// DIFFERENCES HANDLED HERE:
+// - Content-Type: application/openmetrics-text; version=1.0.0
+// - Counter families are named without `_total` in HELP/TYPE lines, while
+//   their samples keep the `_total` suffix
+// - The exposition must end with `# EOF`
+// - Optional `<name>_created` samples for counters (when each series was
+//   first seen by this process), enabled with OPENMETRICS_CREATED=true
+//
+// LEARNING NOTES:
+// - Prometheus sends: Accept: application/openmetrics-text;version=1.0.0,...
+// - Anything else (curl, browsers) still gets the classic text format
+// =============================================================================
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Content type for OpenMetrics 1.0 responses
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Content type for the classic Prometheus text format
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Whether an Accept header asks for OpenMetrics
+pub fn wants_openmetrics(accept: &str) -> bool {
+    accept
+        .split(',')
+        .any(|media| media.trim().starts_with("application/openmetrics-text"))
+}
+
+// -----------------------------------------------------------------------------
+// CREATED TIMESTAMPS
+// -----------------------------------------------------------------------------
+/// Remembers when each counter series was first exposed.
+///
+/// The metrics library doesn't record creation times, so the first scrape
+/// that sees a series defines it. Good enough for rate() reset detection.
+#[derive(Default)]
+pub struct CreatedTracker {
+    first_seen: Mutex<HashMap<String, f64>>,
+}
+
+impl CreatedTracker {
+    /// Creation time (Unix seconds) of a series, recording it if new
+    fn created(&self, series: &str) -> f64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
+        *self
+            .first_seen
+            .lock()
+            .expect("created tracker lock poisoned")
+            .entry(series.to_string())
+            .or_insert(now)
+    }
+}
+
+// =============================================================================
+// CONVERSION
+// =============================================================================
+/// Convert a Prometheus text exposition into OpenMetrics text.
+///
+/// # Arguments
+/// * `text` - Output of `PrometheusHandle::render()`
+/// * `created` - Emit `_created` samples for counters when provided
+pub fn from_prometheus_text(text: &str, created: Option<&CreatedTracker>) -> String {
+    // First pass: learn which families are counters
+    let counters: Vec<&str> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("# TYPE "))
+        .filter_map(|rest| rest.split_once(' '))
+        .filter(|(_, kind)| kind.trim() == "counter")
+        .map(|(name, _)| name)
+        .collect();
+
+    let mut out = String::with_capacity(text.len() + 64);
+
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // Metadata lines: rename counter families to their base name
+        if let Some((prefix, rest)) = line
+            .strip_prefix("# HELP ")
+            .map(|rest| ("# HELP ", rest))
+            .or_else(|| line.strip_prefix("# TYPE ").map(|rest| ("# TYPE ", rest)))
+        {
+            let (name, tail) = rest.split_once(' ').unwrap_or((rest, ""));
+            let name = if counters.contains(&name) {
+                name.strip_suffix("_total").unwrap_or(name)
+            } else {
+                name
+            };
+            out.push_str(prefix);
+            out.push_str(name);
+            out.push(' ');
+            out.push_str(tail);
+            out.push('\n');
+            continue;
+        }
+
+        if line.starts_with('#') {
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+
+        // Sample line: "<name>{<labels>} <value>" or "<name> <value>"
+        let name_end = line.find(['{', ' ']).unwrap_or(line.len());
+        let name = &line[..name_end];
+
+        if !counters.contains(&name) {
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+
+        // Counter samples must carry the _total suffix
+        let base = name.strip_suffix("_total").unwrap_or(name);
+        let rest = &line[name_end..];
+        out.push_str(base);
+        out.push_str("_total");
+        out.push_str(rest);
+        out.push('\n');
+
+        if let Some(tracker) = created {
+            let labels = rest.rsplit_once(' ').map(|(labels, _)| labels).unwrap_or("");
+            let created_at = tracker.created(&format!("{}{}", base, labels));
+            out.push_str(&format!("{}_created{} {:.3}\n", base, labels, created_at));
+        }
+    }
+
+    out.push_str("# EOF\n");
+    out
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+# HELP http_requests_total Total number of HTTP requests received
+# TYPE http_requests_total counter
+http_requests_total{method=\"GET\",status=\"200\"} 42
+
+# TYPE inventory_low_stock_items gauge
+inventory_low_stock_items 3
+";
+
+    #[test]
+    fn test_wants_openmetrics() {
+        assert!(wants_openmetrics(
+            "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5"
+        ));
+        assert!(!wants_openmetrics("text/plain"));
+    }
+
+    #[test]
+    fn test_counter_families_and_eof() {
+        let out = from_prometheus_text(SAMPLE, None);
+
+        assert!(out.contains("# HELP http_requests Total number"));
+        assert!(out.contains("# TYPE http_requests counter\n"));
+        assert!(out.contains("http_requests_total{method=\"GET\",status=\"200\"} 42\n"));
+        assert!(out.contains("# TYPE inventory_low_stock_items gauge\n"));
+        assert!(out.ends_with("# EOF\n"));
+        assert!(!out.contains("\n\n"));
+    }
+
+    #[test]
+    fn test_created_samples_are_stable() {
+        let tracker = CreatedTracker::default();
+        let first = from_prometheus_text(SAMPLE, Some(&tracker));
+        let second = from_prometheus_text(SAMPLE, Some(&tracker));
+
+        assert!(first.contains("http_requests_created{method=\"GET\",status=\"200\"} "));
+        assert_eq!(first, second);
+    }
+}
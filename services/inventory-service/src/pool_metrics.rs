@@ -0,0 +1,72 @@
+// =============================================================================
+// POOL METRICS MODULE
+// =============================================================================
+// Connection pool gauges, so latency spikes in Grafana can be matched with a
+// saturated PostgreSQL pool or a lost Redis connection.
+//
+// HOW:
+// A supervised job ("pool-sampler") runs every POOL_METRICS_INTERVAL_SECS
+// (default 5) and sets:
+// - db_pool_connections{pool, state="idle"|"in_use"}, db_pool_max_connections
+// - db_pool_acquire_wait_seconds: how long the sample itself waited for a
+//   connection. Near zero while the pool has idle connections; it rises
+//   toward the acquire timeout (5s) as requests queue for connections.
+// - `pool` is "primary", or "replica-<n>" for each DATABASE_REPLICA_URLS
+//   entry (their acquire timeout is 1s)
+// - redis_connection_up and redis_ping_seconds from a PING on the shared
+//   connection
+//
+// LEARNING NOTES:
+// - These are samples, not totals: a burst shorter than the interval can
+//   fall between two samples. http_request_duration_seconds still shows it.
+// - The sample holds a pool connection for an instant; on a pool of 10
+//   that is a tenth of the capacity for well under a millisecond
+// - in_use / max near 1 together with a growing acquire wait means
+//   requests are queueing for connections (raise the pool size or find the
+//   slow queries); a Redis PING that fails while Postgres looks fine points
+//   at the cache instead
+// =============================================================================
+
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use std::time::{Duration, Instant};
+
+use crate::db::Database;
+use crate::metrics;
+
+/// Longest a Redis PING may take before it counts as down
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Sample the pools every `interval`, forever
+pub async fn run_sampler(db: Database, redis: ConnectionManager, interval: Duration) -> Result<()> {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+        sample_db(&db).await;
+        sample_redis(&mut redis.clone()).await;
+    }
+}
+
+async fn sample_db(db: &Database) {
+    for probe in db.probe_pools().await {
+        if let Err(e) = &probe.result {
+            tracing::warn!(
+                pool = %probe.pool,
+                wait_ms = probe.acquire_wait.as_millis() as u64,
+                error = %e,
+                "Pool sample got no connection"
+            );
+        }
+        metrics::set_db_pool(&probe.pool, &probe.stats, probe.acquire_wait.as_secs_f64());
+    }
+}
+
+async fn sample_redis(redis: &mut ConnectionManager) {
+    let start = Instant::now();
+    let ping = redis::cmd("PING");
+    let answer = tokio::time::timeout(PING_TIMEOUT, ping.query_async::<_, String>(redis)).await;
+    let up = matches!(answer, Ok(Ok(_)));
+    metrics::set_redis_connection(up, start.elapsed().as_secs_f64());
+}
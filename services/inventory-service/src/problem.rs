@@ -0,0 +1,189 @@
+// =============================================================================
+// PROBLEM DETAILS MODULE
+// =============================================================================
+// Error bodies in the standard Problem Details format (RFC 7807 / 9457,
+// `application/problem+json`) for clients that ask for it, next to our own
+// ErrorResponse format.
+//
+// WHO GETS WHICH FORMAT:
+// - Accept: application/problem+json  → Problem Details
+// - PROBLEM_JSON=true                 → Problem Details for every client
+// - otherwise                         → ErrorResponse, as before
+//
+// EXAMPLE:
+//   HTTP/1.1 409 Conflict
+//   Content-Type: application/problem+json
+//   {
+//     "type": "urn:problem-type:inventory:insufficient-stock",
+//     "title": "Insufficient stock",
+//     "status": 409,
+//     "detail": "Available: 2, Requested: 5",
+//     "instance": "urn:request-id:1f0c6a52-...",
+//     "code": "INSUFFICIENT_STOCK"
+//   }
+// `code` is the ErrorResponse `error` code; `details` and `fields` are kept
+// as extension members too, so no information is lost by switching.
+//
+// REQUEST IDS:
+// Every request gets an ID: the caller's X-Request-Id when it sends a
+// usable one, a fresh UUID otherwise. It is echoed in the X-Request-Id
+// response header and is the `instance` of Problem Details bodies, so a
+// failed call can be found in the logs.
+//
+// LEARNING NOTES:
+// - The choice lives in a task-local, like the language in i18n.rs, so
+//   AppError::into_response can read it without access to the request
+// - `type` is a URN rather than a URL: there are no pages to link to, and
+//   clients should match on it (or on `code`), not fetch it
+// =============================================================================
+
+use axum::{
+    extract::{Request, State},
+    http::{header::ACCEPT, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::models::{ErrorResponse, ProblemDetails};
+use crate::AppState;
+
+/// Media type of Problem Details bodies
+pub const CONTENT_TYPE: &str = "application/problem+json";
+
+/// Header carrying the request ID, in both directions
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest caller-supplied request ID that is kept
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+// -----------------------------------------------------------------------------
+// REQUEST CONTEXT
+// -----------------------------------------------------------------------------
+/// How errors of the current request are reported
+#[derive(Debug, Clone)]
+pub struct ErrorFormat {
+    /// Answer errors with Problem Details
+    pub problem: bool,
+    pub request_id: String,
+}
+
+tokio::task_local! {
+    static FORMAT: ErrorFormat;
+}
+
+/// Error format of the current request (`None` outside requests)
+pub fn current() -> Option<ErrorFormat> {
+    FORMAT.try_with(|format| format.clone()).ok()
+}
+
+/// Middleware: assign the request ID and pick the error format
+pub async fn negotiate(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| usable_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let problem = state.config.problem_json
+        || request
+            .headers()
+            .get(ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(accepts_problem);
+
+    let format = ErrorFormat {
+        problem,
+        request_id: request_id.clone(),
+    };
+    let mut response = FORMAT.scope(format, next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Short, visible ASCII only: the ID ends up in headers and logs
+fn usable_request_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Whether an Accept header lists application/problem+json (with q > 0)
+fn accepts_problem(accept: &str) -> bool {
+    accept.split(',').any(|range| {
+        let mut parts = range.split(';').map(str::trim);
+        let media_type = parts.next().unwrap_or("");
+        let refused = parts.any(|param| {
+            param
+                .strip_prefix("q=")
+                .and_then(|q| q.parse::<f32>().ok())
+                .is_some_and(|q| q <= 0.0)
+        });
+        media_type.eq_ignore_ascii_case(CONTENT_TYPE) && !refused
+    })
+}
+
+// -----------------------------------------------------------------------------
+// CONVERSION
+// -----------------------------------------------------------------------------
+/// The Problem Details form of an (already localized) error body
+pub fn from_error(status: StatusCode, body: ErrorResponse, request_id: &str) -> ProblemDetails {
+    ProblemDetails {
+        problem_type: format!(
+            "urn:problem-type:inventory:{}",
+            body.error.to_ascii_lowercase().replace('_', "-")
+        ),
+        title: title(&body.error),
+        status: status.as_u16(),
+        detail: body.message,
+        instance: format!("urn:request-id:{}", request_id),
+        code: body.error,
+        details: body.details,
+        fields: body.fields,
+    }
+}
+
+/// "INSUFFICIENT_STOCK" → "Insufficient stock"
+fn title(code: &str) -> String {
+    let words = code.to_ascii_lowercase().replace('_', " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts_problem() {
+        assert!(accepts_problem("application/problem+json"));
+        assert!(accepts_problem("application/json, Application/Problem+JSON;q=0.9"));
+        assert!(!accepts_problem("application/json"));
+        assert!(!accepts_problem("*/*"));
+        assert!(!accepts_problem("application/problem+json;q=0"));
+    }
+
+    #[test]
+    fn test_request_id_and_conversion() {
+        assert!(usable_request_id("req-42"));
+        assert!(!usable_request_id(""));
+        assert!(!usable_request_id("two words"));
+        assert!(!usable_request_id(&"x".repeat(MAX_REQUEST_ID_LEN + 1)));
+
+        let body = ErrorResponse::new("INSUFFICIENT_STOCK", "Available: 2, Requested: 5");
+        let problem = from_error(StatusCode::CONFLICT, body, "req-42");
+        assert_eq!(problem.problem_type, "urn:problem-type:inventory:insufficient-stock");
+        assert_eq!(problem.title, "Insufficient stock");
+        assert_eq!(problem.status, 409);
+        assert_eq!(problem.detail, "Available: 2, Requested: 5");
+        assert_eq!(problem.instance, "urn:request-id:req-42");
+        assert_eq!(problem.code, "INSUFFICIENT_STOCK");
+    }
+}
@@ -0,0 +1,348 @@
+// =============================================================================
+// PUBLIC MODE MODULE
+// =============================================================================
+// Read-only profile for the lab's public demo frontend, which calls the
+// service straight from the browser without credentials.
+//
+// WHAT IT DOES (PUBLIC_READ_ONLY=true):
+// - Only GET and HEAD get through (plus CORS preflights); anything that
+//   would change stock answers 403 READ_ONLY
+// - Admin, chaos and debug endpoints are hidden, even for GET
+// - Every client IP gets a token bucket: PUBLIC_RATE_LIMIT_RPS requests per
+//   second (default 5) with bursts up to PUBLIC_RATE_LIMIT_BURST (default
+//   20); past that, 429 RATE_LIMITED with Retry-After
+// - Successful responses get `Cache-Control: public, max-age=N`
+//   (PUBLIC_CACHE_MAX_AGE_SECS, default 10), so browsers and CDNs absorb
+//   the polling
+//
+// TRUSTED PROXIES:
+// The client IP is the TCP peer. Only when the peer is one of
+// TRUSTED_PROXIES (comma-separated addresses or CIDR ranges, default none)
+// is X-Forwarded-For read: from the right, skipping hops that are trusted
+// proxies too, the first other address is the client.
+//
+//     TRUSTED_PROXIES="172.28.0.10, 10.0.0.0/8"
+//
+// LEARNING NOTES:
+// - Anyone can send X-Forwarded-For. Read from an untrusted peer, or past
+//   the first untrusted hop, it could be forged to dodge the limit, so it
+//   only counts when our own proxies added it.
+// - The same client IP keys the per-client rate limit (rate_limit.rs)
+// - Buckets live in this process; with N replicas a client gets up to N
+//   times the rate. Good enough for a demo, not for billing.
+// - /health, /ready and /metrics stay reachable for probes and scrapes
+// =============================================================================
+
+use anyhow::{Context, Result};
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use ipnet::IpNet;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::AppError;
+use crate::graphql;
+use crate::metrics;
+use crate::AppState;
+
+/// Path prefixes never served in public mode
+const HIDDEN_PREFIXES: [&str; 3] = ["/api/v1/admin", "/api/v1/chaos", "/debug"];
+
+/// Past this many tracked clients, idle buckets are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Settings of public read-only mode
+#[derive(Debug, Clone, Copy)]
+pub struct PublicModeConfig {
+    /// Requests per second each client may sustain
+    pub rate_per_sec: f64,
+    /// Requests a client may send at once after being idle
+    pub burst: u32,
+    /// max-age of successful responses, in seconds
+    pub cache_max_age_secs: u64,
+}
+
+// -----------------------------------------------------------------------------
+// RATE LIMITER
+// -----------------------------------------------------------------------------
+/// Tokens left for one client
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Per-client token buckets
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate_per_sec: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(rate_per_sec: f64, burst: u32) -> Self {
+        Self {
+            rate_per_sec: rate_per_sec.max(0.001),
+            burst: f64::from(burst.max(1)),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take one token for `client`
+    ///
+    /// # Returns
+    /// - `Err(wait)` until the next token is available
+    fn acquire(&self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() > MAX_TRACKED_CLIENTS {
+            // A bucket idle long enough to refill completely is the same as
+            // no bucket
+            let refill = Duration::from_secs_f64(self.burst / self.rate_per_sec);
+            buckets.retain(|_, bucket| now.saturating_duration_since(bucket.updated) < refill);
+        }
+
+        let bucket = buckets.entry(client).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate_per_sec).min(self.burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate_per_sec))
+        }
+    }
+}
+
+/// Public mode settings plus the client buckets
+#[derive(Debug)]
+pub struct PublicMode {
+    config: PublicModeConfig,
+    limiter: RateLimiter,
+}
+
+impl PublicMode {
+    pub fn new(config: PublicModeConfig) -> Self {
+        Self {
+            limiter: RateLimiter::new(config.rate_per_sec, config.burst),
+            config,
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// REQUEST CHECKS
+// -----------------------------------------------------------------------------
+/// Why a request isn't served in public mode, if it isn't
+pub fn rejection(method: &Method, path: &str) -> Option<&'static str> {
+    if HIDDEN_PREFIXES.iter().any(|prefix| path.starts_with(prefix)) {
+        return Some("This endpoint is not available in public read-only mode");
+    }
+    // Queries are reads whatever the method; mutations are refused per field
+    if path == graphql::PATH {
+        return None;
+    }
+    if !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Some("The service is in public read-only mode");
+    }
+    None
+}
+
+// -----------------------------------------------------------------------------
+// CLIENT ADDRESS
+// -----------------------------------------------------------------------------
+/// Proxies whose X-Forwarded-For entries are believed (TRUSTED_PROXIES)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustedProxies(Vec<IpNet>);
+
+impl TrustedProxies {
+    /// Parse a comma-separated list of addresses and CIDR ranges; empty
+    /// trusts no one
+    pub fn parse(list: &str) -> Result<Self> {
+        let nets = list
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                entry
+                    .parse::<IpNet>()
+                    .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                    .with_context(|| format!("'{}' is not an IP address or CIDR range", entry))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self(nets))
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        self.0.iter().any(|net| net.contains(ip))
+    }
+}
+
+/// Client IP: the TCP peer, or, when the peer is a trusted proxy, the
+/// nearest X-Forwarded-For hop that isn't one
+///
+/// # Returns
+/// - `None` without a peer address
+pub fn client_ip(headers: &HeaderMap, peer: Option<IpAddr>, trusted: &TrustedProxies) -> Option<IpAddr> {
+    let mut client = peer?;
+    if !trusted.contains(&client) {
+        return Some(client);
+    }
+
+    let hops = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect::<Vec<_>>();
+    for hop in hops.into_iter().rev() {
+        // A garbled entry is as far as the chain can be followed
+        let Ok(hop) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = hop;
+        if !trusted.contains(&hop) {
+            break;
+        }
+    }
+    Some(client)
+}
+
+/// Client IP of a request (see `client_ip`)
+pub fn request_client_ip(
+    extensions: &axum::http::Extensions,
+    headers: &HeaderMap,
+    trusted: &TrustedProxies,
+) -> Option<IpAddr> {
+    let peer = extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    client_ip(headers, peer, trusted)
+}
+
+// =============================================================================
+// MIDDLEWARE
+// =============================================================================
+/// Enforce public read-only mode when it is configured; a no-op otherwise
+pub async fn enforce(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(public) = &state.public_mode else {
+        return next.run(request).await;
+    };
+
+    if let Some(reason) = rejection(request.method(), request.uri().path()) {
+        return AppError::ReadOnly(reason.to_string()).into_response();
+    }
+
+    // Preflights are answered by the CORS layer and don't count
+    if request.method() != Method::OPTIONS {
+        let client = request_client_ip(
+            request.extensions(),
+            request.headers(),
+            &state.config.trusted_proxies,
+        );
+        if let Some(client) = client {
+            if let Err(wait) = public.limiter.acquire(client, Instant::now()) {
+                let retry_after_secs = wait.as_secs_f64().ceil() as u64;
+                let route = request
+                    .extensions()
+                    .get::<MatchedPath>()
+                    .map(|path| path.as_str())
+                    .unwrap_or("unmatched");
+                metrics::record_rate_limited("public", route);
+                return AppError::RateLimited { retry_after_secs }.into_response();
+            }
+        }
+    }
+
+    let mut response = next.run(request).await;
+
+    if response.status().is_success() && !response.headers().contains_key(header::CACHE_CONTROL) {
+        let value = format!("public, max-age={}", public.config.cache_max_age_secs);
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response.headers_mut().insert(header::CACHE_CONTROL, value);
+        }
+    }
+
+    response
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = RateLimiter::new(2.0, 3);
+        let client: IpAddr = "10.0.0.1".parse().unwrap();
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.acquire(client, start).is_ok());
+        }
+        let wait = limiter.acquire(client, start).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(500));
+
+        // Half a second buys one more token; other clients are unaffected
+        assert!(limiter.acquire(client, start + Duration::from_millis(500)).is_ok());
+        assert!(limiter.acquire("10.0.0.2".parse().unwrap(), start).is_ok());
+    }
+
+    #[test]
+    fn test_read_only_rejections() {
+        assert!(rejection(&Method::GET, "/api/v1/inventory").is_none());
+        assert!(rejection(&Method::OPTIONS, "/api/v1/inventory/reserve").is_none());
+        assert!(rejection(&Method::POST, "/api/v1/inventory/reserve").is_some());
+        assert!(rejection(&Method::GET, "/api/v1/admin/tasks").is_some());
+        assert!(rejection(&Method::GET, "/debug/vars").is_some());
+        assert!(rejection(&Method::GET, "/api/v1/chaos").is_some());
+    }
+
+    #[test]
+    fn test_client_ip_ignores_forwarded_for_from_untrusted_peers() {
+        let peer = Some("198.51.100.9".parse().unwrap());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "203.0.113.7".parse().unwrap());
+
+        assert_eq!(client_ip(&headers, peer, &TrustedProxies::default()), peer);
+        let trusted = TrustedProxies::parse("172.28.0.0/16").unwrap();
+        assert_eq!(client_ip(&headers, peer, &trusted), peer);
+        assert_eq!(client_ip(&headers, None, &trusted), None);
+    }
+
+    #[test]
+    fn test_client_ip_walks_trusted_hops() {
+        let trusted = TrustedProxies::parse("172.28.0.0/16, 10.1.2.3").unwrap();
+        let peer = Some("172.28.0.5".parse().unwrap());
+        let mut headers = HeaderMap::new();
+        assert_eq!(client_ip(&headers, peer, &trusted), peer);
+
+        // The client forged the first entry; the proxies added the others
+        headers.insert("x-forwarded-for", "1.1.1.1, 203.0.113.7, 10.1.2.3".parse().unwrap());
+        assert_eq!(client_ip(&headers, peer, &trusted), Some("203.0.113.7".parse().unwrap()));
+
+        headers.insert("x-forwarded-for", "junk, 10.1.2.3".parse().unwrap());
+        assert_eq!(client_ip(&headers, peer, &trusted), Some("10.1.2.3".parse().unwrap()));
+
+        assert!(TrustedProxies::parse("not-an-ip").is_err());
+        assert_eq!(TrustedProxies::parse(" ").unwrap(), TrustedProxies::default());
+    }
+}
@@ -0,0 +1,210 @@
+// =============================================================================
+// RATE LIMIT MODULE
+// =============================================================================
+// Per-client rate limiting for the API, so one runaway caller (a frontend
+// stuck in a refetch loop, a misconfigured job) can't take the service down
+// for everybody.
+//
+// HOW (RATE_LIMIT_ENABLED=true):
+// - Each client has a token bucket: RATE_LIMIT_RPS requests per second
+//   (default 50) with bursts up to RATE_LIMIT_BURST (default 100)
+// - The client is its X-API-Key (by fingerprint, like usage metering) when
+//   the key is one of API_KEYS, else its IP (the peer, or X-Forwarded-For
+//   from a trusted proxy: see public_mode.rs). An unknown key counts as no
+//   key: otherwise a fresh made-up key per request would get a fresh bucket.
+// - Buckets live in Redis (`ratelimit:<client>`) and are updated by one Lua
+//   script, so every replica draws from the same bucket and the limit holds
+//   service-wide
+// - Past the limit: 429 RATE_LIMITED with Retry-After, counted in
+//   rate_limited_requests_total{limiter="client", route}
+//
+// LEARNING NOTES:
+// - Only /api/ routes are limited; probes and scrapes always get through
+// - The script reads the clock with Redis TIME, so replicas with drifting
+//   clocks still agree on the refill
+// - Fails open: with Redis down, requests are let through (and
+//   rate_limit_errors_total counts them) rather than refused
+// - Public mode has its own, per-process limiter (public_mode.rs); both
+//   apply when both are on
+// =============================================================================
+
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use redis::aio::ConnectionManager;
+use redis::Script;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, OnceLock};
+
+use crate::deadline;
+use crate::error::AppError;
+use crate::graphql;
+use crate::metrics;
+use crate::public_mode::{self, TrustedProxies};
+use crate::usage::{self, ApiKeys};
+use crate::AppState;
+
+const KEY_PREFIX: &str = "ratelimit:";
+
+/// Take one token from the bucket in KEYS[1]
+///
+/// ARGV: rate per second, burst. Returns {allowed (0/1), wait in ms}.
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local rate = tonumber(ARGV[1])
+local burst = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(bucket[1]) or burst
+local updated = tonumber(bucket[2]) or now
+tokens = math.min(burst, tokens + math.max(0, now - updated) * rate / 1000)
+
+local allowed, wait_ms = 0, 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+else
+    wait_ms = math.ceil((1 - tokens) * 1000 / rate)
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', now)
+-- A bucket idle long enough to refill completely is the same as no bucket
+redis.call('PEXPIRE', KEYS[1], math.ceil(burst * 1000 / rate) + 1000)
+return {allowed, wait_ms}
+"#;
+
+/// Settings of the per-client limiter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitConfig {
+    /// Requests per second each client may sustain
+    pub rate_per_sec: f64,
+    /// Requests a client may send at once after being idle
+    pub burst: u32,
+}
+
+/// The bucket script, compiled (hashed) once
+fn script() -> &'static Script {
+    static SCRIPT: OnceLock<Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| Script::new(TOKEN_BUCKET_SCRIPT))
+}
+
+/// Who a request is counted against: `key:<fingerprint>` for a known API
+/// key, else `ip:<address>`
+pub fn client_id(
+    headers: &HeaderMap,
+    peer: Option<IpAddr>,
+    api_keys: &ApiKeys,
+    trusted: &TrustedProxies,
+) -> Option<String> {
+    let api_key = headers
+        .get(usage::API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|key| api_keys.contains(key));
+    match api_key {
+        Some(key) => Some(format!("key:{}", usage::key_id(key))),
+        None => public_mode::client_ip(headers, peer, trusted).map(|ip| format!("ip:{}", ip)),
+    }
+}
+
+/// Take one token for `client`
+///
+/// # Returns
+/// - `Ok(None)` when allowed, `Ok(Some(wait_secs))` when limited
+async fn acquire(
+    redis: &ConnectionManager,
+    config: RateLimitConfig,
+    client: &str,
+) -> redis::RedisResult<Option<u64>> {
+    let (allowed, wait_ms): (i64, u64) = script()
+        .key(format!("{}{}", KEY_PREFIX, client))
+        .arg(config.rate_per_sec.max(0.001))
+        .arg(config.burst.max(1))
+        .invoke_async(&mut deadline::redis(redis))
+        .await?;
+
+    Ok((allowed == 0).then(|| wait_ms.div_ceil(1000).max(1)))
+}
+
+// =============================================================================
+// MIDDLEWARE
+// =============================================================================
+/// Enforce the per-client limit on /api/ routes when it is configured
+pub async fn enforce(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let Some(config) = state.config.rate_limit else {
+        return next.run(request).await;
+    };
+    let path = request.uri().path();
+    if !path.starts_with("/api/") && path != graphql::PATH {
+        return next.run(request).await;
+    }
+
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let client = client_id(
+        request.headers(),
+        peer,
+        &state.config.api_keys,
+        &state.config.trusted_proxies,
+    );
+    let Some(client) = client else {
+        return next.run(request).await;
+    };
+
+    match acquire(&state.redis, config, &client).await {
+        Ok(None) => {}
+        Ok(Some(retry_after_secs)) => {
+            let route = request
+                .extensions()
+                .get::<MatchedPath>()
+                .map(|path| path.as_str())
+                .unwrap_or("unmatched");
+            metrics::record_rate_limited("client", route);
+            tracing::debug!(client = %client, route, "Client rate limited");
+            return AppError::RateLimited { retry_after_secs }.into_response();
+        }
+        Err(e) => {
+            metrics::record_rate_limit_error();
+            tracing::debug!(error = %e, "Rate limiter unavailable, letting request through");
+        }
+    }
+
+    next.run(request).await
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_id_prefers_known_api_key() {
+        let keys = ApiKeys::parse("frontend-key, batch-key");
+        let trusted = TrustedProxies::parse("10.0.0.1").unwrap();
+        let peer = Some("10.0.0.1".parse().unwrap());
+        let mut headers = HeaderMap::new();
+        assert_eq!(client_id(&headers, peer, &keys, &trusted).as_deref(), Some("ip:10.0.0.1"));
+
+        headers.insert("x-forwarded-for", "203.0.113.7".parse().unwrap());
+        assert_eq!(client_id(&headers, peer, &keys, &trusted).as_deref(), Some("ip:203.0.113.7"));
+
+        headers.insert(usage::API_KEY_HEADER, "frontend-key".parse().unwrap());
+        let id = client_id(&headers, peer, &keys, &trusted).unwrap();
+        assert_eq!(id, format!("key:{}", usage::key_id("frontend-key")));
+
+        // Made-up and blank keys count against the address
+        headers.insert(usage::API_KEY_HEADER, "made-up-key".parse().unwrap());
+        assert_eq!(client_id(&headers, peer, &keys, &trusted).as_deref(), Some("ip:203.0.113.7"));
+        headers.insert(usage::API_KEY_HEADER, " ".parse().unwrap());
+        assert_eq!(client_id(&headers, peer, &keys, &trusted).as_deref(), Some("ip:203.0.113.7"));
+        assert_eq!(client_id(&HeaderMap::new(), None, &keys, &trusted), None);
+    }
+}
@@ -0,0 +1,295 @@
+// =============================================================================
+// REDACTION MODULE
+// =============================================================================
+// Scrubs sensitive fields (order IDs, customer references, API keys) out of
+// structured logs and captured request bodies.
+//
+// HOW IT WORKS:
+// The JSON log layer writes one complete JSON object per event. Instead of
+// writing it straight to stdout, it goes through RedactingWriter, which
+// parses the line, replaces the value of every configured field (at any
+// nesting depth, e.g. inside "fields" or "span") and writes the result.
+// Text values get the same treatment for `name=value` pairs inside them,
+// so a formatted message ("retrying with token=...") or a request span's
+// `uri` ("/api/v1/items?token=...") doesn't leak what the field would not.
+//
+// CONFIGURATION:
+//   LOG_REDACT_FIELDS="order_id,customer_id,api_key"   (case-insensitive)
+//   LOG_REDACT_FIELDS=""                                 (disable redaction)
+//
+// LEARNING NOTES:
+// - Redacting at the writer means handlers keep logging naturally; nobody
+//   has to remember to mask values at each call site
+// - The same `redact_json` helper is used for anything else we persist or
+//   print that may contain request bodies
+// =============================================================================
+
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::sync::Arc;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Fields redacted when LOG_REDACT_FIELDS is not set
+pub const DEFAULT_REDACT_FIELDS: &str =
+    "order_id,customer_id,customer_ref,api_key,authorization,password,token";
+
+// -----------------------------------------------------------------------------
+// REDACTOR
+// -----------------------------------------------------------------------------
+/// Set of field names whose values must never be written out
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    fields: Arc<HashSet<String>>,
+}
+
+impl Redactor {
+    /// Build from a comma-separated list of field names
+    pub fn new(fields: &str) -> Self {
+        let fields = fields
+            .split(',')
+            .map(|f| f.trim().to_ascii_lowercase())
+            .filter(|f| !f.is_empty())
+            .collect();
+        Self {
+            fields: Arc::new(fields),
+        }
+    }
+
+    /// Build from LOG_REDACT_FIELDS (falls back to DEFAULT_REDACT_FIELDS)
+    ///
+    /// Read directly from the environment because logging is initialized
+    /// before the rest of the configuration is loaded.
+    pub fn from_env() -> Self {
+        Self::new(
+            &std::env::var("LOG_REDACT_FIELDS").unwrap_or_else(|_| DEFAULT_REDACT_FIELDS.into()),
+        )
+    }
+
+    /// Whether a field name is on the redaction list
+    pub fn is_sensitive(&self, field: &str) -> bool {
+        self.fields.contains(&field.to_ascii_lowercase())
+    }
+
+    /// Redact sensitive fields in a JSON value, in place, at any depth
+    pub fn redact_json(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if self.is_sensitive(key) {
+                        *field = Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact_json(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_json(item)),
+            Value::String(text) => {
+                if let Some(redacted) = self.redact_text(text) {
+                    *text = redacted;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Redact the value of every sensitive `name=value` pair in free text,
+    /// such as query parameters in a URI or fields spelled out in a message.
+    /// None when there was nothing to redact.
+    pub fn redact_text(&self, text: &str) -> Option<String> {
+        if self.fields.is_empty() || !text.contains('=') {
+            return None;
+        }
+
+        let mut out = String::with_capacity(text.len());
+        let mut redacted = false;
+        let mut rest = text;
+        while let Some(eq) = rest.find('=') {
+            let (before, value) = (&rest[..eq], &rest[eq + 1..]);
+            // The name runs back to the previous separator (`?`, `&`, space...)
+            let name_start = before
+                .rfind(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+                .map_or(0, |i| i + 1);
+            let name = &before[name_start..];
+            let value_end = value
+                .find(|c: char| c.is_whitespace() || matches!(c, '&' | ';' | ',' | '#' | '"' | '\'' | ')'))
+                .unwrap_or(value.len());
+
+            out.push_str(before);
+            out.push('=');
+            if !name.is_empty() && value_end > 0 && self.is_sensitive(name) {
+                out.push_str(REDACTED);
+                redacted = true;
+                rest = &value[value_end..];
+            } else {
+                rest = value;
+            }
+        }
+        out.push_str(rest);
+
+        redacted.then_some(out)
+    }
+
+    /// Redact a single JSON log line; non-JSON input is returned unchanged
+    pub fn redact_line(&self, line: &[u8]) -> Vec<u8> {
+        if self.fields.is_empty() {
+            return line.to_vec();
+        }
+        match serde_json::from_slice::<Value>(line) {
+            Ok(mut value) => {
+                self.redact_json(&mut value);
+                let mut out = serde_json::to_vec(&value).unwrap_or_else(|_| line.to_vec());
+                if line.ends_with(b"\n") {
+                    out.push(b'\n');
+                }
+                out
+            }
+            Err(_) => line.to_vec(),
+        }
+    }
+}
+
+// =============================================================================
+// LOG WRITER
+// =============================================================================
+// tracing-subscriber asks the MakeWriter for a fresh writer per event and
+// writes the formatted event into it. We buffer the event and emit the
+// redacted version when the writer is dropped.
+
+impl<'a> MakeWriter<'a> for Redactor {
+    type Writer = RedactingWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter {
+            redactor: self.clone(),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+/// Per-event writer that redacts before forwarding to stdout
+pub struct RedactingWriter {
+    redactor: Redactor,
+    buffer: Vec<u8>,
+}
+
+impl Write for RedactingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let line = self.redactor.redact_line(&self.buffer);
+        self.buffer.clear();
+        io::stdout().lock().write_all(&line)
+    }
+}
+
+impl Drop for RedactingWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redacts_nested_fields_case_insensitively() {
+        let redactor = Redactor::new("order_id, API_KEY");
+        let mut value = json!({
+            "level": "INFO",
+            "fields": { "order_id": "ORD-1", "sku": "SKU-1" },
+            "spans": [{ "api_key": "secret" }]
+        });
+
+        redactor.redact_json(&mut value);
+
+        assert_eq!(value["fields"]["order_id"], REDACTED);
+        assert_eq!(value["fields"]["sku"], "SKU-1");
+        assert_eq!(value["spans"][0]["api_key"], REDACTED);
+    }
+
+    #[test]
+    fn test_redact_line_keeps_newline_and_non_json() {
+        let redactor = Redactor::new("order_id");
+
+        let line = redactor.redact_line(b"{\"order_id\":\"ORD-1\"}\n");
+        assert_eq!(line, b"{\"order_id\":\"[REDACTED]\"}\n");
+
+        assert_eq!(redactor.redact_line(b"plain text"), b"plain text");
+    }
+
+    #[test]
+    fn test_redact_text_masks_query_and_message_pairs() {
+        let redactor = Redactor::new("token,order_id");
+
+        assert_eq!(
+            redactor.redact_text("/api/v1/items?sku=S-1&token=abc123&page=2").as_deref(),
+            Some("/api/v1/items?sku=S-1&token=[REDACTED]&page=2")
+        );
+        assert_eq!(
+            redactor.redact_text("retrying ORDER_ID=ORD-1, attempt=2").as_deref(),
+            Some("retrying ORDER_ID=[REDACTED], attempt=2")
+        );
+        // Similar names and pairs without a value are left alone
+        assert_eq!(redactor.redact_text("/api?csrf_token=x&token=&sku=S-1"), None);
+    }
+
+    /// Buffer standing in for stdout, so the test can read what was logged
+    #[derive(Clone, Default)]
+    struct Captured(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_logged_uri_query_token_is_redacted() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(move || writer.clone())
+            .finish();
+
+        // Same span fields as the request span built in main.rs
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!(
+                "request",
+                method = "GET",
+                route = "/api/v1/items",
+                uri = "/api/v1/items?token=s3cr3t&sku=SKU-1",
+            );
+            let _entered = span.enter();
+            tracing::info!("calling upstream /lookup?token=s3cr3t");
+        });
+
+        let line = captured.0.lock().unwrap().clone();
+        let logged = String::from_utf8(Redactor::new(DEFAULT_REDACT_FIELDS).redact_line(&line)).unwrap();
+
+        assert!(String::from_utf8_lossy(&line).contains("s3cr3t"));
+        assert!(!logged.contains("s3cr3t"), "token leaked: {}", logged);
+        assert!(logged.contains("token=[REDACTED]&sku=SKU-1"));
+        assert!(logged.contains("/lookup?token=[REDACTED]"));
+    }
+}
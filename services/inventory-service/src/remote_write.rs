@@ -0,0 +1,290 @@
+// =============================================================================
+// REMOTE WRITE MODULE
+// =============================================================================
+// Pushes our metrics to Prometheus / Mimir with the remote-write protocol,
+// for environments where nothing can scrape the pod.
+//
+// HOW:
+// 1. Every REMOTE_WRITE_INTERVAL_SECS, render the same exposition /metrics
+//    serves (so both paths always carry identical series)
+// 2. Parse each sample line into a time series stamped with the push time,
+//    adding `job` and `instance` labels (a scraper would normally add them)
+// 3. Encode a protobuf WriteRequest, snappy-compress it and POST it
+//
+// CONFIGURATION:
+// - REMOTE_WRITE_URL: receiver, e.g. http://mimir:9009/api/v1/push
+//   (default: unset, pushing is off)
+// - REMOTE_WRITE_INTERVAL_SECS: push interval (default: 15)
+// - REMOTE_WRITE_TENANT: sent as X-Scope-OrgID for multi-tenant Mimir
+//
+// LEARNING NOTES:
+// - Remote write 1.0 has no metadata, so HELP/TYPE lines are dropped;
+//   histograms arrive as their _bucket / _sum / _count series, just like
+//   after a scrape
+// - A failed push is logged and skipped; the next push carries the
+//   current values, so counters and gauges catch up on their own
+// =============================================================================
+
+use anyhow::{Context, Result};
+use metrics_exporter_prometheus::PrometheusHandle;
+use prost::Message;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::http_client::HttpClient;
+
+/// `job` label added to every pushed series
+const JOB: &str = "inventory-service";
+
+/// Header selecting the tenant in Mimir / Cortex
+const TENANT_HEADER: &str = "X-Scope-OrgID";
+
+// -----------------------------------------------------------------------------
+// CONFIGURATION
+// -----------------------------------------------------------------------------
+/// Where and how often metrics are pushed
+#[derive(Debug, Clone)]
+pub struct RemoteWriteTarget {
+    pub url: String,
+    pub interval: Duration,
+    pub tenant: Option<String>,
+}
+
+// -----------------------------------------------------------------------------
+// PROTOCOL (prometheus/prompb/remote.proto, types.proto)
+// -----------------------------------------------------------------------------
+#[derive(Clone, PartialEq, Message)]
+struct WriteRequest {
+    #[prost(message, repeated, tag = "1")]
+    timeseries: Vec<TimeSeries>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TimeSeries {
+    #[prost(message, repeated, tag = "1")]
+    labels: Vec<Label>,
+    #[prost(message, repeated, tag = "2")]
+    samples: Vec<Sample>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Label {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(string, tag = "2")]
+    value: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Sample {
+    #[prost(double, tag = "1")]
+    value: f64,
+    /// Unix milliseconds
+    #[prost(int64, tag = "2")]
+    timestamp: i64,
+}
+
+// =============================================================================
+// PUSHER
+// =============================================================================
+/// Push the current metrics every `target.interval`, forever
+pub async fn run_pusher(
+    handle: PrometheusHandle,
+    http: HttpClient,
+    target: RemoteWriteTarget,
+) -> Result<()> {
+    let instance = std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string());
+    let mut ticker = tokio::time::interval(target.interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        let request = to_write_request(&handle.render(), &instance, timestamp);
+        let series = request.timeseries.len();
+
+        if let Err(e) = push(&http, &target, &request).await {
+            tracing::warn!(error = %e, series, "Remote write push failed");
+        } else {
+            tracing::debug!(series, "Pushed metrics via remote write");
+        }
+    }
+}
+
+/// Compress and POST one write request
+async fn push(http: &HttpClient, target: &RemoteWriteTarget, request: &WriteRequest) -> Result<()> {
+    let body = snap::raw::Encoder::new()
+        .compress_vec(&request.encode_to_vec())
+        .context("Failed to snappy-compress remote write request")?;
+
+    let mut builder = http
+        .client()
+        .post(&target.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-protobuf")
+        .header(reqwest::header::CONTENT_ENCODING, "snappy")
+        .header("X-Prometheus-Remote-Write-Version", "0.1.0")
+        .body(body);
+    if let Some(tenant) = &target.tenant {
+        builder = builder.header(TENANT_HEADER, tenant);
+    }
+
+    http.send("remote-write", builder)
+        .await?
+        .error_for_status()
+        .context("Remote write receiver rejected the push")?;
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// CONVERSION
+// -----------------------------------------------------------------------------
+/// Turn a Prometheus text exposition into a write request.
+///
+/// # Arguments
+/// * `text` - Output of `PrometheusHandle::render()`
+/// * `instance` - Value of the `instance` label
+/// * `timestamp` - Sample time, Unix milliseconds
+fn to_write_request(text: &str, instance: &str, timestamp: i64) -> WriteRequest {
+    let timeseries = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(parse_sample)
+        .map(|(name, mut labels, value)| {
+            labels.push(("__name__".to_string(), name));
+            for (key, default) in [("job", JOB), ("instance", instance)] {
+                if !labels.iter().any(|(name, _)| name == key) {
+                    labels.push((key.to_string(), default.to_string()));
+                }
+            }
+            // Receivers require labels sorted by name
+            labels.sort_by(|a, b| a.0.cmp(&b.0));
+
+            TimeSeries {
+                labels: labels
+                    .into_iter()
+                    .map(|(name, value)| Label { name, value })
+                    .collect(),
+                samples: vec![Sample { value, timestamp }],
+            }
+        })
+        .collect();
+
+    WriteRequest { timeseries }
+}
+
+/// Metric name, labels and value of one exposition line
+type ParsedSample = (String, Vec<(String, String)>, f64);
+
+/// Parse `name{label="value",...} value [timestamp]`
+fn parse_sample(line: &str) -> Option<ParsedSample> {
+    let name_end = line.find(['{', ' ']).unwrap_or(line.len());
+    let name = line[..name_end].to_string();
+    let mut rest = &line[name_end..];
+    let mut labels = Vec::new();
+
+    if let Some(mut inner) = rest.strip_prefix('{') {
+        loop {
+            inner = inner.trim_start_matches([',', ' ']);
+            if let Some(after) = inner.strip_prefix('}') {
+                rest = after;
+                break;
+            }
+
+            let (key, after_key) = inner.split_once("=\"")?;
+            let mut value = String::new();
+            let mut chars = after_key.char_indices();
+            let end = loop {
+                match chars.next()? {
+                    (i, '"') => break i,
+                    (_, '\\') => match chars.next()?.1 {
+                        'n' => value.push('\n'),
+                        other => value.push(other),
+                    },
+                    (_, c) => value.push(c),
+                }
+            };
+
+            labels.push((key.trim().to_string(), value));
+            inner = &after_key[end + 1..];
+        }
+    }
+
+    let value = rest.split_whitespace().next()?.parse().ok()?;
+    Some((name, labels, value))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+# HELP http_requests_total Total number of HTTP requests received
+# TYPE http_requests_total counter
+http_requests_total{method=\"GET\",status=\"200\"} 42
+
+# TYPE inventory_low_stock_items gauge
+inventory_low_stock_items 3
+db_query_duration_seconds_bucket{operation=\"select\",le=\"+Inf\"} 7
+";
+
+    fn labels(series: &TimeSeries) -> Vec<(&str, &str)> {
+        series
+            .labels
+            .iter()
+            .map(|l| (l.name.as_str(), l.value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_series_from_exposition() {
+        let request = to_write_request(SAMPLE, "pod-1", 1_700_000_000_000);
+        assert_eq!(request.timeseries.len(), 3);
+
+        let first = &request.timeseries[0];
+        assert_eq!(
+            labels(first),
+            vec![
+                ("__name__", "http_requests_total"),
+                ("instance", "pod-1"),
+                ("job", "inventory-service"),
+                ("method", "GET"),
+                ("status", "200"),
+            ]
+        );
+        assert_eq!(first.samples[0].value, 42.0);
+        assert_eq!(first.samples[0].timestamp, 1_700_000_000_000);
+
+        assert_eq!(request.timeseries[1].samples[0].value, 3.0);
+        assert!(labels(&request.timeseries[2]).contains(&("le", "+Inf")));
+    }
+
+    #[test]
+    fn test_escaped_label_values() {
+        let (name, labels, value) =
+            parse_sample(r#"m{path="a\"b\\c",line="x\ny"} +Inf"#).expect("parses");
+
+        assert_eq!(name, "m");
+        assert_eq!(labels[0], ("path".to_string(), "a\"b\\c".to_string()));
+        assert_eq!(labels[1], ("line".to_string(), "x\ny".to_string()));
+        assert!(value.is_infinite());
+    }
+
+    #[test]
+    fn test_encoded_request_round_trips() {
+        let request = to_write_request(SAMPLE, "pod-1", 1);
+        let compressed = snap::raw::Encoder::new()
+            .compress_vec(&request.encode_to_vec())
+            .unwrap();
+
+        let bytes = snap::raw::Decoder::new().decompress_vec(&compressed).unwrap();
+        assert_eq!(WriteRequest::decode(bytes.as_slice()).unwrap(), request);
+    }
+}
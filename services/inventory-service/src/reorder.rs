@@ -0,0 +1,167 @@
+// =============================================================================
+// REORDER MODULE
+// =============================================================================
+// Suggests what to order before SKUs run low, and drafts purchase orders
+// from the suggestions.
+//
+// HOW (GET /api/v1/inventory/reorder-suggestions):
+// - Consumption is what the movement ledger (`stock_movements`) shows
+//   leaving over the last `lookback_days` (default 30): confirmed
+//   reservations and negative adjustments. Transfers between warehouses
+//   don't count; the units are still there.
+// - reorder point = low_stock_threshold + consumption over `lead_time_days`
+//   (default 7), the stock needed to last until a delivery arrives
+// - A SKU whose available stock is at or below its reorder point is
+//   suggested, for enough units to reach the reorder point plus
+//   `cover_days` (default 30) of consumption
+//
+// DRAFT PURCHASE ORDERS (POST, same parameters):
+// The suggestions are grouped by home warehouse into `purchase_orders` rows
+// with status "draft"; GET /api/v1/purchase-orders lists them.
+//
+// LEARNING NOTES:
+// - SKUs without consumption are only suggested below their threshold
+// - Drafting twice drafts twice: nothing remembers what is already on
+//   order, so review the drafts before drafting again
+// =============================================================================
+
+use crate::models::{ReorderCandidate, ReorderSuggestion};
+
+/// Longest lookback, lead time or cover accepted, in days
+pub const MAX_DAYS: i32 = 365;
+
+/// Windows the suggestions are computed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReorderPolicy {
+    /// Days of ledger consumption to average
+    pub lookback_days: i32,
+    /// Days until an order placed now arrives
+    pub lead_time_days: i32,
+    /// Days of consumption an order should cover beyond the reorder point
+    pub cover_days: i32,
+}
+
+impl ReorderPolicy {
+    /// Check the windows given as query parameters
+    ///
+    /// # Returns
+    /// - `Err(reason)` when one is out of range
+    pub fn new(lookback_days: i32, lead_time_days: i32, cover_days: i32) -> Result<Self, String> {
+        for (name, days, min) in [
+            ("lookback_days", lookback_days, 1),
+            ("lead_time_days", lead_time_days, 0),
+            ("cover_days", cover_days, 1),
+        ] {
+            if !(min..=MAX_DAYS).contains(&days) {
+                return Err(format!("`{}` must be {}-{}, got {}", name, min, MAX_DAYS, days));
+            }
+        }
+        Ok(Self {
+            lookback_days,
+            lead_time_days,
+            cover_days,
+        })
+    }
+}
+
+/// The suggestion for one SKU, None when it doesn't need reordering
+pub fn suggest(candidate: &ReorderCandidate, policy: &ReorderPolicy) -> Option<ReorderSuggestion> {
+    let daily = candidate.consumed.max(0) as f64 / f64::from(policy.lookback_days);
+    let reorder_point =
+        i64::from(candidate.low_stock_threshold) + (daily * f64::from(policy.lead_time_days)).ceil() as i64;
+    let available = i64::from(candidate.available);
+    if available > reorder_point {
+        return None;
+    }
+
+    let order_up_to = reorder_point + (daily * f64::from(policy.cover_days)).ceil() as i64;
+    let suggested_quantity = order_up_to - available;
+    if suggested_quantity <= 0 {
+        return None;
+    }
+
+    Some(ReorderSuggestion {
+        sku: candidate.sku.clone(),
+        name: candidate.name.clone(),
+        warehouse: candidate.warehouse.clone(),
+        available: candidate.available,
+        low_stock_threshold: candidate.low_stock_threshold,
+        daily_consumption: round(daily, 100.0),
+        days_of_cover: (daily > 0.0).then(|| round(available.max(0) as f64 / daily, 10.0)),
+        reorder_point,
+        suggested_quantity,
+        unit_cost: candidate.unit_cost,
+        estimated_cost: candidate
+            .unit_cost
+            .map(|cost| round(cost * suggested_quantity as f64, 100.0)),
+    })
+}
+
+/// Suggestions for all candidates, fewest days of cover first (SKUs
+/// without consumption last)
+pub fn suggest_all(candidates: &[ReorderCandidate], policy: &ReorderPolicy) -> Vec<ReorderSuggestion> {
+    let mut suggestions: Vec<ReorderSuggestion> =
+        candidates.iter().filter_map(|c| suggest(c, policy)).collect();
+    suggestions.sort_by(|a, b| {
+        let cover = |s: &ReorderSuggestion| s.days_of_cover.unwrap_or(f64::INFINITY);
+        cover(a).total_cmp(&cover(b)).then_with(|| a.sku.cmp(&b.sku))
+    });
+    suggestions
+}
+
+fn round(value: f64, scale: f64) -> f64 {
+    (value * scale).round() / scale
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(sku: &str, available: i32, threshold: i32, consumed: i64) -> ReorderCandidate {
+        ReorderCandidate {
+            sku: sku.to_string(),
+            name: sku.to_string(),
+            warehouse: "JKT-1".to_string(),
+            available,
+            low_stock_threshold: threshold,
+            consumed,
+            unit_cost: Some(2.5),
+        }
+    }
+
+    #[test]
+    fn test_suggest_quantities() {
+        let policy = ReorderPolicy::new(30, 7, 30).unwrap();
+
+        // 2/day: reorder point 10 + 14, order up to 24 + 60
+        let low = suggest(&candidate("A", 20, 10, 60), &policy).unwrap();
+        assert_eq!(low.reorder_point, 24);
+        assert_eq!(low.suggested_quantity, 64);
+        assert_eq!(low.days_of_cover, Some(10.0));
+        assert_eq!(low.estimated_cost, Some(160.0));
+
+        assert!(suggest(&candidate("B", 25, 10, 60), &policy).is_none());
+
+        // No consumption: only below the threshold, back up to it
+        let idle = suggest(&candidate("C", 4, 10, 0), &policy).unwrap();
+        assert_eq!((idle.suggested_quantity, idle.days_of_cover), (6, None));
+        assert!(suggest(&candidate("D", 0, 0, 0), &policy).is_none());
+    }
+
+    #[test]
+    fn test_suggestions_most_urgent_first() {
+        let policy = ReorderPolicy::new(30, 7, 30).unwrap();
+        let suggestions = suggest_all(
+            &[candidate("IDLE", 1, 5, 0), candidate("SLOW", 10, 10, 30), candidate("FAST", 10, 10, 300)],
+            &policy,
+        );
+        let skus: Vec<&str> = suggestions.iter().map(|s| s.sku.as_str()).collect();
+        assert_eq!(skus, ["FAST", "SLOW", "IDLE"]);
+
+        assert!(ReorderPolicy::new(0, 7, 30).is_err());
+        assert!(ReorderPolicy::new(30, 7, MAX_DAYS + 1).is_err());
+    }
+}
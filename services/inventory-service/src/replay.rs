@@ -0,0 +1,226 @@
+// =============================================================================
+// REPLAY MODULE
+// =============================================================================
+// Replays a recorded request log against this service, so a specific traffic
+// shape (a morning spike, a burst of failed reservations) can be reproduced
+// while tuning dashboards and alerts.
+//
+// LOG FORMAT (JSON lines, one request per line):
+//   {"offset_ms":0,"method":"GET","path":"/api/v1/inventory"}
+//   {"offset_ms":250,"method":"POST","path":"/api/v1/inventory/reserve",
+//    "body":{"sku":"SKU-PHONE-001","quantity":1,"order_id":"ORD-1"}}
+//
+// `offset_ms` is the time since the first recorded request. Replaying at
+// speed 2.0 halves every gap, 0.5 doubles them.
+//
+// LEARNING NOTES:
+// - Requests are fired on schedule without waiting for earlier responses,
+//   otherwise a slow response would stretch the rest of the replay
+// - Log files are only read from REPLAY_DIR, never from arbitrary paths
+// - Paths must start with a single `/`: glued to the base URL, a path like
+//   `@evil.example/` or `//evil.example/` would name another host. Replay
+//   also re-checks the host of every URL it builds.
+// =============================================================================
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+use reqwest::Url;
+
+use crate::http_client::HttpClient;
+
+/// Fastest allowed replay speed multiplier
+pub const MAX_SPEED: f64 = 100.0;
+
+/// Header marking replayed requests (value: log file name)
+pub const REPLAYED_FROM_HEADER: &str = "x-replayed-from";
+
+// -----------------------------------------------------------------------------
+// RECORDS
+// -----------------------------------------------------------------------------
+/// One recorded request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayRecord {
+    /// Milliseconds since the first recorded request
+    pub offset_ms: u64,
+
+    /// HTTP method (GET, POST, ...)
+    pub method: String,
+
+    /// Path and query string, e.g. /api/v1/inventory?page=2
+    pub path: String,
+
+    /// JSON body, if the request had one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+}
+
+/// Resolve a log file name inside the replay directory.
+///
+/// Only plain file names are accepted, so callers can't read other files.
+pub fn resolve(dir: &str, file: &str) -> Option<PathBuf> {
+    let name = Path::new(file);
+    let is_plain_name = name.components().count() == 1
+        && name.file_name().is_some_and(|n| n == name.as_os_str());
+
+    is_plain_name.then(|| Path::new(dir).join(name))
+}
+
+/// Parse a JSON lines request log (blank lines are ignored)
+pub fn parse(contents: &str) -> Result<Vec<ReplayRecord>> {
+    let mut records = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let record = serde_json::from_str::<ReplayRecord>(line)
+                .with_context(|| format!("Invalid replay record on line {}", i + 1))?;
+            if !record.path.starts_with('/') || record.path.starts_with("//") {
+                anyhow::bail!(
+                    "Invalid replay record on line {}: path must start with a single /, got {:?}",
+                    i + 1,
+                    record.path
+                );
+            }
+            Ok(record)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    records.sort_by_key(|r| r.offset_ms);
+    Ok(records)
+}
+
+/// URL of a recorded path on `base`, unless it would leave `base`'s origin
+fn target_url(base: &Url, path: &str) -> Option<Url> {
+    let url = base.join(path).ok()?;
+    let same_origin = url.scheme() == base.scheme()
+        && url.host() == base.host()
+        && url.port_or_known_default() == base.port_or_known_default()
+        && url.username() == base.username()
+        && url.password() == base.password();
+    same_origin.then_some(url)
+}
+
+/// Read and parse a request log file
+pub async fn load(path: &Path) -> Result<Vec<ReplayRecord>> {
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read replay log {}", path.display()))?;
+    parse(&contents)
+}
+
+// =============================================================================
+// REPLAY
+// =============================================================================
+/// Outcome of a finished replay
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReplaySummary {
+    /// Requests that got a response (any status)
+    pub sent: usize,
+    /// Requests that failed at the transport level
+    pub failed: usize,
+}
+
+/// Send every record to `base_url`, keeping the recorded gaps divided by `speed`
+pub async fn run(
+    http: HttpClient,
+    base_url: String,
+    file: String,
+    records: Vec<ReplayRecord>,
+    speed: f64,
+) -> ReplaySummary {
+    let Ok(base) = Url::parse(&base_url) else {
+        tracing::warn!(base_url = %base_url, "Invalid replay base URL");
+        return ReplaySummary {
+            sent: 0,
+            failed: records.len(),
+        };
+    };
+    let start = Instant::now();
+    let mut in_flight = JoinSet::new();
+
+    for record in records {
+        let due = start + Duration::from_secs_f64(record.offset_ms as f64 / 1000.0 / speed);
+        tokio::time::sleep_until(due).await;
+
+        let Ok(method) = reqwest::Method::from_bytes(record.method.as_bytes()) else {
+            tracing::warn!(method = %record.method, "Skipping replay record with invalid method");
+            continue;
+        };
+        let Some(url) = target_url(&base, &record.path) else {
+            tracing::warn!(path = %record.path, "Skipping replay record that leaves the service");
+            continue;
+        };
+
+        let mut request = http
+            .client()
+            .request(method, url)
+            .header(REPLAYED_FROM_HEADER, &file);
+        if let Some(body) = &record.body {
+            request = request.json(body);
+        }
+
+        // Replays must reproduce the recorded traffic, so no retries
+        let http = http.clone();
+        in_flight.spawn(async move { http.send_once("replay", request).await.is_ok() });
+    }
+
+    let mut summary = ReplaySummary::default();
+    while let Some(result) = in_flight.join_next().await {
+        match result {
+            Ok(true) => summary.sent += 1,
+            _ => summary.failed += 1,
+        }
+    }
+    summary
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sorts_and_skips_blank_lines() {
+        let log = r#"{"offset_ms":500,"method":"GET","path":"/health"}
+
+{"offset_ms":0,"method":"POST","path":"/api/v1/inventory/reserve","body":{"sku":"A","quantity":1,"order_id":"O"}}
+"#;
+        let records = parse(log).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].method, "POST");
+        assert!(records[0].body.is_some());
+        assert_eq!(records[1].path, "/health");
+
+        assert!(parse("{not json}").is_err());
+    }
+
+    #[test]
+    fn test_paths_stay_on_the_service() {
+        for path in ["@evil.example/steal", "//evil.example/steal", "api/v1/inventory"] {
+            let log = format!(r#"{{"offset_ms":0,"method":"GET","path":{:?}}}"#, path);
+            assert!(parse(&log).is_err(), "{} accepted", path);
+        }
+
+        let base = Url::parse("http://127.0.0.1:8090").unwrap();
+        let url = target_url(&base, "/api/v1/inventory?page=2").unwrap();
+        assert_eq!(url.as_str(), "http://127.0.0.1:8090/api/v1/inventory?page=2");
+        assert_eq!(target_url(&base, "//evil.example/steal"), None);
+        assert_eq!(target_url(&base, "http://evil.example/"), None);
+    }
+
+    #[test]
+    fn test_resolve_rejects_paths() {
+        assert_eq!(resolve("replays", "spike.jsonl"), Some(PathBuf::from("replays/spike.jsonl")));
+        assert_eq!(resolve("replays", "../etc/passwd"), None);
+        assert_eq!(resolve("replays", "/etc/passwd"), None);
+        assert_eq!(resolve("replays", "sub/file.jsonl"), None);
+        assert_eq!(resolve("replays", ".."), None);
+    }
+}
@@ -0,0 +1,79 @@
+// =============================================================================
+// REPORTS MODULE
+// =============================================================================
+// Summaries computed from the audit trail and the stock event outbox, for
+// people who want numbers without writing PromQL.
+//
+// STOCKOUTS (GET /api/v1/reports/stockouts?period=7d):
+// Per SKU/warehouse over the period:
+// - incidents and seconds without available stock, from stock.out /
+//   stock.back pairs in `stock_events` (an open stockout counts until now)
+// - reservation fill rate = units reserved / units requested, from the
+//   reserve outcomes in `audit_events`
+//
+// LEARNING NOTES:
+// - Periods are "<n>h" or "<n>d", at most stock_events::RETENTION_DAYS,
+//   because older stock events have been pruned
+// - Stock events exist only since the trigger was installed; stockouts
+//   before that are not in the report
+// =============================================================================
+
+use chrono::Duration;
+
+use crate::stock_events::RETENTION_DAYS;
+
+/// Period used when none is given
+pub const DEFAULT_PERIOD: &str = "7d";
+
+/// Parse a report period like "24h" or "7d"
+///
+/// # Returns
+/// - `Err(reason)` for other formats or periods outside 1h..=RETENTION_DAYS
+pub fn parse_period(value: &str) -> Result<Duration, String> {
+    parse_duration(value, i64::from(RETENTION_DAYS))
+}
+
+/// Parse "<n>h" or "<n>d" between 1h and `max_days` (shared with the
+/// analytics windows, which reach further back than stock events)
+pub fn parse_duration(value: &str, max_days: i64) -> Result<Duration, String> {
+    let value = value.trim();
+    let invalid = || format!("Invalid period '{}', expected e.g. 24h or 7d", value);
+
+    let (number, unit) = value.split_at(value.len().saturating_sub(1));
+    let number: i64 = number.parse().map_err(|_| invalid())?;
+    let period = match unit {
+        "h" => Duration::hours(number),
+        "d" => Duration::days(number),
+        _ => return Err(invalid()),
+    };
+
+    if period < Duration::hours(1) || period > Duration::days(max_days) {
+        return Err(format!(
+            "Period must be between 1h and {}d, got '{}'",
+            max_days, value
+        ));
+    }
+
+    Ok(period)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_period() {
+        assert_eq!(parse_period("7d"), Ok(Duration::days(7)));
+        assert_eq!(parse_period("24h"), Ok(Duration::hours(24)));
+        assert_eq!(parse_period(DEFAULT_PERIOD), Ok(Duration::days(7)));
+
+        assert!(parse_period("0h").is_err());
+        assert!(parse_period("31d").is_err());
+        assert!(parse_period("7w").is_err());
+        assert!(parse_period("d").is_err());
+        assert!(parse_period("").is_err());
+    }
+}
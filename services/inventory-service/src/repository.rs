@@ -0,0 +1,174 @@
+// =============================================================================
+// REPOSITORY MODULE
+// =============================================================================
+// The item operations the core inventory endpoints need, as a trait, so
+// they can run against something other than Postgres.
+//
+// IMPLEMENTATIONS:
+// - Database (db.rs): Postgres, what the service normally runs on
+// - InMemoryRepository (in_memory.rs): a map in the process, for unit
+//   tests and `inventory-service --in-memory`
+//
+// WHAT GOES THROUGH IT:
+// AppState::repo serves listing, reading, creating, updating, adjusting,
+// reserving and releasing items, the home warehouse check, audit events of
+// failed operations, and the /ready database check. Everything
+// else (warehouses, movements, snapshots, webhooks, background jobs...)
+// still uses AppState::db directly; those need Postgres.
+//
+// LEARNING NOTES:
+// - `Arc<dyn InventoryRepository>` needs an object-safe trait, and async fns
+//   in traits aren't object safe yet; #[async_trait] boxes their futures
+// - Errors are the same anyhow errors db.rs returns (DbError::NotFound,
+//   DbError::InsufficientStock, AppError::...), so handlers map them to
+//   statuses the same way whichever implementation answered
+// - The Postgres implementation only forwards to the Database methods of
+//   the same names; their doc comments describe the behaviour
+// =============================================================================
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::db::Database;
+use crate::models::{
+    AdjustStockRequest, CreateItemRejection, CreateItemRequest, InventoryFilter, InventoryItem, NewAuditEvent,
+    ReleaseStockRequest, ReservationResponse, ReserveStockRequest, SortBy, SortOrder, UpdateItemRequest,
+};
+
+/// Storage of inventory items (see the module notes)
+#[async_trait]
+pub trait InventoryRepository: Send + Sync {
+    /// One page of items and the number of items matching `filter`
+    async fn list_items(
+        &self,
+        page: i32,
+        per_page: i32,
+        filter: &InventoryFilter<'_>,
+        sort_by: SortBy,
+        order: SortOrder,
+    ) -> Result<(Vec<InventoryItem>, i64)>;
+
+    /// Number of SKUs in the catalog
+    async fn count_items(&self) -> Result<i64>;
+
+    /// One item, as of the latest write
+    async fn get_by_sku(&self, sku: &str) -> Result<Option<InventoryItem>>;
+
+    /// One item for a response; may lag behind writes (read replicas)
+    async fn read_item(&self, sku: &str) -> Result<Option<InventoryItem>> {
+        self.get_by_sku(sku).await
+    }
+
+    /// Add an item, unless the SKU exists or the catalog holds `hard_limit`
+    /// SKUs; `Ok(Ok((item, skus)))` with the catalog size after the insert
+    async fn create_item(
+        &self,
+        req: &CreateItemRequest,
+        hard_limit: Option<i64>,
+    ) -> Result<std::result::Result<(InventoryItem, i64), CreateItemRejection>>;
+
+    /// Change name, home warehouse and/or threshold; `Ok(None)` for an
+    /// unknown SKU
+    async fn update_item(
+        &self,
+        sku: &str,
+        req: &UpdateItemRequest,
+        expected_version: Option<i64>,
+    ) -> Result<Option<InventoryItem>>;
+
+    /// Add `req.delta` units (negative removes); with `dry_run` nothing is
+    /// kept
+    async fn adjust_stock(&self, req: &AdjustStockRequest, dry_run: bool) -> Result<InventoryItem>;
+
+    /// Reserve units of a SKU for an order
+    async fn reserve_stock(&self, req: &ReserveStockRequest) -> Result<ReservationResponse>;
+
+    /// Give reserved units back
+    async fn release_stock(&self, req: &ReleaseStockRequest) -> Result<()>;
+
+    /// Add an event to the audit trail (used for failed operations; the
+    /// successful ones record their own)
+    async fn record_audit_event(&self, event: &NewAuditEvent<'_>) -> Result<()>;
+
+    /// Whether a warehouse code may be an item's home warehouse
+    async fn warehouse_exists(&self, code: &str) -> Result<bool>;
+
+    /// Whether the store answers
+    async fn health_check(&self) -> bool;
+
+    /// Read replicas behind read_item (0 when reads are never stale)
+    fn replica_count(&self) -> usize {
+        0
+    }
+}
+
+#[async_trait]
+impl InventoryRepository for Database {
+    async fn list_items(
+        &self,
+        page: i32,
+        per_page: i32,
+        filter: &InventoryFilter<'_>,
+        sort_by: SortBy,
+        order: SortOrder,
+    ) -> Result<(Vec<InventoryItem>, i64)> {
+        Database::list_items(self, page, per_page, filter, sort_by, order).await
+    }
+
+    async fn count_items(&self) -> Result<i64> {
+        Database::count_items(self).await
+    }
+
+    async fn get_by_sku(&self, sku: &str) -> Result<Option<InventoryItem>> {
+        Database::get_by_sku(self, sku).await
+    }
+
+    async fn read_item(&self, sku: &str) -> Result<Option<InventoryItem>> {
+        Database::read_item(self, sku).await
+    }
+
+    async fn create_item(
+        &self,
+        req: &CreateItemRequest,
+        hard_limit: Option<i64>,
+    ) -> Result<std::result::Result<(InventoryItem, i64), CreateItemRejection>> {
+        Database::create_item(self, req, hard_limit).await
+    }
+
+    async fn update_item(
+        &self,
+        sku: &str,
+        req: &UpdateItemRequest,
+        expected_version: Option<i64>,
+    ) -> Result<Option<InventoryItem>> {
+        Database::update_item(self, sku, req, expected_version).await
+    }
+
+    async fn adjust_stock(&self, req: &AdjustStockRequest, dry_run: bool) -> Result<InventoryItem> {
+        Database::adjust_stock(self, req, dry_run).await
+    }
+
+    async fn reserve_stock(&self, req: &ReserveStockRequest) -> Result<ReservationResponse> {
+        Database::reserve_stock(self, req).await
+    }
+
+    async fn release_stock(&self, req: &ReleaseStockRequest) -> Result<()> {
+        Database::release_stock(self, req).await
+    }
+
+    async fn record_audit_event(&self, event: &NewAuditEvent<'_>) -> Result<()> {
+        Database::record_audit_event(self, event).await
+    }
+
+    async fn warehouse_exists(&self, code: &str) -> Result<bool> {
+        Database::warehouse_exists(self, code).await
+    }
+
+    async fn health_check(&self) -> bool {
+        Database::health_check(self).await
+    }
+
+    fn replica_count(&self) -> usize {
+        Database::replica_count(self)
+    }
+}
@@ -0,0 +1,119 @@
+// =============================================================================
+// RESERVATION EXPIRY MODULE
+// =============================================================================
+// Gives back stock held by reservations that were never released or
+// confirmed. Reservations advertise `expires_at` = reserve time + 24 hours;
+// past that, the units return to available stock.
+//
+// HOW:
+// A supervised job ("reservation-expiry-sweeper") looks for expired holdings
+// every RESERVATION_EXPIRY_INTERVAL_SECS (default: 60) and releases each one
+// in its own transaction, audited as a release with detail "expired".
+// With several replicas, only the one holding the sweeper's lease sweeps
+// (distributed_lock.rs); each release is fenced with the lease's token, so
+// a sweeper that stalled past its lease can't release after the next one
+// took over.
+//
+// METRICS:
+// - inventory_reservations_expired_total{sku}: expired reservation lines
+//   released
+//
+// LEARNING NOTES:
+// - A holding is a `reservations` row with units still held
+//   (held_quantity > 0); it expires 24 hours after it was reserved and
+//   ends with status "expired"
+// - Expiring takes the same per-order lock as cancel-by-order, so an order
+//   cancelled while being swept is released once, not twice
+// - Expired releases show up as reservation.released webhook events
+// =============================================================================
+
+use anyhow::Result;
+use chrono::Utc;
+use redis::aio::ConnectionManager;
+use std::time::Duration;
+
+use crate::db::Database;
+use crate::distributed_lock::{DistributedLock, StaleLease};
+use crate::fast_reserve::FastReserve;
+use crate::item_cache;
+use crate::list_cache;
+use crate::metrics;
+
+/// How long a reservation holds stock
+pub const RESERVATION_TTL_HOURS: i64 = 24;
+
+/// Most expired holdings released per sweep; the rest wait for the next one
+const SWEEP_BATCH_SIZE: i64 = 500;
+
+/// Hold time of a reservation, as used for `expires_at`
+pub fn reservation_ttl() -> chrono::Duration {
+    chrono::Duration::hours(RESERVATION_TTL_HOURS)
+}
+
+/// Release expired reservations every `interval`, forever
+pub async fn run_sweeper(
+    db: Database,
+    redis: ConnectionManager,
+    fast_reserve: Option<FastReserve>,
+    interval: Duration,
+) -> Result<()> {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let lock = DistributedLock::periodic(redis.clone(), "reservation-expiry-sweeper", interval);
+
+    loop {
+        ticker.tick().await;
+        let Some(lease) = lock.acquire().await else {
+            continue;
+        };
+
+        let cutoff = Utc::now() - reservation_ttl();
+        let expired = db.expired_reservations(cutoff, SWEEP_BATCH_SIZE).await?;
+        let mut released = 0;
+
+        for holding in &expired {
+            // One bad row (e.g. `reserved` drifted below the holding) must
+            // not stop the others from being released
+            let quantity = match db.expire_reservation(holding, cutoff, lease).await {
+                Ok(Some(quantity)) => quantity,
+                Ok(None) => continue,
+                // A newer holder sweeps now; leave the rest to it
+                Err(e) if e.is::<StaleLease>() => {
+                    tracing::warn!(error = %e, "Sweeper lease taken over, stopping the sweep");
+                    if let Some(stale) = e.downcast_ref::<StaleLease>() {
+                        lock.resync(stale).await;
+                    }
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        order_id = %holding.order_id,
+                        sku = %holding.sku,
+                        error = %e,
+                        "Failed to expire reservation"
+                    );
+                    continue;
+                }
+            };
+
+            item_cache::invalidate(&redis, &holding.sku).await;
+            if let Some(fast) = &fast_reserve {
+                fast.invalidate(&holding.sku).await;
+            }
+
+            metrics::record_reservation_expired(&holding.sku);
+            tracing::info!(
+                order_id = %holding.order_id,
+                sku = %holding.sku,
+                quantity,
+                fencing_token = lease.token,
+                "Expired reservation released"
+            );
+            released += 1;
+        }
+
+        if released > 0 {
+            list_cache::invalidate(&redis).await;
+        }
+    }
+}
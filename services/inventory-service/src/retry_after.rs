@@ -0,0 +1,78 @@
+// =============================================================================
+// RETRY-AFTER MODULE
+// =============================================================================
+// Makes sure every 429 (Too Many Requests) and 503 (Service Unavailable)
+// response tells the client when it is worth trying again.
+//
+// LEARNING NOTES:
+// - Retry-After is a standard HTTP header (RFC 9110) in seconds or HTTP-date
+// - Without it, clients tend to retry immediately and make overload worse
+// - Code that knows a precise wait (rate limiter, readiness) sets the header
+//   itself; this middleware only fills in a default when nobody did
+// =============================================================================
+
+use axum::{
+    extract::{Request, State},
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::AppState;
+
+/// Set the Retry-After header (in whole seconds, minimum 1).
+pub fn set_retry_after(response: &mut Response, secs: u64) {
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(secs.max(1)));
+}
+
+/// Whether a status code means "back off and retry later"
+pub fn is_throttling_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE
+}
+
+// =============================================================================
+// MIDDLEWARE
+// =============================================================================
+/// Add a default Retry-After to 429/503 responses that don't carry one.
+///
+/// This covers responses produced outside our handlers, e.g. by Tower
+/// layers, so no throttling response ever leaves without the header.
+pub async fn ensure_retry_after(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+
+    if is_throttling_status(response.status()) && !response.headers().contains_key(RETRY_AFTER) {
+        set_retry_after(&mut response, state.config.retry_after_secs);
+    }
+
+    response
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+
+    #[test]
+    fn test_set_retry_after_has_floor_of_one_second() {
+        let mut response = StatusCode::SERVICE_UNAVAILABLE.into_response();
+        set_retry_after(&mut response, 0);
+        assert_eq!(response.headers()[RETRY_AFTER], "1");
+    }
+
+    #[test]
+    fn test_is_throttling_status() {
+        assert!(is_throttling_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_throttling_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_throttling_status(StatusCode::GATEWAY_TIMEOUT));
+    }
+}
@@ -0,0 +1,120 @@
+// =============================================================================
+// TRACE SAMPLING MODULE
+// =============================================================================
+// Decides which requests get a request span (trace) and which are dropped.
+//
+// WHY PER-ROUTE SAMPLING:
+// Health checks and metrics scrapes produce most of the traffic and almost
+// none of the interesting traces. Reservations are rare but matter. A single
+// global rate either drowns the backend in noise or loses the traces you
+// need, so routes can override the default rate.
+//
+// CONFIGURATION:
+//   TRACE_SAMPLE_RATE=0.1
+//   TRACE_SAMPLE_OVERRIDES="/api/v1/inventory/reserve=1.0,/health=0"
+//
+// LEARNING NOTES:
+// - Overrides match the route template (e.g. /api/v1/inventory/:sku), not
+//   the concrete URL, so one rule covers every SKU
+// - Rates are probabilities: 1.0 = always, 0 = never
+// =============================================================================
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+
+// -----------------------------------------------------------------------------
+// SAMPLING RULES
+// -----------------------------------------------------------------------------
+/// Sampling rate per route with a default for everything else
+#[derive(Debug, Clone)]
+pub struct TraceSampling {
+    /// Rate for routes without an override
+    pub default_rate: f64,
+
+    /// Route template → rate
+    pub overrides: HashMap<String, f64>,
+}
+
+impl TraceSampling {
+    /// Build sampling rules from the default rate and an override list.
+    ///
+    /// # Arguments
+    /// * `default_rate` - Rate in [0, 1] for routes without an override
+    /// * `overrides` - Comma-separated `route=rate` pairs (may be empty)
+    pub fn parse(default_rate: f64, overrides: &str) -> Result<Self> {
+        check_rate(default_rate).context("Invalid TRACE_SAMPLE_RATE")?;
+
+        let mut parsed = HashMap::new();
+        for pair in overrides.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (route, rate) = pair
+                .rsplit_once('=')
+                .with_context(|| format!("Expected route=rate, got '{}'", pair))?;
+            let rate: f64 = rate
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid sample rate in '{}'", pair))?;
+            check_rate(rate).with_context(|| format!("Invalid sample rate in '{}'", pair))?;
+            parsed.insert(route.trim().to_string(), rate);
+        }
+
+        Ok(Self {
+            default_rate,
+            overrides: parsed,
+        })
+    }
+
+    /// Sampling rate that applies to a route template
+    pub fn rate_for(&self, route: &str) -> f64 {
+        self.overrides
+            .get(route)
+            .copied()
+            .unwrap_or(self.default_rate)
+    }
+
+    /// Make a sampling decision for one request
+    pub fn should_sample(&self, route: &str) -> bool {
+        let rate = self.rate_for(route);
+        if rate >= 1.0 {
+            true
+        } else if rate <= 0.0 {
+            false
+        } else {
+            rand::random::<f64>() < rate
+        }
+    }
+}
+
+fn check_rate(rate: f64) -> Result<()> {
+    if !(0.0..=1.0).contains(&rate) {
+        bail!("sample rate must be between 0 and 1, got {}", rate);
+    }
+    Ok(())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_overrides() {
+        let sampling =
+            TraceSampling::parse(0.1, "/api/v1/inventory/reserve=1.0, /health=0").unwrap();
+
+        assert_eq!(sampling.rate_for("/api/v1/inventory/reserve"), 1.0);
+        assert_eq!(sampling.rate_for("/health"), 0.0);
+        assert_eq!(sampling.rate_for("/api/v1/inventory"), 0.1);
+        assert!(sampling.should_sample("/api/v1/inventory/reserve"));
+        assert!(!sampling.should_sample("/health"));
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_rates() {
+        assert!(TraceSampling::parse(1.5, "").is_err());
+        assert!(TraceSampling::parse(1.0, "/health=abc").is_err());
+        assert!(TraceSampling::parse(1.0, "/health").is_err());
+        assert!(TraceSampling::parse(1.0, "/health=-1").is_err());
+    }
+}
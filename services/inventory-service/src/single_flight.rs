@@ -0,0 +1,127 @@
+// =============================================================================
+// SINGLE FLIGHT MODULE
+// =============================================================================
+// Request coalescing: while a fetch for a key is running, callers asking for
+// the same key wait for its result instead of starting their own.
+//
+// WHY:
+// When a hot SKU's cache entry expires, every request for it misses at
+// once. Without coalescing each of them queries Postgres (a cache
+// stampede); with it one query runs and the rest share its answer.
+//
+// HOW:
+// - The first caller for a key starts the fetch and parks it, as a shared
+//   future, in a DashMap keyed by the key
+// - Later callers find it there and await the same future
+// - The fetch removes its entry when it finishes, so the next miss fetches
+//   fresh data instead of reusing an old answer
+//
+// LEARNING NOTES:
+// - This works inside one process. item_cache.rs adds an optional Redis
+//   lock (ITEM_CACHE_FILL_LOCK) so replicas don't each refill the same
+//   entry either.
+// - A caller that gives up (client gone, deadline) doesn't cancel the
+//   fetch for the others; whoever still waits keeps driving it
+// - Results must be Clone, since every waiter gets its own copy
+// =============================================================================
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use std::future::Future;
+use std::sync::Arc;
+
+/// Fetches in flight, by key
+pub struct SingleFlight<T> {
+    pending: Arc<DashMap<String, Shared<BoxFuture<'static, T>>>>,
+}
+
+impl<T> Clone for SingleFlight<T> {
+    fn clone(&self) -> Self {
+        Self {
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+impl<T> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self {
+            pending: Arc::default(),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> SingleFlight<T> {
+    /// Run `fetch` for `key`, or wait for the run already in flight
+    ///
+    /// # Returns
+    /// The result, and whether it came from another caller's run
+    pub async fn run<F, Fut>(&self, key: &str, fetch: F) -> (T, bool)
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let (flight, joined) = match self.pending.entry(key.to_string()) {
+            Entry::Occupied(entry) => (entry.get().clone(), true),
+            Entry::Vacant(entry) => {
+                let (pending, key, fetch) = (self.pending.clone(), key.to_string(), fetch());
+                let flight = async move {
+                    let result = fetch.await;
+                    pending.remove(&key);
+                    result
+                }
+                .boxed()
+                .shared();
+                entry.insert(flight.clone());
+                (flight, false)
+            }
+        };
+
+        (flight.await, joined)
+    }
+
+    /// Keys with a fetch in flight
+    pub fn in_flight(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_callers_share_one_fetch() {
+        let flights = SingleFlight::<usize>::default();
+        let fetches = Arc::new(AtomicUsize::new(0));
+
+        let fetch = || {
+            let fetches = fetches.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                fetches.fetch_add(1, Ordering::SeqCst) + 1
+            }
+        };
+        let (a, b, c) = tokio::join!(
+            flights.run("SKU-1", fetch),
+            flights.run("SKU-1", fetch),
+            flights.run("SKU-2", fetch),
+        );
+
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        assert_eq!(a.0, b.0);
+        assert!(!a.1 && b.1 && !c.1);
+        assert_eq!(flights.in_flight(), 0);
+
+        // Finished fetches aren't reused
+        let (again, joined) = flights.run("SKU-1", fetch).await;
+        assert_eq!(again, 3);
+        assert!(!joined);
+    }
+}
@@ -0,0 +1,123 @@
+// =============================================================================
+// SNAPSHOTS MODULE
+// =============================================================================
+// Point-in-time copies of stock (quantity, reserved, unit price per SKU), so
+// lab scenarios and real stock takes can be compared before/after in one
+// call.
+//
+// ENDPOINTS:
+// - POST /api/v1/snapshots                      {label}   take a snapshot
+// - GET  /api/v1/snapshots                      newest first
+// - GET  /api/v1/snapshots/diff?from=3&to=5     per-SKU deltas
+//
+// `from` and `to` are snapshot IDs or RFC 3339 timestamps; a timestamp
+// means the newest snapshot taken at or before it. Without `to` (or with
+// `to=now`) the comparison is against current stock.
+//
+// LEARNING NOTES:
+// - A snapshot is one INSERT ... SELECT, so it is consistent even while
+//   reservations keep coming in
+// - Value uses each side's own unit price, so a price change alone shows
+//   up as a value delta with no quantity delta
+// =============================================================================
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{DiffTotals, SkuDelta};
+
+/// Most snapshots returned by the list endpoint
+pub const LIST_LIMIT: i64 = 100;
+
+/// Longest snapshot label
+pub const MAX_LABEL_LEN: usize = 255;
+
+/// How a diff side names its snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotRef {
+    /// Snapshot ID
+    Id(i64),
+    /// Newest snapshot taken at or before this time
+    At(DateTime<Utc>),
+}
+
+impl SnapshotRef {
+    /// Parse a snapshot ID or an RFC 3339 timestamp
+    pub fn parse(value: &str) -> Result<Self, String> {
+        if let Ok(id) = value.parse::<i64>() {
+            return Ok(Self::Id(id));
+        }
+        DateTime::parse_from_rfc3339(value)
+            .map(|at| Self::At(at.with_timezone(&Utc)))
+            .map_err(|_| {
+                format!(
+                    "Invalid snapshot '{}', expected an ID or an RFC 3339 timestamp",
+                    value
+                )
+            })
+    }
+
+    /// Parse the `to` side, where a missing value or "now" means current stock
+    pub fn parse_to(value: Option<&str>) -> Result<Option<Self>, String> {
+        match value {
+            None | Some("") | Some("now") => Ok(None),
+            Some(value) => Self::parse(value).map(Some),
+        }
+    }
+}
+
+/// Sum the per-SKU deltas
+pub fn totals(items: &[SkuDelta]) -> DiffTotals {
+    items.iter().fold(DiffTotals::default(), |mut totals, item| {
+        totals.quantity_delta += i64::from(item.quantity_delta);
+        totals.reserved_delta += i64::from(item.reserved_delta);
+        totals.value_delta += item.value_delta;
+        totals
+    })
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_snapshot_ref() {
+        assert_eq!(SnapshotRef::parse("42"), Ok(SnapshotRef::Id(42)));
+        assert_eq!(
+            SnapshotRef::parse("2024-01-15T10:30:00+07:00"),
+            Ok(SnapshotRef::At(Utc.with_ymd_and_hms(2024, 1, 15, 3, 30, 0).unwrap()))
+        );
+        assert!(SnapshotRef::parse("yesterday").is_err());
+
+        assert_eq!(SnapshotRef::parse_to(None), Ok(None));
+        assert_eq!(SnapshotRef::parse_to(Some("now")), Ok(None));
+        assert_eq!(SnapshotRef::parse_to(Some("7")), Ok(Some(SnapshotRef::Id(7))));
+    }
+
+    #[test]
+    fn test_totals() {
+        let delta = |sku: &str, quantity_delta: i32, value_delta: f64| SkuDelta {
+            sku: sku.to_string(),
+            quantity_before: 10,
+            quantity_after: 10 + quantity_delta,
+            quantity_delta,
+            reserved_before: 0,
+            reserved_after: 1,
+            reserved_delta: 1,
+            value_delta,
+        };
+
+        let totals = totals(&[delta("A", -5, -50.0), delta("B", 2, 7.5)]);
+        assert_eq!(
+            totals,
+            DiffTotals {
+                quantity_delta: -3,
+                reserved_delta: 2,
+                value_delta: -42.5,
+            }
+        );
+    }
+}
@@ -0,0 +1,359 @@
+// =============================================================================
+// STARTUP MODULE
+// =============================================================================
+// Fail-fast checks at boot, so a misconfigured deployment stops with a list
+// of what to fix instead of failing one request (or one job) at a time.
+//
+// WHAT RUNS (in main, before anything connects or listens):
+// 1. validate(): the whole configuration at once
+//    - URLs parse (DATABASE_URL, REDIS_URL, webhook, audit, remote write)
+//    - host:port values parse (AUDIT_SYSLOG_ADDR, KAFKA_BROKERS)
+//    - PORT and GRPC_PORT differ and are free
+//    - referenced files and directories exist (capture file's directory)
+//    - conflicting settings (e.g. SUPERVISOR_BACKOFF_BASE_MS above the max)
+//    Every problem is reported in one error; anything merely suspicious
+//    (e.g. REPLAY_DIR missing) is logged as a warning.
+// 2. preflight(): optional targets (Kafka brokers, webhook and collector
+//    hosts, read replicas) get a TCP connect within
+//    STARTUP_CHECK_TIMEOUT_SECS (default 5); if unreachable, that's a
+//    warning. Postgres and Redis aren't checked here: they are connected
+//    with retries while /startup reports progress (startup_probe.rs).
+// 3. log_summary(): one "Startup summary" line listing every subsystem and
+//    whether it is on, right before the full router starts serving
+//
+// LEARNING NOTES:
+// - Messages name the environment variable to fix; values holding
+//   credentials (DATABASE_URL, REDIS_URL) are never printed
+// - The port check binds and releases each port; something could still
+//   grab it in between, but a port that's already taken is the usual case
+// =============================================================================
+
+use anyhow::Result;
+use reqwest::Url;
+use std::net::TcpListener;
+use std::path::Path;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::capture::CaptureTarget;
+use crate::config::Config;
+use crate::ids::IdFormat;
+
+/// What validation found
+#[derive(Debug, Default)]
+struct Findings {
+    /// Problems that stop startup
+    errors: Vec<String>,
+    /// Suspicious settings, logged as warnings
+    warnings: Vec<String>,
+}
+
+// =============================================================================
+// VALIDATION
+// =============================================================================
+/// Check the whole configuration, returning every problem in one error
+///
+/// # Arguments
+/// * `serving` - Whether the service will listen (false for `migrate`);
+///   ports are only checked when it will
+pub fn validate(config: &Config, serving: bool) -> Result<()> {
+    let mut findings = check_config(config);
+    if serving {
+        findings.errors.extend(check_ports(config));
+    }
+
+    for warning in &findings.warnings {
+        warn!(warning = %warning, "Configuration warning");
+    }
+    if !findings.errors.is_empty() {
+        anyhow::bail!(
+            "Invalid configuration ({} problem(s)):\n  - {}",
+            findings.errors.len(),
+            findings.errors.join("\n  - ")
+        );
+    }
+    Ok(())
+}
+
+/// Everything that can be checked without touching the network
+fn check_config(config: &Config) -> Findings {
+    let mut findings = Findings::default();
+    let errors = &mut findings.errors;
+
+    check_url(errors, "DATABASE_URL", &config.database_url, &["postgres", "postgresql"]);
+    for url in &config.database_replica_urls {
+        check_url(errors, "DATABASE_REPLICA_URLS", url, &["postgres", "postgresql"]);
+    }
+    check_url(errors, "REDIS_URL", &config.redis_url, &["redis", "rediss", "unix", "redis+unix"]);
+    let http_targets = [
+        ("AUDIT_HTTP_URL", &config.audit_http_url),
+        ("STOCK_EVENTS_WEBHOOK_URL", &config.stock_events_webhook_url),
+    ];
+    for (name, url) in http_targets {
+        if let Some(url) = url {
+            check_url(errors, name, url, &["http", "https"]);
+        }
+    }
+    if let Some(target) = &config.low_stock_alerts {
+        check_url(errors, "LOW_STOCK_ALERT_WEBHOOK_URL", &target.url, &["http", "https"]);
+    }
+    if let Some(target) = &config.remote_write {
+        check_url(errors, "REMOTE_WRITE_URL", &target.url, &["http", "https"]);
+    }
+
+    if let Some(addr) = &config.audit_syslog_addr {
+        check_host_port(errors, "AUDIT_SYSLOG_ADDR", addr);
+    }
+    if let Some(kafka) = &config.kafka {
+        for broker in kafka.brokers.split(',') {
+            check_host_port(errors, "KAFKA_BROKERS", broker.trim());
+        }
+    }
+
+    if config.grpc_enabled && config.grpc_port == config.port {
+        errors.push(format!(
+            "GRPC_PORT and PORT are both {}; give the gRPC API its own port or set GRPC_ENABLED=false",
+            config.port
+        ));
+    }
+    if let Some(admin_port) = config.admin_port {
+        if admin_port == config.port {
+            errors.push(format!(
+                "ADMIN_PORT and PORT are both {}; give the admin endpoints their own port or unset ADMIN_PORT",
+                admin_port
+            ));
+        }
+        if config.grpc_enabled && admin_port == config.grpc_port {
+            errors.push(format!("ADMIN_PORT and GRPC_PORT are both {}", admin_port));
+        }
+    }
+    if config.supervisor_backoff_base_ms > config.supervisor_backoff_max_ms {
+        errors.push(format!(
+            "SUPERVISOR_BACKOFF_BASE_MS ({}) is above SUPERVISOR_BACKOFF_MAX_MS ({})",
+            config.supervisor_backoff_base_ms, config.supervisor_backoff_max_ms
+        ));
+    }
+    if let Some(CaptureTarget::File(path)) = &config.capture {
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+        if dir.is_some_and(|dir| !dir.is_dir()) {
+            errors.push(format!(
+                "CAPTURE_FILE {} is in a directory that doesn't exist",
+                path.display()
+            ));
+        }
+    }
+
+    if let Some(tls) = &config.tls {
+        for (name, path) in tls.files() {
+            if !path.is_file() {
+                errors.push(format!("{} {} doesn't exist", name, path.display()));
+            }
+        }
+    }
+
+    if !Path::new(&config.replay_dir).is_dir() {
+        findings.warnings.push(format!(
+            "REPLAY_DIR {} doesn't exist; traffic replay will find no logs",
+            config.replay_dir
+        ));
+    }
+    if config.snowflake_node_id != 0 && config.id_format != IdFormat::Snowflake {
+        findings.warnings.push(format!(
+            "SNOWFLAKE_NODE_ID is set but ID_FORMAT is {}; it has no effect",
+            config.id_format.as_str()
+        ));
+    }
+
+    findings
+}
+
+/// Check that `value` is a URL with one of the `schemes`. The value itself
+/// isn't printed: it may hold a password.
+fn check_url(errors: &mut Vec<String>, name: &str, value: &str, schemes: &[&str]) {
+    match Url::parse(value) {
+        Ok(url) if schemes.contains(&url.scheme()) => {}
+        Ok(url) => errors.push(format!(
+            "{} has scheme '{}', expected {}",
+            name,
+            url.scheme(),
+            schemes.join(" or ")
+        )),
+        Err(e) => errors.push(format!("{} is not a valid URL: {}", name, e)),
+    }
+}
+
+/// Check that `value` is `host:port`
+fn check_host_port(errors: &mut Vec<String>, name: &str, value: &str) {
+    let valid = value
+        .rsplit_once(':')
+        .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+    if !valid {
+        errors.push(format!("{} must be host:port, got '{}'", name, value));
+    }
+}
+
+/// Check that the ports we are about to listen on are free
+fn check_ports(config: &Config) -> Vec<String> {
+    let mut ports = vec![("PORT", config.port)];
+    if config.grpc_enabled && config.grpc_port != config.port {
+        ports.push(("GRPC_PORT", config.grpc_port));
+    }
+    if let Some(admin_port) = config.admin_port.filter(|port| *port != config.port) {
+        ports.push(("ADMIN_PORT", admin_port));
+    }
+
+    ports
+        .into_iter()
+        .filter_map(|(name, port)| {
+            TcpListener::bind(("0.0.0.0", port))
+                .err()
+                .map(|e| format!("{} {} can't be bound: {}", name, port, e))
+        })
+        .collect()
+}
+
+// =============================================================================
+// PREFLIGHT
+// =============================================================================
+/// Reach every optional dependency once, warning about the unreachable ones
+pub async fn preflight(config: &Config) {
+    let timeout = Duration::from_secs(config.startup_check_timeout_secs.max(1));
+
+    let mut unreachable = 0;
+    for (name, addr) in optional_targets(config) {
+        if let Err(e) = within(timeout, check_tcp(&addr)).await {
+            unreachable += 1;
+            warn!(
+                dependency = name,
+                address = %addr,
+                error = %format!("{:#}", e),
+                "Optional dependency unreachable"
+            );
+        }
+    }
+
+    info!(timeout_secs = timeout.as_secs(), unreachable, "Preflight checks done");
+}
+
+/// Run a check, failing it after `timeout`
+async fn within<F>(timeout: Duration, check: F) -> Result<()>
+where
+    F: std::future::Future<Output = Result<()>>,
+{
+    tokio::time::timeout(timeout, check)
+        .await
+        .map_err(|_| anyhow::anyhow!("no answer within {}s", timeout.as_secs()))?
+}
+
+async fn check_tcp(addr: &str) -> Result<()> {
+    tokio::net::TcpStream::connect(addr).await?;
+    Ok(())
+}
+
+/// host:port of every optional TCP target
+fn optional_targets(config: &Config) -> Vec<(&'static str, String)> {
+    let mut targets = Vec::new();
+    if let Some(kafka) = &config.kafka {
+        for broker in kafka.brokers.split(',') {
+            targets.push(("KAFKA_BROKERS", broker.trim().to_string()));
+        }
+    }
+    let urls = [
+        ("AUDIT_HTTP_URL", config.audit_http_url.as_deref()),
+        ("STOCK_EVENTS_WEBHOOK_URL", config.stock_events_webhook_url.as_deref()),
+        ("REMOTE_WRITE_URL", config.remote_write.as_ref().map(|t| t.url.as_str())),
+        ("LOW_STOCK_ALERT_WEBHOOK_URL", config.low_stock_alerts.as_ref().map(|t| t.url.as_str())),
+    ];
+    for (name, url) in urls {
+        let Some(url) = url.and_then(|url| Url::parse(url).ok()) else {
+            continue;
+        };
+        if let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) {
+            targets.push((name, format!("{}:{}", host, port)));
+        }
+    }
+    // A replica that is down only costs failovers to the primary
+    for url in config.database_replica_urls.iter().filter_map(|url| Url::parse(url).ok()) {
+        if let Some(host) = url.host_str() {
+            let port = url.port().unwrap_or(5432);
+            targets.push(("DATABASE_REPLICA_URLS", format!("{}:{}", host, port)));
+        }
+    }
+    targets
+}
+
+// =============================================================================
+// SUMMARY
+// =============================================================================
+/// Log which subsystems are on, in one line
+pub fn log_summary(config: &Config) {
+    let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+    let grpc = if config.grpc_enabled {
+        config.grpc_port.to_string()
+    } else {
+        "off".to_string()
+    };
+    let disabled_endpoints = config.features.disabled_names().join(",");
+
+    info!(
+        port = config.port,
+        tls = on_off(config.tls.is_some()),
+        grpc = %grpc,
+        admin_port = %config.admin_port.map_or("off".to_string(), |port| port.to_string()),
+        db_replicas = config.database_replica_urls.len(),
+        auto_migrate = config.auto_migrate,
+        reserve_strategy = config.reserve_strategy.as_str(),
+        reserve_fast_path = on_off(config.reserve_fast_path),
+        reserve_queue = on_off(config.reserve_queue_enabled),
+        id_format = config.id_format.as_str(),
+        public_mode = on_off(config.public_mode.is_some()),
+        rate_limit = on_off(config.rate_limit.is_some()),
+        max_concurrent_requests = config.load_shed.map_or(0, |limit| limit.max_in_flight),
+        load_gen_rps = config.load_gen.map_or(0.0, |load_gen| load_gen.rps),
+        usage_metering = on_off(config.usage_metering),
+        dynamic_thresholds = on_off(config.dynamic_thresholds.is_some()),
+        cache_warm = config.cache_warm.map_or("off", |strategy| strategy.as_str()),
+        capture = on_off(config.capture.is_some()),
+        kafka = on_off(config.kafka.is_some()),
+        stock_events_webhook = on_off(config.stock_events_webhook_url.is_some()),
+        audit_syslog = on_off(config.audit_syslog_addr.is_some()),
+        audit_http = on_off(config.audit_http_url.is_some()),
+        remote_write = on_off(config.remote_write.is_some()),
+        disabled_endpoints = %disabled_endpoints,
+        "Startup summary"
+    );
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_url() {
+        let mut errors = Vec::new();
+        check_url(&mut errors, "DATABASE_URL", "postgres://u:p@db:5432/x", &["postgres"]);
+        assert!(errors.is_empty());
+
+        check_url(&mut errors, "DATABASE_URL", "mysql://u:secret@db/x", &["postgres"]);
+        check_url(&mut errors, "REDIS_URL", "not a url", &["redis"]);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("scheme 'mysql'"));
+        assert!(errors.iter().all(|e| !e.contains("secret")));
+    }
+
+    #[test]
+    fn test_check_host_port() {
+        let mut errors = Vec::new();
+        check_host_port(&mut errors, "KAFKA_BROKERS", "kafka:9092");
+        check_host_port(&mut errors, "KAFKA_BROKERS", "[::1]:9092");
+        assert!(errors.is_empty());
+
+        check_host_port(&mut errors, "KAFKA_BROKERS", "kafka");
+        check_host_port(&mut errors, "KAFKA_BROKERS", ":9092");
+        check_host_port(&mut errors, "AUDIT_SYSLOG_ADDR", "syslog:port");
+        assert_eq!(errors.len(), 3);
+    }
+}
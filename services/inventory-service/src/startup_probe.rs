@@ -0,0 +1,338 @@
+// =============================================================================
+// STARTUP PROBE MODULE
+// =============================================================================
+// Keeps the pod alive while Postgres or Redis are still coming up, instead
+// of exiting on the first refused connection (and crash-looping with them
+// after a cluster restart).
+//
+// HOW:
+// - The port is bound before any dependency is reached. Until the service
+//   is fully built, a small router answers there:
+//     /health    200, the process is alive
+//     /startup   503 with the progress below, 200 once started
+//     /ready     503, no traffic yet
+//     /metrics   what has been recorded so far
+// - Postgres and Redis are connected with retries (connect_with_retry):
+//   each attempt gets STARTUP_CHECK_TIMEOUT_SECS, failures back off from
+//   0.5s up to 10s, and after STARTUP_CONNECT_DEADLINE_SECS (default 300,
+//   0 = no limit) startup gives up and the process exits
+// - Once everything is built the small router stops and the full one takes
+//   over the same listening socket; /startup keeps answering 200
+// - With TLS configured (tls.rs) both routers speak HTTPS, so probes with
+//   `scheme: HTTPS` work from the first second
+// - With ADMIN_PORT set, that port is bound at boot instead (plain HTTP,
+//   like the admin router that takes it over) and PORT opens once the API
+//   is ready
+//
+// STEPS (GET /startup):
+//   postgres → migrations → redis → app
+// Each is pending, running (with attempts and the last error) or done.
+//
+// LEARNING NOTES:
+// - Point the Kubernetes startupProbe at /startup and the livenessProbe at
+//   /health: the liveness probe passes while dependencies converge, and
+//   the startup probe decides how long that may take
+// - Migrations and the other boot steps are not retried; a failure there
+//   still stops the process
+// - Nothing listens on GRPC_PORT until startup is done
+// =============================================================================
+
+use anyhow::Result;
+use axum_server::tls_rustls::RustlsConfig;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use metrics_exporter_prometheus::PrometheusHandle;
+use sqlx::Connection;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::handlers;
+use crate::models::{StartupResponse, StartupStep};
+use crate::supervisor::Backoff;
+
+/// Boot steps, in order
+pub const STEPS: [&str; 4] = ["postgres", "migrations", "redis", "app"];
+
+/// Delays between connection attempts
+const RETRY_BACKOFF: Backoff = Backoff {
+    base: Duration::from_millis(500),
+    max: Duration::from_secs(10),
+};
+
+// -----------------------------------------------------------------------------
+// PROGRESS
+// -----------------------------------------------------------------------------
+/// How far startup has come, shared by both routers
+#[derive(Clone)]
+pub struct StartupProgress {
+    steps: Arc<RwLock<Vec<StartupStep>>>,
+    started_at: Instant,
+}
+
+impl StartupProgress {
+    pub fn new() -> Self {
+        let steps = STEPS
+            .iter()
+            .map(|name| StartupStep {
+                name: name.to_string(),
+                state: "pending".to_string(),
+                attempts: 0,
+                last_error: None,
+            })
+            .collect();
+        Self {
+            steps: Arc::new(RwLock::new(steps)),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Mark a step running and count an attempt
+    pub fn begin(&self, name: &str) {
+        self.update(name, |step| {
+            step.state = "running".to_string();
+            step.attempts += 1;
+        });
+    }
+
+    /// Record why an attempt of a step failed
+    fn failed(&self, name: &str, error: String) {
+        self.update(name, |step| step.last_error = Some(error));
+    }
+
+    pub fn complete(&self, name: &str) {
+        self.update(name, |step| {
+            step.state = "done".to_string();
+            step.last_error = None;
+        });
+    }
+
+    /// The /startup answer: 200 once started, 503 before
+    pub fn respond(&self) -> (StatusCode, Json<StartupResponse>) {
+        let steps = self.snapshot();
+        let started = steps.iter().all(|step| step.state == "done");
+        let status = if started {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+        (
+            status,
+            Json(StartupResponse {
+                status: if started { "started" } else { "starting" }.to_string(),
+                elapsed_secs: self.started_at.elapsed().as_secs_f64(),
+                steps,
+            }),
+        )
+    }
+
+    fn snapshot(&self) -> Vec<StartupStep> {
+        self.steps.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn update(&self, name: &str, apply: impl FnOnce(&mut StartupStep)) {
+        let mut steps = self.steps.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(step) = steps.iter_mut().find(|step| step.name == name) {
+            apply(step);
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// RETRIES
+// -----------------------------------------------------------------------------
+/// Run `connect` until it succeeds, backing off between attempts
+///
+/// # Arguments
+/// * `step` - The step shown under /startup
+/// * `attempt_timeout` - Budget of one attempt
+/// * `deadline` - Give up after this long (None = never)
+pub async fn connect_with_retry<T, F, Fut>(
+    progress: &StartupProgress,
+    step: &'static str,
+    attempt_timeout: Duration,
+    deadline: Option<Duration>,
+    mut connect: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let started = Instant::now();
+    let mut attempt = 0;
+
+    loop {
+        progress.begin(step);
+        let error = match tokio::time::timeout(attempt_timeout, connect()).await {
+            Ok(Ok(value)) => {
+                progress.complete(step);
+                return Ok(value);
+            }
+            Ok(Err(e)) => format!("{:#}", e),
+            Err(_) => format!("no answer within {}s", attempt_timeout.as_secs()),
+        };
+
+        if deadline.is_some_and(|deadline| started.elapsed() >= deadline) {
+            anyhow::bail!(
+                "Gave up on {} after {} attempt(s) (STARTUP_CONNECT_DEADLINE_SECS): {}",
+                step,
+                attempt + 1,
+                error
+            );
+        }
+
+        let delay = RETRY_BACKOFF.delay(attempt);
+        warn!(
+            step,
+            attempt = attempt + 1,
+            retry_in_ms = delay.as_millis() as u64,
+            error = %error,
+            "Dependency not reachable yet, retrying"
+        );
+        progress.failed(step, error);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// One plain connection to Postgres. The pool keeps retrying a refused
+/// connection until its acquire timeout and then only reports the timeout;
+/// connecting once first gives /startup the actual error.
+pub async fn ping_postgres(url: &str) -> Result<()> {
+    let mut conn = sqlx::PgConnection::connect(url).await?;
+    sqlx::query("SELECT 1").execute(&mut conn).await?;
+    conn.close().await?;
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// BOOT ROUTER
+// -----------------------------------------------------------------------------
+/// The router that answers while the service is starting
+pub struct BootServer {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Clone)]
+struct BootState {
+    progress: StartupProgress,
+    metrics_handle: PrometheusHandle,
+}
+
+impl BootServer {
+    /// Serve the probes on `listener` until stop() is called, over TLS
+    /// when `tls` is given
+    pub fn start(
+        listener: std::net::TcpListener,
+        tls: Option<RustlsConfig>,
+        progress: StartupProgress,
+        metrics_handle: PrometheusHandle,
+    ) -> Result<Self> {
+        let app = Router::new()
+            .route("/health", get(handlers::health_check))
+            .route("/startup", get(|State(boot): State<BootState>| async move { boot.progress.respond() }))
+            .route("/ready", get(|| async { (StatusCode::SERVICE_UNAVAILABLE, "starting") }))
+            .route(
+                "/metrics",
+                get(|State(boot): State<BootState>| async move { boot.metrics_handle.render() }),
+            )
+            .with_state(BootState {
+                progress,
+                metrics_handle,
+            });
+
+        let (stop, stopped) = oneshot::channel::<()>();
+        let task = match tls {
+            Some(tls) => {
+                let handle = axum_server::Handle::new();
+                let shutdown = handle.clone();
+                tokio::spawn(async move {
+                    stopped.await.ok();
+                    shutdown.graceful_shutdown(None);
+                });
+                let server = axum_server::from_tcp_rustls(listener, tls)
+                    .handle(handle)
+                    .serve(app.into_make_service());
+                tokio::spawn(async move {
+                    if let Err(e) = server.await {
+                        warn!(error = %e, "Startup probe server failed");
+                    }
+                })
+            }
+            None => {
+                let listener = tokio::net::TcpListener::from_std(listener)?;
+                tokio::spawn(async move {
+                    let server = axum::serve(listener, app).with_graceful_shutdown(async {
+                        stopped.await.ok();
+                    });
+                    if let Err(e) = server.await {
+                        warn!(error = %e, "Startup probe server failed");
+                    }
+                })
+            }
+        };
+        info!("Serving startup probes while dependencies connect");
+        Ok(Self { stop, task })
+    }
+
+    /// Stop answering; open connections are finished first
+    pub async fn stop(self) {
+        self.stop.send(()).ok();
+        self.task.await.ok();
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_connect_with_retry() {
+        let progress = StartupProgress::new();
+        let mut calls = 0;
+        let value = connect_with_retry(&progress, "redis", Duration::from_secs(1), None, || {
+            calls += 1;
+            let attempt = calls;
+            async move {
+                if attempt >= 2 {
+                    Ok(attempt)
+                } else {
+                    Err(anyhow::anyhow!("connection refused"))
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(value, 2);
+
+        let (status, Json(body)) = progress.respond();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let redis = body.steps.iter().find(|s| s.name == "redis").unwrap();
+        assert_eq!((redis.state.as_str(), redis.attempts), ("done", 2));
+
+        for step in STEPS {
+            progress.complete(step);
+        }
+        assert_eq!(progress.respond().0, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_connect_gives_up_after_deadline() {
+        let progress = StartupProgress::new();
+        let result: Result<()> = connect_with_retry(
+            &progress,
+            "postgres",
+            Duration::from_secs(1),
+            Some(Duration::ZERO),
+            || async { Err(anyhow::anyhow!("connection refused")) },
+        )
+        .await;
+        assert!(result.unwrap_err().to_string().contains("Gave up on postgres"));
+    }
+}
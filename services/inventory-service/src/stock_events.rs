@@ -0,0 +1,139 @@
+// =============================================================================
+// STOCK EVENTS MODULE
+// =============================================================================
+// "Sold out" and "back in stock" events, separate from low-stock alerts.
+//
+// EVENTS:
+// - stock.out:  available stock (quantity - reserved) dropped to zero
+// - stock.back: available stock went from zero back above it
+//
+// HOW:
+// 1. A Postgres trigger on `products` inserts a row into `stock_events`
+//    whenever available stock crosses zero, in the same transaction as the
+//    change (so rolled-back reservations never emit anything)
+// 2. A supervised job ("stock-event-dispatcher") picks up undelivered
+//    events in order, logs them, counts them in `stock_events_total` and,
+//    if STOCK_EVENTS_WEBHOOK_URL is set, POSTs each one as JSON
+//
+// CONFIGURATION:
+// - STOCK_EVENTS_WEBHOOK_URL: receiver of the events (default: unset, the
+//   events are only logged and counted)
+// - STOCK_EVENTS_INTERVAL_MS: how often the outbox is polled (default: 1000)
+//
+// LEARNING NOTES:
+// - Delivery is at-least-once: an event is marked delivered only after the
+//   webhook answered 2xx. Receivers drop duplicates by `id` (also sent in
+//   the X-Stock-Event-Id header).
+// - A failing webhook stops the job; the supervisor restarts it with
+//   backoff and delivery resumes from the oldest undelivered event
+// - Delivered events are kept for RETENTION_DAYS (30) for the stockout
+//   report (reports.rs), then pruned
+// =============================================================================
+
+use anyhow::{Context, Result};
+use std::time::{Duration, Instant};
+
+use crate::db::Database;
+use crate::http_client::HttpClient;
+use crate::metrics;
+use crate::models::StockEvent;
+
+/// Maximum events handled per poll
+const DISPATCH_BATCH_SIZE: i64 = 100;
+
+/// Days delivered events are kept (also the longest stockout report period)
+pub const RETENTION_DAYS: i32 = 30;
+
+/// Time between prunes of delivered events
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Header carrying the event ID, for receivers that dedupe on headers
+const EVENT_ID_HEADER: &str = "X-Stock-Event-Id";
+
+/// Deliver stock events every `interval`, forever
+pub async fn run_dispatcher(
+    db: Database,
+    http: HttpClient,
+    webhook_url: Option<String>,
+    interval: Duration,
+) -> Result<()> {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut last_prune: Option<Instant> = None;
+
+    loop {
+        ticker.tick().await;
+
+        let events = db.pending_stock_events(DISPATCH_BATCH_SIZE).await?;
+        metrics::set_stock_events_pending(events.len());
+
+        for (delivered, event) in events.iter().enumerate() {
+            if let Some(url) = &webhook_url {
+                deliver(&http, url, event).await?;
+            }
+            db.mark_stock_event_delivered(event.id).await?;
+
+            tracing::info!(
+                event_id = event.id,
+                event_type = %event.event_type,
+                sku = %event.sku,
+                warehouse = %event.warehouse,
+                available = event.available,
+                "Stock event emitted"
+            );
+            metrics::record_stock_event(&event.event_type);
+            metrics::set_stock_events_pending(events.len() - delivered - 1);
+        }
+
+        if last_prune.is_none_or(|at| at.elapsed() >= PRUNE_INTERVAL) {
+            let pruned = db.prune_stock_events(RETENTION_DAYS).await?;
+            if pruned > 0 {
+                tracing::debug!(pruned, "Pruned delivered stock events");
+            }
+            last_prune = Some(Instant::now());
+        }
+    }
+}
+
+/// POST one event to the webhook
+async fn deliver(http: &HttpClient, url: &str, event: &StockEvent) -> Result<()> {
+    let request = http
+        .client()
+        .post(url)
+        .header(EVENT_ID_HEADER, event.id.to_string())
+        .json(event);
+
+    http.send("stock-events", request)
+        .await?
+        .error_for_status()
+        .with_context(|| format!("Stock event webhook rejected event {}", event.id))?;
+
+    Ok(())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_webhook_payload() {
+        let event = StockEvent {
+            id: 17,
+            event_type: "stock.back".to_string(),
+            sku: "SKU-1".to_string(),
+            warehouse: "JKT-1".to_string(),
+            available: 20,
+            occurred_at: chrono::Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "stock.back");
+        assert_eq!(json["id"], 17);
+        assert_eq!(json["available"], 20);
+        assert!(json.get("event_type").is_none());
+    }
+}
@@ -0,0 +1,79 @@
+// =============================================================================
+// STOCK VALUE MODULE
+// =============================================================================
+// What the stock is worth, for "total stock value" dashboard panels.
+//
+// METRICS:
+// - inventory_stock_value{warehouse}: quantity × unit_cost of the units
+//   held at the warehouse (items without a unit_cost count for nothing)
+//
+// HOW:
+// A supervised job ("stock-valuer") sums the value per warehouse every
+// STOCK_VALUE_INTERVAL_SECS (default 60). Units transferred to another
+// warehouse count there, like GET /api/v1/inventory/:sku/warehouses.
+// Set the cost with PUT /api/v1/inventory/:sku/catalog (`unit_cost`); item
+// responses carry `stock_value` for the single SKU.
+//
+// LEARNING NOTES:
+// - The value is at cost; unit_price (the selling price) isn't used
+// - A warehouse that no longer holds anything is set to 0 rather than
+//   left at its last value
+// - Every replica reports the same values; aggregate with
+//   `max by (warehouse)`, not `sum`
+// - Total stock value:
+//   sum(max by (warehouse) (inventory_stock_value))
+// =============================================================================
+
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use crate::db::Database;
+use crate::metrics;
+
+/// Recompute the stock value per warehouse every `interval`, forever
+pub async fn run_valuer(db: Database, interval: Duration) -> Result<()> {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut reported = BTreeSet::new();
+
+    loop {
+        ticker.tick().await;
+
+        let values = db.stock_value_by_warehouse().await?;
+        reported = report(&values, reported);
+    }
+}
+
+/// Set the gauges; warehouses reported last time but missing now go to 0.
+/// Returns the warehouses reported now.
+fn report(values: &[(String, f64)], previous: BTreeSet<String>) -> BTreeSet<String> {
+    let current: BTreeSet<String> = values.iter().map(|(warehouse, _)| warehouse.clone()).collect();
+    for warehouse in previous.difference(&current) {
+        metrics::set_stock_value(warehouse, 0.0);
+    }
+    for (warehouse, value) in values {
+        metrics::set_stock_value(warehouse, *value);
+    }
+    current
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_keeps_current_warehouses() {
+        let first = report(
+            &[("JKT-1".to_string(), 120.0), ("SBY-1".to_string(), 40.5)],
+            BTreeSet::new(),
+        );
+        assert_eq!(first.len(), 2);
+
+        let second = report(&[("JKT-1".to_string(), 100.0)], first);
+        assert_eq!(second, BTreeSet::from(["JKT-1".to_string()]));
+    }
+}
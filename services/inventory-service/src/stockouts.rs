@@ -0,0 +1,109 @@
+// =============================================================================
+// STOCKOUTS MODULE
+// =============================================================================
+// How long SKUs sit at zero available stock, for fill-rate and stockout SLO
+// graphs.
+//
+// METRICS:
+// - inventory_stockout_seconds_total{sku}: time spent without available
+//   stock (quantity - reserved <= 0)
+// - inventory_current_stockouts: SKUs out of stock right now
+//
+// HOW:
+// A supervised job ("stockout-tracker") lists out-of-stock SKUs every
+// STOCKOUT_TRACK_INTERVAL_SECS (default 5). A SKU that was out at the
+// previous sample is charged the time since that sample.
+//
+// LEARNING NOTES:
+// - Durations are accurate to one interval: a SKU that sells out and comes
+//   back between two samples is never seen
+// - Every replica counts the same stockouts; aggregate with
+//   `max by (sku)`, not `sum`
+// - Stockout ratio per SKU:
+//   rate(inventory_stockout_seconds_total[1h])   (0 = always in stock)
+// =============================================================================
+
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+use crate::db::Database;
+use crate::metrics;
+
+/// Out-of-stock SKUs seen at the last sample
+#[derive(Debug, Default)]
+struct Sample {
+    skus: BTreeSet<String>,
+    /// Start of the time not charged yet; advances in whole seconds so the
+    /// fractions carry over to the next sample
+    charged_until: Option<Instant>,
+}
+
+impl Sample {
+    /// Take a new sample; returns the SKUs that were out at the previous
+    /// one and the whole seconds to charge them
+    fn advance(&mut self, skus: BTreeSet<String>, now: Instant) -> (BTreeSet<String>, u64) {
+        let since = *self.charged_until.get_or_insert(now);
+        let seconds = now.saturating_duration_since(since).as_secs();
+        self.charged_until = Some(since + Duration::from_secs(seconds));
+
+        (std::mem::replace(&mut self.skus, skus), seconds)
+    }
+}
+
+/// Sample out-of-stock SKUs every `interval`, forever
+pub async fn run_tracker(db: Database, interval: Duration) -> Result<()> {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut sample = Sample::default();
+
+    loop {
+        ticker.tick().await;
+
+        let skus: BTreeSet<String> = db.out_of_stock_skus().await?.into_iter().collect();
+        metrics::set_current_stockouts(skus.len());
+
+        // Touch new stockouts so their series exist before the first charge
+        for sku in &skus {
+            metrics::record_stockout_seconds(sku, 0);
+        }
+        let (previous, seconds) = sample.advance(skus, Instant::now());
+        for sku in &previous {
+            metrics::record_stockout_seconds(sku, seconds);
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skus(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_sample_charges_previous_stockouts() {
+        let start = Instant::now();
+        let mut sample = Sample::default();
+
+        let (previous, seconds) = sample.advance(skus(&["A"]), start);
+        assert!(previous.is_empty());
+        assert_eq!(seconds, 0);
+
+        // A was out for the whole 5.6s; B only just sold out
+        let at = start + Duration::from_millis(5_600);
+        let (previous, seconds) = sample.advance(skus(&["A", "B"]), at);
+        assert_eq!(previous, skus(&["A"]));
+        assert_eq!(seconds, 5);
+
+        // The 0.6s left over is carried into the next charge
+        let at = start + Duration::from_millis(10_000);
+        let (previous, seconds) = sample.advance(skus(&[]), at);
+        assert_eq!(previous, skus(&["A", "B"]));
+        assert_eq!(seconds, 5);
+    }
+}
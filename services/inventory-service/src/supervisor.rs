@@ -0,0 +1,264 @@
+// =============================================================================
+// SUPERVISOR MODULE
+// =============================================================================
+// This module owns every long-running background worker in the service
+// (sweepers, schedulers, relays, consumers).
+//
+// WHAT THE SUPERVISOR DOES:
+// - Spawns each worker as its own Tokio task
+// - Notices when a worker returns an error, exits, or panics
+// - Restarts it after an exponential backoff (base * 2^n, capped)
+// - Keeps a status table for the admin endpoint and per-task metrics
+//
+// LEARNING NOTES:
+// - A panic inside tokio::spawn doesn't crash the process; it surfaces as a
+//   JoinError when awaiting the JoinHandle. That's how we detect panics.
+//   JoinError::into_panic hands back the payload, which holds the message.
+// - Workers are given as factories (Fn() -> Future) because a finished
+//   future can't be polled again; every restart needs a fresh one.
+// =============================================================================
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use crate::metrics;
+
+// -----------------------------------------------------------------------------
+// TASK STATUS
+// -----------------------------------------------------------------------------
+/// Lifecycle state of a supervised task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    /// Currently executing
+    Running,
+    /// Exited or crashed, waiting before the next restart
+    Backoff,
+}
+
+/// Snapshot of one supervised task, returned by the admin endpoint
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TaskStatus {
+    pub name: String,
+    pub state: TaskState,
+    /// How many times the task has been restarted since boot
+    pub restarts: u64,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_exit_at: Option<DateTime<Utc>>,
+    /// Error or panic message from the most recent exit
+    pub last_error: Option<String>,
+}
+
+// -----------------------------------------------------------------------------
+// BACKOFF POLICY
+// -----------------------------------------------------------------------------
+/// Restart delay settings
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub base: Duration,
+    pub max: Duration,
+}
+
+impl Backoff {
+    /// Delay before restart number `attempt` (0-based): base * 2^attempt, capped at max
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.min(31));
+        self.base.saturating_mul(factor).min(self.max)
+    }
+}
+
+// =============================================================================
+// SUPERVISOR
+// =============================================================================
+/// Owns background workers and their status table.
+///
+/// Cheap to clone: clones share the same status table.
+#[derive(Clone)]
+pub struct Supervisor {
+    backoff: Backoff,
+    tasks: Arc<RwLock<BTreeMap<String, TaskStatus>>>,
+}
+
+impl Supervisor {
+    pub fn new(backoff: Backoff) -> Self {
+        Self {
+            backoff,
+            tasks: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    /// Status of all supervised tasks, sorted by name
+    pub fn statuses(&self) -> Vec<TaskStatus> {
+        self.tasks
+            .read()
+            .expect("supervisor status lock poisoned")
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Start a worker under supervision.
+    ///
+    /// `make_task` is called for the first run and again for every restart.
+    /// A task that ran longer than the maximum backoff is considered healthy,
+    /// so its next failure starts the backoff sequence from the beginning.
+    pub fn spawn<F, Fut>(&self, name: &str, make_task: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let supervisor = self.clone();
+        let name = name.to_string();
+
+        tokio::spawn(async move {
+            let mut attempt: u32 = 0;
+
+            loop {
+                supervisor.mark_running(&name);
+                let started = tokio::time::Instant::now();
+
+                // Run the worker in its own task so a panic becomes a JoinError
+                let outcome = tokio::spawn(make_task()).await;
+                let panicked = matches!(&outcome, Err(join_error) if join_error.is_panic());
+                let error = match outcome {
+                    Ok(Ok(())) => "task exited".to_string(),
+                    Ok(Err(e)) => format!("task failed: {:#}", e),
+                    Err(join_error) if join_error.is_panic() => {
+                        format!("task panicked: {}", panic_message(join_error.into_panic()))
+                    }
+                    Err(join_error) => format!("task aborted: {}", join_error),
+                };
+
+                if started.elapsed() > supervisor.backoff.max {
+                    attempt = 0;
+                }
+                let delay = supervisor.backoff.delay(attempt);
+                attempt = attempt.saturating_add(1);
+
+                if panicked {
+                    tracing::error!(
+                        task = %name,
+                        error = %error,
+                        restart_in_ms = delay.as_millis() as u64,
+                        "Background task panicked, restarting after backoff"
+                    );
+                } else {
+                    tracing::warn!(
+                        task = %name,
+                        error = %error,
+                        restart_in_ms = delay.as_millis() as u64,
+                        "Background task stopped, restarting after backoff"
+                    );
+                }
+                supervisor.mark_backoff(&name, error);
+
+                tokio::time::sleep(delay).await;
+            }
+        });
+    }
+
+    // -------------------------------------------------------------------------
+    // STATUS BOOKKEEPING
+    // -------------------------------------------------------------------------
+
+    fn mark_running(&self, name: &str) {
+        let mut tasks = self.tasks.write().expect("supervisor status lock poisoned");
+        let status = tasks.entry(name.to_string()).or_insert_with(|| TaskStatus {
+            name: name.to_string(),
+            state: TaskState::Running,
+            restarts: 0,
+            last_started_at: None,
+            last_exit_at: None,
+            last_error: None,
+        });
+        if status.last_started_at.is_some() {
+            status.restarts += 1;
+            metrics::record_background_task_restart(name);
+        }
+        status.state = TaskState::Running;
+        status.last_started_at = Some(Utc::now());
+        metrics::set_background_task_up(name, true);
+    }
+
+    fn mark_backoff(&self, name: &str, error: String) {
+        let mut tasks = self.tasks.write().expect("supervisor status lock poisoned");
+        if let Some(status) = tasks.get_mut(name) {
+            status.state = TaskState::Backoff;
+            status.last_exit_at = Some(Utc::now());
+            status.last_error = Some(error);
+        }
+        metrics::set_background_task_up(name, false);
+    }
+}
+
+/// The message a panic was raised with: panic!("literal") carries a &str,
+/// panic!("{}", x) a String, and anything else (panic_any) has no text
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let backoff = Backoff {
+            base: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+        };
+        assert_eq!(backoff.delay(0), Duration::from_millis(100));
+        assert_eq!(backoff.delay(1), Duration::from_millis(200));
+        assert_eq!(backoff.delay(3), Duration::from_millis(800));
+        assert_eq!(backoff.delay(4), Duration::from_secs(1));
+        assert_eq!(backoff.delay(100), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_panicking_task_is_restarted() {
+        let supervisor = Supervisor::new(Backoff {
+            base: Duration::from_millis(1),
+            max: Duration::from_millis(5),
+        });
+        supervisor.spawn("crashy", || async { panic!("boom") });
+
+        // Wait (bounded) for at least one restart to be recorded
+        for _ in 0..200 {
+            if supervisor.statuses().first().is_some_and(|s| s.restarts >= 1) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let statuses = supervisor.statuses();
+        assert_eq!(statuses.len(), 1);
+        assert!(statuses[0].restarts >= 1);
+        assert_eq!(statuses[0].last_error.as_deref(), Some("task panicked: boom"));
+    }
+
+    #[tokio::test]
+    async fn test_panic_message_reads_every_payload() {
+        let sku = "SKU-1";
+        let formatted = tokio::spawn(async move { panic!("no stock row for {}", sku) });
+        let payload = formatted.await.unwrap_err().into_panic();
+        assert_eq!(panic_message(payload), "no stock row for SKU-1");
+
+        let opaque = tokio::spawn(async { std::panic::panic_any(42u32) });
+        let payload = opaque.await.unwrap_err().into_panic();
+        assert_eq!(panic_message(payload), "non-string panic payload");
+    }
+}
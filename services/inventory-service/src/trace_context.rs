@@ -0,0 +1,160 @@
+// =============================================================================
+// TRACE CONTEXT MODULE
+// =============================================================================
+// W3C Trace Context (`traceparent` header) propagation.
+//
+// FORMAT:
+//   traceparent: 00-<trace-id: 32 hex>-<parent-id: 16 hex>-<flags: 2 hex>
+//   Example:     00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01
+//
+// HOW IT'S USED:
+// - Incoming requests: the caller's traceparent is captured in a task-local
+// - Outbound calls: we send the same trace-id with a fresh parent-id, so
+//   Tempo can stitch our downstream calls into the caller's trace
+// - No incoming context (background jobs, direct calls): a new trace-id
+//
+// LEARNING NOTES:
+// - trace-id identifies the whole distributed trace
+// - parent-id identifies the span making the call, so it changes per hop
+// =============================================================================
+
+use axum::{extract::Request, middleware::Next, response::Response};
+use rand::Rng;
+
+/// Header name defined by the W3C Trace Context spec
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+// -----------------------------------------------------------------------------
+// TRACE PARENT
+// -----------------------------------------------------------------------------
+/// Parsed `traceparent` value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceParent {
+    pub trace_id: String,
+    pub parent_id: String,
+    pub sampled: bool,
+}
+
+impl TraceParent {
+    /// Parse a version-00 traceparent header; `None` if malformed
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split('-');
+        let (version, trace_id, parent_id, flags) =
+            (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+
+        let is_hex = |s: &str, len: usize| {
+            s.len() == len && s.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+        };
+        if version != "00" || !is_hex(trace_id, 32) || !is_hex(parent_id, 16) || !is_hex(flags, 2)
+        {
+            return None;
+        }
+        // All-zero IDs are invalid per spec
+        if trace_id.chars().all(|c| c == '0') || parent_id.chars().all(|c| c == '0') {
+            return None;
+        }
+
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+        Some(Self {
+            trace_id: trace_id.to_string(),
+            parent_id: parent_id.to_string(),
+            sampled: flags & 0x01 == 0x01,
+        })
+    }
+
+    /// Start a brand-new trace
+    pub fn new_root() -> Self {
+        Self {
+            trace_id: random_hex(16),
+            parent_id: random_hex(8),
+            sampled: true,
+        }
+    }
+
+    /// Context for an outbound call: same trace, new parent span ID
+    pub fn child(&self) -> Self {
+        Self {
+            trace_id: self.trace_id.clone(),
+            parent_id: random_hex(8),
+            sampled: self.sampled,
+        }
+    }
+
+    /// Render as a header value
+    pub fn to_header_value(&self) -> String {
+        format!(
+            "00-{}-{}-{:02x}",
+            self.trace_id,
+            self.parent_id,
+            u8::from(self.sampled)
+        )
+    }
+}
+
+fn random_hex(bytes: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..bytes).map(|_| format!("{:02x}", rng.gen::<u8>())).collect()
+}
+
+// -----------------------------------------------------------------------------
+// REQUEST CONTEXT
+// -----------------------------------------------------------------------------
+tokio::task_local! {
+    static CURRENT: Option<TraceParent>;
+}
+
+/// Trace context to use for an outbound call made right now
+pub fn outbound() -> TraceParent {
+    CURRENT
+        .try_with(|current| current.as_ref().map(TraceParent::child))
+        .ok()
+        .flatten()
+        .unwrap_or_else(TraceParent::new_root)
+}
+
+/// Middleware: capture the caller's traceparent for outbound propagation
+pub async fn capture_trace_context(request: Request, next: Next) -> Response {
+    let incoming = request
+        .headers()
+        .get(TRACEPARENT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(TraceParent::parse);
+
+    CURRENT.scope(incoming, next.run(request)).await
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_child() {
+        let parent =
+            TraceParent::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").unwrap();
+        assert!(parent.sampled);
+
+        let child = parent.child();
+        assert_eq!(child.trace_id, parent.trace_id);
+        assert_ne!(child.parent_id, parent.parent_id);
+        assert!(child.to_header_value().starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+        assert!(child.to_header_value().ends_with("-01"));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid() {
+        assert!(TraceParent::parse("garbage").is_none());
+        assert!(TraceParent::parse("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").is_none());
+        assert!(TraceParent::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_none());
+        assert!(TraceParent::parse("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01").is_none());
+    }
+
+    #[test]
+    fn test_outbound_without_context_is_new_root() {
+        let root = outbound();
+        assert_eq!(root.trace_id.len(), 32);
+        assert_eq!(root.parent_id.len(), 16);
+    }
+}
@@ -0,0 +1,300 @@
+// =============================================================================
+// TRAFFIC GENERATOR MODULE
+// =============================================================================
+// Built-in synthetic API traffic, so a freshly started lab has live request
+// rates, latencies and stock movements on its dashboards without running k6
+// or another external load tool.
+//
+// HOW (LOAD_GEN_ENABLED=true):
+// A supervised job ("traffic-generator") sends LOAD_GEN_RPS requests per
+// second (default 5) to this service's own HTTP port. LOAD_GEN_MIX weighs
+// the kinds of request (default read=70,reserve=15,release=15):
+// - read:     a page of the item list, one item, or the low-stock alerts
+// - reserve:  1-3 units of a SKU for a fresh LOADGEN-... order
+// - release:  gives back the oldest reservation the generator made (a read
+//             when it holds none)
+// SKUs are sampled from the catalog every minute; a few of them get most of
+// the traffic, as in a real shop.
+//
+// LEARNING NOTES:
+// - Requests go through the whole middleware stack, so they land in
+//   http_requests_total and friends like real traffic. They carry
+//   `X-Load-Generator: builtin`, and http_client_requests_total
+//   {destination="load_gen"} counts them from the sending side.
+// - Requests are fired on schedule without waiting for earlier responses,
+//   as in replay.rs; past MAX_IN_FLIGHT outstanding requests, ticks are
+//   skipped instead of piling up
+// - Keep release close to reserve, or stock slowly drains; reservations
+//   the generator loses track of expire with the reservation TTL
+// - Rate limits, public read-only mode, chaos experiments and load shedding
+//   apply to it like to any other client
+// =============================================================================
+
+use anyhow::{bail, Context, Result};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+use crate::db::Database;
+use crate::http_client::HttpClient;
+
+/// Header marking generated requests
+pub const GENERATOR_HEADER: &str = "x-load-generator";
+
+/// Fastest allowed rate
+pub const MAX_RPS: f64 = 1000.0;
+
+/// Largest weight of one kind in LOAD_GEN_MIX
+const MAX_WEIGHT: u32 = 10_000;
+
+/// Requests waiting for a response before ticks are skipped
+const MAX_IN_FLIGHT: usize = 200;
+
+/// SKUs traffic is spread over
+const MAX_SKUS: i64 = 200;
+
+/// How often the SKU sample is refreshed
+const SKU_REFRESH: Duration = Duration::from_secs(60);
+
+/// Reservations remembered for releasing; older ones are left to expire
+const MAX_HELD: usize = 1000;
+
+/// Sales channels of generated reservations, web twice as often
+const CHANNELS: [&str; 4] = ["web", "web", "pos", "b2b"];
+
+/// Wait before the first request, so the HTTP listener is up
+const STARTUP_DELAY: Duration = Duration::from_secs(3);
+
+// -----------------------------------------------------------------------------
+// CONFIGURATION
+// -----------------------------------------------------------------------------
+/// Settings of the traffic generator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrafficConfig {
+    /// Requests per second
+    pub rps: f64,
+    pub mix: TrafficMix,
+}
+
+/// Relative weights of the kinds of request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrafficMix {
+    pub read: u32,
+    pub reserve: u32,
+    pub release: u32,
+}
+
+impl Default for TrafficMix {
+    fn default() -> Self {
+        Self {
+            read: 70,
+            reserve: 15,
+            release: 15,
+        }
+    }
+}
+
+impl TrafficMix {
+    /// Parse "read=70,reserve=20,release=10"; kinds left out weigh 0
+    pub fn parse(value: &str) -> Result<Self> {
+        let mut mix = Self {
+            read: 0,
+            reserve: 0,
+            release: 0,
+        };
+        for part in value.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let (kind, weight) = part
+                .split_once('=')
+                .with_context(|| format!("LOAD_GEN_MIX entry '{}' is not kind=weight", part))?;
+            let weight: u32 = weight
+                .trim()
+                .parse()
+                .with_context(|| format!("LOAD_GEN_MIX weight of '{}' is not a number", kind))?;
+            if weight > MAX_WEIGHT {
+                bail!("LOAD_GEN_MIX weights must be at most {}", MAX_WEIGHT);
+            }
+            match kind.trim().to_ascii_lowercase().as_str() {
+                "read" => mix.read = weight,
+                "reserve" => mix.reserve = weight,
+                "release" => mix.release = weight,
+                other => bail!("LOAD_GEN_MIX has an unknown kind '{}' (read, reserve, release)", other),
+            }
+        }
+        if mix.read + mix.reserve + mix.release == 0 {
+            bail!("LOAD_GEN_MIX must give at least one kind a weight");
+        }
+        Ok(mix)
+    }
+
+    /// Kind of request for a roll in 0..total
+    fn pick(&self, roll: u32) -> Operation {
+        if roll < self.read {
+            Operation::Read
+        } else if roll < self.read + self.reserve {
+            Operation::Reserve
+        } else {
+            Operation::Release
+        }
+    }
+
+    fn total(&self) -> u32 {
+        self.read + self.reserve + self.release
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Read,
+    Reserve,
+    Release,
+}
+
+/// A reservation the generator made and may release
+struct Held {
+    sku: String,
+    order_id: String,
+    quantity: i32,
+}
+
+// =============================================================================
+// GENERATOR
+// =============================================================================
+/// Send generated traffic to `base_url`, forever
+pub async fn run(http: HttpClient, db: Database, base_url: String, config: TrafficConfig) -> Result<()> {
+    tokio::time::sleep(STARTUP_DELAY).await;
+    tracing::info!(rps = config.rps, mix = ?config.mix, "Traffic generator started");
+
+    let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / config.rps));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    let held: Arc<Mutex<VecDeque<Held>>> = Arc::default();
+    let mut in_flight = JoinSet::new();
+    let mut skus: Vec<String> = Vec::new();
+    let mut skus_loaded: Option<Instant> = None;
+
+    loop {
+        ticker.tick().await;
+        while in_flight.try_join_next().is_some() {}
+
+        if skus_loaded.is_none_or(|at| at.elapsed() >= SKU_REFRESH) {
+            match db.sample_skus(MAX_SKUS).await {
+                Ok(sample) => skus = sample,
+                Err(e) => tracing::warn!(error = %e, "Traffic generator couldn't sample SKUs"),
+            }
+            skus_loaded = Some(Instant::now());
+        }
+        if skus.is_empty() || in_flight.len() >= MAX_IN_FLIGHT {
+            continue;
+        }
+
+        let operation = config.mix.pick(rand::random::<u32>() % config.mix.total());
+        let released = match operation {
+            Operation::Release => held.lock().expect("traffic lock poisoned").pop_front(),
+            _ => None,
+        };
+
+        let client = http.client();
+        let (request, reservation) = match (operation, released) {
+            (Operation::Release, Some(reservation)) => (
+                client
+                    .post(format!("{}/api/v1/inventory/release", base_url))
+                    .json(&serde_json::json!({
+                        "sku": reservation.sku,
+                        "quantity": reservation.quantity,
+                        "order_id": reservation.order_id,
+                    })),
+                None,
+            ),
+            (Operation::Reserve, _) => {
+                let reservation = Held {
+                    sku: pick_sku(&skus).to_string(),
+                    order_id: format!("LOADGEN-{}", uuid::Uuid::new_v4().simple()),
+                    quantity: 1 + (rand::random::<u32>() % 3) as i32,
+                };
+                let request = client
+                    .post(format!("{}/api/v1/inventory/reserve", base_url))
+                    .json(&serde_json::json!({
+                        "sku": reservation.sku,
+                        "quantity": reservation.quantity,
+                        "order_id": reservation.order_id,
+                        "channel": CHANNELS[rand::random::<usize>() % CHANNELS.len()],
+                    }));
+                (request, Some(reservation))
+            }
+            // Reads, and releases with nothing to release
+            _ => (client.get(read_url(&base_url, &skus)), None),
+        };
+
+        let request = request.header(GENERATOR_HEADER, "builtin");
+        let (http, held) = (http.clone(), held.clone());
+        in_flight.spawn(async move {
+            let Ok(response) = http.send_once("load_gen", request).await else {
+                return;
+            };
+            if let Some(reservation) = reservation.filter(|_| response.status().is_success()) {
+                let mut held = held.lock().expect("traffic lock poisoned");
+                if held.len() >= MAX_HELD {
+                    held.pop_front();
+                }
+                held.push_back(reservation);
+            }
+        });
+    }
+}
+
+/// A SKU, favouring the first ones of the sample
+fn pick_sku(skus: &[String]) -> &str {
+    let index = (rand::random::<f64>().powi(2) * skus.len() as f64) as usize;
+    &skus[index.min(skus.len() - 1)]
+}
+
+/// A list page, one item or the alerts
+fn read_url(base_url: &str, skus: &[String]) -> String {
+    match rand::random::<u32>() % 10 {
+        0..=4 => format!(
+            "{}/api/v1/inventory?page={}&per_page=20",
+            base_url,
+            1 + rand::random::<u32>() % 3
+        ),
+        5..=8 => format!("{}/api/v1/inventory/{}", base_url, pick_sku(skus)),
+        _ => format!("{}/api/v1/inventory/alerts", base_url),
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mix() {
+        let mix = TrafficMix::parse(" read=60, Reserve=30,release=10 ").unwrap();
+        assert_eq!(
+            mix,
+            TrafficMix {
+                read: 60,
+                reserve: 30,
+                release: 10
+            }
+        );
+        assert_eq!(TrafficMix::parse("read=1").unwrap().reserve, 0);
+
+        assert!(TrafficMix::parse("read=0").is_err());
+        assert!(TrafficMix::parse("read=70,write=30").is_err());
+        assert!(TrafficMix::parse("read").is_err());
+    }
+
+    #[test]
+    fn test_pick_follows_weights() {
+        let mix = TrafficMix::default();
+        assert_eq!(mix.pick(0), Operation::Read);
+        assert_eq!(mix.pick(69), Operation::Read);
+        assert_eq!(mix.pick(70), Operation::Reserve);
+        assert_eq!(mix.pick(85), Operation::Release);
+        assert_eq!(mix.pick(mix.total() - 1), Operation::Release);
+    }
+}
@@ -0,0 +1,365 @@
+// =============================================================================
+// USAGE MODULE
+// =============================================================================
+// Per-API-key usage metering, for quotas and chargeback dashboards.
+//
+// WHAT IS COUNTED:
+// Requests to /api/ endpoints that carry an `X-API-Key` header, and the
+// units those requests reserved (single and batch reservations). Requests
+// without a key aren't metered. The key is not checked against anything:
+// it identifies the caller, it doesn't authorize it.
+//
+// HOW:
+// - Request path: one Redis hash per key and UTC day,
+//   `usage:<YYYY-MM-DD>:<key_id>` {requests, reserved_units}, bumped with
+//   HINCRBY after the response is built. Every replica increments the same
+//   hash, so the counts are service-wide.
+// - Rollup: a supervised job ("usage-rollup") copies the hashes of the
+//   last USAGE_RETENTION_DAYS days into api_usage_daily every
+//   USAGE_ROLLUP_INTERVAL_SECS (default 60). Postgres keeps the larger
+//   value, so the job is safe to run on every replica.
+// - Report: GET /api/v1/admin/usage reads api_usage_daily; today's row
+//   trails live traffic by up to one rollup interval.
+//
+// KEY IDS:
+// `key_id` is the FNV-1a fingerprint of the key (16 hex digits), so keys
+// never reach Redis, Postgres or reports. Metered responses carry it in
+// `X-Usage-Key-Id` so a client can find itself in the report. FNV is not a
+// cryptographic hash: it keeps keys out of storage, it doesn't protect
+// guessable ones.
+//
+// LEARNING NOTES:
+// - The Redis write is spawned off the request, so metering adds no latency;
+//   with Redis down the increment is lost and usage_record_errors_total
+//   counts it
+// - Hashes expire USAGE_RETENTION_DAYS after their last write: that's how
+//   long the rollup job may be down without losing a day
+// - Quotas can read the live hash of today (HGET usage:<day>:<key_id>
+//   requests) instead of the rolled-up table
+// =============================================================================
+
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use chrono::{Days, NaiveDate, Utc};
+use redis::aio::ConnectionManager;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::db::Database;
+use crate::graphql;
+use crate::metrics;
+use crate::models::{ApiUsage, ApiUsageTotal};
+use crate::AppState;
+
+/// Header identifying the caller
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Response header echoing the caller's key ID
+pub const KEY_ID_HEADER: &str = "x-usage-key-id";
+
+/// Days of counts kept in Redis (today included)
+pub const USAGE_RETENTION_DAYS: u64 = 3;
+
+/// Longest period one report may cover, in days
+pub const MAX_REPORT_DAYS: u64 = 92;
+
+/// Report period when none is given, in days (today included)
+pub const DEFAULT_REPORT_DAYS: u64 = 7;
+
+const KEY_PREFIX: &str = "usage:";
+
+/// Fingerprint of an API key (FNV-1a, 64 bits, as hex)
+pub fn key_id(api_key: &str) -> String {
+    let hash = api_key
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{:016x}", hash)
+}
+
+/// API keys issued to known clients (API_KEYS, comma-separated)
+///
+/// Usage metering counts any key; rate limiting and the fairness caps only
+/// trust a key found here, since anyone can make up a new key per request.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ApiKeys(HashSet<String>);
+
+impl fmt::Debug for ApiKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ApiKeys(<{} redacted>)", self.0.len())
+    }
+}
+
+impl ApiKeys {
+    pub fn parse(list: &str) -> Self {
+        Self(
+            list.split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+
+    /// Whether `key` was issued to a client
+    pub fn contains(&self, key: &str) -> bool {
+        self.0.contains(key)
+    }
+}
+
+/// Redis hash of a key's counts on a day
+fn redis_key(day: NaiveDate, key_id: &str) -> String {
+    format!("{}{}:{}", KEY_PREFIX, day.format("%Y-%m-%d"), key_id)
+}
+
+// -----------------------------------------------------------------------------
+// REQUEST METERING
+// -----------------------------------------------------------------------------
+// The middleware puts a unit counter in a task-local; reservation handlers
+// add to it, and the middleware flushes it with the request count.
+tokio::task_local! {
+    static RESERVED_UNITS: Arc<AtomicI64>;
+}
+
+/// Count units reserved by the current request (no-op outside a metered one)
+pub fn record_reserved(units: i64) {
+    let _ = RESERVED_UNITS.try_with(|reserved| reserved.fetch_add(units, Ordering::Relaxed));
+}
+
+/// Middleware: meter /api/ requests carrying an API key
+pub async fn meter(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let key_id = (state.config.usage_metering && (path.starts_with("/api/") || path == graphql::PATH))
+        .then(|| request.headers().get(API_KEY_HEADER))
+        .flatten()
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(key_id);
+    let Some(key_id) = key_id else {
+        return next.run(request).await;
+    };
+
+    let reserved = Arc::new(AtomicI64::new(0));
+    let mut response = RESERVED_UNITS
+        .scope(reserved.clone(), next.run(request))
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(&key_id) {
+        response.headers_mut().insert(KEY_ID_HEADER, value);
+    }
+    let redis = state.redis.clone();
+    let units = reserved.load(Ordering::Relaxed);
+    tokio::spawn(async move {
+        increment(&redis, &redis_key(Utc::now().date_naive(), &key_id), units).await;
+    });
+
+    response
+}
+
+/// Add one request (and its reserved units) to a usage hash
+async fn increment(redis: &ConnectionManager, key: &str, units: i64) {
+    let mut pipe = redis::pipe();
+    pipe.cmd("HINCRBY").arg(key).arg("requests").arg(1).ignore();
+    if units > 0 {
+        pipe.cmd("HINCRBY")
+            .arg(key)
+            .arg("reserved_units")
+            .arg(units)
+            .ignore();
+    }
+    pipe.cmd("EXPIRE")
+        .arg(key)
+        .arg(USAGE_RETENTION_DAYS * 86_400)
+        .ignore();
+
+    let result: redis::RedisResult<()> = pipe.query_async(&mut redis.clone()).await;
+    if let Err(e) = result {
+        tracing::debug!(error = %e, "Usage not recorded");
+        metrics::record_usage_error();
+    }
+}
+
+// -----------------------------------------------------------------------------
+// ROLLUP
+// -----------------------------------------------------------------------------
+/// Copy the Redis counts into Postgres every `interval`, forever
+pub async fn run_rollup(db: Database, redis: ConnectionManager, interval: Duration) -> Result<()> {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        let today = Utc::now().date_naive();
+        for offset in 0..USAGE_RETENTION_DAYS {
+            let Some(day) = today.checked_sub_days(Days::new(offset)) else {
+                continue;
+            };
+            rollup_day(&db, &redis, day).await?;
+        }
+        metrics::set_usage_rolled_up(Utc::now().timestamp());
+    }
+}
+
+/// Store one day's Redis counts
+async fn rollup_day(db: &Database, redis: &ConnectionManager, day: NaiveDate) -> Result<()> {
+    let mut redis = redis.clone();
+    let prefix = redis_key(day, "");
+    let keys: Vec<String> = {
+        let mut iter = redis::cmd("SCAN")
+            .cursor_arg(0)
+            .arg("MATCH")
+            .arg(format!("{}*", prefix))
+            .clone()
+            .iter_async::<String>(&mut redis)
+            .await
+            .context("Failed to list usage counters")?;
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        keys
+    };
+
+    let mut counts = Vec::with_capacity(keys.len());
+    for key in keys {
+        let fields: HashMap<String, i64> = redis::cmd("HGETALL")
+            .arg(&key)
+            .query_async(&mut redis)
+            .await
+            .context("Failed to read usage counter")?;
+        counts.push((
+            key[prefix.len()..].to_string(),
+            fields.get("requests").copied().unwrap_or(0),
+            fields.get("reserved_units").copied().unwrap_or(0),
+        ));
+    }
+
+    db.upsert_api_usage(day, &counts).await
+}
+
+// -----------------------------------------------------------------------------
+// REPORT
+// -----------------------------------------------------------------------------
+/// Resolve a report period: `to` defaults to today, `from` to
+/// DEFAULT_REPORT_DAYS before it
+///
+/// # Returns
+/// - `Err` when the period is reversed or longer than MAX_REPORT_DAYS
+pub fn report_period(
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    today: NaiveDate,
+) -> Result<(NaiveDate, NaiveDate), String> {
+    let to = to.unwrap_or(today);
+    let from = from.unwrap_or_else(|| to - Days::new(DEFAULT_REPORT_DAYS - 1));
+
+    if from > to {
+        return Err(format!("from ({}) is after to ({})", from, to));
+    }
+    if (to - from).num_days() >= MAX_REPORT_DAYS as i64 {
+        return Err(format!("A report covers at most {} days", MAX_REPORT_DAYS));
+    }
+    Ok((from, to))
+}
+
+/// Per-key totals of daily rows, most requests first
+pub fn totals(days: &[ApiUsage]) -> Vec<ApiUsageTotal> {
+    let mut by_key: HashMap<&str, (i64, i64)> = HashMap::new();
+    for row in days {
+        let total = by_key.entry(&row.key_id).or_default();
+        total.0 += row.requests;
+        total.1 += row.reserved_units;
+    }
+
+    let mut totals: Vec<ApiUsageTotal> = by_key
+        .into_iter()
+        .map(|(key_id, (requests, reserved_units))| ApiUsageTotal {
+            key_id: key_id.to_string(),
+            requests,
+            reserved_units,
+        })
+        .collect();
+    totals.sort_by(|a, b| {
+        b.requests
+            .cmp(&a.requests)
+            .then_with(|| a.key_id.cmp(&b.key_id))
+    });
+    totals
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn row(day: &str, key_id: &str, requests: i64, reserved_units: i64) -> ApiUsage {
+        ApiUsage {
+            day: date(day),
+            key_id: key_id.to_string(),
+            requests,
+            reserved_units,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_key_id_is_stable_fingerprint() {
+        // FNV-1a test vectors
+        assert_eq!(key_id(""), "cbf29ce484222325");
+        assert_eq!(key_id("a"), "af63dc4c8601ec8c");
+        assert_ne!(key_id("lab-key-1"), key_id("lab-key-2"));
+        assert_eq!(redis_key(date("2024-05-01"), "abc"), "usage:2024-05-01:abc");
+    }
+
+    #[test]
+    fn test_report_period() {
+        let today = date("2024-05-10");
+        assert_eq!(
+            report_period(None, None, today),
+            Ok((date("2024-05-04"), today))
+        );
+        assert_eq!(
+            report_period(Some(date("2024-05-01")), Some(date("2024-05-01")), today),
+            Ok((date("2024-05-01"), date("2024-05-01")))
+        );
+        assert!(report_period(Some(date("2024-05-02")), Some(date("2024-05-01")), today).is_err());
+        assert!(report_period(Some(date("2024-01-01")), None, today).is_err());
+    }
+
+    #[test]
+    fn test_totals_per_key() {
+        let days = [
+            row("2024-05-01", "a", 10, 2),
+            row("2024-05-01", "b", 30, 0),
+            row("2024-05-02", "a", 25, 3),
+        ];
+        let totals = totals(&days);
+        assert_eq!(totals.len(), 2);
+        assert_eq!(
+            totals[0],
+            ApiUsageTotal {
+                key_id: "a".to_string(),
+                requests: 35,
+                reserved_units: 5
+            }
+        );
+        assert_eq!(totals[1].key_id, "b");
+    }
+}
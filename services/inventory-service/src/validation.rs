@@ -0,0 +1,257 @@
+// =============================================================================
+// VALIDATION MODULE
+// =============================================================================
+// Checks of request bodies before a handler runs, so every endpoint rejects
+// bad input the same way and reports every bad field at once.
+//
+// HOW:
+// - Each request model implements `Validate`, adding one FieldError per bad
+//   field to a `Validator`
+// - Handlers take the body as `ValidJson<T>` instead of `Json<T>`: it parses
+//   the JSON and runs the checks, so the handler only sees valid requests
+// - Invalid bodies get 422 VALIDATION_FAILED with the fields listed:
+//     {
+//       "error": "VALIDATION_FAILED",
+//       "message": "quantity: must be positive; order_id: must be 1-255 characters",
+//       "fields": [
+//         { "field": "quantity", "message": "must be positive" },
+//         { "field": "order_id", "message": "must be 1-255 characters" }
+//       ]
+//     }
+// - A body that doesn't match the model's types (missing field, string
+//   where a number belongs) is a 422 too, reported on the field "body";
+//   a body that isn't JSON at all stays a 400
+//
+// LEARNING NOTES:
+// - Nested fields are named like JSON paths: items[2].quantity
+// - Checks here are about the request alone. Whether the SKU exists or the
+//   stock suffices is the handler's (and database's) business.
+// =============================================================================
+
+use axum::{
+    async_trait,
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use std::fmt::Display;
+
+use crate::error::AppError;
+use crate::models::FieldError;
+
+/// Longest order ID or free-text reason (VARCHAR(255) columns)
+pub const MAX_TEXT_LEN: usize = 255;
+
+// -----------------------------------------------------------------------------
+// VALIDATOR
+// -----------------------------------------------------------------------------
+/// Collects the errors of one request
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    /// Record an error on a field
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Record the error of a check that reports a plain message
+    pub fn check(&mut self, field: impl Into<String>, result: Result<(), String>) {
+        if let Err(message) = result {
+            self.add(field, message);
+        }
+    }
+
+    /// Text that must not be blank and is at most `max` bytes long
+    pub fn text(&mut self, field: impl Into<String>, value: &str, max: usize) {
+        if value.trim().is_empty() || value.len() > max {
+            self.add(field, format!("must be 1-{} characters", max));
+        }
+    }
+
+    /// Optional text: when given, at most `max` bytes long
+    pub fn optional_text(&mut self, field: impl Into<String>, value: Option<&str>, max: usize) {
+        if value.is_some_and(|value| value.len() > max) {
+            self.add(field, format!("must be at most {} characters", max));
+        }
+    }
+
+    pub fn positive(&mut self, field: impl Into<String>, value: i32) {
+        if value <= 0 {
+            self.add(field, "must be positive");
+        }
+    }
+
+    pub fn non_negative(&mut self, field: impl Into<String>, value: i32) {
+        if value < 0 {
+            self.add(field, "must not be negative");
+        }
+    }
+
+    /// Number between `min` and `max`, inclusive
+    pub fn range<T: PartialOrd + Display + Copy>(&mut self, field: impl Into<String>, value: T, min: T, max: T) {
+        if !(min..=max).contains(&value) {
+            self.add(field, format!("must be between {} and {}", min, max));
+        }
+    }
+
+    fn finish(self) -> Result<(), Vec<FieldError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// A request body that can check itself
+pub trait Validate {
+    /// Add an error for every invalid field
+    fn check(&self, v: &mut Validator);
+
+    /// Run the checks
+    ///
+    /// # Returns
+    /// - `Err(errors)` with one entry per invalid field
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut v = Validator::default();
+        self.check(&mut v);
+        v.finish()
+    }
+}
+
+/// One-line summary of field errors, for logs and plain-text callers
+pub fn summary(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("{}: {}", e.field, e.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// -----------------------------------------------------------------------------
+// EXTRACTOR
+// -----------------------------------------------------------------------------
+/// JSON body that has passed its `Validate` checks
+pub struct ValidJson<T>(pub T);
+
+impl<T: Validate> ValidJson<T> {
+    /// Check a request that didn't come in as JSON (the gRPC API calls the
+    /// handlers with these)
+    pub fn new(value: T) -> Result<Self, AppError> {
+        value.validate().map_err(AppError::Validation)?;
+        Ok(Self(value))
+    }
+}
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(request, state).await.map_err(|rejection| match rejection {
+            JsonRejection::JsonDataError(e) => AppError::Validation(vec![FieldError {
+                field: "body".to_string(),
+                message: e.body_text(),
+            }]),
+            other => AppError::BadRequest(other.body_text()),
+        })?;
+        Self::new(value)
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ReleaseStockRequest, ReserveBatchRequest, ReserveLine, ReserveStockRequest};
+    use axum::{body::Body, http::header::CONTENT_TYPE};
+
+    fn reserve(sku: &str, quantity: i32, order_id: &str) -> ReserveStockRequest {
+        ReserveStockRequest {
+            sku: sku.to_string(),
+            quantity,
+            order_id: order_id.to_string(),
+            channel: Default::default(),
+            hold: Default::default(),
+            priority: 0,
+            quota: None,
+        }
+    }
+
+    fn fields(errors: &[FieldError]) -> Vec<&str> {
+        errors.iter().map(|e| e.field.as_str()).collect()
+    }
+
+    #[test]
+    fn test_every_bad_field_is_reported() {
+        assert!(reserve("SKU-1", 1, "ORD-1").validate().is_ok());
+
+        let errors = reserve(" ", 0, &"x".repeat(MAX_TEXT_LEN + 1)).validate().unwrap_err();
+        assert_eq!(fields(&errors), vec!["sku", "quantity", "order_id"]);
+        assert_eq!(
+            summary(&errors[1..2]),
+            "quantity: must be positive"
+        );
+
+        let release = ReleaseStockRequest {
+            sku: "SKU-1".to_string(),
+            quantity: -2,
+            order_id: "ORD-1".to_string(),
+        };
+        assert_eq!(fields(&release.validate().unwrap_err()), vec!["quantity"]);
+    }
+
+    #[test]
+    fn test_nested_fields_are_named_by_path() {
+        let batch = ReserveBatchRequest {
+            order_id: "ORD-1".to_string(),
+            channel: Default::default(),
+            hold: Default::default(),
+            priority: 0,
+            quota: None,
+            items: vec![
+                ReserveLine { sku: "SKU-1".to_string(), quantity: 1 },
+                ReserveLine { sku: "SKU-2".to_string(), quantity: 0 },
+                ReserveLine { sku: "SKU-1".to_string(), quantity: 1 },
+            ],
+        };
+        assert_eq!(
+            fields(&batch.validate().unwrap_err()),
+            vec!["items[1].quantity", "items[2].sku"]
+        );
+    }
+
+    async fn extract(body: &'static str) -> Result<ValidJson<ReserveStockRequest>, AppError> {
+        let request = Request::builder()
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        ValidJson::from_request(request, &()).await
+    }
+
+    #[tokio::test]
+    async fn test_extractor_rejections() {
+        let ok = extract(r#"{"sku":"SKU-1","quantity":2,"order_id":"ORD-1"}"#).await;
+        assert!(ok.is_ok_and(|ValidJson(request)| request.quantity == 2));
+
+        let invalid = extract(r#"{"sku":"SKU-1","quantity":0,"order_id":""}"#).await;
+        assert!(matches!(invalid, Err(AppError::Validation(errors)) if errors.len() == 2));
+
+        let wrong_type = extract(r#"{"sku":"SKU-1","quantity":"two","order_id":"ORD-1"}"#).await;
+        assert!(matches!(wrong_type, Err(AppError::Validation(errors)) if errors[0].field == "body"));
+
+        assert!(matches!(extract("{not json").await, Err(AppError::BadRequest(_))));
+    }
+}
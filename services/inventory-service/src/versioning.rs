@@ -0,0 +1,153 @@
+// =============================================================================
+// VERSIONING MODULE
+// =============================================================================
+// Optimistic concurrency for item updates: a client that read an item can
+// make its update conditional on nobody having changed the item since, and
+// learns about the lost update instead of overwriting it.
+//
+// HOW:
+// - inventory.version starts at 1 and a trigger bumps it on every change
+//   (migrations/0008_item_version.sql); items carry it as `version`, and
+//   responses with one item as `ETag: "<version>"`
+// - These endpoints take `If-Match: "<version>"`:
+//     PUT  /api/v1/inventory/:sku/policy
+//     PUT  /api/v1/inventory/:sku/catalog
+//     PUT  /api/v1/inventory/:sku/thresholds
+//     POST /api/v1/inventory/adjust   (or "expected_version" in the body)
+// - The item is locked and its version compared before the update; a
+//   mismatch is 412 VERSION_MISMATCH, with the current version in `details`
+//
+// LEARNING NOTES:
+// - Without If-Match an update applies as before: last writer wins
+// - `If-Match: *` only requires the item to exist
+// - Every change bumps the version, reservations included. On a busy SKU a
+//   conditional adjust may need a few retries; send a version only when
+//   the adjustment depends on what was read (a stock count, say).
+// - Reservations through the Redis fast path (fast_reserve.rs) bump the
+//   version when they are written back to Postgres, not when they happen
+// =============================================================================
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderValue},
+    response::{IntoResponseParts, ResponseParts},
+};
+use std::convert::Infallible;
+
+use crate::error::AppError;
+
+// -----------------------------------------------------------------------------
+// REQUEST
+// -----------------------------------------------------------------------------
+/// Version from the If-Match header; None when absent or `*`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IfMatch(pub Option<i64>);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(header::IF_MATCH) else {
+            return Ok(Self(None));
+        };
+        let value = value
+            .to_str()
+            .map_err(|_| AppError::BadRequest("If-Match is not valid text".to_string()))?;
+        parse_if_match(value).map(Self).map_err(AppError::BadRequest)
+    }
+}
+
+/// Parse an If-Match value: `"<version>"`, a bare number, or `*`
+///
+/// Weak tags (W/"3") and lists are rejected: an update needs one exact
+/// version.
+fn parse_if_match(value: &str) -> Result<Option<i64>, String> {
+    let value = value.trim();
+    if value == "*" {
+        return Ok(None);
+    }
+
+    let unquoted = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value);
+    unquoted
+        .parse::<i64>()
+        .map(Some)
+        .map_err(|_| format!("If-Match must be one item version like \"3\", got '{}'", value))
+}
+
+/// The version an update expects, from the header and/or the body
+///
+/// # Returns
+/// - `Err(AppError::BadRequest)` when both are given and differ
+pub fn expected_version(if_match: IfMatch, body: Option<i64>) -> Result<Option<i64>, AppError> {
+    match (if_match.0, body) {
+        (Some(header), Some(body)) if header != body => Err(AppError::BadRequest(format!(
+            "If-Match ({}) and expected_version ({}) disagree",
+            header, body
+        ))),
+        (header, body) => Ok(header.or(body)),
+    }
+}
+
+/// Check an item's version against the expected one
+pub fn check(expected: Option<i64>, current: i64) -> Result<(), AppError> {
+    match expected {
+        Some(expected) if expected != current => Err(AppError::VersionMismatch { expected, current }),
+        _ => Ok(()),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// RESPONSE
+// -----------------------------------------------------------------------------
+/// Version of the item in a response; add it to the handler's response
+/// tuple to send the ETag header
+#[derive(Debug, Clone, Copy)]
+pub struct ETag(pub i64);
+
+impl IntoResponseParts for ETag {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        if let Ok(value) = HeaderValue::from_str(&format!("\"{}\"", self.0)) {
+            res.headers_mut().insert(header::ETAG, value);
+        }
+        Ok(res)
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_if_match() {
+        assert_eq!(parse_if_match("\"7\""), Ok(Some(7)));
+        assert_eq!(parse_if_match(" 7 "), Ok(Some(7)));
+        assert_eq!(parse_if_match("*"), Ok(None));
+        assert!(parse_if_match("W/\"7\"").is_err());
+        assert!(parse_if_match("\"7\", \"8\"").is_err());
+    }
+
+    #[test]
+    fn test_expected_version_and_check() {
+        assert_eq!(expected_version(IfMatch(Some(3)), None).unwrap(), Some(3));
+        assert_eq!(expected_version(IfMatch(None), Some(4)).unwrap(), Some(4));
+        assert_eq!(expected_version(IfMatch(Some(4)), Some(4)).unwrap(), Some(4));
+        assert!(expected_version(IfMatch(Some(3)), Some(4)).is_err());
+
+        assert!(check(None, 9).is_ok());
+        assert!(check(Some(9), 9).is_ok());
+        assert!(matches!(
+            check(Some(8), 9),
+            Err(AppError::VersionMismatch { expected: 8, current: 9 })
+        ));
+    }
+}
@@ -0,0 +1,325 @@
+// =============================================================================
+// WEBHOOKS MODULE
+// =============================================================================
+// Webhook subscriptions for third parties (WMS, ERP), filtered by event type
+// and SKU pattern, so each receiver only gets the events it cares about.
+//
+// EVENTS (from successful entries of the audit trail):
+// - reservation.created    stock reserved for an order
+// - reservation.released   reservation released (cancelled order)
+// - reservation.bumped     soft hold released for a higher-priority order
+// - reservation.confirmed  reservation settled as sold
+// - reservation.reconciled `reserved` corrected by reconciliation
+// - stock.adjusted         quantity adjusted (receiving, corrections)
+//
+// SUBSCRIPTIONS:
+// - POST   /api/v1/admin/webhooks       {url, event_types, sku_pattern}
+// - GET    /api/v1/admin/webhooks
+// - DELETE /api/v1/admin/webhooks/:id
+// Empty `event_types` means every type; `sku_pattern` is a glob where `*`
+// matches any run of characters and `?` a single one ("LAPTOP-*").
+//
+// DELIVERY:
+// A supervised job ("webhook-dispatcher") follows the audit trail every
+// WEBHOOK_DISPATCH_INTERVAL_MS (default: 1000) and POSTs each event to every
+// matching subscription, with the shared client's retries.
+//
+// LEARNING NOTES:
+// - Delivery is best effort: a receiver that still fails after retries
+//   misses that event (logged), so one broken receiver can't hold up the
+//   others. The `id` (also in X-Webhook-Event-Id) is the audit event ID;
+//   receivers can spot gaps with GET /api/v1/admin/audit/export.
+// - Like the audit shippers, the job follows the audit trail through its
+//   outbox queue (audit.rs): an event whose transaction commits late is
+//   still delivered, one replica dispatches at a time, and the queue
+//   starts when the dispatcher first ran, so history is never replayed to
+//   new subscribers
+// =============================================================================
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::audit::AuditFollower;
+use crate::db::Database;
+use crate::http_client::HttpClient;
+use crate::models::{AuditEvent, WebhookSubscription, WebhookSubscriptionRequest};
+use crate::validation::{Validate, Validator};
+
+/// Audit actions and the event type each one is published as
+pub const EVENT_TYPES: [(&str, &str); 6] = [
+    ("reserve", "reservation.created"),
+    ("release", "reservation.released"),
+    ("bump", "reservation.bumped"),
+    ("confirm", "reservation.confirmed"),
+    ("reconcile", "reservation.reconciled"),
+    ("adjust", "stock.adjusted"),
+];
+
+/// Maximum audit events handled per poll
+const DISPATCH_BATCH_SIZE: i64 = 500;
+
+/// Header carrying the event ID, for receivers that dedupe on headers
+const EVENT_ID_HEADER: &str = "X-Webhook-Event-Id";
+
+/// Header telling the receiver which subscription matched
+const SUBSCRIPTION_HEADER: &str = "X-Webhook-Subscription-Id";
+
+// -----------------------------------------------------------------------------
+// PAYLOAD
+// -----------------------------------------------------------------------------
+/// Body POSTed to subscribers
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEvent<'a> {
+    /// Audit event ID (unique, increasing)
+    pub id: i64,
+    #[serde(rename = "type")]
+    pub event_type: &'static str,
+    pub sku: &'a str,
+    /// Units involved (negative for stock removed by an adjustment)
+    pub quantity: i32,
+    /// Order ID, or the reason of an adjustment
+    pub reference: Option<&'a str>,
+    /// Sales channel (reservation.created only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<&'a str>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl<'a> WebhookEvent<'a> {
+    /// Webhook view of an audit event; `None` for failures and actions
+    /// that aren't published
+    pub fn from_audit(event: &'a AuditEvent) -> Option<Self> {
+        if event.outcome != "success" {
+            return None;
+        }
+        let event_type = event_type_of(&event.action)?;
+
+        Some(Self {
+            id: event.id,
+            event_type,
+            sku: &event.sku,
+            quantity: event.quantity,
+            reference: event.reference.as_deref(),
+            channel: event.channel.as_deref(),
+            occurred_at: event.occurred_at,
+        })
+    }
+}
+
+/// Published event type of an audit action
+fn event_type_of(action: &str) -> Option<&'static str> {
+    EVENT_TYPES
+        .iter()
+        .find(|(known, _)| *known == action)
+        .map(|(_, event_type)| *event_type)
+}
+
+// -----------------------------------------------------------------------------
+// FILTERING
+// -----------------------------------------------------------------------------
+/// Check a subscription request before storing it: an http(s) URL, known
+/// event types and a non-empty SKU pattern
+impl Validate for WebhookSubscriptionRequest {
+    fn check(&self, v: &mut Validator) {
+        if !(self.url.starts_with("http://") || self.url.starts_with("https://")) {
+            v.add("url", format!("must be http(s), got '{}'", self.url));
+        }
+
+        for (i, event_type) in self.event_types.iter().enumerate() {
+            if !EVENT_TYPES.iter().any(|(_, known)| *known == event_type.as_str()) {
+                let known: Vec<&str> = EVENT_TYPES.iter().map(|(_, t)| *t).collect();
+                v.add(
+                    format!("event_types[{}]", i),
+                    format!("unknown event type '{}', expected one of: {}", event_type, known.join(", ")),
+                );
+            }
+        }
+
+        if self.sku_pattern.as_deref().is_some_and(|p| p.trim().is_empty()) {
+            v.add("sku_pattern", "must not be empty (omit it to match every SKU)");
+        }
+    }
+}
+
+/// Whether a subscription wants an event
+pub fn wants(subscription: &WebhookSubscription, event: &WebhookEvent) -> bool {
+    let type_matches = subscription.event_types.is_empty()
+        || subscription.event_types.iter().any(|t| t == event.event_type);
+    let sku_matches = subscription
+        .sku_pattern
+        .as_deref()
+        .is_none_or(|pattern| glob_match(pattern, event.sku));
+
+    type_matches && sku_matches
+}
+
+/// Match `text` against a glob with `*` (any run) and `?` (one character)
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character
+                Some((star, from)) => {
+                    p = star + 1;
+                    t = from + 1;
+                    backtrack = Some((star, from + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// =============================================================================
+// DISPATCHER
+// =============================================================================
+/// Deliver new audit events to matching subscriptions every `interval`, forever
+pub async fn run_dispatcher(db: Database, http: HttpClient, interval: Duration) -> Result<()> {
+    let mut follower = AuditFollower::register(&db, "webhooks").await?;
+
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        let Some(claim) = follower.claim(&db, DISPATCH_BATCH_SIZE).await? else {
+            continue;
+        };
+        if claim.events.is_empty() {
+            claim.finish().await?;
+            continue;
+        }
+
+        let subscriptions = db.list_webhook_subscriptions().await?;
+        for event in claim.events.iter().filter_map(WebhookEvent::from_audit) {
+            for subscription in subscriptions.iter().filter(|s| wants(s, &event)) {
+                if let Err(e) = deliver(&http, subscription, &event).await {
+                    tracing::warn!(
+                        subscription_id = subscription.id,
+                        event_id = event.id,
+                        error = %e,
+                        "Webhook delivery failed"
+                    );
+                }
+            }
+        }
+
+        claim.finish().await?;
+    }
+}
+
+/// POST one event to one subscriber
+async fn deliver(
+    http: &HttpClient,
+    subscription: &WebhookSubscription,
+    event: &WebhookEvent<'_>,
+) -> Result<()> {
+    let request = http
+        .client()
+        .post(&subscription.url)
+        .header(EVENT_ID_HEADER, event.id.to_string())
+        .header(SUBSCRIPTION_HEADER, subscription.id.to_string())
+        .json(event);
+
+    http.send("webhooks", request)
+        .await?
+        .error_for_status()
+        .with_context(|| format!("Webhook receiver rejected event {}", event.id))?;
+
+    Ok(())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn audit_event(action: &str, outcome: &str, sku: &str) -> AuditEvent {
+        AuditEvent {
+            id: 7,
+            occurred_at: Utc::now(),
+            action: action.to_string(),
+            outcome: outcome.to_string(),
+            sku: sku.to_string(),
+            quantity: 2,
+            reference: Some("ORD-1".to_string()),
+            detail: None,
+            channel: Some("web".to_string()),
+        }
+    }
+
+    fn subscription(event_types: &[&str], sku_pattern: Option<&str>) -> WebhookSubscription {
+        WebhookSubscription {
+            id: 1,
+            url: "http://wms.local/hook".to_string(),
+            event_types: event_types.iter().map(|t| t.to_string()).collect(),
+            sku_pattern: sku_pattern.map(str::to_string),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("LAPTOP-*", "LAPTOP-001"));
+        assert!(glob_match("*-001", "LAPTOP-001"));
+        assert!(glob_match("SKU-?-*X", "SKU-A-BXBX"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("LAPTOP-*", "PHONE-001"));
+        assert!(!glob_match("SKU-?", "SKU-10"));
+    }
+
+    #[test]
+    fn test_only_successful_published_actions() {
+        let reserve = audit_event("reserve", "success", "SKU-1");
+        let event = WebhookEvent::from_audit(&reserve).expect("published");
+        assert_eq!(event.event_type, "reservation.created");
+
+        assert!(WebhookEvent::from_audit(&audit_event("reserve", "failure", "SKU-1")).is_none());
+        assert!(WebhookEvent::from_audit(&audit_event("unknown", "success", "SKU-1")).is_none());
+    }
+
+    #[test]
+    fn test_subscription_filters() {
+        let source = audit_event("release", "success", "LAPTOP-001");
+        let event = WebhookEvent::from_audit(&source).unwrap();
+
+        assert!(wants(&subscription(&[], None), &event));
+        assert!(wants(&subscription(&["reservation.released"], Some("LAPTOP-*")), &event));
+        assert!(!wants(&subscription(&["reservation.created"], None), &event));
+        assert!(!wants(&subscription(&[], Some("PHONE-*")), &event));
+    }
+
+    #[test]
+    fn test_validate_request() {
+        let request = |url: &str, types: &[&str]| WebhookSubscriptionRequest {
+            url: url.to_string(),
+            event_types: types.iter().map(|t| t.to_string()).collect(),
+            sku_pattern: None,
+        };
+
+        assert!(request("https://erp.local/hook", &["stock.adjusted"]).validate().is_ok());
+        assert!(request("ftp://erp.local", &[]).validate().is_err());
+        assert!(request("https://erp.local", &["low_stock"]).validate().is_err());
+    }
+}